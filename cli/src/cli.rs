@@ -144,6 +144,55 @@ pub struct RunCmd {
 	/// **Dangerous!** Do not touch unless explicitly adviced to.
 	#[arg(long)]
 	pub overseer_channel_capacity_override: Option<usize>,
+
+	/// Record every signal and message delivered to the subsystems into the given file.
+	///
+	/// Meant for debugging, the recording grows quickly. Recordings can be replayed against a
+	/// single subsystem in tests.
+	#[arg(long, value_name = "PATH")]
+	pub overseer_message_tap: Option<std::path::PathBuf>,
+
+	/// Only accept collations of a para from the given collators.
+	///
+	/// Expects `<para id>:<collator id>`, with the collator id in SS58 format. Can be passed
+	/// multiple times. Paras without an allowed collator accept all collators which are not
+	/// denied.
+	#[arg(long, value_name = "PARA_ID:COLLATOR_ID")]
+	pub collator_allowlist: Vec<String>,
+
+	/// Refuse collations of a para from the given collator.
+	///
+	/// Expects `<para id>:<collator id>`, with the collator id in SS58 format. Can be passed
+	/// multiple times.
+	#[arg(long, value_name = "PARA_ID:COLLATOR_ID")]
+	pub collator_denylist: Vec<String>,
+
+	/// Limit the outbound traffic of the parachain notification protocols.
+	///
+	/// When the limit is reached, collation messages are dropped first, then statements and
+	/// bitfields and finally approvals. Dispute traffic, requests and view updates are never
+	/// dropped. Should be well above the size of the largest notification.
+	#[arg(long, value_name = "BYTES_PER_SECOND")]
+	pub parachain_outbound_bandwidth: Option<u64>,
+
+	/// Minimum interval between two dispute requests sent to the same peer.
+	///
+	/// Slowing down can help in situations with a lot of disputes. Values below the rate limit
+	/// enforced by receivers are raised to it.
+	#[arg(long, value_name = "MILLISECONDS")]
+	pub dispute_send_rate_limit: Option<u64>,
+
+	/// Never report misbehavior of the given peer on the parachain protocols.
+	///
+	/// Expects a peer id in base58 format. Can be passed multiple times.
+	#[arg(long, value_name = "PEER_ID")]
+	pub parachain_peer_allowlist: Vec<String>,
+
+	/// Disconnect the given peer whenever it connects on the parachain protocols.
+	///
+	/// Expects a peer id in base58 format. Can be passed multiple times.
+	#[arg(long, value_name = "PEER_ID")]
+	pub parachain_peer_banlist: Vec<String>,
 }
 
 #[allow(missing_docs)]
@@ -327,6 +327,26 @@ where
 		None
 	};
 
+	let collator_filter = service::CollatorFilter::from_entries(
+		&cli.run.collator_allowlist,
+		&cli.run.collator_denylist,
+	)
+	.map_err(Error::InvalidCollatorFilter)?;
+
+	let peer_filter = service::PeerFilter::from_entries(
+		&cli.run.parachain_peer_allowlist,
+		&cli.run.parachain_peer_banlist,
+	)
+	.map_err(Error::InvalidPeerFilter)?;
+
+	let message_tap = cli
+		.run
+		.overseer_message_tap
+		.as_deref()
+		.map(service::MessageTap::create)
+		.transpose()
+		.map_err(Error::MessageTap)?;
+
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = (!cli.run.no_hardware_benchmarks)
 			.then_some(config.database.path().map(|database_path| {
@@ -345,6 +365,11 @@ where
 			false,
 			overseer_gen,
 			cli.run.overseer_channel_capacity_override,
+			collator_filter,
+			cli.run.parachain_outbound_bandwidth,
+			cli.run.dispute_send_rate_limit.map(std::time::Duration::from_millis),
+			peer_filter,
+			message_tap,
 			maybe_malus_finality_delay,
 			hwbench,
 		)
@@ -45,6 +45,15 @@ pub enum Error {
 	#[error("URL did not resolve to anything")]
 	AddressResolutionMissing,
 
+	#[error("Invalid collator allow or deny list: {0}")]
+	InvalidCollatorFilter(String),
+
+	#[error("Invalid parachain peer allow or ban list: {0}")]
+	InvalidPeerFilter(String),
+
+	#[error("Failed to create the overseer message tap: {0}")]
+	MessageTap(std::io::Error),
+
 	#[error("Command is not implemented")]
 	CommandNotImplemented,
 
@@ -331,6 +331,7 @@ mod handle_new_activations {
 		match AllMessages::from(sent_messages.pop().unwrap()) {
 			AllMessages::CollatorProtocol(CollatorProtocolMessage::DistributeCollation(
 				CandidateReceipt { descriptor, .. },
+				_parent_head_data_hash,
 				_pov,
 				..,
 			)) => {
@@ -473,6 +474,7 @@ mod handle_new_activations {
 			overseer::CollationGenerationOutgoingMessages::CollatorProtocolMessage(
 				CollatorProtocolMessage::DistributeCollation(
 					CandidateReceipt { descriptor, .. },
+					_parent_head_data_hash,
 					_pov,
 					..,
 				),
@@ -324,6 +324,9 @@ fn filled_tranche_iterator(
 /// based on the earliest assignment `tick` of a non-approving, yet-to-be-no-show validator:
 ///  - if `tick` <= `clock_drift`: the value will always be `clock_drift` + `no_show_duration`.
 ///  - if `tick` >  `clock_drift`: the value is equal to `tick` + `no_show_duration`.
+///
+/// A coalesced approval vote is recorded in `approvals` of every candidate it covers, so it
+/// covers the assignment of its validator for each of them.
 fn count_no_shows(
 	assignments: &[(ValidatorIndex, Tick)],
 	approvals: &BitSlice<u8, BitOrderLsb0>,
@@ -460,7 +463,7 @@ mod tests {
 	use crate::{approval_db, BTreeMap};
 	use ::test_helpers::{dummy_candidate_receipt, dummy_hash};
 	use bitvec::{bitvec, order::Lsb0 as BitOrderLsb0, vec::BitVec};
-	use polkadot_primitives::GroupIndex;
+	use polkadot_primitives::{GroupIndex, Hash};
 
 	#[test]
 	fn pending_is_not_approved() {
@@ -495,6 +498,55 @@ mod tests {
 		.is_approved(Tick::max_value()));
 	}
 
+	#[test]
+	fn coalesced_approval_counts_for_each_covered_candidate() {
+		let approval_entry: ApprovalEntry = approval_db::v1::ApprovalEntry {
+			tranches: vec![approval_db::v1::TrancheEntry {
+				tranche: 0,
+				assignments: (0..2).map(|i| (ValidatorIndex(i), 0.into())).collect(),
+			}],
+			assignments: bitvec![u8, BitOrderLsb0; 1; 2],
+			our_assignment: None,
+			our_approval_sig: None,
+			backing_group: GroupIndex(0),
+			approved: false,
+		}
+		.into();
+
+		let mut candidates: Vec<CandidateEntry> = (0..2u8)
+			.map(|i| {
+				approval_db::v1::CandidateEntry {
+					candidate: dummy_candidate_receipt(Hash::repeat_byte(i)),
+					session: 0,
+					block_assignments: BTreeMap::default(),
+					approvals: bitvec![u8, BitOrderLsb0; 0; 2],
+				}
+				.into()
+			})
+			.collect();
+
+		// Validator 0 approves both candidates with a single coalesced vote, validator 1 only
+		// approves the first one.
+		for candidate in &mut candidates {
+			candidate.mark_approval(ValidatorIndex(0));
+		}
+		candidates[0].mark_approval(ValidatorIndex(1));
+
+		let required = |tolerated_missing| RequiredTranches::Exact {
+			needed: 0,
+			tolerated_missing,
+			next_no_show: None,
+			last_assignment_tick: None,
+		};
+
+		assert!(check_approval(&candidates[0], &approval_entry, required(0))
+			.is_approved(Tick::max_value()));
+		assert!(!check_approval(&candidates[1], &approval_entry, required(0))
+			.is_approved(Tick::max_value()));
+		assert!(check_approval(&candidates[1], &approval_entry, required(1))
+			.is_approved(Tick::max_value()));
+	}
+
 	#[test]
 	fn exact_takes_only_assignments_up_to() {
 		let mut candidate: CandidateEntry = approval_db::v1::CandidateEntry {
@@ -29,5 +29,10 @@
 //! As such, we provide a function from this module to clear the database on start-up.
 //! In the future, we may use a temporary DB which doesn't need to be wiped, but for the
 //! time being we share the same DB with the rest of Substrate.
+//!
+//! Every change to the encoding of persisted entries gets a new schema module. The
+//! `migration_helpers` of the newest schema upgrade the column in-place from the previous
+//! one, and are run by the parachains DB upgrade code on start-up.
 
 pub mod v1;
+pub mod v2;
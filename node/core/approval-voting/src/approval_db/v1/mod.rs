@@ -34,7 +34,7 @@ use crate::{
 	persisted_entries,
 };
 
-const STORED_BLOCKS_KEY: &[u8] = b"Approvals_StoredBlocks";
+pub(crate) const STORED_BLOCKS_KEY: &[u8] = b"Approvals_StoredBlocks";
 
 #[cfg(test)]
 pub mod tests;
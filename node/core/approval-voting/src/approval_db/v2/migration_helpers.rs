@@ -0,0 +1,208 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Approval DB migration helpers.
+
+use super::*;
+use crate::{approval_db::v1, LOG_TARGET};
+#[cfg(test)]
+use polkadot_primitives::{CoreIndex, GroupIndex};
+#[cfg(test)]
+use sp_consensus_slots::Slot;
+
+use std::collections::HashSet;
+
+/// How many blocks are migrated between two progress reports.
+const PROGRESS_REPORT_INTERVAL: usize = 1000;
+
+/// Migrate the approval-voting column from the v1 schema to the v2 schema.
+///
+/// All entries are converted in memory and written in a single transaction, so the migration
+/// either fully succeeds or leaves the column untouched and can be retried.
+///
+/// Returns the number of candidate entries which were migrated.
+pub fn v1_to_v2(db: Arc<dyn Database>, config: Config) -> Result<usize> {
+	let all_blocks = load_all_blocks_raw(&*db, &config)?;
+
+	gum::info!(
+		target: LOG_TARGET,
+		"Migrating approval-voting DB from v1 to v2, {} blocks to process",
+		all_blocks.len(),
+	);
+
+	let mut tx = DBTransaction::new();
+	let mut migrated_candidates = HashSet::new();
+
+	for (i, block_hash) in all_blocks.iter().enumerate() {
+		let block_entry: Option<BlockEntry> =
+			load_decode(&*db, config.col_approval_data, &block_entry_key(block_hash))?;
+		let block_entry = match block_entry {
+			Some(block_entry) => block_entry,
+			None => continue,
+		};
+
+		for (_, candidate_hash) in block_entry.candidates {
+			if !migrated_candidates.insert(candidate_hash) {
+				continue
+			}
+
+			let key = candidate_entry_key(&candidate_hash);
+			let candidate_entry: Option<v1::CandidateEntry> =
+				load_decode(&*db, config.col_approval_data, &key)?;
+
+			if let Some(candidate_entry) = candidate_entry {
+				tx.put_vec(
+					config.col_approval_data,
+					&key,
+					CandidateEntry::from(candidate_entry).encode(),
+				);
+			}
+		}
+
+		if (i + 1) % PROGRESS_REPORT_INTERVAL == 0 {
+			gum::info!(
+				target: LOG_TARGET,
+				"Approval-voting DB migration progress: {}/{} blocks",
+				i + 1,
+				all_blocks.len(),
+			);
+		}
+	}
+
+	db.write(tx)?;
+
+	gum::info!(
+		target: LOG_TARGET,
+		"Approval-voting DB migration complete, {} candidate entries migrated",
+		migrated_candidates.len(),
+	);
+
+	Ok(migrated_candidates.len())
+}
+
+/// Check that all candidate entries referenced by the stored blocks decode with the v2 schema.
+pub fn v2_sanity_check(db: Arc<dyn Database>, config: Config) -> Result<()> {
+	for block_hash in load_all_blocks_raw(&*db, &config)? {
+		let block_entry: Option<BlockEntry> =
+			load_decode(&*db, config.col_approval_data, &block_entry_key(&block_hash))?;
+
+		for (_, candidate_hash) in block_entry.map(|e| e.candidates).unwrap_or_default() {
+			let _: Option<CandidateEntry> =
+				load_decode(&*db, config.col_approval_data, &candidate_entry_key(&candidate_hash))?;
+		}
+	}
+
+	Ok(())
+}
+
+// Like `load_all_blocks`, but doesn't wrap the errors into a `SubsystemError`.
+fn load_all_blocks_raw(store: &dyn Database, config: &Config) -> Result<Vec<Hash>> {
+	let mut hashes = Vec::new();
+	let stored_blocks: Option<StoredBlockRange> =
+		load_decode(store, config.col_approval_data, STORED_BLOCKS_KEY)?;
+
+	if let Some(stored_blocks) = stored_blocks {
+		for height in stored_blocks.0..stored_blocks.1 {
+			let blocks: Option<Vec<Hash>> =
+				load_decode(store, config.col_approval_data, &blocks_at_height_key(height))?;
+			hashes.extend(blocks.unwrap_or_default());
+		}
+	}
+
+	Ok(hashes)
+}
+
+/// Fill the approval-voting column with some v1 entries, for testing migrations.
+///
+/// Adds one block per candidate returned by `make_candidate`, each including that candidate
+/// with a local approval vote. Returns the hashes of all candidates written.
+#[cfg(test)]
+pub(crate) fn v1_fill_test_data<F>(
+	db: Arc<dyn Database>,
+	config: Config,
+	num_blocks: BlockNumber,
+	make_candidate: F,
+) -> Result<HashSet<CandidateHash>>
+where
+	F: Fn(BlockNumber) -> CandidateReceipt,
+{
+	let mut tx = DBTransaction::new();
+	let mut candidates = HashSet::new();
+
+	for number in 0..num_blocks {
+		let block_hash = Hash::repeat_byte(number as u8 + 1);
+		let candidate = make_candidate(number);
+		let candidate_hash = candidate.hash();
+
+		let block_entry = BlockEntry {
+			block_hash,
+			block_number: number,
+			parent_hash: Hash::repeat_byte(number as u8),
+			session: 1,
+			slot: Slot::from(1),
+			relay_vrf_story: [0u8; 32],
+			candidates: vec![(CoreIndex(0), candidate_hash)],
+			approved_bitfield: bitvec::bitvec![u8, bitvec::order::Lsb0; 0; 1],
+			children: Vec::new(),
+		};
+
+		let candidate_entry = v1::CandidateEntry {
+			candidate,
+			session: 1,
+			block_assignments: vec![(
+				block_hash,
+				v1::ApprovalEntry {
+					tranches: Vec::new(),
+					backing_group: GroupIndex(1),
+					our_assignment: None,
+					our_approval_sig: Some(
+						ValidatorSignature::decode(&mut &[number as u8; 64][..])
+							.expect("64 bytes are a valid sr25519 signature; qed"),
+					),
+					assignments: Default::default(),
+					approved: false,
+				},
+			)]
+			.into_iter()
+			.collect(),
+			approvals: Default::default(),
+		};
+
+		tx.put_vec(config.col_approval_data, &block_entry_key(&block_hash), block_entry.encode());
+		tx.put_vec(
+			config.col_approval_data,
+			&blocks_at_height_key(number),
+			vec![block_hash].encode(),
+		);
+		tx.put_vec(
+			config.col_approval_data,
+			&candidate_entry_key(&candidate_hash),
+			candidate_entry.encode(),
+		);
+
+		candidates.insert(candidate_hash);
+	}
+
+	tx.put_vec(
+		config.col_approval_data,
+		&STORED_BLOCKS_KEY,
+		StoredBlockRange(0, num_blocks).encode(),
+	);
+
+	db.write(tx)?;
+
+	Ok(candidates)
+}
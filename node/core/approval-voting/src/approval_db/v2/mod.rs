@@ -0,0 +1,221 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Version 2 of the DB schema.
+//!
+//! Compared to version 1, our own approval votes are persisted together with the indices
+//! of the candidates they cover, so that approvals covering multiple candidates survive a
+//! restart. Entries which didn't change are re-exported from version 1.
+
+use parity_scale_codec::{Decode, Encode};
+use polkadot_node_primitives::approval::CandidateBitfield;
+use polkadot_node_subsystem::{SubsystemError, SubsystemResult};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_primitives::{
+	BlockNumber, CandidateHash, CandidateReceipt, GroupIndex, Hash, SessionIndex,
+	ValidatorSignature,
+};
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+	backend::{Backend, BackendWriteOp},
+	persisted_entries,
+};
+
+pub(crate) use super::v1::{
+	block_entry_key, blocks_at_height_key, candidate_entry_key, load_decode, STORED_BLOCKS_KEY,
+};
+pub use super::v1::{
+	load_all_blocks, load_block_entry, load_blocks_at_height, load_stored_blocks, Bitfield,
+	BlockEntry, Config, Error, OurAssignment, Result, StoredBlockRange, Tick, TrancheEntry,
+};
+
+pub mod migration_helpers;
+
+#[cfg(test)]
+pub mod tests;
+
+/// `DbBackend` is a concrete implementation of the higher-level Backend trait
+pub struct DbBackend {
+	inner: Arc<dyn Database>,
+	config: Config,
+}
+
+impl DbBackend {
+	/// Create a new [`DbBackend`] with the supplied key-value store and
+	/// config.
+	pub fn new(db: Arc<dyn Database>, config: Config) -> Self {
+		DbBackend { inner: db, config }
+	}
+}
+
+impl Backend for DbBackend {
+	fn load_block_entry(
+		&self,
+		block_hash: &Hash,
+	) -> SubsystemResult<Option<persisted_entries::BlockEntry>> {
+		load_block_entry(&*self.inner, &self.config, block_hash).map(|e| e.map(Into::into))
+	}
+
+	fn load_candidate_entry(
+		&self,
+		candidate_hash: &CandidateHash,
+	) -> SubsystemResult<Option<persisted_entries::CandidateEntry>> {
+		load_candidate_entry(&*self.inner, &self.config, candidate_hash).map(|e| e.map(Into::into))
+	}
+
+	fn load_blocks_at_height(&self, block_height: &BlockNumber) -> SubsystemResult<Vec<Hash>> {
+		load_blocks_at_height(&*self.inner, &self.config, block_height)
+	}
+
+	fn load_all_blocks(&self) -> SubsystemResult<Vec<Hash>> {
+		load_all_blocks(&*self.inner, &self.config)
+	}
+
+	fn load_stored_blocks(&self) -> SubsystemResult<Option<StoredBlockRange>> {
+		load_stored_blocks(&*self.inner, &self.config)
+	}
+
+	/// Atomically write the list of operations, with later operations taking precedence over prior.
+	fn write<I>(&mut self, ops: I) -> SubsystemResult<()>
+	where
+		I: IntoIterator<Item = BackendWriteOp>,
+	{
+		let mut tx = DBTransaction::new();
+		for op in ops {
+			match op {
+				BackendWriteOp::WriteStoredBlockRange(stored_block_range) => {
+					tx.put_vec(
+						self.config.col_approval_data,
+						&STORED_BLOCKS_KEY,
+						stored_block_range.encode(),
+					);
+				},
+				BackendWriteOp::DeleteStoredBlockRange => {
+					tx.delete(self.config.col_approval_data, &STORED_BLOCKS_KEY);
+				},
+				BackendWriteOp::WriteBlocksAtHeight(h, blocks) => {
+					tx.put_vec(
+						self.config.col_approval_data,
+						&blocks_at_height_key(h),
+						blocks.encode(),
+					);
+				},
+				BackendWriteOp::DeleteBlocksAtHeight(h) => {
+					tx.delete(self.config.col_approval_data, &blocks_at_height_key(h));
+				},
+				BackendWriteOp::WriteBlockEntry(block_entry) => {
+					let block_entry: BlockEntry = block_entry.into();
+					tx.put_vec(
+						self.config.col_approval_data,
+						&block_entry_key(&block_entry.block_hash),
+						block_entry.encode(),
+					);
+				},
+				BackendWriteOp::DeleteBlockEntry(hash) => {
+					tx.delete(self.config.col_approval_data, &block_entry_key(&hash));
+				},
+				BackendWriteOp::WriteCandidateEntry(candidate_entry) => {
+					let candidate_entry: CandidateEntry = candidate_entry.into();
+					tx.put_vec(
+						self.config.col_approval_data,
+						&candidate_entry_key(&candidate_entry.candidate.hash()),
+						candidate_entry.encode(),
+					);
+				},
+				BackendWriteOp::DeleteCandidateEntry(candidate_hash) => {
+					tx.delete(self.config.col_approval_data, &candidate_entry_key(&candidate_hash));
+				},
+			}
+		}
+
+		self.inner.write(tx).map_err(|e| e.into())
+	}
+}
+
+/// Our local approval vote for a candidate.
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub struct OurApproval {
+	pub signature: ValidatorSignature,
+	// The indices of all candidates of the block covered by the signature, if the vote
+	// covers multiple candidates.
+	pub signed_candidates_indices: Option<CandidateBitfield>,
+}
+
+/// Metadata regarding approval of a particular candidate within the context of some
+/// particular block.
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub struct ApprovalEntry {
+	pub tranches: Vec<TrancheEntry>,
+	pub backing_group: GroupIndex,
+	pub our_assignment: Option<OurAssignment>,
+	pub our_approval_sig: Option<OurApproval>,
+	// `n_validators` bits.
+	pub assignments: Bitfield,
+	pub approved: bool,
+}
+
+/// Metadata regarding approval of a particular candidate.
+#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+pub struct CandidateEntry {
+	pub candidate: CandidateReceipt,
+	pub session: SessionIndex,
+	// Assignments are based on blocks, so we need to track assignments separately
+	// based on the block we are looking at.
+	pub block_assignments: BTreeMap<Hash, ApprovalEntry>,
+	pub approvals: Bitfield,
+}
+
+impl From<super::v1::ApprovalEntry> for ApprovalEntry {
+	fn from(entry: super::v1::ApprovalEntry) -> Self {
+		ApprovalEntry {
+			tranches: entry.tranches,
+			backing_group: entry.backing_group,
+			our_assignment: entry.our_assignment,
+			our_approval_sig: entry
+				.our_approval_sig
+				.map(|signature| OurApproval { signature, signed_candidates_indices: None }),
+			assignments: entry.assignments,
+			approved: entry.approved,
+		}
+	}
+}
+
+impl From<super::v1::CandidateEntry> for CandidateEntry {
+	fn from(entry: super::v1::CandidateEntry) -> Self {
+		CandidateEntry {
+			candidate: entry.candidate,
+			session: entry.session,
+			block_assignments: entry
+				.block_assignments
+				.into_iter()
+				.map(|(h, ae)| (h, ae.into()))
+				.collect(),
+			approvals: entry.approvals,
+		}
+	}
+}
+
+/// Load a candidate entry from the aux store.
+pub fn load_candidate_entry(
+	store: &dyn Database,
+	config: &Config,
+	candidate_hash: &CandidateHash,
+) -> SubsystemResult<Option<CandidateEntry>> {
+	load_decode(store, config.col_approval_data, &candidate_entry_key(candidate_hash))
+		.map_err(|e| SubsystemError::with_origin("approval-voting", e))
+}
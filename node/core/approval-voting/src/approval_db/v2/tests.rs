@@ -0,0 +1,153 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the aux-schema of approval voting.
+
+use super::{migration_helpers, DbBackend, StoredBlockRange, *};
+use crate::backend::{Backend, OverlayedBackend};
+use polkadot_node_subsystem_util::database::Database;
+use polkadot_primitives::{CoreIndex, Id as ParaId};
+use sp_consensus_slots::Slot;
+use std::sync::Arc;
+
+use ::test_helpers::{dummy_candidate_receipt, dummy_candidate_receipt_bad_sig, dummy_hash};
+
+const DATA_COL: u32 = 0;
+const SESSION_DATA_COL: u32 = 1;
+
+const NUM_COLUMNS: u32 = 2;
+
+const TEST_CONFIG: Config =
+	Config { col_approval_data: DATA_COL, col_session_data: SESSION_DATA_COL };
+
+fn make_db() -> (DbBackend, Arc<dyn Database>) {
+	let db = kvdb_memorydb::create(NUM_COLUMNS);
+	let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[]);
+	let db_writer: Arc<dyn Database> = Arc::new(db);
+	(DbBackend::new(db_writer.clone(), TEST_CONFIG), db_writer)
+}
+
+fn make_candidate(para_id: ParaId, relay_parent: Hash) -> CandidateReceipt {
+	let mut c = dummy_candidate_receipt(dummy_hash());
+
+	c.descriptor.para_id = para_id;
+	c.descriptor.relay_parent = relay_parent;
+
+	c
+}
+
+fn dummy_signature() -> ValidatorSignature {
+	ValidatorSignature::decode(&mut &[1u8; 64][..]).unwrap()
+}
+
+#[test]
+fn read_write_coalesced_approval() {
+	let (mut db, store) = make_db();
+
+	let hash_a = Hash::repeat_byte(1);
+	let candidate_a = dummy_candidate_receipt_bad_sig(dummy_hash(), None);
+	let candidate_b = make_candidate(ParaId::from(2_u32), hash_a);
+	let candidate_hash_a = candidate_a.hash();
+	let candidate_hash_b = candidate_b.hash();
+
+	let block_entry = BlockEntry {
+		block_hash: hash_a,
+		parent_hash: Default::default(),
+		block_number: 1,
+		session: 1,
+		slot: Slot::from(1),
+		relay_vrf_story: [0u8; 32],
+		approved_bitfield: bitvec::bitvec![u8, bitvec::order::Lsb0; 0; 2],
+		candidates: vec![(CoreIndex(0), candidate_hash_a), (CoreIndex(1), candidate_hash_b)],
+		children: Vec::new(),
+	};
+
+	let candidate_entry = CandidateEntry {
+		candidate: candidate_a,
+		session: 1,
+		block_assignments: vec![(
+			hash_a,
+			ApprovalEntry {
+				tranches: Vec::new(),
+				backing_group: GroupIndex(1),
+				our_assignment: None,
+				our_approval_sig: Some(OurApproval {
+					signature: dummy_signature(),
+					signed_candidates_indices: Some(bitvec::bitvec![u8, bitvec::order::Lsb0; 1; 2]),
+				}),
+				assignments: Default::default(),
+				approved: false,
+			},
+		)]
+		.into_iter()
+		.collect(),
+		approvals: Default::default(),
+	};
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	overlay_db.write_stored_block_range(StoredBlockRange(1, 2));
+	overlay_db.write_blocks_at_height(1, vec![hash_a]);
+	overlay_db.write_block_entry(block_entry.clone().into());
+	overlay_db.write_candidate_entry(candidate_entry.clone().into());
+
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	assert_eq!(load_block_entry(store.as_ref(), &TEST_CONFIG, &hash_a).unwrap(), Some(block_entry));
+	assert_eq!(
+		load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_a).unwrap(),
+		Some(candidate_entry),
+	);
+}
+
+#[test]
+fn migrate_v1_to_v2_keeps_entries() {
+	let (_, store) = make_db();
+
+	let candidates =
+		migration_helpers::v1_fill_test_data(store.clone(), TEST_CONFIG, 10, |number| {
+			make_candidate(ParaId::from(number), Hash::repeat_byte(number as u8))
+		})
+		.unwrap();
+
+	let migrated = migration_helpers::v1_to_v2(store.clone(), TEST_CONFIG).unwrap();
+	assert_eq!(migrated, candidates.len());
+
+	migration_helpers::v2_sanity_check(store.clone(), TEST_CONFIG).unwrap();
+
+	for candidate_hash in candidates {
+		let entry = load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash)
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(entry.candidate.hash(), candidate_hash);
+		assert_eq!(entry.block_assignments.len(), 1);
+
+		let approval_entry = entry.block_assignments.values().next().unwrap();
+		let our_approval = approval_entry.our_approval_sig.as_ref().unwrap();
+		assert!(our_approval.signed_candidates_indices.is_none());
+	}
+
+	assert_eq!(load_all_blocks(store.as_ref(), &TEST_CONFIG).unwrap().len(), 10);
+}
+
+#[test]
+fn migrate_v1_to_v2_empty_db() {
+	let (_, store) = make_db();
+
+	assert_eq!(migration_helpers::v1_to_v2(store.clone(), TEST_CONFIG).unwrap(), 0);
+	migration_helpers::v2_sanity_check(store, TEST_CONFIG).unwrap();
+}
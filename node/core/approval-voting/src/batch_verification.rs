@@ -0,0 +1,171 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batched verification of approval vote signatures.
+//!
+//! Approval votes are plain sr25519 signatures, so a set of them can be checked with a single
+//! multi-scalar multiplication instead of one per signature. A failing batch only tells us that
+//! at least one signature is bad, so in that case we fall back to checking every signature on
+//! its own in order to find the culprits.
+//!
+//! Assignment certificates are not batched: they carry VRF proofs in their short `(c, s)` form,
+//! which cannot be verified in batches.
+
+use polkadot_primitives::{ValidatorId, ValidatorSignature};
+use sp_runtime::traits::AppVerify;
+
+/// The signing context used by substrate for sr25519 signatures.
+const SUBSTRATE_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Batches smaller than this are verified signature by signature, as batching does not pay off.
+pub(crate) const MIN_BATCH_SIZE: usize = 2;
+
+/// A signature waiting to be verified.
+pub(crate) struct SignatureCheck<'a> {
+	/// The key the payload is expected to be signed with.
+	pub pubkey: &'a ValidatorId,
+	/// The signed payload.
+	pub payload: Vec<u8>,
+	/// The signature to check.
+	pub signature: &'a ValidatorSignature,
+}
+
+impl<'a> SignatureCheck<'a> {
+	fn check(&self) -> bool {
+		self.signature.verify(&self.payload[..], self.pubkey)
+	}
+}
+
+/// The outcome of verifying a batch of signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BatchOutcome {
+	/// Whether the signature at the respective position of the batch is valid.
+	pub valid: Vec<bool>,
+	/// Whether the batch had to be re-checked signature by signature.
+	pub fell_back: bool,
+}
+
+/// Verify all given signatures, batching them where possible.
+///
+/// The returned outcome has exactly one entry per given check, in the same order.
+pub(crate) fn verify_signatures(checks: &[SignatureCheck<'_>]) -> BatchOutcome {
+	if checks.len() < MIN_BATCH_SIZE {
+		return BatchOutcome {
+			valid: checks.iter().map(SignatureCheck::check).collect(),
+			fell_back: false,
+		}
+	}
+
+	if verify_batch(checks) {
+		return BatchOutcome { valid: vec![true; checks.len()], fell_back: false }
+	}
+
+	BatchOutcome { valid: checks.iter().map(SignatureCheck::check).collect(), fell_back: true }
+}
+
+// Returns `true` only if all signatures of the batch are valid.
+fn verify_batch(checks: &[SignatureCheck<'_>]) -> bool {
+	let mut public_keys = Vec::with_capacity(checks.len());
+	let mut signatures = Vec::with_capacity(checks.len());
+
+	for check in checks {
+		let public = schnorrkel::PublicKey::from_bytes(check.pubkey.as_ref());
+		let signature = schnorrkel::Signature::from_bytes(check.signature.as_ref());
+
+		match (public, signature) {
+			(Ok(public), Ok(signature)) => {
+				public_keys.push(public);
+				signatures.push(signature);
+			},
+			_ => return false,
+		}
+	}
+
+	let signing_context = schnorrkel::signing_context(SUBSTRATE_SIGNING_CONTEXT);
+	let transcripts = checks.iter().map(|check| signing_context.bytes(&check.payload[..]));
+
+	schnorrkel::verify_batch(transcripts, &signatures[..], &public_keys[..], false).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use polkadot_primitives::{ApprovalVote, CandidateHash, Hash};
+	use sp_keyring::sr25519::Keyring as Sr25519Keyring;
+
+	fn payload(n: u8) -> Vec<u8> {
+		ApprovalVote(CandidateHash(Hash::repeat_byte(n))).signing_payload(1)
+	}
+
+	fn signed(keys: &[Sr25519Keyring]) -> Vec<(ValidatorId, Vec<u8>, ValidatorSignature)> {
+		keys.iter()
+			.enumerate()
+			.map(|(i, key)| {
+				let payload = payload(i as u8);
+				(key.public().into(), payload.clone(), key.sign(&payload[..]).into())
+			})
+			.collect()
+	}
+
+	fn checks(signed: &[(ValidatorId, Vec<u8>, ValidatorSignature)]) -> Vec<SignatureCheck<'_>> {
+		signed
+			.iter()
+			.map(|(pubkey, payload, signature)| SignatureCheck {
+				pubkey,
+				payload: payload.clone(),
+				signature,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn valid_batch_does_not_fall_back() {
+		let signed = signed(&[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie]);
+
+		assert_eq!(
+			verify_signatures(&checks(&signed)),
+			BatchOutcome { valid: vec![true, true, true], fell_back: false },
+		);
+	}
+
+	#[test]
+	fn bad_signature_is_identified() {
+		let mut signed =
+			signed(&[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie]);
+		// Bob signs Alice's payload instead of his own.
+		signed[1].2 = Sr25519Keyring::Bob.sign(&payload(0)[..]).into();
+
+		assert_eq!(
+			verify_signatures(&checks(&signed)),
+			BatchOutcome { valid: vec![true, false, true], fell_back: true },
+		);
+	}
+
+	#[test]
+	fn single_signature_is_checked_directly() {
+		let mut signed = signed(&[Sr25519Keyring::Alice]);
+		assert_eq!(
+			verify_signatures(&checks(&signed)),
+			BatchOutcome { valid: vec![true], fell_back: false },
+		);
+
+		signed[0].1 = payload(42);
+		assert_eq!(
+			verify_signatures(&checks(&signed)),
+			BatchOutcome { valid: vec![false], fell_back: false },
+		);
+	}
+}
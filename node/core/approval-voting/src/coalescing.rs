@@ -0,0 +1,179 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Coalescing of local approvals.
+//!
+//! When the runtime enables it, approvals of candidates included in the same relay-chain block
+//! are held back for a short while, so that they can be issued as a single vote with a single
+//! signature covering all of them.
+//!
+//! Approvals are never held back for longer than [`MAX_APPROVAL_COALESCE_WAIT_TICKS`], which is
+//! well below the no-show duration, so that coalescing does not turn us into a no-show.
+
+use futures::future;
+use polkadot_primitives::{CandidateHash, Hash, ValidatorIndex};
+use std::collections::{btree_map::Entry as BTMEntry, BTreeMap, HashMap};
+
+use crate::time::{Clock, Tick};
+
+/// The maximum number of ticks an approval is held back, waiting for approvals of other
+/// candidates in the same block.
+pub(crate) const MAX_APPROVAL_COALESCE_WAIT_TICKS: Tick = 4;
+
+/// Approvals waiting to be issued, per relay-chain block and validator.
+#[derive(Default)]
+pub(crate) struct PendingApprovals {
+	pending: HashMap<(Hash, ValidatorIndex), PendingBlockApprovals>,
+	// Tick -> [(Relay Block, Validator Index)]
+	deadlines: BTreeMap<Tick, Vec<(Hash, ValidatorIndex)>>,
+}
+
+struct PendingBlockApprovals {
+	candidates: Vec<CandidateHash>,
+	deadline: Tick,
+}
+
+impl PendingApprovals {
+	/// Hold back the approval of a candidate in the given block.
+	///
+	/// Once `max_coalesce_count` approvals are pending for the block, all of them are returned
+	/// and need to be issued right away.
+	pub(crate) fn insert(
+		&mut self,
+		block_hash: Hash,
+		validator_index: ValidatorIndex,
+		candidate_hash: CandidateHash,
+		max_coalesce_count: u32,
+		tick_now: Tick,
+	) -> Option<Vec<CandidateHash>> {
+		let key = (block_hash, validator_index);
+		let pending = self.pending.entry(key).or_insert_with(|| {
+			let deadline = tick_now + MAX_APPROVAL_COALESCE_WAIT_TICKS;
+			self.deadlines.entry(deadline).or_default().push(key);
+
+			PendingBlockApprovals { candidates: Vec::new(), deadline }
+		});
+
+		if !pending.candidates.contains(&candidate_hash) {
+			pending.candidates.push(candidate_hash);
+		}
+
+		if pending.candidates.len() >= max_coalesce_count as usize {
+			self.take(block_hash, validator_index)
+		} else {
+			None
+		}
+	}
+
+	/// Take all approvals pending for the given block, if any.
+	pub(crate) fn take(
+		&mut self,
+		block_hash: Hash,
+		validator_index: ValidatorIndex,
+	) -> Option<Vec<CandidateHash>> {
+		let key = (block_hash, validator_index);
+		let pending = self.pending.remove(&key)?;
+
+		if let BTMEntry::Occupied(mut entry) = self.deadlines.entry(pending.deadline) {
+			entry.get_mut().retain(|k| k != &key);
+			if entry.get().is_empty() {
+				let _ = entry.remove();
+			}
+		}
+
+		Some(pending.candidates)
+	}
+
+	/// Returns the next approvals whose waiting time is over. This future never returns if
+	/// there are no pending approvals.
+	pub(crate) async fn next(
+		&mut self,
+		clock: &(dyn Clock + Sync),
+	) -> (Hash, ValidatorIndex, Vec<CandidateHash>) {
+		let (tick, (block_hash, validator_index)) = match self
+			.deadlines
+			.iter()
+			.next()
+			.and_then(|(t, keys)| keys.first().map(|k| (*t, *k)))
+		{
+			None => return future::pending().await,
+			Some(next) => next,
+		};
+
+		clock.wait(tick).await;
+
+		let candidates = self
+			.take(block_hash, validator_index)
+			.expect("deadlines are only kept for pending approvals; qed");
+
+		(block_hash, validator_index, candidates)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn candidate(n: u8) -> CandidateHash {
+		CandidateHash(Hash::repeat_byte(n))
+	}
+
+	#[test]
+	fn approvals_are_released_once_max_count_is_reached() {
+		let mut pending = PendingApprovals::default();
+		let block_hash = Hash::repeat_byte(1);
+
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(1), 3, 10).is_none());
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(2), 3, 11).is_none());
+		// Approvals of other validators or blocks are kept apart.
+		assert!(pending.insert(block_hash, ValidatorIndex(1), candidate(3), 3, 11).is_none());
+		assert!(pending
+			.insert(Hash::repeat_byte(2), ValidatorIndex(0), candidate(3), 3, 11)
+			.is_none());
+
+		assert_eq!(
+			pending.insert(block_hash, ValidatorIndex(0), candidate(3), 3, 12),
+			Some(vec![candidate(1), candidate(2), candidate(3)]),
+		);
+		assert!(pending.take(block_hash, ValidatorIndex(0)).is_none());
+		assert_eq!(pending.deadlines.len(), 1);
+	}
+
+	#[test]
+	fn duplicate_approvals_are_ignored() {
+		let mut pending = PendingApprovals::default();
+		let block_hash = Hash::repeat_byte(1);
+
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(1), 2, 10).is_none());
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(1), 2, 10).is_none());
+		assert_eq!(pending.take(block_hash, ValidatorIndex(0)), Some(vec![candidate(1)]));
+		assert!(pending.deadlines.is_empty());
+	}
+
+	#[test]
+	fn deadline_is_set_by_first_approval() {
+		let mut pending = PendingApprovals::default();
+		let block_hash = Hash::repeat_byte(1);
+
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(1), 5, 10).is_none());
+		assert!(pending.insert(block_hash, ValidatorIndex(0), candidate(2), 5, 12).is_none());
+
+		assert_eq!(
+			pending.deadlines.keys().copied().collect::<Vec<_>>(),
+			vec![10 + MAX_APPROVAL_COALESCE_WAIT_TICKS],
+		);
+	}
+}
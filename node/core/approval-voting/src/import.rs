@@ -56,7 +56,7 @@ use futures::{channel::oneshot, prelude::*};
 
 use std::collections::HashMap;
 
-use super::approval_db::v1;
+use super::approval_db::v2;
 use crate::{
 	backend::{Backend, OverlayedBackend},
 	criteria::{AssignmentCriteria, OurAssignment},
@@ -517,7 +517,7 @@ pub(crate) async fn handle_new_head<Context, B: Backend>(
 			ctx.send_message(ChainSelectionMessage::Approved(block_hash)).await;
 		}
 
-		let block_entry = v1::BlockEntry {
+		let block_entry = v2::BlockEntry {
 			block_hash,
 			parent_hash: block_header.parent_hash,
 			block_number: block_header.number,
@@ -609,7 +609,7 @@ pub(crate) async fn handle_new_head<Context, B: Backend>(
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use crate::approval_db::v1::DbBackend;
+	use crate::approval_db::v2::DbBackend;
 	use ::test_helpers::{dummy_candidate_receipt, dummy_hash};
 	use assert_matches::assert_matches;
 	use merlin::Transcript;
@@ -627,7 +627,7 @@ pub(crate) mod tests {
 	pub(crate) use sp_runtime::{Digest, DigestItem};
 	use std::{pin::Pin, sync::Arc};
 
-	use crate::{approval_db::v1::Config as DatabaseConfig, criteria, BlockEntry};
+	use crate::{approval_db::v2::Config as DatabaseConfig, criteria, BlockEntry};
 
 	const DATA_COL: u32 = 0;
 	const SESSION_DATA_COL: u32 = 1;
@@ -661,6 +661,9 @@ pub(crate) mod tests {
 			assignment_criteria: Box::new(MockAssignmentCriteria),
 			db,
 			db_config: TEST_CONFIG,
+			approval_voting_params_cache: lru::LruCache::new(
+				crate::APPROVAL_VOTING_PARAMS_CACHE_SIZE,
+			),
 		}
 	}
 
@@ -1225,7 +1228,7 @@ pub(crate) mod tests {
 
 		let mut state = single_session_state(session, session_info);
 		overlay_db.write_block_entry(
-			v1::BlockEntry {
+			v2::BlockEntry {
 				block_hash: parent_hash.clone(),
 				parent_hash: Default::default(),
 				block_number: 4,
@@ -1260,7 +1263,7 @@ pub(crate) mod tests {
 				// the first candidate should be insta-approved
 				// the second should not
 				let entry: BlockEntry =
-					v1::load_block_entry(db_writer.as_ref(), &TEST_CONFIG, &hash)
+					v2::load_block_entry(db_writer.as_ref(), &TEST_CONFIG, &hash)
 						.unwrap()
 						.unwrap()
 						.into();
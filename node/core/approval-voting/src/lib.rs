@@ -24,7 +24,8 @@
 use polkadot_node_jaeger as jaeger;
 use polkadot_node_primitives::{
 	approval::{
-		BlockApprovalMeta, DelayTranche, IndirectAssignmentCert, IndirectSignedApprovalVote,
+		BlockApprovalMeta, CandidateBitfield, DelayTranche, IndirectAssignmentCert,
+		IndirectSignedApprovalVote, IndirectSignedCoalescedApprovalVote,
 	},
 	ValidationResult, APPROVAL_EXECUTION_TIMEOUT,
 };
@@ -43,15 +44,17 @@ use polkadot_node_subsystem::{
 use polkadot_node_subsystem_util::{
 	database::Database,
 	metrics::{self, prometheus},
+	pvf_queue_depth::PvfQueueDepth,
 	rolling_session_window::{
 		DatabaseParams, RollingSessionWindow, SessionWindowUpdate, SessionsUnavailable,
 	},
 	TimeoutExt,
 };
 use polkadot_primitives::{
-	ApprovalVote, BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, DisputeStatement,
-	GroupIndex, Hash, SessionIndex, SessionInfo, ValidDisputeStatementKind, ValidatorId,
-	ValidatorIndex, ValidatorPair, ValidatorSignature,
+	vstaging::{ApprovalVoteMultipleCandidates, ApprovalVotingParams},
+	ApprovalVote, BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, GroupIndex, Hash,
+	SessionIndex, SessionInfo, ValidDisputeStatementKind, ValidatorId, ValidatorIndex,
+	ValidatorPair, ValidatorSignature,
 };
 use sc_keystore::LocalKeystore;
 use sp_application_crypto::Pair;
@@ -75,21 +78,26 @@ use std::{
 };
 
 use approval_checking::RequiredTranches;
+use coalescing::PendingApprovals;
 use criteria::{AssignmentCriteria, RealAssignmentCriteria};
-use persisted_entries::{ApprovalEntry, BlockEntry, CandidateEntry};
+use load_shedding::{CheckPriority, LoadShedder};
+use persisted_entries::{ApprovalEntry, BlockEntry, CandidateEntry, OurApproval};
 use time::{slot_number_to_tick, Clock, ClockExt, SystemClock, Tick};
 
 mod approval_checking;
-mod approval_db;
+pub mod approval_db;
 mod backend;
+mod batch_verification;
+mod coalescing;
 mod criteria;
 mod import;
+mod load_shedding;
 mod ops;
 mod persisted_entries;
 mod time;
 
 use crate::{
-	approval_db::v1::{Config as DatabaseConfig, DbBackend},
+	approval_db::v2::{Config as DatabaseConfig, DbBackend},
 	backend::{Backend, OverlayedBackend},
 };
 
@@ -106,6 +114,10 @@ const APPROVAL_CACHE_SIZE: NonZeroUsize = match NonZeroUsize::new(1024) {
 	Some(cap) => cap,
 	None => panic!("Approval cache size must be non-zero."),
 };
+const APPROVAL_VOTING_PARAMS_CACHE_SIZE: NonZeroUsize = match NonZeroUsize::new(128) {
+	Some(cap) => cap,
+	None => panic!("Approval voting params cache size must be non-zero."),
+};
 
 const TICK_TOO_FAR_IN_FUTURE: Tick = 20; // 10 seconds.
 const APPROVAL_DELAY: Tick = 2;
@@ -121,6 +133,8 @@ pub struct Config {
 	/// The slot duration of the consensus algorithm, in milliseconds. Should be evenly
 	/// divisible by 500.
 	pub slot_duration_millis: u64,
+	/// The depth of the PVF execution queue, shared with candidate validation.
+	pub pvf_queue_depth: PvfQueueDepth,
 }
 
 // The mode of the approval voting subsystem. It should start in a `Syncing` mode when it first
@@ -148,6 +162,7 @@ pub struct ApprovalVotingSubsystem {
 	db: Arc<dyn Database>,
 	mode: Mode,
 	metrics: Metrics,
+	pvf_queue_depth: PvfQueueDepth,
 }
 
 #[derive(Clone)]
@@ -162,6 +177,12 @@ struct MetricsInner {
 	time_db_transaction: prometheus::Histogram,
 	time_recover_and_approve: prometheus::Histogram,
 	candidate_signatures_requests_total: prometheus::Counter<prometheus::U64>,
+	approval_signature_batch_size: prometheus::Histogram,
+	approval_signature_batch_fallbacks_total: prometheus::Counter<prometheus::U64>,
+	time_verify_approval_signatures: prometheus::Histogram,
+	coalesced_approvals: prometheus::Histogram,
+	approval_checks_deferred_total: prometheus::Counter<prometheus::U64>,
+	approval_checks_deferred: prometheus::Gauge<prometheus::U64>,
 }
 
 /// Approval Voting metrics.
@@ -241,6 +262,33 @@ impl Metrics {
 		}
 	}
 
+	fn on_approval_signatures_verified(&self, batch_size: usize, fell_back: bool) {
+		if let Some(metrics) = &self.0 {
+			metrics.approval_signature_batch_size.observe(batch_size as f64);
+			if fell_back {
+				metrics.approval_signature_batch_fallbacks_total.inc();
+			}
+		}
+	}
+
+	fn on_approvals_coalesced(&self, n: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.coalesced_approvals.observe(n as f64);
+		}
+	}
+
+	fn on_approval_check_deferred(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.approval_checks_deferred_total.inc();
+		}
+	}
+
+	fn on_deferred_approval_checks(&self, n: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.approval_checks_deferred.set(n as u64);
+		}
+	}
+
 	fn time_db_transaction(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.time_db_transaction.start_timer())
 	}
@@ -248,6 +296,14 @@ impl Metrics {
 	fn time_recover_and_approve(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.time_recover_and_approve.start_timer())
 	}
+
+	fn time_verify_approval_signatures(
+		&self,
+	) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
+		self.0
+			.as_ref()
+			.map(|metrics| metrics.time_verify_approval_signatures.start_timer())
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -338,6 +394,54 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			approval_signature_batch_size: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_approval_signature_batch_size",
+						"Number of approval signatures verified together in one batch",
+					).buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0]),
+				)?,
+				registry,
+			)?,
+			approval_signature_batch_fallbacks_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_approval_signature_batch_fallbacks_total",
+					"Number of signature batches which had to be re-checked one by one because of a bad signature",
+				)?,
+				registry,
+			)?,
+			time_verify_approval_signatures: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_time_verify_approval_signatures",
+						"Time spent verifying a batch of approval signatures",
+					)
+				)?,
+				registry,
+			)?,
+			coalesced_approvals: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_approvals_coalesced",
+						"Number of candidates covered by a single approval vote we issued",
+					).buckets(vec![1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0]),
+				)?,
+				registry,
+			)?,
+			approval_checks_deferred_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_approval_checks_deferred_total",
+					"Number of approval checks deferred because too many checks were in flight",
+				)?,
+				registry,
+			)?,
+			approval_checks_deferred: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_approval_checks_deferred",
+					"Number of approval checks currently waiting to be launched",
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -363,17 +467,18 @@ impl ApprovalVotingSubsystem {
 			},
 			mode: Mode::Syncing(sync_oracle),
 			metrics,
+			pvf_queue_depth: config.pvf_queue_depth,
 		}
 	}
 
 	/// Revert to the block corresponding to the specified `hash`.
 	/// The operation is not allowed for blocks older than the last finalized one.
 	pub fn revert_to(&self, hash: Hash) -> Result<(), SubsystemError> {
-		let config = approval_db::v1::Config {
+		let config = approval_db::v2::Config {
 			col_approval_data: self.db_config.col_approval_data,
 			col_session_data: self.db_config.col_session_data,
 		};
-		let mut backend = approval_db::v1::DbBackend::new(self.db.clone(), config);
+		let mut backend = approval_db::v2::DbBackend::new(self.db.clone(), config);
 		let mut overlay = OverlayedBackend::new(&backend);
 
 		ops::revert_to(&mut overlay, hash)?;
@@ -628,6 +733,21 @@ impl CurrentlyCheckingSet {
 
 		future::pending().await
 	}
+
+	// Whether the given candidate is being checked.
+	pub fn contains(&self, candidate_hash: &CandidateHash) -> bool {
+		self.candidate_hash_map.contains_key(candidate_hash)
+	}
+
+	// The number of candidates being checked.
+	pub fn len(&self) -> usize {
+		self.candidate_hash_map.len()
+	}
+
+	// Whether any candidate included in the given relay block is still being checked.
+	pub fn is_checking_block(&self, relay_block: &Hash) -> bool {
+		self.candidate_hash_map.values().any(|blocks| blocks.contains(relay_block))
+	}
 }
 
 struct State {
@@ -639,6 +759,8 @@ struct State {
 	// Require for `RollingSessionWindow`.
 	db_config: DatabaseConfig,
 	db: Arc<dyn Database>,
+	// Approval voting parameters advertised by the runtime, per relay block.
+	approval_voting_params_cache: lru::LruCache<Hash, ApprovalVotingParams>,
 }
 
 #[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
@@ -647,6 +769,52 @@ impl State {
 		self.session_window.as_ref().and_then(|w| w.session_info(i))
 	}
 
+	/// Fetch the approval voting parameters the runtime advertises at the given block.
+	///
+	/// Falls back to the defaults, which disable coalescing, if the runtime does not support
+	/// the request.
+	async fn approval_voting_params<Context>(
+		&mut self,
+		ctx: &mut Context,
+		block_hash: Hash,
+	) -> ApprovalVotingParams {
+		if let Some(params) = self.approval_voting_params_cache.get(&block_hash) {
+			return params.clone()
+		}
+
+		let (tx, rx) = oneshot::channel();
+		ctx.send_message(RuntimeApiMessage::Request(
+			block_hash,
+			RuntimeApiRequest::ApprovalVotingParams(tx),
+		))
+		.await;
+
+		match rx.await {
+			Ok(Ok(params)) => {
+				self.approval_voting_params_cache.put(block_hash, params.clone());
+				params
+			},
+			Ok(Err(err)) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					?block_hash,
+					?err,
+					"Could not fetch approval voting params, coalescing disabled",
+				);
+				ApprovalVotingParams::default()
+			},
+			Err(err) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					?block_hash,
+					?err,
+					"Approval voting params request canceled, coalescing disabled",
+				);
+				ApprovalVotingParams::default()
+			},
+		}
+	}
+
 	/// Bring `session_window` up to date.
 	pub async fn cache_session_info_for_head<Context>(
 		&mut self,
@@ -743,13 +911,19 @@ enum Action {
 		indirect_cert: IndirectAssignmentCert,
 		assignment_tranche: DelayTranche,
 		relay_block_hash: Hash,
+		block_number: BlockNumber,
+		block_tick: Tick,
 		candidate_index: CandidateIndex,
 		session: SessionIndex,
 		candidate: CandidateReceipt,
 		backing_group: GroupIndex,
+		// Whether our assignment still needs to be distributed. Cleared once a deferred
+		// launch has distributed it.
+		distribute_assignment: bool,
 	},
 	NoteApprovedInChainSelection(Hash),
 	IssueApproval(CandidateHash, ApprovalVoteRequest),
+	IssuePendingApprovals(Vec<CandidateHash>, ApprovalVoteRequest),
 	BecomeActive,
 	Conclude,
 }
@@ -777,11 +951,14 @@ where
 		assignment_criteria,
 		db_config: subsystem.db_config,
 		db: subsystem.db,
+		approval_voting_params_cache: lru::LruCache::new(APPROVAL_VOTING_PARAMS_CACHE_SIZE),
 	};
 
 	let mut wakeups = Wakeups::default();
 	let mut currently_checking_set = CurrentlyCheckingSet::default();
 	let mut approvals_cache = lru::LruCache::new(APPROVAL_CACHE_SIZE);
+	let mut pending_approvals = PendingApprovals::default();
+	let mut load_shedder = LoadShedder::new(subsystem.pvf_queue_depth.clone());
 
 	let mut last_finalized_height: Option<BlockNumber> = {
 		let (tx, rx) = oneshot::channel();
@@ -820,6 +997,10 @@ where
 					&mut wakeups,
 				).await?;
 
+				if let Some(finalized) = last_finalized_height {
+					load_shedder.prune_finalized(finalized);
+				}
+
 				if let Mode::Syncing(ref mut oracle) = subsystem.mode {
 					if !oracle.is_major_syncing() {
 						// note that we're active before processing other actions.
@@ -858,6 +1039,14 @@ where
 
 				actions
 			}
+			(block_hash, validator_index, candidate_hashes) =
+				pending_approvals.next(&*state.clock).fuse() =>
+			{
+				vec![Action::IssuePendingApprovals(
+					candidate_hashes,
+					ApprovalVoteRequest { validator_index, block_hash },
+				)]
+			}
 		};
 
 		if handle_actions(
@@ -868,6 +1057,8 @@ where
 			&mut wakeups,
 			&mut currently_checking_set,
 			&mut approvals_cache,
+			&mut pending_approvals,
+			&mut load_shedder,
 			&mut subsystem.mode,
 			actions,
 		)
@@ -915,13 +1106,71 @@ async fn handle_actions<Context>(
 	wakeups: &mut Wakeups,
 	currently_checking_set: &mut CurrentlyCheckingSet,
 	approvals_cache: &mut lru::LruCache<CandidateHash, ApprovalOutcome>,
+	pending_approvals: &mut PendingApprovals,
+	load_shedder: &mut LoadShedder<Action>,
 	mode: &mut Mode,
 	actions: Vec<Action>,
 ) -> SubsystemResult<bool> {
 	let mut conclude = false;
 
 	let mut actions_iter = actions.into_iter();
-	while let Some(action) = actions_iter.next() {
+	// Once all actions are handled, launch as many deferred approval checks as we have
+	// capacity for.
+	while let Some(action) = actions_iter
+		.next()
+		.or_else(|| load_shedder.take_next(currently_checking_set.len()))
+	{
+		// Launching approval work which isn't already done or ongoing may need to be deferred.
+		let launch_priority = match &action {
+			Action::LaunchApproval {
+				candidate_hash,
+				assignment_tranche,
+				block_number,
+				block_tick,
+				..
+			} if matches!(*mode, Mode::Active) &&
+				approvals_cache.peek(candidate_hash).is_none() &&
+				!currently_checking_set.contains(candidate_hash) =>
+				Some((
+					CheckPriority { block_number: *block_number, tranche: *assignment_tranche },
+					*block_tick,
+				)),
+			_ => None,
+		};
+
+		if let Some((priority, block_tick)) = launch_priority {
+			load_shedder.note_lag(state.clock.tick_now().saturating_sub(block_tick));
+
+			if load_shedder.should_defer(currently_checking_set.len(), priority) {
+				let mut action = action;
+				// Deferring the check must not delay our assignment, or other validators would
+				// see us as a no-show and trigger more checks on their side.
+				if let Action::LaunchApproval {
+					indirect_cert,
+					assignment_tranche,
+					candidate_index,
+					distribute_assignment,
+					..
+				} = &mut action
+				{
+					if *distribute_assignment {
+						metrics.on_assignment_produced(*assignment_tranche);
+						ctx.send_unbounded_message(
+							ApprovalDistributionMessage::DistributeAssignment(
+								indirect_cert.clone(),
+								*candidate_index,
+							),
+						);
+						*distribute_assignment = false;
+					}
+				}
+
+				metrics.on_approval_check_deferred();
+				load_shedder.defer(currently_checking_set.len(), priority, action);
+				continue
+			}
+		}
+
 		match action {
 			Action::ScheduleWakeup { block_hash, block_number, candidate_hash, tick } =>
 				wakeups.schedule(block_hash, block_number, candidate_hash, tick),
@@ -942,6 +1191,8 @@ async fn handle_actions<Context>(
 					state,
 					overlayed_db,
 					metrics,
+					pending_approvals,
+					currently_checking_set,
 					candidate_hash,
 					approval_request,
 				)
@@ -953,29 +1204,49 @@ async fn handle_actions<Context>(
 
 				actions_iter = next_actions.into_iter();
 			},
+			Action::IssuePendingApprovals(candidate_hashes, approval_request) => {
+				let next_actions: Vec<Action> = issue_pending_approvals(
+					ctx,
+					state,
+					overlayed_db,
+					metrics,
+					candidate_hashes,
+					approval_request,
+				)?
+				.into_iter()
+				.chain(actions_iter)
+				.collect();
+
+				actions_iter = next_actions.into_iter();
+			},
 			Action::LaunchApproval {
 				candidate_hash,
 				indirect_cert,
 				assignment_tranche,
 				relay_block_hash,
+				block_number: _,
+				block_tick: _,
 				candidate_index,
 				session,
 				candidate,
 				backing_group,
+				distribute_assignment,
 			} => {
 				// Don't launch approval work if the node is syncing.
 				if let Mode::Syncing(_) = *mode {
 					continue
 				}
 
-				metrics.on_assignment_produced(assignment_tranche);
 				let block_hash = indirect_cert.block_hash;
 				let validator_index = indirect_cert.validator;
 
-				ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeAssignment(
-					indirect_cert,
-					candidate_index,
-				));
+				if distribute_assignment {
+					metrics.on_assignment_produced(assignment_tranche);
+					ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeAssignment(
+						indirect_cert,
+						candidate_index,
+					));
+				}
 
 				match approvals_cache.get(&candidate_hash) {
 					Some(ApprovalOutcome::Approved) => {
@@ -1029,6 +1300,8 @@ async fn handle_actions<Context>(
 		}
 	}
 
+	metrics.on_deferred_approval_checks(load_shedder.len());
+
 	Ok(conclude)
 }
 
@@ -1089,7 +1362,7 @@ fn distribution_messages_for_activation(
 								i as _,
 							));
 						},
-						(Some(assignment), Some(approval_sig)) => {
+						(Some(assignment), Some(our_approval)) => {
 							messages.push(ApprovalDistributionMessage::DistributeAssignment(
 								IndirectAssignmentCert {
 									block_hash,
@@ -1099,14 +1372,32 @@ fn distribution_messages_for_activation(
 								i as _,
 							));
 
-							messages.push(ApprovalDistributionMessage::DistributeApproval(
-								IndirectSignedApprovalVote {
-									block_hash,
-									candidate_index: i as _,
-									validator: assignment.validator_index(),
-									signature: approval_sig,
-								},
-							))
+							match our_approval.signed_candidates_indices {
+								None =>
+									messages.push(ApprovalDistributionMessage::DistributeApproval(
+										IndirectSignedApprovalVote {
+											block_hash,
+											candidate_index: i as _,
+											validator: assignment.validator_index(),
+											signature: our_approval.signature,
+										},
+									)),
+								// A coalesced approval is only sent once, after the assignments
+								// of all candidates it covers.
+								Some(candidate_indices)
+									if candidate_indices.last_one() == Some(i) =>
+									messages.push(
+										ApprovalDistributionMessage::DistributeCoalescedApproval(
+											IndirectSignedCoalescedApprovalVote {
+												block_hash,
+												candidate_indices,
+												validator: assignment.validator_index(),
+												signature: our_approval.signature,
+											},
+										),
+									),
+								Some(_) => {},
+							}
 						},
 					}
 				},
@@ -1219,6 +1510,18 @@ async fn handle_from_overseer<Context>(
 					let _ = res.send(r);
 				})?
 				.0,
+			ApprovalVotingMessage::CheckAndImportApprovals(a, res) =>
+				check_and_import_approvals(state, db, metrics, a, |r| {
+					let _ = res.send(r);
+				})?
+				.0,
+			ApprovalVotingMessage::CheckAndImportCoalescedApproval(a, res) => {
+				let params = state.approval_voting_params(ctx, a.block_hash).await;
+				check_and_import_coalesced_approval(state, db, metrics, &params, a, |r| {
+					let _ = res.send(r);
+				})?
+				.0
+			},
 			ApprovalVotingMessage::ApprovedAncestor(target, lower_bound, res) => {
 				match handle_approved_ancestor(ctx, db, target, lower_bound, wakeups).await {
 					Ok(v) => {
@@ -1252,7 +1555,7 @@ async fn get_approval_signatures_for_candidate<Context>(
 	ctx: &mut Context,
 	db: &OverlayedBackend<'_, impl Backend>,
 	candidate_hash: CandidateHash,
-	tx: oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+	tx: oneshot::Sender<HashMap<ValidatorIndex, (ValidDisputeStatementKind, ValidatorSignature)>>,
 ) -> SubsystemResult<()> {
 	let send_votes = |votes| {
 		if let Err(_) = tx.send(votes) {
@@ -1278,6 +1581,8 @@ async fn get_approval_signatures_for_candidate<Context>(
 	let relay_hashes = entry.block_assignments.keys();
 
 	let mut candidate_indices = HashSet::new();
+	// The candidates of every block, for resolving the candidates covered by coalesced approvals.
+	let mut block_candidates = HashMap::new();
 	// Retrieve `CoreIndices`/`CandidateIndices` as required by approval-distribution:
 	for hash in relay_hashes {
 		let entry = match db.load_block_entry(hash)? {
@@ -1298,6 +1603,8 @@ async fn get_approval_signatures_for_candidate<Context>(
 				break
 			}
 		}
+		block_candidates
+			.insert(*hash, entry.candidates().iter().map(|(_, h)| *h).collect::<Vec<_>>());
 	}
 
 	let mut sender = ctx.sender().clone();
@@ -1321,7 +1628,28 @@ async fn get_approval_signatures_for_candidate<Context>(
 				target: LOG_TARGET,
 				"Request for approval signatures got cancelled by `approval-distribution`."
 			),
-			Some(Ok(votes)) => send_votes(votes),
+			Some(Ok(votes)) => {
+				let votes = votes
+					.into_iter()
+					.filter_map(|(validator, (block_hash, indices, signature))| {
+						let kind = match indices {
+							None => ValidDisputeStatementKind::ApprovalChecking,
+							Some(indices) => {
+								let candidates = block_candidates.get(&block_hash)?;
+								let candidate_hashes = indices
+									.iter_ones()
+									.map(|i| candidates.get(i).copied())
+									.collect::<Option<Vec<_>>>()?;
+								ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(
+									candidate_hashes,
+								)
+							},
+						};
+						Some((validator, (kind, signature)))
+					})
+					.collect();
+				send_votes(votes)
+			},
 		}
 	};
 
@@ -1815,105 +2143,294 @@ fn check_and_import_approval<T>(
 	approval: IndirectSignedApprovalVote,
 	with_response: impl FnOnce(ApprovalCheckResult) -> T,
 ) -> SubsystemResult<(Vec<Action>, T)> {
-	macro_rules! respond_early {
+	check_and_import_approvals(state, db, metrics, vec![approval], |mut results| {
+		with_response(results.pop().expect("one result is produced per approval; qed"))
+	})
+}
+
+// The key and payload an approval vote is expected to be signed with.
+struct ApprovalSigner<'a> {
+	pubkey: &'a ValidatorId,
+	candidate_hash: CandidateHash,
+	session: SessionIndex,
+}
+
+fn approval_signer<'a>(
+	state: &'a State,
+	db: &OverlayedBackend<'_, impl Backend>,
+	approval: &IndirectSignedApprovalVote,
+) -> SubsystemResult<Result<ApprovalSigner<'a>, ApprovalCheckError>> {
+	let block_entry = match db.load_block_entry(&approval.block_hash)? {
+		Some(b) => b,
+		None => return Ok(Err(ApprovalCheckError::UnknownBlock(approval.block_hash))),
+	};
+
+	let session_info = match state.session_info(block_entry.session()) {
+		Some(s) => s,
+		None => return Ok(Err(ApprovalCheckError::UnknownSessionIndex(block_entry.session()))),
+	};
+
+	let candidate_hash = match block_entry.candidate(approval.candidate_index as usize) {
+		Some((_, h)) => *h,
+		None => return Ok(Err(ApprovalCheckError::InvalidCandidateIndex(approval.candidate_index))),
+	};
+
+	let pubkey = match session_info.validators.get(approval.validator) {
+		Some(k) => k,
+		None => return Ok(Err(ApprovalCheckError::InvalidValidatorIndex(approval.validator))),
+	};
+
+	Ok(Ok(ApprovalSigner { pubkey, candidate_hash, session: block_entry.session() }))
+}
+
+// Check a batch of approvals and import the ones which are valid, in order.
+//
+// The signatures of the batch are verified together, falling back to individual checks only
+// if the batch contains an invalid signature. The response is given before the heavy part of
+// importing the approvals.
+fn check_and_import_approvals<T>(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	approvals: Vec<IndirectSignedApprovalVote>,
+	with_response: impl FnOnce(Vec<ApprovalCheckResult>) -> T,
+) -> SubsystemResult<(Vec<Action>, T)> {
+	let mut results: Vec<Option<ApprovalCheckResult>> = vec![None; approvals.len()];
+	let mut signers = Vec::with_capacity(approvals.len());
+
+	for (i, approval) in approvals.iter().enumerate() {
+		match approval_signer(state, db, approval)? {
+			Ok(signer) => signers.push((i, signer)),
+			Err(e) => results[i] = Some(ApprovalCheckResult::Bad(e)),
+		}
+	}
+
+	// Signature checks:
+	let checks = signers
+		.iter()
+		.map(|(i, signer)| batch_verification::SignatureCheck {
+			pubkey: signer.pubkey,
+			payload: ApprovalVote(signer.candidate_hash).signing_payload(signer.session),
+			signature: &approvals[*i].signature,
+		})
+		.collect::<Vec<_>>();
+
+	let outcome = {
+		let _timer = metrics.time_verify_approval_signatures();
+		batch_verification::verify_signatures(&checks)
+	};
+	metrics.on_approval_signatures_verified(checks.len(), outcome.fell_back);
+
+	let mut checked = Vec::with_capacity(signers.len());
+	for ((i, signer), valid) in signers.into_iter().zip(outcome.valid) {
+		let approval = &approvals[i];
+
+		if !valid {
+			results[i] = Some(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
+				approval.validator,
+			)));
+			continue
+		}
+
+		let candidate_entry = match db.load_candidate_entry(&signer.candidate_hash)? {
+			Some(c) => c,
+			None => {
+				results[i] = Some(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
+					approval.candidate_index,
+					signer.candidate_hash,
+				)));
+				continue
+			},
+		};
+
+		// Don't accept approvals until assignment.
+		match candidate_entry.approval_entry(&approval.block_hash) {
+			None => {
+				results[i] = Some(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
+					approval.block_hash,
+					signer.candidate_hash,
+				)));
+				continue
+			},
+			Some(e) if !e.is_assigned(approval.validator) => {
+				results[i] = Some(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
+					approval.validator,
+				)));
+				continue
+			},
+			_ => {},
+		}
+
+		results[i] = Some(ApprovalCheckResult::Accepted);
+		checked.push((i, signer));
+	}
+
+	// importing the approvals can be heavy as it may trigger acceptance for a series of blocks.
+	let t = with_response(
+		results
+			.into_iter()
+			.map(|r| r.expect("every approval is given a result above; qed"))
+			.collect(),
+	);
+
+	let mut actions = Vec::new();
+	for (i, signer) in checked {
+		let approval = &approvals[i];
+
+		// The entries are loaded again, as importing an earlier approval of the batch
+		// may have altered them.
+		let block_entry = match db.load_block_entry(&approval.block_hash)? {
+			Some(b) => b,
+			None => continue,
+		};
+		let candidate_entry = match db.load_candidate_entry(&signer.candidate_hash)? {
+			Some(c) => c,
+			None => continue,
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			validator_index = approval.validator.0,
+			validator = ?signer.pubkey,
+			candidate_hash = ?signer.candidate_hash,
+			para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+			"Importing approval vote",
+		);
+
+		actions.extend(advance_approval_state(
+			state,
+			db,
+			&metrics,
+			block_entry,
+			signer.candidate_hash,
+			candidate_entry,
+			ApprovalStateTransition::RemoteApproval(approval.validator),
+		));
+	}
+
+	Ok((actions, t))
+}
+
+// Check an approval vote covering several candidates of a block and import it for each of
+// them, if valid.
+//
+// Such votes are only accepted if the runtime enables coalescing at the block, and only if the
+// validator is assigned to every covered candidate.
+fn check_and_import_coalesced_approval<T>(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	params: &ApprovalVotingParams,
+	approval: IndirectSignedCoalescedApprovalVote,
+	with_response: impl FnOnce(ApprovalCheckResult) -> T,
+) -> SubsystemResult<(Vec<Action>, T)> {
+	macro_rules! reject {
 		($e: expr) => {{
-			let t = with_response($e);
+			let t = with_response(ApprovalCheckResult::Bad($e));
 			return Ok((Vec::new(), t))
 		}};
 	}
 
+	let count = approval.candidate_indices.count_ones();
+	if !params.coalescing_enabled() ||
+		count == 0 ||
+		count > params.max_approval_coalesce_count as usize
+	{
+		reject!(ApprovalCheckError::CoalescingNotAllowed(approval.block_hash, count))
+	}
+
 	let block_entry = match db.load_block_entry(&approval.block_hash)? {
 		Some(b) => b,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(
-				approval.block_hash
-			),))
-		},
+		None => reject!(ApprovalCheckError::UnknownBlock(approval.block_hash)),
 	};
 
 	let session_info = match state.session_info(block_entry.session()) {
 		Some(s) => s,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownSessionIndex(
-				block_entry.session()
-			),))
-		},
+		None => reject!(ApprovalCheckError::UnknownSessionIndex(block_entry.session())),
 	};
 
-	let approved_candidate_hash = match block_entry.candidate(approval.candidate_index as usize) {
-		Some((_, h)) => *h,
-		None => respond_early!(ApprovalCheckResult::Bad(
-			ApprovalCheckError::InvalidCandidateIndex(approval.candidate_index),
-		)),
-	};
+	let mut candidate_hashes = Vec::with_capacity(count);
+	for candidate_index in approval.candidate_indices() {
+		match block_entry.candidate(candidate_index as usize) {
+			Some((_, h)) => candidate_hashes.push(*h),
+			None => reject!(ApprovalCheckError::InvalidCandidateIndex(candidate_index)),
+		}
+	}
 
 	let pubkey = match session_info.validators.get(approval.validator) {
 		Some(k) => k,
-		None => respond_early!(ApprovalCheckResult::Bad(
-			ApprovalCheckError::InvalidValidatorIndex(approval.validator),
-		)),
+		None => reject!(ApprovalCheckError::InvalidValidatorIndex(approval.validator)),
 	};
 
 	// Signature check:
-	match DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking).check_signature(
-		&pubkey,
-		approved_candidate_hash,
-		block_entry.session(),
-		&approval.signature,
-	) {
-		Err(_) => respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
-			approval.validator
-		),)),
-		Ok(()) => {},
+	let check = batch_verification::SignatureCheck {
+		pubkey,
+		payload: ApprovalVoteMultipleCandidates(&candidate_hashes)
+			.signing_payload(block_entry.session()),
+		signature: &approval.signature,
 	};
-
-	let candidate_entry = match db.load_candidate_entry(&approved_candidate_hash)? {
-		Some(c) => c,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
-				approval.candidate_index,
-				approved_candidate_hash
-			),))
-		},
+	let outcome = {
+		let _timer = metrics.time_verify_approval_signatures();
+		batch_verification::verify_signatures(&[check])
 	};
+	metrics.on_approval_signatures_verified(1, outcome.fell_back);
 
-	// Don't accept approvals until assignment.
-	match candidate_entry.approval_entry(&approval.block_hash) {
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
-				approval.block_hash,
-				approved_candidate_hash
-			),))
-		},
-		Some(e) if !e.is_assigned(approval.validator) => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
-				approval.validator
-			),))
-		},
-		_ => {},
+	if outcome.valid != [true] {
+		reject!(ApprovalCheckError::InvalidSignature(approval.validator))
 	}
 
-	// importing the approval can be heavy as it may trigger acceptance for a series of blocks.
+	// Don't accept approvals until assignment, for any of the covered candidates.
+	for (candidate_index, candidate_hash) in
+		approval.candidate_indices().zip(candidate_hashes.iter())
+	{
+		let candidate_entry = match db.load_candidate_entry(candidate_hash)? {
+			Some(c) => c,
+			None => reject!(ApprovalCheckError::InvalidCandidate(candidate_index, *candidate_hash)),
+		};
+
+		match candidate_entry.approval_entry(&approval.block_hash) {
+			None => reject!(ApprovalCheckError::Internal(approval.block_hash, *candidate_hash)),
+			Some(e) if !e.is_assigned(approval.validator) =>
+				reject!(ApprovalCheckError::NoAssignment(approval.validator)),
+			_ => {},
+		}
+	}
+
+	// importing the approvals can be heavy as it may trigger acceptance for a series of blocks.
 	let t = with_response(ApprovalCheckResult::Accepted);
 
-	gum::trace!(
-		target: LOG_TARGET,
-		validator_index = approval.validator.0,
-		validator = ?pubkey,
-		candidate_hash = ?approved_candidate_hash,
-		para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
-		"Importing approval vote",
-	);
+	let mut actions = Vec::new();
+	for candidate_hash in candidate_hashes {
+		// The entries are loaded again, as importing the approval of an earlier candidate
+		// may have altered them.
+		let block_entry = match db.load_block_entry(&approval.block_hash)? {
+			Some(b) => b,
+			None => break,
+		};
+		let candidate_entry = match db.load_candidate_entry(&candidate_hash)? {
+			Some(c) => c,
+			None => continue,
+		};
 
-	let actions = advance_approval_state(
-		state,
-		db,
-		&metrics,
-		block_entry,
-		approved_candidate_hash,
-		candidate_entry,
-		ApprovalStateTransition::RemoteApproval(approval.validator),
-	);
+		gum::trace!(
+			target: LOG_TARGET,
+			validator_index = approval.validator.0,
+			validator = ?pubkey,
+			?candidate_hash,
+			para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+			"Importing coalesced approval vote",
+		);
+
+		actions.extend(advance_approval_state(
+			state,
+			db,
+			&metrics,
+			block_entry,
+			candidate_hash,
+			candidate_entry,
+			ApprovalStateTransition::RemoteApproval(approval.validator),
+		));
+	}
 
 	Ok((actions, t))
 }
@@ -1921,7 +2438,7 @@ fn check_and_import_approval<T>(
 #[derive(Debug)]
 enum ApprovalStateTransition {
 	RemoteApproval(ValidatorIndex),
-	LocalApproval(ValidatorIndex, ValidatorSignature),
+	LocalApproval(ValidatorIndex, OurApproval),
 	WakeupProcessed,
 }
 
@@ -2051,8 +2568,8 @@ fn advance_approval_state(
 			approval_entry.mark_approved();
 		}
 
-		if let ApprovalStateTransition::LocalApproval(_, ref sig) = transition {
-			approval_entry.import_approval_sig(sig.clone());
+		if let ApprovalStateTransition::LocalApproval(_, ref our_approval) = transition {
+			approval_entry.import_approval_sig(our_approval.clone());
 		}
 
 		actions.extend(schedule_wakeup_action(
@@ -2236,10 +2753,13 @@ fn process_wakeup(
 				indirect_cert,
 				assignment_tranche: tranche,
 				relay_block_hash: relay_block,
+				block_number: block_entry.block_number(),
+				block_tick,
 				candidate_index: i as _,
 				session: block_entry.session(),
 				candidate: candidate_receipt,
 				backing_group,
+				distribute_assignment: true,
 			});
 		}
 	}
@@ -2454,15 +2974,22 @@ async fn launch_approval<Context>(
 
 // Issue and import a local approval vote. Should only be invoked after approval checks
 // have been done.
+//
+// If the runtime enables coalescing of approvals, the approval is held back for a short while
+// and issued together with the approvals of other candidates in the same block.
 #[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
 async fn issue_approval<Context>(
 	ctx: &mut Context,
 	state: &mut State,
 	db: &mut OverlayedBackend<'_, impl Backend>,
 	metrics: &Metrics,
+	pending_approvals: &mut PendingApprovals,
+	currently_checking_set: &CurrentlyCheckingSet,
 	candidate_hash: CandidateHash,
-	ApprovalVoteRequest { validator_index, block_hash }: ApprovalVoteRequest,
+	approval_request: ApprovalVoteRequest,
 ) -> SubsystemResult<Vec<Action>> {
+	let ApprovalVoteRequest { validator_index, block_hash } = approval_request;
+
 	let block_entry = match db.load_block_entry(&block_hash)? {
 		Some(b) => b,
 		None => {
@@ -2472,64 +2999,119 @@ async fn issue_approval<Context>(
 		},
 	};
 
-	let candidate_index = match block_entry.candidates().iter().position(|e| e.1 == candidate_hash)
-	{
+	// Whether we are able to sign at all is checked upfront, so that a missing key is
+	// reported right away and not only once the approval is taken from the pending set.
+	let key = match signing_key(state, &block_entry, validator_index) {
+		Some(key) => key,
 		None => {
-			gum::warn!(
-				target: LOG_TARGET,
-				"Candidate hash {} is not present in the block entry's candidates for relay block {}",
-				candidate_hash,
-				block_entry.parent_hash(),
-			);
-
 			metrics.on_approval_error();
 			return Ok(Vec::new())
 		},
-		Some(idx) => idx,
 	};
 
-	let session_info = match state.session_info(block_entry.session()) {
-		Some(s) => s,
+	let params = state.approval_voting_params(ctx, block_hash).await;
+	if !params.coalescing_enabled() {
+		return sign_and_import_approvals(
+			ctx,
+			state,
+			db,
+			metrics,
+			&key,
+			vec![candidate_hash],
+			approval_request,
+		)
+	}
+
+	let tick_now = state.clock.tick_now();
+	let ready = pending_approvals
+		.insert(
+			block_hash,
+			validator_index,
+			candidate_hash,
+			params.max_approval_coalesce_count,
+			tick_now,
+		)
+		.or_else(|| {
+			// Nothing else to wait for, unless more candidates of the block are being checked.
+			if currently_checking_set.is_checking_block(&block_hash) {
+				None
+			} else {
+				pending_approvals.take(block_hash, validator_index)
+			}
+		});
+
+	match ready {
+		Some(candidate_hashes) => sign_and_import_approvals(
+			ctx,
+			state,
+			db,
+			metrics,
+			&key,
+			candidate_hashes,
+			approval_request,
+		),
 		None => {
-			gum::warn!(
+			gum::trace!(
 				target: LOG_TARGET,
-				"Missing session info for live block {} in session {}",
-				block_hash,
-				block_entry.session(),
+				?candidate_hash,
+				?block_hash,
+				validator_index = validator_index.0,
+				"Holding back approval vote for coalescing",
 			);
 
-			metrics.on_approval_error();
+			Ok(Vec::new())
+		},
+	}
+}
+
+// Issue approvals which have been held back for coalescing.
+#[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
+fn issue_pending_approvals<Context>(
+	ctx: &mut Context,
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	candidate_hashes: Vec<CandidateHash>,
+	approval_request: ApprovalVoteRequest,
+) -> SubsystemResult<Vec<Action>> {
+	let block_entry = match db.load_block_entry(&approval_request.block_hash)? {
+		Some(b) => b,
+		None => {
+			// not a cause for alarm - just lost a race with pruning, most likely.
+			metrics.on_approval_stale();
 			return Ok(Vec::new())
 		},
 	};
 
-	let candidate_hash = match block_entry.candidate(candidate_index as usize) {
-		Some((_, h)) => *h,
+	let key = match signing_key(state, &block_entry, approval_request.validator_index) {
+		Some(key) => key,
 		None => {
-			gum::warn!(
-				target: LOG_TARGET,
-				"Received malformed request to approve out-of-bounds candidate index {} included at block {:?}",
-				candidate_index,
-				block_hash,
-			);
-
 			metrics.on_approval_error();
 			return Ok(Vec::new())
 		},
 	};
 
-	let candidate_entry = match db.load_candidate_entry(&candidate_hash)? {
-		Some(c) => c,
+	sign_and_import_approvals(ctx, state, db, metrics, &key, candidate_hashes, approval_request)
+}
+
+// Fetch our key for signing approvals in the session of the given block.
+fn signing_key(
+	state: &State,
+	block_entry: &BlockEntry,
+	validator_index: ValidatorIndex,
+) -> Option<ValidatorPair> {
+	let session = block_entry.session();
+	let session_info = match state.session_info(session) {
+		Some(s) => s,
 		None => {
 			gum::warn!(
 				target: LOG_TARGET,
-				"Missing entry for candidate index {} included at block {:?}",
-				candidate_index,
-				block_hash,
+				"Missing session info for live block {} in session {}",
+				block_entry.block_hash(),
+				session,
 			);
 
-			metrics.on_approval_error();
-			return Ok(Vec::new())
+			return None
 		},
 	};
 
@@ -2540,75 +3122,166 @@ async fn issue_approval<Context>(
 				target: LOG_TARGET,
 				"Validator index {} out of bounds in session {}",
 				validator_index.0,
-				block_entry.session(),
+				session,
 			);
 
-			metrics.on_approval_error();
-			return Ok(Vec::new())
+			return None
 		},
 	};
 
-	let session = block_entry.session();
-	let sig = match sign_approval(&state.keystore, &validator_pubkey, candidate_hash, session) {
-		Some(sig) => sig,
-		None => {
-			gum::warn!(
-				target: LOG_TARGET,
-				validator_index = ?validator_index,
-				session,
-				"Could not issue approval signature. Assignment key present but not validator key?",
-			);
+	let key = state.keystore.key_pair::<ValidatorPair>(validator_pubkey).ok().flatten();
+	if key.is_none() {
+		gum::warn!(
+			target: LOG_TARGET,
+			validator_index = ?validator_index,
+			session,
+			"Could not issue approval signature. Assignment key present but not validator key?",
+		);
+	}
 
-			metrics.on_approval_error();
+	key
+}
+
+// Sign a single approval vote for all of the given candidates of a block, import it and
+// dispatch it to approval distribution.
+//
+// A vote for a single candidate uses the original approval vote format, which doesn't require
+// coalescing to be enabled.
+#[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
+fn sign_and_import_approvals<Context>(
+	ctx: &mut Context,
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	key: &ValidatorPair,
+	candidate_hashes: Vec<CandidateHash>,
+	ApprovalVoteRequest { validator_index, block_hash }: ApprovalVoteRequest,
+) -> SubsystemResult<Vec<Action>> {
+	let block_entry = match db.load_block_entry(&block_hash)? {
+		Some(b) => b,
+		None => {
+			// not a cause for alarm - just lost a race with pruning, most likely.
+			metrics.on_approval_stale();
 			return Ok(Vec::new())
 		},
 	};
+	let session = block_entry.session();
 
-	gum::trace!(
-		target: LOG_TARGET,
-		?candidate_hash,
-		?block_hash,
-		validator_index = validator_index.0,
-		"Issuing approval vote",
-	);
-
-	let actions = advance_approval_state(
-		state,
-		db,
-		metrics,
-		block_entry,
-		candidate_hash,
-		candidate_entry,
-		ApprovalStateTransition::LocalApproval(validator_index as _, sig.clone()),
-	);
+	let mut candidates = Vec::with_capacity(candidate_hashes.len());
+	for candidate_hash in candidate_hashes {
+		match block_entry.candidates().iter().position(|e| e.1 == candidate_hash) {
+			None => {
+				gum::warn!(
+					target: LOG_TARGET,
+					"Candidate hash {} is not present in the block entry's candidates for relay block {}",
+					candidate_hash,
+					block_entry.parent_hash(),
+				);
 
-	metrics.on_approval_produced();
+				metrics.on_approval_error();
+			},
+			Some(idx) => candidates.push((idx as CandidateIndex, candidate_hash)),
+		}
+	}
+	// The signature of a coalesced vote is over the candidate hashes ordered by
+	// candidate index.
+	candidates.sort_by_key(|(idx, _)| *idx);
+
+	let (signature, signed_candidates_indices) = match &candidates[..] {
+		[] => return Ok(Vec::new()),
+		[(_, candidate_hash)] => {
+			let payload = ApprovalVote(*candidate_hash).signing_payload(session);
+			(key.sign(&payload[..]), None)
+		},
+		candidates => {
+			let candidate_hashes = candidates.iter().map(|(_, h)| *h).collect::<Vec<_>>();
+			let payload =
+				ApprovalVoteMultipleCandidates(&candidate_hashes).signing_payload(session);
+
+			let mut indices = CandidateBitfield::repeat(false, block_entry.candidates().len());
+			for (idx, _) in candidates {
+				indices.set(*idx as usize, true);
+			}
 
-	// dispatch to approval distribution.
-	ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeApproval(
-		IndirectSignedApprovalVote {
-			block_hash,
-			candidate_index: candidate_index as _,
-			validator: validator_index,
-			signature: sig,
+			(key.sign(&payload[..]), Some(indices))
 		},
-	));
+	};
 
-	Ok(actions)
-}
+	let our_approval = OurApproval {
+		signature: signature.clone(),
+		signed_candidates_indices: signed_candidates_indices.clone(),
+	};
 
-// Sign an approval vote. Fails if the key isn't present in the store.
-fn sign_approval(
-	keystore: &LocalKeystore,
-	public: &ValidatorId,
-	candidate_hash: CandidateHash,
-	session_index: SessionIndex,
-) -> Option<ValidatorSignature> {
-	let key = keystore.key_pair::<ValidatorPair>(public).ok().flatten()?;
+	let mut actions = Vec::new();
+	for (candidate_index, candidate_hash) in candidates.iter().copied() {
+		// The block entry is loaded again, as importing the approval of an earlier candidate
+		// may have altered it.
+		let block_entry = match db.load_block_entry(&block_hash)? {
+			Some(b) => b,
+			None => break,
+		};
 
-	let payload = ApprovalVote(candidate_hash).signing_payload(session_index);
+		let candidate_entry = match db.load_candidate_entry(&candidate_hash)? {
+			Some(c) => c,
+			None => {
+				gum::warn!(
+					target: LOG_TARGET,
+					"Missing entry for candidate index {} included at block {:?}",
+					candidate_index,
+					block_hash,
+				);
 
-	Some(key.sign(&payload[..]))
+				metrics.on_approval_error();
+				continue
+			},
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?candidate_hash,
+			?block_hash,
+			validator_index = validator_index.0,
+			coalesced = signed_candidates_indices.is_some(),
+			"Issuing approval vote",
+		);
+
+		actions.extend(advance_approval_state(
+			state,
+			db,
+			metrics,
+			block_entry,
+			candidate_hash,
+			candidate_entry,
+			ApprovalStateTransition::LocalApproval(validator_index as _, our_approval.clone()),
+		));
+
+		metrics.on_approval_produced();
+	}
+
+	metrics.on_approvals_coalesced(candidates.len());
+
+	// dispatch to approval distribution.
+	match signed_candidates_indices {
+		None => ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeApproval(
+			IndirectSignedApprovalVote {
+				block_hash,
+				candidate_index: candidates[0].0,
+				validator: validator_index,
+				signature,
+			},
+		)),
+		Some(candidate_indices) =>
+			ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeCoalescedApproval(
+				IndirectSignedCoalescedApprovalVote {
+					block_hash,
+					candidate_indices,
+					validator: validator_index,
+					signature,
+				},
+			)),
+	}
+
+	Ok(actions)
 }
 
 /// Send `IssueLocalStatement` to dispute-coordinator.
@@ -0,0 +1,267 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Load-shedding of approval checks.
+//!
+//! Launching every approval check as soon as our assignment triggers makes a node which is
+//! already behind fall further behind, as checks for new blocks compete with checks for the
+//! old, unapproved blocks finality is waiting on.
+//!
+//! Once the number of checks in flight reaches the limit, further launches are deferred and
+//! later launched in priority order: checks for older blocks first, and within a block, checks
+//! for lower tranches first. The limit is lowered while the blocks we check lag behind the
+//! current time, and while other subsystems keep the PVF host busy.
+//!
+//! Deferred checks are never dropped, unless the block they are for gets finalized. Our
+//! assignments for them are still broadcast right away.
+
+use polkadot_node_primitives::approval::DelayTranche;
+use polkadot_node_subsystem_util::pvf_queue_depth::PvfQueueDepth;
+use polkadot_primitives::BlockNumber;
+use std::collections::BTreeMap;
+
+use crate::{time::Tick, LOG_TARGET};
+
+/// The maximum number of approval checks in flight before further checks are deferred.
+pub(crate) const MAX_IN_FLIGHT_CHECKS: usize = 16;
+
+/// The maximum number of approval checks in flight while lagging behind or while the PVF host
+/// is busy.
+pub(crate) const MAX_IN_FLIGHT_CHECKS_UNDER_LOAD: usize = 8;
+
+/// The age of a block, in ticks, starting from which we consider ourselves lagging behind
+/// when checking candidates of the block. This is about 8 relay-chain blocks.
+pub(crate) const APPROVAL_LAG_THRESHOLD_TICKS: Tick = 96;
+
+/// The number of pending PVF executions not requested by approval checks in flight, starting
+/// from which we consider the PVF host busy.
+pub(crate) const PVF_QUEUE_DEPTH_THRESHOLD: usize = 4;
+
+/// The priority of an approval check. Lower is more urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct CheckPriority {
+	/// The number of the block the candidate is checked in.
+	pub block_number: BlockNumber,
+	/// The tranche of our assignment.
+	pub tranche: DelayTranche,
+}
+
+/// Approval checks deferred because of load.
+pub(crate) struct LoadShedder<T> {
+	deferred: BTreeMap<(CheckPriority, u64), T>,
+	next_sequence: u64,
+	lagging: bool,
+	pvf_queue_depth: PvfQueueDepth,
+}
+
+impl<T> Default for LoadShedder<T> {
+	fn default() -> Self {
+		Self::new(PvfQueueDepth::default())
+	}
+}
+
+impl<T> LoadShedder<T> {
+	/// Create a load shedder taking the given PVF queue depth into account.
+	pub(crate) fn new(pvf_queue_depth: PvfQueueDepth) -> Self {
+		Self { deferred: BTreeMap::new(), next_sequence: 0, lagging: false, pvf_queue_depth }
+	}
+
+	/// Note the age of the block of a check we are about to launch.
+	pub(crate) fn note_lag(&mut self, lag: Tick) {
+		let lagging = lag >= APPROVAL_LAG_THRESHOLD_TICKS;
+		if lagging != self.lagging {
+			gum::debug!(target: LOG_TARGET, lag, lagging, "Approval checking lag changed");
+		}
+
+		self.lagging = lagging;
+	}
+
+	/// Whether other subsystems keep the PVF host busy, given the number of approval checks in
+	/// flight.
+	///
+	/// Every check in flight accounts for at most one pending PVF execution.
+	fn pvf_busy(&self, in_flight: usize) -> bool {
+		self.pvf_queue_depth.get().saturating_sub(in_flight) >= PVF_QUEUE_DEPTH_THRESHOLD
+	}
+
+	/// The number of checks which may be in flight at the same time.
+	pub(crate) fn capacity(&self, in_flight: usize) -> usize {
+		if self.lagging || self.pvf_busy(in_flight) {
+			MAX_IN_FLIGHT_CHECKS_UNDER_LOAD
+		} else {
+			MAX_IN_FLIGHT_CHECKS
+		}
+	}
+
+	/// Whether a check with the given priority needs to be deferred.
+	///
+	/// Checks are deferred if there is no capacity left, or if more urgent checks are already
+	/// waiting.
+	pub(crate) fn should_defer(&self, in_flight: usize, priority: CheckPriority) -> bool {
+		in_flight >= self.capacity(in_flight) ||
+			self.deferred.keys().next().map_or(false, |(head, _)| priority > *head)
+	}
+
+	/// Defer a check, given the number of checks in flight.
+	pub(crate) fn defer(&mut self, in_flight: usize, priority: CheckPriority, check: T) {
+		if self.deferred.is_empty() {
+			gum::info!(
+				target: LOG_TARGET,
+				capacity = self.capacity(in_flight),
+				lagging = self.lagging,
+				pvf_queue_depth = self.pvf_queue_depth.get(),
+				"Approval checking is overloaded, deferring new checks",
+			);
+		}
+
+		gum::debug!(
+			target: LOG_TARGET,
+			block_number = priority.block_number,
+			tranche = priority.tranche,
+			deferred = self.deferred.len() + 1,
+			"Deferring approval check",
+		);
+
+		self.deferred.insert((priority, self.next_sequence), check);
+		self.next_sequence += 1;
+	}
+
+	/// Take the most urgent deferred check, if there is capacity to launch it.
+	pub(crate) fn take_next(&mut self, in_flight: usize) -> Option<T> {
+		if in_flight >= self.capacity(in_flight) {
+			return None
+		}
+
+		let key = *self.deferred.keys().next()?;
+		let check = self.deferred.remove(&key);
+
+		if self.deferred.is_empty() {
+			gum::info!(target: LOG_TARGET, "All deferred approval checks launched");
+		}
+
+		check
+	}
+
+	/// Drop deferred checks for finalized blocks, which don't need approval anymore.
+	pub(crate) fn prune_finalized(&mut self, finalized: BlockNumber) {
+		let before = self.deferred.len();
+		self.deferred.retain(|(priority, _), _| priority.block_number > finalized);
+
+		let pruned = before - self.deferred.len();
+		if pruned > 0 {
+			gum::debug!(
+				target: LOG_TARGET,
+				pruned,
+				finalized,
+				"Dropped deferred approval checks of finalized blocks",
+			);
+		}
+	}
+
+	/// The number of deferred checks.
+	pub(crate) fn len(&self) -> usize {
+		self.deferred.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn priority(block_number: BlockNumber, tranche: DelayTranche) -> CheckPriority {
+		CheckPriority { block_number, tranche }
+	}
+
+	#[test]
+	fn defers_only_when_at_capacity() {
+		let shedder = LoadShedder::<()>::default();
+
+		assert!(!shedder.should_defer(MAX_IN_FLIGHT_CHECKS - 1, priority(1, 0)));
+		assert!(shedder.should_defer(MAX_IN_FLIGHT_CHECKS, priority(1, 0)));
+	}
+
+	#[test]
+	fn lagging_lowers_capacity() {
+		let mut shedder = LoadShedder::<()>::default();
+
+		shedder.note_lag(APPROVAL_LAG_THRESHOLD_TICKS);
+		assert_eq!(shedder.capacity(0), MAX_IN_FLIGHT_CHECKS_UNDER_LOAD);
+		assert!(shedder.should_defer(MAX_IN_FLIGHT_CHECKS_UNDER_LOAD, priority(1, 0)));
+
+		shedder.note_lag(0);
+		assert_eq!(shedder.capacity(0), MAX_IN_FLIGHT_CHECKS);
+	}
+
+	#[test]
+	fn busy_pvf_host_lowers_capacity() {
+		let pvf_queue_depth = PvfQueueDepth::default();
+		let shedder = LoadShedder::<()>::new(pvf_queue_depth.clone());
+
+		// Our own checks don't make the PVF host count as busy.
+		let ours: Vec<_> =
+			(0..MAX_IN_FLIGHT_CHECKS_UNDER_LOAD).map(|_| pvf_queue_depth.enter()).collect();
+		assert_eq!(shedder.capacity(MAX_IN_FLIGHT_CHECKS_UNDER_LOAD), MAX_IN_FLIGHT_CHECKS);
+
+		let others: Vec<_> =
+			(0..PVF_QUEUE_DEPTH_THRESHOLD).map(|_| pvf_queue_depth.enter()).collect();
+		assert_eq!(
+			shedder.capacity(MAX_IN_FLIGHT_CHECKS_UNDER_LOAD),
+			MAX_IN_FLIGHT_CHECKS_UNDER_LOAD
+		);
+		assert!(shedder.should_defer(MAX_IN_FLIGHT_CHECKS_UNDER_LOAD, priority(1, 0)));
+
+		drop(others);
+		assert_eq!(shedder.capacity(MAX_IN_FLIGHT_CHECKS_UNDER_LOAD), MAX_IN_FLIGHT_CHECKS);
+		drop(ours);
+		assert_eq!(pvf_queue_depth.get(), 0);
+	}
+
+	#[test]
+	fn launches_oldest_blocks_and_lowest_tranches_first() {
+		let mut shedder = LoadShedder::default();
+
+		shedder.defer(0, priority(5, 0), "5/0");
+		shedder.defer(0, priority(3, 2), "3/2");
+		shedder.defer(0, priority(3, 0), "3/0");
+		shedder.defer(0, priority(4, 1), "4/1");
+		assert_eq!(shedder.len(), 4);
+
+		// Less urgent than the queue head, so deferred despite the free capacity.
+		assert!(shedder.should_defer(0, priority(4, 0)));
+		// More urgent than anything deferred.
+		assert!(!shedder.should_defer(0, priority(2, 5)));
+
+		assert_eq!(shedder.take_next(MAX_IN_FLIGHT_CHECKS), None);
+
+		let order: Vec<_> = std::iter::from_fn(|| shedder.take_next(0)).collect();
+		assert_eq!(order, vec!["3/0", "3/2", "4/1", "5/0"]);
+		assert_eq!(shedder.len(), 0);
+	}
+
+	#[test]
+	fn prunes_finalized_blocks() {
+		let mut shedder = LoadShedder::default();
+
+		shedder.defer(0, priority(1, 0), 1);
+		shedder.defer(0, priority(2, 0), 2);
+		shedder.defer(0, priority(3, 0), 3);
+
+		shedder.prune_finalized(2);
+
+		assert_eq!(shedder.len(), 1);
+		assert_eq!(shedder.take_next(0), Some(3));
+	}
+}
@@ -20,7 +20,9 @@
 //! Within that context, things are plain-old-data. Within this module,
 //! data and logic are intertwined.
 
-use polkadot_node_primitives::approval::{AssignmentCert, DelayTranche, RelayVRFStory};
+use polkadot_node_primitives::approval::{
+	AssignmentCert, CandidateBitfield, DelayTranche, RelayVRFStory,
+};
 use polkadot_primitives::{
 	BlockNumber, CandidateHash, CandidateReceipt, CoreIndex, GroupIndex, Hash, SessionIndex,
 	ValidatorIndex, ValidatorSignature,
@@ -71,6 +73,41 @@ impl From<TrancheEntry> for crate::approval_db::v1::TrancheEntry {
 	}
 }
 
+/// Our local approval vote for a candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OurApproval {
+	/// The signature of the approval vote.
+	pub signature: ValidatorSignature,
+	/// The indices of all candidates of the block covered by the signature, if the vote
+	/// covers multiple candidates. `None` if the signature is over this candidate only.
+	pub signed_candidates_indices: Option<CandidateBitfield>,
+}
+
+impl OurApproval {
+	/// Create an approval vote covering a single candidate.
+	pub fn single(signature: ValidatorSignature) -> Self {
+		Self { signature, signed_candidates_indices: None }
+	}
+}
+
+impl From<crate::approval_db::v2::OurApproval> for OurApproval {
+	fn from(approval: crate::approval_db::v2::OurApproval) -> Self {
+		Self {
+			signature: approval.signature,
+			signed_candidates_indices: approval.signed_candidates_indices,
+		}
+	}
+}
+
+impl From<OurApproval> for crate::approval_db::v2::OurApproval {
+	fn from(approval: OurApproval) -> Self {
+		Self {
+			signature: approval.signature,
+			signed_candidates_indices: approval.signed_candidates_indices,
+		}
+	}
+}
+
 /// Metadata regarding approval of a particular candidate within the context of some
 /// particular block.
 #[derive(Debug, Clone, PartialEq)]
@@ -78,7 +115,7 @@ pub struct ApprovalEntry {
 	tranches: Vec<TrancheEntry>,
 	backing_group: GroupIndex,
 	our_assignment: Option<OurAssignment>,
-	our_approval_sig: Option<ValidatorSignature>,
+	our_approval_sig: Option<OurApproval>,
 	// `n_validators` bits.
 	assignments: BitVec<u8, BitOrderLsb0>,
 	approved: bool,
@@ -90,7 +127,7 @@ impl ApprovalEntry {
 		tranches: Vec<TrancheEntry>,
 		backing_group: GroupIndex,
 		our_assignment: Option<OurAssignment>,
-		our_approval_sig: Option<ValidatorSignature>,
+		our_approval_sig: Option<OurApproval>,
 		// `n_validators` bits.
 		assignments: BitVec<u8, BitOrderLsb0>,
 		approved: bool,
@@ -125,7 +162,7 @@ impl ApprovalEntry {
 	}
 
 	/// Import our local approval vote signature for this candidate.
-	pub fn import_approval_sig(&mut self, approval_sig: ValidatorSignature) {
+	pub fn import_approval_sig(&mut self, approval_sig: OurApproval) {
 		self.our_approval_sig = Some(approval_sig);
 	}
 
@@ -209,7 +246,7 @@ impl ApprovalEntry {
 	/// Get the assignment cert & approval signature.
 	///
 	/// The approval signature will only be `Some` if the assignment is too.
-	pub fn local_statements(&self) -> (Option<OurAssignment>, Option<ValidatorSignature>) {
+	pub fn local_statements(&self) -> (Option<OurAssignment>, Option<OurApproval>) {
 		let approval_sig = self.our_approval_sig.clone();
 		if let Some(our_assignment) = self.our_assignment.as_ref().filter(|a| a.triggered()) {
 			(Some(our_assignment.clone()), approval_sig)
@@ -225,7 +262,7 @@ impl From<crate::approval_db::v1::ApprovalEntry> for ApprovalEntry {
 			tranches: entry.tranches.into_iter().map(Into::into).collect(),
 			backing_group: entry.backing_group,
 			our_assignment: entry.our_assignment.map(Into::into),
-			our_approval_sig: entry.our_approval_sig.map(Into::into),
+			our_approval_sig: entry.our_approval_sig.map(OurApproval::single),
 			assignments: entry.assignments,
 			approved: entry.approved,
 		}
@@ -233,6 +270,38 @@ impl From<crate::approval_db::v1::ApprovalEntry> for ApprovalEntry {
 }
 
 impl From<ApprovalEntry> for crate::approval_db::v1::ApprovalEntry {
+	fn from(entry: ApprovalEntry) -> Self {
+		Self {
+			tranches: entry.tranches.into_iter().map(Into::into).collect(),
+			backing_group: entry.backing_group,
+			our_assignment: entry.our_assignment.map(Into::into),
+			// The v1 schema can't represent approvals covering multiple candidates, so these
+			// aren't persisted. The approval itself is still recorded in the candidate entry, we
+			// just won't be able to re-distribute our vote after a restart.
+			our_approval_sig: entry
+				.our_approval_sig
+				.filter(|approval| approval.signed_candidates_indices.is_none())
+				.map(|approval| approval.signature),
+			assignments: entry.assignments,
+			approved: entry.approved,
+		}
+	}
+}
+
+impl From<crate::approval_db::v2::ApprovalEntry> for ApprovalEntry {
+	fn from(entry: crate::approval_db::v2::ApprovalEntry) -> Self {
+		ApprovalEntry {
+			tranches: entry.tranches.into_iter().map(Into::into).collect(),
+			backing_group: entry.backing_group,
+			our_assignment: entry.our_assignment.map(Into::into),
+			our_approval_sig: entry.our_approval_sig.map(Into::into),
+			assignments: entry.assignments,
+			approved: entry.approved,
+		}
+	}
+}
+
+impl From<ApprovalEntry> for crate::approval_db::v2::ApprovalEntry {
 	fn from(entry: ApprovalEntry) -> Self {
 		Self {
 			tranches: entry.tranches.into_iter().map(Into::into).collect(),
@@ -320,6 +389,36 @@ impl From<CandidateEntry> for crate::approval_db::v1::CandidateEntry {
 	}
 }
 
+impl From<crate::approval_db::v2::CandidateEntry> for CandidateEntry {
+	fn from(entry: crate::approval_db::v2::CandidateEntry) -> Self {
+		CandidateEntry {
+			candidate: entry.candidate,
+			session: entry.session,
+			block_assignments: entry
+				.block_assignments
+				.into_iter()
+				.map(|(h, ae)| (h, ae.into()))
+				.collect(),
+			approvals: entry.approvals,
+		}
+	}
+}
+
+impl From<CandidateEntry> for crate::approval_db::v2::CandidateEntry {
+	fn from(entry: CandidateEntry) -> Self {
+		Self {
+			candidate: entry.candidate,
+			session: entry.session,
+			block_assignments: entry
+				.block_assignments
+				.into_iter()
+				.map(|(h, ae)| (h, ae.into()))
+				.collect(),
+			approvals: entry.approvals,
+		}
+	}
+}
+
 /// Metadata regarding approval of a particular block, by way of approval of the
 /// candidates contained within it.
 #[derive(Debug, Clone, PartialEq)]
@@ -25,6 +25,7 @@ use polkadot_node_primitives::{
 	AvailableData, BlockData, PoV,
 };
 use polkadot_node_subsystem::{
+	errors::RuntimeApiError,
 	messages::{
 		AllMessages, ApprovalVotingMessage, AssignmentCheckResult, AvailabilityRecoveryMessage,
 	},
@@ -53,7 +54,7 @@ use std::{
 };
 
 use super::{
-	approval_db::v1::StoredBlockRange,
+	approval_db::v2::StoredBlockRange,
 	backend::BackendWriteOp,
 	import::tests::{
 		garbage_vrf, AllowedSlots, BabeEpoch, BabeEpochConfiguration, CompatibleDigestItem, Digest,
@@ -111,7 +112,7 @@ fn make_sync_oracle(val: bool) -> (Box<dyn SyncOracle + Send>, TestSyncOracleHan
 
 #[cfg(test)]
 pub mod test_constants {
-	use crate::approval_db::v1::Config as DatabaseConfig;
+	use crate::approval_db::v2::Config as DatabaseConfig;
 	const DATA_COL: u32 = 0;
 	const SESSION_DATA_COL: u32 = 1;
 
@@ -492,6 +493,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 				col_approval_data: test_constants::TEST_CONFIG.col_approval_data,
 				slot_duration_millis: SLOT_DURATION_MILLIS,
 				col_session_data: TEST_CONFIG.col_session_data,
+				pvf_queue_depth: Default::default(),
 			},
 			Arc::new(db),
 			Arc::new(keystore),
@@ -1519,6 +1521,222 @@ fn subsystem_accepts_and_imports_approval_after_assignment() {
 	});
 }
 
+#[test]
+fn subsystem_checks_and_imports_approvals_in_batch() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+
+		let candidate_hash = {
+			let mut candidate_receipt =
+				dummy_candidate_receipt_bad_sig(block_hash, Some(Default::default()));
+			candidate_receipt.descriptor.para_id = ParaId::from(0_u32);
+			candidate_receipt.descriptor.relay_parent = block_hash;
+			candidate_receipt.hash()
+		};
+
+		let candidate_index = 0;
+		let session_index = 1;
+
+		// Add block hash 0x01...
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig { slot: Slot::from(1), candidates: None, session_info: None },
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		for validator in [ValidatorIndex(0), ValidatorIndex(1)] {
+			let rx = check_and_import_assignment(
+				&mut virtual_overseer,
+				block_hash,
+				candidate_index,
+				validator,
+			)
+			.await;
+
+			assert_eq!(rx.await, Ok(AssignmentCheckResult::Accepted));
+		}
+
+		let vote = |validator, key| IndirectSignedApprovalVote {
+			block_hash,
+			candidate_index,
+			validator,
+			signature: sign_approval(key, candidate_hash, session_index),
+		};
+
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportApprovals(
+					vec![
+						vote(ValidatorIndex(0), Sr25519Keyring::Alice),
+						// Signed with the wrong key.
+						vote(ValidatorIndex(1), Sr25519Keyring::Alice),
+						vote(ValidatorIndex(1), Sr25519Keyring::Bob),
+					],
+					tx,
+				),
+			},
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+				assert_eq!(b_hash, block_hash);
+			}
+		);
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![
+				ApprovalCheckResult::Accepted,
+				ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(ValidatorIndex(1))),
+				ApprovalCheckResult::Accepted,
+			]),
+		);
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn subsystem_checks_and_imports_coalesced_approval() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+		let session_index = 1;
+
+		let candidates = vec![
+			(make_candidate(ParaId::from(0_u32), &block_hash), CoreIndex(0), GroupIndex(0)),
+			(make_candidate(ParaId::from(1_u32), &block_hash), CoreIndex(1), GroupIndex(1)),
+		];
+		let candidate_hashes =
+			candidates.iter().map(|(receipt, _, _)| receipt.hash()).collect::<Vec<_>>();
+
+		// Add block hash 0x01...
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig {
+					slot: Slot::from(1),
+					candidates: Some(candidates),
+					session_info: None,
+				},
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		for candidate_index in [0, 1] {
+			let rx = check_and_import_assignment(
+				&mut virtual_overseer,
+				block_hash,
+				candidate_index,
+				ValidatorIndex(0),
+			)
+			.await;
+
+			assert_eq!(rx.await, Ok(AssignmentCheckResult::Accepted));
+		}
+
+		let vote = IndirectSignedCoalescedApprovalVote {
+			block_hash,
+			candidate_indices: CandidateBitfield::repeat(true, 2),
+			validator: ValidatorIndex(0),
+			signature: Sr25519Keyring::Alice
+				.sign(
+					&ApprovalVoteMultipleCandidates(&candidate_hashes)
+						.signing_payload(session_index),
+				)
+				.into(),
+		};
+
+		// Rejected while the runtime doesn't enable coalescing.
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportCoalescedApproval(vote.clone(), tx),
+			},
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				hash,
+				RuntimeApiRequest::ApprovalVotingParams(p_tx),
+			)) => {
+				assert_eq!(hash, block_hash);
+				p_tx.send(Err(RuntimeApiError::NotSupported {
+					runtime_api_name: "approval_voting_params",
+				}))
+				.unwrap();
+			}
+		);
+
+		assert_eq!(
+			rx.await,
+			Ok(ApprovalCheckResult::Bad(ApprovalCheckError::CoalescingNotAllowed(block_hash, 2))),
+		);
+
+		// Accepted for both candidates once it does.
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportCoalescedApproval(vote, tx),
+			},
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				hash,
+				RuntimeApiRequest::ApprovalVotingParams(p_tx),
+			)) => {
+				assert_eq!(hash, block_hash);
+				p_tx.send(Ok(ApprovalVotingParams { max_approval_coalesce_count: 2 })).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+				assert_eq!(b_hash, block_hash);
+			}
+		);
+
+		assert_eq!(rx.await, Ok(ApprovalCheckResult::Accepted));
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn subsystem_second_approval_import_only_schedules_wakeups() {
 	test_harness(HarnessConfig::default(), |test_harness| async move {
@@ -2295,7 +2513,7 @@ fn subsystem_validate_approvals_cache() {
 			let mut assignments = HashMap::new();
 			let _ = assignments.insert(
 				CoreIndex(0),
-				approval_db::v1::OurAssignment {
+				approval_db::v2::OurAssignment {
 					cert: garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo { sample: 0 }),
 					tranche: 0,
 					validator_index: ValidatorIndex(0),
@@ -2509,7 +2727,7 @@ where
 			let mut assignments = HashMap::new();
 			let _ = assignments.insert(
 				CoreIndex(0),
-				approval_db::v1::OurAssignment {
+				approval_db::v2::OurAssignment {
 					cert: garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo { sample: 0 }),
 					tranche: our_assigned_tranche,
 					validator_index: ValidatorIndex(0),
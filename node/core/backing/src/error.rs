@@ -17,7 +17,9 @@
 use fatality::Nested;
 use futures::channel::{mpsc, oneshot};
 
-use polkadot_node_subsystem::{messages::ValidationFailed, SubsystemError};
+use polkadot_node_subsystem::{
+	errors::RuntimeApiError, messages::ValidationFailed, SubsystemError,
+};
 use polkadot_node_subsystem_util::Error as UtilError;
 use polkadot_primitives::BackedCandidate;
 
@@ -49,6 +51,15 @@ pub enum Error {
 	#[error("ValidateFromChainState channel closed before receipt")]
 	ValidateFromChainState(#[source] oneshot::Canceled),
 
+	#[error("ValidateFromExhaustive channel closed before receipt")]
+	ValidateFromExhaustive(#[source] oneshot::Canceled),
+
+	#[error("Runtime API channel closed before receipt")]
+	RuntimeApiUnavailable(#[source] oneshot::Canceled),
+
+	#[error(transparent)]
+	RuntimeApi(#[from] RuntimeApiError),
+
 	#[error("StoreAvailableData channel closed before receipt")]
 	StoreAvailableData(#[source] oneshot::Canceled),
 
@@ -39,26 +39,29 @@ use polkadot_node_subsystem::{
 	messages::{
 		AvailabilityDistributionMessage, AvailabilityStoreMessage, CandidateBackingMessage,
 		CandidateValidationMessage, CollatorProtocolMessage, ProvisionableData, ProvisionerMessage,
-		RuntimeApiRequest, StatementDistributionMessage,
+		RuntimeApiMessage, RuntimeApiRequest, StatementDistributionMessage,
 	},
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, PerLeafSpan, SpawnedSubsystem,
 	Stage, SubsystemError,
 };
 use polkadot_node_subsystem_util::{
-	self as util, request_from_runtime, request_session_index_for_child, request_validator_groups,
+	self as util, request_from_runtime, request_persisted_validation_data,
+	request_session_index_for_child, request_validation_code_by_hash, request_validator_groups,
 	request_validators, Validator,
 };
 use polkadot_primitives::{
+	vstaging::{BackingMisbehavior, BackingMisbehaviorReport},
 	BackedCandidate, CandidateCommitments, CandidateHash, CandidateReceipt, CollatorId,
-	CommittedCandidateReceipt, CoreIndex, CoreState, Hash, Id as ParaId, SigningContext,
-	ValidatorId, ValidatorIndex, ValidatorSignature, ValidityAttestation,
+	CommittedCandidateReceipt, CoreIndex, CoreState, Hash, HeadData, Id as ParaId,
+	OccupiedCoreAssumption, PersistedValidationData, SigningContext, ValidatorId, ValidatorIndex,
+	ValidatorSignature, ValidityAttestation,
 };
 use sp_keystore::SyncCryptoStorePtr;
 use statement_table::{
 	generic::AttestedCandidate as TableAttestedCandidate,
 	v2::{
-		SignedStatement as TableSignedStatement, Statement as TableStatement,
-		Summary as TableSummary,
+		slashable_misbehavior, SignedStatement as TableSignedStatement,
+		Statement as TableStatement, Summary as TableSummary,
 	},
 	Context as TableContextTrait, Table,
 };
@@ -342,6 +345,7 @@ async fn handle_active_leaves_update<Context>(
 	let mut assignments_span = span.child("compute-assignments");
 
 	let mut groups = HashMap::new();
+	let mut paras = HashMap::new();
 
 	let n_cores = cores.len();
 
@@ -354,24 +358,25 @@ async fn handle_active_leaves_update<Context>(
 			let group_index = group_rotation_info.group_for_core(core_index, n_cores);
 			if let Some(g) = validator_groups.get(group_index.0 as usize) {
 				if validator.as_ref().map_or(false, |v| g.contains(&v.index())) {
-					assignment = Some((scheduled.para_id, scheduled.collator));
+					assignment = Some((scheduled.para_id, scheduled.collator, core_index));
 				}
-				groups.insert(scheduled.para_id, g.clone());
+				groups.insert(core_index, g.clone());
+				paras.insert(core_index, scheduled.para_id);
 			}
 		}
 	}
 
-	let table_context = TableContext { groups, validators, validator };
+	let table_context = TableContext { groups, paras, validators, validator };
 
 	let (assignment, required_collator) = match assignment {
 		None => {
 			assignments_span.add_string_tag("assigned", "false");
 			(None, None)
 		},
-		Some((assignment, required_collator)) => {
+		Some((assignment, required_collator, core_index)) => {
 			assignments_span.add_string_tag("assigned", "true");
 			assignments_span.add_para_id(assignment);
-			(Some(assignment), required_collator)
+			(Some((assignment, core_index)), required_collator)
 		},
 	};
 
@@ -410,8 +415,8 @@ struct JobAndSpan<Context> {
 struct CandidateBackingJob<Context> {
 	/// The hash of the relay parent on top of which this job is doing it's work.
 	parent: Hash,
-	/// The `ParaId` assigned to this validator
-	assignment: Option<ParaId>,
+	/// The `ParaId` assigned to this validator and the core it is scheduled on.
+	assignment: Option<(ParaId, CoreIndex)>,
 	/// The collator required to author the candidate, if any.
 	required_collator: Option<CollatorId>,
 	/// Spans for all candidates that are not yet backable.
@@ -462,14 +467,40 @@ fn minimum_votes(n_validators: usize) -> usize {
 #[derive(Default)]
 struct TableContext {
 	validator: Option<Validator>,
-	groups: HashMap<ParaId, Vec<ValidatorIndex>>,
+	/// The backing groups of the scheduled cores.
+	groups: HashMap<CoreIndex, Vec<ValidatorIndex>>,
+	/// The paras scheduled on the cores.
+	paras: HashMap<CoreIndex, ParaId>,
 	validators: Vec<ValidatorId>,
 }
 
+impl TableContext {
+	/// The core a candidate of the given para seconded by the given validator is backed on.
+	///
+	/// This is the core of the validator's group, if the para is scheduled on it. Otherwise, the
+	/// candidate is placed on some core of the para for the statement to be rejected as
+	/// unauthorized.
+	fn core_for_candidate(&self, seconder: &ValidatorIndex, para_id: &ParaId) -> Option<CoreIndex> {
+		let mut para_cores = self
+			.paras
+			.iter()
+			.filter(|(_, para)| *para == para_id)
+			.map(|(core, _)| *core)
+			.collect::<Vec<_>>();
+		para_cores.sort();
+
+		para_cores
+			.iter()
+			.find(|core| self.groups.get(core).map_or(false, |g| g.contains(seconder)))
+			.or_else(|| para_cores.first())
+			.copied()
+	}
+}
+
 impl TableContextTrait for TableContext {
 	type AuthorityId = ValidatorIndex;
 	type Digest = CandidateHash;
-	type GroupId = ParaId;
+	type GroupId = CoreIndex;
 	type Signature = ValidatorSignature;
 	type Candidate = CommittedCandidateReceipt;
 
@@ -477,15 +508,11 @@ impl TableContextTrait for TableContext {
 		candidate.hash()
 	}
 
-	fn candidate_group(candidate: &CommittedCandidateReceipt) -> ParaId {
-		candidate.descriptor().para_id
-	}
-
-	fn is_member_of(&self, authority: &ValidatorIndex, group: &ParaId) -> bool {
+	fn is_member_of(&self, authority: &ValidatorIndex, group: &CoreIndex) -> bool {
 		self.groups.get(group).map_or(false, |g| g.iter().any(|a| a == authority))
 	}
 
-	fn requisite_votes(&self, group: &ParaId) -> usize {
+	fn requisite_votes(&self, group: &CoreIndex) -> usize {
 		self.groups.get(group).map_or(usize::MAX, |g| minimum_votes(g.len()))
 	}
 }
@@ -509,19 +536,19 @@ fn primitive_statement_to_table(s: &SignedFullStatement) -> TableSignedStatement
 
 fn table_attested_to_backed(
 	attested: TableAttestedCandidate<
-		ParaId,
+		CoreIndex,
 		CommittedCandidateReceipt,
 		ValidatorIndex,
 		ValidatorSignature,
 	>,
 	table_context: &TableContext,
 ) -> Option<BackedCandidate> {
-	let TableAttestedCandidate { candidate, validity_votes, group_id: para_id } = attested;
+	let TableAttestedCandidate { candidate, validity_votes, group_id: core_index } = attested;
 
 	let (ids, validity_votes): (Vec<_>, Vec<ValidityAttestation>) =
 		validity_votes.into_iter().map(|(id, vote)| (id, vote.into())).unzip();
 
-	let group = table_context.groups.get(&para_id)?;
+	let group = table_context.groups.get(&core_index)?;
 
 	let mut validator_indices = BitVec::with_capacity(group.len());
 
@@ -662,6 +689,75 @@ async fn request_candidate_validation(
 	}
 }
 
+/// Validate a candidate building on top of another candidate of the para, which is pending
+/// availability or backed on the same relay parent.
+///
+/// The candidate is validated against the persisted validation data of the relay parent, with the
+/// parent head replaced by the first of `parent_heads` matching the hash in the descriptor.
+///
+/// The acceptance criteria are only checked by the runtime once the candidate is backed.
+async fn request_chained_candidate_validation(
+	sender: &mut impl overseer::CandidateBackingSenderTrait,
+	candidate_receipt: CandidateReceipt,
+	pov: Arc<PoV>,
+	parent_heads: Vec<HeadData>,
+) -> Result<ValidationResult, Error> {
+	let descriptor = &candidate_receipt.descriptor;
+
+	let validation_data = request_persisted_validation_data(
+		descriptor.relay_parent,
+		descriptor.para_id,
+		OccupiedCoreAssumption::Included,
+		sender,
+	)
+	.await
+	.await
+	.map_err(Error::RuntimeApiUnavailable)??;
+
+	let validation_data = match validation_data.and_then(|validation_data| {
+		parent_heads
+			.into_iter()
+			.map(|parent_head| PersistedValidationData { parent_head, ..validation_data.clone() })
+			.find(|validation_data| {
+				validation_data.hash() == descriptor.persisted_validation_data_hash
+			})
+	}) {
+		Some(validation_data) => validation_data,
+		None => return Ok(ValidationResult::Invalid(InvalidCandidate::BadParent)),
+	};
+
+	let validation_code = match request_validation_code_by_hash(
+		descriptor.relay_parent,
+		descriptor.validation_code_hash,
+		sender,
+	)
+	.await
+	.await
+	.map_err(Error::RuntimeApiUnavailable)??
+	{
+		Some(validation_code) => validation_code,
+		None => return Ok(ValidationResult::Invalid(InvalidCandidate::CodeHashMismatch)),
+	};
+
+	let (tx, rx) = oneshot::channel();
+	sender
+		.send_message(CandidateValidationMessage::ValidateFromExhaustive(
+			validation_data,
+			validation_code,
+			candidate_receipt,
+			pov,
+			BACKING_EXECUTION_TIMEOUT,
+			tx,
+		))
+		.await;
+
+	match rx.await {
+		Ok(Ok(validation_result)) => Ok(validation_result),
+		Ok(Err(err)) => Err(Error::ValidationFailed(err)),
+		Err(err) => Err(Error::ValidateFromExhaustive(err)),
+	}
+}
+
 type BackgroundValidationResult =
 	Result<(CandidateReceipt, CandidateCommitments, Arc<PoV>), CandidateReceipt>;
 
@@ -672,6 +768,8 @@ struct BackgroundValidationParams<S: overseer::CandidateBackingSenderTrait, F> {
 	relay_parent: Hash,
 	pov: PoVData,
 	n_validators: usize,
+	/// The heads of the other candidates of the para, which the candidate might build on.
+	chain_parents: Vec<HeadData>,
 	span: Option<jaeger::Span>,
 	make_command: F,
 }
@@ -689,6 +787,7 @@ async fn validate_and_make_available(
 		relay_parent,
 		pov,
 		n_validators,
+		chain_parents,
 		span,
 		make_command,
 	} = params;
@@ -729,7 +828,19 @@ async fn validate_and_make_available(
 				.with_pov(&pov)
 				.with_para_id(candidate.descriptor().para_id)
 		});
-		request_candidate_validation(&mut sender, candidate.clone(), pov.clone()).await?
+		match request_candidate_validation(&mut sender, candidate.clone(), pov.clone()).await? {
+			// The para might be assigned multiple cores, in which case the candidate may build
+			// on top of another candidate of the para.
+			ValidationResult::Invalid(InvalidCandidate::BadParent) if !chain_parents.is_empty() =>
+				request_chained_candidate_validation(
+					&mut sender,
+					candidate.clone(),
+					pov.clone(),
+					chain_parents,
+				)
+				.await?,
+			v => v,
+		}
 	};
 
 	let res = match v {
@@ -958,6 +1069,7 @@ impl<Context> CandidateBackingJob<Context> {
 				relay_parent: self.parent,
 				pov: PoVData::Ready(pov),
 				n_validators: self.table_context.validators.len(),
+				chain_parents: self.chain_parents(candidate),
 				span,
 				make_command: ValidatedCandidateCommand::Second,
 			},
@@ -985,10 +1097,19 @@ impl<Context> CandidateBackingJob<Context> {
 	}
 
 	/// Check if there have happened any new misbehaviors and issue necessary messages.
-	fn issue_new_misbehaviors(&mut self, sender: &mut impl overseer::CandidateBackingSenderTrait) {
+	///
+	/// Slashable misbehavior is additionally reported on-chain, if we are a validator.
+	async fn issue_new_misbehaviors(
+		&mut self,
+		sender: &mut impl overseer::CandidateBackingSenderTrait,
+	) {
 		// collect the misbehaviors to avoid double mutable self borrow issues
 		let misbehaviors: Vec<_> = self.table.drain_misbehaviors().collect();
 		for (validator_id, report) in misbehaviors {
+			if let Some(misbehavior) = slashable_misbehavior(&report) {
+				self.submit_misbehavior_report(sender, validator_id, misbehavior).await;
+			}
+
 			// The provisioner waits on candidate-backing, which means
 			// that we need to send unbounded messages to avoid cycles.
 			//
@@ -1001,6 +1122,57 @@ impl<Context> CandidateBackingJob<Context> {
 		}
 	}
 
+	/// Sign a report of slashable misbehavior and submit it on-chain.
+	async fn submit_misbehavior_report(
+		&self,
+		sender: &mut impl overseer::CandidateBackingSenderTrait,
+		offender: ValidatorIndex,
+		misbehavior: BackingMisbehavior,
+	) {
+		let validator = match self.table_context.validator.as_ref() {
+			Some(v) => v,
+			None => return,
+		};
+
+		let report = BackingMisbehaviorReport {
+			session_index: validator.signing_context().session_index,
+			relay_parent: self.parent,
+			offender,
+			misbehavior,
+			reporter: validator.index(),
+		};
+
+		let signature =
+			match util::sign(&self.keystore, &validator.id(), &report.signing_payload()).await {
+				Ok(Some(signature)) => signature,
+				Ok(None) => return,
+				Err(err) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						?err,
+						"Failed to sign backing misbehavior report",
+					);
+					return
+				},
+			};
+
+		gum::info!(
+			target: LOG_TARGET,
+			relay_parent = ?self.parent,
+			offender = offender.0,
+			"Reporting backing misbehavior",
+		);
+
+		// The runtime may not support the report, so we don't wait for the response.
+		let (tx, _rx) = oneshot::channel();
+		sender
+			.send_message(RuntimeApiMessage::Request(
+				self.parent,
+				RuntimeApiRequest::SubmitBackingMisbehaviorReport(report, signature, tx),
+			))
+			.await;
+	}
+
 	/// Import a statement into the statement table and return the summary of the import.
 	async fn import_statement(
 		&mut self,
@@ -1025,9 +1197,29 @@ impl<Context> CandidateBackingJob<Context> {
 			)
 		};
 
+		// Candidates are placed on the core of their seconder's group. The group is ignored for
+		// validity statements, which are checked against the core of the candidate.
+		let core_index = match statement.payload() {
+			Statement::Seconded(candidate) => match self
+				.table_context
+				.core_for_candidate(&statement.validator_index(), &candidate.descriptor.para_id)
+			{
+				Some(core_index) => core_index,
+				None => {
+					gum::debug!(
+						target: LOG_TARGET,
+						?candidate_hash,
+						"Ignoring candidate of a para which isn't scheduled",
+					);
+					return Ok(None)
+				},
+			},
+			Statement::Valid(_) => CoreIndex::default(),
+		};
+
 		let stmt = primitive_statement_to_table(statement);
 
-		let summary = self.table.import_statement(&self.table_context, stmt);
+		let summary = self.table.import_statement(&self.table_context, core_index, stmt);
 
 		let unbacked_span = if let Some(attested) = summary
 			.as_ref()
@@ -1070,7 +1262,7 @@ impl<Context> CandidateBackingJob<Context> {
 			None
 		};
 
-		self.issue_new_misbehaviors(ctx.sender());
+		self.issue_new_misbehaviors(ctx.sender()).await;
 
 		// It is important that the child span is dropped before its parent span (`unbacked_span`)
 		drop(import_statement_span);
@@ -1097,7 +1289,7 @@ impl<Context> CandidateBackingJob<Context> {
 			.with_relay_parent(self.parent);
 
 		// Sanity check that candidate is from our assignment.
-		if Some(candidate.descriptor().para_id) != self.assignment {
+		if Some(candidate.descriptor().para_id) != self.assignment.map(|(para_id, _)| para_id) {
 			gum::debug!(
 				target: LOG_TARGET,
 				our_assignment = ?self.assignment,
@@ -1200,6 +1392,7 @@ impl<Context> CandidateBackingJob<Context> {
 			candidate_hash,
 			pov_hash: attesting.pov_hash,
 		};
+		let chain_parents = self.chain_parents(&attesting.candidate);
 		self.background_validate_and_make_available(
 			ctx,
 			BackgroundValidationParams {
@@ -1209,6 +1402,7 @@ impl<Context> CandidateBackingJob<Context> {
 				relay_parent: self.parent,
 				pov,
 				n_validators: self.table_context.validators.len(),
+				chain_parents,
 				span,
 				make_command: ValidatedCandidateCommand::Attest,
 			},
@@ -1224,7 +1418,7 @@ impl<Context> CandidateBackingJob<Context> {
 		statement: SignedFullStatement,
 	) -> Result<(), Error> {
 		if let Some(summary) = self.import_statement(ctx, &statement, root_span).await? {
-			if Some(summary.group_id) != self.assignment {
+			if Some(summary.group_id) != self.assignment.map(|(_, core_index)| core_index) {
 				return Ok(())
 			}
 			let (attesting, span) = match statement.payload() {
@@ -1234,7 +1428,7 @@ impl<Context> CandidateBackingJob<Context> {
 					let span = self.get_unbacked_validation_child(
 						root_span,
 						summary.candidate,
-						summary.group_id,
+						receipt.descriptor.para_id,
 					);
 
 					let attesting = AttestingData {
@@ -1278,6 +1472,19 @@ impl<Context> CandidateBackingJob<Context> {
 		Ok(())
 	}
 
+	/// The heads of the candidates in the table of the same para as the given candidate, which
+	/// the candidate might build on.
+	fn chain_parents(&self, candidate: &CandidateReceipt) -> Vec<HeadData> {
+		let candidate_hash = candidate.hash();
+		self.table
+			.candidates()
+			.filter(|c| {
+				c.descriptor.para_id == candidate.descriptor.para_id && c.hash() != candidate_hash
+			})
+			.map(|c| c.commitments.head_data.clone())
+			.collect()
+	}
+
 	async fn sign_statement(&mut self, statement: Statement) -> Option<SignedFullStatement> {
 		let signed = self
 			.table_context
@@ -32,7 +32,7 @@ use polkadot_node_subsystem::{
 use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_primitives::{
 	CandidateDescriptor, CollatorId, GroupRotationInfo, HeadData, PersistedValidationData,
-	ScheduledCore,
+	ScheduledCore, ValidatorPair,
 };
 use sp_application_crypto::AppKey;
 use sp_keyring::Sr25519Keyring;
@@ -511,6 +511,218 @@ fn backing_works() {
 	});
 }
 
+// Test that a candidate building on top of another candidate of the para on the same relay parent
+// is validated against the head of that candidate.
+#[test]
+fn backing_validates_chained_candidates() {
+	let test_state = TestState::default();
+	test_harness(test_state.keystore.clone(), |mut virtual_overseer| async move {
+		test_startup(&mut virtual_overseer, &test_state).await;
+
+		let pov = PoV { block_data: BlockData(vec![1, 2, 3]) };
+
+		let pov_hash = pov.hash();
+
+		let head_data_a = test_state.head_data.get(&test_state.chain_ids[0]).unwrap();
+		let head_data_b = HeadData(vec![10, 11, 12]);
+
+		let candidate_a = TestCandidateBuilder {
+			para_id: test_state.chain_ids[0],
+			relay_parent: test_state.relay_parent,
+			pov_hash,
+			head_data: head_data_a.clone(),
+			erasure_root: make_erasure_root(&test_state, pov.clone()),
+			..Default::default()
+		}
+		.build();
+
+		let chained_validation_data = PersistedValidationData {
+			parent_head: head_data_a.clone(),
+			..test_state.validation_data.clone()
+		};
+		let mut candidate_b = TestCandidateBuilder {
+			para_id: test_state.chain_ids[0],
+			relay_parent: test_state.relay_parent,
+			pov_hash,
+			head_data: head_data_b.clone(),
+			erasure_root: make_erasure_root(&test_state, pov.clone()),
+			..Default::default()
+		}
+		.build();
+		candidate_b.descriptor.persisted_validation_data_hash = chained_validation_data.hash();
+
+		let public2 = CryptoStore::sr25519_generate_new(
+			&*test_state.keystore,
+			ValidatorId::ID,
+			Some(&test_state.validators[2].to_seed()),
+		)
+		.await
+		.expect("Insert key into keystore");
+		let public3 = CryptoStore::sr25519_generate_new(
+			&*test_state.keystore,
+			ValidatorId::ID,
+			Some(&test_state.validators[3].to_seed()),
+		)
+		.await
+		.expect("Insert key into keystore");
+
+		let signed_a = SignedFullStatement::sign(
+			&test_state.keystore,
+			Statement::Seconded(candidate_a.clone()),
+			&test_state.signing_context,
+			ValidatorIndex(2),
+			&public2.into(),
+		)
+		.await
+		.ok()
+		.flatten()
+		.expect("should be signed");
+
+		let signed_b = SignedFullStatement::sign(
+			&test_state.keystore,
+			Statement::Seconded(candidate_b.clone()),
+			&test_state.signing_context,
+			ValidatorIndex(3),
+			&public3.into(),
+		)
+		.await
+		.ok()
+		.flatten()
+		.expect("should be signed");
+
+		for (signed, candidate) in [(signed_a, &candidate_a), (signed_b, &candidate_b)] {
+			let statement = CandidateBackingMessage::Statement(test_state.relay_parent, signed);
+
+			virtual_overseer.send(FromOrchestra::Communication { msg: statement }).await;
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::AvailabilityDistribution(
+					AvailabilityDistributionMessage::FetchPoV {
+						relay_parent,
+						tx,
+						..
+					}
+				) if relay_parent == test_state.relay_parent => {
+					tx.send(pov.clone()).unwrap();
+				}
+			);
+
+			let commitments = CandidateCommitments {
+				head_data: candidate.commitments.head_data.clone(),
+				upward_messages: Vec::new(),
+				horizontal_messages: Vec::new(),
+				new_validation_code: None,
+				processed_downward_messages: 0,
+				hrmp_watermark: 0,
+			};
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::CandidateValidation(
+					CandidateValidationMessage::ValidateFromChainState(c, _pov, _timeout, tx)
+				) if c.descriptor() == candidate.descriptor() => {
+					if candidate.hash() == candidate_a.hash() {
+						tx.send(Ok(ValidationResult::Valid(
+							commitments.clone(),
+							test_state.validation_data.clone(),
+						)))
+						.unwrap();
+					} else {
+						// candidate B doesn't build on top of the head on chain.
+						tx.send(Ok(ValidationResult::Invalid(InvalidCandidate::BadParent))).unwrap();
+					}
+				}
+			);
+
+			if candidate.hash() == candidate_b.hash() {
+				assert_matches!(
+					virtual_overseer.recv().await,
+					AllMessages::RuntimeApi(
+						RuntimeApiMessage::Request(_, RuntimeApiRequest::PersistedValidationData(
+							para_id,
+							OccupiedCoreAssumption::Included,
+							tx,
+						))
+					) if para_id == test_state.chain_ids[0] => {
+						tx.send(Ok(Some(test_state.validation_data.clone()))).unwrap();
+					}
+				);
+
+				assert_matches!(
+					virtual_overseer.recv().await,
+					AllMessages::RuntimeApi(
+						RuntimeApiMessage::Request(_, RuntimeApiRequest::ValidationCodeByHash(
+							hash,
+							tx,
+						))
+					) if hash == candidate_b.descriptor.validation_code_hash => {
+						tx.send(Ok(Some(dummy_validation_code()))).unwrap();
+					}
+				);
+
+				assert_matches!(
+					virtual_overseer.recv().await,
+					AllMessages::CandidateValidation(
+						CandidateValidationMessage::ValidateFromExhaustive(
+							validation_data,
+							_validation_code,
+							c,
+							_pov,
+							_timeout,
+							tx,
+						)
+					) if c.descriptor() == candidate_b.descriptor() => {
+						assert_eq!(validation_data, chained_validation_data);
+						tx.send(Ok(ValidationResult::Valid(
+							commitments.clone(),
+							test_state.validation_data.clone(),
+						)))
+						.unwrap();
+					}
+				);
+			}
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::AvailabilityStore(
+					AvailabilityStoreMessage::StoreAvailableData { candidate_hash, tx, .. }
+				) if candidate_hash == candidate.hash() => {
+					tx.send(Ok(())).unwrap();
+				}
+			);
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::Provisioner(
+					ProvisionerMessage::ProvisionableData(
+						_,
+						ProvisionableData::BackedCandidate(candidate_receipt)
+					)
+				) => {
+					assert_eq!(candidate_receipt, candidate.to_plain());
+				}
+			);
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::StatementDistribution(
+					StatementDistributionMessage::Share(hash, _stmt)
+				) => {
+					assert_eq!(test_state.relay_parent, hash);
+				}
+			);
+		}
+
+		virtual_overseer
+			.send(FromOrchestra::Signal(OverseerSignal::ActiveLeaves(
+				ActiveLeavesUpdate::stop_work(test_state.relay_parent),
+			)))
+			.await;
+		virtual_overseer
+	});
+}
+
 #[test]
 fn backing_works_while_validation_ongoing() {
 	let test_state = TestState::default();
@@ -868,6 +1080,159 @@ fn backing_misbehavior_works() {
 	});
 }
 
+// Seconding multiple candidates at the same relay parent should be
+// reported on-chain.
+#[test]
+fn backing_multiple_candidates_misbehavior_is_reported() {
+	let test_state = TestState::default();
+	test_harness(test_state.keystore.clone(), |mut virtual_overseer| async move {
+		test_startup(&mut virtual_overseer, &test_state).await;
+
+		let pov = PoV { block_data: BlockData(vec![1, 2, 3]) };
+		let pov_hash = pov.hash();
+
+		let expected_head_data = test_state.head_data.get(&test_state.chain_ids[0]).unwrap();
+
+		let candidate_a = TestCandidateBuilder {
+			para_id: test_state.chain_ids[0],
+			relay_parent: test_state.relay_parent,
+			pov_hash,
+			erasure_root: make_erasure_root(&test_state, pov.clone()),
+			head_data: expected_head_data.clone(),
+			..Default::default()
+		}
+		.build();
+
+		let candidate_b = TestCandidateBuilder {
+			para_id: test_state.chain_ids[0],
+			relay_parent: test_state.relay_parent,
+			pov_hash: Hash::repeat_byte(42),
+			..Default::default()
+		}
+		.build();
+
+		let public2 = CryptoStore::sr25519_generate_new(
+			&*test_state.keystore,
+			ValidatorId::ID,
+			Some(&test_state.validators[2].to_seed()),
+		)
+		.await
+		.expect("Insert key into keystore");
+
+		let mut seconded = Vec::new();
+		for candidate in [&candidate_a, &candidate_b] {
+			let statement = SignedFullStatement::sign(
+				&test_state.keystore,
+				Statement::Seconded(candidate.clone()),
+				&test_state.signing_context,
+				ValidatorIndex(2),
+				&public2.into(),
+			)
+			.await
+			.ok()
+			.flatten()
+			.expect("should be signed");
+			seconded.push(statement);
+		}
+
+		let statement =
+			CandidateBackingMessage::Statement(test_state.relay_parent, seconded[0].clone());
+		virtual_overseer.send(FromOrchestra::Communication { msg: statement }).await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::AvailabilityDistribution(
+				AvailabilityDistributionMessage::FetchPoV { tx, .. }
+			) => {
+				tx.send(pov.clone()).unwrap();
+			}
+		);
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::CandidateValidation(
+				CandidateValidationMessage::ValidateFromChainState(_, _, _, tx)
+			) => {
+				tx.send(Ok(
+					ValidationResult::Valid(CandidateCommitments {
+						head_data: expected_head_data.clone(),
+						upward_messages: Vec::new(),
+						horizontal_messages: Vec::new(),
+						new_validation_code: None,
+						processed_downward_messages: 0,
+						hrmp_watermark: 0,
+					}, test_state.validation_data.clone()),
+				)).unwrap();
+			}
+		);
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::AvailabilityStore(
+				AvailabilityStoreMessage::StoreAvailableData { tx, .. }
+			) => {
+				tx.send(Ok(())).unwrap();
+			}
+		);
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+				_,
+				ProvisionableData::BackedCandidate(_),
+			))
+		);
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::StatementDistribution(StatementDistributionMessage::Share(..))
+		);
+
+		let statement =
+			CandidateBackingMessage::Statement(test_state.relay_parent, seconded[1].clone());
+		virtual_overseer.send(FromOrchestra::Communication { msg: statement }).await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				relay_parent,
+				RuntimeApiRequest::SubmitBackingMisbehaviorReport(report, signature, _),
+			)) if relay_parent == test_state.relay_parent => {
+				assert_eq!(report.session_index, test_state.signing_context.session_index);
+				assert_eq!(report.relay_parent, test_state.relay_parent);
+				assert_eq!(report.offender, ValidatorIndex(2));
+				assert_eq!(report.reporter, ValidatorIndex(0));
+				assert_eq!(
+					report.misbehavior,
+					BackingMisbehavior::MultipleCandidates {
+						first: (candidate_a.hash(), seconded[0].signature().clone()),
+						second: (candidate_b.hash(), seconded[1].signature().clone()),
+					},
+				);
+				assert!(<ValidatorPair as sp_core::Pair>::verify(
+					&signature,
+					report.signing_payload(),
+					&test_state.validator_public[0],
+				));
+			}
+		);
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+				_,
+				ProvisionableData::MisbehaviorReport(
+					_,
+					ValidatorIndex(2),
+					Misbehavior::MultipleCandidates(_)
+				),
+			))
+		);
+
+		virtual_overseer
+	});
+}
+
 // Test that if we are asked to second an invalid candidate we
 // can still second a valid one afterwards.
 #[test]
@@ -1340,16 +1705,18 @@ fn candidate_backing_reorders_votes() {
 	];
 
 	let validator_public = validator_pubkeys(&validators);
+	let core_index = CoreIndex(0);
 	let validator_groups = {
 		let mut validator_groups = HashMap::new();
 		validator_groups
-			.insert(para_id, vec![0, 1, 2, 3, 4, 5].into_iter().map(ValidatorIndex).collect());
+			.insert(core_index, vec![0, 1, 2, 3, 4, 5].into_iter().map(ValidatorIndex).collect());
 		validator_groups
 	};
 
 	let table_context = TableContext {
 		validator: None,
 		groups: validator_groups,
+		paras: vec![(core_index, para_id)].into_iter().collect(),
 		validators: validator_public.clone(),
 	};
 
@@ -1372,7 +1739,7 @@ fn candidate_backing_reorders_votes() {
 			(ValidatorIndex(3), fake_attestation(3)),
 			(ValidatorIndex(1), fake_attestation(1)),
 		],
-		group_id: para_id,
+		group_id: core_index,
 	};
 
 	let backed = table_attested_to_backed(attested, &table_context).unwrap();
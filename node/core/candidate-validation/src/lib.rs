@@ -38,6 +38,7 @@ use polkadot_node_subsystem::{
 	overseer, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError, SubsystemResult,
 	SubsystemSender,
 };
+use polkadot_node_subsystem_util::pvf_queue_depth::PvfQueueDepth;
 use polkadot_parachain::primitives::{ValidationParams, ValidationResult as WasmValidationResult};
 use polkadot_primitives::{
 	CandidateCommitments, CandidateDescriptor, CandidateReceipt, Hash, OccupiedCoreAssumption,
@@ -74,6 +75,8 @@ pub struct Config {
 	/// The path to the executable which can be used for spawning PVF compilation & validation
 	/// workers.
 	pub program_path: PathBuf,
+	/// Counts the validations which were requested and didn't finish yet.
+	pub pvf_queue_depth: PvfQueueDepth,
 }
 
 /// The candidate validation subsystem.
@@ -108,6 +111,7 @@ impl<Context> CandidateValidationSubsystem {
 			self.pvf_metrics,
 			self.config.artifacts_cache_path,
 			self.config.program_path,
+			self.config.pvf_queue_depth,
 		)
 		.map_err(|e| SubsystemError::with_origin("candidate-validation", e))
 		.boxed();
@@ -122,6 +126,7 @@ async fn run<Context>(
 	pvf_metrics: polkadot_node_core_pvf::Metrics,
 	cache_path: PathBuf,
 	program_path: PathBuf,
+	pvf_queue_depth: PvfQueueDepth,
 ) -> SubsystemResult<()> {
 	let (validation_host, task) = polkadot_node_core_pvf::start(
 		polkadot_node_core_pvf::Config::new(cache_path, program_path),
@@ -145,8 +150,10 @@ async fn run<Context>(
 						let mut sender = ctx.sender().clone();
 						let metrics = metrics.clone();
 						let validation_host = validation_host.clone();
+						let pending = pvf_queue_depth.enter();
 
 						async move {
+							let _pending = pending;
 							let _timer = metrics.time_validate_from_chain_state();
 							let res = validate_from_chain_state(
 								&mut sender,
@@ -176,8 +183,10 @@ async fn run<Context>(
 					let bg = {
 						let metrics = metrics.clone();
 						let validation_host = validation_host.clone();
+						let pending = pvf_queue_depth.enter();
 
 						async move {
+							let _pending = pending;
 							let _timer = metrics.time_validate_from_exhaustive();
 							let res = validate_candidate_exhaustive(
 								validation_host,
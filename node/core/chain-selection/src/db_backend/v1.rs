@@ -34,7 +34,7 @@
 
 use crate::{
 	backend::{Backend, BackendWriteOp},
-	Error,
+	Error, LOG_TARGET,
 };
 
 use polkadot_node_primitives::BlockWeight;
@@ -50,6 +50,9 @@ const BLOCK_HEIGHT_PREFIX: &[u8; 15] = b"CS_block_height";
 const STAGNANT_AT_PREFIX: &[u8; 14] = b"CS_stagnant_at";
 const LEAVES_KEY: &[u8; 9] = b"CS_leaves";
 
+/// How many entries are checked between two progress reports of the DB sanity check.
+const PROGRESS_REPORT_INTERVAL: usize = 1000;
+
 type Timestamp = u64;
 
 #[derive(Debug, Encode, Decode, Clone, PartialEq)]
@@ -336,6 +339,45 @@ impl Backend for DbBackend {
 	}
 }
 
+/// Check that all entries of the chain-selection column decode with the v1 schema.
+///
+/// This is run by the parachains DB upgrade. The v1 schema is still current, so there is
+/// nothing to convert. Once it changes, a `v1_to_v2` migration is added next to this check.
+///
+/// Returns the number of entries checked.
+pub fn v1_sanity_check(db: Arc<dyn Database>, col_data: u32) -> Result<usize, Error> {
+	let mut checked = 0;
+
+	check_entries::<BlockEntry>(&*db, col_data, &BLOCK_ENTRY_PREFIX[..], &mut checked)?;
+	check_entries::<Vec<Hash>>(&*db, col_data, &BLOCK_HEIGHT_PREFIX[..], &mut checked)?;
+	check_entries::<Vec<Hash>>(&*db, col_data, &STAGNANT_AT_PREFIX[..], &mut checked)?;
+	check_entries::<LeafEntrySet>(&*db, col_data, &LEAVES_KEY[..], &mut checked)?;
+
+	gum::info!(target: LOG_TARGET, "Chain-selection DB check complete, {} entries checked", checked);
+
+	Ok(checked)
+}
+
+// Decode all entries with the given key prefix, counting them.
+fn check_entries<D: Decode>(
+	db: &dyn Database,
+	col_data: u32,
+	prefix: &[u8],
+	checked: &mut usize,
+) -> Result<(), Error> {
+	for entry in db.iter_with_prefix(col_data, prefix) {
+		let (_, raw) = entry?;
+		D::decode(&mut &raw[..])?;
+
+		*checked += 1;
+		if *checked % PROGRESS_REPORT_INTERVAL == 0 {
+			gum::info!(target: LOG_TARGET, "Chain-selection DB check progress: {} entries", checked);
+		}
+	}
+
+	Ok(())
+}
+
 fn load_decode<D: Decode>(
 	db: &dyn Database,
 	col_data: u32,
@@ -627,4 +669,41 @@ mod tests {
 
 		assert_eq!(backend.load_blocks_by_number(10).unwrap(), vec![Hash::repeat_byte(3)]);
 	}
+
+	#[test]
+	fn sanity_check_decodes_all_entries() {
+		let db = test_db();
+		let config = Config { col_data: 0 };
+
+		let mut backend = DbBackend::new(db.clone(), config);
+
+		let block_entry = BlockEntry {
+			block_hash: Hash::repeat_byte(1),
+			block_number: 1,
+			parent_hash: Hash::repeat_byte(0),
+			children: vec![],
+			viability: ViabilityCriteria {
+				earliest_unviable_ancestor: None,
+				explicitly_reverted: false,
+				approval: Approval::Unapproved,
+			},
+			weight: 100,
+		};
+
+		backend
+			.write(vec![
+				BackendWriteOp::WriteBlockEntry(block_entry.clone().into()),
+				BackendWriteOp::WriteBlocksByNumber(1, vec![block_entry.block_hash]),
+				BackendWriteOp::WriteStagnantAt(5, vec![block_entry.block_hash]),
+			])
+			.unwrap();
+
+		assert_eq!(v1_sanity_check(db.clone(), config.col_data).unwrap(), 3);
+
+		let mut tx = DBTransaction::new();
+		tx.put_vec(config.col_data, &block_entry_key(&Hash::repeat_byte(2)), vec![1, 2, 3]);
+		db.write(tx).unwrap();
+
+		assert_matches::assert_matches!(v1_sanity_check(db, config.col_data), Err(Error::Codec(_)));
+	}
 }
@@ -40,6 +40,11 @@ mod backend;
 mod db_backend;
 mod tree;
 
+/// Chain-selection DB migration helpers, run by the parachains DB upgrade.
+pub mod migration_helpers {
+	pub use crate::db_backend::v1::v1_sanity_check;
+}
+
 #[cfg(test)]
 mod tests;
 
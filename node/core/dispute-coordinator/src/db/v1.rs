@@ -57,6 +57,9 @@ const MAX_CLEAN_BATCH_SIZE: u32 = 10;
 #[cfg(not(test))]
 const MAX_CLEAN_BATCH_SIZE: u32 = 300;
 
+/// How many entries are checked between two progress reports of the DB sanity check.
+const PROGRESS_REPORT_INTERVAL: usize = 1000;
+
 pub struct DbBackend {
 	inner: Arc<dyn Database>,
 	config: ColumnConfiguration,
@@ -281,6 +284,43 @@ fn load_decode<D: Decode>(
 	}
 }
 
+/// Check that all entries of the dispute-coordinator column decode with the v1 schema.
+///
+/// This is run by the parachains DB upgrade. The v1 schema is still current, so there is
+/// nothing to convert. Once it changes, a `v1_to_v2` migration is added next to this check.
+///
+/// Returns the number of entries checked.
+pub fn v1_sanity_check(db: Arc<dyn Database>, col_dispute_data: u32) -> Result<usize> {
+	let mut checked = 0;
+
+	for key in [&EARLIEST_SESSION_KEY[..], &CLEANED_VOTES_WATERMARK_KEY[..]] {
+		let session: Option<SessionIndex> = load_decode(&*db, col_dispute_data, key)?;
+		checked += session.map_or(0, |_| 1);
+	}
+
+	let recent_disputes: Option<RecentDisputes> =
+		load_decode(&*db, col_dispute_data, RECENT_DISPUTES_KEY)?;
+	checked += recent_disputes.map_or(0, |_| 1);
+
+	for entry in db.iter_with_prefix(col_dispute_data, &CANDIDATE_VOTES_SUBKEY[..]) {
+		let (_, raw) = entry?;
+		CandidateVotes::decode(&mut &raw[..])?;
+
+		checked += 1;
+		if checked % PROGRESS_REPORT_INTERVAL == 0 {
+			gum::info!(target: LOG_TARGET, "Dispute-coordinator DB check progress: {} entries", checked);
+		}
+	}
+
+	gum::info!(
+		target: LOG_TARGET,
+		"Dispute-coordinator DB check complete, {} entries checked",
+		checked,
+	);
+
+	Ok(checked)
+}
+
 /// Load the candidate votes for the specific session-candidate pair, if any.
 pub(crate) fn load_candidate_votes(
 	db: &dyn Database,
@@ -466,6 +506,43 @@ mod tests {
 			.is_some());
 	}
 
+	#[test]
+	fn sanity_check_decodes_all_entries() {
+		let mut backend = make_db();
+
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		overlay_db.write_earliest_session(1);
+		overlay_db.write_recent_disputes(
+			vec![((1, CandidateHash(Hash::repeat_byte(1))), DisputeStatus::Active)]
+				.into_iter()
+				.collect(),
+		);
+		overlay_db.write_candidate_votes(
+			1,
+			CandidateHash(Hash::repeat_byte(1)),
+			CandidateVotes {
+				candidate_receipt: dummy_candidate_receipt(dummy_hash()),
+				valid: Vec::new(),
+				invalid: Vec::new(),
+			},
+		);
+
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+
+		// The earliest session, the cleaned votes watermark, the recent disputes and the votes.
+		assert_eq!(v1_sanity_check(backend.inner.clone(), 0).unwrap(), 4);
+
+		let mut tx = DBTransaction::new();
+		tx.put_vec(0, &candidate_votes_key(2, &CandidateHash(Hash::repeat_byte(2))), vec![1]);
+		backend.inner.write(tx).unwrap();
+
+		assert_matches::assert_matches!(
+			v1_sanity_check(backend.inner.clone(), 0),
+			Err(Error::Codec(_))
+		);
+	}
+
 	#[test]
 	fn overlay_pre_and_post_commit_consistency() {
 		let mut backend = make_db();
@@ -106,7 +106,9 @@ impl OwnVoteState {
 		let our_valid_votes = controlled_indices
 			.iter()
 			.filter_map(|i| votes.valid.raw().get_key_value(i))
-			.map(|(index, (kind, sig))| (*index, (DisputeStatement::Valid(*kind), sig.clone())));
+			.map(|(index, (kind, sig))| {
+				(*index, (DisputeStatement::Valid(kind.clone()), sig.clone()))
+			});
 		let our_invalid_votes = controlled_indices
 			.iter()
 			.filter_map(|i| votes.invalid.get_key_value(i))
@@ -129,15 +131,18 @@ impl OwnVoteState {
 	/// vote).
 	fn approval_votes(
 		&self,
-	) -> Option<impl Iterator<Item = (ValidatorIndex, &ValidatorSignature)>> {
+	) -> Option<
+		impl Iterator<Item = (ValidatorIndex, &ValidDisputeStatementKind, &ValidatorSignature)>,
+	> {
 		match self {
-			Self::Voted(votes) => Some(votes.iter().filter_map(|(index, (kind, sig))| {
-				if let DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking) = kind {
-					Some((*index, sig))
-				} else {
-					None
-				}
-			})),
+			Self::Voted(votes) =>
+				Some(votes.iter().filter_map(|(index, (kind, sig))| match kind {
+					DisputeStatement::Valid(
+						kind @ (ValidDisputeStatementKind::ApprovalChecking |
+						ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(_)),
+					) => Some((*index, kind, sig)),
+					_ => None,
+				})),
 			Self::CannotVote => None,
 		}
 	}
@@ -277,7 +282,7 @@ impl CandidateVoteState<CandidateVotes> {
 				DisputeStatement::Valid(valid_kind) => {
 					let fresh = votes.valid.insert_vote(
 						val_index,
-						*valid_kind,
+						valid_kind.clone(),
 						statement.into_validator_signature(),
 					);
 					if fresh {
@@ -343,7 +348,9 @@ impl<V> CandidateVoteState<V> {
 	/// Own approval votes if any:
 	pub fn own_approval_votes(
 		&self,
-	) -> Option<impl Iterator<Item = (ValidatorIndex, &ValidatorSignature)>> {
+	) -> Option<
+		impl Iterator<Item = (ValidatorIndex, &ValidDisputeStatementKind, &ValidatorSignature)>,
+	> {
 		self.own_vote.approval_votes()
 	}
 
@@ -472,7 +479,7 @@ impl ImportResult {
 	pub fn import_approval_votes(
 		self,
 		env: &CandidateEnvironment,
-		approval_votes: HashMap<ValidatorIndex, ValidatorSignature>,
+		approval_votes: HashMap<ValidatorIndex, (ValidDisputeStatementKind, ValidatorSignature)>,
 		now: Timestamp,
 	) -> Self {
 		let Self {
@@ -486,19 +493,19 @@ impl ImportResult {
 
 		let (mut votes, _) = new_state.into_old_state();
 
-		for (index, sig) in approval_votes.into_iter() {
+		for (index, (kind, sig)) in approval_votes.into_iter() {
 			debug_assert!(
 				{
 					let pub_key = &env.session_info().validators.get(index).expect("indices are validated by approval-voting subsystem; qed");
 					let candidate_hash = votes.candidate_receipt.hash();
 					let session_index = env.session_index();
-					DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking)
+					DisputeStatement::Valid(kind.clone())
 						.check_signature(pub_key, candidate_hash, session_index, &sig)
 						.is_ok()
 				},
 				"Signature check for imported approval votes failed! This is a serious bug. Session: {:?}, candidate hash: {:?}, validator index: {:?}", env.session_index(), votes.candidate_receipt.hash(), index
 			);
-			if votes.valid.insert_vote(index, ValidDisputeStatementKind::ApprovalChecking, sig) {
+			if votes.valid.insert_vote(index, kind, sig) {
 				imported_valid_votes += 1;
 				imported_approval_votes += 1;
 			}
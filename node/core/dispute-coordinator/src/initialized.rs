@@ -401,7 +401,7 @@ impl Initialized {
 						};
 					debug_assert!(
 						SignedDisputeStatement::new_checked(
-							DisputeStatement::Valid(valid_statement_kind),
+							DisputeStatement::Valid(valid_statement_kind.clone()),
 							candidate_hash,
 							session,
 							validator_public.clone(),
@@ -947,7 +947,7 @@ impl Initialized {
 		// Also send any already existing approval vote on new disputes:
 		if import_result.is_freshly_disputed() {
 			let our_approval_votes = new_state.own_approval_votes().into_iter().flatten();
-			for (validator_index, sig) in our_approval_votes {
+			for (validator_index, kind, sig) in our_approval_votes {
 				let pub_key = match env.validators().get(validator_index) {
 					None => {
 						gum::error!(
@@ -961,7 +961,7 @@ impl Initialized {
 					Some(k) => k,
 				};
 				let statement = SignedDisputeStatement::new_unchecked_from_trusted_source(
-					DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking),
+					DisputeStatement::Valid(kind.clone()),
 					candidate_hash,
 					session,
 					pub_key.clone(),
@@ -63,6 +63,11 @@ pub(crate) mod backend;
 pub(crate) mod db;
 pub(crate) mod error;
 
+/// Dispute-coordinator DB migration helpers, run by the parachains DB upgrade.
+pub mod migration_helpers {
+	pub use crate::db::v1::{v1_sanity_check, Error};
+}
+
 /// Subsystem after receiving the first active leaf.
 mod initialized;
 use initialized::Initialized;
@@ -539,7 +544,7 @@ pub fn make_dispute_message(
 				.next()
 				.ok_or(DisputeMessageCreationError::NoOppositeVote)?;
 			let other_vote = SignedDisputeStatement::new_checked(
-				DisputeStatement::Valid(*statement_kind),
+				DisputeStatement::Valid(statement_kind.clone()),
 				*our_vote.candidate_hash(),
 				our_vote.session_index(),
 				validators
@@ -38,10 +38,14 @@ use polkadot_node_subsystem_util::{
 	request_availability_cores, request_persisted_validation_data, TimeoutExt,
 };
 use polkadot_primitives::{
-	BackedCandidate, BlockNumber, CandidateReceipt, CoreState, Hash, OccupiedCoreAssumption,
-	SignedAvailabilityBitfield, ValidatorIndex,
+	vstaging::{InherentWeight, InherentWeightInfo},
+	BackedCandidate, BlockNumber, CandidateReceipt, CoreState, Hash, Id as ParaId,
+	MultiDisputeStatementSet, OccupiedCoreAssumption, SignedAvailabilityBitfield, ValidatorIndex,
+};
+use std::{
+	cmp::Reverse,
+	collections::{BTreeMap, HashMap, HashSet},
 };
-use std::collections::{BTreeMap, HashMap};
 
 mod disputes;
 mod error;
@@ -371,7 +375,7 @@ async fn send_inherent_data(
 		"Selecting disputes"
 	);
 
-	let disputes = match has_required_runtime(
+	let mut disputes = match has_required_runtime(
 		from_job,
 		leaf.hash,
 		PRIORITIZED_SELECTION_RUNTIME_VERSION_REQUIREMENT,
@@ -382,6 +386,16 @@ async fn send_inherent_data(
 		false => disputes::random_selection::select_disputes(from_job, metrics).await,
 	};
 
+	// The inherent data is limited to the block weight in the same order the runtime limits it:
+	// disputes first, then bitfields and candidates. Without the weights the runtime is left to
+	// trim the inherent data.
+	let mut weight_budget = request_inherent_weight_info(from_job, leaf.hash)
+		.await
+		.map(InherentWeightBudget::new);
+	if let Some(ref mut weight_budget) = weight_budget {
+		metrics.on_weight_limited("disputes", weight_budget.limit_disputes(&mut disputes));
+	}
+
 	gum::trace!(
 		target: LOG_TARGET,
 		relay_parent = ?leaf.hash,
@@ -390,20 +404,29 @@ async fn send_inherent_data(
 
 	// Only include bitfields on fresh leaves. On chain reversions, we want to make sure that
 	// there will be at least one block, which cannot get disputed, so the chain can make progress.
-	let bitfields = match leaf.status {
+	let mut bitfields = match leaf.status {
 		LeafStatus::Fresh =>
 			select_availability_bitfields(&availability_cores, bitfields, &leaf.hash),
 		LeafStatus::Stale => Vec::new(),
 	};
 
+	// Candidates are selected based on the bitfields which make it into the block.
+	if let Some(ref mut weight_budget) = weight_budget {
+		metrics.on_weight_limited("bitfields", weight_budget.limit_bitfields(&mut bitfields));
+	}
+
 	gum::trace!(
 		target: LOG_TARGET,
 		relay_parent = ?leaf.hash,
 		"Selected bitfields"
 	);
-	let candidates =
+	let mut candidates =
 		select_candidates(&availability_cores, &bitfields, candidates, leaf.hash, from_job).await?;
 
+	if let Some(ref mut weight_budget) = weight_budget {
+		metrics.on_weight_limited("candidates", weight_budget.limit_candidates(&mut candidates));
+	}
+
 	gum::trace!(
 		target: LOG_TARGET,
 		relay_parent = ?leaf.hash,
@@ -529,6 +552,13 @@ async fn select_candidates(
 		"Candidate receipts (before selection)",
 	);
 
+	// The cores each para is scheduled on, in ascending order, along with the assumption
+	// about the first one. A para may be assigned multiple cores, which are filled with a chain of
+	// candidates building on top of each other.
+	let mut para_cores = BTreeMap::<ParaId, (OccupiedCoreAssumption, Vec<usize>)>::new();
+	// The runtime requires all candidates of a para to be freed before it accepts new ones.
+	let mut paras_not_freed = HashSet::new();
+
 	for (core_idx, core) in availability_cores.iter().enumerate() {
 		let (scheduled_core, assumption) = match core {
 			CoreState::Scheduled(scheduled_core) => (scheduled_core, OccupiedCoreAssumption::Free),
@@ -542,6 +572,7 @@ async fn select_candidates(
 					}
 				} else {
 					if occupied_core.time_out_at != block_number {
+						paras_not_freed.insert(occupied_core.para_id());
 						continue
 					}
 					if let Some(ref scheduled_core) = occupied_core.next_up_on_time_out {
@@ -554,42 +585,75 @@ async fn select_candidates(
 			CoreState::Free => continue,
 		};
 
-		let validation_data = match request_persisted_validation_data(
-			relay_parent,
-			scheduled_core.para_id,
-			assumption,
-			sender,
-		)
-		.await
-		.await
-		.map_err(|err| Error::CanceledPersistedValidationData(err))??
-		{
-			Some(v) => v,
-			None => continue,
-		};
+		para_cores
+			.entry(scheduled_core.para_id)
+			.or_insert_with(|| (assumption, Vec::new()))
+			.1
+			.push(core_idx);
+	}
 
-		let computed_validation_data_hash = validation_data.hash();
+	let mut selected = Vec::new();
+	for (para_id, (assumption, cores)) in para_cores {
+		if paras_not_freed.contains(&para_id) {
+			continue
+		}
 
-		// we arbitrarily pick the first of the backed candidates which match the appropriate selection criteria
-		if let Some(candidate) = candidates.iter().find(|backed_candidate| {
-			let descriptor = &backed_candidate.descriptor;
-			descriptor.para_id == scheduled_core.para_id &&
-				descriptor.persisted_validation_data_hash == computed_validation_data_hash
-		}) {
-			let candidate_hash = candidate.hash();
-			gum::trace!(
-				target: LOG_TARGET,
-				leaf_hash=?relay_parent,
-				?candidate_hash,
-				para = ?candidate.descriptor.para_id,
-				core = core_idx,
-				"Selected candidate receipt",
-			);
+		let mut validation_data =
+			match request_persisted_validation_data(relay_parent, para_id, assumption, sender)
+				.await
+				.await
+				.map_err(|err| Error::CanceledPersistedValidationData(err))??
+			{
+				Some(v) => v,
+				None => continue,
+			};
+
+		for (chain_idx, core_idx) in cores.into_iter().enumerate() {
+			// the candidates on further cores build on top of the previously selected one.
+			if chain_idx > 0 {
+				let (_, previous) = selected.last().expect("selected on the previous core; qed");
+				let (tx, rx) = oneshot::channel();
+				sender.send_unbounded_message(CandidateBackingMessage::GetBackedCandidates(
+					relay_parent,
+					vec![*previous],
+					tx,
+				));
+				let backed = rx.await.map_err(|err| Error::CanceledBackedCandidates(err))?;
+				match backed.into_iter().next() {
+					Some(backed) =>
+						validation_data.parent_head = backed.candidate.commitments.head_data,
+					None => break,
+				}
+			}
 
-			selected_candidates.push(candidate_hash);
+			let computed_validation_data_hash = validation_data.hash();
+
+			// we arbitrarily pick the first of the backed candidates which match the appropriate selection criteria
+			if let Some(candidate) = candidates.iter().find(|backed_candidate| {
+				let descriptor = &backed_candidate.descriptor;
+				descriptor.para_id == para_id &&
+					descriptor.persisted_validation_data_hash == computed_validation_data_hash
+			}) {
+				let candidate_hash = candidate.hash();
+				gum::trace!(
+					target: LOG_TARGET,
+					leaf_hash=?relay_parent,
+					?candidate_hash,
+					para = ?candidate.descriptor.para_id,
+					core = core_idx,
+					"Selected candidate receipt",
+				);
+
+				selected.push((core_idx, candidate_hash));
+			} else {
+				break
+			}
 		}
 	}
 
+	selected.sort_by_key(|(core_idx, _)| *core_idx);
+	selected_candidates.extend(selected.into_iter().map(|(_, candidate_hash)| candidate_hash));
+
 	// now get the backed candidates corresponding to these candidate receipts
 	let (tx, rx) = oneshot::channel();
 	sender.send_unbounded_message(CandidateBackingMessage::GetBackedCandidates(
@@ -617,11 +681,18 @@ async fn select_candidates(
 		Err(Error::BackedCandidateOrderingProblem)?;
 	}
 
-	// keep only one candidate with validation code.
+	// keep only one candidate with validation code, along with the candidates it is chained to.
 	let mut with_validation_code = false;
+	let mut broken_chains = HashSet::new();
 	candidates.retain(|c| {
+		let para_id = c.descriptor().para_id;
+		if broken_chains.contains(&para_id) {
+			return false
+		}
+
 		if c.candidate.commitments.new_validation_code.is_some() {
 			if with_validation_code {
+				broken_chains.insert(para_id);
 				return false
 			}
 
@@ -642,6 +713,168 @@ async fn select_candidates(
 	Ok(candidates)
 }
 
+/// The weight left for the `paras_inherent`, used to pick inherent data which fits into the block
+/// without being trimmed by the runtime.
+///
+/// Uses the same weight functions as the runtime, so the weight accounted for here is exactly the
+/// weight the runtime accounts for the inherent data.
+struct InherentWeightBudget {
+	info: InherentWeightInfo,
+	remaining: InherentWeight,
+}
+
+impl InherentWeightBudget {
+	fn new(info: InherentWeightInfo) -> Self {
+		Self { remaining: info.max_block, info }
+	}
+
+	/// Consume `weight` from the budget, if it fits.
+	fn try_consume(&mut self, weight: InherentWeight) -> bool {
+		match self.remaining.checked_sub(weight) {
+			Some(remaining) => {
+				self.remaining = remaining;
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Keep the dispute statement sets which fit, preferring them in the order they were selected.
+	///
+	/// Returns the number of dispute statement sets dropped.
+	fn limit_disputes(&mut self, disputes: &mut MultiDisputeStatementSet) -> usize {
+		let before = disputes.len();
+		disputes.retain(|set| self.try_consume(self.info.dispute_statement_set_weight(set)));
+		before - disputes.len()
+	}
+
+	/// Keep the bitfields which fit, preferring the ones with the most bits set to maximize
+	/// availability. The order of the bitfields is preserved.
+	///
+	/// Returns the number of bitfields dropped.
+	fn limit_bitfields(&mut self, bitfields: &mut Vec<SignedAvailabilityBitfield>) -> usize {
+		if self.try_consume(self.info.bitfields_weight(bitfields.len())) {
+			return 0
+		}
+
+		let mut by_bits_set = (0..bitfields.len()).collect::<Vec<_>>();
+		by_bits_set.sort_by_key(|&idx| Reverse(bitfields[idx].payload().0.count_ones()));
+
+		let mut picked = vec![false; bitfields.len()];
+		for idx in by_bits_set {
+			if !self.try_consume(self.info.bitfields_weight(1)) {
+				break
+			}
+			picked[idx] = true;
+		}
+
+		retain_picked(bitfields, &picked)
+	}
+
+	/// Keep the backed candidates which fit, maximizing the number of candidates included.
+	///
+	/// The candidates of a para are chained, so only a prefix of each chain can be kept. Chains are
+	/// filled up level by level, so every para gets a candidate in before any gets a second one.
+	/// On each level code upgrades are preferred, as they tend to be large and stand no chance to
+	/// be picked late, and the remaining candidates are picked from the lightest. The order of the
+	/// candidates is preserved.
+	///
+	/// Returns the number of candidates dropped.
+	fn limit_candidates(&mut self, candidates: &mut Vec<BackedCandidate>) -> usize {
+		let weights = candidates
+			.iter()
+			.map(|candidate| self.info.backed_candidate_weight(candidate))
+			.collect::<Vec<_>>();
+		let total = weights.iter().fold(InherentWeight::default(), |acc, w| acc.saturating_add(*w));
+		if self.try_consume(total) {
+			return 0
+		}
+
+		let mut chain_lengths = HashMap::<ParaId, usize>::new();
+		let chain_positions = candidates
+			.iter()
+			.map(|candidate| {
+				let chain_length = chain_lengths.entry(candidate.descriptor().para_id).or_default();
+				*chain_length += 1;
+				*chain_length - 1
+			})
+			.collect::<Vec<_>>();
+
+		let mut by_preference = (0..candidates.len()).collect::<Vec<_>>();
+		by_preference.sort_by_key(|&idx| {
+			(
+				chain_positions[idx],
+				candidates[idx].candidate.commitments.new_validation_code.is_none(),
+				weights[idx].ref_time,
+				weights[idx].proof_size,
+			)
+		});
+
+		let mut picked = vec![false; candidates.len()];
+		let mut broken_chains = HashSet::new();
+		for idx in by_preference {
+			let para_id = candidates[idx].descriptor().para_id;
+			if broken_chains.contains(&para_id) {
+				continue
+			}
+
+			if self.try_consume(weights[idx]) {
+				picked[idx] = true;
+			} else {
+				broken_chains.insert(para_id);
+			}
+		}
+
+		retain_picked(candidates, &picked)
+	}
+}
+
+/// Retain the items marked as picked, returning the number of items dropped.
+fn retain_picked<T>(items: &mut Vec<T>, picked: &[bool]) -> usize {
+	let before = items.len();
+	let mut picked = picked.iter();
+	items.retain(|_| picked.next().copied().unwrap_or(false));
+	before - items.len()
+}
+
+/// Fetch the weight parameters of the `paras_inherent`, if the runtime exposes them.
+async fn request_inherent_weight_info(
+	sender: &mut impl overseer::ProvisionerSenderTrait,
+	relay_parent: Hash,
+) -> Option<InherentWeightInfo> {
+	if !has_required_runtime(
+		sender,
+		relay_parent,
+		RuntimeApiRequest::INHERENT_WEIGHT_INFO_RUNTIME_REQUIREMENT,
+	)
+	.await
+	{
+		return None
+	}
+
+	let (tx, rx) = oneshot::channel();
+	sender
+		.send_message(RuntimeApiMessage::Request(
+			relay_parent,
+			RuntimeApiRequest::InherentWeightInfo(tx),
+		))
+		.await;
+
+	match rx.await {
+		Ok(Ok(info)) => Some(info),
+		Ok(Err(error)) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?relay_parent,
+				?error,
+				"Failed to fetch inherent weight info, leaving weight limiting to the runtime"
+			);
+			None
+		},
+		Err(_) => None,
+	}
+}
+
 /// Produces a block number 1 higher than that of the relay parent
 /// in the event of an invalid `relay_parent`, returns `Ok(0)`
 async fn get_block_number_under_construction(
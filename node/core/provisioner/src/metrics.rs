@@ -36,6 +36,9 @@ struct MetricsInner {
 
 	/// The disputes received from `disputes-coordinator` by partition
 	partitioned_disputes: prometheus::CounterVec<prometheus::U64>,
+
+	/// Inherent data left out to fit into the block weight, by kind
+	inherent_data_weight_limited: prometheus::CounterVec<prometheus::U64>,
 }
 
 /// Provisioner metrics.
@@ -106,6 +109,15 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_weight_limited(&self, kind: &str, dropped: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.inherent_data_weight_limited
+				.with_label_values(&[kind])
+				.inc_by(dropped.try_into().unwrap_or(0));
+		}
+	}
+
 	pub(crate) fn on_partition_recent_disputes(&self, disputes: &PartitionedDisputes) {
 		if let Some(metrics) = &self.0 {
 			let PartitionedDisputes {
@@ -208,6 +220,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				&registry,
 			)?,
+			inherent_data_weight_limited: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_provisioner_inherent_data_weight_limited",
+						"Number of inherent data items left out to fit into the block weight.",
+					),
+					&["kind"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
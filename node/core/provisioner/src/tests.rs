@@ -46,7 +46,7 @@ mod select_availability_bitfields {
 	use sp_keystore::{testing::KeyStore, CryptoStore, SyncCryptoStorePtr};
 	use std::sync::Arc;
 
-	async fn signed_bitfield(
+	pub(super) async fn signed_bitfield(
 		keystore: &SyncCryptoStorePtr,
 		field: CoreAvailability,
 		validator_idx: ValidatorIndex,
@@ -496,4 +496,252 @@ mod select_candidates {
 			},
 		)
 	}
+
+	#[test]
+	fn selects_chained_candidates_for_multiple_cores() {
+		use polkadot_primitives::HeadData;
+
+		// para 1 is assigned two cores, para 2 one.
+		let mock_cores = vec![
+			CoreState::Scheduled(scheduled_core(1)),
+			CoreState::Scheduled(scheduled_core(2)),
+			CoreState::Scheduled(scheduled_core(1)),
+		];
+
+		let make_receipt = |para_id: u32, parent_head: HeadData, head_data: HeadData| {
+			let validation_data =
+				PersistedValidationData::<Hash, BlockNumber> { parent_head, ..Default::default() };
+			let mut descriptor = dummy_candidate_descriptor(dummy_hash());
+			descriptor.para_id = para_id.into();
+			descriptor.persisted_validation_data_hash = validation_data.hash();
+			CommittedCandidateReceipt {
+				descriptor,
+				commitments: CandidateCommitments { head_data, ..Default::default() },
+			}
+		};
+
+		let receipt_a = make_receipt(1, Default::default(), vec![1].into());
+		let receipt_b = make_receipt(2, Default::default(), vec![2].into());
+		// builds on top of candidate A.
+		let receipt_c = make_receipt(1, vec![1].into(), vec![3].into());
+		// builds on top of a candidate which isn't backed.
+		let receipt_d = make_receipt(1, vec![4].into(), vec![5].into());
+
+		let backed: Vec<_> = [&receipt_a, &receipt_b, &receipt_c, &receipt_d]
+			.iter()
+			.map(|&receipt| BackedCandidate {
+				candidate: receipt.clone(),
+				validity_votes: Vec::new(),
+				validator_indices: default_bitvec(mock_cores.len()),
+			})
+			.collect();
+
+		let candidates: Vec<_> = [&receipt_d, &receipt_c, &receipt_b, &receipt_a]
+			.iter()
+			.map(|receipt| receipt.to_plain())
+			.collect();
+
+		let mock_overseer = |mut receiver: mpsc::UnboundedReceiver<AllMessages>| async move {
+			while let Some(from_job) = receiver.next().await {
+				match from_job {
+					AllMessages::ChainApi(ChainApiMessage::BlockNumber(_, tx)) =>
+						tx.send(Ok(Some(BLOCK_UNDER_PRODUCTION - 1))).unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_,
+						PersistedValidationDataReq(_, _, tx),
+					)) => tx.send(Ok(Some(Default::default()))).unwrap(),
+					AllMessages::CandidateBacking(
+						CandidateBackingMessage::GetBackedCandidates(_, hashes, sender),
+					) => {
+						let _ = sender.send(
+							hashes
+								.iter()
+								.filter_map(|hash| backed.iter().find(|b| &b.hash() == hash))
+								.cloned()
+								.collect(),
+						);
+					},
+					_ => panic!("Unexpected message: {:?}", from_job),
+				}
+			}
+		};
+
+		test_harness(mock_overseer, |mut tx: TestSubsystemSender| async move {
+			let result =
+				select_candidates(&mock_cores, &[], &candidates, Default::default(), &mut tx)
+					.await
+					.unwrap();
+
+			// ordered by the cores the candidates occupy.
+			assert_eq!(
+				result.into_iter().map(|c| c.hash()).collect::<Vec<_>>(),
+				vec![receipt_a.hash(), receipt_b.hash(), receipt_c.hash()],
+			);
+		})
+	}
+}
+
+mod limit_to_block_weight {
+	use super::{super::*, default_bitvec, select_availability_bitfields::signed_bitfield};
+	use ::test_helpers::{dummy_candidate_descriptor, dummy_hash, dummy_signature};
+	use bitvec::bitvec;
+	use futures::executor::block_on;
+	use polkadot_primitives::{
+		CandidateCommitments, CommittedCandidateReceipt, DisputeStatement, DisputeStatementSet,
+		ValidDisputeStatementKind, ValidityAttestation,
+	};
+	use sp_keystore::{testing::KeyStore, SyncCryptoStorePtr};
+	use std::sync::Arc;
+
+	fn weight(ref_time: u64) -> InherentWeight {
+		InherentWeight { ref_time, proof_size: 0 }
+	}
+
+	fn weight_info(max_block: u64) -> InherentWeightInfo {
+		InherentWeightInfo {
+			max_block: weight(max_block),
+			dispute_statement_set: weight(2),
+			dispute_statement: weight(1),
+			bitfield: weight(1),
+			backed_candidate: weight(1),
+			validity_vote: weight(1),
+			backed_candidate_code_upgrade: weight(5),
+		}
+	}
+
+	fn backed_candidate(
+		para_id: u32,
+		head_data: u8,
+		votes: usize,
+		code_upgrade: bool,
+	) -> BackedCandidate {
+		let mut descriptor = dummy_candidate_descriptor(dummy_hash());
+		descriptor.para_id = para_id.into();
+		BackedCandidate {
+			candidate: CommittedCandidateReceipt {
+				descriptor,
+				commitments: CandidateCommitments {
+					new_validation_code: code_upgrade.then(|| vec![].into()),
+					head_data: vec![head_data].into(),
+					..Default::default()
+				},
+			},
+			validity_votes: vec![ValidityAttestation::Implicit(dummy_signature()); votes],
+			validator_indices: default_bitvec(votes),
+		}
+	}
+
+	fn dispute_statement_set(statements: usize) -> DisputeStatementSet {
+		DisputeStatementSet {
+			candidate_hash: Default::default(),
+			session: 1,
+			statements: (0..statements)
+				.map(|i| {
+					(
+						DisputeStatement::Valid(ValidDisputeStatementKind::Explicit),
+						ValidatorIndex(i as u32),
+						dummy_signature(),
+					)
+				})
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn keeps_everything_that_fits() {
+		let mut budget = InherentWeightBudget::new(weight_info(100));
+
+		let mut disputes = vec![dispute_statement_set(3)];
+		let mut candidates =
+			vec![backed_candidate(1, 1, 2, false), backed_candidate(2, 1, 0, true)];
+
+		assert_eq!(budget.limit_disputes(&mut disputes), 0);
+		assert_eq!(budget.limit_candidates(&mut candidates), 0);
+		assert_eq!(disputes.len(), 1);
+		assert_eq!(candidates.len(), 2);
+		assert_eq!(budget.remaining, weight(100 - 5 - 3 - 5));
+	}
+
+	#[test]
+	fn disputes_are_limited_before_the_rest() {
+		let mut budget = InherentWeightBudget::new(weight_info(10));
+
+		// weigh 6, 8 and 4.
+		let mut disputes =
+			vec![dispute_statement_set(4), dispute_statement_set(6), dispute_statement_set(2)];
+
+		assert_eq!(budget.limit_disputes(&mut disputes), 1);
+		assert_eq!(disputes.iter().map(|set| set.statements.len()).collect::<Vec<_>>(), vec![4, 2]);
+
+		// nothing is left for candidates.
+		let mut candidates = vec![backed_candidate(1, 1, 0, false)];
+		assert_eq!(budget.limit_candidates(&mut candidates), 1);
+		assert!(candidates.is_empty());
+	}
+
+	#[test]
+	fn bitfields_with_most_bits_set_are_kept() {
+		let keystore: SyncCryptoStorePtr = Arc::new(KeyStore::new());
+		let bitfields = vec![
+			block_on(signed_bitfield(
+				&keystore,
+				bitvec![u8, bitvec::order::Lsb0; 1, 0, 0],
+				ValidatorIndex(0),
+			)),
+			block_on(signed_bitfield(
+				&keystore,
+				bitvec![u8, bitvec::order::Lsb0; 1, 1, 1],
+				ValidatorIndex(1),
+			)),
+			block_on(signed_bitfield(
+				&keystore,
+				bitvec![u8, bitvec::order::Lsb0; 0, 1, 1],
+				ValidatorIndex(2),
+			)),
+		];
+
+		let mut budget = InherentWeightBudget::new(weight_info(2));
+		let mut limited = bitfields.clone();
+		assert_eq!(budget.limit_bitfields(&mut limited), 1);
+
+		// ordered by validator index.
+		assert_eq!(limited, vec![bitfields[1].clone(), bitfields[2].clone()]);
+	}
+
+	#[test]
+	fn candidate_chains_are_cut_off_at_the_weight_limit() {
+		// para 1 has a chain of two candidates, weighing 2 each. Para 2's candidate weighs 5, as
+		// does the code upgrade of para 3.
+		let candidates = vec![
+			backed_candidate(1, 1, 1, false),
+			backed_candidate(2, 1, 4, false),
+			backed_candidate(1, 2, 1, false),
+			backed_candidate(3, 1, 2, true),
+		];
+
+		// the code upgrade is preferred and then the first candidates of the paras, starting
+		// with the lightest.
+		let mut budget = InherentWeightBudget::new(weight_info(12));
+		let mut limited = candidates.clone();
+		assert_eq!(budget.limit_candidates(&mut limited), 1);
+		assert_eq!(
+			limited.iter().map(|c| c.hash()).collect::<Vec<_>>(),
+			vec![candidates[0].hash(), candidates[1].hash(), candidates[3].hash()],
+		);
+
+		// para 2's candidate doesn't fit anymore, which leaves space for para 1's chain.
+		let mut budget = InherentWeightBudget::new(weight_info(10));
+		let mut limited = candidates.clone();
+		assert_eq!(budget.limit_candidates(&mut limited), 1);
+		assert_eq!(
+			limited.iter().map(|c| c.hash()).collect::<Vec<_>>(),
+			vec![candidates[0].hash(), candidates[2].hash(), candidates[3].hash()],
+		);
+
+		// the second candidate of a chain is never kept without the first.
+		let mut budget = InherentWeightBudget::new(weight_info(1));
+		let mut limited = candidates.clone();
+		assert_eq!(budget.limit_candidates(&mut limited), 4);
+		assert!(limited.is_empty());
+	}
 }
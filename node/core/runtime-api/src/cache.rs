@@ -19,7 +19,9 @@ use std::{collections::btree_map::BTreeMap, num::NonZeroUsize};
 use lru::LruCache;
 use sp_consensus_babe::Epoch;
 
+use polkadot_node_subsystem::messages::RuntimeApiRequest as Request;
 use polkadot_primitives::{
+	vstaging::{ApprovalVotingParams, BackingMisbehaviorReport, InherentWeightInfo},
 	AuthorityDiscoveryId, BlockNumber, CandidateCommitments, CandidateEvent, CandidateHash,
 	CommittedCandidateReceipt, CoreState, DisputeState, GroupRotationInfo, Hash, Id as ParaId,
 	InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption, PersistedValidationData,
@@ -27,7 +29,7 @@ use polkadot_primitives::{
 	ValidationCodeHash, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 
-/// For consistency we have the same capacity for all caches. We use 128 as we'll only need that
+/// By default we have the same capacity for all caches. We use 128 as we'll only need that
 /// much if finality stalls (we only query state for unfinalized blocks + maybe latest finalized).
 /// In any case, a cache is an optimization. We should avoid a situation where having a large cache
 /// leads to OOM or puts pressure on other important stuff like PVF execution/preparation.
@@ -36,6 +38,87 @@ const DEFAULT_CACHE_CAP: NonZeroUsize = match NonZeroUsize::new(128) {
 	None => panic!("lru capacity must be non-zero"),
 };
 
+/// The number of results cached per type of runtime API request.
+#[derive(Debug, Clone)]
+pub struct CacheCapacities {
+	/// Results of `Authorities` requests.
+	pub authorities: NonZeroUsize,
+	/// Results of `Validators` requests.
+	pub validators: NonZeroUsize,
+	/// Results of `ValidatorGroups` requests.
+	pub validator_groups: NonZeroUsize,
+	/// Results of `AvailabilityCores` requests.
+	pub availability_cores: NonZeroUsize,
+	/// Results of `PersistedValidationData` requests.
+	pub persisted_validation_data: NonZeroUsize,
+	/// Results of `AssumedValidationData` requests.
+	pub assumed_validation_data: NonZeroUsize,
+	/// Results of `CheckValidationOutputs` requests.
+	pub check_validation_outputs: NonZeroUsize,
+	/// Results of `SessionIndexForChild` requests.
+	pub session_index_for_child: NonZeroUsize,
+	/// Results of `ValidationCode` requests.
+	pub validation_code: NonZeroUsize,
+	/// Results of `ValidationCodeByHash` requests.
+	pub validation_code_by_hash: NonZeroUsize,
+	/// Results of `CandidatePendingAvailability` requests.
+	pub candidate_pending_availability: NonZeroUsize,
+	/// Results of `CandidateEvents` requests.
+	pub candidate_events: NonZeroUsize,
+	/// Results of `SessionInfo` requests.
+	pub session_info: NonZeroUsize,
+	/// Results of `DmqContents` requests.
+	pub dmq_contents: NonZeroUsize,
+	/// Results of `InboundHrmpChannelsContents` requests.
+	pub inbound_hrmp_channels_contents: NonZeroUsize,
+	/// Results of `CurrentBabeEpoch` requests.
+	pub current_babe_epoch: NonZeroUsize,
+	/// Results of `FetchOnChainVotes` requests.
+	pub on_chain_votes: NonZeroUsize,
+	/// Results of `PvfsRequirePrecheck` requests.
+	pub pvfs_require_precheck: NonZeroUsize,
+	/// Results of `ValidationCodeHash` requests.
+	pub validation_code_hash: NonZeroUsize,
+	/// Results of `Version` requests.
+	pub version: NonZeroUsize,
+	/// Results of `Disputes` requests.
+	pub disputes: NonZeroUsize,
+	/// Results of `ApprovalVotingParams` requests.
+	pub approval_voting_params: NonZeroUsize,
+	/// Results of `InherentWeightInfo` requests.
+	pub inherent_weight_info: NonZeroUsize,
+}
+
+impl Default for CacheCapacities {
+	fn default() -> Self {
+		Self {
+			authorities: DEFAULT_CACHE_CAP,
+			validators: DEFAULT_CACHE_CAP,
+			validator_groups: DEFAULT_CACHE_CAP,
+			availability_cores: DEFAULT_CACHE_CAP,
+			persisted_validation_data: DEFAULT_CACHE_CAP,
+			assumed_validation_data: DEFAULT_CACHE_CAP,
+			check_validation_outputs: DEFAULT_CACHE_CAP,
+			session_index_for_child: DEFAULT_CACHE_CAP,
+			validation_code: DEFAULT_CACHE_CAP,
+			validation_code_by_hash: DEFAULT_CACHE_CAP,
+			candidate_pending_availability: DEFAULT_CACHE_CAP,
+			candidate_events: DEFAULT_CACHE_CAP,
+			session_info: DEFAULT_CACHE_CAP,
+			dmq_contents: DEFAULT_CACHE_CAP,
+			inbound_hrmp_channels_contents: DEFAULT_CACHE_CAP,
+			current_babe_epoch: DEFAULT_CACHE_CAP,
+			on_chain_votes: DEFAULT_CACHE_CAP,
+			pvfs_require_precheck: DEFAULT_CACHE_CAP,
+			validation_code_hash: DEFAULT_CACHE_CAP,
+			version: DEFAULT_CACHE_CAP,
+			disputes: DEFAULT_CACHE_CAP,
+			approval_voting_params: DEFAULT_CACHE_CAP,
+			inherent_weight_info: DEFAULT_CACHE_CAP,
+		}
+	}
+}
+
 pub(crate) struct RequestResultCache {
 	authorities: LruCache<Hash, Vec<AuthorityDiscoveryId>>,
 	validators: LruCache<Hash, Vec<ValidatorId>>,
@@ -62,37 +145,43 @@ pub(crate) struct RequestResultCache {
 		LruCache<(Hash, ParaId, OccupiedCoreAssumption), Option<ValidationCodeHash>>,
 	version: LruCache<Hash, u32>,
 	disputes: LruCache<Hash, Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>>,
+	approval_voting_params: LruCache<Hash, ApprovalVotingParams>,
+	inherent_weight_info: LruCache<Hash, InherentWeightInfo>,
 }
 
-impl Default for RequestResultCache {
-	fn default() -> Self {
+impl RequestResultCache {
+	pub(crate) fn new(capacities: &CacheCapacities) -> Self {
 		Self {
-			authorities: LruCache::new(DEFAULT_CACHE_CAP),
-			validators: LruCache::new(DEFAULT_CACHE_CAP),
-			validator_groups: LruCache::new(DEFAULT_CACHE_CAP),
-			availability_cores: LruCache::new(DEFAULT_CACHE_CAP),
-			persisted_validation_data: LruCache::new(DEFAULT_CACHE_CAP),
-			assumed_validation_data: LruCache::new(DEFAULT_CACHE_CAP),
-			check_validation_outputs: LruCache::new(DEFAULT_CACHE_CAP),
-			session_index_for_child: LruCache::new(DEFAULT_CACHE_CAP),
-			validation_code: LruCache::new(DEFAULT_CACHE_CAP),
-			validation_code_by_hash: LruCache::new(DEFAULT_CACHE_CAP),
-			candidate_pending_availability: LruCache::new(DEFAULT_CACHE_CAP),
-			candidate_events: LruCache::new(DEFAULT_CACHE_CAP),
-			session_info: LruCache::new(DEFAULT_CACHE_CAP),
-			dmq_contents: LruCache::new(DEFAULT_CACHE_CAP),
-			inbound_hrmp_channels_contents: LruCache::new(DEFAULT_CACHE_CAP),
-			current_babe_epoch: LruCache::new(DEFAULT_CACHE_CAP),
-			on_chain_votes: LruCache::new(DEFAULT_CACHE_CAP),
-			pvfs_require_precheck: LruCache::new(DEFAULT_CACHE_CAP),
-			validation_code_hash: LruCache::new(DEFAULT_CACHE_CAP),
-			version: LruCache::new(DEFAULT_CACHE_CAP),
-			disputes: LruCache::new(DEFAULT_CACHE_CAP),
+			authorities: LruCache::new(capacities.authorities),
+			validators: LruCache::new(capacities.validators),
+			validator_groups: LruCache::new(capacities.validator_groups),
+			availability_cores: LruCache::new(capacities.availability_cores),
+			persisted_validation_data: LruCache::new(capacities.persisted_validation_data),
+			assumed_validation_data: LruCache::new(capacities.assumed_validation_data),
+			check_validation_outputs: LruCache::new(capacities.check_validation_outputs),
+			session_index_for_child: LruCache::new(capacities.session_index_for_child),
+			validation_code: LruCache::new(capacities.validation_code),
+			validation_code_by_hash: LruCache::new(capacities.validation_code_by_hash),
+			candidate_pending_availability: LruCache::new(
+				capacities.candidate_pending_availability,
+			),
+			candidate_events: LruCache::new(capacities.candidate_events),
+			session_info: LruCache::new(capacities.session_info),
+			dmq_contents: LruCache::new(capacities.dmq_contents),
+			inbound_hrmp_channels_contents: LruCache::new(
+				capacities.inbound_hrmp_channels_contents,
+			),
+			current_babe_epoch: LruCache::new(capacities.current_babe_epoch),
+			on_chain_votes: LruCache::new(capacities.on_chain_votes),
+			pvfs_require_precheck: LruCache::new(capacities.pvfs_require_precheck),
+			validation_code_hash: LruCache::new(capacities.validation_code_hash),
+			version: LruCache::new(capacities.version),
+			disputes: LruCache::new(capacities.disputes),
+			approval_voting_params: LruCache::new(capacities.approval_voting_params),
+			inherent_weight_info: LruCache::new(capacities.inherent_weight_info),
 		}
 	}
-}
 
-impl RequestResultCache {
 	pub(crate) fn authorities(
 		&mut self,
 		relay_parent: &Hash,
@@ -368,6 +457,36 @@ impl RequestResultCache {
 	) {
 		self.disputes.put(relay_parent, value);
 	}
+
+	pub(crate) fn approval_voting_params(
+		&mut self,
+		relay_parent: &Hash,
+	) -> Option<&ApprovalVotingParams> {
+		self.approval_voting_params.get(relay_parent)
+	}
+
+	pub(crate) fn cache_approval_voting_params(
+		&mut self,
+		relay_parent: Hash,
+		value: ApprovalVotingParams,
+	) {
+		self.approval_voting_params.put(relay_parent, value);
+	}
+
+	pub(crate) fn inherent_weight_info(
+		&mut self,
+		relay_parent: &Hash,
+	) -> Option<&InherentWeightInfo> {
+		self.inherent_weight_info.get(relay_parent)
+	}
+
+	pub(crate) fn cache_inherent_weight_info(
+		&mut self,
+		relay_parent: Hash,
+		value: InherentWeightInfo,
+	) {
+		self.inherent_weight_info.put(relay_parent, value);
+	}
 }
 
 pub(crate) enum RequestResult {
@@ -404,4 +523,112 @@ pub(crate) enum RequestResult {
 	ValidationCodeHash(Hash, ParaId, OccupiedCoreAssumption, Option<ValidationCodeHash>),
 	Version(Hash, u32),
 	Disputes(Hash, Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>),
+	ApprovalVotingParams(Hash, ApprovalVotingParams),
+	// This is a request with side-effects and no result, hence ().
+	SubmitBackingMisbehaviorReport(Hash, BackingMisbehaviorReport, ValidatorSignature, ()),
+	InherentWeightInfo(Hash, InherentWeightInfo),
+}
+
+/// Identifies requests which are answered by the same runtime API call, so concurrent ones can
+/// share a single execution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum RequestKey {
+	// The structure of each variant is (relay_parent, [params,]*)
+	Authorities(Hash),
+	Validators(Hash),
+	ValidatorGroups(Hash),
+	AvailabilityCores(Hash),
+	PersistedValidationData(Hash, ParaId, OccupiedCoreAssumption),
+	AssumedValidationData(Hash, ParaId, Hash),
+	CheckValidationOutputs(Hash, ParaId, CandidateCommitments),
+	SessionIndexForChild(Hash),
+	ValidationCode(Hash, ParaId, OccupiedCoreAssumption),
+	ValidationCodeByHash(Hash, ValidationCodeHash),
+	CandidatePendingAvailability(Hash, ParaId),
+	CandidateEvents(Hash),
+	SessionInfo(Hash, SessionIndex),
+	DmqContents(Hash, ParaId),
+	InboundHrmpChannelsContents(Hash, ParaId),
+	CurrentBabeEpoch(Hash),
+	FetchOnChainVotes(Hash),
+	PvfsRequirePrecheck(Hash),
+	ValidationCodeHash(Hash, ParaId, OccupiedCoreAssumption),
+	Version(Hash),
+	Disputes(Hash),
+	ApprovalVotingParams(Hash),
+	InherentWeightInfo(Hash),
+}
+
+impl RequestKey {
+	/// The key of a request, or `None` for side-effecting requests which must not be shared.
+	pub(crate) fn new(relay_parent: Hash, request: &Request) -> Option<Self> {
+		Some(match request {
+			Request::Authorities(_) => RequestKey::Authorities(relay_parent),
+			Request::Validators(_) => RequestKey::Validators(relay_parent),
+			Request::ValidatorGroups(_) => RequestKey::ValidatorGroups(relay_parent),
+			Request::AvailabilityCores(_) => RequestKey::AvailabilityCores(relay_parent),
+			Request::PersistedValidationData(para, assumption, _) =>
+				RequestKey::PersistedValidationData(relay_parent, *para, *assumption),
+			Request::AssumedValidationData(para, expected_persisted_validation_data_hash, _) =>
+				RequestKey::AssumedValidationData(
+					relay_parent,
+					*para,
+					*expected_persisted_validation_data_hash,
+				),
+			Request::CheckValidationOutputs(para, commitments, _) =>
+				RequestKey::CheckValidationOutputs(relay_parent, *para, commitments.clone()),
+			Request::SessionIndexForChild(_) => RequestKey::SessionIndexForChild(relay_parent),
+			Request::ValidationCode(para, assumption, _) =>
+				RequestKey::ValidationCode(relay_parent, *para, *assumption),
+			Request::ValidationCodeByHash(validation_code_hash, _) =>
+				RequestKey::ValidationCodeByHash(relay_parent, *validation_code_hash),
+			Request::CandidatePendingAvailability(para, _) =>
+				RequestKey::CandidatePendingAvailability(relay_parent, *para),
+			Request::CandidateEvents(_) => RequestKey::CandidateEvents(relay_parent),
+			Request::SessionInfo(index, _) => RequestKey::SessionInfo(relay_parent, *index),
+			Request::DmqContents(para, _) => RequestKey::DmqContents(relay_parent, *para),
+			Request::InboundHrmpChannelsContents(para, _) =>
+				RequestKey::InboundHrmpChannelsContents(relay_parent, *para),
+			Request::CurrentBabeEpoch(_) => RequestKey::CurrentBabeEpoch(relay_parent),
+			Request::FetchOnChainVotes(_) => RequestKey::FetchOnChainVotes(relay_parent),
+			Request::PvfsRequirePrecheck(_) => RequestKey::PvfsRequirePrecheck(relay_parent),
+			Request::SubmitPvfCheckStatement(..) => return None,
+			Request::ValidationCodeHash(para, assumption, _) =>
+				RequestKey::ValidationCodeHash(relay_parent, *para, *assumption),
+			Request::Version(_) => RequestKey::Version(relay_parent),
+			Request::Disputes(_) => RequestKey::Disputes(relay_parent),
+			Request::ApprovalVotingParams(_) => RequestKey::ApprovalVotingParams(relay_parent),
+			Request::SubmitBackingMisbehaviorReport(..) => return None,
+			Request::InherentWeightInfo(_) => RequestKey::InherentWeightInfo(relay_parent),
+		})
+	}
+
+	/// The type of the request, as used for the cache metrics.
+	pub(crate) fn request_type(&self) -> &'static str {
+		match self {
+			RequestKey::Authorities(..) => "authorities",
+			RequestKey::Validators(..) => "validators",
+			RequestKey::ValidatorGroups(..) => "validator_groups",
+			RequestKey::AvailabilityCores(..) => "availability_cores",
+			RequestKey::PersistedValidationData(..) => "persisted_validation_data",
+			RequestKey::AssumedValidationData(..) => "assumed_validation_data",
+			RequestKey::CheckValidationOutputs(..) => "check_validation_outputs",
+			RequestKey::SessionIndexForChild(..) => "session_index_for_child",
+			RequestKey::ValidationCode(..) => "validation_code",
+			RequestKey::ValidationCodeByHash(..) => "validation_code_by_hash",
+			RequestKey::CandidatePendingAvailability(..) => "candidate_pending_availability",
+			RequestKey::CandidateEvents(..) => "candidate_events",
+			RequestKey::SessionInfo(..) => "session_info",
+			RequestKey::DmqContents(..) => "dmq_contents",
+			RequestKey::InboundHrmpChannelsContents(..) => "inbound_hrmp_channels_contents",
+			RequestKey::CurrentBabeEpoch(..) => "current_babe_epoch",
+			RequestKey::FetchOnChainVotes(..) => "on_chain_votes",
+			RequestKey::PvfsRequirePrecheck(..) => "pvfs_require_precheck",
+			RequestKey::ValidationCodeHash(..) => "validation_code_hash",
+			RequestKey::Version(..) => "version",
+			RequestKey::Disputes(..) => "disputes",
+			RequestKey::ApprovalVotingParams(..) => "approval_voting_params",
+			RequestKey::InherentWeightInfo(..) => "inherent_weight_info",
+		}
+	}
 }
@@ -30,11 +30,12 @@ use polkadot_node_subsystem::{
 use polkadot_node_subsystem_types::RuntimeApiSubsystemClient;
 use polkadot_primitives::Hash;
 
-use cache::{RequestResult, RequestResultCache};
-use futures::{channel::oneshot, prelude::*, select, stream::FuturesUnordered};
-use std::sync::Arc;
+use cache::{RequestKey, RequestResult, RequestResultCache};
+use futures::{channel::oneshot, future::BoxFuture, prelude::*, select, stream::FuturesUnordered};
+use std::{collections::HashMap, sync::Arc};
 
 mod cache;
+pub use cache::CacheCapacities;
 
 mod metrics;
 use self::metrics::Metrics;
@@ -57,7 +58,10 @@ pub struct RuntimeApiSubsystem<Client> {
 	metrics: Metrics,
 	spawn_handle: Box<dyn overseer::gen::Spawner>,
 	/// All the active runtime API requests that are currently being executed.
-	active_requests: FuturesUnordered<oneshot::Receiver<Option<RequestResult>>>,
+	active_requests:
+		FuturesUnordered<BoxFuture<'static, (Option<RequestKey>, Option<RequestResult>)>>,
+	/// Requests waiting for an identical request which is currently being executed.
+	waiting_requests: HashMap<RequestKey, Vec<(Hash, Request)>>,
 	/// Requests results cache
 	requests_cache: RequestResultCache,
 }
@@ -68,13 +72,24 @@ impl<Client> RuntimeApiSubsystem<Client> {
 		client: Arc<Client>,
 		metrics: Metrics,
 		spawner: impl overseer::gen::Spawner + 'static,
+	) -> Self {
+		Self::with_cache_capacities(client, metrics, spawner, &CacheCapacities::default())
+	}
+
+	/// Create a new Runtime API subsystem with the given capacities of the result caches.
+	pub fn with_cache_capacities(
+		client: Arc<Client>,
+		metrics: Metrics,
+		spawner: impl overseer::gen::Spawner + 'static,
+		cache_capacities: &CacheCapacities,
 	) -> Self {
 		RuntimeApiSubsystem {
 			client,
 			metrics,
 			spawn_handle: Box::new(spawner),
 			active_requests: Default::default(),
-			requests_cache: RequestResultCache::default(),
+			waiting_requests: HashMap::new(),
+			requests_cache: RequestResultCache::new(cache_capacities),
 		}
 	}
 }
@@ -155,6 +170,11 @@ where
 				self.requests_cache.cache_version(relay_parent, version),
 			Disputes(relay_parent, disputes) =>
 				self.requests_cache.cache_disputes(relay_parent, disputes),
+			ApprovalVotingParams(relay_parent, params) =>
+				self.requests_cache.cache_approval_voting_params(relay_parent, params),
+			SubmitBackingMisbehaviorReport(_, _, _, ()) => {},
+			InherentWeightInfo(relay_parent, info) =>
+				self.requests_cache.cache_inherent_weight_info(relay_parent, info),
 		}
 	}
 
@@ -165,9 +185,10 @@ where
 				let sender = $sender;
 				if let Some(value) = self.requests_cache.$cache_api_name(&relay_parent) {
 					let _ = sender.send(Ok(value.clone()));
-					self.metrics.on_cached_request();
+					self.metrics.on_cached_request(stringify!($cache_api_name));
 					None
 				} else {
+					self.metrics.on_cache_miss(stringify!($cache_api_name));
 					Some(sender)
 				}
 			}};
@@ -175,10 +196,11 @@ where
 			($cache_api_name:ident ($($param:expr),+), $sender:expr) => {{
 				let sender = $sender;
 				if let Some(value) = self.requests_cache.$cache_api_name((relay_parent.clone(), $($param.clone()),+)) {
-					self.metrics.on_cached_request();
+					self.metrics.on_cached_request(stringify!($cache_api_name));
 					let _ = sender.send(Ok(value.clone()));
 					None
 				} else {
+					self.metrics.on_cache_miss(stringify!($cache_api_name));
 					Some(sender)
 				}
 			}}
@@ -231,10 +253,11 @@ where
 				query!(candidate_events(), sender).map(|sender| Request::CandidateEvents(sender)),
 			Request::SessionInfo(index, sender) => {
 				if let Some(info) = self.requests_cache.session_info(index) {
-					self.metrics.on_cached_request();
+					self.metrics.on_cached_request("session_info");
 					let _ = sender.send(Ok(Some(info.clone())));
 					None
 				} else {
+					self.metrics.on_cache_miss("session_info");
 					Some(Request::SessionInfo(index, sender))
 				}
 			},
@@ -258,6 +281,14 @@ where
 					.map(|sender| Request::ValidationCodeHash(para, assumption, sender)),
 			Request::Disputes(sender) =>
 				query!(disputes(), sender).map(|sender| Request::Disputes(sender)),
+			Request::ApprovalVotingParams(sender) => query!(approval_voting_params(), sender)
+				.map(|sender| Request::ApprovalVotingParams(sender)),
+			request @ Request::SubmitBackingMisbehaviorReport(_, _, _) => {
+				// This request is side-effecting and thus cannot be cached.
+				Some(request)
+			},
+			Request::InherentWeightInfo(sender) => query!(inherent_weight_info(), sender)
+				.map(|sender| Request::InherentWeightInfo(sender)),
 		}
 	}
 
@@ -273,6 +304,18 @@ where
 			None => return,
 		};
 
+		// Identical requests share the execution of the one in flight.
+		let key = RequestKey::new(relay_parent, &request);
+		if let Some(key) = key.as_ref() {
+			if let Some(waiting) = self.waiting_requests.get_mut(key) {
+				self.metrics.on_coalesced_request(key.request_type());
+				waiting.push((relay_parent, request));
+				return
+			}
+
+			self.waiting_requests.insert(key.clone(), Vec::new());
+		}
+
 		let request = async move {
 			let result = make_runtime_api_request(client, metrics, relay_parent, request).await;
 			let _ = sender.send(result);
@@ -281,7 +324,8 @@ where
 
 		self.spawn_handle
 			.spawn_blocking(API_REQUEST_TASK_NAME, Some("runtime-api"), request);
-		self.active_requests.push(receiver);
+		self.active_requests
+			.push(receiver.map(move |result| (key, result.ok().flatten())).boxed());
 	}
 
 	/// Poll the active runtime API requests.
@@ -292,8 +336,17 @@ where
 		}
 
 		// If there are active requests, this will always resolve to `Some(_)` when a request is finished.
-		if let Some(Ok(Some(result))) = self.active_requests.next().await {
-			self.store_cache(result);
+		if let Some((key, result)) = self.active_requests.next().await {
+			if let Some(result) = result {
+				self.store_cache(result);
+			}
+
+			// Waiting requests are answered from the cache now. If the request failed or its
+			// result isn't cached, the first one is executed and the others keep waiting on it.
+			let waiting = key.and_then(|key| self.waiting_requests.remove(&key));
+			for (relay_parent, request) in waiting.into_iter().flatten() {
+				self.spawn_request(relay_parent, request);
+			}
 		}
 	}
 
@@ -502,5 +555,23 @@ where
 			query!(ValidationCodeHash, validation_code_hash(para, assumption), ver = 2, sender),
 		Request::Disputes(sender) =>
 			query!(Disputes, disputes(), ver = Request::DISPUTES_RUNTIME_REQUIREMENT, sender),
+		Request::ApprovalVotingParams(sender) => query!(
+			ApprovalVotingParams,
+			approval_voting_params(),
+			ver = Request::APPROVAL_VOTING_PARAMS_RUNTIME_REQUIREMENT,
+			sender
+		),
+		Request::SubmitBackingMisbehaviorReport(report, signature, sender) => query!(
+			SubmitBackingMisbehaviorReport,
+			submit_backing_misbehavior_report(report, signature),
+			ver = Request::SUBMIT_BACKING_MISBEHAVIOR_REPORT_RUNTIME_REQUIREMENT,
+			sender
+		),
+		Request::InherentWeightInfo(sender) => query!(
+			InherentWeightInfo,
+			inherent_weight_info(),
+			ver = Request::INHERENT_WEIGHT_INFO_RUNTIME_REQUIREMENT,
+			sender
+		),
 	}
 }
@@ -20,6 +20,7 @@ use polkadot_node_metrics::metrics::{self, prometheus};
 pub(crate) struct MetricsInner {
 	pub(crate) chain_api_requests: prometheus::CounterVec<prometheus::U64>,
 	pub(crate) make_runtime_api_request: prometheus::Histogram,
+	pub(crate) cache_lookups: prometheus::CounterVec<prometheus::U64>,
 }
 
 /// Runtime API metrics.
@@ -37,10 +38,24 @@ impl Metrics {
 		}
 	}
 
-	pub fn on_cached_request(&self) {
-		self.0
-			.as_ref()
-			.map(|metrics| metrics.chain_api_requests.with_label_values(&["cached"]).inc());
+	pub fn on_cached_request(&self, request_type: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.chain_api_requests.with_label_values(&["cached"]).inc();
+			metrics.cache_lookups.with_label_values(&[request_type, "hit"]).inc();
+		}
+	}
+
+	pub fn on_cache_miss(&self, request_type: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.cache_lookups.with_label_values(&[request_type, "miss"]).inc();
+		}
+	}
+
+	/// A request was not executed, as an identical one is already in flight.
+	pub fn on_coalesced_request(&self, request_type: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.cache_lookups.with_label_values(&[request_type, "coalesced"]).inc();
+		}
 	}
 
 	/// Provide a timer for `make_runtime_api_request` which observes on drop.
@@ -71,6 +86,16 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			cache_lookups: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_runtime_api_cache_lookups_total",
+						"Number of Runtime API cache lookups, by request type and outcome.",
+					),
+					&["request", "outcome"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
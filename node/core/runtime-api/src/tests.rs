@@ -33,7 +33,10 @@ use sp_consensus_babe::BabeApi;
 use sp_core::testing::TaskExecutor;
 use std::{
 	collections::{BTreeMap, HashMap},
-	sync::{Arc, Mutex},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
 };
 
 #[derive(Default, Clone)]
@@ -43,6 +46,7 @@ struct MockRuntimeApi {
 	validator_groups: Vec<Vec<ValidatorIndex>>,
 	availability_cores: Vec<CoreState>,
 	availability_cores_wait: Arc<Mutex<()>>,
+	availability_cores_calls: Arc<AtomicUsize>,
 	validation_data: HashMap<ParaId, PersistedValidationData>,
 	session_index_for_child: SessionIndex,
 	session_info: HashMap<SessionIndex, SessionInfo>,
@@ -87,6 +91,7 @@ sp_api::mock_impl_runtime_apis! {
 
 		fn availability_cores(&self) -> Vec<CoreState> {
 			let _lock = self.availability_cores_wait.lock().unwrap();
+			self.availability_cores_calls.fetch_add(1, Ordering::SeqCst);
 			self.availability_cores.clone()
 		}
 
@@ -861,13 +866,17 @@ fn multiple_requests_in_parallel_are_working() {
 		// Make all requests block until we release this mutex.
 		let lock = mutex.lock().unwrap();
 
+		// Distinct relay parents, so the requests aren't coalesced.
 		let mut receivers = Vec::new();
-		for _ in 0..MAX_PARALLEL_REQUESTS {
+		for i in 0..MAX_PARALLEL_REQUESTS {
 			let (tx, rx) = oneshot::channel();
 
 			ctx_handle
 				.send(FromOrchestra::Communication {
-					msg: RuntimeApiMessage::Request(relay_parent, Request::AvailabilityCores(tx)),
+					msg: RuntimeApiMessage::Request(
+						[i as u8 + 2; 32].into(),
+						Request::AvailabilityCores(tx),
+					),
 				})
 				.await;
 			receivers.push(rx);
@@ -899,6 +908,60 @@ fn multiple_requests_in_parallel_are_working() {
 	futures::executor::block_on(future::join(subsystem_task, test_task));
 }
 
+#[test]
+fn identical_requests_in_flight_are_coalesced() {
+	let (ctx, mut ctx_handle) = make_subsystem_context(TaskExecutor::new());
+	let runtime_api = Arc::new(MockRuntimeApi::default());
+	let relay_parent = [1; 32].into();
+	let other_relay_parent = [2; 32].into();
+	let spawner = sp_core::testing::TaskExecutor::new();
+	let mutex = runtime_api.availability_cores_wait.clone();
+	let calls = runtime_api.availability_cores_calls.clone();
+
+	let subsystem =
+		RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), SpawnGlue(spawner));
+	let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+	let test_task = async move {
+		// Keep the first request in flight until all others are sent.
+		let lock = mutex.lock().unwrap();
+
+		// More than `MAX_PARALLEL_REQUESTS`, which would block if they weren't coalesced.
+		let mut receivers = Vec::new();
+		for _ in 0..MAX_PARALLEL_REQUESTS * 2 {
+			let (tx, rx) = oneshot::channel();
+
+			ctx_handle
+				.send(FromOrchestra::Communication {
+					msg: RuntimeApiMessage::Request(relay_parent, Request::AvailabilityCores(tx)),
+				})
+				.await;
+			receivers.push(rx);
+		}
+
+		// Requests for another relay parent are executed separately.
+		let (tx, rx) = oneshot::channel();
+		ctx_handle
+			.send(FromOrchestra::Communication {
+				msg: RuntimeApiMessage::Request(other_relay_parent, Request::AvailabilityCores(tx)),
+			})
+			.await;
+		receivers.push(rx);
+
+		drop(lock);
+
+		future::join_all(receivers)
+			.await
+			.into_iter()
+			.for_each(|r| assert_eq!(r.unwrap().unwrap(), runtime_api.availability_cores));
+
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+		ctx_handle.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+	};
+
+	futures::executor::block_on(future::join(subsystem_task, test_task));
+}
+
 #[test]
 fn requests_babe_epoch() {
 	let (ctx, mut ctx_handle) = make_subsystem_context(TaskExecutor::new());
@@ -24,11 +24,12 @@ use futures::{channel::oneshot, FutureExt as _};
 use polkadot_node_network_protocol::{
 	self as net_protocol,
 	grid_topology::{RandomRouting, RequiredRouting, SessionGridTopologies, SessionGridTopology},
-	peer_set::MAX_NOTIFICATION_SIZE,
-	v1 as protocol_v1, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	peer_set::{ProtocolVersion, ValidationVersion, MAX_NOTIFICATION_SIZE},
+	v1 as protocol_v1, v2 as protocol_v2, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
 use polkadot_node_primitives::approval::{
-	AssignmentCert, BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote,
+	AssignmentCert, BlockApprovalMeta, CandidateBitfield, IndirectAssignmentCert,
+	IndirectSignedApprovalVote, IndirectSignedCoalescedApprovalVote,
 };
 use polkadot_node_subsystem::{
 	messages::{
@@ -172,6 +173,11 @@ struct State {
 	/// Peer data is partially stored here, and partially inline within the [`BlockEntry`]s
 	peer_views: HashMap<PeerId, View>,
 
+	/// The validation protocol versions of all connected peers.
+	///
+	/// Coalesced approvals are only sent to peers on v2 of the protocol.
+	peer_versions: HashMap<PeerId, ProtocolVersion>,
+
 	/// Keeps a topology for various different sessions.
 	topologies: SessionGridTopologies,
 
@@ -263,7 +269,16 @@ struct BlockEntry {
 #[derive(Debug)]
 enum ApprovalState {
 	Assigned(AssignmentCert),
-	Approved(AssignmentCert, ValidatorSignature),
+	Approved(AssignmentCert, ApprovalSignature),
+}
+
+#[derive(Debug, Clone)]
+enum ApprovalSignature {
+	/// The signature of an approval of this candidate only.
+	Single(ValidatorSignature),
+	/// The signature of an approval coalesced with the approvals of other candidates
+	/// in the same block.
+	Coalesced(CandidateBitfield, ValidatorSignature),
 }
 
 impl ApprovalState {
@@ -274,7 +289,7 @@ impl ApprovalState {
 		}
 	}
 
-	fn approval_signature(&self) -> Option<ValidatorSignature> {
+	fn approval_signature(&self) -> Option<ApprovalSignature> {
 		match *self {
 			ApprovalState::Assigned(_) => None,
 			ApprovalState::Approved(_, ref sig) => Some(sig.clone()),
@@ -319,6 +334,19 @@ impl MessageSource {
 enum PendingMessage {
 	Assignment(IndirectAssignmentCert, CandidateIndex),
 	Approval(IndirectSignedApprovalVote),
+	CoalescedApproval(IndirectSignedCoalescedApprovalVote),
+}
+
+// The subjects of all approvals covered by a coalesced approval vote.
+fn coalesced_subjects(
+	block_hash: Hash,
+	candidate_indices: &CandidateBitfield,
+	validator: ValidatorIndex,
+) -> Vec<MessageSubject> {
+	candidate_indices
+		.iter_ones()
+		.map(|candidate_index| MessageSubject(block_hash, candidate_index as _, validator))
+		.collect()
 }
 
 #[overseer::contextbounds(ApprovalDistribution, prefix = self::overseer)]
@@ -331,14 +359,22 @@ impl State {
 		rng: &mut (impl CryptoRng + Rng),
 	) {
 		match event {
-			NetworkBridgeEvent::PeerConnected(peer_id, role, _, _) => {
+			NetworkBridgeEvent::PeerConnected(peer_id, role, protocol_version, _) => {
 				// insert a blank view if none already present
-				gum::trace!(target: LOG_TARGET, ?peer_id, ?role, "Peer connected");
+				gum::trace!(
+					target: LOG_TARGET,
+					?peer_id,
+					?role,
+					?protocol_version,
+					"Peer connected"
+				);
 				self.peer_views.entry(peer_id).or_default();
+				self.peer_versions.insert(peer_id, protocol_version);
 			},
 			NetworkBridgeEvent::PeerDisconnected(peer_id) => {
 				gum::trace!(target: LOG_TARGET, ?peer_id, "Peer disconnected");
 				self.peer_views.remove(&peer_id);
+				self.peer_versions.remove(&peer_id);
 				self.blocks.iter_mut().for_each(|(_hash, entry)| {
 					entry.known_by.remove(&peer_id);
 				})
@@ -376,6 +412,9 @@ impl State {
 				});
 			},
 			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V1(msg)) => {
+				self.process_incoming_peer_message(ctx, metrics, peer_id, msg.into(), rng).await;
+			},
+			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V2(msg)) => {
 				self.process_incoming_peer_message(ctx, metrics, peer_id, msg, rng).await;
 			},
 		}
@@ -435,6 +474,7 @@ impl State {
 					&self.topologies,
 					self.peer_views.len(),
 					*peer_id,
+					supports_coalesced_approvals(&self.peer_versions, peer_id),
 					view_intersection,
 					rng,
 				)
@@ -492,6 +532,15 @@ impl State {
 							)
 							.await;
 						},
+						PendingMessage::CoalescedApproval(approval_vote) => {
+							self.import_and_circulate_coalesced_approval(
+								ctx,
+								metrics,
+								MessageSource::Peer(peer_id),
+								approval_vote,
+							)
+							.await;
+						},
 					}
 				}
 			}
@@ -519,6 +568,7 @@ impl State {
 			ctx,
 			&mut self.blocks,
 			&self.topologies,
+			&self.peer_versions,
 			|block_entry| block_entry.session == session,
 			|required_routing, local, validator_index| {
 				if *required_routing == RequiredRouting::PendingTopology {
@@ -536,13 +586,13 @@ impl State {
 		ctx: &mut Context,
 		metrics: &Metrics,
 		peer_id: PeerId,
-		msg: protocol_v1::ApprovalDistributionMessage,
+		msg: protocol_v2::ApprovalDistributionMessage,
 		rng: &mut R,
 	) where
 		R: CryptoRng + Rng,
 	{
 		match msg {
-			protocol_v1::ApprovalDistributionMessage::Assignments(assignments) => {
+			protocol_v2::ApprovalDistributionMessage::Assignments(assignments) => {
 				gum::trace!(
 					target: LOG_TARGET,
 					peer_id = %peer_id,
@@ -581,13 +631,14 @@ impl State {
 					.await;
 				}
 			},
-			protocol_v1::ApprovalDistributionMessage::Approvals(approvals) => {
+			protocol_v2::ApprovalDistributionMessage::Approvals(approvals) => {
 				gum::trace!(
 					target: LOG_TARGET,
 					peer_id = %peer_id,
 					num = approvals.len(),
 					"Processing approvals from a peer",
 				);
+				let mut to_import = Vec::with_capacity(approvals.len());
 				for approval_vote in approvals.into_iter() {
 					if let Some(pending) = self.pending_known.get_mut(&approval_vote.block_hash) {
 						let message_subject = MessageSubject(
@@ -608,6 +659,12 @@ impl State {
 						continue
 					}
 
+					to_import.push(approval_vote);
+				}
+
+				if to_import.len() > 1 {
+					self.import_and_circulate_approvals(ctx, metrics, peer_id, to_import).await;
+				} else if let Some(approval_vote) = to_import.pop() {
 					self.import_and_circulate_approval(
 						ctx,
 						metrics,
@@ -617,6 +674,38 @@ impl State {
 					.await;
 				}
 			},
+			protocol_v2::ApprovalDistributionMessage::CoalescedApprovals(approvals) => {
+				gum::trace!(
+					target: LOG_TARGET,
+					peer_id = %peer_id,
+					num = approvals.len(),
+					"Processing coalesced approvals from a peer",
+				);
+				for approval_vote in approvals.into_iter() {
+					if let Some(pending) = self.pending_known.get_mut(&approval_vote.block_hash) {
+						gum::trace!(
+							target: LOG_TARGET,
+							%peer_id,
+							block_hash = ?approval_vote.block_hash,
+							candidate_indices = ?approval_vote.candidate_indices,
+							validator_index = ?approval_vote.validator,
+							"Pending coalesced approval",
+						);
+
+						pending.push((peer_id, PendingMessage::CoalescedApproval(approval_vote)));
+
+						continue
+					}
+
+					self.import_and_circulate_coalesced_approval(
+						ctx,
+						metrics,
+						MessageSource::Peer(peer_id),
+						approval_vote,
+					)
+					.await;
+				}
+			},
 		}
 	}
 
@@ -663,6 +752,7 @@ impl State {
 			&self.topologies,
 			self.peer_views.len(),
 			peer_id,
+			supports_coalesced_approvals(&self.peer_versions, &peer_id),
 			view,
 			rng,
 		)
@@ -968,136 +1058,280 @@ impl State {
 		source: MessageSource,
 		vote: IndirectSignedApprovalVote,
 	) {
+		if let Some(peer_id) = source.peer_id() {
+			if !self.check_approval_from_peer(ctx, peer_id, &vote).await {
+				return
+			}
+
+			let (tx, rx) = oneshot::channel();
+
+			ctx.send_message(ApprovalVotingMessage::CheckAndImportApproval(vote.clone(), tx))
+				.await;
+
+			let timer = metrics.time_awaiting_approval_voting();
+			let result = match rx.await {
+				Ok(result) => result,
+				Err(_) => {
+					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
+					return
+				},
+			};
+			drop(timer);
+
+			if !self.note_approval_check_result(ctx, peer_id, &vote, result).await {
+				return
+			}
+		} else if !self.note_local_approval(&vote) {
+			return
+		}
+
+		self.circulate_approval(ctx, metrics, source, vote).await;
+	}
+
+	/// Like `import_and_circulate_approval`, but for a batch of approvals received from a
+	/// single peer. The approvals are sent to the approval voting subsystem in one message,
+	/// so that their signatures can be verified in a batch.
+	async fn import_and_circulate_approvals<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		peer_id: PeerId,
+		votes: Vec<IndirectSignedApprovalVote>,
+	) {
+		let mut to_check = Vec::with_capacity(votes.len());
+		let mut batch_subjects = HashSet::with_capacity(votes.len());
+
+		for vote in votes {
+			if !self.check_approval_from_peer(ctx, peer_id, &vote).await {
+				continue
+			}
+
+			// Our knowledge is only updated once the batch is checked, so duplicates within
+			// the batch itself need to be caught here.
+			let message_subject =
+				MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+			if !batch_subjects.insert(message_subject) {
+				gum::debug!(target: LOG_TARGET, ?peer_id, "Duplicate approval within a batch");
+				modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
+				continue
+			}
+
+			to_check.push(vote);
+		}
+
+		if to_check.is_empty() {
+			return
+		}
+
+		metrics.on_approval_batch(to_check.len());
+
+		let (tx, rx) = oneshot::channel();
+
+		ctx.send_message(ApprovalVotingMessage::CheckAndImportApprovals(to_check.clone(), tx))
+			.await;
+
+		let timer = metrics.time_awaiting_approval_voting();
+		let results = match rx.await {
+			Ok(results) => results,
+			Err(_) => {
+				gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
+				return
+			},
+		};
+		drop(timer);
+
+		if results.len() != to_check.len() {
+			gum::warn!(
+				target: LOG_TARGET,
+				expected = to_check.len(),
+				got = results.len(),
+				"Approval voting returned an unexpected number of results",
+			);
+			return
+		}
+
+		for (vote, result) in to_check.into_iter().zip(results) {
+			if self.note_approval_check_result(ctx, peer_id, &vote, result).await {
+				self.circulate_approval(ctx, metrics, MessageSource::Peer(peer_id), vote).await;
+			}
+		}
+	}
+
+	/// Checks an approval received from a peer against our knowledge and the knowledge
+	/// of the peer, modifying the peer's reputation as appropriate.
+	///
+	/// Returns `true` if the approval is new to us and needs to be checked by the approval
+	/// voting subsystem.
+	async fn check_approval_from_peer<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: PeerId,
+		vote: &IndirectSignedApprovalVote,
+	) -> bool {
 		let block_hash = vote.block_hash;
-		let validator_index = vote.validator;
-		let candidate_index = vote.candidate_index;
 
 		let entry = match self.blocks.get_mut(&block_hash) {
-			Some(entry) if entry.candidates.get(candidate_index as usize).is_some() => entry,
+			Some(entry) if entry.candidates.get(vote.candidate_index as usize).is_some() => entry,
 			_ => {
-				if let Some(peer_id) = source.peer_id() {
-					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
-						modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-					}
+				if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
+					modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
 				}
-				return
+				return false
 			},
 		};
 
 		// compute metadata on the assignment.
-		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
+		let message_subject = MessageSubject(block_hash, vote.candidate_index, vote.validator);
 		let message_kind = MessageKind::Approval;
 
-		if let Some(peer_id) = source.peer_id() {
-			if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
+		if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
+			gum::debug!(
+				target: LOG_TARGET,
+				?peer_id,
+				?message_subject,
+				"Unknown approval assignment",
+			);
+			modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
+			return false
+		}
+
+		// check if our knowledge of the peer already contains this approval
+		match entry.known_by.entry(peer_id) {
+			hash_map::Entry::Occupied(mut knowledge) => {
+				let peer_knowledge = knowledge.get_mut();
+				if peer_knowledge.contains(&message_subject, message_kind) {
+					if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?message_subject,
+							"Duplicate approval",
+						);
+
+						modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
+					}
+					return false
+				}
+			},
+			hash_map::Entry::Vacant(_) => {
 				gum::debug!(
 					target: LOG_TARGET,
 					?peer_id,
 					?message_subject,
-					"Unknown approval assignment",
+					"Approval from a peer is out of view",
 				);
 				modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-				return
+			},
+		}
+
+		// if the approval is known to be valid, reward the peer
+		if entry.knowledge.contains(&message_subject, message_kind) {
+			gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
+			modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+				peer_knowledge.received.insert(message_subject.clone(), message_kind);
 			}
+			return false
+		}
 
-			// check if our knowledge of the peer already contains this approval
-			match entry.known_by.entry(peer_id) {
-				hash_map::Entry::Occupied(mut knowledge) => {
-					let peer_knowledge = knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
-							gum::debug!(
-								target: LOG_TARGET,
-								?peer_id,
-								?message_subject,
-								"Duplicate approval",
-							);
+		true
+	}
 
-							modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
-						}
-						return
-					}
-				},
-				hash_map::Entry::Vacant(_) => {
-					gum::debug!(
-						target: LOG_TARGET,
-						?peer_id,
-						?message_subject,
-						"Approval from a peer is out of view",
-					);
-					modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-				},
-			}
+	/// Handles the result of checking an approval received from a peer.
+	///
+	/// Returns `true` if the approval was accepted and should be circulated.
+	async fn note_approval_check_result<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: PeerId,
+		vote: &IndirectSignedApprovalVote,
+		result: ApprovalCheckResult,
+	) -> bool {
+		let message_subject = MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+		let message_kind = MessageKind::Approval;
 
-			// if the approval is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
-				modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE).await;
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_id,
+			?message_subject,
+			?result,
+			"Checked approval",
+		);
+
+		let entry = match self.blocks.get_mut(&vote.block_hash) {
+			Some(entry) => entry,
+			None => return false,
+		};
+
+		match result {
+			ApprovalCheckResult::Accepted => {
+				modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+				entry.knowledge.insert(message_subject.clone(), message_kind);
 				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					peer_knowledge.received.insert(message_subject.clone(), message_kind);
+					peer_knowledge.received.insert(message_subject, message_kind);
 				}
-				return
-			}
 
-			let (tx, rx) = oneshot::channel();
+				true
+			},
+			ApprovalCheckResult::Bad(error) => {
+				modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+				gum::info!(
+					target: LOG_TARGET,
+					?peer_id,
+					%error,
+					"Got a bad approval from peer",
+				);
 
-			ctx.send_message(ApprovalVotingMessage::CheckAndImportApproval(vote.clone(), tx))
-				.await;
+				false
+			},
+		}
+	}
 
-			let timer = metrics.time_awaiting_approval_voting();
-			let result = match rx.await {
-				Ok(result) => result,
-				Err(_) => {
-					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
-					return
-				},
-			};
-			drop(timer);
+	/// Notes a locally issued approval in our knowledge.
+	///
+	/// Returns `true` if the approval is new and should be circulated.
+	fn note_local_approval(&mut self, vote: &IndirectSignedApprovalVote) -> bool {
+		let entry = match self.blocks.get_mut(&vote.block_hash) {
+			Some(entry) if entry.candidates.get(vote.candidate_index as usize).is_some() => entry,
+			_ => return false,
+		};
 
-			gum::trace!(
+		let message_subject = MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+
+		if !entry.knowledge.insert(message_subject.clone(), MessageKind::Approval) {
+			// if we already imported an approval, there is no need to distribute it again
+			gum::warn!(
 				target: LOG_TARGET,
-				?peer_id,
 				?message_subject,
-				?result,
-				"Checked approval",
+				"Importing locally an already known approval",
 			);
-			match result {
-				ApprovalCheckResult::Accepted => {
-					modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE_FIRST).await;
-
-					entry.knowledge.insert(message_subject.clone(), message_kind);
-					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
-					}
-				},
-				ApprovalCheckResult::Bad(error) => {
-					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
-					gum::info!(
-						target: LOG_TARGET,
-						?peer_id,
-						%error,
-						"Got a bad approval from peer",
-					);
-					return
-				},
-			}
+			false
 		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an approval, there is no need to distribute it again
-				gum::warn!(
-					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known approval",
-				);
-				return
-			} else {
-				gum::debug!(
-					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally a new approval",
-				);
-			}
+			gum::debug!(target: LOG_TARGET, ?message_subject, "Importing locally a new approval");
+			true
 		}
+	}
+
+	/// Circulates an imported approval to all peers that need it according to the topology.
+	async fn circulate_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		vote: IndirectSignedApprovalVote,
+	) {
+		let block_hash = vote.block_hash;
+		let validator_index = vote.validator;
+		let candidate_index = vote.candidate_index;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
+		let message_kind = MessageKind::Approval;
 
 		// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
 		metrics.on_approval_imported();
@@ -1118,7 +1352,7 @@ impl State {
 							MessageState {
 								approval_state: ApprovalState::Approved(
 									cert,
-									vote.signature.clone(),
+									ApprovalSignature::Single(vote.signature.clone()),
 								),
 								required_routing,
 								local,
@@ -1223,12 +1457,327 @@ impl State {
 		}
 	}
 
-	/// Retrieve approval signatures from state for the given relay block/indices:
-	fn get_approval_signatures(
+	/// Like `import_and_circulate_approval`, but for an approval vote covering several
+	/// candidates of a block.
+	async fn import_and_circulate_coalesced_approval<Context>(
 		&mut self,
-		indices: HashSet<(Hash, CandidateIndex)>,
-	) -> HashMap<ValidatorIndex, ValidatorSignature> {
-		let mut all_sigs = HashMap::new();
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		vote: IndirectSignedCoalescedApprovalVote,
+	) {
+		if let Some(peer_id) = source.peer_id() {
+			if !self.check_coalesced_approval_from_peer(ctx, peer_id, &vote).await {
+				return
+			}
+
+			let (tx, rx) = oneshot::channel();
+
+			ctx.send_message(ApprovalVotingMessage::CheckAndImportCoalescedApproval(
+				vote.clone(),
+				tx,
+			))
+			.await;
+
+			let timer = metrics.time_awaiting_approval_voting();
+			let result = match rx.await {
+				Ok(result) => result,
+				Err(_) => {
+					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
+					return
+				},
+			};
+			drop(timer);
+
+			gum::trace!(
+				target: LOG_TARGET,
+				?peer_id,
+				block_hash = ?vote.block_hash,
+				candidate_indices = ?vote.candidate_indices,
+				validator_index = ?vote.validator,
+				?result,
+				"Checked coalesced approval",
+			);
+
+			let entry = match self.blocks.get_mut(&vote.block_hash) {
+				Some(entry) => entry,
+				None => return,
+			};
+
+			match result {
+				ApprovalCheckResult::Accepted => {
+					modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+					for subject in
+						coalesced_subjects(vote.block_hash, &vote.candidate_indices, vote.validator)
+					{
+						entry.knowledge.insert(subject.clone(), MessageKind::Approval);
+						if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+							peer_knowledge.received.insert(subject, MessageKind::Approval);
+						}
+					}
+				},
+				ApprovalCheckResult::Bad(error) => {
+					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+					gum::info!(
+						target: LOG_TARGET,
+						?peer_id,
+						%error,
+						"Got a bad coalesced approval from peer",
+					);
+
+					return
+				},
+			}
+		} else {
+			let entry = match self.blocks.get_mut(&vote.block_hash) {
+				Some(entry) => entry,
+				None => return,
+			};
+
+			let mut is_new = false;
+			for subject in
+				coalesced_subjects(vote.block_hash, &vote.candidate_indices, vote.validator)
+			{
+				is_new |= entry.knowledge.insert(subject, MessageKind::Approval);
+			}
+
+			if !is_new {
+				// if we already imported the approvals, there is no need to distribute them again
+				gum::warn!(
+					target: LOG_TARGET,
+					block_hash = ?vote.block_hash,
+					candidate_indices = ?vote.candidate_indices,
+					"Importing locally an already known coalesced approval",
+				);
+				return
+			}
+		}
+
+		self.circulate_coalesced_approval(ctx, metrics, source, vote).await;
+	}
+
+	/// Checks a coalesced approval received from a peer against our knowledge and the
+	/// knowledge of the peer, modifying the peer's reputation as appropriate.
+	///
+	/// Returns `true` if the approval is new to us and needs to be checked by the approval
+	/// voting subsystem.
+	async fn check_coalesced_approval_from_peer<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: PeerId,
+		vote: &IndirectSignedCoalescedApprovalVote,
+	) -> bool {
+		let block_hash = vote.block_hash;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry)
+				if vote.candidate_indices.any() &&
+					vote.candidate_indices.len() <= entry.candidates.len() =>
+				entry,
+			_ => {
+				if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
+					modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
+				}
+				return false
+			},
+		};
+
+		let subjects = coalesced_subjects(block_hash, &vote.candidate_indices, vote.validator);
+
+		if !subjects.iter().all(|s| entry.knowledge.contains(s, MessageKind::Assignment)) {
+			gum::debug!(
+				target: LOG_TARGET,
+				?peer_id,
+				?block_hash,
+				candidate_indices = ?vote.candidate_indices,
+				validator_index = ?vote.validator,
+				"Unknown coalesced approval assignments",
+			);
+			modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
+			return false
+		}
+
+		// check if our knowledge of the peer already contains this approval
+		match entry.known_by.entry(peer_id) {
+			hash_map::Entry::Occupied(mut knowledge) => {
+				let peer_knowledge = knowledge.get_mut();
+				if subjects.iter().all(|s| peer_knowledge.contains(s, MessageKind::Approval)) {
+					let mut is_new = false;
+					for subject in subjects {
+						is_new |= peer_knowledge.received.insert(subject, MessageKind::Approval);
+					}
+
+					if !is_new {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?block_hash,
+							candidate_indices = ?vote.candidate_indices,
+							"Duplicate coalesced approval",
+						);
+
+						modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
+					}
+					return false
+				}
+			},
+			hash_map::Entry::Vacant(_) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					?peer_id,
+					?block_hash,
+					candidate_indices = ?vote.candidate_indices,
+					"Coalesced approval from a peer is out of view",
+				);
+				modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
+			},
+		}
+
+		// if the approval is known to be valid, reward the peer
+		if subjects.iter().all(|s| entry.knowledge.contains(s, MessageKind::Approval)) {
+			gum::trace!(target: LOG_TARGET, ?peer_id, ?block_hash, "Known coalesced approval");
+			modify_reputation(ctx.sender(), peer_id, BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+				for subject in subjects {
+					peer_knowledge.received.insert(subject, MessageKind::Approval);
+				}
+			}
+			return false
+		}
+
+		true
+	}
+
+	/// Circulates an imported coalesced approval to all peers that need it according to
+	/// the topology, and which know the assignments of all covered candidates.
+	async fn circulate_coalesced_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		vote: IndirectSignedCoalescedApprovalVote,
+	) {
+		let block_hash = vote.block_hash;
+		let validator_index = vote.validator;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		let subjects = coalesced_subjects(block_hash, &vote.candidate_indices, validator_index);
+
+		// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
+		metrics.on_approval_imported();
+		metrics.on_coalesced_approval_imported();
+
+		// set the approval state for validator_index to Approved for all covered candidates.
+		// they should be in assigned state already.
+		let mut required_routings = Vec::with_capacity(subjects.len());
+		for MessageSubject(_, candidate_index, _) in &subjects {
+			let message_state = match entry
+				.candidates
+				.get_mut(*candidate_index as usize)
+				.and_then(|c| c.messages.get_mut(&validator_index))
+			{
+				Some(message_state) => message_state,
+				None => {
+					// this would indicate a bug in approval-voting
+					gum::warn!(
+						target: LOG_TARGET,
+						hash = ?block_hash,
+						?candidate_index,
+						?validator_index,
+						"Importing a coalesced approval we don't have an assignment for",
+					);
+
+					return
+				},
+			};
+
+			let cert = message_state.approval_state.assignment_cert().clone();
+			message_state.approval_state = ApprovalState::Approved(
+				cert,
+				ApprovalSignature::Coalesced(
+					vote.candidate_indices.clone(),
+					vote.signature.clone(),
+				),
+			);
+			required_routings.push(message_state.required_routing);
+		}
+
+		// Dispatch a ApprovalDistributionV2Message::CoalescedApprovals(vote)
+		// to all v2 peers required by the topology for any of the covered candidates, with the
+		// exception of the source peer. As with single approvals, peers need to know all
+		// assignments the vote covers.
+		let topology = self.topologies.get_topology(entry.session);
+		let source_peer = source.peer_id();
+		let peer_versions = &self.peer_versions;
+
+		let subjects = &subjects;
+		let required_routings = &required_routings;
+		let peer_filter = move |peer, knowledge: &PeerKnowledge| {
+			if Some(peer) == source_peer.as_ref() ||
+				!supports_coalesced_approvals(peer_versions, peer)
+			{
+				return false
+			}
+
+			subjects.iter().zip(required_routings).all(|(subject, required_routing)| {
+				let in_topology = topology.map_or(false, |t| {
+					t.local_grid_neighbors().route_to_peer(*required_routing, peer)
+				});
+				in_topology || knowledge.sent.contains(subject, MessageKind::Assignment)
+			})
+		};
+
+		let peers = entry
+			.known_by
+			.iter()
+			.filter(|(p, k)| peer_filter(p, k))
+			.map(|(p, _)| p)
+			.cloned()
+			.collect::<Vec<_>>();
+
+		// Add the metadata of the approval to the knowledge of each peer.
+		for peer in peers.iter() {
+			// we already filtered peers above, so this should always be Some
+			if let Some(entry) = entry.known_by.get_mut(peer) {
+				for subject in subjects {
+					entry.sent.insert(subject.clone(), MessageKind::Approval);
+				}
+			}
+		}
+
+		if !peers.is_empty() {
+			gum::trace!(
+				target: LOG_TARGET,
+				?block_hash,
+				candidate_indices = ?vote.candidate_indices,
+				local = source.peer_id().is_none(),
+				num_peers = peers.len(),
+				"Sending a coalesced approval to peers",
+			);
+
+			ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::CoalescedApprovals(vec![vote]),
+				)),
+			))
+			.await;
+		}
+	}
+
+	/// Retrieve approval signatures from state for the given relay block/indices:
+	///
+	/// Signatures of coalesced approvals come with the indices of all the candidates they cover.
+	fn get_approval_signatures(
+		&mut self,
+		indices: HashSet<(Hash, CandidateIndex)>,
+	) -> HashMap<ValidatorIndex, (Hash, Option<CandidateBitfield>, ValidatorSignature)> {
+		let mut all_sigs = HashMap::new();
 		for (hash, index) in indices {
 			let block_entry = match self.blocks.get(&hash) {
 				None => {
@@ -1257,7 +1806,10 @@ impl State {
 			let sigs =
 				candidate_entry.messages.iter().filter_map(|(validator_index, message_state)| {
 					match &message_state.approval_state {
-						ApprovalState::Approved(_, sig) => Some((*validator_index, sig.clone())),
+						ApprovalState::Approved(_, ApprovalSignature::Single(sig)) =>
+							Some((*validator_index, (hash, None, sig.clone()))),
+						ApprovalState::Approved(_, ApprovalSignature::Coalesced(indices, sig)) =>
+							Some((*validator_index, (hash, Some(indices.clone()), sig.clone()))),
 						ApprovalState::Assigned(_) => None,
 					}
 				});
@@ -1273,6 +1825,7 @@ impl State {
 		topologies: &SessionGridTopologies,
 		total_peers: usize,
 		peer_id: PeerId,
+		coalesced_approvals: bool,
 		view: View,
 		rng: &mut (impl CryptoRng + Rng),
 	) {
@@ -1281,6 +1834,7 @@ impl State {
 
 		let mut assignments_to_send = Vec::new();
 		let mut approvals_to_send = Vec::new();
+		let mut coalesced_approvals_to_send = Vec::new();
 
 		let view_finalized_number = view.finalized_number;
 		for head in view.into_iter() {
@@ -1343,16 +1897,6 @@ impl State {
 						candidate_index,
 					);
 
-					let approval_message =
-						message_state.approval_state.approval_signature().map(|signature| {
-							IndirectSignedApprovalVote {
-								block_hash: block,
-								validator: *validator,
-								candidate_index,
-								signature,
-							}
-						});
-
 					if !peer_knowledge.contains(&message_subject, MessageKind::Assignment) {
 						peer_knowledge
 							.sent
@@ -1360,13 +1904,33 @@ impl State {
 						assignments_to_send.push(assignment_message);
 					}
 
-					if let Some(approval_message) = approval_message {
-						if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
-							peer_knowledge
-								.sent
-								.insert(message_subject.clone(), MessageKind::Approval);
-							approvals_to_send.push(approval_message);
-						}
+					match message_state.approval_state.approval_signature() {
+						None => {},
+						Some(ApprovalSignature::Single(signature)) => {
+							if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
+								peer_knowledge
+									.sent
+									.insert(message_subject.clone(), MessageKind::Approval);
+								approvals_to_send.push(IndirectSignedApprovalVote {
+									block_hash: block,
+									validator: *validator,
+									candidate_index,
+									signature,
+								});
+							}
+						},
+						// Legacy peers don't understand coalesced approvals.
+						Some(ApprovalSignature::Coalesced(..)) if !coalesced_approvals => {},
+						Some(ApprovalSignature::Coalesced(candidate_indices, signature)) => {
+							coalesced_approvals_to_send.extend(coalesced_approval_for_peer(
+								peer_knowledge,
+								block,
+								candidate_index,
+								*validator,
+								candidate_indices,
+								signature,
+							));
+						},
 					}
 				}
 
@@ -1395,6 +1959,17 @@ impl State {
 
 			send_approvals_batched(sender, approvals_to_send, peer_id).await;
 		}
+
+		if !coalesced_approvals_to_send.is_empty() {
+			gum::trace!(
+				target: LOG_TARGET,
+				?peer_id,
+				num = coalesced_approvals_to_send.len(),
+				"Sending coalesced approvals to unified peer",
+			);
+
+			send_coalesced_approvals_batched(sender, coalesced_approvals_to_send, peer_id).await;
+		}
 	}
 
 	async fn enable_aggression<Context>(
@@ -1421,6 +1996,7 @@ impl State {
 			ctx,
 			&mut self.blocks,
 			&self.topologies,
+			&self.peer_versions,
 			|block_entry| {
 				let block_age = max_age - block_entry.number;
 
@@ -1448,6 +2024,7 @@ impl State {
 			ctx,
 			&mut self.blocks,
 			&self.topologies,
+			&self.peer_versions,
 			|block_entry| {
 				// Ramp up aggression only for the very oldest block(s).
 				// Approval voting can get stuck on a single block preventing
@@ -1505,6 +2082,7 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 	ctx: &mut Context,
 	blocks: &mut HashMap<Hash, BlockEntry>,
 	topologies: &SessionGridTopologies,
+	peer_versions: &HashMap<PeerId, ProtocolVersion>,
 	block_filter: BlockFilter,
 	routing_modifier: RoutingModifier,
 ) where
@@ -1513,6 +2091,7 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 {
 	let mut peer_assignments = HashMap::new();
 	let mut peer_approvals = HashMap::new();
+	let mut peer_coalesced_approvals = HashMap::new();
 
 	// Iterate all blocks in the session, producing payloads
 	// for each connected peer.
@@ -1550,15 +2129,7 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 				},
 				candidate_index,
 			);
-			let approval_message =
-				message_state.approval_state.approval_signature().map(|signature| {
-					IndirectSignedApprovalVote {
-						block_hash: *block_hash,
-						validator: *validator,
-						candidate_index,
-						signature,
-					}
-				});
+			let approval_signature = message_state.approval_state.approval_signature();
 
 			for (peer, peer_knowledge) in &mut block_entry.known_by {
 				if !topology
@@ -1576,14 +2147,38 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 						.push(assignment_message.clone());
 				}
 
-				if let Some(approval_message) = approval_message.as_ref() {
-					if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
-						peer_knowledge.sent.insert(message_subject.clone(), MessageKind::Approval);
-						peer_approvals
-							.entry(*peer)
-							.or_insert_with(Vec::new)
-							.push(approval_message.clone());
-					}
+				match approval_signature.as_ref() {
+					None => {},
+					Some(ApprovalSignature::Single(signature)) => {
+						if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
+							peer_knowledge
+								.sent
+								.insert(message_subject.clone(), MessageKind::Approval);
+							peer_approvals.entry(*peer).or_insert_with(Vec::new).push(
+								IndirectSignedApprovalVote {
+									block_hash: *block_hash,
+									validator: *validator,
+									candidate_index,
+									signature: signature.clone(),
+								},
+							);
+						}
+					},
+					// Legacy peers don't understand coalesced approvals.
+					Some(ApprovalSignature::Coalesced(..))
+						if !supports_coalesced_approvals(peer_versions, peer) => {},
+					Some(ApprovalSignature::Coalesced(candidate_indices, signature)) => {
+						peer_coalesced_approvals.entry(*peer).or_insert_with(Vec::new).extend(
+							coalesced_approval_for_peer(
+								peer_knowledge,
+								*block_hash,
+								candidate_index,
+								*validator,
+								candidate_indices.clone(),
+								signature.clone(),
+							),
+						);
+					},
 				}
 			}
 		}
@@ -1598,6 +2193,56 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 	for (peer, approvals_packet) in peer_approvals {
 		send_approvals_batched(ctx.sender(), approvals_packet, peer).await;
 	}
+
+	for (peer, approvals_packet) in peer_coalesced_approvals {
+		if !approvals_packet.is_empty() {
+			send_coalesced_approvals_batched(ctx.sender(), approvals_packet, peer).await;
+		}
+	}
+}
+
+// Whether the peer is on a version of the validation protocol which supports coalesced approvals.
+fn supports_coalesced_approvals(
+	peer_versions: &HashMap<PeerId, ProtocolVersion>,
+	peer: &PeerId,
+) -> bool {
+	peer_versions.get(peer).map_or(false, |v| *v == ValidationVersion::V2.into())
+}
+
+// Returns the coalesced approval to send to a peer, if it is due.
+//
+// A coalesced approval is only considered at the last candidate it covers, once the
+// assignments of all covered candidates have been considered. It is only sent to peers
+// which know all of these assignments and don't know the approval yet.
+fn coalesced_approval_for_peer(
+	peer_knowledge: &mut PeerKnowledge,
+	block_hash: Hash,
+	candidate_index: CandidateIndex,
+	validator: ValidatorIndex,
+	candidate_indices: CandidateBitfield,
+	signature: ValidatorSignature,
+) -> Option<IndirectSignedCoalescedApprovalVote> {
+	if candidate_indices.last_one() != Some(candidate_index as usize) {
+		return None
+	}
+
+	let subjects = coalesced_subjects(block_hash, &candidate_indices, validator);
+	if !subjects.iter().all(|s| peer_knowledge.contains(s, MessageKind::Assignment)) ||
+		subjects.iter().all(|s| peer_knowledge.contains(s, MessageKind::Approval))
+	{
+		return None
+	}
+
+	for subject in subjects {
+		peer_knowledge.sent.insert(subject, MessageKind::Approval);
+	}
+
+	Some(IndirectSignedCoalescedApprovalVote {
+		block_hash,
+		candidate_indices,
+		validator,
+		signature,
+	})
 }
 
 /// Modify the reputation of a peer based on its behavior.
@@ -1712,6 +2357,23 @@ impl ApprovalDistribution {
 					.import_and_circulate_approval(ctx, metrics, MessageSource::Local, vote)
 					.await;
 			},
+			ApprovalDistributionMessage::DistributeCoalescedApproval(vote) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					"Distributing our coalesced approval vote on candidates (block={}, indices={:?})",
+					vote.block_hash,
+					vote.candidate_indices,
+				);
+
+				state
+					.import_and_circulate_coalesced_approval(
+						ctx,
+						metrics,
+						MessageSource::Local,
+						vote,
+					)
+					.await;
+			},
 			ApprovalDistributionMessage::GetApprovalSignatures(indices, tx) => {
 				let sigs = state.get_approval_signatures(indices);
 				if let Err(_) = tx.send(sigs) {
@@ -1805,3 +2467,30 @@ pub(crate) async fn send_approvals_batched(
 			.await;
 	}
 }
+
+/// The maximum amount of coalesced approvals per batch is 33% of maximum allowed by protocol.
+pub const MAX_COALESCED_APPROVAL_BATCH_SIZE: usize = ensure_size_not_zero(
+	MAX_NOTIFICATION_SIZE as usize / std::mem::size_of::<IndirectSignedCoalescedApprovalVote>() / 3,
+);
+
+/// Send coalesced approvals while honoring the `max_notification_size` of the protocol.
+pub(crate) async fn send_coalesced_approvals_batched(
+	sender: &mut impl overseer::ApprovalDistributionSenderTrait,
+	approvals: Vec<IndirectSignedCoalescedApprovalVote>,
+	peer: PeerId,
+) {
+	let mut batches = approvals.into_iter().peekable();
+
+	while batches.peek().is_some() {
+		let batch: Vec<_> = batches.by_ref().take(MAX_COALESCED_APPROVAL_BATCH_SIZE).collect();
+
+		sender
+			.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+				vec![peer],
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::CoalescedApprovals(batch),
+				)),
+			))
+			.await;
+	}
+}
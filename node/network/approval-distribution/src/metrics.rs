@@ -24,6 +24,8 @@ pub struct Metrics(Option<MetricsInner>);
 struct MetricsInner {
 	assignments_imported_total: prometheus::Counter<prometheus::U64>,
 	approvals_imported_total: prometheus::Counter<prometheus::U64>,
+	coalesced_approvals_imported_total: prometheus::Counter<prometheus::U64>,
+	approval_batch_size: prometheus::Histogram,
 	unified_with_peer_total: prometheus::Counter<prometheus::U64>,
 	aggression_l1_messages_total: prometheus::Counter<prometheus::U64>,
 	aggression_l2_messages_total: prometheus::Counter<prometheus::U64>,
@@ -46,6 +48,18 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_coalesced_approval_imported(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.coalesced_approvals_imported_total.inc();
+		}
+	}
+
+	pub(crate) fn on_approval_batch(&self, size: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.approval_batch_size.observe(size as f64);
+		}
+	}
+
 	pub(crate) fn on_unify_with_peer(&self) {
 		if let Some(metrics) = &self.0 {
 			metrics.unified_with_peer_total.inc();
@@ -102,6 +116,20 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			coalesced_approvals_imported_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_coalesced_approvals_imported_total",
+					"Number of valid approvals covering multiple candidates imported locally or from other peers.",
+				)?,
+				registry,
+			)?,
+			approval_batch_size: prometheus::register(
+				prometheus::Histogram::with_opts(prometheus::HistogramOpts::new(
+					"polkadot_parachain_approval_distribution_approval_batch_size",
+					"Number of approvals from a peer sent to approval voting in one batch.",
+				).buckets(vec![2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0]))?,
+				registry,
+			)?,
 			unified_with_peer_total: prometheus::register(
 				prometheus::Counter::new(
 					"polkadot_parachain_unified_with_peer_total",
@@ -216,13 +216,22 @@ async fn setup_peer_with_view(
 	virtual_overseer: &mut VirtualOverseer,
 	peer_id: &PeerId,
 	view: View,
+) {
+	setup_peer_with_view_and_version(virtual_overseer, peer_id, view, ValidationVersion::V1).await
+}
+
+async fn setup_peer_with_view_and_version(
+	virtual_overseer: &mut VirtualOverseer,
+	peer_id: &PeerId,
+	view: View,
+	version: ValidationVersion,
 ) {
 	overseer_send(
 		virtual_overseer,
 		ApprovalDistributionMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerConnected(
 			peer_id.clone(),
 			ObservedRole::Full,
-			ValidationVersion::V1.into(),
+			version.into(),
 			None,
 		)),
 	)
@@ -252,6 +261,21 @@ async fn send_message_from_peer(
 	.await;
 }
 
+async fn send_v2_message_from_peer(
+	virtual_overseer: &mut VirtualOverseer,
+	peer_id: &PeerId,
+	msg: protocol_v2::ApprovalDistributionMessage,
+) {
+	overseer_send(
+		virtual_overseer,
+		ApprovalDistributionMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
+			peer_id.clone(),
+			Versioned::V2(msg),
+		)),
+	)
+	.await;
+}
+
 fn fake_assignment_cert(block_hash: Hash, validator: ValidatorIndex) -> IndirectAssignmentCert {
 	let ctx = schnorrkel::signing_context(RELAY_VRF_MODULO_CONTEXT);
 	let msg = b"WhenParachains?";
@@ -626,6 +650,106 @@ fn import_approval_happy_path() {
 	});
 }
 
+#[test]
+fn import_coalesced_approval_happy_path() {
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let peer_d = PeerId::random();
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+
+	let _ = test_harness(State::default(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		// setup peers, `peer_d` is a legacy peer which doesn't understand coalesced approvals
+		setup_peer_with_view(overseer, &peer_a, view![]).await;
+		setup_peer_with_view_and_version(overseer, &peer_b, view![hash], ValidationVersion::V2)
+			.await;
+		setup_peer_with_view_and_version(overseer, &peer_c, view![hash], ValidationVersion::V2)
+			.await;
+		setup_peer_with_view(overseer, &peer_d, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 1,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
+		overseer_send(overseer, msg).await;
+
+		// import assignments for both candidates locally
+		let validator_index = ValidatorIndex(0);
+		for candidate_index in 0..2u32 {
+			let cert = fake_assignment_cert(hash, validator_index);
+			overseer_send(
+				overseer,
+				ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index),
+			)
+			.await;
+
+			assert_matches!(
+				overseer_recv(overseer).await,
+				AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+					peers,
+					Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+						protocol_v1::ApprovalDistributionMessage::Assignments(assignments)
+					))
+				)) => {
+					assert_eq!(peers.len(), 3);
+					assert_eq!(assignments.len(), 1);
+				}
+			);
+		}
+
+		// send an approval of both candidates from peer_b
+		let approval = IndirectSignedCoalescedApprovalVote {
+			block_hash: hash,
+			candidate_indices: CandidateBitfield::repeat(true, 2),
+			validator: validator_index,
+			signature: dummy_signature(),
+		};
+		let msg =
+			protocol_v2::ApprovalDistributionMessage::CoalescedApprovals(vec![approval.clone()]);
+		send_v2_message_from_peer(overseer, &peer_b, msg.clone()).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportCoalescedApproval(
+				vote,
+				tx,
+			)) => {
+				assert_eq!(vote, approval);
+				tx.send(ApprovalCheckResult::Accepted).unwrap();
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_b, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::CoalescedApprovals(approvals)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_c]);
+				assert_eq!(approvals, vec![approval]);
+			}
+		);
+
+		// the same approval again is a duplicate
+		send_v2_message_from_peer(overseer, &peer_b, msg).await;
+		expect_reputation_change(overseer, &peer_b, COST_DUPLICATE_MESSAGE).await;
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn import_approval_bad() {
 	let peer_a = PeerId::random();
@@ -707,6 +831,111 @@ fn import_approval_bad() {
 	});
 }
 
+#[test]
+fn import_approvals_in_batch() {
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+
+	let _ = test_harness(State::default(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		// setup peers
+		setup_peer_with_view(overseer, &peer_a, view![]).await;
+		setup_peer_with_view(overseer, &peer_b, view![hash]).await;
+		setup_peer_with_view(overseer, &peer_c, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 1,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
+		overseer_send(overseer, msg).await;
+
+		// import assignments for both candidates locally
+		let validator_index = ValidatorIndex(0);
+		for candidate_index in 0..2u32 {
+			let cert = fake_assignment_cert(hash, validator_index);
+			overseer_send(
+				overseer,
+				ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index),
+			)
+			.await;
+
+			assert_matches!(
+				overseer_recv(overseer).await,
+				AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+					peers,
+					Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+						protocol_v1::ApprovalDistributionMessage::Assignments(assignments)
+					))
+				)) => {
+					assert_eq!(peers.len(), 2);
+					assert_eq!(assignments.len(), 1);
+				}
+			);
+		}
+
+		let approvals = (0..2u32)
+			.map(|candidate_index| IndirectSignedApprovalVote {
+				block_hash: hash,
+				candidate_index,
+				validator: validator_index,
+				signature: dummy_signature(),
+			})
+			.collect::<Vec<_>>();
+
+		// send both approvals from peer_b, one of them twice
+		let msg = protocol_v1::ApprovalDistributionMessage::Approvals(vec![
+			approvals[0].clone(),
+			approvals[1].clone(),
+			approvals[0].clone(),
+		]);
+		send_message_from_peer(overseer, &peer_b, msg).await;
+
+		expect_reputation_change(overseer, &peer_b, COST_DUPLICATE_MESSAGE).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApprovals(
+				votes,
+				tx,
+			)) => {
+				assert_eq!(votes, approvals);
+				tx.send(vec![
+					ApprovalCheckResult::Accepted,
+					ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(validator_index)),
+				])
+				.unwrap();
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_b, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+					protocol_v1::ApprovalDistributionMessage::Approvals(sent_approvals)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_c]);
+				assert_eq!(sent_approvals, vec![approvals[0].clone()]);
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_b, COST_INVALID_MESSAGE).await;
+		virtual_overseer
+	});
+}
+
 /// make sure we clean up the state on block finalized
 #[test]
 fn update_our_view() {
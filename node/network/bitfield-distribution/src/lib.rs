@@ -19,6 +19,11 @@
 //! In case this node is a validator, gossips its own signed availability bitfield
 //! for a particular relay parent.
 //! Independently of that, gossips on received messages from peers to other interested peers.
+//!
+//! Peers on version 2 of the validation protocol receive bitfields in compact encoding: either
+//! run-length encoded or as delta against a bitfield of the same validator under another relay
+//! parent, which the peer is known to have. The signature is checked over the reconstructed
+//! bitfield.
 
 #![deny(unused_crate_dependencies)]
 
@@ -29,7 +34,10 @@ use polkadot_node_network_protocol::{
 	grid_topology::{
 		GridNeighbors, RandomRouting, RequiredRouting, SessionBoundGridTopologyStorage,
 	},
-	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	peer_set::{ProtocolVersion, ValidationVersion},
+	v1 as protocol_v1,
+	v2::{self as protocol_v2, CompactBitfield, CompactSignedBitfield},
+	OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
 use polkadot_node_subsystem::{
 	jaeger, messages::*, overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, PerLeafSpan,
@@ -37,7 +45,11 @@ use polkadot_node_subsystem::{
 };
 use polkadot_node_subsystem_util::{self as util};
 
-use polkadot_primitives::{Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId};
+use parity_scale_codec::Encode;
+use polkadot_primitives::{
+	AvailabilityBitfield, Hash, SignedAvailabilityBitfield, SigningContext,
+	UncheckedSignedAvailabilityBitfield, ValidatorId,
+};
 use rand::{CryptoRng, Rng, SeedableRng};
 use std::collections::{HashMap, HashSet};
 
@@ -52,12 +64,22 @@ const COST_SIGNATURE_INVALID: Rep = Rep::CostMajor("Bitfield signature invalid")
 const COST_VALIDATOR_INDEX_INVALID: Rep = Rep::CostMajor("Bitfield validator index invalid");
 const COST_MISSING_PEER_SESSION_KEY: Rep = Rep::CostMinor("Missing peer session key");
 const COST_NOT_IN_VIEW: Rep = Rep::CostMinor("Not interested in that parent hash");
+const COST_INVALID_COMPACT_BITFIELD: Rep = Rep::CostMajor("Invalid compact bitfield encoding");
+const COST_MISSING_DELTA_BASE: Rep = Rep::CostMinor("Bitfield delta base unknown");
+const COST_DELTA_SIGNATURE_INVALID: Rep =
+	Rep::CostMinor("Bitfield reconstructed from delta has invalid signature");
 const COST_PEER_DUPLICATE_MESSAGE: Rep =
 	Rep::CostMinorRepeated("Peer sent the same message multiple times");
 const BENEFIT_VALID_MESSAGE_FIRST: Rep =
 	Rep::BenefitMinorFirst("Valid message with new information");
 const BENEFIT_VALID_MESSAGE: Rep = Rep::BenefitMinor("Valid message");
 
+/// The maximum length of a bitfield reconstructed from a compact encoding.
+///
+/// Availability bitfields have one bit per availability core, this is way more than there will
+/// ever be.
+const MAX_COMPACT_BITFIELD_LEN: usize = 16 * 1024;
+
 /// Checked signed availability bitfield that is distributed
 /// to other peers.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -79,6 +101,28 @@ impl BitfieldGossipMessage {
 			self.signed_availability.into(),
 		))
 	}
+
+	/// Convert into a message for peers understanding compact bitfields, in the given encoding.
+	fn into_encoded_validation_protocol(
+		self,
+		encoding: Option<CompactBitfield>,
+	) -> net_protocol::VersionedValidationProtocol {
+		let message = match encoding {
+			None => protocol_v2::BitfieldDistributionMessage::Bitfield(
+				self.relay_parent,
+				self.signed_availability.into(),
+			),
+			Some(bits) => protocol_v2::BitfieldDistributionMessage::CompactBitfield(
+				self.relay_parent,
+				CompactSignedBitfield {
+					validator_index: self.signed_availability.validator_index(),
+					signature: self.signed_availability.signature().clone(),
+					bits,
+				},
+			),
+		};
+		Versioned::V2(message).into()
+	}
 }
 
 /// Data used to track information of peers and relay parents the
@@ -89,6 +133,11 @@ struct ProtocolState {
 	/// to determine what is relevant to them.
 	peer_views: HashMap<PeerId, View>,
 
+	/// The validation protocol versions of all active peers.
+	///
+	/// Peers without an entry are treated as legacy peers.
+	peer_versions: HashMap<PeerId, ProtocolVersion>,
+
 	/// The current and previous gossip topologies
 	topologies: SessionBoundGridTopologyStorage,
 
@@ -142,6 +191,17 @@ impl PerRelayParentData {
 		}
 	}
 
+	/// Determines if the bitfield signed by a validator under this relay parent is known to
+	/// the peer, because we sent it.
+	///
+	/// Only bitfields we sent ourselves can serve as base for a delta, as only those are
+	/// guaranteed to be the same for both of us.
+	fn message_from_validator_sent_to_peer(&self, peer: &PeerId, signed_by: &ValidatorId) -> bool {
+		self.message_sent_to_peer
+			.get(peer)
+			.map_or(false, |pubkeys| pubkeys.contains(signed_by))
+	}
+
 	/// Determines if that particular message signed by a
 	/// validator is needed by the given peer.
 	fn message_from_validator_needed_by_peer(
@@ -332,13 +392,15 @@ async fn handle_bitfield_distribution<Context>(
 
 	relay_message(
 		ctx,
-		job_data,
+		&mut state.per_relay_parent,
 		topology,
 		&mut state.peer_views,
+		&state.peer_versions,
 		validator,
 		msg,
 		required_routing,
 		rng,
+		metrics,
 	)
 	.await;
 
@@ -351,15 +413,21 @@ async fn handle_bitfield_distribution<Context>(
 #[overseer::contextbounds(BitfieldDistribution, prefix=self::overseer)]
 async fn relay_message<Context>(
 	ctx: &mut Context,
-	job_data: &mut PerRelayParentData,
+	per_relay_parent: &mut HashMap<Hash, PerRelayParentData>,
 	topology_neighbors: &GridNeighbors,
 	peer_views: &mut HashMap<PeerId, View>,
+	peer_versions: &HashMap<PeerId, ProtocolVersion>,
 	validator: ValidatorId,
 	message: BitfieldGossipMessage,
 	required_routing: RequiredRouting,
 	rng: &mut (impl CryptoRng + Rng),
+	metrics: &Metrics,
 ) {
 	let relay_parent = message.relay_parent;
+	let job_data = match per_relay_parent.get_mut(&relay_parent) {
+		Some(job_data) => job_data,
+		None => return,
+	};
 	let span = job_data.span.child("relay-msg");
 
 	let _span = span.child("provisionable");
@@ -427,12 +495,146 @@ async fn relay_message<Context>(
 		);
 	} else {
 		let _span = span.child("gossip");
-		ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+		send_bitfield(
+			ctx,
+			per_relay_parent,
+			peer_views,
+			peer_versions,
 			interested_peers,
-			message.into_validation_protocol(),
+			&validator,
+			message,
+			metrics,
+		)
+		.await;
+	}
+}
+
+/// Send a bitfield to the given peers.
+///
+/// Legacy peers get the full bitfield, all other peers get the smallest of the full bitfield, the
+/// run-length encoded bitfield and the delta against any bitfield of the same validator the peer
+/// is known to have.
+#[overseer::contextbounds(BitfieldDistribution, prefix=self::overseer)]
+async fn send_bitfield<Context>(
+	ctx: &mut Context,
+	per_relay_parent: &HashMap<Hash, PerRelayParentData>,
+	peer_views: &HashMap<PeerId, View>,
+	peer_versions: &HashMap<PeerId, ProtocolVersion>,
+	peers: Vec<PeerId>,
+	validator: &ValidatorId,
+	message: BitfieldGossipMessage,
+	metrics: &Metrics,
+) {
+	let (compact_peers, legacy_peers): (Vec<_>, Vec<_>) = peers.into_iter().partition(|peer| {
+		peer_versions.get(peer).map_or(false, |v| *v == ValidationVersion::V2.into())
+	});
+
+	if !legacy_peers.is_empty() {
+		ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+			legacy_peers,
+			message.clone().into_validation_protocol(),
 		))
 		.await;
 	}
+
+	if compact_peers.is_empty() {
+		return
+	}
+
+	let bits = &message.signed_availability.payload().0;
+	let full_len = message.signed_availability.payload().encoded_size();
+	let run_length = CompactBitfield::run_length(bits);
+	let run_length_len = run_length.encoded_size();
+
+	// All possible deltas, smallest first.
+	let mut deltas = per_relay_parent
+		.iter()
+		.filter(|(base, _)| **base != message.relay_parent)
+		.filter_map(|(base, job_data)| {
+			let base_message = job_data.one_per_validator.get(validator)?;
+			let delta =
+				CompactBitfield::delta(base, &base_message.signed_availability.payload().0, bits)?;
+			Some((delta.encoded_size(), *base, delta))
+		})
+		.collect::<Vec<_>>();
+	deltas.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+	// Group the peers by the encoding they get, `None` being the full bitfield.
+	let mut groups: Vec<(Option<CompactBitfield>, usize, Vec<PeerId>)> = Vec::new();
+	for peer in compact_peers {
+		let delta = deltas.iter().find(|(_, base, _)| {
+			peer_views.get(&peer).map_or(false, |view| view.contains(base)) &&
+				per_relay_parent.get(base).map_or(false, |job_data| {
+					job_data.message_from_validator_sent_to_peer(&peer, validator)
+				})
+		});
+
+		let (encoding, len) = match delta {
+			Some((len, _, delta)) if *len < run_length_len.min(full_len) =>
+				(Some(delta.clone()), *len),
+			_ if run_length_len < full_len => (Some(run_length.clone()), run_length_len),
+			_ => (None, full_len),
+		};
+
+		match groups.iter_mut().find(|(e, _, _)| e == &encoding) {
+			Some((_, _, peers)) => peers.push(peer),
+			None => groups.push((encoding, len, vec![peer])),
+		}
+	}
+
+	for (encoding, len, peers) in groups {
+		match &encoding {
+			Some(CompactBitfield::RunLength(_)) =>
+				metrics.on_compact_bitfields_sent("run_length", peers.len(), full_len - len),
+			Some(CompactBitfield::Delta { .. }) =>
+				metrics.on_compact_bitfields_sent("delta", peers.len(), full_len - len),
+			None => {},
+		}
+
+		ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+			peers,
+			message.clone().into_encoded_validation_protocol(encoding),
+		))
+		.await;
+	}
+}
+
+/// Reconstruct a compactly encoded bitfield received from a peer.
+///
+/// The signature is not checked here.
+fn reconstruct_bitfield(
+	state: &ProtocolState,
+	relay_parent: Hash,
+	bitfield: CompactSignedBitfield,
+) -> Result<UncheckedSignedAvailabilityBitfield, Rep> {
+	let CompactSignedBitfield { validator_index, signature, bits } = bitfield;
+	let job_data = state.per_relay_parent.get(&relay_parent).ok_or(COST_NOT_IN_VIEW)?;
+
+	let base_bits = if bits.is_delta() {
+		let validator = job_data
+			.validator_set
+			.get(validator_index.0 as usize)
+			.ok_or(COST_VALIDATOR_INDEX_INVALID)?;
+		let base_message = state
+			.per_relay_parent
+			.iter()
+			.filter(|(base, _)| **base != relay_parent && bits.has_base(base))
+			.find_map(|(_, base_data)| base_data.one_per_validator.get(validator))
+			.ok_or(COST_MISSING_DELTA_BASE)?;
+		Some(&base_message.signed_availability.payload().0)
+	} else {
+		None
+	};
+
+	let bits = bits
+		.reconstruct(base_bits.map(|b| b.as_bitslice()), MAX_COMPACT_BITFIELD_LEN)
+		.ok_or(COST_INVALID_COMPACT_BITFIELD)?;
+
+	Ok(UncheckedSignedAvailabilityBitfield::new(
+		AvailabilityBitfield(bits),
+		validator_index,
+		signature,
+	))
 }
 
 /// Handle an incoming message from a peer.
@@ -442,10 +644,39 @@ async fn process_incoming_peer_message<Context>(
 	state: &mut ProtocolState,
 	metrics: &Metrics,
 	origin: PeerId,
-	message: protocol_v1::BitfieldDistributionMessage,
+	message: net_protocol::BitfieldDistributionMessage,
 	rng: &mut (impl CryptoRng + Rng),
 ) {
-	let protocol_v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) = message;
+	let (relay_parent, bitfield, from_delta) = match message {
+		Versioned::V1(protocol_v1::BitfieldDistributionMessage::Bitfield(
+			relay_parent,
+			bitfield,
+		)) |
+		Versioned::V2(protocol_v2::BitfieldDistributionMessage::Bitfield(
+			relay_parent,
+			bitfield,
+		)) => (relay_parent, bitfield, false),
+		Versioned::V2(protocol_v2::BitfieldDistributionMessage::CompactBitfield(
+			relay_parent,
+			bitfield,
+		)) => {
+			let from_delta = bitfield.bits.is_delta();
+			match reconstruct_bitfield(state, relay_parent, bitfield) {
+				Ok(bitfield) => (relay_parent, bitfield, from_delta),
+				Err(rep) => {
+					gum::debug!(
+						target: LOG_TARGET,
+						peer = %origin,
+						?relay_parent,
+						?rep,
+						"Failed to reconstruct compact bitfield"
+					);
+					modify_reputation(ctx.sender(), relay_parent, origin, rep).await;
+					return
+				},
+			}
+		},
+	};
 	gum::trace!(
 		target: LOG_TARGET,
 		peer = %origin,
@@ -523,7 +754,11 @@ async fn process_incoming_peer_message<Context>(
 	}
 	let signed_availability = match bitfield.try_into_checked(&signing_context, &validator) {
 		Err(_) => {
-			modify_reputation(ctx.sender(), relay_parent, origin, COST_SIGNATURE_INVALID).await;
+			// The base of a delta might differ between us and the peer in case of equivocations,
+			// so the peer can't be sure about the signature.
+			let rep =
+				if from_delta { COST_DELTA_SIGNATURE_INVALID } else { COST_SIGNATURE_INVALID };
+			modify_reputation(ctx.sender(), relay_parent, origin, rep).await;
 			return
 		},
 		Ok(bitfield) => bitfield,
@@ -542,13 +777,15 @@ async fn process_incoming_peer_message<Context>(
 
 	relay_message(
 		ctx,
-		job_data,
+		&mut state.per_relay_parent,
 		topology,
 		&mut state.peer_views,
+		&state.peer_versions,
 		validator,
 		message,
 		required_routing,
 		rng,
+		metrics,
 	)
 	.await;
 
@@ -568,15 +805,17 @@ async fn handle_network_msg<Context>(
 	let _timer = metrics.time_handle_network_msg();
 
 	match bridge_message {
-		NetworkBridgeEvent::PeerConnected(peer, role, _, _) => {
-			gum::trace!(target: LOG_TARGET, ?peer, ?role, "Peer connected");
+		NetworkBridgeEvent::PeerConnected(peer, role, protocol_version, _) => {
+			gum::trace!(target: LOG_TARGET, ?peer, ?role, ?protocol_version, "Peer connected");
 			// insert if none already present
 			state.peer_views.entry(peer).or_default();
+			state.peer_versions.insert(peer, protocol_version);
 		},
 		NetworkBridgeEvent::PeerDisconnected(peer) => {
 			gum::trace!(target: LOG_TARGET, ?peer, "Peer disconnected");
 			// get rid of superfluous data
 			state.peer_views.remove(&peer);
+			state.peer_versions.remove(&peer);
 		},
 		NetworkBridgeEvent::NewGossipTopology(gossip_topology) => {
 			let session_index = gossip_topology.session;
@@ -605,19 +844,19 @@ async fn handle_network_msg<Context>(
 				// it might have had an existing view, we use to initialize
 				// and minimize the delta on `PeerViewChange` to be sent
 				if let Some(old_view) = state.peer_views.remove(&new_peer) {
-					handle_peer_view_change(ctx, state, new_peer, old_view, rng).await;
+					handle_peer_view_change(ctx, state, metrics, new_peer, old_view, rng).await;
 				}
 			}
 		},
 		NetworkBridgeEvent::PeerViewChange(peerid, new_view) => {
 			gum::trace!(target: LOG_TARGET, ?peerid, ?new_view, "Peer view change");
-			handle_peer_view_change(ctx, state, peerid, new_view, rng).await;
+			handle_peer_view_change(ctx, state, metrics, peerid, new_view, rng).await;
 		},
 		NetworkBridgeEvent::OurViewChange(new_view) => {
 			gum::trace!(target: LOG_TARGET, ?new_view, "Our view change");
 			handle_our_view_change(state, new_view);
 		},
-		NetworkBridgeEvent::PeerMessage(remote, Versioned::V1(message)) =>
+		NetworkBridgeEvent::PeerMessage(remote, message) =>
 			process_incoming_peer_message(ctx, state, metrics, remote, message, rng).await,
 	}
 }
@@ -650,6 +889,7 @@ fn handle_our_view_change(state: &mut ProtocolState, view: OurView) {
 async fn handle_peer_view_change<Context>(
 	ctx: &mut Context,
 	state: &mut ProtocolState,
+	metrics: &Metrics,
 	origin: PeerId,
 	view: View,
 	rng: &mut (impl CryptoRng + Rng),
@@ -698,7 +938,7 @@ async fn handle_peer_view_change<Context>(
 		.collect();
 
 	for (validator, message) in delta_set.into_iter() {
-		send_tracked_gossip_message(ctx, state, origin, validator, message).await;
+		send_tracked_gossip_message(ctx, state, metrics, origin, validator, message).await;
 	}
 }
 
@@ -707,6 +947,7 @@ async fn handle_peer_view_change<Context>(
 async fn send_tracked_gossip_message<Context>(
 	ctx: &mut Context,
 	state: &mut ProtocolState,
+	metrics: &Metrics,
 	dest: PeerId,
 	validator: ValidatorId,
 	message: BitfieldGossipMessage,
@@ -728,10 +969,16 @@ async fn send_tracked_gossip_message<Context>(
 
 	job_data.message_sent_to_peer.entry(dest).or_default().insert(validator.clone());
 
-	ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
+	send_bitfield(
+		ctx,
+		&state.per_relay_parent,
+		&state.peer_views,
+		&state.peer_versions,
 		vec![dest],
-		message.into_validation_protocol(),
-	))
+		&validator,
+		message,
+		metrics,
+	)
 	.await;
 }
 
@@ -20,6 +20,8 @@ use polkadot_node_subsystem_util::metrics::{prometheus, Metrics as MetricsTrait}
 struct MetricsInner {
 	sent_own_availability_bitfields: prometheus::Counter<prometheus::U64>,
 	received_availability_bitfields: prometheus::Counter<prometheus::U64>,
+	sent_compact_bitfields: prometheus::CounterVec<prometheus::U64>,
+	compact_bitfields_bytes_saved: prometheus::Counter<prometheus::U64>,
 	active_leaves_update: prometheus::Histogram,
 	handle_bitfield_distribution: prometheus::Histogram,
 	handle_network_msg: prometheus::Histogram,
@@ -42,6 +44,23 @@ impl Metrics {
 		}
 	}
 
+	/// Note compactly encoded bitfields sent to `count` peers, saving `bytes_saved` bytes per peer
+	/// compared to the full encoding.
+	pub(crate) fn on_compact_bitfields_sent(
+		&self,
+		encoding: &'static str,
+		count: usize,
+		bytes_saved: usize,
+	) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.sent_compact_bitfields
+				.with_label_values(&[encoding])
+				.inc_by(count as u64);
+			metrics.compact_bitfields_bytes_saved.inc_by((count * bytes_saved) as u64);
+		}
+	}
+
 	/// Provide a timer for `active_leaves_update` which observes on drop.
 	pub(crate) fn time_active_leaves_update(
 		&self,
@@ -81,6 +100,23 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			sent_compact_bitfields: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_bitfield_distribution_compact_bitfields_sent_total",
+						"Number of availability bitfields sent to peers in compact encoding.",
+					),
+					&["encoding"],
+				)?,
+				registry,
+			)?,
+			compact_bitfields_bytes_saved: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_bitfield_distribution_bytes_saved_total",
+					"Number of bytes saved by sending availability bitfields in compact encoding.",
+				)?,
+				registry,
+			)?,
 			active_leaves_update: prometheus::register(
 				prometheus::Histogram::with_opts(prometheus::HistogramOpts::new(
 					"polkadot_parachain_bitfield_distribution_active_leaves_update",
@@ -84,6 +84,7 @@ fn prewarmed_state(
 				},
 		},
 		peer_views: peers.iter().cloned().map(|peer| (peer, view!(relay_parent))).collect(),
+		peer_versions: Default::default(),
 		topologies,
 		view: our_view!(relay_parent),
 	}
@@ -465,13 +466,15 @@ fn do_not_relay_message_twice() {
 
 		relay_message(
 			&mut ctx,
-			state.per_relay_parent.get_mut(&hash).unwrap(),
+			&mut state.per_relay_parent,
 			&gossip_peers,
 			&mut state.peer_views,
+			&state.peer_versions,
 			validator.clone(),
 			msg.clone(),
 			RequiredRouting::GridXY,
 			&mut rng,
+			&Default::default(),
 		)
 		.await;
 
@@ -501,13 +504,15 @@ fn do_not_relay_message_twice() {
 		// Relaying the message a second time shouldn't work.
 		relay_message(
 			&mut ctx,
-			state.per_relay_parent.get_mut(&hash).unwrap(),
+			&mut state.per_relay_parent,
 			&gossip_peers,
 			&mut state.peer_views,
+			&state.peer_versions,
 			validator.clone(),
 			msg.clone(),
 			RequiredRouting::GridXY,
 			&mut rng,
+			&Default::default(),
 		)
 		.await;
 
@@ -955,3 +960,330 @@ fn need_message_works() {
 	// also not ok for Bob
 	assert!(false == pretend_send(&mut state, peer_b, &validator_set[1]));
 }
+
+#[test]
+fn compact_bitfield_encodings_reconstruct() {
+	use parity_scale_codec::Compact;
+
+	let base_hash = Hash::repeat_byte(1);
+	let base = bitvec![u8, bitvec::order::Lsb0; 1, 1, 0, 0, 0, 1, 0, 1, 1, 1];
+	let bits = bitvec![u8, bitvec::order::Lsb0; 0, 1, 0, 0, 1, 1, 0, 1, 1, 0];
+
+	let run_length = CompactBitfield::run_length(&bits);
+	assert!(!run_length.is_delta());
+	assert_eq!(run_length.reconstruct(None, bits.len()), Some(bits.clone()));
+	// Too long:
+	assert_eq!(run_length.reconstruct(None, bits.len() - 1), None);
+
+	let delta = CompactBitfield::delta(&base_hash, &base, &bits).unwrap();
+	assert!(delta.is_delta());
+	assert!(delta.has_base(&base_hash));
+	assert!(!delta.has_base(&Hash::repeat_byte(2)));
+	assert_eq!(delta.reconstruct(Some(base.as_bitslice()), bits.len()), Some(bits.clone()));
+	// The base is required:
+	assert_eq!(delta.reconstruct(None, bits.len()), None);
+
+	// Bitfields of different length can't be delta encoded.
+	assert_eq!(CompactBitfield::delta(&base_hash, &base[..5], &bits), None);
+
+	// Flipped bits out of bounds of the base are invalid.
+	let out_of_bounds = CompactBitfield::Delta { base: [1; 4], flipped: vec![Compact(10)] };
+	assert_eq!(out_of_bounds.reconstruct(Some(base.as_bitslice()), bits.len()), None);
+}
+
+fn into_compact(
+	signed: &SignedAvailabilityBitfield,
+	bits: CompactBitfield,
+) -> CompactSignedBitfield {
+	CompactSignedBitfield {
+		validator_index: signed.validator_index(),
+		signature: signed.signature().clone(),
+		bits,
+	}
+}
+
+#[test]
+fn relay_compact_bitfields_to_v2_peers() {
+	let _ = env_logger::builder()
+		.filter(None, log::LevelFilter::Trace)
+		.is_test(true)
+		.try_init();
+
+	let hash_a: Hash = [0; 32].into();
+	let hash_b: Hash = [1; 32].into();
+
+	// Peer A is a legacy peer, peer B and C understand compact bitfields, but only peer C knows
+	// the bitfield of the validator under `hash_a`.
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+
+	let (mut state, signing_context, keystore, validator) =
+		state_with_view(our_view![hash_a, hash_b], hash_b);
+
+	// Alternating bits, so run-length encoding doesn't pay off.
+	let base_bits =
+		bitvec::vec::BitVec::<u8, bitvec::order::Lsb0>::from_iter((0..64).map(|i| i % 2 == 0));
+	let mut bits = base_bits.clone();
+	bits.set(3, true);
+
+	let sign = |bits| {
+		executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			AvailabilityBitfield(bits),
+			&signing_context,
+			ValidatorIndex(0),
+			&validator,
+		))
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+	let signed_base = sign(base_bits.clone());
+	let signed_bitfield = sign(bits.clone());
+
+	{
+		let base_data = state.per_relay_parent.get_mut(&hash_a).unwrap();
+		base_data.one_per_validator.insert(
+			validator.clone(),
+			BitfieldGossipMessage { relay_parent: hash_a, signed_availability: signed_base },
+		);
+		base_data
+			.message_sent_to_peer
+			.entry(peer_c)
+			.or_default()
+			.insert(validator.clone());
+	}
+
+	state.peer_views.insert(peer_a, view![hash_b]);
+	state.peer_views.insert(peer_b, view![hash_b]);
+	state.peer_views.insert(peer_c, view![hash_a, hash_b]);
+	state.peer_versions.insert(peer_a, ValidationVersion::V1.into());
+	state.peer_versions.insert(peer_b, ValidationVersion::V2.into());
+	state.peer_versions.insert(peer_c, ValidationVersion::V2.into());
+
+	let msg = BitfieldGossipMessage { relay_parent: hash_b, signed_availability: signed_bitfield };
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+	let mut rng = dummy_rng();
+
+	executor::block_on(async move {
+		let mut gossip_peers = GridNeighbors::empty();
+		gossip_peers.peers_x = HashSet::from_iter(vec![peer_a, peer_b, peer_c].into_iter());
+
+		relay_message(
+			&mut ctx,
+			&mut state.per_relay_parent,
+			&gossip_peers,
+			&mut state.peer_views,
+			&state.peer_versions,
+			validator.clone(),
+			msg.clone(),
+			RequiredRouting::GridXY,
+			&mut rng,
+			&Default::default(),
+		)
+		.await;
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+				_,
+				ProvisionableData::Bitfield(h, _)
+			)) => {
+				assert_eq!(h, hash_b);
+			}
+		);
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::SendValidationMessage(peers, send_msg),
+			) => {
+				assert_eq!(peers, vec![peer_a]);
+				assert_eq!(send_msg, msg.clone().into_validation_protocol());
+			}
+		);
+
+		let delta = CompactBitfield::delta(&hash_a, &base_bits, &bits).unwrap();
+		let mut expected = vec![
+			(peer_b, msg.clone().into_encoded_validation_protocol(None)),
+			(peer_c, msg.clone().into_encoded_validation_protocol(Some(delta))),
+		];
+		for _ in 0..2 {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridgeTx(
+					NetworkBridgeTxMessage::SendValidationMessage(peers, send_msg),
+				) => {
+					assert_eq!(peers.len(), 1);
+					let i = expected.iter().position(|(p, _)| p == &peers[0]).unwrap();
+					assert_eq!(send_msg, expected.remove(i).1);
+				}
+			);
+		}
+
+		assert!(handle.recv().timeout(Duration::from_millis(10)).await.is_none());
+	});
+}
+
+#[test]
+fn receive_compact_bitfields() {
+	let _ = env_logger::builder()
+		.filter(None, log::LevelFilter::Trace)
+		.is_test(true)
+		.try_init();
+
+	let hash_a: Hash = [0; 32].into();
+	let hash_b: Hash = [1; 32].into();
+
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let peer_d = PeerId::random();
+
+	let (mut state, signing_context, keystore, validator) =
+		state_with_view(our_view![hash_a, hash_b], hash_b);
+
+	let base_bits = bitvec![u8, bitvec::order::Lsb0; 1u8; 32];
+	let mut bits = base_bits.clone();
+	bits.set(7, false);
+
+	let sign = |bits| {
+		executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			AvailabilityBitfield(bits),
+			&signing_context,
+			ValidatorIndex(0),
+			&validator,
+		))
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+	let signed_base = sign(base_bits.clone());
+	let signed_bitfield = sign(bits.clone());
+
+	state.per_relay_parent.get_mut(&hash_a).unwrap().one_per_validator.insert(
+		validator.clone(),
+		BitfieldGossipMessage { relay_parent: hash_a, signed_availability: signed_base },
+	);
+
+	let compact_message = {
+		let signed_bitfield = signed_bitfield.clone();
+		move |bits| {
+			Versioned::V2(protocol_v2::BitfieldDistributionMessage::CompactBitfield(
+				hash_b,
+				into_compact(&signed_bitfield, bits),
+			))
+		}
+	};
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+	let mut rng = dummy_rng();
+
+	executor::block_on(async move {
+		// Delta against a bitfield we don't have.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_c,
+				compact_message(
+					CompactBitfield::delta(&Hash::repeat_byte(2), &base_bits, &bits).unwrap()
+				),
+			),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_c);
+				assert_eq!(rep, COST_MISSING_DELTA_BASE)
+			}
+		);
+
+		// Delta resulting in the wrong bitfield.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_d,
+				compact_message(CompactBitfield::delta(&hash_a, &base_bits, &base_bits).unwrap()),
+			),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_d);
+				assert_eq!(rep, COST_DELTA_SIGNATURE_INVALID)
+			}
+		);
+
+		// Valid delta.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_b,
+				compact_message(CompactBitfield::delta(&hash_a, &base_bits, &bits).unwrap()),
+			),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+				_,
+				ProvisionableData::Bitfield(hash, signed)
+			)) => {
+				assert_eq!(hash, hash_b);
+				assert_eq!(signed, signed_bitfield)
+			}
+		);
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, BENEFIT_VALID_MESSAGE_FIRST)
+			}
+		);
+
+		// The same bitfield run-length encoded.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(
+				peer_a,
+				compact_message(CompactBitfield::run_length(&bits)),
+			),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_a);
+				assert_eq!(rep, BENEFIT_VALID_MESSAGE)
+			}
+		);
+	});
+}
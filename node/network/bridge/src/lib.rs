@@ -50,6 +50,14 @@ use self::network::Network;
 mod metrics;
 pub use self::metrics::Metrics;
 
+/// Accounting and prioritization of the traffic on the parachain peer-sets.
+mod traffic;
+pub use self::traffic::{NetworkTraffic, TopTalker, TrafficClass};
+
+/// Aggregation and persistence of peer reputation changes, including operator ban and allow lists.
+mod reputation;
+pub use self::reputation::{PeerFilter, PeerScore, ReputationAggregator};
+
 mod errors;
 pub(crate) use self::errors::Error;
 
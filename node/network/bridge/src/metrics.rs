@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{PeerSet, ProtocolVersion};
+use super::{PeerSet, ProtocolVersion, TrafficClass};
 use polkadot_node_metrics::metrics::{self, prometheus};
+use polkadot_node_network_protocol::request_response::Protocol;
 
 /// Metrics for the network bridge.
 #[derive(Clone, Default)]
@@ -94,6 +95,33 @@ impl Metrics {
 		}
 	}
 
+	/// Note bytes of notifications sent on the protocol with the given label.
+	pub fn on_protocol_bytes_sent(&self, protocol: &str, size: usize) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics.protocol_bytes_sent.with_label_values(&[protocol]).inc_by(size as u64);
+		}
+	}
+
+	/// Note bytes of notifications received on the protocol with the given label.
+	pub fn on_protocol_bytes_received(&self, protocol: &str, size: usize) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics
+				.protocol_bytes_received
+				.with_label_values(&[protocol])
+				.inc_by(size as u64);
+		}
+	}
+
+	/// Note notifications of the given class which were not sent because of the outbound budget.
+	pub fn on_notifications_dropped(&self, class: TrafficClass, count: usize) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics
+				.notifications_dropped
+				.with_label_values(&[class.label()])
+				.inc_by(count as u64);
+		}
+	}
+
 	pub fn note_desired_peer_count(&self, peer_set: PeerSet, size: usize) {
 		self.0.as_ref().map(|metrics| {
 			metrics
@@ -108,6 +136,26 @@ impl Metrics {
 			metrics.report_events.inc()
 		}
 	}
+
+	/// Note a request sent on the given protocol, after negotiating the version with the peer.
+	pub fn on_request_sent(&self, protocol: Protocol) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics
+				.requests_sent
+				.with_label_values(&[protocol.get_base_name(), &protocol.get_version().to_string()])
+				.inc();
+		}
+	}
+
+	/// Note the bytes of a request payload about to be sent on the given protocol.
+	pub fn on_request_bytes_sent(&self, protocol: Protocol, size: usize) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics
+				.protocol_bytes_sent
+				.with_label_values(&[protocol.get_base_name()])
+				.inc_by(size as u64);
+		}
+	}
 }
 
 #[derive(Clone)]
@@ -123,6 +171,12 @@ pub(crate) struct MetricsInner {
 
 	bytes_received: prometheus::CounterVec<prometheus::U64>,
 	bytes_sent: prometheus::CounterVec<prometheus::U64>,
+
+	protocol_bytes_received: prometheus::CounterVec<prometheus::U64>,
+	protocol_bytes_sent: prometheus::CounterVec<prometheus::U64>,
+	notifications_dropped: prometheus::CounterVec<prometheus::U64>,
+
+	requests_sent: prometheus::CounterVec<prometheus::U64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -217,6 +271,46 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			protocol_bytes_received: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_protocol_bytes_received_total",
+						"The number of notification bytes received, by the protocol of the subsystem they are meant for",
+					),
+					&["protocol"]
+				)?,
+				registry,
+			)?,
+			protocol_bytes_sent: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_protocol_bytes_sent_total",
+						"The number of notification and request bytes sent, by the protocol of the subsystem they originate from",
+					),
+					&["protocol"]
+				)?,
+				registry,
+			)?,
+			notifications_dropped: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_notifications_dropped_total",
+						"The number of notifications not sent to a peer because the outbound bandwidth budget was exceeded, by traffic class",
+					),
+					&["class"]
+				)?,
+				registry,
+			)?,
+			requests_sent: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_requests_sent_total",
+						"The number of requests sent, by protocol and negotiated protocol version",
+					),
+					&["protocol", "version"]
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -17,7 +17,7 @@
 use std::{collections::HashSet, sync::Arc};
 
 use async_trait::async_trait;
-use futures::{prelude::*, stream::BoxStream};
+use futures::{channel::oneshot, future::BoxFuture, prelude::*, stream::BoxStream};
 
 use parity_scale_codec::Encode;
 
@@ -33,12 +33,15 @@ use sc_network_common::{
 
 use polkadot_node_network_protocol::{
 	peer_set::{PeerSet, PeerSetProtocolNames, ProtocolVersion},
-	request_response::{OutgoingRequest, Recipient, ReqProtocolNames, Requests},
-	PeerId, UnifiedReputationChange as Rep,
+	request_response::{OutgoingRequest, Protocol, Recipient, ReqProtocolNames, Requests},
+	PeerId, ReputationChange,
 };
 use polkadot_primitives::{AuthorityDiscoveryId, Block, Hash};
 
-use crate::validator_discovery::AuthorityDiscovery;
+use crate::{
+	traffic::{NetworkTraffic, TrafficSource},
+	validator_discovery::AuthorityDiscovery,
+};
 
 // network bridge network abstraction log target
 const LOG_TARGET: &'static str = "parachain::network-bridge-net";
@@ -48,29 +51,51 @@ const LOG_TARGET: &'static str = "parachain::network-bridge-net";
 /// This function is only used internally by the network-bridge, which is responsible to only send
 /// messages that are compatible with the passed peer set, as that is currently not enforced by
 /// this function. These are messages of type `WireMessage` parameterized on the matching type.
+///
+/// The message is only sent to as many of the peers as the outbound budget of `traffic` allows.
 pub(crate) fn send_message<M>(
 	net: &mut impl Network,
-	mut peers: Vec<PeerId>,
+	peers: Vec<PeerId>,
 	peer_set: PeerSet,
 	version: ProtocolVersion,
 	protocol_names: &PeerSetProtocolNames,
 	message: M,
 	metrics: &super::Metrics,
+	traffic: &NetworkTraffic,
 ) where
-	M: Encode + Clone,
+	M: Encode + Clone + TrafficSource,
 {
-	let message = {
-		let encoded = message.encode();
-		metrics.on_notification_sent(peer_set, version, encoded.len(), peers.len());
-		encoded
-	};
+	let class = message.traffic_class();
+	let encoded = message.encode();
+
+	let num_peers = peers.len();
+	let mut peers = traffic.admit_notification(peers, peer_set, class, encoded.len());
+	if peers.len() < num_peers {
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_set,
+			?class,
+			size = encoded.len(),
+			dropped = num_peers - peers.len(),
+			"Outbound budget exceeded, dropping notification",
+		);
+
+		metrics.on_notifications_dropped(class, num_peers - peers.len());
+	}
+
+	metrics.on_notification_sent(peer_set, version, encoded.len(), peers.len());
+	metrics.on_protocol_bytes_sent(message.protocol_label(), encoded.len() * peers.len());
+	let message = encoded;
 
 	// optimization: avoid cloning the message for the last peer in the
 	// list. The message payload can be quite large. If the underlying
 	// network used `Bytes` this would not be necessary.
 	let last_peer = peers.pop();
 	// optimization: generate the protocol name once.
-	let protocol_name = protocol_names.get_name(peer_set, version);
+	//
+	// Notifications are always written on the main protocol name, the network takes care of
+	// the negotiated fallback. The version only determines the encoding of the message.
+	let protocol_name = protocol_names.get_main_name(peer_set);
 	peers.into_iter().for_each(|peer| {
 		net.write_notification(peer, protocol_name.clone(), message.clone());
 	});
@@ -101,16 +126,21 @@ pub trait Network: Clone + Send + 'static {
 	async fn remove_from_peers_set(&mut self, protocol: ProtocolName, peers: Vec<PeerId>);
 
 	/// Send a request to a remote peer.
+	///
+	/// If the request has a fallback request, a future is returned which sends the fallback
+	/// request in case the peer does not support the protocol of the request. It resolves to the
+	/// protocol the request ended up being sent on and has to be polled for the response to be
+	/// delivered.
 	async fn start_request<AD: AuthorityDiscovery>(
 		&self,
 		authority_discovery: &mut AD,
 		req: Requests,
 		req_protocol_names: &ReqProtocolNames,
 		if_disconnected: IfDisconnected,
-	);
+	) -> Option<BoxFuture<'static, Protocol>>;
 
 	/// Report a given peer as either beneficial (+) or costly (-) according to the given scalar.
-	fn report_peer(&self, who: PeerId, cost_benefit: Rep);
+	fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange);
 
 	/// Disconnect a given peer from the protocol specified without harming reputation.
 	fn disconnect_peer(&self, who: PeerId, protocol: ProtocolName);
@@ -137,8 +167,8 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 		NetworkService::remove_peers_from_reserved_set(&**self, protocol, peers);
 	}
 
-	fn report_peer(&self, who: PeerId, cost_benefit: Rep) {
-		NetworkService::report_peer(&**self, who, cost_benefit.into_base_rep());
+	fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange) {
+		NetworkService::report_peer(&**self, who, cost_benefit);
 	}
 
 	fn disconnect_peer(&self, who: PeerId, protocol: ProtocolName) {
@@ -155,8 +185,9 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 		req: Requests,
 		req_protocol_names: &ReqProtocolNames,
 		if_disconnected: IfDisconnected,
-	) {
-		let (protocol, OutgoingRequest { peer, payload, pending_response }) = req.encode_request();
+	) -> Option<BoxFuture<'static, Protocol>> {
+		let (protocol, OutgoingRequest { peer, payload, fallback_request, pending_response }) =
+			req.encode_request();
 
 		let peer_id = match peer {
 			Recipient::Peer(peer_id) => Some(peer_id),
@@ -197,7 +228,7 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 						gum::debug!(target: LOG_TARGET, "Sending failed request response failed."),
 					Ok(_) => {},
 				}
-				return
+				return None
 			},
 			Some(peer_id) => peer_id,
 		};
@@ -210,14 +241,63 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 			"Starting request",
 		);
 
+		let (fallback_payload, fallback_protocol) = match fallback_request {
+			None => {
+				NetworkService::start_request(
+					self,
+					peer_id,
+					req_protocol_names.get_name(protocol),
+					payload,
+					pending_response,
+					if_disconnected,
+				);
+				return None
+			},
+			Some(fallback_request) => fallback_request,
+		};
+
+		let (tx, rx) = oneshot::channel();
 		NetworkService::start_request(
 			self,
 			peer_id,
 			req_protocol_names.get_name(protocol),
 			payload,
-			pending_response,
+			tx,
 			if_disconnected,
 		);
+
+		let network = self.clone();
+		let fallback_name = req_protocol_names.get_name(fallback_protocol);
+		let negotiation = async move {
+			match rx.await {
+				Ok(Err(RequestFailure::Network(OutboundFailure::UnsupportedProtocols))) => {
+					gum::trace!(
+						target: LOG_TARGET,
+						%peer_id,
+						protocol = %fallback_name,
+						"Peer does not support the request protocol, starting fallback request",
+					);
+
+					NetworkService::start_request(
+						&network,
+						peer_id,
+						fallback_name,
+						fallback_payload,
+						pending_response,
+						if_disconnected,
+					);
+					fallback_protocol
+				},
+				Ok(response) => {
+					let _ = pending_response.send(response);
+					protocol
+				},
+				// Dropping `pending_response` informs the requester of the cancellation.
+				Err(oneshot::Canceled) => protocol,
+			}
+		};
+
+		Some(negotiation.boxed())
 	}
 }
 
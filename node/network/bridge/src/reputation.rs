@@ -0,0 +1,556 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Aggregation of the peer reputation changes reported by all parachain subsystems.
+//!
+//! Instead of passing every single change on to the network, changes are summed up per peer and
+//! reported in batches every [`REPUTATION_CHANGE_INTERVAL`]. Only malicious behavior is reported
+//! right away. In addition the aggregator keeps a decaying score per peer, which is persisted in
+//! the parachains database, so misbehaving peers are remembered across restarts. The node
+//! operator can ban peers, which get disconnected whenever they connect, and allow peers, whose
+//! costs are never reported.
+
+use std::{
+	collections::{HashMap, HashSet},
+	str::FromStr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use parity_scale_codec::{Decode, Encode};
+use parking_lot::Mutex;
+
+use polkadot_node_network_protocol::{PeerId, ReputationChange, UnifiedReputationChange as Rep};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+
+use crate::network::Network;
+
+const LOG_TARGET: &str = "parachain::network-bridge-reputation";
+
+/// How often the aggregated reputation changes are reported to the network.
+pub const REPUTATION_CHANGE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The time after which a score has decayed to half its value.
+const SCORE_HALF_LIFE: Duration = Duration::from_secs(60 * 60);
+
+/// Scores closer to neutral than this are forgotten.
+const FORGET_THRESHOLD: u32 = 1_000;
+
+/// The maximum number of peers a score is kept for.
+///
+/// Once exceeded, the scores closest to neutral are forgotten first.
+const MAX_ENTRIES: usize = 10_000;
+
+/// The key all scores are stored under.
+const REPUTATIONS_KEY: &[u8] = b"PeerReputations";
+
+/// The reason given to the network for batched reputation changes.
+const AGGREGATED_REASON: &str = "Aggregated reputation change";
+
+/// The reason given to the network for reputations restored from the database.
+const RESTORED_REASON: &str = "Restored reputation";
+
+/// The reason given to the network when disconnecting a banned peer.
+const BANNED_REASON: &str = "Banned by the node operator";
+
+/// Peers the node operator banned or allowed.
+#[derive(Debug, Clone, Default)]
+pub struct PeerFilter {
+	/// Peers which are disconnected whenever they connect.
+	pub banned: HashSet<PeerId>,
+	/// Peers whose costs are never reported, so the network never disconnects them for
+	/// misbehavior on the parachain protocols.
+	pub allowed: HashSet<PeerId>,
+}
+
+impl PeerFilter {
+	/// Build a filter from peer ids given in base58 format.
+	pub fn from_entries(allowed: &[String], banned: &[String]) -> Result<Self, String> {
+		fn collect(entries: &[String]) -> Result<HashSet<PeerId>, String> {
+			entries
+				.iter()
+				.map(|entry| {
+					PeerId::from_str(entry.trim())
+						.map_err(|e| format!("Invalid peer id `{}`: {}", entry, e))
+				})
+				.collect()
+		}
+
+		let filter = Self { allowed: collect(allowed)?, banned: collect(banned)? };
+		if let Some(peer) = filter.allowed.intersection(&filter.banned).next() {
+			return Err(format!("Peer `{}` is both allowed and banned", peer))
+		}
+		Ok(filter)
+	}
+}
+
+/// The score of a peer as kept by the [`ReputationAggregator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerScore {
+	/// The peer.
+	pub peer: PeerId,
+	/// The sum of all reputation changes reported for the peer, decayed over time.
+	pub score: i32,
+	/// Whether the peer is banned by the node operator.
+	pub banned: bool,
+	/// Whether the peer is allowed by the node operator.
+	pub allowed: bool,
+}
+
+/// A score which decays towards zero over time.
+#[derive(Debug, Clone, Copy)]
+struct DecayingScore {
+	value: i32,
+	updated: Instant,
+}
+
+impl DecayingScore {
+	fn value_at(&self, now: Instant) -> i32 {
+		let elapsed = now.saturating_duration_since(self.updated);
+		let halvings = elapsed.as_secs_f64() / SCORE_HALF_LIFE.as_secs_f64();
+		(self.value as f64 * 0.5f64.powf(halvings)) as i32
+	}
+
+	fn add(&mut self, delta: i32, now: Instant) {
+		self.value = self.value_at(now).saturating_add(delta);
+		self.updated = now;
+	}
+}
+
+/// Aggregates the reputation changes of all subsystems.
+///
+/// Cloning the handle gives access to the same scores, so it can be shared between both sides of
+/// the bridge and the RPC.
+#[derive(Clone)]
+pub struct ReputationAggregator(Arc<Mutex<ReputationInner>>);
+
+struct ReputationInner {
+	/// The reputation changes not yet reported to the network.
+	pending: HashMap<PeerId, i32>,
+	/// Scores of all peers which recently had reputation changes.
+	scores: HashMap<PeerId, DecayingScore>,
+	/// Peers with a score restored from the database, which still needs to be reported to the
+	/// network once they connect.
+	restored: HashSet<PeerId>,
+	filter: PeerFilter,
+	/// The database and column scores are persisted in.
+	persistence: Option<(Arc<dyn Database>, u32)>,
+	/// Whether there are score changes which were not yet written to the database.
+	dirty: bool,
+	/// Whether a reputation change is reported right away instead of being batched.
+	send_immediately: fn(&Rep) -> bool,
+}
+
+impl Default for ReputationAggregator {
+	fn default() -> Self {
+		Self::new(PeerFilter::default(), None)
+	}
+}
+
+impl ReputationAggregator {
+	/// Create a new aggregator, restoring the scores persisted in the given database column, if
+	/// any.
+	pub fn new(filter: PeerFilter, persistence: Option<(Arc<dyn Database>, u32)>) -> Self {
+		let now = Instant::now();
+		let scores: HashMap<_, _> = persistence
+			.as_ref()
+			.map(|(db, col)| load_scores(&**db, *col))
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(peer, value)| (peer, DecayingScore { value, updated: now }))
+			.collect();
+		let restored = scores.keys().copied().collect();
+
+		Self(Arc::new(Mutex::new(ReputationInner {
+			pending: HashMap::new(),
+			scores,
+			restored,
+			filter,
+			persistence,
+			dirty: false,
+			send_immediately: |rep| matches!(rep, Rep::Malicious(_)),
+		})))
+	}
+
+	/// Create an aggregator reporting all changes right away.
+	#[cfg(test)]
+	pub(crate) fn unbatched() -> Self {
+		let aggregator = Self::default();
+		aggregator.0.lock().send_immediately = |_| true;
+		aggregator
+	}
+
+	/// The scores of the peers with the lowest scores, lowest first.
+	pub fn lowest_scores(&self, limit: usize) -> Vec<PeerScore> {
+		let now = Instant::now();
+		let inner = self.0.lock();
+		let mut scores: Vec<_> = inner
+			.scores
+			.iter()
+			.map(|(peer, score)| PeerScore {
+				peer: *peer,
+				score: score.value_at(now),
+				banned: inner.filter.banned.contains(peer),
+				allowed: inner.filter.allowed.contains(peer),
+			})
+			.collect();
+		scores.sort_by_key(|score| score.score);
+		scores.truncate(limit);
+		scores
+	}
+
+	/// Note a reputation change of the given peer.
+	///
+	/// The change is reported to the network with the next flush, unless it is malicious.
+	pub(crate) fn modify(&self, network: &impl Network, peer: PeerId, rep: Rep) {
+		let mut inner = self.0.lock();
+		if inner.filter.banned.contains(&peer) ||
+			(!rep.is_benefit() && inner.filter.allowed.contains(&peer))
+		{
+			return
+		}
+
+		let delta = rep.into_base_rep().value;
+		inner
+			.scores
+			.entry(peer)
+			.or_insert(DecayingScore { value: 0, updated: Instant::now() })
+			.add(delta, Instant::now());
+		inner.dirty = true;
+
+		if (inner.send_immediately)(&rep) {
+			network.report_peer(peer, rep.into_base_rep());
+		} else {
+			let pending = inner.pending.entry(peer).or_default();
+			*pending = pending.saturating_add(delta);
+		}
+	}
+
+	/// Handle a newly connected peer.
+	///
+	/// Banned peers are reported with a fatal reputation change, `true` is returned for them and
+	/// they should be disconnected. Restored scores of other peers are reported to the network.
+	pub(crate) fn on_peer_connected(&self, network: &impl Network, peer: PeerId) -> bool {
+		let mut inner = self.0.lock();
+		if inner.filter.banned.contains(&peer) {
+			gum::debug!(target: LOG_TARGET, ?peer, "Banned peer connected");
+			network.report_peer(peer, ReputationChange::new_fatal(BANNED_REASON));
+			return true
+		}
+
+		if inner.restored.remove(&peer) {
+			let score = inner.scores.get(&peer).map_or(0, |score| score.value_at(Instant::now()));
+			if score < 0 && !inner.filter.allowed.contains(&peer) {
+				network.report_peer(peer, ReputationChange::new(score, RESTORED_REASON));
+			}
+		}
+		false
+	}
+
+	/// Report all pending reputation changes to the network and persist the scores.
+	pub(crate) fn flush(&self, network: &impl Network) {
+		self.flush_at(network, Instant::now())
+	}
+
+	fn flush_at(&self, network: &impl Network, now: Instant) {
+		let mut inner = self.0.lock();
+		for (peer, value) in inner.pending.drain() {
+			network.report_peer(peer, ReputationChange::new(value, AGGREGATED_REASON));
+		}
+
+		let len_before = inner.scores.len();
+		inner
+			.scores
+			.retain(|_, score| score.value_at(now).unsigned_abs() >= FORGET_THRESHOLD);
+		if inner.scores.len() > MAX_ENTRIES {
+			let mut by_magnitude: Vec<_> = inner
+				.scores
+				.iter()
+				.map(|(peer, score)| (score.value_at(now).unsigned_abs(), *peer))
+				.collect();
+			by_magnitude.sort_unstable_by_key(|(magnitude, _)| *magnitude);
+			let excess = inner.scores.len() - MAX_ENTRIES;
+			for (_, peer) in by_magnitude.into_iter().take(excess) {
+				inner.scores.remove(&peer);
+			}
+		}
+		if inner.scores.len() != len_before {
+			let ReputationInner { scores, restored, .. } = &mut *inner;
+			restored.retain(|peer| scores.contains_key(peer));
+			inner.dirty = true;
+		}
+
+		if let Err(err) = inner.persist(now) {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to persist peer reputations");
+		}
+	}
+}
+
+impl ReputationInner {
+	/// Write the scores to the database, if they changed since the last write.
+	fn persist(&mut self, now: Instant) -> std::io::Result<()> {
+		let (db, col) = match &self.persistence {
+			Some(persistence) if self.dirty => persistence,
+			_ => return Ok(()),
+		};
+
+		let scores: Vec<(Vec<u8>, i32)> = self
+			.scores
+			.iter()
+			.map(|(peer, score)| (peer.to_bytes(), score.value_at(now)))
+			.collect();
+
+		let mut tx = DBTransaction::new();
+		tx.put_vec(*col, REPUTATIONS_KEY, scores.encode());
+		db.write(tx)?;
+
+		self.dirty = false;
+		Ok(())
+	}
+}
+
+/// Load the persisted scores.
+///
+/// Scores which can not be read are discarded and we start out with neutral scores.
+fn load_scores(db: &dyn Database, col: u32) -> Vec<(PeerId, i32)> {
+	let raw = match db.get(col, REPUTATIONS_KEY) {
+		Ok(Some(raw)) => raw,
+		Ok(None) => return Vec::new(),
+		Err(err) => {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to load persisted peer reputations");
+			return Vec::new()
+		},
+	};
+
+	match Vec::<(Vec<u8>, i32)>::decode(&mut &raw[..]) {
+		Ok(scores) => scores
+			.into_iter()
+			.filter_map(|(peer, score)| Some((PeerId::from_bytes(&peer).ok()?, score)))
+			.collect(),
+		Err(err) => {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to decode persisted peer reputations");
+			Vec::new()
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use async_trait::async_trait;
+	use futures::{future::BoxFuture, stream::BoxStream};
+	use sc_network::{Event as NetworkEvent, Multiaddr, ProtocolName};
+
+	use polkadot_node_network_protocol::{
+		request_response::{outgoing::Requests, Protocol, ReqProtocolNames},
+		IfDisconnected,
+	};
+
+	use crate::validator_discovery::AuthorityDiscovery;
+
+	const COST: Rep = Rep::CostMinor("cost");
+	const BENEFIT: Rep = Rep::BenefitMinor("benefit");
+	const MALICIOUS: Rep = Rep::Malicious("malicious");
+
+	#[derive(Default)]
+	struct TestNetwork {
+		reports: Mutex<Vec<(PeerId, ReputationChange)>>,
+	}
+
+	impl TestNetwork {
+		fn take_reports(&self) -> Vec<(PeerId, ReputationChange)> {
+			std::mem::take(&mut *self.reports.lock())
+		}
+	}
+
+	#[async_trait]
+	impl Network for TestNetwork {
+		fn event_stream(&mut self) -> BoxStream<'static, NetworkEvent> {
+			panic!()
+		}
+
+		async fn set_reserved_peers(
+			&mut self,
+			_protocol: ProtocolName,
+			_: HashSet<Multiaddr>,
+		) -> Result<(), String> {
+			Ok(())
+		}
+
+		async fn remove_from_peers_set(&mut self, _protocol: ProtocolName, _: Vec<PeerId>) {}
+
+		async fn start_request<AD: AuthorityDiscovery>(
+			&self,
+			_: &mut AD,
+			_: Requests,
+			_: &ReqProtocolNames,
+			_: IfDisconnected,
+		) -> Option<BoxFuture<'static, Protocol>> {
+			None
+		}
+
+		fn report_peer(&self, who: PeerId, rep: ReputationChange) {
+			self.reports.lock().push((who, rep));
+		}
+
+		fn disconnect_peer(&self, _: PeerId, _: ProtocolName) {}
+
+		fn write_notification(&self, _: PeerId, _: ProtocolName, _: Vec<u8>) {}
+	}
+
+	fn make_db() -> Arc<dyn Database> {
+		let db = kvdb_memorydb::create(1);
+		Arc::new(polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[]))
+	}
+
+	#[test]
+	fn changes_are_batched_per_peer() {
+		let network = TestNetwork::default();
+		let aggregator = ReputationAggregator::default();
+		let peer_a = PeerId::random();
+		let peer_b = PeerId::random();
+
+		aggregator.modify(&network, peer_a, COST);
+		aggregator.modify(&network, peer_a, COST);
+		aggregator.modify(&network, peer_b, BENEFIT);
+		assert!(network.take_reports().is_empty());
+
+		aggregator.flush(&network);
+		let reports: HashMap<_, _> = network
+			.take_reports()
+			.into_iter()
+			.map(|(peer, rep)| (peer, rep.value))
+			.collect();
+		assert_eq!(
+			reports,
+			HashMap::from([
+				(peer_a, 2 * COST.into_base_rep().value),
+				(peer_b, BENEFIT.into_base_rep().value),
+			])
+		);
+
+		// Nothing left to report.
+		aggregator.flush(&network);
+		assert!(network.take_reports().is_empty());
+	}
+
+	#[test]
+	fn malicious_changes_are_reported_immediately() {
+		let network = TestNetwork::default();
+		let aggregator = ReputationAggregator::default();
+		let peer = PeerId::random();
+
+		aggregator.modify(&network, peer, MALICIOUS);
+		assert_eq!(network.take_reports(), vec![(peer, MALICIOUS.into_base_rep())]);
+
+		aggregator.flush(&network);
+		assert!(network.take_reports().is_empty());
+	}
+
+	#[test]
+	fn scores_decay() {
+		let network = TestNetwork::default();
+		let aggregator = ReputationAggregator::default();
+		let peer = PeerId::random();
+
+		aggregator.modify(&network, peer, Rep::CostMajor("cost"));
+		let score = aggregator.lowest_scores(1)[0].score;
+		assert_eq!(score, Rep::CostMajor("cost").into_base_rep().value);
+
+		let later = Instant::now() + SCORE_HALF_LIFE;
+		let decayed = aggregator.0.lock().scores[&peer].value_at(later);
+		assert!((decayed - score / 2).abs() <= 1);
+
+		// Long forgotten.
+		aggregator.flush_at(&network, Instant::now() + SCORE_HALF_LIFE * 20);
+		assert!(aggregator.lowest_scores(10).is_empty());
+	}
+
+	#[test]
+	fn banned_and_allowed_peers() {
+		let network = TestNetwork::default();
+		let banned = PeerId::random();
+		let allowed = PeerId::random();
+		let filter =
+			PeerFilter { banned: HashSet::from([banned]), allowed: HashSet::from([allowed]) };
+		let aggregator = ReputationAggregator::new(filter, None);
+
+		assert!(aggregator.on_peer_connected(&network, banned));
+		assert_eq!(
+			network.take_reports(),
+			vec![(banned, ReputationChange::new_fatal(BANNED_REASON))]
+		);
+		assert!(!aggregator.on_peer_connected(&network, allowed));
+
+		aggregator.modify(&network, banned, BENEFIT);
+		aggregator.modify(&network, allowed, MALICIOUS);
+		aggregator.modify(&network, allowed, COST);
+		aggregator.flush(&network);
+		assert!(network.take_reports().is_empty());
+
+		aggregator.modify(&network, allowed, BENEFIT);
+		aggregator.flush(&network);
+		assert_eq!(
+			network.take_reports(),
+			vec![(
+				allowed,
+				ReputationChange::new(BENEFIT.into_base_rep().value, AGGREGATED_REASON)
+			)]
+		);
+	}
+
+	#[test]
+	fn scores_are_restored() {
+		let network = TestNetwork::default();
+		let db = make_db();
+		let peer = PeerId::random();
+
+		let aggregator = ReputationAggregator::new(PeerFilter::default(), Some((db.clone(), 0)));
+		aggregator.modify(&network, peer, Rep::CostMajor("cost"));
+		aggregator.flush(&network);
+		network.take_reports();
+
+		let aggregator = ReputationAggregator::new(PeerFilter::default(), Some((db, 0)));
+		let scores = aggregator.lowest_scores(10);
+		assert_eq!(scores.len(), 1);
+		assert_eq!(scores[0].peer, peer);
+
+		// The restored score is reported once the peer connects.
+		assert!(!aggregator.on_peer_connected(&network, peer));
+		let reports = network.take_reports();
+		assert_eq!(reports.len(), 1);
+		assert_eq!(reports[0].0, peer);
+		assert!(reports[0].1.value < 0);
+
+		assert!(!aggregator.on_peer_connected(&network, peer));
+		assert!(network.take_reports().is_empty());
+	}
+
+	#[test]
+	fn peer_filter_from_entries() {
+		let peer_a = PeerId::random();
+		let peer_b = PeerId::random();
+
+		let filter =
+			PeerFilter::from_entries(&[peer_a.to_base58()], &[peer_b.to_base58()]).unwrap();
+		assert_eq!(filter.allowed, HashSet::from([peer_a]));
+		assert_eq!(filter.banned, HashSet::from([peer_b]));
+
+		assert!(PeerFilter::from_entries(&["invalid".into()], &[]).is_err());
+		assert!(PeerFilter::from_entries(&[peer_a.to_base58()], &[peer_a.to_base58()]).is_err());
+	}
+}
@@ -32,7 +32,8 @@ use polkadot_node_network_protocol::{
 		CollationVersion, PeerSet, PeerSetProtocolNames, PerPeerSet, ProtocolVersion,
 		ValidationVersion,
 	},
-	v1 as protocol_v1, ObservedRole, OurView, PeerId, UnifiedReputationChange as Rep, View,
+	v1 as protocol_v1, v2 as protocol_v2, ObservedRole, OurView, PeerId,
+	UnifiedReputationChange as Rep, View,
 };
 
 use polkadot_node_subsystem::{
@@ -66,7 +67,11 @@ use crate::network::{send_message, Network};
 
 use crate::network::get_peer_id_by_authority_id;
 
-use super::metrics::Metrics;
+use super::{
+	metrics::Metrics,
+	reputation::ReputationAggregator,
+	traffic::{NetworkTraffic, TrafficSource},
+};
 
 #[cfg(test)]
 mod tests;
@@ -83,6 +88,8 @@ pub struct NetworkBridgeRx<N, AD> {
 	shared: Shared,
 	metrics: Metrics,
 	peerset_protocol_names: PeerSetProtocolNames,
+	traffic: NetworkTraffic,
+	reputation: ReputationAggregator,
 }
 
 impl<N, AD> NetworkBridgeRx<N, AD> {
@@ -90,12 +97,17 @@ impl<N, AD> NetworkBridgeRx<N, AD> {
 	///
 	/// This assumes that the network service has had the notifications protocol for the network
 	/// bridge already registered. See [`peers_sets_info`](peers_sets_info).
+	///
+	/// Incoming traffic is accounted in `traffic` and reputation changes of peers are aggregated
+	/// in `reputation`, both of which need to be shared with the sending side of the bridge.
 	pub fn new(
 		network_service: N,
 		authority_discovery_service: AD,
 		sync_oracle: Box<dyn SyncOracle + Send>,
 		metrics: Metrics,
 		peerset_protocol_names: PeerSetProtocolNames,
+		traffic: NetworkTraffic,
+		reputation: ReputationAggregator,
 	) -> Self {
 		let shared = Shared::default();
 		Self {
@@ -105,6 +117,8 @@ impl<N, AD> NetworkBridgeRx<N, AD> {
 			shared,
 			metrics,
 			peerset_protocol_names,
+			traffic,
+			reputation,
 		}
 	}
 }
@@ -137,6 +151,8 @@ async fn handle_network_messages<AD>(
 	metrics: Metrics,
 	shared: Shared,
 	peerset_protocol_names: PeerSetProtocolNames,
+	traffic: NetworkTraffic,
+	reputation: ReputationAggregator,
 ) -> Result<(), Error>
 where
 	AD: validator_discovery::AuthorityDiscovery + Send,
@@ -197,6 +213,20 @@ where
 					}
 				};
 
+				if reputation.on_peer_connected(&network_service, peer) {
+					gum::debug!(
+						target: LOG_TARGET,
+						action = "DisconnectBannedPeer",
+						peer_set = ?peer_set,
+						peer = ?peer,
+					);
+
+					// [`NetworkService`] keeps track of the protocols by their main name.
+					let protocol = peerset_protocol_names.get_main_name(peer_set);
+					network_service.disconnect_peer(peer, protocol);
+					continue
+				}
+
 				gum::debug!(
 					target: LOG_TARGET,
 					action = "PeerConnected",
@@ -220,6 +250,8 @@ where
 						},
 					}
 
+					traffic.on_peer_connected(peer, peer_set);
+
 					metrics.on_peer_connected(peer_set, version);
 					metrics.note_peer_count(peer_set, version, peer_map.len());
 
@@ -253,6 +285,7 @@ where
 							&peerset_protocol_names,
 							WireMessage::<protocol_v1::ValidationProtocol>::ViewUpdate(local_view),
 							&metrics,
+							&traffic,
 						);
 					},
 					PeerSet::Collation => {
@@ -278,6 +311,7 @@ where
 							&peerset_protocol_names,
 							WireMessage::<protocol_v1::CollationProtocol>::ViewUpdate(local_view),
 							&metrics,
+							&traffic,
 						);
 					},
 				}
@@ -302,12 +336,18 @@ where
 						PeerSet::Collation => &mut shared.collation_peers,
 					};
 
-					let w = peer_map.remove(&peer).is_some();
+					// The stream is closed on the main protocol name, but the peer may have been
+					// connected on a fallback version.
+					let removed = peer_map.remove(&peer);
+					let version = removed.as_ref().map_or(version, |data| data.version);
+					if removed.is_some() {
+						traffic.on_peer_disconnected(peer, peer_set);
+					}
 
 					metrics.on_peer_disconnected(peer_set, version);
 					metrics.note_peer_count(peer_set, version, peer_map.len());
 
-					w
+					removed.is_some()
 				};
 
 				if was_connected && version == peer_set.get_main_version() {
@@ -365,7 +405,7 @@ where
 				let v_messages = match v_messages {
 					Err(rep) => {
 						gum::debug!(target: LOG_TARGET, action = "ReportPeer");
-						network_service.report_peer(remote, rep);
+						reputation.modify(&network_service, remote, rep);
 
 						continue
 					},
@@ -396,7 +436,7 @@ where
 				let c_messages = match c_messages {
 					Err(rep) => {
 						gum::debug!(target: LOG_TARGET, action = "ReportPeer");
-						network_service.report_peer(remote, rep);
+						reputation.modify(&network_service, remote, rep);
 
 						continue
 					},
@@ -416,66 +456,88 @@ where
 				);
 
 				if !v_messages.is_empty() {
-					let (events, reports) =
-						if expected_versions[PeerSet::Validation] ==
-							Some(ValidationVersion::V1.into())
-						{
-							handle_v1_peer_messages::<protocol_v1::ValidationProtocol, _>(
-								remote,
-								PeerSet::Validation,
-								&mut shared.0.lock().validation_peers,
-								v_messages,
-								&metrics,
-							)
-						} else {
-							gum::warn!(
-								target: LOG_TARGET,
-								version = ?expected_versions[PeerSet::Validation],
-								"Major logic bug. Peer somehow has unsupported validation protocol version."
-							);
+					let (events, reports) = if expected_versions[PeerSet::Validation] ==
+						Some(ValidationVersion::V1.into())
+					{
+						handle_peer_messages::<protocol_v1::ValidationProtocol, _>(
+							remote,
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+							&traffic,
+						)
+					} else if expected_versions[PeerSet::Validation] ==
+						Some(ValidationVersion::V2.into())
+					{
+						handle_peer_messages::<protocol_v2::ValidationProtocol, _>(
+							remote,
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+							&traffic,
+						)
+					} else {
+						gum::warn!(
+							target: LOG_TARGET,
+							version = ?expected_versions[PeerSet::Validation],
+							"Major logic bug. Peer somehow has unsupported validation protocol version."
+						);
 
-							never!("Only version 1 is supported; peer set connection checked above; qed");
+						never!("Only versions 1 and 2 are supported; peer set connection checked above; qed");
 
-							// If a peer somehow triggers this, we'll disconnect them
-							// eventually.
-							(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
-						};
+						// If a peer somehow triggers this, we'll disconnect them
+						// eventually.
+						(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
+					};
 
 					for report in reports {
-						network_service.report_peer(remote, report);
+						reputation.modify(&network_service, remote, report);
 					}
 
 					dispatch_validation_events_to_all(events, &mut sender).await;
 				}
 
 				if !c_messages.is_empty() {
-					let (events, reports) =
-						if expected_versions[PeerSet::Collation] ==
-							Some(CollationVersion::V1.into())
-						{
-							handle_v1_peer_messages::<protocol_v1::CollationProtocol, _>(
-								remote,
-								PeerSet::Collation,
-								&mut shared.0.lock().collation_peers,
-								c_messages,
-								&metrics,
-							)
-						} else {
-							gum::warn!(
-								target: LOG_TARGET,
-								version = ?expected_versions[PeerSet::Collation],
-								"Major logic bug. Peer somehow has unsupported collation protocol version."
-							);
+					let (events, reports) = if expected_versions[PeerSet::Collation] ==
+						Some(CollationVersion::V1.into())
+					{
+						handle_peer_messages::<protocol_v1::CollationProtocol, _>(
+							remote,
+							PeerSet::Collation,
+							&mut shared.0.lock().collation_peers,
+							c_messages,
+							&metrics,
+							&traffic,
+						)
+					} else if expected_versions[PeerSet::Collation] ==
+						Some(CollationVersion::V2.into())
+					{
+						handle_peer_messages::<protocol_v2::CollationProtocol, _>(
+							remote,
+							PeerSet::Collation,
+							&mut shared.0.lock().collation_peers,
+							c_messages,
+							&metrics,
+							&traffic,
+						)
+					} else {
+						gum::warn!(
+							target: LOG_TARGET,
+							version = ?expected_versions[PeerSet::Collation],
+							"Major logic bug. Peer somehow has unsupported collation protocol version."
+						);
 
-							never!("Only version 1 is supported; peer set connection checked above; qed");
+						never!("Only versions 1 and 2 are supported; peer set connection checked above; qed");
 
-							// If a peer somehow triggers this, we'll disconnect them
-							// eventually.
-							(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
-						};
+						// If a peer somehow triggers this, we'll disconnect them
+						// eventually.
+						(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
+					};
 
 					for report in reports {
-						network_service.report_peer(remote, report);
+						reputation.modify(&network_service, remote, report);
 					}
 
 					dispatch_collation_events_to_all(events, &mut sender).await;
@@ -514,6 +576,7 @@ async fn run_incoming_orchestra_signals<Context, N, AD>(
 	sync_oracle: Box<dyn SyncOracle + Send>,
 	metrics: Metrics,
 	peerset_protocol_names: PeerSetProtocolNames,
+	traffic: NetworkTraffic,
 ) -> Result<(), Error>
 where
 	N: Network,
@@ -594,6 +657,7 @@ where
 							finalized_number,
 							&metrics,
 							&peerset_protocol_names,
+							&traffic,
 						);
 					}
 				}
@@ -640,6 +704,8 @@ where
 		sync_oracle,
 		shared,
 		peerset_protocol_names,
+		traffic,
+		reputation,
 	} = bridge;
 
 	let (task, network_event_handler) = handle_network_messages(
@@ -650,6 +716,8 @@ where
 		metrics.clone(),
 		shared.clone(),
 		peerset_protocol_names.clone(),
+		traffic.clone(),
+		reputation,
 	)
 	.remote_handle();
 
@@ -664,6 +732,7 @@ where
 		sync_oracle,
 		metrics,
 		peerset_protocol_names,
+		traffic,
 	);
 
 	futures::pin_mut!(orchestra_signal_handler);
@@ -691,6 +760,7 @@ fn update_our_view<Net, Context>(
 	finalized_number: BlockNumber,
 	metrics: &Metrics,
 	peerset_protocol_names: &PeerSetProtocolNames,
+	traffic: &NetworkTraffic,
 ) where
 	Net: Network,
 {
@@ -716,25 +786,61 @@ fn update_our_view<Net, Context>(
 		}
 
 		(
-			shared.validation_peers.keys().cloned().collect::<Vec<_>>(),
-			shared.collation_peers.keys().cloned().collect::<Vec<_>>(),
+			shared
+				.validation_peers
+				.iter()
+				.map(|(peer, data)| (*peer, data.version))
+				.collect::<Vec<_>>(),
+			shared
+				.collation_peers
+				.iter()
+				.map(|(peer, data)| (*peer, data.version))
+				.collect::<Vec<_>>(),
 		)
 	};
 
+	let (validation_peers_v1, validation_peers_v2): (Vec<_>, Vec<_>) = validation_peers
+		.into_iter()
+		.partition(|(_, version)| *version != ValidationVersion::V2.into());
+
+	let (collation_peers_v1, collation_peers_v2): (Vec<_>, Vec<_>) = collation_peers
+		.into_iter()
+		.partition(|(_, version)| *version != CollationVersion::V2.into());
+
 	send_validation_message_v1(
 		net,
-		validation_peers,
+		validation_peers_v1.into_iter().map(|(peer, _)| peer).collect(),
+		peerset_protocol_names,
+		WireMessage::ViewUpdate(new_view.clone()),
+		metrics,
+		traffic,
+	);
+
+	send_validation_message_v2(
+		net,
+		validation_peers_v2.into_iter().map(|(peer, _)| peer).collect(),
 		peerset_protocol_names,
 		WireMessage::ViewUpdate(new_view.clone()),
 		metrics,
+		traffic,
 	);
 
 	send_collation_message_v1(
 		net,
-		collation_peers,
+		collation_peers_v1.into_iter().map(|(peer, _)| peer).collect(),
+		peerset_protocol_names,
+		WireMessage::ViewUpdate(new_view.clone()),
+		metrics,
+		traffic,
+	);
+
+	send_collation_message_v2(
+		net,
+		collation_peers_v2.into_iter().map(|(peer, _)| peer).collect(),
 		peerset_protocol_names,
 		WireMessage::ViewUpdate(new_view),
 		metrics,
+		traffic,
 	);
 
 	let our_view = OurView::new(
@@ -753,15 +859,20 @@ fn update_our_view<Net, Context>(
 	);
 }
 
-// Handle messages on a specific v1 peer-set. The peer is expected to be connected on that
-// peer-set.
-fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
+// Handle messages on a specific peer-set, decoded as `RawMessage` of the version the peer is
+// connected with. The peer is expected to be connected on that peer-set.
+fn handle_peer_messages<RawMessage, OutMessage>(
 	peer: PeerId,
 	peer_set: PeerSet,
 	peers: &mut HashMap<PeerId, PeerData>,
 	messages: Vec<Bytes>,
 	metrics: &Metrics,
-) -> (Vec<NetworkBridgeEvent<OutMessage>>, Vec<Rep>) {
+	traffic: &NetworkTraffic,
+) -> (Vec<NetworkBridgeEvent<OutMessage>>, Vec<Rep>)
+where
+	RawMessage: Decode + TrafficSource,
+	OutMessage: From<RawMessage>,
+{
 	let peer_data = match peers.get_mut(&peer) {
 		None => return (Vec::new(), vec![UNCONNECTED_PEERSET_COST]),
 		Some(d) => d,
@@ -771,7 +882,9 @@ fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 	let mut reports = Vec::new();
 
 	for message in messages {
-		metrics.on_notification_received(peer_set, peer_data.version, message.len());
+		let size = message.len();
+		metrics.on_notification_received(peer_set, peer_data.version, size);
+		traffic.on_notification_received(peer, peer_set, size);
 		let message = match WireMessage::<RawMessage>::decode_all(&mut message.as_ref()) {
 			Err(_) => {
 				reports.push(MALFORMED_MESSAGE_COST);
@@ -779,6 +892,7 @@ fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 			},
 			Ok(m) => m,
 		};
+		metrics.on_protocol_bytes_received(message.protocol_label(), size);
 
 		outgoing_events.push(match message {
 			WireMessage::ViewUpdate(new_view) => {
@@ -812,6 +926,7 @@ fn send_validation_message_v1(
 	peerset_protocol_names: &PeerSetProtocolNames,
 	message: WireMessage<protocol_v1::ValidationProtocol>,
 	metrics: &Metrics,
+	traffic: &NetworkTraffic,
 ) {
 	send_message(
 		net,
@@ -821,6 +936,27 @@ fn send_validation_message_v1(
 		peerset_protocol_names,
 		message,
 		metrics,
+		traffic,
+	);
+}
+
+fn send_validation_message_v2(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	peerset_protocol_names: &PeerSetProtocolNames,
+	message: WireMessage<protocol_v2::ValidationProtocol>,
+	metrics: &Metrics,
+	traffic: &NetworkTraffic,
+) {
+	send_message(
+		net,
+		peers,
+		PeerSet::Validation,
+		ValidationVersion::V2.into(),
+		peerset_protocol_names,
+		message,
+		metrics,
+		traffic,
 	);
 }
 
@@ -830,6 +966,7 @@ fn send_collation_message_v1(
 	peerset_protocol_names: &PeerSetProtocolNames,
 	message: WireMessage<protocol_v1::CollationProtocol>,
 	metrics: &Metrics,
+	traffic: &NetworkTraffic,
 ) {
 	send_message(
 		net,
@@ -839,6 +976,27 @@ fn send_collation_message_v1(
 		peerset_protocol_names,
 		message,
 		metrics,
+		traffic,
+	);
+}
+
+fn send_collation_message_v2(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	peerset_protocol_names: &PeerSetProtocolNames,
+	message: WireMessage<protocol_v2::CollationProtocol>,
+	metrics: &Metrics,
+	traffic: &NetworkTraffic,
+) {
+	send_message(
+		net,
+		peers,
+		PeerSet::Collation,
+		CollationVersion::V2.into(),
+		peerset_protocol_names,
+		message,
+		metrics,
+		traffic,
 	);
 }
 
@@ -15,7 +15,7 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
-use futures::{channel::oneshot, executor, stream::BoxStream};
+use futures::{channel::oneshot, executor, future::BoxFuture, stream::BoxStream};
 use polkadot_node_network_protocol::{self as net_protocol, OurView};
 use polkadot_node_subsystem::{messages::NetworkBridgeEvent, ActivatedLeaf};
 
@@ -31,8 +31,8 @@ use sc_network::{Event as NetworkEvent, IfDisconnected, ProtocolName};
 
 use polkadot_node_network_protocol::{
 	peer_set::PeerSetProtocolNames,
-	request_response::{outgoing::Requests, ReqProtocolNames},
-	view, ObservedRole, Versioned,
+	request_response::{outgoing::Requests, Protocol, ReqProtocolNames},
+	view, ObservedRole, ReputationChange, Versioned,
 };
 use polkadot_node_subsystem::{
 	jaeger,
@@ -46,17 +46,17 @@ use polkadot_node_subsystem_test_helpers::{
 	SingleItemSink, SingleItemStream, TestSubsystemContextHandle,
 };
 use polkadot_node_subsystem_util::metered;
-use polkadot_primitives::{AuthorityDiscoveryId, Hash};
+use polkadot_primitives::{AuthorityDiscoveryId, CandidateHash, Hash};
 
 use sc_network::Multiaddr;
 use sp_keyring::Sr25519Keyring;
 
-use crate::{network::Network, validator_discovery::AuthorityDiscovery, Rep};
+use crate::{network::Network, validator_discovery::AuthorityDiscovery, PeerFilter};
 
 #[derive(Debug, PartialEq)]
 pub enum NetworkAction {
 	/// Note a change in reputation for a peer.
-	ReputationChange(PeerId, Rep),
+	ReputationChange(PeerId, ReputationChange),
 	/// Disconnect a peer from the given peer-set.
 	DisconnectPeer(PeerId, PeerSet),
 	/// Write a notification to a given peer on the given peer-set.
@@ -125,10 +125,11 @@ impl Network for TestNetwork {
 		_: Requests,
 		_: &ReqProtocolNames,
 		_: IfDisconnected,
-	) {
+	) -> Option<BoxFuture<'static, Protocol>> {
+		None
 	}
 
-	fn report_peer(&self, who: PeerId, cost_benefit: Rep) {
+	fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange) {
 		self.action_tx
 			.lock()
 			.unbounded_send(NetworkAction::ReputationChange(who, cost_benefit))
@@ -199,6 +200,28 @@ impl TestNetworkHandle {
 		.await;
 	}
 
+	async fn connect_peer_on_version(
+		&mut self,
+		peer: PeerId,
+		peer_set: PeerSet,
+		version: ProtocolVersion,
+		role: ObservedRole,
+	) {
+		let negotiated_fallback = if version == peer_set.get_main_version() {
+			None
+		} else {
+			Some(self.protocol_names.get_name(peer_set, version))
+		};
+
+		self.send_network_event(NetworkEvent::NotificationStreamOpened {
+			remote: peer,
+			protocol: self.protocol_names.get_main_name(peer_set),
+			negotiated_fallback,
+			role: role.into(),
+		})
+		.await;
+	}
+
 	async fn disconnect_peer(&mut self, peer: PeerId, peer_set: PeerSet) {
 		self.send_network_event(NetworkEvent::NotificationStreamClosed {
 			remote: peer,
@@ -295,6 +318,14 @@ struct TestHarness {
 fn test_harness<T: Future<Output = VirtualOverseer>>(
 	sync_oracle: Box<dyn SyncOracle + Send>,
 	test: impl FnOnce(TestHarness) -> T,
+) {
+	test_harness_with_reputation(sync_oracle, ReputationAggregator::unbatched(), test)
+}
+
+fn test_harness_with_reputation<T: Future<Output = VirtualOverseer>>(
+	sync_oracle: Box<dyn SyncOracle + Send>,
+	reputation: ReputationAggregator,
+	test: impl FnOnce(TestHarness) -> T,
 ) {
 	let genesis_hash = Hash::repeat_byte(0xff);
 	let fork_id = None;
@@ -313,6 +344,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 		sync_oracle,
 		shared: Shared::default(),
 		peerset_protocol_names,
+		traffic: NetworkTraffic::default(),
+		reputation,
 	};
 
 	let network_bridge = run_network_in(bridge, context, network_stream)
@@ -675,7 +708,7 @@ fn peer_view_updates_sent_via_overseer() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -706,6 +739,56 @@ fn peer_view_updates_sent_via_overseer() {
 	});
 }
 
+#[test]
+fn banned_peers_are_disconnected() {
+	let banned = PeerId::random();
+	let filter = PeerFilter { banned: [banned].into_iter().collect(), ..Default::default() };
+	let reputation = ReputationAggregator::new(filter, None);
+
+	test_harness_with_reputation(done_syncing_oracle(), reputation, |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer = PeerId::random();
+
+		network_handle
+			.connect_peer(banned.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		let actions = network_handle.next_network_actions(2).await;
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::DisconnectPeer(banned.clone(), PeerSet::Validation),
+		);
+		assert!(actions.iter().any(|action| matches!(
+			action,
+			NetworkAction::ReputationChange(p, rep) if *p == banned && rep.value == i32::MIN
+		)));
+
+		// The banned peer is not announced to the subsystems, other peers are.
+		network_handle
+			.connect_peer(peer.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerConnected(
+				peer.clone(),
+				ObservedRole::Full,
+				ValidationVersion::V2.into(),
+				None,
+			),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+			&mut virtual_overseer,
+		)
+		.await;
+		virtual_overseer
+	});
+}
+
 #[test]
 fn peer_messages_sent_via_overseer() {
 	test_harness(done_syncing_oracle(), |test_harness| async move {
@@ -723,7 +806,7 @@ fn peer_messages_sent_via_overseer() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -761,7 +844,7 @@ fn peer_messages_sent_via_overseer() {
 			virtual_overseer.recv().await,
 			AllMessages::ApprovalDistribution(
 				ApprovalDistributionMessage::NetworkBridgeUpdate(
-					NetworkBridgeEvent::PeerMessage(p, Versioned::V1(m))
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
 				)
 			) => {
 				assert_eq!(p, peer);
@@ -798,7 +881,7 @@ fn peer_disconnect_from_just_one_peerset() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -817,7 +900,7 @@ fn peer_disconnect_from_just_one_peerset() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					CollationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -882,7 +965,12 @@ fn relays_collation_protocol_messages() {
 			.connect_peer(peer_a.clone(), PeerSet::Validation, ObservedRole::Full)
 			.await;
 		network_handle
-			.connect_peer(peer_b.clone(), PeerSet::Collation, ObservedRole::Full)
+			.connect_peer_on_version(
+				peer_b.clone(),
+				PeerSet::Collation,
+				CollationVersion::V1.into(),
+				ObservedRole::Full,
+			)
 			.await;
 
 		// bridge will inform about all connected peers.
@@ -891,7 +979,7 @@ fn relays_collation_protocol_messages() {
 				NetworkBridgeEvent::PeerConnected(
 					peer_a.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -910,7 +998,7 @@ fn relays_collation_protocol_messages() {
 				NetworkBridgeEvent::PeerConnected(
 					peer_b.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					CollationVersion::V1.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -946,7 +1034,10 @@ fn relays_collation_protocol_messages() {
 		let actions = network_handle.next_network_actions(3).await;
 		assert_network_actions_contains(
 			&actions,
-			&NetworkAction::ReputationChange(peer_a.clone(), UNCONNECTED_PEERSET_COST),
+			&NetworkAction::ReputationChange(
+				peer_a.clone(),
+				UNCONNECTED_PEERSET_COST.into_base_rep(),
+			),
 		);
 
 		// peer B has the message relayed.
@@ -974,6 +1065,171 @@ fn relays_collation_protocol_messages() {
 	});
 }
 
+#[test]
+fn relays_collation_protocol_messages_v2() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer = PeerId::random();
+
+		network_handle
+			.connect_peer(peer.clone(), PeerSet::Collation, ObservedRole::Full)
+			.await;
+
+		assert_sends_collation_event_to_all(
+			NetworkBridgeEvent::PeerConnected(
+				peer.clone(),
+				ObservedRole::Full,
+				CollationVersion::V2.into(),
+				None,
+			),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_collation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		let collator_protocol_message = protocol_v2::CollatorProtocolMessage::AdvertiseCollation {
+			relay_parent: Hash::repeat_byte(1),
+			candidate_hash: CandidateHash(Hash::repeat_byte(2)),
+			parent_head_data_hash: Hash::repeat_byte(3),
+		};
+
+		let message_v2 =
+			protocol_v2::CollationProtocol::CollatorProtocol(collator_protocol_message.clone());
+
+		network_handle
+			.peer_message(
+				peer.clone(),
+				PeerSet::Collation,
+				WireMessage::ProtocolMessage(message_v2).encode(),
+			)
+			.await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::CollatorProtocol(
+				CollatorProtocolMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
+				)
+			) => {
+				assert_eq!(p, peer);
+				assert_eq!(m, collator_protocol_message);
+			}
+		);
+		virtual_overseer
+	});
+}
+
+#[test]
+fn relays_validation_protocol_messages_per_version() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		let peer_v1 = PeerId::random();
+		let peer_v2 = PeerId::random();
+
+		network_handle
+			.connect_peer_on_version(
+				peer_v1.clone(),
+				PeerSet::Validation,
+				ValidationVersion::V1.into(),
+				ObservedRole::Full,
+			)
+			.await;
+		network_handle
+			.connect_peer(peer_v2.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		for (peer, version) in [(peer_v1, ValidationVersion::V1), (peer_v2, ValidationVersion::V2)]
+		{
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					version.into(),
+					None,
+				),
+				&mut virtual_overseer,
+			)
+			.await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+				&mut virtual_overseer,
+			)
+			.await;
+		}
+
+		let approval_distribution_message =
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new());
+
+		network_handle
+			.peer_message(
+				peer_v1.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(
+					protocol_v1::ValidationProtocol::ApprovalDistribution(
+						approval_distribution_message.clone(),
+					),
+				)
+				.encode(),
+			)
+			.await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::ApprovalDistribution(
+				ApprovalDistributionMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V1(m))
+				)
+			) => {
+				assert_eq!(p, peer_v1);
+				assert_eq!(m, approval_distribution_message);
+			}
+		);
+
+		let statement_distribution_message =
+			protocol_v2::StatementDistributionMessage::Announcement(
+				protocol_v2::StatementsAnnouncement {
+					relay_parent: Hash::repeat_byte(1),
+					candidate_hash: CandidateHash(Hash::repeat_byte(2)),
+					known: protocol_v2::StatementFilter::new(4),
+				},
+			);
+
+		network_handle
+			.peer_message(
+				peer_v2.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(
+					protocol_v2::ValidationProtocol::StatementDistribution(
+						statement_distribution_message.clone(),
+					),
+				)
+				.encode(),
+			)
+			.await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::StatementDistribution(
+				StatementDistributionMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
+				)
+			) => {
+				assert_eq!(p, peer_v2);
+				assert_eq!(m, statement_distribution_message);
+			}
+		);
+		virtual_overseer
+	});
+}
+
 #[test]
 fn different_views_on_different_peer_sets() {
 	test_harness(done_syncing_oracle(), |test_harness| async move {
@@ -994,7 +1250,7 @@ fn different_views_on_different_peer_sets() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -1013,7 +1269,7 @@ fn different_views_on_different_peer_sets() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					CollationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -1141,7 +1397,7 @@ fn view_finalized_number_can_not_go_down() {
 		let actions = network_handle.next_network_actions(2).await;
 		assert_network_actions_contains(
 			&actions,
-			&NetworkAction::ReputationChange(peer_a.clone(), MALFORMED_VIEW_COST),
+			&NetworkAction::ReputationChange(peer_a.clone(), MALFORMED_VIEW_COST.into_base_rep()),
 		);
 		virtual_overseer
 	});
@@ -0,0 +1,467 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Accounting of the traffic on the parachain peer-sets and prioritization of outbound
+//! notifications.
+//!
+//! All notifications sent and received by the bridge are accounted per peer. If an outbound
+//! bandwidth budget is configured, outbound notifications are admitted by a token bucket, which
+//! keeps a reserve of the budget for the more important traffic classes. This way collation
+//! traffic can't starve validator gossip.
+
+use std::{
+	cmp::{min, Reverse},
+	collections::HashMap,
+	sync::Arc,
+	time::Instant,
+};
+
+use parking_lot::Mutex;
+
+use polkadot_node_network_protocol::{
+	peer_set::PeerSet, request_response::Protocol, v1 as protocol_v1, v2 as protocol_v2, PeerId,
+};
+
+use crate::WireMessage;
+
+/// Classes of outbound traffic, in order of decreasing priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrafficClass {
+	/// Dispute statements. Never dropped.
+	Dispute,
+	/// View updates and requests other than disputes. Small and never dropped.
+	Control,
+	/// Approval assignments and votes.
+	Approval,
+	/// Backing statements and availability bitfields.
+	Statement,
+	/// Collator protocol messages.
+	Collation,
+}
+
+impl TrafficClass {
+	/// The label of the class, used in metrics.
+	pub fn label(&self) -> &'static str {
+		match self {
+			TrafficClass::Dispute => "dispute",
+			TrafficClass::Control => "control",
+			TrafficClass::Approval => "approval",
+			TrafficClass::Statement => "statement",
+			TrafficClass::Collation => "collation",
+		}
+	}
+
+	/// The percentage of the budget which needs to be left after sending traffic of this class.
+	///
+	/// `None` for classes which are never dropped.
+	fn reserve_percent(&self) -> Option<i64> {
+		match self {
+			TrafficClass::Dispute | TrafficClass::Control => None,
+			TrafficClass::Approval => Some(0),
+			TrafficClass::Statement => Some(25),
+			TrafficClass::Collation => Some(50),
+		}
+	}
+
+	/// The class of a request sent on the given protocol.
+	fn of_request(protocol: Protocol) -> Self {
+		match protocol {
+			Protocol::DisputeSendingV1 => TrafficClass::Dispute,
+			Protocol::ChunkFetchingV1 |
+			Protocol::CollationFetchingV1 |
+			Protocol::CollationFetchingV2 |
+			Protocol::PoVFetchingV1 |
+			Protocol::AvailableDataFetchingV1 |
+			Protocol::StatementFetchingV1 |
+			Protocol::CandidateStatementsFetchingV1 => TrafficClass::Control,
+		}
+	}
+}
+
+/// Notifications whose traffic can be attributed to a protocol and a traffic class.
+pub(crate) trait TrafficSource {
+	/// The label of the protocol the notification belongs to, used in metrics.
+	fn protocol_label(&self) -> &'static str;
+
+	/// The traffic class of the notification.
+	fn traffic_class(&self) -> TrafficClass;
+}
+
+impl<M: TrafficSource> TrafficSource for WireMessage<M> {
+	fn protocol_label(&self) -> &'static str {
+		match self {
+			WireMessage::ProtocolMessage(message) => message.protocol_label(),
+			WireMessage::ViewUpdate(_) => "view",
+		}
+	}
+
+	fn traffic_class(&self) -> TrafficClass {
+		match self {
+			WireMessage::ProtocolMessage(message) => message.traffic_class(),
+			WireMessage::ViewUpdate(_) => TrafficClass::Control,
+		}
+	}
+}
+
+macro_rules! impl_validation_traffic_source {
+	($validation_protocol:ty) => {
+		impl TrafficSource for $validation_protocol {
+			fn protocol_label(&self) -> &'static str {
+				match self {
+					Self::BitfieldDistribution(_) => "bitfield_distribution",
+					Self::StatementDistribution(_) => "statement_distribution",
+					Self::ApprovalDistribution(_) => "approval_distribution",
+				}
+			}
+
+			fn traffic_class(&self) -> TrafficClass {
+				match self {
+					Self::BitfieldDistribution(_) | Self::StatementDistribution(_) =>
+						TrafficClass::Statement,
+					Self::ApprovalDistribution(_) => TrafficClass::Approval,
+				}
+			}
+		}
+	};
+}
+
+macro_rules! impl_collation_traffic_source {
+	($collation_protocol:ty) => {
+		impl TrafficSource for $collation_protocol {
+			fn protocol_label(&self) -> &'static str {
+				match self {
+					Self::CollatorProtocol(_) => "collator_protocol",
+				}
+			}
+
+			fn traffic_class(&self) -> TrafficClass {
+				TrafficClass::Collation
+			}
+		}
+	};
+}
+
+impl_validation_traffic_source!(protocol_v1::ValidationProtocol);
+impl_validation_traffic_source!(protocol_v2::ValidationProtocol);
+impl_collation_traffic_source!(protocol_v1::CollationProtocol);
+impl_collation_traffic_source!(protocol_v2::CollationProtocol);
+
+/// The notification traffic exchanged with a peer on a peer-set since it connected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopTalker {
+	/// The peer.
+	pub peer: PeerId,
+	/// The peer-set the traffic was exchanged on.
+	pub peer_set: PeerSet,
+	/// Bytes of notifications sent to the peer.
+	pub bytes_sent: u64,
+	/// Bytes of notifications received from the peer.
+	pub bytes_received: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PeerTraffic {
+	bytes_sent: u64,
+	bytes_received: u64,
+}
+
+/// Token bucket limiting the outbound notification traffic.
+///
+/// The bucket holds up to one second worth of budget. Traffic of classes which are never dropped
+/// may put the bucket into debt, which has to be paid off before other traffic is admitted again.
+struct OutboundBudget {
+	bytes_per_second: i64,
+	tokens: i64,
+	last_refill: Instant,
+}
+
+impl OutboundBudget {
+	fn new(bytes_per_second: u64, now: Instant) -> Self {
+		let bytes_per_second = bytes_per_second.min(i64::MAX as u64) as i64;
+		OutboundBudget { bytes_per_second, tokens: bytes_per_second, last_refill: now }
+	}
+
+	fn refill(&mut self, now: Instant) {
+		let elapsed = now.saturating_duration_since(self.last_refill);
+		let refill = elapsed.as_micros().saturating_mul(self.bytes_per_second as u128) / 1_000_000;
+
+		// Only move the refill time forward if we actually added tokens, so no budget is lost
+		// when sending often.
+		if refill > 0 {
+			let refill = min(refill, i64::MAX as u128) as i64;
+			self.tokens = min(self.tokens.saturating_add(refill), self.bytes_per_second);
+			self.last_refill = now;
+		}
+	}
+
+	/// Try to spend `bytes` of the budget on traffic of the given class.
+	fn try_spend(&mut self, class: TrafficClass, bytes: usize, now: Instant) -> bool {
+		self.refill(now);
+
+		let bytes = min(bytes, i64::MAX as usize) as i64;
+		let reserve = match class.reserve_percent() {
+			None => {
+				self.tokens = self.tokens.saturating_sub(bytes);
+				return true
+			},
+			Some(percent) => self.bytes_per_second / 100 * percent,
+		};
+
+		// Notifications larger than the bucket are admitted once the bucket is full enough,
+		// otherwise they could never be sent.
+		let needed = reserve + min(bytes, self.bytes_per_second - reserve);
+		if self.tokens >= needed {
+			self.tokens -= bytes;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+#[derive(Default)]
+struct TrafficInner {
+	peers: HashMap<(PeerId, PeerSet), PeerTraffic>,
+	budget: Option<OutboundBudget>,
+}
+
+/// Accounting of the notification traffic of the network bridge and its outbound budget.
+///
+/// Shared between the sending and the receiving side of the bridge, as well as the RPC exposing
+/// the top talkers. By default the outbound traffic is not limited.
+#[derive(Clone, Default)]
+pub struct NetworkTraffic(Arc<Mutex<TrafficInner>>);
+
+impl NetworkTraffic {
+	/// Create traffic accounting, limiting outbound notifications to `bytes_per_second`.
+	pub fn with_outbound_limit(bytes_per_second: u64) -> Self {
+		let budget = OutboundBudget::new(bytes_per_second, Instant::now());
+		NetworkTraffic(Arc::new(Mutex::new(TrafficInner {
+			peers: HashMap::new(),
+			budget: Some(budget),
+		})))
+	}
+
+	/// The connected peers with the most notification traffic since they connected, sorted by
+	/// the total number of bytes exchanged.
+	pub fn top_talkers(&self, limit: usize) -> Vec<TopTalker> {
+		let mut talkers = self
+			.0
+			.lock()
+			.peers
+			.iter()
+			.map(|((peer, peer_set), traffic)| TopTalker {
+				peer: *peer,
+				peer_set: *peer_set,
+				bytes_sent: traffic.bytes_sent,
+				bytes_received: traffic.bytes_received,
+			})
+			.collect::<Vec<_>>();
+
+		talkers.sort_by_key(|t| Reverse(t.bytes_sent.saturating_add(t.bytes_received)));
+		talkers.truncate(limit);
+		talkers
+	}
+
+	pub(crate) fn on_peer_connected(&self, peer: PeerId, peer_set: PeerSet) {
+		self.0.lock().peers.insert((peer, peer_set), PeerTraffic::default());
+	}
+
+	pub(crate) fn on_peer_disconnected(&self, peer: PeerId, peer_set: PeerSet) {
+		self.0.lock().peers.remove(&(peer, peer_set));
+	}
+
+	/// Account a notification received from a connected peer.
+	pub(crate) fn on_notification_received(&self, peer: PeerId, peer_set: PeerSet, bytes: usize) {
+		if let Some(traffic) = self.0.lock().peers.get_mut(&(peer, peer_set)) {
+			traffic.bytes_received = traffic.bytes_received.saturating_add(bytes as u64);
+		}
+	}
+
+	/// Charge a request sent on the given protocol against the outbound budget.
+	///
+	/// Requests are never dropped, as the requester is waiting for the response, but they
+	/// reduce the budget left for notifications.
+	pub(crate) fn on_request_sent(&self, protocol: Protocol, bytes: usize) {
+		if let Some(budget) = self.0.lock().budget.as_mut() {
+			let admitted =
+				budget.try_spend(TrafficClass::of_request(protocol), bytes, Instant::now());
+			debug_assert!(admitted, "Requests are never dropped; qed");
+		}
+	}
+
+	/// Admit a notification of `bytes` size and the given class to as many of `peers` as the
+	/// outbound budget allows, accounting the traffic to the admitted peers.
+	///
+	/// Returns the admitted peers, in the order they were passed in.
+	pub(crate) fn admit_notification(
+		&self,
+		peers: Vec<PeerId>,
+		peer_set: PeerSet,
+		class: TrafficClass,
+		bytes: usize,
+	) -> Vec<PeerId> {
+		self.admit_notification_at(peers, peer_set, class, bytes, Instant::now())
+	}
+
+	fn admit_notification_at(
+		&self,
+		mut peers: Vec<PeerId>,
+		peer_set: PeerSet,
+		class: TrafficClass,
+		bytes: usize,
+		now: Instant,
+	) -> Vec<PeerId> {
+		let mut inner = self.0.lock();
+		let TrafficInner { peers: peer_traffic, budget } = &mut *inner;
+
+		if let Some(budget) = budget.as_mut() {
+			let mut admitted = 0;
+			while admitted < peers.len() && budget.try_spend(class, bytes, now) {
+				admitted += 1;
+			}
+			peers.truncate(admitted);
+		}
+
+		for peer in &peers {
+			if let Some(traffic) = peer_traffic.get_mut(&(*peer, peer_set)) {
+				traffic.bytes_sent = traffic.bytes_sent.saturating_add(bytes as u64);
+			}
+		}
+
+		peers
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[test]
+	fn lower_classes_keep_reserve_for_higher_classes() {
+		let now = Instant::now();
+		let mut budget = OutboundBudget::new(1000, now);
+
+		// Collations may only use the upper half of the budget.
+		assert!(budget.try_spend(TrafficClass::Collation, 400, now));
+		assert!(!budget.try_spend(TrafficClass::Collation, 200, now));
+
+		// Statements may use up to three quarters.
+		assert!(budget.try_spend(TrafficClass::Statement, 300, now));
+		assert!(!budget.try_spend(TrafficClass::Statement, 100, now));
+
+		// Approvals may use everything that is left.
+		assert!(budget.try_spend(TrafficClass::Approval, 300, now));
+		assert!(!budget.try_spend(TrafficClass::Approval, 1, now));
+
+		// Disputes are never dropped and put the budget into debt.
+		assert!(budget.try_spend(TrafficClass::Dispute, 500, now));
+		assert_eq!(budget.tokens, -500);
+
+		// The debt needs to be paid off before approvals are admitted again.
+		let later = now + Duration::from_millis(500);
+		assert!(!budget.try_spend(TrafficClass::Approval, 1, later));
+		let later = later + Duration::from_millis(100);
+		assert!(budget.try_spend(TrafficClass::Approval, 100, later));
+	}
+
+	#[test]
+	fn budget_refills_up_to_one_second() {
+		let now = Instant::now();
+		let mut budget = OutboundBudget::new(1000, now);
+
+		assert!(budget.try_spend(TrafficClass::Approval, 1000, now));
+		assert!(!budget.try_spend(TrafficClass::Approval, 1, now));
+
+		budget.refill(now + Duration::from_secs(10));
+		assert_eq!(budget.tokens, 1000);
+	}
+
+	#[test]
+	fn large_notifications_are_admitted_on_a_full_bucket() {
+		let now = Instant::now();
+		let mut budget = OutboundBudget::new(1000, now);
+
+		assert!(budget.try_spend(TrafficClass::Collation, 5000, now));
+		assert!(!budget.try_spend(TrafficClass::Approval, 1, now + Duration::from_secs(3)));
+		assert!(budget.try_spend(TrafficClass::Approval, 1, now + Duration::from_secs(6)));
+	}
+
+	#[test]
+	fn notifications_are_admitted_to_as_many_peers_as_budget_allows() {
+		let now = Instant::now();
+		let traffic = NetworkTraffic::with_outbound_limit(1000);
+		let peers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+		for peer in &peers {
+			traffic.on_peer_connected(*peer, PeerSet::Validation);
+		}
+
+		let admitted = traffic.admit_notification_at(
+			peers.clone(),
+			PeerSet::Validation,
+			TrafficClass::Statement,
+			300,
+			now,
+		);
+		assert_eq!(admitted, peers[..2].to_vec());
+
+		let talkers = traffic.top_talkers(10);
+		assert_eq!(talkers.len(), 4);
+		assert!(talkers[..2].iter().all(|t| t.bytes_sent == 300 && peers[..2].contains(&t.peer)));
+		assert!(talkers[2..].iter().all(|t| t.bytes_sent == 0));
+	}
+
+	#[test]
+	fn top_talkers_are_sorted_by_total_traffic() {
+		let traffic = NetworkTraffic::default();
+		let a = PeerId::random();
+		let b = PeerId::random();
+		let c = PeerId::random();
+
+		traffic.on_peer_connected(a, PeerSet::Validation);
+		traffic.on_peer_connected(b, PeerSet::Collation);
+		traffic.on_peer_connected(c, PeerSet::Validation);
+
+		traffic.on_notification_received(a, PeerSet::Validation, 100);
+		traffic.on_notification_received(b, PeerSet::Collation, 500);
+		traffic.admit_notification(vec![c], PeerSet::Validation, TrafficClass::Approval, 300);
+		// Traffic of disconnected peers or on other peer-sets is not accounted.
+		traffic.on_notification_received(a, PeerSet::Collation, 1000);
+
+		let talkers = traffic.top_talkers(2);
+		assert_eq!(
+			talkers,
+			vec![
+				TopTalker {
+					peer: b,
+					peer_set: PeerSet::Collation,
+					bytes_sent: 0,
+					bytes_received: 500
+				},
+				TopTalker {
+					peer: c,
+					peer_set: PeerSet::Validation,
+					bytes_sent: 300,
+					bytes_received: 0
+				},
+			],
+		);
+
+		traffic.on_peer_disconnected(b, PeerSet::Collation);
+		assert_eq!(traffic.top_talkers(1)[0].peer, c);
+	}
+}
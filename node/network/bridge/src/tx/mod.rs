@@ -17,10 +17,12 @@
 //! The Network Bridge Subsystem - handles _outgoing_ messages, from subsystem to the network.
 use super::*;
 
+use futures_timer::Delay;
+
 use polkadot_node_network_protocol::{
 	peer_set::{CollationVersion, PeerSet, PeerSetProtocolNames, ValidationVersion},
 	request_response::ReqProtocolNames,
-	v1 as protocol_v1, PeerId, Versioned,
+	v1 as protocol_v1, v2 as protocol_v2, PeerId, Versioned,
 };
 
 use polkadot_node_subsystem::{
@@ -40,7 +42,11 @@ use crate::validator_discovery;
 /// Defines the `Network` trait with an implementation for an `Arc<NetworkService>`.
 use crate::network::{send_message, Network};
 
-use crate::metrics::Metrics;
+use crate::{
+	metrics::Metrics,
+	reputation::{ReputationAggregator, REPUTATION_CHANGE_INTERVAL},
+	traffic::NetworkTraffic,
+};
 
 #[cfg(test)]
 mod tests;
@@ -56,6 +62,8 @@ pub struct NetworkBridgeTx<N, AD> {
 	metrics: Metrics,
 	req_protocol_names: ReqProtocolNames,
 	peerset_protocol_names: PeerSetProtocolNames,
+	traffic: NetworkTraffic,
+	reputation: ReputationAggregator,
 }
 
 impl<N, AD> NetworkBridgeTx<N, AD> {
@@ -63,12 +71,17 @@ impl<N, AD> NetworkBridgeTx<N, AD> {
 	///
 	/// This assumes that the network service has had the notifications protocol for the network
 	/// bridge already registered. See [`peers_sets_info`](peers_sets_info).
+	///
+	/// Outbound traffic is accounted in `traffic` and reported reputation changes are aggregated
+	/// in `reputation`, both of which need to be shared with the receiving side of the bridge.
 	pub fn new(
 		network_service: N,
 		authority_discovery_service: AD,
 		metrics: Metrics,
 		req_protocol_names: ReqProtocolNames,
 		peerset_protocol_names: PeerSetProtocolNames,
+		traffic: NetworkTraffic,
+		reputation: ReputationAggregator,
 	) -> Self {
 		Self {
 			network_service,
@@ -76,6 +89,8 @@ impl<N, AD> NetworkBridgeTx<N, AD> {
 			metrics,
 			req_protocol_names,
 			peerset_protocol_names,
+			traffic,
+			reputation,
 		}
 	}
 }
@@ -102,6 +117,8 @@ async fn handle_subsystem_messages<Context, N, AD>(
 	metrics: Metrics,
 	req_protocol_names: ReqProtocolNames,
 	peerset_protocol_names: PeerSetProtocolNames,
+	traffic: NetworkTraffic,
+	reputation: ReputationAggregator,
 ) -> Result<(), Error>
 where
 	N: Network,
@@ -110,9 +127,21 @@ where
 	let mut validator_discovery =
 		validator_discovery::Service::<N, AD>::new(peerset_protocol_names.clone());
 
+	let mut next_reputation_flush = Delay::new(REPUTATION_CHANGE_INTERVAL).fuse();
 	loop {
-		match ctx.recv().fuse().await? {
-			FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
+		let message = futures::select!(
+			_ = next_reputation_flush => {
+				reputation.flush(&network_service);
+				next_reputation_flush = Delay::new(REPUTATION_CHANGE_INTERVAL).fuse();
+				continue
+			}
+			message = ctx.recv().fuse() => message?,
+		);
+		match message {
+			FromOrchestra::Signal(OverseerSignal::Conclude) => {
+				reputation.flush(&network_service);
+				return Ok(())
+			},
 			FromOrchestra::Signal(_) => { /* handled by incoming */ },
 			FromOrchestra::Communication { msg } => {
 				(network_service, authority_discovery_service) =
@@ -125,6 +154,8 @@ where
 						&metrics,
 						&req_protocol_names,
 						&peerset_protocol_names,
+						&traffic,
+						&reputation,
 					)
 					.await;
 			},
@@ -134,7 +165,7 @@ where
 
 #[overseer::contextbounds(NetworkBridgeTx, prefix = self::overseer)]
 async fn handle_incoming_subsystem_communication<Context, N, AD>(
-	_ctx: &mut Context,
+	ctx: &mut Context,
 	mut network_service: N,
 	validator_discovery: &mut validator_discovery::Service<N, AD>,
 	mut authority_discovery_service: AD,
@@ -142,6 +173,8 @@ async fn handle_incoming_subsystem_communication<Context, N, AD>(
 	metrics: &Metrics,
 	req_protocol_names: &ReqProtocolNames,
 	peerset_protocol_names: &PeerSetProtocolNames,
+	traffic: &NetworkTraffic,
+	reputation: &ReputationAggregator,
 ) -> (N, AD)
 where
 	N: Network,
@@ -154,7 +187,7 @@ where
 			}
 
 			metrics.on_report_event();
-			network_service.report_peer(peer, rep);
+			reputation.modify(&network_service, peer, rep);
 		},
 		NetworkBridgeTxMessage::DisconnectPeer(peer, peer_set) => {
 			gum::trace!(
@@ -182,6 +215,15 @@ where
 					peerset_protocol_names,
 					WireMessage::ProtocolMessage(msg),
 					&metrics,
+					traffic,
+				),
+				Versioned::V2(msg) => send_validation_message_v2(
+					&mut network_service,
+					peers,
+					peerset_protocol_names,
+					WireMessage::ProtocolMessage(msg),
+					&metrics,
+					traffic,
 				),
 			}
 		},
@@ -200,6 +242,15 @@ where
 						peerset_protocol_names,
 						WireMessage::ProtocolMessage(msg),
 						&metrics,
+						traffic,
+					),
+					Versioned::V2(msg) => send_validation_message_v2(
+						&mut network_service,
+						peers,
+						peerset_protocol_names,
+						WireMessage::ProtocolMessage(msg),
+						&metrics,
+						traffic,
 					),
 				}
 			}
@@ -218,6 +269,15 @@ where
 					peerset_protocol_names,
 					WireMessage::ProtocolMessage(msg),
 					&metrics,
+					traffic,
+				),
+				Versioned::V2(msg) => send_collation_message_v2(
+					&mut network_service,
+					peers,
+					peerset_protocol_names,
+					WireMessage::ProtocolMessage(msg),
+					&metrics,
+					traffic,
 				),
 			}
 		},
@@ -236,6 +296,15 @@ where
 						peerset_protocol_names,
 						WireMessage::ProtocolMessage(msg),
 						&metrics,
+						traffic,
+					),
+					Versioned::V2(msg) => send_collation_message_v2(
+						&mut network_service,
+						peers,
+						peerset_protocol_names,
+						WireMessage::ProtocolMessage(msg),
+						&metrics,
+						traffic,
 					),
 				}
 			}
@@ -248,7 +317,12 @@ where
 			);
 
 			for req in reqs {
-				network_service
+				let protocol = req.get_protocol();
+				let payload_size = req.payload_size();
+				metrics.on_request_bytes_sent(protocol, payload_size);
+				traffic.on_request_sent(protocol, payload_size);
+
+				let negotiation = network_service
 					.start_request(
 						&mut authority_discovery_service,
 						req,
@@ -256,6 +330,24 @@ where
 						if_disconnected,
 					)
 					.await;
+
+				match negotiation {
+					None => metrics.on_request_sent(protocol),
+					Some(negotiation) => {
+						let metrics = metrics.clone();
+						let negotiation = async move {
+							metrics.on_request_sent(negotiation.await);
+						};
+						if let Err(err) = ctx.spawn("request-negotiation", negotiation.boxed()) {
+							gum::warn!(
+								target: LOG_TARGET,
+								?err,
+								?protocol,
+								"Failed to spawn request version negotiation",
+							);
+						}
+					},
+				}
 			}
 		},
 		NetworkBridgeTxMessage::ConnectToValidators { validator_ids, peer_set, failed } => {
@@ -317,6 +409,8 @@ where
 		metrics,
 		req_protocol_names,
 		peerset_protocol_names,
+		traffic,
+		reputation,
 	} = bridge;
 
 	handle_subsystem_messages(
@@ -326,6 +420,8 @@ where
 		metrics,
 		req_protocol_names,
 		peerset_protocol_names,
+		traffic,
+		reputation,
 	)
 	.await?;
 
@@ -338,6 +434,7 @@ fn send_validation_message_v1(
 	protocol_names: &PeerSetProtocolNames,
 	message: WireMessage<protocol_v1::ValidationProtocol>,
 	metrics: &Metrics,
+	traffic: &NetworkTraffic,
 ) {
 	send_message(
 		net,
@@ -347,6 +444,27 @@ fn send_validation_message_v1(
 		protocol_names,
 		message,
 		metrics,
+		traffic,
+	);
+}
+
+fn send_validation_message_v2(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	protocol_names: &PeerSetProtocolNames,
+	message: WireMessage<protocol_v2::ValidationProtocol>,
+	metrics: &Metrics,
+	traffic: &NetworkTraffic,
+) {
+	send_message(
+		net,
+		peers,
+		PeerSet::Validation,
+		ValidationVersion::V2.into(),
+		protocol_names,
+		message,
+		metrics,
+		traffic,
 	);
 }
 
@@ -356,6 +474,7 @@ fn send_collation_message_v1(
 	protocol_names: &PeerSetProtocolNames,
 	message: WireMessage<protocol_v1::CollationProtocol>,
 	metrics: &Metrics,
+	traffic: &NetworkTraffic,
 ) {
 	send_message(
 		net,
@@ -365,5 +484,26 @@ fn send_collation_message_v1(
 		protocol_names,
 		message,
 		metrics,
+		traffic,
+	);
+}
+
+fn send_collation_message_v2(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	protocol_names: &PeerSetProtocolNames,
+	message: WireMessage<protocol_v2::CollationProtocol>,
+	metrics: &Metrics,
+	traffic: &NetworkTraffic,
+) {
+	send_message(
+		net,
+		peers,
+		PeerSet::Collation,
+		CollationVersion::V2.into(),
+		protocol_names,
+		message,
+		metrics,
+		traffic,
 	);
 }
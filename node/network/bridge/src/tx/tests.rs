@@ -15,7 +15,7 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
-use futures::{executor, stream::BoxStream};
+use futures::{executor, future::BoxFuture, stream::BoxStream};
 use polkadot_node_subsystem_util::TimeoutExt;
 
 use async_trait::async_trait;
@@ -26,8 +26,8 @@ use sc_network::{Event as NetworkEvent, IfDisconnected, ProtocolName};
 
 use polkadot_node_network_protocol::{
 	peer_set::PeerSetProtocolNames,
-	request_response::{outgoing::Requests, ReqProtocolNames},
-	ObservedRole, Versioned,
+	request_response::{outgoing::Requests, Protocol, ReqProtocolNames},
+	ObservedRole, ReputationChange, Versioned,
 };
 use polkadot_node_subsystem::{FromOrchestra, OverseerSignal};
 use polkadot_node_subsystem_test_helpers::TestSubsystemContextHandle;
@@ -39,12 +39,12 @@ use sp_keyring::Sr25519Keyring;
 
 const TIMEOUT: std::time::Duration = polkadot_node_subsystem_test_helpers::TestSubsystemContextHandle::<NetworkBridgeTxMessage>::TIMEOUT;
 
-use crate::{network::Network, validator_discovery::AuthorityDiscovery, Rep};
+use crate::{network::Network, validator_discovery::AuthorityDiscovery};
 
 #[derive(Debug, PartialEq)]
 pub enum NetworkAction {
 	/// Note a change in reputation for a peer.
-	ReputationChange(PeerId, Rep),
+	ReputationChange(PeerId, ReputationChange),
 	/// Disconnect a peer from the given peer-set.
 	DisconnectPeer(PeerId, PeerSet),
 	/// Write a notification to a given peer on the given peer-set.
@@ -113,10 +113,11 @@ impl Network for TestNetwork {
 		_: Requests,
 		_: &ReqProtocolNames,
 		_: IfDisconnected,
-	) {
+	) -> Option<BoxFuture<'static, Protocol>> {
+		None
 	}
 
-	fn report_peer(&self, who: PeerId, cost_benefit: Rep) {
+	fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange) {
 		self.action_tx
 			.lock()
 			.unbounded_send(NetworkAction::ReputationChange(who, cost_benefit))
@@ -190,6 +191,13 @@ struct TestHarness {
 }
 
 fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarness) -> T) {
+	test_harness_with_traffic(NetworkTraffic::default(), test)
+}
+
+fn test_harness_with_traffic<T: Future<Output = VirtualOverseer>>(
+	traffic: NetworkTraffic,
+	test: impl FnOnce(TestHarness) -> T,
+) {
 	let genesis_hash = Hash::repeat_byte(0xff);
 	let fork_id = None;
 	let req_protocol_names = ReqProtocolNames::new(genesis_hash, fork_id);
@@ -207,6 +215,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarne
 		Metrics(None),
 		req_protocol_names,
 		peerset_protocol_names,
+		traffic,
+		ReputationAggregator::unbatched(),
 	);
 
 	let network_bridge_out_fut = run_network_out(bridge_out, context)
@@ -321,3 +331,144 @@ fn send_messages_to_peers() {
 		virtual_overseer
 	});
 }
+
+#[test]
+fn sent_notifications_are_accounted_to_peers() {
+	let traffic = NetworkTraffic::default();
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	traffic.on_peer_connected(peer_a, PeerSet::Validation);
+	traffic.on_peer_connected(peer_b, PeerSet::Validation);
+
+	let message = protocol_v1::ValidationProtocol::ApprovalDistribution(
+		protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new()),
+	);
+	let size = WireMessage::ProtocolMessage(message.clone()).encode().len() as u64;
+
+	test_harness_with_traffic(traffic.clone(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeTxMessage::SendValidationMessages(vec![
+					(vec![peer_a, peer_b], Versioned::V1(message.clone())),
+					(vec![peer_a], Versioned::V1(message)),
+				]),
+			})
+			.timeout(TIMEOUT)
+			.await
+			.expect("Timeout does not occur");
+
+		for _ in 0..3 {
+			network_handle
+				.next_network_action()
+				.timeout(TIMEOUT)
+				.await
+				.expect("Timeout does not occur");
+		}
+
+		virtual_overseer
+	});
+
+	let talkers = traffic.top_talkers(10);
+	assert_eq!(
+		talkers,
+		vec![
+			TopTalker {
+				peer: peer_a,
+				peer_set: PeerSet::Validation,
+				bytes_sent: 2 * size,
+				bytes_received: 0,
+			},
+			TopTalker {
+				peer: peer_b,
+				peer_set: PeerSet::Validation,
+				bytes_sent: size,
+				bytes_received: 0
+			},
+		],
+	);
+}
+
+#[test]
+fn collation_messages_are_dropped_before_validation_messages() {
+	// The `Declare` message takes up about half of the budget, which is the reserve kept for
+	// validation traffic.
+	test_harness_with_traffic(
+		NetworkTraffic::with_outbound_limit(200),
+		|test_harness| async move {
+			let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+			let peer = PeerId::random();
+
+			let collation_message_v1 = protocol_v1::CollationProtocol::CollatorProtocol(
+				protocol_v1::CollatorProtocolMessage::Declare(
+					Sr25519Keyring::Alice.public().into(),
+					0_u32.into(),
+					dummy_collator_signature(),
+				),
+			);
+			let approval_message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+				protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new()),
+			);
+
+			// The first collation message fits into the budget.
+			virtual_overseer
+				.send(FromOrchestra::Communication {
+					msg: NetworkBridgeTxMessage::SendCollationMessage(
+						vec![peer],
+						Versioned::V1(collation_message_v1.clone()),
+					),
+				})
+				.await;
+
+			assert_eq!(
+				network_handle
+					.next_network_action()
+					.timeout(TIMEOUT)
+					.await
+					.expect("Timeout does not occur"),
+				NetworkAction::WriteNotification(
+					peer,
+					PeerSet::Collation,
+					WireMessage::ProtocolMessage(collation_message_v1.clone()).encode(),
+				)
+			);
+
+			// The second one would eat into the reserve and is dropped, while the approval message
+			// is still sent.
+			virtual_overseer
+				.send(FromOrchestra::Communication {
+					msg: NetworkBridgeTxMessage::SendCollationMessage(
+						vec![peer],
+						Versioned::V1(collation_message_v1),
+					),
+				})
+				.await;
+
+			virtual_overseer
+				.send(FromOrchestra::Communication {
+					msg: NetworkBridgeTxMessage::SendValidationMessage(
+						vec![peer],
+						Versioned::V1(approval_message_v1.clone()),
+					),
+				})
+				.await;
+
+			assert_eq!(
+				network_handle
+					.next_network_action()
+					.timeout(TIMEOUT)
+					.await
+					.expect("Timeout does not occur"),
+				NetworkAction::WriteNotification(
+					peer,
+					PeerSet::Validation,
+					WireMessage::ProtocolMessage(approval_message_v1).encode(),
+				)
+			);
+
+			virtual_overseer
+		},
+	);
+}
@@ -168,10 +168,10 @@ mod tests {
 	use crate::network::Network;
 
 	use async_trait::async_trait;
-	use futures::stream::BoxStream;
+	use futures::{future::BoxFuture, stream::BoxStream};
 	use polkadot_node_network_protocol::{
-		request_response::{outgoing::Requests, ReqProtocolNames},
-		PeerId,
+		request_response::{outgoing::Requests, Protocol, ReqProtocolNames},
+		PeerId, ReputationChange,
 	};
 	use polkadot_primitives::Hash;
 	use sc_network::{Event as NetworkEvent, IfDisconnected, ProtocolName};
@@ -246,10 +246,11 @@ mod tests {
 			_: Requests,
 			_: &ReqProtocolNames,
 			_: IfDisconnected,
-		) {
+		) -> Option<BoxFuture<'static, Protocol>> {
+			None
 		}
 
-		fn report_peer(&self, _: PeerId, _: crate::Rep) {
+		fn report_peer(&self, _: PeerId, _: ReputationChange) {
 			panic!()
 		}
 
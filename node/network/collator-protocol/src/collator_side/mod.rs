@@ -27,13 +27,14 @@ use sp_core::Pair;
 
 use polkadot_node_network_protocol::{
 	self as net_protocol,
-	peer_set::PeerSet,
+	peer_set::{CollationVersion, PeerSet, ProtocolVersion},
 	request_response::{
 		incoming::{self, OutgoingResponse},
-		v1::{self as request_v1, CollationFetchingRequest, CollationFetchingResponse},
-		IncomingRequest, IncomingRequestReceiver,
+		v1::{self as request_v1, CollationFetchingResponse},
+		v2 as request_v2, IncomingRequest, IncomingRequestReceiver,
 	},
-	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	v1 as protocol_v1, v2 as protocol_v2, OurView, PeerId, UnifiedReputationChange as Rep,
+	Versioned, View,
 };
 use polkadot_node_primitives::{CollationSecondedSignal, PoV, Statement};
 use polkadot_node_subsystem::{
@@ -162,6 +163,8 @@ impl CollationStatus {
 /// A collation built by the collator.
 struct Collation {
 	receipt: CandidateReceipt,
+	/// Hash of the para head the candidate builds upon.
+	parent_head_data_hash: Hash,
 	pov: PoV,
 	status: CollationStatus,
 }
@@ -172,7 +175,7 @@ struct WaitingCollationFetches {
 	/// Is there currently a collation getting fetched?
 	collation_fetch_active: bool,
 	/// The collation fetches waiting to be fulfilled.
-	waiting: VecDeque<IncomingRequest<CollationFetchingRequest>>,
+	waiting: VecDeque<VersionedCollationRequest>,
 	/// All peers that are waiting or actively uploading.
 	///
 	/// We will not accept multiple requests from the same peer, otherwise our DoS protection of
@@ -180,6 +183,70 @@ struct WaitingCollationFetches {
 	waiting_peers: HashSet<PeerId>,
 }
 
+/// A collation fetch request on any version of the protocol.
+#[derive(Debug)]
+enum VersionedCollationRequest {
+	V1(IncomingRequest<request_v1::CollationFetchingRequest>),
+	V2(IncomingRequest<request_v2::CollationFetchingRequest>),
+}
+
+impl From<IncomingRequest<request_v1::CollationFetchingRequest>> for VersionedCollationRequest {
+	fn from(req: IncomingRequest<request_v1::CollationFetchingRequest>) -> Self {
+		Self::V1(req)
+	}
+}
+
+impl From<IncomingRequest<request_v2::CollationFetchingRequest>> for VersionedCollationRequest {
+	fn from(req: IncomingRequest<request_v2::CollationFetchingRequest>) -> Self {
+		Self::V2(req)
+	}
+}
+
+impl VersionedCollationRequest {
+	/// The relay parent of the requested collation.
+	fn relay_parent(&self) -> Hash {
+		match self {
+			Self::V1(req) => req.payload.relay_parent,
+			Self::V2(req) => req.payload.relay_parent,
+		}
+	}
+
+	/// The para of the requested collation.
+	fn para_id(&self) -> ParaId {
+		match self {
+			Self::V1(req) => req.payload.para_id,
+			Self::V2(req) => req.payload.para_id,
+		}
+	}
+
+	/// The hash of the requested candidate, only known for requests of version 2.
+	fn candidate_hash(&self) -> Option<CandidateHash> {
+		match self {
+			Self::V1(_) => None,
+			Self::V2(req) => Some(req.payload.candidate_hash),
+		}
+	}
+
+	/// The peer which sent the request.
+	fn peer_id(&self) -> PeerId {
+		match self {
+			Self::V1(req) => req.peer,
+			Self::V2(req) => req.peer,
+		}
+	}
+
+	/// Send the response back, see [`IncomingRequest::send_outgoing_response`].
+	fn send_outgoing_response(
+		self,
+		response: OutgoingResponse<CollationFetchingResponse>,
+	) -> std::result::Result<(), ()> {
+		match self {
+			Self::V1(req) => req.send_outgoing_response(response),
+			Self::V2(req) => req.send_outgoing_response(response),
+		}
+	}
+}
+
 struct CollationSendResult {
 	relay_parent: Hash,
 	peer_id: PeerId,
@@ -225,6 +292,9 @@ struct State {
 	/// by `PeerConnected` events.
 	peer_ids: HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
 
+	/// The collation protocol version each connected peer negotiated.
+	peer_versions: HashMap<PeerId, ProtocolVersion>,
+
 	/// Tracks which validators we want to stay connected to.
 	validator_groups_buf: ValidatorGroupsBuffer,
 
@@ -263,6 +333,7 @@ impl State {
 			collation_result_senders: Default::default(),
 			our_validators_groups: Default::default(),
 			peer_ids: Default::default(),
+			peer_versions: Default::default(),
 			validator_groups_buf: ValidatorGroupsBuffer::with_capacity(VALIDATORS_BUFFER_CAPACITY),
 			last_connected_at: None,
 			waiting_collation_fetches: Default::default(),
@@ -278,6 +349,11 @@ impl State {
 			.map(|(peer, _)| *peer)
 			.collect()
 	}
+
+	/// Whether the given peer is connected on the second version of the collation protocol.
+	fn is_v2_peer(&self, peer: &PeerId) -> bool {
+		self.peer_versions.get(peer) == Some(&CollationVersion::V2.into())
+	}
 }
 
 /// Distribute a collation.
@@ -295,6 +371,7 @@ async fn distribute_collation<Context>(
 	state: &mut State,
 	id: ParaId,
 	receipt: CandidateReceipt,
+	parent_head_data_hash: Hash,
 	pov: PoV,
 	result_sender: Option<oneshot::Sender<CollationSecondedSignal>>,
 ) -> Result<()> {
@@ -384,9 +461,10 @@ async fn distribute_collation<Context>(
 		state.collation_result_senders.insert(candidate_hash, result_sender);
 	}
 
-	state
-		.collations
-		.insert(relay_parent, Collation { receipt, pov, status: CollationStatus::Created });
+	state.collations.insert(
+		relay_parent,
+		Collation { receipt, parent_head_data_hash, pov, status: CollationStatus::Created },
+	);
 
 	let interested = state.peers_interested_in_leaf(&relay_parent);
 	// Make sure already connected peers get collations:
@@ -471,17 +549,21 @@ async fn declare<Context>(ctx: &mut Context, state: &mut State, peer: PeerId) {
 	let declare_signature_payload = protocol_v1::declare_signature_payload(&state.local_peer_id);
 
 	if let Some(para_id) = state.collating_on {
-		let wire_message = protocol_v1::CollatorProtocolMessage::Declare(
-			state.collator_pair.public(),
-			para_id,
-			state.collator_pair.sign(&declare_signature_payload),
-		);
-
-		ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(
-			vec![peer],
-			Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(wire_message)),
-		))
-		.await;
+		let collator_id = state.collator_pair.public();
+		let signature = state.collator_pair.sign(&declare_signature_payload);
+
+		let wire_message = if state.is_v2_peer(&peer) {
+			Versioned::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+				protocol_v2::CollatorProtocolMessage::Declare(collator_id, para_id, signature),
+			))
+		} else {
+			Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(
+				protocol_v1::CollatorProtocolMessage::Declare(collator_id, para_id, signature),
+			))
+		};
+
+		ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(vec![peer], wire_message))
+			.await;
 	}
 }
 
@@ -528,7 +610,9 @@ async fn advertise_collation<Context>(
 		.map(|g| g.should_advertise_to(&state.peer_ids, &peer))
 		.unwrap_or(false);
 
-	match (state.collations.get_mut(&relay_parent), should_advertise) {
+	let is_v2_peer = state.is_v2_peer(&peer);
+
+	let collation = match (state.collations.get_mut(&relay_parent), should_advertise) {
 		(None, _) => {
 			gum::trace!(
 				target: LOG_TARGET,
@@ -554,17 +638,27 @@ async fn advertise_collation<Context>(
 				peer_id = %peer,
 				"Advertising collation.",
 			);
-			collation.status.advance_to_advertised()
+			collation.status.advance_to_advertised();
+			collation
 		},
-	}
+	};
 
-	let wire_message = protocol_v1::CollatorProtocolMessage::AdvertiseCollation(relay_parent);
+	let wire_message = if is_v2_peer {
+		Versioned::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+			protocol_v2::CollatorProtocolMessage::AdvertiseCollation {
+				relay_parent,
+				candidate_hash: collation.receipt.hash(),
+				parent_head_data_hash: collation.parent_head_data_hash,
+			},
+		))
+	} else {
+		Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(
+			protocol_v1::CollatorProtocolMessage::AdvertiseCollation(relay_parent),
+		))
+	};
 
-	ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(
-		vec![peer],
-		Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(wire_message)),
-	))
-	.await;
+	ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(vec![peer], wire_message))
+		.await;
 
 	if let Some(validators) = state.our_validators_groups.get_mut(&relay_parent) {
 		validators.advertised_to_peer(&state.peer_ids, &peer);
@@ -587,7 +681,7 @@ async fn process_msg<Context>(
 		CollateOn(id) => {
 			state.collating_on = Some(id);
 		},
-		DistributeCollation(receipt, pov, result_sender) => {
+		DistributeCollation(receipt, parent_head_data_hash, pov, result_sender) => {
 			let _span1 = state
 				.span_per_relay_parent
 				.get(&receipt.descriptor.relay_parent)
@@ -606,8 +700,17 @@ async fn process_msg<Context>(
 				},
 				Some(id) => {
 					let _ = state.metrics.time_collation_distribution("distribute");
-					distribute_collation(ctx, runtime, state, id, receipt, pov, result_sender)
-						.await?;
+					distribute_collation(
+						ctx,
+						runtime,
+						state,
+						id,
+						receipt,
+						parent_head_data_hash,
+						pov,
+						result_sender,
+					)
+					.await?;
 				},
 				None => {
 					gum::warn!(
@@ -645,14 +748,14 @@ async fn process_msg<Context>(
 /// Issue a response to a previously requested collation.
 async fn send_collation(
 	state: &mut State,
-	request: IncomingRequest<CollationFetchingRequest>,
+	request: VersionedCollationRequest,
 	receipt: CandidateReceipt,
 	pov: PoV,
 ) {
 	let (tx, rx) = oneshot::channel();
 
-	let relay_parent = request.payload.relay_parent;
-	let peer_id = request.peer;
+	let relay_parent = request.relay_parent();
+	let peer_id = request.peer_id();
 
 	let response = OutgoingResponse {
 		result: Ok(CollationFetchingResponse::Collation(receipt, pov)),
@@ -684,12 +787,13 @@ async fn handle_incoming_peer_message<Context>(
 	runtime: &mut RuntimeInfo,
 	state: &mut State,
 	origin: PeerId,
-	msg: protocol_v1::CollatorProtocolMessage,
+	msg: net_protocol::CollatorProtocolMessage,
 ) -> Result<()> {
-	use protocol_v1::CollatorProtocolMessage::*;
+	use protocol_v1::CollatorProtocolMessage as V1;
+	use protocol_v2::CollatorProtocolMessage as V2;
 
 	match msg {
-		Declare(_, _, _) => {
+		Versioned::V1(V1::Declare(_, _, _)) | Versioned::V2(V2::Declare(_, _, _)) => {
 			gum::trace!(
 				target: LOG_TARGET,
 				?origin,
@@ -700,7 +804,7 @@ async fn handle_incoming_peer_message<Context>(
 			ctx.send_message(NetworkBridgeTxMessage::DisconnectPeer(origin, PeerSet::Collation))
 				.await;
 		},
-		AdvertiseCollation(_) => {
+		Versioned::V1(V1::AdvertiseCollation(_)) | Versioned::V2(V2::AdvertiseCollation { .. }) => {
 			gum::trace!(
 				target: LOG_TARGET,
 				?origin,
@@ -714,7 +818,8 @@ async fn handle_incoming_peer_message<Context>(
 			ctx.send_message(NetworkBridgeTxMessage::DisconnectPeer(origin, PeerSet::Collation))
 				.await;
 		},
-		CollationSeconded(relay_parent, statement) => {
+		Versioned::V1(V1::CollationSeconded(relay_parent, statement)) |
+		Versioned::V2(V2::CollationSeconded(relay_parent, statement)) => {
 			if !matches!(statement.unchecked_payload(), Statement::Seconded(_)) {
 				gum::warn!(
 					target: LOG_TARGET,
@@ -759,43 +864,62 @@ async fn handle_incoming_peer_message<Context>(
 async fn handle_incoming_request<Context>(
 	ctx: &mut Context,
 	state: &mut State,
-	req: IncomingRequest<request_v1::CollationFetchingRequest>,
+	req: VersionedCollationRequest,
 ) -> Result<()> {
+	let relay_parent = req.relay_parent();
 	let _span = state
 		.span_per_relay_parent
-		.get(&req.payload.relay_parent)
+		.get(&relay_parent)
 		.map(|s| s.child("request-collation"));
 
 	match state.collating_on {
-		Some(our_para_id) if our_para_id == req.payload.para_id => {
-			let (receipt, pov) =
-				if let Some(collation) = state.collations.get_mut(&req.payload.relay_parent) {
+		Some(our_para_id) if our_para_id == req.para_id() => {
+			let (receipt, pov) = match state.collations.get_mut(&relay_parent) {
+				Some(collation)
+					if req.candidate_hash().map_or(true, |candidate_hash| {
+						candidate_hash == collation.receipt.hash()
+					}) =>
+				{
 					collation.status.advance_to_requested();
 					(collation.receipt.clone(), collation.pov.clone())
-				} else {
+				},
+				Some(_) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						%relay_parent,
+						candidate_hash = ?req.candidate_hash(),
+						"received a `RequestCollation` for a candidate we don't have stored.",
+					);
+
+					return Ok(())
+				},
+				None => {
 					gum::warn!(
 						target: LOG_TARGET,
-						relay_parent = %req.payload.relay_parent,
+						%relay_parent,
 						"received a `RequestCollation` for a relay parent we don't have collation stored.",
 					);
 
 					return Ok(())
-				};
+				},
+			};
 
 			state.metrics.on_collation_sent_requested();
 
 			let _span = _span.as_ref().map(|s| s.child("sending"));
 
-			let waiting =
-				state.waiting_collation_fetches.entry(req.payload.relay_parent).or_default();
+			let waiting = state.waiting_collation_fetches.entry(relay_parent).or_default();
 
-			if !waiting.waiting_peers.insert(req.peer) {
+			if !waiting.waiting_peers.insert(req.peer_id()) {
 				gum::debug!(
 					target: LOG_TARGET,
 					"Dropping incoming request as peer has a request in flight already."
 				);
-				ctx.send_message(NetworkBridgeTxMessage::ReportPeer(req.peer, COST_APPARENT_FLOOD))
-					.await;
+				ctx.send_message(NetworkBridgeTxMessage::ReportPeer(
+					req.peer_id(),
+					COST_APPARENT_FLOOD,
+				))
+				.await;
 				return Ok(())
 			}
 
@@ -811,7 +935,7 @@ async fn handle_incoming_request<Context>(
 		Some(our_para_id) => {
 			gum::warn!(
 				target: LOG_TARGET,
-				for_para_id = %req.payload.para_id,
+				for_para_id = %req.para_id(),
 				our_para_id = %our_para_id,
 				"received a `CollationFetchingRequest` for unexpected para_id",
 			);
@@ -819,7 +943,7 @@ async fn handle_incoming_request<Context>(
 		None => {
 			gum::warn!(
 				target: LOG_TARGET,
-				for_para_id = %req.payload.para_id,
+				for_para_id = %req.para_id(),
 				"received a `RequestCollation` while not collating on any para",
 			);
 		},
@@ -857,10 +981,11 @@ async fn handle_network_msg<Context>(
 	use NetworkBridgeEvent::*;
 
 	match bridge_message {
-		PeerConnected(peer_id, observed_role, _, maybe_authority) => {
+		PeerConnected(peer_id, observed_role, version, maybe_authority) => {
 			// If it is possible that a disconnected validator would attempt a reconnect
 			// it should be handled here.
-			gum::trace!(target: LOG_TARGET, ?peer_id, ?observed_role, "Peer connected");
+			gum::trace!(target: LOG_TARGET, ?peer_id, ?observed_role, %version, "Peer connected");
+			state.peer_versions.insert(peer_id, version);
 			if let Some(authority_ids) = maybe_authority {
 				gum::trace!(
 					target: LOG_TARGET,
@@ -881,12 +1006,13 @@ async fn handle_network_msg<Context>(
 			gum::trace!(target: LOG_TARGET, ?peer_id, "Peer disconnected");
 			state.peer_views.remove(&peer_id);
 			state.peer_ids.remove(&peer_id);
+			state.peer_versions.remove(&peer_id);
 		},
 		OurViewChange(view) => {
 			gum::trace!(target: LOG_TARGET, ?view, "Own view change");
 			handle_our_view_change(state, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, msg) => {
 			handle_incoming_peer_message(ctx, runtime, state, remote, msg).await?;
 		},
 		NewGossipTopology { .. } => {
@@ -943,7 +1069,8 @@ pub(crate) async fn run<Context>(
 	mut ctx: Context,
 	local_peer_id: PeerId,
 	collator_pair: CollatorPair,
-	mut req_receiver: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+	mut req_receiver_v1: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+	mut req_receiver_v2: IncomingRequestReceiver<request_v2::CollationFetchingRequest>,
 	metrics: Metrics,
 ) -> std::result::Result<(), FatalError> {
 	use OverseerSignal::*;
@@ -955,8 +1082,10 @@ pub(crate) async fn run<Context>(
 	pin_mut!(reconnect_stream);
 
 	loop {
-		let recv_req = req_receiver.recv(|| vec![COST_INVALID_REQUEST]).fuse();
-		pin_mut!(recv_req);
+		let recv_req_v1 = req_receiver_v1.recv(|| vec![COST_INVALID_REQUEST]).fuse();
+		let recv_req_v2 = req_receiver_v2.recv(|| vec![COST_INVALID_REQUEST]).fuse();
+		pin_mut!(recv_req_v1);
+		pin_mut!(recv_req_v2);
 		select! {
 			msg = ctx.recv().fuse() => match msg.map_err(FatalError::SubsystemReceive)? {
 				FromOrchestra::Communication { msg } => {
@@ -1030,25 +1159,39 @@ pub(crate) async fn run<Context>(
 					);
 				}
 			},
-			in_req = recv_req => {
-				match in_req {
-					Ok(req) => {
-						log_error(
-							handle_incoming_request(&mut ctx, &mut state, req).await,
-							"Handling incoming request"
-						)?;
-					}
-					Err(error) => {
-						let jfyi = error.split().map_err(incoming::Error::from)?;
-						gum::debug!(
-							target: LOG_TARGET,
-							error = ?jfyi,
-							"Decoding incoming request failed"
-						);
-						continue
-					}
-				}
+			in_req = recv_req_v1 => {
+				handle_incoming_request_result(
+					&mut ctx,
+					&mut state,
+					in_req.map(VersionedCollationRequest::from),
+				).await?;
+			}
+			in_req = recv_req_v2 => {
+				handle_incoming_request_result(
+					&mut ctx,
+					&mut state,
+					in_req.map(VersionedCollationRequest::from),
+				).await?;
 			}
 		}
 	}
 }
+
+/// Handle a received incoming request or the error receiving it.
+#[overseer::contextbounds(CollatorProtocol, prefix = crate::overseer)]
+async fn handle_incoming_request_result<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	in_req: incoming::Result<VersionedCollationRequest>,
+) -> std::result::Result<(), FatalError> {
+	match in_req {
+		Ok(req) => {
+			log_error(handle_incoming_request(ctx, state, req).await, "Handling incoming request")?;
+		},
+		Err(error) => {
+			let jfyi = error.split().map_err(incoming::Error::from)?;
+			gum::debug!(target: LOG_TARGET, error = ?jfyi, "Decoding incoming request failed");
+		},
+	}
+	Ok(())
+}
@@ -32,7 +32,7 @@ use sp_runtime::traits::AppVerify;
 use polkadot_node_network_protocol::{
 	our_view,
 	peer_set::CollationVersion,
-	request_response::{IncomingRequest, ReqProtocolNames},
+	request_response::{v1::CollationFetchingRequest, IncomingRequest, ReqProtocolNames},
 	view,
 };
 use polkadot_node_primitives::BlockData;
@@ -192,6 +192,7 @@ type VirtualOverseer = test_helpers::TestSubsystemContextHandle<CollatorProtocol
 struct TestHarness {
 	virtual_overseer: VirtualOverseer,
 	req_cfg: sc_network::config::RequestResponseConfig,
+	req_v2_cfg: sc_network::config::RequestResponseConfig,
 }
 
 fn test_harness<T: Future<Output = TestHarness>>(
@@ -214,13 +215,22 @@ fn test_harness<T: Future<Output = TestHarness>>(
 
 	let (collation_req_receiver, req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (collation_req_v2_receiver, req_v2_cfg) =
+		IncomingRequest::get_config_receiver(&req_protocol_names);
 	let subsystem = async {
-		run(context, local_peer_id, collator_pair, collation_req_receiver, Default::default())
-			.await
-			.unwrap();
+		run(
+			context,
+			local_peer_id,
+			collator_pair,
+			collation_req_receiver,
+			collation_req_v2_receiver,
+			Default::default(),
+		)
+		.await
+		.unwrap();
 	};
 
-	let test_fut = test(TestHarness { virtual_overseer, req_cfg });
+	let test_fut = test(TestHarness { virtual_overseer, req_cfg, req_v2_cfg });
 
 	futures::pin_mut!(test_fut);
 	futures::pin_mut!(subsystem);
@@ -299,6 +309,7 @@ async fn setup_system(virtual_overseer: &mut VirtualOverseer, test_state: &TestS
 /// Result of [`distribute_collation`]
 struct DistributeCollation {
 	candidate: CandidateReceipt,
+	parent_head_data_hash: Hash,
 	pov_block: PoV,
 }
 
@@ -322,9 +333,16 @@ async fn distribute_collation(
 	}
 	.build();
 
+	let parent_head_data_hash = Hash::repeat_byte(0xAA);
+
 	overseer_send(
 		virtual_overseer,
-		CollatorProtocolMessage::DistributeCollation(candidate.clone(), pov_block.clone(), None),
+		CollatorProtocolMessage::DistributeCollation(
+			candidate.clone(),
+			parent_head_data_hash,
+			pov_block.clone(),
+			None,
+		),
 	)
 	.await;
 
@@ -389,7 +407,7 @@ async fn distribute_collation(
 		);
 	}
 
-	DistributeCollation { candidate, pov_block }
+	DistributeCollation { candidate, parent_head_data_hash, pov_block }
 }
 
 /// Connect a peer
@@ -397,13 +415,23 @@ async fn connect_peer(
 	virtual_overseer: &mut VirtualOverseer,
 	peer: PeerId,
 	authority_id: Option<AuthorityDiscoveryId>,
+) {
+	connect_peer_on_version(virtual_overseer, peer, CollationVersion::V1, authority_id).await
+}
+
+/// Connect a peer on the given version of the collation protocol.
+async fn connect_peer_on_version(
+	virtual_overseer: &mut VirtualOverseer,
+	peer: PeerId,
+	version: CollationVersion,
+	authority_id: Option<AuthorityDiscoveryId>,
 ) {
 	overseer_send(
 		virtual_overseer,
 		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerConnected(
 			peer.clone(),
 			polkadot_node_network_protocol::ObservedRole::Authority,
-			CollationVersion::V1.into(),
+			version.into(),
 			authority_id.map(|v| HashSet::from([v])),
 		)),
 	)
@@ -514,10 +542,11 @@ fn advertise_and_send_collation() {
 	test_harness(local_peer_id, collator_pair, |test_harness| async move {
 		let mut virtual_overseer = test_harness.virtual_overseer;
 		let mut req_cfg = test_harness.req_cfg;
+		let req_v2_cfg = test_harness.req_v2_cfg;
 
 		setup_system(&mut virtual_overseer, &test_state).await;
 
-		let DistributeCollation { candidate, pov_block } =
+		let DistributeCollation { candidate, pov_block, .. } =
 			distribute_collation(&mut virtual_overseer, &test_state, true).await;
 
 		for (val, peer) in test_state
@@ -648,7 +677,7 @@ fn advertise_and_send_collation() {
 		.await;
 
 		expect_advertise_collation_msg(&mut virtual_overseer, &peer, test_state.relay_parent).await;
-		TestHarness { virtual_overseer, req_cfg }
+		TestHarness { virtual_overseer, req_cfg, req_v2_cfg }
 	});
 }
 
@@ -736,6 +765,164 @@ fn collations_are_only_advertised_to_validators_with_correct_view() {
 	})
 }
 
+#[test]
+fn v2_peers_are_advertised_candidate_and_parent_head() {
+	let test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+	let collator_pair = test_state.collator_pair.clone();
+
+	test_harness(local_peer_id, collator_pair, |mut test_harness| async move {
+		let virtual_overseer = &mut test_harness.virtual_overseer;
+
+		let peer = test_state.current_group_validator_peer_ids()[0].clone();
+		let validator_id = test_state.current_group_validator_authority_ids()[0].clone();
+
+		setup_system(virtual_overseer, &test_state).await;
+
+		connect_peer_on_version(
+			virtual_overseer,
+			peer.clone(),
+			CollationVersion::V2,
+			Some(validator_id),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::SendCollationMessage(
+					to,
+					Versioned::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+						protocol_v2::CollatorProtocolMessage::Declare(collator_id, para_id, _),
+					)),
+				)
+			) => {
+				assert_eq!(to, vec![peer]);
+				assert_eq!(collator_id, test_state.collator_pair.public());
+				assert_eq!(para_id, test_state.para_id);
+			}
+		);
+
+		let DistributeCollation { candidate, parent_head_data_hash, .. } =
+			distribute_collation(virtual_overseer, &test_state, true).await;
+
+		send_peer_view_change(virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::SendCollationMessage(
+					to,
+					Versioned::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+						protocol_v2::CollatorProtocolMessage::AdvertiseCollation {
+							relay_parent,
+							candidate_hash,
+							parent_head_data_hash: advertised_parent_head_data_hash,
+						},
+					)),
+				)
+			) => {
+				assert_eq!(to, vec![peer]);
+				assert_eq!(relay_parent, test_state.relay_parent);
+				assert_eq!(candidate_hash, candidate.hash());
+				assert_eq!(advertised_parent_head_data_hash, parent_head_data_hash);
+			}
+		);
+		test_harness
+	})
+}
+
+#[test]
+fn v2_collation_requests_are_served_by_candidate_hash() {
+	let test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+	let collator_pair = test_state.collator_pair.clone();
+
+	test_harness(local_peer_id, collator_pair, |mut test_harness| async move {
+		let virtual_overseer = &mut test_harness.virtual_overseer;
+		let req_v2_cfg = &mut test_harness.req_v2_cfg;
+
+		let peer = test_state.current_group_validator_peer_ids()[0].clone();
+		let validator_id = test_state.current_group_validator_authority_ids()[0].clone();
+
+		setup_system(virtual_overseer, &test_state).await;
+
+		let DistributeCollation { candidate, pov_block, .. } =
+			distribute_collation(virtual_overseer, &test_state, true).await;
+
+		connect_peer_on_version(
+			virtual_overseer,
+			peer.clone(),
+			CollationVersion::V2,
+			Some(validator_id),
+		)
+		.await;
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendCollationMessage(..))
+		);
+
+		send_peer_view_change(virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendCollationMessage(..))
+		);
+
+		// A request for a candidate we don't have is not answered.
+		let (pending_response, rx) = oneshot::channel();
+		req_v2_cfg
+			.inbound_queue
+			.as_mut()
+			.unwrap()
+			.send(RawIncomingRequest {
+				peer,
+				payload: request_v2::CollationFetchingRequest {
+					relay_parent: test_state.relay_parent,
+					para_id: test_state.para_id,
+					candidate_hash: CandidateHash(Hash::repeat_byte(0xBB)),
+				}
+				.encode(),
+				pending_response,
+			})
+			.await
+			.unwrap();
+		assert_matches!(rx.await, Err(_));
+
+		let (pending_response, rx) = oneshot::channel();
+		req_v2_cfg
+			.inbound_queue
+			.as_mut()
+			.unwrap()
+			.send(RawIncomingRequest {
+				peer,
+				payload: request_v2::CollationFetchingRequest {
+					relay_parent: test_state.relay_parent,
+					para_id: test_state.para_id,
+					candidate_hash: candidate.hash(),
+				}
+				.encode(),
+				pending_response,
+			})
+			.await
+			.unwrap();
+
+		assert_matches!(
+			rx.await,
+			Ok(full_response) => {
+				let CollationFetchingResponse::Collation(receipt, pov): CollationFetchingResponse
+					= CollationFetchingResponse::decode(
+						&mut full_response.result
+						.expect("We should have a proper answer").as_ref()
+				)
+				.expect("Decoding should work");
+				assert_eq!(receipt, candidate);
+				assert_eq!(pov, pov_block);
+			}
+		);
+		test_harness
+	})
+}
+
 #[test]
 fn collate_on_two_different_relay_chain_blocks() {
 	let mut test_state = TestState::default();
@@ -883,7 +1070,7 @@ where
 
 		setup_system(virtual_overseer, &test_state).await;
 
-		let DistributeCollation { candidate, pov_block } =
+		let DistributeCollation { candidate, pov_block, .. } =
 			distribute_collation(virtual_overseer, &test_state, true).await;
 
 		for (val, peer) in test_state
@@ -1000,6 +1187,7 @@ fn connect_to_buffered_groups() {
 	test_harness(local_peer_id, collator_pair, |test_harness| async move {
 		let mut virtual_overseer = test_harness.virtual_overseer;
 		let mut req_cfg = test_harness.req_cfg;
+		let req_v2_cfg = test_harness.req_v2_cfg;
 
 		setup_system(&mut virtual_overseer, &test_state).await;
 
@@ -1088,6 +1276,6 @@ fn connect_to_buffered_groups() {
 			}
 		);
 
-		TestHarness { virtual_overseer, req_cfg }
+		TestHarness { virtual_overseer, req_cfg, req_v2_cfg }
 	});
 }
@@ -21,20 +21,26 @@
 #![deny(unused_crate_dependencies)]
 #![recursion_limit = "256"]
 
-use std::time::{Duration, Instant};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use futures::{
 	stream::{FusedStream, StreamExt},
 	FutureExt, TryFutureExt,
 };
 
+use sp_core::crypto::Ss58Codec;
 use sp_keystore::SyncCryptoStorePtr;
 
 use polkadot_node_network_protocol::{
-	request_response::{v1 as request_v1, IncomingRequestReceiver},
+	request_response::{v1 as request_v1, v2 as request_v2, IncomingRequestReceiver},
 	PeerId, UnifiedReputationChange as Rep,
 };
-use polkadot_primitives::CollatorPair;
+use polkadot_node_subsystem_util::database::Database;
+use polkadot_primitives::{CollatorId, CollatorPair, Id as ParaId};
 
 use polkadot_node_subsystem::{
 	errors::SubsystemError, messages::NetworkBridgeTxMessage, overseer, SpawnedSubsystem,
@@ -65,6 +71,71 @@ impl Default for CollatorEvictionPolicy {
 	}
 }
 
+/// Per-para lists of collators a validator accepts or refuses collations from.
+#[derive(Debug, Clone, Default)]
+pub struct CollatorFilter {
+	/// The collators accepted for a para. Paras without an entry accept all collators which
+	/// are not denied.
+	pub allowed: HashMap<ParaId, HashSet<CollatorId>>,
+	/// The collators refused for a para.
+	pub denied: HashMap<ParaId, HashSet<CollatorId>>,
+}
+
+impl CollatorFilter {
+	/// Build a filter from `<para id>:<collator id>` entries, with the collator id given in
+	/// SS58 format.
+	pub fn from_entries(
+		allowed: &[String],
+		denied: &[String],
+	) -> std::result::Result<Self, String> {
+		fn collect(
+			entries: &[String],
+		) -> std::result::Result<HashMap<ParaId, HashSet<CollatorId>>, String> {
+			let mut collators = HashMap::<ParaId, HashSet<CollatorId>>::new();
+			for entry in entries {
+				let (para_id, collator_id) = CollatorFilter::parse_entry(entry)?;
+				collators.entry(para_id).or_default().insert(collator_id);
+			}
+			Ok(collators)
+		}
+
+		Ok(Self { allowed: collect(allowed)?, denied: collect(denied)? })
+	}
+
+	/// Parse a single `<para id>:<collator id>` entry.
+	pub fn parse_entry(entry: &str) -> std::result::Result<(ParaId, CollatorId), String> {
+		let (para_id, collator_id) = entry
+			.split_once(':')
+			.ok_or_else(|| format!("Expected `<para id>:<collator id>`, got `{}`", entry))?;
+		let para_id = para_id
+			.trim()
+			.parse::<u32>()
+			.map_err(|e| format!("Invalid para id `{}`: {}", para_id, e))?;
+		let collator_id = CollatorId::from_ss58check(collator_id.trim())
+			.map_err(|e| format!("Invalid collator id `{}`: {:?}", collator_id, e))?;
+
+		Ok((ParaId::from(para_id), collator_id))
+	}
+
+	/// Whether collations of the given collator are accepted for the given para.
+	pub fn is_allowed(&self, para_id: &ParaId, collator_id: &CollatorId) -> bool {
+		if self.denied.get(para_id).map_or(false, |denied| denied.contains(collator_id)) {
+			return false
+		}
+
+		self.allowed.get(para_id).map_or(true, |allowed| allowed.contains(collator_id))
+	}
+}
+
+/// Configuration of the validator side of the collator protocol.
+#[derive(Debug, Clone)]
+pub struct ValidatorSideConfig {
+	/// The column of the database collator reputations are persisted in.
+	pub col_reputation_data: u32,
+	/// The collators accepted or refused per para.
+	pub collator_filter: CollatorFilter,
+}
+
 /// What side of the collator protocol is being engaged
 pub enum ProtocolSide {
 	/// Validators operate on the relay chain.
@@ -73,6 +144,10 @@ pub enum ProtocolSide {
 		keystore: SyncCryptoStorePtr,
 		/// An eviction policy for inactive peers or validators.
 		eviction_policy: CollatorEvictionPolicy,
+		/// The database collator reputations are persisted in.
+		db: Arc<dyn Database>,
+		/// Configuration of the validator side.
+		config: ValidatorSideConfig,
 		/// Prometheus metrics for validators.
 		metrics: validator_side::Metrics,
 	},
@@ -81,6 +156,7 @@ pub enum ProtocolSide {
 		PeerId,
 		CollatorPair,
 		IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+		IncomingRequestReceiver<request_v2::CollationFetchingRequest>,
 		collator_side::Metrics,
 	),
 }
@@ -102,10 +178,24 @@ impl CollatorProtocolSubsystem {
 
 	async fn run<Context>(self, ctx: Context) -> std::result::Result<(), error::FatalError> {
 		match self.protocol_side {
-			ProtocolSide::Validator { keystore, eviction_policy, metrics } =>
-				validator_side::run(ctx, keystore, eviction_policy, metrics).await,
-			ProtocolSide::Collator(local_peer_id, collator_pair, req_receiver, metrics) =>
-				collator_side::run(ctx, local_peer_id, collator_pair, req_receiver, metrics).await,
+			ProtocolSide::Validator { keystore, eviction_policy, db, config, metrics } =>
+				validator_side::run(ctx, keystore, eviction_policy, db, config, metrics).await,
+			ProtocolSide::Collator(
+				local_peer_id,
+				collator_pair,
+				req_receiver_v1,
+				req_receiver_v2,
+				metrics,
+			) =>
+				collator_side::run(
+					ctx,
+					local_peer_id,
+					collator_pair,
+					req_receiver_v1,
+					req_receiver_v2,
+					metrics,
+				)
+				.await,
 		}
 	}
 }
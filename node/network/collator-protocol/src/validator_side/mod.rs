@@ -34,14 +34,15 @@ use sp_keystore::SyncCryptoStorePtr;
 
 use polkadot_node_network_protocol::{
 	self as net_protocol,
-	peer_set::PeerSet,
+	peer_set::{CollationVersion, PeerSet, ProtocolVersion},
 	request_response as req_res,
 	request_response::{
 		outgoing::{Recipient, RequestError},
 		v1::{CollationFetchingRequest, CollationFetchingResponse},
-		OutgoingRequest, Requests,
+		v2 as request_v2, OutgoingRequest, Requests,
 	},
-	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	v1 as protocol_v1, v2 as protocol_v2, OurView, PeerId, UnifiedReputationChange as Rep,
+	Versioned, View,
 };
 use polkadot_node_primitives::{PoV, SignedFullStatement};
 use polkadot_node_subsystem::{
@@ -52,13 +53,25 @@ use polkadot_node_subsystem::{
 	},
 	overseer, FromOrchestra, OverseerSignal, PerLeafSpan, SubsystemSender,
 };
-use polkadot_node_subsystem_util::metrics::{self, prometheus};
-use polkadot_primitives::{CandidateReceipt, CollatorId, Hash, Id as ParaId};
+use polkadot_node_subsystem_util::{
+	database::Database,
+	metrics::{self, prometheus},
+};
+use polkadot_primitives::{
+	CandidateHash, CandidateReceipt, CollatorId, Hash, Id as ParaId, OccupiedCoreAssumption,
+};
 
-use crate::error::Result;
+use crate::{error::Result, CollatorFilter, ValidatorSideConfig};
 
 use super::{modify_reputation, tick_stream, LOG_TARGET};
 
+mod reputation;
+
+use reputation::{
+	CollatorReputations, SCORE_CORRUPTED_MESSAGE, SCORE_NETWORK_ERROR, SCORE_REPORTED,
+	SCORE_SECONDED, SCORE_WRONG_CANDIDATE, SCORE_WRONG_PARA,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -70,6 +83,10 @@ const COST_NETWORK_ERROR: Rep = Rep::CostMinor("Some network error");
 const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Invalid network message signature");
 const COST_REPORT_BAD: Rep = Rep::Malicious("A collator was reported by another subsystem");
 const COST_WRONG_PARA: Rep = Rep::Malicious("A collator provided a collation for the wrong para");
+const COST_WRONG_CANDIDATE: Rep =
+	Rep::Malicious("A collator provided a collation other than the advertised one");
+const COST_STALE_COLLATION: Rep =
+	Rep::CostMinor("An advertised collation builds on a stale para head");
 const COST_UNNEEDED_COLLATOR: Rep = Rep::CostMinor("An unneeded collator connected");
 const BENEFIT_NOTIFY_GOOD: Rep =
 	Rep::BenefitMinor("A collator was noted good by another subsystem");
@@ -98,6 +115,13 @@ const MAX_UNSHARED_DOWNLOAD_TIME: Duration = Duration::from_millis(100);
 #[cfg(test)]
 const ACTIVITY_POLL: Duration = Duration::from_millis(10);
 
+/// How long to hold back fetching a collation from a collator which was served last for its
+/// para, if other collators of the para are connected which might still advertise.
+///
+/// This gives the other collators of a para a chance to have their collations fetched, even if
+/// they are slower to advertise.
+const FAIR_FETCH_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
 // How often to poll collation responses.
 // This is a hack that should be removed in a refactoring.
 // See https://github.com/paritytech/polkadot/issues/4182
@@ -143,6 +167,14 @@ impl Metrics {
 	) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.collation_request_duration.start_timer())
 	}
+
+	/// Note the outcome of collation advertisements: `fetched`, `discarded`, `rejected`,
+	/// `duplicate` or `stale`.
+	fn on_advertisements(&self, outcome: &'static str, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.advertisements.with_label_values(&[outcome]).inc_by(count as u64);
+		}
+	}
 }
 
 #[derive(Clone)]
@@ -152,6 +184,7 @@ struct MetricsInner {
 	handle_collation_request_result: prometheus::Histogram,
 	collator_peer_count: prometheus::Gauge<prometheus::U64>,
 	collation_request_duration: prometheus::Histogram,
+	advertisements: prometheus::CounterVec<prometheus::U64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -203,6 +236,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			advertisements: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_protocol_validator_advertisements_total",
+						"Number of collation advertisements by outcome.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -214,6 +257,8 @@ struct PerRequest {
 	from_collator: Fuse<BoxFuture<'static, req_res::OutgoingResult<CollationFetchingResponse>>>,
 	/// Sender to forward to initial requester.
 	to_requester: oneshot::Sender<(CandidateReceipt, PoV)>,
+	/// The hash of the candidate the collator advertised, if it did.
+	advertised_candidate: Option<CandidateHash>,
 	/// A jaeger span corresponding to the lifetime of the request.
 	span: Option<jaeger::Span>,
 	/// A metric histogram for the lifetime of the request
@@ -248,11 +293,13 @@ enum AdvertisementError {
 struct PeerData {
 	view: View,
 	state: PeerState,
+	/// The version of the collation protocol the peer is connected with.
+	version: ProtocolVersion,
 }
 
 impl PeerData {
-	fn new(view: View) -> Self {
-		PeerData { view, state: PeerState::Connected(Instant::now()) }
+	fn new(view: View, version: ProtocolVersion) -> Self {
+		PeerData { view, state: PeerState::Connected(Instant::now()), version }
 	}
 
 	/// Update the view, clearing all advertisements that are no longer in the
@@ -349,7 +396,7 @@ impl PeerData {
 
 impl Default for PeerData {
 	fn default() -> Self {
-		PeerData::new(Default::default())
+		PeerData::new(Default::default(), CollationVersion::V1.into())
 	}
 }
 
@@ -478,17 +525,103 @@ struct PendingCollation {
 	relay_parent: Hash,
 	para_id: ParaId,
 	peer_id: PeerId,
+	/// The hash of the advertised candidate, only known for advertisements of version 2.
+	advertised_candidate: Option<CandidateHash>,
 	commitments_hash: Option<Hash>,
 }
 
 impl PendingCollation {
-	fn new(relay_parent: Hash, para_id: &ParaId, peer_id: &PeerId) -> Self {
-		Self { relay_parent, para_id: *para_id, peer_id: *peer_id, commitments_hash: None }
+	fn new(
+		relay_parent: Hash,
+		para_id: &ParaId,
+		peer_id: &PeerId,
+		advertised_candidate: Option<CandidateHash>,
+	) -> Self {
+		Self {
+			relay_parent,
+			para_id: *para_id,
+			peer_id: *peer_id,
+			advertised_candidate,
+			commitments_hash: None,
+		}
 	}
 }
 
 type CollationEvent = (CollatorId, PendingCollation);
 
+/// Keeps track of which collators of a para we fetched from last, so fetches can be rotated
+/// among all collators advertising for the para.
+#[derive(Default)]
+struct FetchRotation {
+	/// Incremented on every fetch.
+	counter: u64,
+	/// The value of `counter` at the last fetch from a collator, per para.
+	last_served: HashMap<ParaId, HashMap<CollatorId, u64>>,
+}
+
+impl FetchRotation {
+	/// Note that we started fetching a collation of the para from the collator.
+	fn note_fetch(&mut self, para_id: ParaId, collator_id: CollatorId) {
+		self.counter += 1;
+		self.last_served.entry(para_id).or_default().insert(collator_id, self.counter);
+	}
+
+	/// When we last fetched a collation of the para from the collator, if ever.
+	///
+	/// Collators which were served longer ago compare lower.
+	fn last_served(&self, para_id: &ParaId, collator_id: &CollatorId) -> Option<u64> {
+		self.last_served
+			.get(para_id)
+			.and_then(|served| served.get(collator_id))
+			.copied()
+	}
+
+	/// Forget about paras we are no longer assigned to.
+	fn retain_paras(&mut self, mut keep: impl FnMut(&ParaId) -> bool) {
+		self.last_served.retain(|para_id, _| keep(para_id));
+	}
+}
+
+/// How much a collator is trusted when deciding which collation to fetch next.
+///
+/// Collators in earlier tiers are fetched from first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FetchTier {
+	/// The collator has a positive reputation.
+	Reputable,
+	/// We have fetched from the collator or it has a reputation which is neither positive nor low.
+	Known,
+	/// We know nothing about the collator, e.g. it just came up with a fresh `CollatorId`.
+	Unknown,
+	/// The collator has a low reputation.
+	LowReputation,
+}
+
+/// The rank of a collator of the para when picking the next collation to fetch. Lower ranks go
+/// first.
+///
+/// Collators are ranked by their [`FetchTier`] and within a tier, the collator we fetched from
+/// least recently for the para goes first.
+fn fetch_rank(
+	para_id: &ParaId,
+	collator_id: &CollatorId,
+	rotation: &FetchRotation,
+	reputations: &CollatorReputations,
+) -> (FetchTier, Option<u64>) {
+	let last_served = rotation.last_served(para_id, collator_id);
+	let tier = if reputations.is_low(collator_id) {
+		FetchTier::LowReputation
+	} else if reputations.score(collator_id) > 0 {
+		FetchTier::Reputable
+	} else if last_served.is_some() || reputations.is_known(collator_id) {
+		FetchTier::Known
+	} else {
+		FetchTier::Unknown
+	};
+
+	(tier, last_served)
+}
+
 type PendingCollationFetch =
 	(CollationEvent, std::result::Result<(CandidateReceipt, PoV), oneshot::Canceled>);
 
@@ -531,8 +664,13 @@ struct CollationsPerRelayParent {
 	/// This is the currently last started fetch, which did not exceed `MAX_UNSHARED_DOWNLOAD_TIME`
 	/// yet.
 	waiting_collation: Option<CollatorId>,
-	/// Collation that were advertised to us, but we did not yet fetch.
+	/// Collation that were advertised to us, but we did not yet fetch, in order of arrival.
 	unfetched_collations: Vec<(PendingCollation, CollatorId)>,
+	/// Hashes of the candidates which were advertised to us or fetched.
+	///
+	/// Advertisements of these candidates by other collators are duplicates, which are not
+	/// fetched again.
+	known_candidates: HashSet<CandidateHash>,
 }
 
 impl CollationsPerRelayParent {
@@ -542,9 +680,15 @@ impl CollationsPerRelayParent {
 	///
 	/// Returns `Some(_)` if there is any collation to fetch, the `status` is not `Seconded` and
 	/// the passed in `finished_one` is the currently `waiting_collation`.
+	///
+	/// Collators are picked by their [`fetch_rank`], so collators with a positive reputation go
+	/// first and collators we know nothing about only go before collators with a low reputation.
+	/// Ties are broken by preferring higher reputations and earlier advertisements.
 	pub fn get_next_collation_to_fetch(
 		&mut self,
 		finished_one: Option<&CollatorId>,
+		rotation: &FetchRotation,
+		reputations: &CollatorReputations,
 	) -> Option<(PendingCollation, CollatorId)> {
 		// If finished one does not match waiting_collation, then we already dequeued another fetch
 		// to replace it.
@@ -563,7 +707,24 @@ impl CollationsPerRelayParent {
 			// We don't need to fetch any other collation when we already have seconded one.
 			CollationStatus::Seconded => None,
 			CollationStatus::Waiting => {
-				let next = self.unfetched_collations.pop();
+				let next_index = self
+					.unfetched_collations
+					.iter()
+					.enumerate()
+					.min_by_key(|(index, (pending_collation, collator_id))| {
+						(
+							fetch_rank(
+								&pending_collation.para_id,
+								collator_id,
+								rotation,
+								reputations,
+							),
+							std::cmp::Reverse(reputations.score(collator_id)),
+							*index,
+						)
+					})
+					.map(|(index, _)| index);
+				let next = next_index.map(|index| self.unfetched_collations.remove(index));
 				self.waiting_collation = next.as_ref().map(|(_, collator_id)| collator_id.clone());
 				next
 			},
@@ -574,7 +735,6 @@ impl CollationsPerRelayParent {
 }
 
 /// All state relevant for the validator side of the protocol lives here.
-#[derive(Default)]
 struct State {
 	/// Our own view.
 	view: OurView,
@@ -613,6 +773,71 @@ struct State {
 
 	/// Keep track of all pending candidate collations
 	pending_candidates: HashMap<Hash, CollationEvent>,
+
+	/// When a timer in this `FuturesUnordered` triggers, the fetch of a collation for the
+	/// relay parent, which we held back to give other collators of the para a chance, is
+	/// started.
+	held_back_fetches: FuturesUnordered<BoxFuture<'static, Hash>>,
+
+	/// Which collators we fetched from last, per para.
+	fetch_rotation: FetchRotation,
+
+	/// The persisted reputations of collators.
+	reputations: CollatorReputations,
+
+	/// The collators we accept or refuse per para.
+	collator_filter: CollatorFilter,
+
+	/// Hashes of the para heads collations may build upon, per relay parent and para.
+	///
+	/// These are the head of the para included at the relay parent and the head of the candidate
+	/// pending availability, if any. Only populated once an advertisement of version 2 is
+	/// received.
+	parent_heads: HashMap<(Hash, ParaId), HashSet<Hash>>,
+}
+
+impl State {
+	fn new(
+		reputations: CollatorReputations,
+		collator_filter: CollatorFilter,
+		metrics: Metrics,
+	) -> Self {
+		Self {
+			view: Default::default(),
+			active_paras: Default::default(),
+			peer_data: Default::default(),
+			requested_collations: Default::default(),
+			metrics,
+			span_per_relay_parent: Default::default(),
+			collation_fetches: Default::default(),
+			collation_fetch_timeouts: Default::default(),
+			collations_per_relay_parent: Default::default(),
+			pending_candidates: Default::default(),
+			held_back_fetches: Default::default(),
+			fetch_rotation: Default::default(),
+			reputations,
+			collator_filter,
+			parent_heads: Default::default(),
+		}
+	}
+
+	/// Whether fetching a collation of the para from the collator should be held back, because
+	/// we fetched from it before and another connected collator of the para ranks before it.
+	fn should_hold_back_fetch(&self, para_id: &ParaId, collator_id: &CollatorId) -> bool {
+		let rank = fetch_rank(para_id, collator_id, &self.fetch_rotation, &self.reputations);
+		if rank.1.is_none() {
+			return false
+		}
+
+		self.peer_data.values().any(|peer_data| {
+			peer_data.collating_para().as_ref() == Some(para_id) &&
+				peer_data.collator_id().map_or(false, |other| {
+					other != collator_id &&
+						fetch_rank(para_id, other, &self.fetch_rotation, &self.reputations) <
+							rank
+				})
+		})
+	}
 }
 
 // O(n) search for collator ID by iterating through the peers map. This should be fast enough
@@ -641,17 +866,28 @@ async fn fetch_collation(
 ) {
 	let (tx, rx) = oneshot::channel();
 
-	let PendingCollation { relay_parent, para_id, peer_id, .. } = pc;
+	let PendingCollation { relay_parent, para_id, peer_id, advertised_candidate, .. } = pc;
 
 	let timeout = |collator_id, relay_parent| async move {
 		Delay::new(MAX_UNSHARED_DOWNLOAD_TIME).await;
 		(collator_id, relay_parent)
 	};
 	state.collation_fetch_timeouts.push(timeout(id.clone(), relay_parent).boxed());
+	state.fetch_rotation.note_fetch(para_id, id.clone());
+	state.metrics.on_advertisements("fetched", 1);
 
 	if let Some(peer_data) = state.peer_data.get(&peer_id) {
 		if peer_data.has_advertised(&relay_parent) {
-			request_collation(sender, state, relay_parent, para_id, peer_id, tx).await;
+			request_collation(
+				sender,
+				state,
+				relay_parent,
+				para_id,
+				peer_id,
+				advertised_candidate,
+				tx,
+			)
+			.await;
 		} else {
 			gum::debug!(
 				target: LOG_TARGET,
@@ -678,8 +914,11 @@ async fn fetch_collation(
 async fn report_collator(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	reputations: &mut CollatorReputations,
 	id: CollatorId,
 ) {
+	reputations.modify(&id, SCORE_REPORTED);
+
 	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
 		modify_reputation(sender, peer_id, COST_REPORT_BAD).await;
 	}
@@ -689,8 +928,11 @@ async fn report_collator(
 async fn note_good_collation(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	reputations: &mut CollatorReputations,
 	id: CollatorId,
 ) {
+	reputations.modify(&id, SCORE_SECONDED);
+
 	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
 		modify_reputation(sender, peer_id, BENEFIT_NOTIFY_GOOD).await;
 	}
@@ -700,16 +942,21 @@ async fn note_good_collation(
 async fn notify_collation_seconded(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_id: PeerId,
+	version: ProtocolVersion,
 	relay_parent: Hash,
 	statement: SignedFullStatement,
 ) {
-	let wire_message =
-		protocol_v1::CollatorProtocolMessage::CollationSeconded(relay_parent, statement.into());
-	sender
-		.send_message(NetworkBridgeTxMessage::SendCollationMessage(
-			vec![peer_id],
-			Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(wire_message)),
+	let wire_message = if version == CollationVersion::V2.into() {
+		Versioned::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+			protocol_v2::CollatorProtocolMessage::CollationSeconded(relay_parent, statement.into()),
+		))
+	} else {
+		Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(
+			protocol_v1::CollatorProtocolMessage::CollationSeconded(relay_parent, statement.into()),
 		))
+	};
+	sender
+		.send_message(NetworkBridgeTxMessage::SendCollationMessage(vec![peer_id], wire_message))
 		.await;
 
 	modify_reputation(sender, peer_id, BENEFIT_NOTIFY_GOOD).await;
@@ -741,6 +988,7 @@ async fn request_collation(
 	relay_parent: Hash,
 	para_id: ParaId,
 	peer_id: PeerId,
+	advertised_candidate: Option<CandidateHash>,
 	result: oneshot::Sender<(CandidateReceipt, PoV)>,
 ) {
 	if !state.view.contains(&relay_parent) {
@@ -753,7 +1001,7 @@ async fn request_collation(
 		);
 		return
 	}
-	let pending_collation = PendingCollation::new(relay_parent, &para_id, &peer_id);
+	let pending_collation = PendingCollation::new(relay_parent, &para_id, &peer_id, None);
 	if state.requested_collations.contains_key(&pending_collation) {
 		gum::warn!(
 			target: LOG_TARGET,
@@ -765,15 +1013,30 @@ async fn request_collation(
 		return
 	}
 
-	let (full_request, response_recv) = OutgoingRequest::new(
-		Recipient::Peer(peer_id),
-		CollationFetchingRequest { relay_parent, para_id },
-	);
-	let requests = Requests::CollationFetchingV1(full_request);
+	// Collations advertised with their candidate hash are requested by it, falling back to the
+	// request by relay parent for collators not supporting it.
+	let (requests, response_recv) = match advertised_candidate {
+		Some(candidate_hash) => {
+			let (full_request, response_recv) = OutgoingRequest::new_with_fallback(
+				Recipient::Peer(peer_id),
+				request_v2::CollationFetchingRequest { relay_parent, para_id, candidate_hash },
+				CollationFetchingRequest { relay_parent, para_id },
+			);
+			(Requests::CollationFetchingV2(full_request), response_recv.boxed())
+		},
+		None => {
+			let (full_request, response_recv) = OutgoingRequest::new(
+				Recipient::Peer(peer_id),
+				CollationFetchingRequest { relay_parent, para_id },
+			);
+			(Requests::CollationFetchingV1(full_request), response_recv.boxed())
+		},
+	};
 
 	let per_request = PerRequest {
-		from_collator: response_recv.boxed().fuse(),
+		from_collator: response_recv.fuse(),
 		to_requester: result,
+		advertised_candidate,
 		span: state
 			.span_per_relay_parent
 			.get(&relay_parent)
@@ -783,7 +1046,7 @@ async fn request_collation(
 
 	state
 		.requested_collations
-		.insert(PendingCollation::new(relay_parent, &para_id, &peer_id), per_request);
+		.insert(PendingCollation::new(relay_parent, &para_id, &peer_id, None), per_request);
 
 	gum::debug!(
 		target: LOG_TARGET,
@@ -807,12 +1070,14 @@ async fn process_incoming_peer_message<Context>(
 	ctx: &mut Context,
 	state: &mut State,
 	origin: PeerId,
-	msg: protocol_v1::CollatorProtocolMessage,
+	msg: net_protocol::CollatorProtocolMessage,
 ) {
-	use protocol_v1::CollatorProtocolMessage::*;
+	use protocol_v1::CollatorProtocolMessage as V1;
+	use protocol_v2::CollatorProtocolMessage as V2;
 	use sp_runtime::traits::AppVerify;
 	match msg {
-		Declare(collator_id, para_id, signature) => {
+		Versioned::V1(V1::Declare(collator_id, para_id, signature)) |
+		Versioned::V2(V2::Declare(collator_id, para_id, signature)) => {
 			if collator_peer_id(&state.peer_data, &collator_id).is_some() {
 				modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
 				return
@@ -854,128 +1119,309 @@ async fn process_incoming_peer_message<Context>(
 				return
 			}
 
-			if state.active_paras.is_current(&para_id) {
+			if !state.active_paras.is_current(&para_id) {
 				gum::debug!(
 					target: LOG_TARGET,
 					peer_id = ?origin,
 					?collator_id,
 					?para_id,
-					"Declared as collator for current para",
+					"Declared as collator for unneeded para",
 				);
 
-				peer_data.set_collating(collator_id, para_id);
-			} else {
+				modify_reputation(ctx.sender(), origin, COST_UNNEEDED_COLLATOR).await;
+				gum::trace!(target: LOG_TARGET, "Disconnecting unneeded collator");
+				disconnect_peer(ctx.sender(), origin).await;
+			} else if !state.collator_filter.is_allowed(&para_id, &collator_id) {
 				gum::debug!(
 					target: LOG_TARGET,
 					peer_id = ?origin,
 					?collator_id,
 					?para_id,
-					"Declared as collator for unneeded para",
+					"Declared as collator which is not allowed for the para",
 				);
 
-				modify_reputation(ctx.sender(), origin, COST_UNNEEDED_COLLATOR).await;
-				gum::trace!(target: LOG_TARGET, "Disconnecting unneeded collator");
 				disconnect_peer(ctx.sender(), origin).await;
-			}
-		},
-		AdvertiseCollation(relay_parent) => {
-			let _span = state
-				.span_per_relay_parent
-				.get(&relay_parent)
-				.map(|s| s.child("advertise-collation"));
-			if !state.view.contains(&relay_parent) {
+			} else {
 				gum::debug!(
 					target: LOG_TARGET,
 					peer_id = ?origin,
-					?relay_parent,
-					"Advertise collation out of view",
+					?collator_id,
+					?para_id,
+					"Declared as collator for current para",
 				);
 
-				modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
-				return
+				peer_data.set_collating(collator_id, para_id);
 			}
+		},
+		Versioned::V1(V1::AdvertiseCollation(relay_parent)) =>
+			handle_advertisement(ctx, state, origin, relay_parent, None).await,
+		Versioned::V2(V2::AdvertiseCollation {
+			relay_parent,
+			candidate_hash,
+			parent_head_data_hash,
+		}) =>
+			handle_advertisement(
+				ctx,
+				state,
+				origin,
+				relay_parent,
+				Some((candidate_hash, parent_head_data_hash)),
+			)
+			.await,
+		Versioned::V1(V1::CollationSeconded(_, _)) | Versioned::V2(V2::CollationSeconded(_, _)) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				"Unexpected `CollationSeconded` message, decreasing reputation",
+			);
+		},
+	}
+}
 
-			let peer_data = match state.peer_data.get_mut(&origin) {
-				None => {
-					gum::debug!(
+/// An advertisement of a collation was received.
+///
+/// Advertisements of version 2 carry the candidate hash and the hash of the para head the
+/// candidate builds upon, which allows rejecting duplicate and stale collations before fetching
+/// them.
+#[overseer::contextbounds(CollatorProtocol, prefix = overseer)]
+async fn handle_advertisement<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	origin: PeerId,
+	relay_parent: Hash,
+	advertised_candidate: Option<(CandidateHash, Hash)>,
+) {
+	let _span = state
+		.span_per_relay_parent
+		.get(&relay_parent)
+		.map(|s| s.child("advertise-collation"));
+	if !state.view.contains(&relay_parent) {
+		gum::debug!(
+			target: LOG_TARGET,
+			peer_id = ?origin,
+			?relay_parent,
+			"Advertise collation out of view",
+		);
+
+		state.metrics.on_advertisements("rejected", 1);
+		modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+		return
+	}
+
+	let peer_data = match state.peer_data.get_mut(&origin) {
+		None => {
+			gum::debug!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				?relay_parent,
+				"Advertise collation message has been received from an unknown peer",
+			);
+			state.metrics.on_advertisements("rejected", 1);
+			modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+			return
+		},
+		Some(p) => p,
+	};
+
+	match peer_data.insert_advertisement(relay_parent, &state.view) {
+		Ok((id, para_id)) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				%para_id,
+				?relay_parent,
+				"Received advertise collation",
+			);
+
+			if let Some((candidate_hash, parent_head_data_hash)) = advertised_candidate {
+				let rejection = check_advertised_candidate(
+					ctx.sender(),
+					state,
+					relay_parent,
+					para_id,
+					candidate_hash,
+					parent_head_data_hash,
+				)
+				.await;
+
+				match rejection {
+					Some(AdvertisedCandidateRejection::Duplicate) => {
+						gum::debug!(
+							target: LOG_TARGET,
+							peer_id = ?origin,
+							%para_id,
+							?relay_parent,
+							?candidate_hash,
+							"Advertised candidate is known already, not fetching it again",
+						);
+						state.metrics.on_advertisements("duplicate", 1);
+						return
+					},
+					Some(AdvertisedCandidateRejection::Stale) => {
+						gum::debug!(
+							target: LOG_TARGET,
+							peer_id = ?origin,
+							%para_id,
+							?relay_parent,
+							?candidate_hash,
+							?parent_head_data_hash,
+							"Advertised candidate builds on a stale para head",
+						);
+						state.metrics.on_advertisements("stale", 1);
+						modify_reputation(ctx.sender(), origin, COST_STALE_COLLATION).await;
+						return
+					},
+					None => {},
+				}
+			}
+
+			let pending_collation = PendingCollation::new(
+				relay_parent,
+				&para_id,
+				&origin,
+				advertised_candidate.map(|(candidate_hash, _)| candidate_hash),
+			);
+			let hold_back = state.should_hold_back_fetch(&para_id, &id);
+
+			let collations = state.collations_per_relay_parent.entry(relay_parent).or_default();
+			if let Some((candidate_hash, _)) = advertised_candidate {
+				collations.known_candidates.insert(candidate_hash);
+			}
+
+			match collations.status {
+				CollationStatus::Fetching | CollationStatus::WaitingOnValidation => {
+					gum::trace!(
 						target: LOG_TARGET,
 						peer_id = ?origin,
+						%para_id,
 						?relay_parent,
-						"Advertise collation message has been received from an unknown peer",
+						"Added collation to the pending list"
 					);
-					modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
-					return
+					collations.unfetched_collations.push((pending_collation, id));
 				},
-				Some(p) => p,
-			};
-
-			match peer_data.insert_advertisement(relay_parent, &state.view) {
-				Ok((id, para_id)) => {
-					gum::debug!(
+				CollationStatus::Waiting if hold_back => {
+					gum::trace!(
 						target: LOG_TARGET,
 						peer_id = ?origin,
 						%para_id,
 						?relay_parent,
-						"Received advertise collation",
+						"Holding back fetch to give other collators of the para a chance"
 					);
+					collations.status = CollationStatus::Fetching;
+					collations.waiting_collation = None;
+					collations.unfetched_collations.push((pending_collation, id));
 
-					let pending_collation = PendingCollation::new(relay_parent, &para_id, &origin);
-
-					let collations =
-						state.collations_per_relay_parent.entry(relay_parent).or_default();
+					state.held_back_fetches.push(
+						Delay::new(FAIR_FETCH_GRACE_PERIOD).map(move |_| relay_parent).boxed(),
+					);
+				},
+				CollationStatus::Waiting => {
+					collations.status = CollationStatus::Fetching;
+					collations.waiting_collation = Some(id.clone());
 
-					match collations.status {
-						CollationStatus::Fetching | CollationStatus::WaitingOnValidation => {
-							gum::trace!(
-								target: LOG_TARGET,
-								peer_id = ?origin,
-								%para_id,
-								?relay_parent,
-								"Added collation to the pending list"
-							);
-							collations.unfetched_collations.push((pending_collation, id));
-						},
-						CollationStatus::Waiting => {
-							collations.status = CollationStatus::Fetching;
-							collations.waiting_collation = Some(id.clone());
-
-							fetch_collation(ctx.sender(), state, pending_collation.clone(), id)
-								.await;
-						},
-						CollationStatus::Seconded => {
-							gum::trace!(
-								target: LOG_TARGET,
-								peer_id = ?origin,
-								%para_id,
-								?relay_parent,
-								"Valid seconded collation"
-							);
-						},
-					}
+					fetch_collation(ctx.sender(), state, pending_collation.clone(), id).await;
 				},
-				Err(error) => {
-					gum::debug!(
+				CollationStatus::Seconded => {
+					gum::trace!(
 						target: LOG_TARGET,
 						peer_id = ?origin,
+						%para_id,
 						?relay_parent,
-						?error,
-						"Invalid advertisement",
+						"Valid seconded collation"
 					);
-
-					modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+					state.metrics.on_advertisements("discarded", 1);
 				},
 			}
 		},
-		CollationSeconded(_, _) => {
-			gum::warn!(
+		Err(error) => {
+			gum::debug!(
 				target: LOG_TARGET,
 				peer_id = ?origin,
-				"Unexpected `CollationSeconded` message, decreasing reputation",
+				?relay_parent,
+				?error,
+				"Invalid advertisement",
 			);
+
+			state.metrics.on_advertisements("rejected", 1);
+			modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
 		},
 	}
 }
 
+/// Why an advertised candidate is not fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AdvertisedCandidateRejection {
+	/// The candidate was advertised by another collator or fetched already.
+	Duplicate,
+	/// The candidate builds on a para head other than the one included at the relay parent or
+	/// the head of the candidate pending availability.
+	Stale,
+}
+
+/// Check whether an advertised candidate should be fetched.
+///
+/// The para heads a candidate may build upon are requested from the runtime once per relay
+/// parent and para. If they can not be determined, candidates are not rejected as stale.
+async fn check_advertised_candidate(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+	relay_parent: Hash,
+	para_id: ParaId,
+	candidate_hash: CandidateHash,
+	parent_head_data_hash: Hash,
+) -> Option<AdvertisedCandidateRejection> {
+	let is_known = state
+		.collations_per_relay_parent
+		.get(&relay_parent)
+		.map_or(false, |collations| collations.known_candidates.contains(&candidate_hash));
+	if is_known {
+		return Some(AdvertisedCandidateRejection::Duplicate)
+	}
+
+	let parent_heads = match state.parent_heads.entry((relay_parent, para_id)) {
+		Entry::Occupied(entry) => entry.into_mut(),
+		Entry::Vacant(entry) => {
+			let mut parent_heads = HashSet::new();
+			for assumption in [OccupiedCoreAssumption::TimedOut, OccupiedCoreAssumption::Included] {
+				let validation_data =
+					polkadot_node_subsystem_util::request_persisted_validation_data(
+						relay_parent,
+						para_id,
+						assumption,
+						sender,
+					)
+					.await
+					.await;
+
+				match validation_data {
+					Ok(Ok(Some(validation_data))) => {
+						parent_heads.insert(validation_data.parent_head.hash());
+					},
+					Ok(Ok(None)) => {},
+					Ok(Err(err)) => {
+						gum::debug!(
+							target: LOG_TARGET,
+							?relay_parent,
+							%para_id,
+							?err,
+							"Failed to query persisted validation data",
+						);
+						return None
+					},
+					Err(_) => return None,
+				}
+			}
+			entry.insert(parent_heads)
+		},
+	};
+
+	if parent_heads.is_empty() || parent_heads.contains(&parent_head_data_hash) {
+		None
+	} else {
+		Some(AdvertisedCandidateRejection::Stale)
+	}
+}
+
 /// A leaf has become inactive so we want to
 ///   - Cancel all ongoing collation requests that are on top of that leaf.
 ///   - Remove all stored collations relevant to that leaf.
@@ -984,7 +1430,13 @@ async fn remove_relay_parent(state: &mut State, relay_parent: Hash) -> Result<()
 
 	state.pending_candidates.retain(|k, _| k != &relay_parent);
 
-	state.collations_per_relay_parent.remove(&relay_parent);
+	state.parent_heads.retain(|(k, _), _| k != &relay_parent);
+
+	if let Some(collations) = state.collations_per_relay_parent.remove(&relay_parent) {
+		state
+			.metrics
+			.on_advertisements("discarded", collations.unfetched_collations.len());
+	}
 	Ok(())
 }
 
@@ -1020,6 +1472,9 @@ async fn handle_our_view_change<Context>(
 
 	state.active_paras.assign_incoming(ctx.sender(), keystore, added).await;
 	state.active_paras.remove_outgoing(removed);
+	state
+		.fetch_rotation
+		.retain_paras(|para_id| state.active_paras.is_current(para_id));
 
 	for (peer_id, peer_data) in state.peer_data.iter_mut() {
 		peer_data.prune_old_advertisements(&state.view);
@@ -1055,8 +1510,11 @@ async fn handle_network_msg<Context>(
 	use NetworkBridgeEvent::*;
 
 	match bridge_message {
-		PeerConnected(peer_id, _role, _version, _) => {
-			state.peer_data.entry(peer_id).or_default();
+		PeerConnected(peer_id, _role, version, _) => {
+			state
+				.peer_data
+				.entry(peer_id)
+				.or_insert_with(|| PeerData::new(View::default(), version));
 			state.metrics.note_collator_peer_count(state.peer_data.len());
 		},
 		PeerDisconnected(peer_id) => {
@@ -1072,7 +1530,7 @@ async fn handle_network_msg<Context>(
 		OurViewChange(view) => {
 			handle_our_view_change(ctx, state, keystore, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, msg) => {
 			process_incoming_peer_message(ctx, state, remote, msg).await;
 		},
 	}
@@ -1100,14 +1558,14 @@ async fn process_msg<Context>(
 				"CollateOn message is not expected on the validator side of the protocol",
 			);
 		},
-		DistributeCollation(_, _, _) => {
+		DistributeCollation(_, _, _, _) => {
 			gum::warn!(
 				target: LOG_TARGET,
 				"DistributeCollation message is not expected on the validator side of the protocol",
 			);
 		},
 		ReportCollator(id) => {
-			report_collator(ctx.sender(), &state.peer_data, id).await;
+			report_collator(ctx.sender(), &state.peer_data, &mut state.reputations, id).await;
 		},
 		NetworkBridgeUpdate(event) => {
 			if let Err(e) = handle_network_msg(ctx, state, keystore, event).await {
@@ -1122,8 +1580,18 @@ async fn process_msg<Context>(
 			if let Some(collation_event) = state.pending_candidates.remove(&parent) {
 				let (collator_id, pending_collation) = collation_event;
 				let PendingCollation { relay_parent, peer_id, .. } = pending_collation;
-				note_good_collation(ctx.sender(), &state.peer_data, collator_id).await;
-				notify_collation_seconded(ctx.sender(), peer_id, relay_parent, stmt).await;
+				note_good_collation(
+					ctx.sender(),
+					&state.peer_data,
+					&mut state.reputations,
+					collator_id,
+				)
+				.await;
+				let version = state
+					.peer_data
+					.get(&peer_id)
+					.map_or(CollationVersion::V1.into(), |peer_data| peer_data.version);
+				notify_collation_seconded(ctx.sender(), peer_id, version, relay_parent, stmt).await;
 
 				if let Some(collations) = state.collations_per_relay_parent.get_mut(&parent) {
 					collations.status = CollationStatus::Seconded;
@@ -1154,9 +1622,10 @@ async fn process_msg<Context>(
 				Entry::Vacant(_) => return,
 			};
 
-			report_collator(ctx.sender(), &state.peer_data, id.clone()).await;
+			report_collator(ctx.sender(), &state.peer_data, &mut state.reputations, id.clone())
+				.await;
 
-			dequeue_next_collation_and_fetch(ctx, state, parent, id).await;
+			dequeue_next_collation_and_fetch(ctx, state, parent, Some(id)).await;
 		},
 	}
 }
@@ -1167,9 +1636,12 @@ pub(crate) async fn run<Context>(
 	mut ctx: Context,
 	keystore: SyncCryptoStorePtr,
 	eviction_policy: crate::CollatorEvictionPolicy,
+	db: Arc<dyn Database>,
+	config: ValidatorSideConfig,
 	metrics: Metrics,
 ) -> std::result::Result<(), crate::error::FatalError> {
-	let mut state = State { metrics, ..Default::default() };
+	let reputations = CollatorReputations::load(db, config.col_reputation_data);
+	let mut state = State::new(reputations, config.collator_filter, metrics);
 
 	let next_inactivity_stream = tick_stream(ACTIVITY_POLL);
 	futures::pin_mut!(next_inactivity_stream);
@@ -1196,6 +1668,7 @@ pub(crate) async fn run<Context>(
 			}
 			_ = next_inactivity_stream.next() => {
 				disconnect_inactive_peers(ctx.sender(), &eviction_policy, &state.peer_data).await;
+				persist_reputations(&mut state.reputations);
 			}
 			res = state.collation_fetches.select_next_some() => {
 				handle_collation_fetched_result(&mut ctx, &mut state, res).await;
@@ -1208,7 +1681,10 @@ pub(crate) async fn run<Context>(
 					?collator_id,
 					"Timeout hit - already seconded?"
 				);
-				dequeue_next_collation_and_fetch(&mut ctx, &mut state, relay_parent, collator_id).await;
+				dequeue_next_collation_and_fetch(&mut ctx, &mut state, relay_parent, Some(collator_id)).await;
+			}
+			relay_parent = state.held_back_fetches.select_next_some() => {
+				dequeue_next_collation_and_fetch(&mut ctx, &mut state, relay_parent, None).await;
 			}
 			_ = check_collations_stream.next() => {
 				let reputation_changes = poll_requests(
@@ -1218,15 +1694,45 @@ pub(crate) async fn run<Context>(
 				).await;
 
 				for (peer_id, rep) in reputation_changes {
+					let collator_id =
+						state.peer_data.get(&peer_id).and_then(|p| p.collator_id()).cloned();
+					if let (Some(collator_id), Some(score)) = (collator_id, collator_score_change(rep)) {
+						state.reputations.modify(&collator_id, score);
+					}
 					modify_reputation(ctx.sender(), peer_id, rep).await;
 				}
 			},
 		}
 	}
 
+	persist_reputations(&mut state.reputations);
+
 	Ok(())
 }
 
+/// Write changed collator reputations to the database.
+fn persist_reputations(reputations: &mut CollatorReputations) {
+	if let Err(err) = reputations.persist() {
+		gum::warn!(target: LOG_TARGET, ?err, "Failed to persist collator reputations");
+	}
+}
+
+/// The change of the persisted score of a collator, for a reputation change of its peer after
+/// fetching a collation.
+fn collator_score_change(rep: Rep) -> Option<i32> {
+	if rep == COST_WRONG_PARA {
+		Some(SCORE_WRONG_PARA)
+	} else if rep == COST_WRONG_CANDIDATE {
+		Some(SCORE_WRONG_CANDIDATE)
+	} else if rep == COST_CORRUPTED_MESSAGE {
+		Some(SCORE_CORRUPTED_MESSAGE)
+	} else if rep == COST_NETWORK_ERROR {
+		Some(SCORE_NETWORK_ERROR)
+	} else {
+		None
+	}
+}
+
 async fn poll_requests(
 	requested_collations: &mut HashMap<PendingCollation, PerRequest>,
 	metrics: &Metrics,
@@ -1257,14 +1763,13 @@ async fn dequeue_next_collation_and_fetch<Context>(
 	ctx: &mut Context,
 	state: &mut State,
 	relay_parent: Hash,
-	// The collator we tried to fetch from last.
-	previous_fetch: CollatorId,
+	// The collator we tried to fetch from last, `None` if the fetch was held back.
+	previous_fetch: Option<CollatorId>,
 ) {
-	if let Some((next, id)) = state
-		.collations_per_relay_parent
-		.get_mut(&relay_parent)
-		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch)))
-	{
+	let State { collations_per_relay_parent, fetch_rotation, reputations, .. } = &mut *state;
+	if let Some((next, id)) = collations_per_relay_parent.get_mut(&relay_parent).and_then(|c| {
+		c.get_next_collation_to_fetch(previous_fetch.as_ref(), fetch_rotation, reputations)
+	}) {
 		gum::debug!(
 			target: LOG_TARGET,
 			?relay_parent,
@@ -1307,7 +1812,8 @@ async fn handle_collation_fetched_result<Context>(
 				"Failed to fetch collation.",
 			);
 
-			dequeue_next_collation_and_fetch(ctx, state, relay_parent, collation_event.0).await;
+			dequeue_next_collation_and_fetch(ctx, state, relay_parent, Some(collation_event.0))
+				.await;
 			return
 		},
 	};
@@ -1322,6 +1828,7 @@ async fn handle_collation_fetched_result<Context>(
 			return
 		}
 		collations.status = CollationStatus::WaitingOnValidation;
+		collations.known_candidates.insert(candidate_receipt.hash());
 	}
 
 	if let Entry::Vacant(entry) = state.pending_candidates.entry(relay_parent) {
@@ -1463,6 +1970,21 @@ async fn poll_collation_response(
 
 				CollationFetchResult::Error(Some(COST_WRONG_PARA))
 			},
+			Ok(CollationFetchingResponse::Collation(receipt, _))
+				if per_req
+					.advertised_candidate
+					.map_or(false, |candidate_hash| candidate_hash != receipt.hash()) =>
+			{
+				gum::debug!(
+					target: LOG_TARGET,
+					expected_candidate_hash = ?per_req.advertised_candidate,
+					got_candidate_hash = ?receipt.hash(),
+					peer_id = ?pending_collation.peer_id,
+					"Got a collation other than the advertised one."
+				);
+
+				CollationFetchResult::Error(Some(COST_WRONG_CANDIDATE))
+			},
 			Ok(CollationFetchingResponse::Collation(receipt, pov)) => {
 				gum::debug!(
 					target: LOG_TARGET,
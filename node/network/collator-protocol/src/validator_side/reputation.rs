@@ -0,0 +1,162 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reputations of collators as observed by this validator.
+//!
+//! Unlike the peer reputation kept by the network, these are tracked per `CollatorId` and are
+//! persisted in the parachains database, so they survive restarts and reconnects under a new
+//! peer id.
+
+use std::{collections::HashMap, io, sync::Arc};
+
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_primitives::CollatorId;
+
+use crate::LOG_TARGET;
+
+/// The key all reputations are stored under.
+const REPUTATIONS_KEY: &[u8] = b"CollatorReputations";
+
+/// The highest score a collator can reach.
+const MAX_SCORE: i32 = 1_000;
+
+/// The lowest score a collator can reach.
+const MIN_SCORE: i32 = -1_000;
+
+/// The maximum number of collators a reputation is kept for.
+///
+/// Once exceeded, the reputations closest to neutral are forgotten first.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Collators with a score below this are only fetched from when no other collator advertised a
+/// collation for the same relay parent.
+pub(super) const LOW_REPUTATION_THRESHOLD: i32 = -100;
+
+/// A collation of the collator got seconded.
+pub(super) const SCORE_SECONDED: i32 = 10;
+/// The collator was reported by another subsystem, e.g. for an invalid collation.
+pub(super) const SCORE_REPORTED: i32 = -200;
+/// The collator provided a collation for the wrong para.
+pub(super) const SCORE_WRONG_PARA: i32 = -200;
+/// The collator provided a collation other than the one it advertised.
+pub(super) const SCORE_WRONG_CANDIDATE: i32 = -200;
+/// The collator provided a response which could not be decoded.
+pub(super) const SCORE_CORRUPTED_MESSAGE: i32 = -50;
+/// Fetching a collation from the collator failed due to a network error.
+pub(super) const SCORE_NETWORK_ERROR: i32 = -5;
+
+/// Scores of all collators we know of, backed by the database.
+pub(super) struct CollatorReputations {
+	db: Arc<dyn Database>,
+	col_data: u32,
+	scores: HashMap<CollatorId, i32>,
+	/// Whether there are changes which were not yet written to the database.
+	dirty: bool,
+}
+
+impl CollatorReputations {
+	/// Load the persisted reputations.
+	///
+	/// Reputations which can not be read are discarded and we start out with neutral scores.
+	pub(super) fn load(db: Arc<dyn Database>, col_data: u32) -> Self {
+		let scores = match db.get(col_data, REPUTATIONS_KEY) {
+			Ok(Some(raw)) => match Vec::<(CollatorId, i32)>::decode(&mut &raw[..]) {
+				Ok(scores) => scores.into_iter().collect(),
+				Err(err) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						?err,
+						"Failed to decode persisted collator reputations",
+					);
+					HashMap::new()
+				},
+			},
+			Ok(None) => HashMap::new(),
+			Err(err) => {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to load persisted collator reputations");
+				HashMap::new()
+			},
+		};
+
+		Self { db, col_data, scores, dirty: false }
+	}
+
+	/// The score of the given collator. Unknown collators have a neutral score of zero.
+	pub(super) fn score(&self, collator_id: &CollatorId) -> i32 {
+		self.scores.get(collator_id).copied().unwrap_or_default()
+	}
+
+	/// Whether the given collator has a score other than neutral.
+	pub(super) fn is_known(&self, collator_id: &CollatorId) -> bool {
+		self.scores.contains_key(collator_id)
+	}
+
+	/// Whether the given collator has a low reputation.
+	pub(super) fn is_low(&self, collator_id: &CollatorId) -> bool {
+		self.score(collator_id) < LOW_REPUTATION_THRESHOLD
+	}
+
+	/// Change the score of the given collator by `delta`.
+	pub(super) fn modify(&mut self, collator_id: &CollatorId, delta: i32) {
+		let score = self.scores.entry(collator_id.clone()).or_default();
+		*score = score.saturating_add(delta).clamp(MIN_SCORE, MAX_SCORE);
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?collator_id,
+			delta,
+			score = *score,
+			"Collator reputation changed",
+		);
+
+		if *score == 0 {
+			self.scores.remove(collator_id);
+		}
+
+		if self.scores.len() > MAX_ENTRIES {
+			let most_neutral = self
+				.scores
+				.iter()
+				.filter(|(id, _)| *id != collator_id)
+				.min_by_key(|(_, score)| score.unsigned_abs())
+				.map(|(id, _)| id.clone());
+			if let Some(id) = most_neutral {
+				self.scores.remove(&id);
+			}
+		}
+
+		self.dirty = true;
+	}
+
+	/// Write the reputations to the database, if they changed since the last write.
+	pub(super) fn persist(&mut self) -> io::Result<()> {
+		if !self.dirty {
+			return Ok(())
+		}
+
+		let scores: Vec<(CollatorId, i32)> =
+			self.scores.iter().map(|(id, score)| (id.clone(), *score)).collect();
+
+		let mut tx = DBTransaction::new();
+		tx.put_vec(self.col_data, REPUTATIONS_KEY, scores.encode());
+		self.db.write(tx)?;
+
+		self.dirty = false;
+		Ok(())
+	}
+}
@@ -17,7 +17,10 @@
 use super::*;
 use assert_matches::assert_matches;
 use futures::{executor, future, Future};
-use sp_core::{crypto::Pair, Encode};
+use sp_core::{
+	crypto::{Pair, Ss58Codec},
+	Encode,
+};
 use sp_keyring::Sr25519Keyring;
 use sp_keystore::{testing::KeyStore as TestKeyStore, SyncCryptoStore};
 use std::{iter, sync::Arc, task::Poll, time::Duration};
@@ -33,8 +36,8 @@ use polkadot_node_subsystem::messages::{AllMessages, RuntimeApiMessage, RuntimeA
 use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_node_subsystem_util::TimeoutExt;
 use polkadot_primitives::{
-	CollatorPair, CoreState, GroupIndex, GroupRotationInfo, OccupiedCore, ScheduledCore,
-	ValidatorId, ValidatorIndex,
+	CollatorPair, CoreState, GroupIndex, GroupRotationInfo, HeadData, OccupiedCore,
+	PersistedValidationData, ScheduledCore, ValidatorId, ValidatorIndex,
 };
 use polkadot_primitives_test_helpers::{
 	dummy_candidate_descriptor, dummy_candidate_receipt_bad_sig, dummy_hash,
@@ -42,6 +45,7 @@ use polkadot_primitives_test_helpers::{
 
 const ACTIVITY_TIMEOUT: Duration = Duration::from_millis(500);
 const DECLARE_TIMEOUT: Duration = Duration::from_millis(25);
+const COL_REPUTATION_DATA: u32 = 0;
 
 #[derive(Clone)]
 struct TestState {
@@ -119,7 +123,20 @@ struct TestHarness {
 	virtual_overseer: VirtualOverseer,
 }
 
+fn make_db() -> Arc<dyn Database> {
+	let db = kvdb_memorydb::create(1);
+	Arc::new(polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[]))
+}
+
 fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarness) -> T) {
+	test_harness_with(make_db(), Default::default(), test)
+}
+
+fn test_harness_with<T: Future<Output = VirtualOverseer>>(
+	db: Arc<dyn Database>,
+	collator_filter: CollatorFilter,
+	test: impl FnOnce(TestHarness) -> T,
+) {
 	let _ = env_logger::builder()
 		.is_test(true)
 		.filter(Some("polkadot_collator_protocol"), log::LevelFilter::Trace)
@@ -145,6 +162,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarne
 			inactive_collator: ACTIVITY_TIMEOUT,
 			undeclared: DECLARE_TIMEOUT,
 		},
+		db,
+		ValidatorSideConfig { col_reputation_data: COL_REPUTATION_DATA, collator_filter },
 		Metrics::default(),
 	);
 
@@ -290,6 +309,18 @@ async fn assert_fetch_collation_request(
 				assert_eq!(payload.para_id, para_id);
 				req.pending_response
 			}
+			Requests::CollationFetchingV2(req) => {
+				let payload = req.payload;
+				assert_eq!(payload.relay_parent, relay_parent);
+				assert_eq!(payload.para_id, para_id);
+
+				let (fallback, protocol) = req.fallback_request
+					.expect("Requests by candidate hash fall back to requests by relay parent");
+				assert_eq!(protocol, req_res::Protocol::CollationFetchingV1);
+				assert_eq!(fallback.relay_parent, relay_parent);
+				assert_eq!(fallback.para_id, para_id);
+				req.pending_response
+			}
 			_ => panic!("Unexpected request"),
 		}
 	})
@@ -301,27 +332,55 @@ async fn connect_and_declare_collator(
 	peer: PeerId,
 	collator: CollatorPair,
 	para_id: ParaId,
+) {
+	connect_and_declare_collator_on_version(
+		virtual_overseer,
+		peer,
+		collator,
+		para_id,
+		CollationVersion::V1,
+	)
+	.await
+}
+
+/// Connect a collator on the given version of the collation protocol and declare it.
+async fn connect_and_declare_collator_on_version(
+	virtual_overseer: &mut VirtualOverseer,
+	peer: PeerId,
+	collator: CollatorPair,
+	para_id: ParaId,
+	version: CollationVersion,
 ) {
 	overseer_send(
 		virtual_overseer,
 		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerConnected(
 			peer.clone(),
 			ObservedRole::Full,
-			CollationVersion::V1.into(),
+			version.into(),
 			None,
 		)),
 	)
 	.await;
 
+	let signature = collator.sign(&protocol_v1::declare_signature_payload(&peer));
+	let declare = match version {
+		CollationVersion::V1 => Versioned::V1(protocol_v1::CollatorProtocolMessage::Declare(
+			collator.public(),
+			para_id,
+			signature,
+		)),
+		CollationVersion::V2 => Versioned::V2(protocol_v2::CollatorProtocolMessage::Declare(
+			collator.public(),
+			para_id,
+			signature,
+		)),
+	};
+
 	overseer_send(
 		virtual_overseer,
 		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
 			peer.clone(),
-			Versioned::V1(protocol_v1::CollatorProtocolMessage::Declare(
-				collator.public(),
-				para_id,
-				collator.sign(&protocol_v1::declare_signature_payload(&peer)),
-			)),
+			declare,
 		)),
 	)
 	.await;
@@ -343,6 +402,55 @@ async fn advertise_collation(
 	.await;
 }
 
+/// Advertise a collation on version 2 of the collation protocol.
+async fn advertise_collation_v2(
+	virtual_overseer: &mut VirtualOverseer,
+	peer: PeerId,
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+	parent_head_data_hash: Hash,
+) {
+	overseer_send(
+		virtual_overseer,
+		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
+			peer,
+			Versioned::V2(protocol_v2::CollatorProtocolMessage::AdvertiseCollation {
+				relay_parent,
+				candidate_hash,
+				parent_head_data_hash,
+			}),
+		)),
+	)
+	.await;
+}
+
+/// Answer the queries for the para heads advertised collations may build upon.
+async fn respond_to_parent_head_queries(
+	virtual_overseer: &mut VirtualOverseer,
+	expected_para_id: ParaId,
+	parent_head: HeadData,
+) {
+	for expected_assumption in [OccupiedCoreAssumption::TimedOut, OccupiedCoreAssumption::Included]
+	{
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::PersistedValidationData(para_id, assumption, tx),
+			)) => {
+				assert_eq!(para_id, expected_para_id);
+				assert_eq!(assumption, expected_assumption);
+				let _ = tx.send(Ok(Some(PersistedValidationData {
+					parent_head: parent_head.clone(),
+					relay_parent_number: 1,
+					relay_parent_storage_root: Default::default(),
+					max_pov_size: 1024,
+				})));
+			}
+		);
+	}
+}
+
 // As we receive a relevant advertisement act on it and issue a collation request.
 #[test]
 fn act_on_advertisement() {
@@ -822,6 +930,239 @@ fn fetch_next_collation_on_invalid_collation() {
 	});
 }
 
+// Advertisements of version 2 for candidates known already or building on a stale para head
+// are not fetched.
+#[test]
+fn v2_duplicate_and_stale_advertisements_are_not_fetched() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+		let peer_d = PeerId::random();
+		let peer_e = PeerId::random();
+
+		for (peer, collator) in [peer_b, peer_c, peer_d, peer_e].iter().zip(&test_state.collators) {
+			connect_and_declare_collator_on_version(
+				&mut virtual_overseer,
+				*peer,
+				collator.clone(),
+				test_state.chain_ids[0],
+				CollationVersion::V2,
+			)
+			.await;
+		}
+
+		let parent_head = HeadData(vec![1, 2, 3]);
+
+		let pov = PoV { block_data: BlockData(vec![]) };
+		let mut candidate_a =
+			dummy_candidate_receipt_bad_sig(dummy_hash(), Some(Default::default()));
+		candidate_a.descriptor.para_id = test_state.chain_ids[0];
+		candidate_a.descriptor.relay_parent = test_state.relay_parent;
+
+		advertise_collation_v2(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.relay_parent,
+			candidate_a.hash(),
+			parent_head.hash(),
+		)
+		.await;
+
+		respond_to_parent_head_queries(
+			&mut virtual_overseer,
+			test_state.chain_ids[0],
+			parent_head.clone(),
+		)
+		.await;
+
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// The same candidate advertised by another collator is a duplicate.
+		advertise_collation_v2(
+			&mut virtual_overseer,
+			peer_c,
+			test_state.relay_parent,
+			candidate_a.hash(),
+			parent_head.hash(),
+		)
+		.await;
+
+		// A candidate building on another para head is stale.
+		advertise_collation_v2(
+			&mut virtual_overseer,
+			peer_d,
+			test_state.relay_parent,
+			CandidateHash(Hash::repeat_byte(0xBB)),
+			Hash::repeat_byte(0xCC),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(
+				peer,
+				rep,
+			)) => {
+				assert_eq!(peer, peer_d);
+				assert_eq!(rep, COST_STALE_COLLATION);
+			}
+		);
+
+		advertise_collation_v2(
+			&mut virtual_overseer,
+			peer_e,
+			test_state.relay_parent,
+			CandidateHash(Hash::repeat_byte(0xDD)),
+			parent_head.hash(),
+		)
+		.await;
+
+		response_channel
+			.send(Ok(
+				CollationFetchingResponse::Collation(candidate_a.clone(), pov.clone()).encode()
+			))
+			.expect("Sending response should succeed");
+
+		let receipt = assert_candidate_backing_second(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+			&pov,
+		)
+		.await;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::Invalid(test_state.relay_parent, receipt),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(
+				peer,
+				rep,
+			)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_REPORT_BAD);
+			}
+		);
+
+		// The next collation is fetched from the collator of the only other valid advertisement.
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(reqs, _)) => {
+				assert_matches!(
+					reqs.as_slice(),
+					[Requests::CollationFetchingV2(req)] => {
+						assert_eq!(req.peer, Recipient::Peer(peer_e));
+						assert_eq!(req.payload.candidate_hash, CandidateHash(Hash::repeat_byte(0xDD)));
+					}
+				);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
+// A collation other than the advertised one is rejected.
+#[test]
+fn v2_collation_other_than_advertised_is_rejected() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+
+		connect_and_declare_collator_on_version(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+			CollationVersion::V2,
+		)
+		.await;
+
+		let parent_head = HeadData(vec![1, 2, 3]);
+
+		advertise_collation_v2(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.relay_parent,
+			CandidateHash(Hash::repeat_byte(0xBB)),
+			parent_head.hash(),
+		)
+		.await;
+
+		respond_to_parent_head_queries(
+			&mut virtual_overseer,
+			test_state.chain_ids[0],
+			parent_head.clone(),
+		)
+		.await;
+
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		let pov = PoV { block_data: BlockData(vec![]) };
+		let mut candidate_a =
+			dummy_candidate_receipt_bad_sig(dummy_hash(), Some(Default::default()));
+		candidate_a.descriptor.para_id = test_state.chain_ids[0];
+		candidate_a.descriptor.relay_parent = test_state.relay_parent;
+		response_channel
+			.send(Ok(CollationFetchingResponse::Collation(candidate_a, pov).encode()))
+			.expect("Sending response should succeed");
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(
+				peer,
+				rep,
+			)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_WRONG_CANDIDATE);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn inactive_disconnected() {
 	let test_state = TestState::default();
@@ -1078,3 +1419,277 @@ fn view_change_clears_old_collators() {
 		virtual_overseer
 	})
 }
+
+#[test]
+fn denied_collator_is_disconnected() {
+	let test_state = TestState::default();
+
+	let mut collator_filter = CollatorFilter::default();
+	collator_filter
+		.denied
+		.entry(test_state.chain_ids[0])
+		.or_default()
+		.insert(test_state.collators[0].public());
+
+	test_harness_with(make_db(), collator_filter, |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		assert_collator_disconnect(&mut virtual_overseer, peer_b).await;
+
+		// Other collators of the para are unaffected.
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_c,
+			test_state.collators[1].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		advertise_collation(&mut virtual_overseer, peer_c, test_state.relay_parent).await;
+
+		assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn collator_not_allowed_is_disconnected() {
+	let test_state = TestState::default();
+
+	let mut collator_filter = CollatorFilter::default();
+	collator_filter
+		.allowed
+		.entry(test_state.chain_ids[0])
+		.or_default()
+		.insert(test_state.collators[1].public());
+
+	test_harness_with(make_db(), collator_filter, |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		assert_collator_disconnect(&mut virtual_overseer, peer_b).await;
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_c,
+			test_state.collators[1].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		advertise_collation(&mut virtual_overseer, peer_c, test_state.relay_parent).await;
+
+		assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn collator_filter_parses_entries() {
+	let collator = CollatorPair::generate().0.public();
+	let entry = format!("2000:{}", collator.to_ss58check());
+
+	let filter = CollatorFilter::from_entries(&[entry.clone()], &[]).unwrap();
+	assert!(filter.is_allowed(&ParaId::from(2000), &collator));
+	assert!(!filter.is_allowed(&ParaId::from(2000), &CollatorPair::generate().0.public()));
+	assert!(filter.is_allowed(&ParaId::from(2001), &CollatorPair::generate().0.public()));
+
+	let filter = CollatorFilter::from_entries(&[], &[entry]).unwrap();
+	assert!(!filter.is_allowed(&ParaId::from(2000), &collator));
+	assert!(filter.is_allowed(&ParaId::from(2001), &collator));
+
+	assert!(CollatorFilter::from_entries(&["2000".into()], &[]).is_err());
+	assert!(CollatorFilter::from_entries(&["x:y".into()], &[]).is_err());
+}
+
+#[test]
+fn collator_reputation_is_persisted() {
+	let test_state = TestState::default();
+	let db = make_db();
+	let reported = test_state.collators[0].public();
+	let other = test_state.collators[1].public();
+
+	test_harness_with(db.clone(), Default::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::ReportCollator(test_state.collators[0].public()),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep),
+			) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_REPORT_BAD);
+			}
+		);
+
+		virtual_overseer
+	});
+
+	let reputations = CollatorReputations::load(db, COL_REPUTATION_DATA);
+	assert_eq!(reputations.score(&reported), SCORE_REPORTED);
+	assert_eq!(reputations.score(&other), 0);
+}
+
+#[test]
+fn next_collation_is_fetched_from_least_recently_served_collator() {
+	let test_state = TestState::default();
+	let para_id = test_state.chain_ids[0];
+	let collator_a = test_state.collators[0].public();
+	let collator_b = test_state.collators[1].public();
+	let collator_c = test_state.collators[2].public();
+
+	let pending =
+		|peer_id| PendingCollation::new(test_state.relay_parent, &para_id, &peer_id, None);
+
+	let mut rotation = FetchRotation::default();
+	rotation.note_fetch(para_id, collator_a.clone());
+	rotation.note_fetch(para_id, collator_b.clone());
+
+	let mut reputations = CollatorReputations::load(make_db(), COL_REPUTATION_DATA);
+
+	let mut collations = CollationsPerRelayParent::default();
+	collations.unfetched_collations = vec![
+		(pending(PeerId::random()), collator_c.clone()),
+		(pending(PeerId::random()), collator_b.clone()),
+		(pending(PeerId::random()), collator_a.clone()),
+	];
+
+	// The least recently served collator goes first, never served ones are unknown and go last.
+	let next = collations.get_next_collation_to_fetch(None, &rotation, &reputations);
+	assert_eq!(next.map(|(_, id)| id), Some(collator_a.clone()));
+	collations
+		.unfetched_collations
+		.push((pending(PeerId::random()), collator_a.clone()));
+
+	// A positive reputation goes before recency.
+	reputations.modify(&collator_b, SCORE_SECONDED);
+	let next = collations.get_next_collation_to_fetch(Some(&collator_a), &rotation, &reputations);
+	assert_eq!(next.map(|(_, id)| id), Some(collator_b.clone()));
+
+	// Collators with a low reputation go even after unknown ones.
+	reputations.modify(&collator_a, SCORE_REPORTED);
+	let next = collations.get_next_collation_to_fetch(Some(&collator_b), &rotation, &reputations);
+	assert_eq!(next.map(|(_, id)| id), Some(collator_c.clone()));
+	let next = collations.get_next_collation_to_fetch(Some(&collator_c), &rotation, &reputations);
+	assert_eq!(next.map(|(_, id)| id), Some(collator_a));
+}
+
+#[test]
+fn fetch_is_held_back_for_collator_served_last() {
+	let test_state = TestState::default();
+	let para_id = test_state.chain_ids[0];
+	let collator_a = test_state.collators[0].public();
+	let collator_b = test_state.collators[1].public();
+
+	let reputations = CollatorReputations::load(make_db(), COL_REPUTATION_DATA);
+	let mut state = State::new(reputations, Default::default(), Metrics::default());
+
+	let mut peer_a = PeerData::default();
+	peer_a.set_collating(collator_a.clone(), para_id);
+	state.peer_data.insert(PeerId::random(), peer_a);
+
+	// Nothing to hold back for a collator on its own.
+	state.fetch_rotation.note_fetch(para_id, collator_a.clone());
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_a));
+
+	let mut peer_b = PeerData::default();
+	peer_b.set_collating(collator_b.clone(), para_id);
+	state.peer_data.insert(PeerId::random(), peer_b);
+
+	// Collators we know nothing about are not waited for.
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_a));
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_b));
+
+	// Now it was served after us.
+	state.fetch_rotation.note_fetch(para_id, collator_b.clone());
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_a));
+	assert!(state.should_hold_back_fetch(&para_id, &collator_b));
+
+	// Collators with a positive reputation are waited for, even if served more recently.
+	state.reputations.modify(&collator_b, SCORE_SECONDED);
+	assert!(state.should_hold_back_fetch(&para_id, &collator_a));
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_b));
+
+	// Collators with a low reputation are not waited for.
+	state.reputations.modify(&collator_b, SCORE_REPORTED);
+	assert!(!state.should_hold_back_fetch(&para_id, &collator_a));
+}
@@ -24,7 +24,7 @@
 //! The sender is responsible for getting our vote out, see [`sender`]. The receiver handles
 //! incoming [`DisputeRequest`]s and offers spam protection, see [`receiver`].
 
-use std::{num::NonZeroUsize, time::Duration};
+use std::{cmp::max, num::NonZeroUsize, time::Duration};
 
 use futures::{channel::mpsc, FutureExt, StreamExt, TryFutureExt};
 
@@ -113,6 +113,23 @@ pub const RECEIVE_RATE_LIMIT: Duration = Duration::from_millis(100);
 /// We add 50ms extra, just to have some save margin to the `RECEIVE_RATE_LIMIT`.
 pub const SEND_RATE_LIMIT: Duration = RECEIVE_RATE_LIMIT.saturating_add(Duration::from_millis(50));
 
+/// Configuration for the dispute distribution subsystem.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+	/// Minimum interval between two dispute requests sent to the same peer.
+	///
+	/// Nodes under a lot of disputes might want to send slower than the default
+	/// [`SEND_RATE_LIMIT`]. Values below `RECEIVE_RATE_LIMIT` are raised to it, as receivers would
+	/// drop our requests otherwise.
+	pub send_rate_limit: Duration,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self { send_rate_limit: SEND_RATE_LIMIT }
+	}
+}
+
 /// The dispute distribution subsystem.
 pub struct DisputeDistributionSubsystem<AD> {
 	/// Easy and efficient runtime access for this subsystem.
@@ -163,13 +180,33 @@ where
 		authority_discovery: AD,
 		metrics: Metrics,
 	) -> Self {
+		Self::with_config(keystore, req_receiver, authority_discovery, Config::default(), metrics)
+	}
+
+	/// Create a new instance of the dispute distribution with the given configuration.
+	pub fn with_config(
+		keystore: SyncCryptoStorePtr,
+		req_receiver: IncomingRequestReceiver<v1::DisputeRequest>,
+		authority_discovery: AD,
+		config: Config,
+		metrics: Metrics,
+	) -> Self {
+		if config.send_rate_limit < RECEIVE_RATE_LIMIT {
+			gum::warn!(
+				target: LOG_TARGET,
+				send_rate_limit = ?config.send_rate_limit,
+				receive_rate_limit = ?RECEIVE_RATE_LIMIT,
+				"Configured send rate limit is faster than peers accept, using receive rate limit."
+			);
+		}
+		let send_rate_limit = max(config.send_rate_limit, RECEIVE_RATE_LIMIT);
 		let runtime = RuntimeInfo::new_with_config(runtime::Config {
 			keystore: Some(keystore),
 			session_cache_lru_size: NonZeroUsize::new(DISPUTE_WINDOW.get() as usize)
 				.expect("Dispute window can not be 0; qed"),
 		});
 		let (tx, sender_rx) = NestingSender::new_root(1);
-		let disputes_sender = DisputeSender::new(tx, metrics.clone());
+		let disputes_sender = DisputeSender::new(tx, send_rate_limit, metrics.clone());
 		Self {
 			runtime,
 			disputes_sender,
@@ -49,7 +49,7 @@ mod error;
 pub use error::{Error, FatalError, JfyiError, Result};
 
 use self::error::JfyiErrorResult;
-use crate::{Metrics, LOG_TARGET, SEND_RATE_LIMIT};
+use crate::{Metrics, LOG_TARGET};
 
 /// Messages as sent by background tasks.
 #[derive(Debug)]
@@ -106,22 +106,29 @@ struct WaitForActiveDisputesState {
 #[overseer::contextbounds(DisputeDistribution, prefix = self::overseer)]
 impl<M: 'static + Send + Sync> DisputeSender<M> {
 	/// Create a new `DisputeSender` which can be used to start dispute sendings.
-	pub fn new(tx: NestingSender<M, DisputeSenderMessage>, metrics: Metrics) -> Self {
+	///
+	/// Dispute sendings are paced so that each peer receives at most one request per
+	/// `send_rate_limit`.
+	pub fn new(
+		tx: NestingSender<M, DisputeSenderMessage>,
+		send_rate_limit: Duration,
+		metrics: Metrics,
+	) -> Self {
 		Self {
 			active_heads: Vec::new(),
 			active_sessions: HashMap::new(),
 			disputes: IndexMap::new(),
 			tx,
 			waiting_for_active_disputes: None,
-			rate_limit: RateLimit::new(),
+			rate_limit: RateLimit::new(send_rate_limit),
 			metrics,
 		}
 	}
 
 	/// Create a `SendTask` for a particular new dispute.
 	///
-	/// This function is rate-limited by `SEND_RATE_LIMIT`. It will block if called too frequently
-	/// in order to maintain the limit.
+	/// This function is rate-limited by the configured send rate limit. It will block if called too
+	/// frequently in order to maintain the limit.
 	pub async fn start_sender<Context>(
 		&mut self,
 		ctx: &mut Context,
@@ -250,7 +257,7 @@ impl<M: 'static + Send + Sync> DisputeSender<M> {
 	/// - Get new authorities to send messages to.
 	/// - Get rid of obsolete tasks and disputes.
 	///
-	/// This function ensures the `SEND_RATE_LIMIT`, therefore it might block.
+	/// This function ensures the configured send rate limit, therefore it might block.
 	async fn handle_new_active_disputes<Context>(
 		&mut self,
 		ctx: &mut Context,
@@ -311,18 +318,15 @@ impl<M: 'static + Send + Sync> DisputeSender<M> {
 /// Suitable for the sending side.
 struct RateLimit {
 	limit: Delay,
+	/// Minimum interval between two calls to `limit`.
+	interval: Duration,
 }
 
 impl RateLimit {
 	/// Create new `RateLimit` that is immediately ready.
-	fn new() -> Self {
+	fn new(interval: Duration) -> Self {
 		// Start with an empty duration, as there has not been any previous call.
-		Self { limit: Delay::new(Duration::new(0, 0)) }
-	}
-
-	/// Initialized with actual `SEND_RATE_LIMIT` duration.
-	fn new_limit() -> Self {
-		Self { limit: Delay::new(SEND_RATE_LIMIT) }
+		Self { limit: Delay::new(Duration::new(0, 0)), interval }
 	}
 
 	/// Wait until ready and prepare for next call.
@@ -349,7 +353,7 @@ impl RateLimit {
 			}
 		})
 		.await;
-		*self = Self::new_limit();
+		self.limit = Delay::new(self.interval);
 	}
 }
 
@@ -126,8 +126,8 @@ impl<M: 'static + Send + Sync> SendTask<M> {
 	/// happens and on a regular basis to ensure we are retrying failed attempts.
 	///
 	/// This might resend to validators and is thus subject to any rate limiting we might want.
-	/// Calls to this function for different instances should be rate limited according to
-	/// `SEND_RATE_LIMIT`.
+	/// Calls to this function for different instances should be rate limited according to the
+	/// configured send rate limit.
 	///
 	/// Returns: `True` if this call resulted in new requests.
 	pub async fn refresh_sends<Context>(
@@ -68,7 +68,7 @@ use self::mock::{
 use crate::{
 	receiver::BATCH_COLLECTING_INTERVAL,
 	tests::mock::{BOB_INDEX, CHARLIE_INDEX},
-	DisputeDistributionSubsystem, Metrics, LOG_TARGET, SEND_RATE_LIMIT,
+	Config, DisputeDistributionSubsystem, Metrics, LOG_TARGET, SEND_RATE_LIMIT,
 };
 
 /// Useful mock providers.
@@ -116,6 +116,28 @@ fn send_honors_rate_limit() {
 	test_harness(test);
 }
 
+#[test]
+fn send_honors_configured_rate_limit() {
+	sp_tracing::try_init_simple();
+	let send_rate_limit = SEND_RATE_LIMIT * 4;
+	let test = |mut handle: TestSubsystemContextHandle<DisputeDistributionMessage>, _req_cfg| async move {
+		let _ = handle_subsystem_startup(&mut handle, None).await;
+
+		let relay_parent = Hash::random();
+		let candidate = make_candidate_receipt(relay_parent);
+		let before_request = Instant::now();
+		send_dispute(&mut handle, candidate, true).await;
+
+		let relay_parent = Hash::random();
+		let candidate = make_candidate_receipt(relay_parent);
+		send_dispute(&mut handle, candidate, false).await;
+		// Second send should be rate limited by the configured limit:
+		assert!(Instant::now() - before_request >= send_rate_limit);
+		conclude(&mut handle).await;
+	};
+	test_harness_with_config(Config { send_rate_limit }, test);
+}
+
 /// Helper for sending a new dispute to dispute-distribution sender and handling resulting messages.
 async fn send_dispute(
 	handle: &mut TestSubsystemContextHandle<DisputeDistributionMessage>,
@@ -889,6 +911,18 @@ async fn handle_subsystem_startup(
 ///
 /// which simulates the overseer.
 fn test_harness<TestFn, Fut>(test: TestFn)
+where
+	TestFn: FnOnce(
+		TestSubsystemContextHandle<DisputeDistributionMessage>,
+		RequestResponseConfig,
+	) -> Fut,
+	Fut: Future<Output = ()>,
+{
+	test_harness_with_config(Config::default(), test)
+}
+
+/// Launch subsystem with the given configuration and provided test function.
+fn test_harness_with_config<TestFn, Fut>(config: Config, test: TestFn)
 where
 	TestFn: FnOnce(
 		TestSubsystemContextHandle<DisputeDistributionMessage>,
@@ -902,10 +936,11 @@ where
 	let genesis_hash = Hash::repeat_byte(0xff);
 	let req_protocol_names = ReqProtocolNames::new(&genesis_hash, None);
 	let (req_receiver, req_cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
-	let subsystem = DisputeDistributionSubsystem::new(
+	let subsystem = DisputeDistributionSubsystem::with_config(
 		keystore,
 		req_receiver,
 		MOCK_AUTHORITY_DISCOVERY.clone(),
+		config,
 		Metrics::new_dummy(),
 	);
 
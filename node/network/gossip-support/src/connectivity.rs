@@ -0,0 +1,80 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reports on the connectivity to the authorities the gossip support tries to stay connected to.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_primitives::{AuthorityDiscoveryId, SessionIndex};
+
+/// Connectivity to a single authority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorityStatus {
+	/// Authority discovery knows no addresses of the authority.
+	Unresolved,
+	/// The addresses of the authority are known, but we are not connected to it.
+	Unreachable,
+	/// We are connected to the authority via the given peer.
+	Connected(PeerId),
+}
+
+/// The labels of all kinds of [`AuthorityStatus`].
+pub(crate) const STATUS_LABELS: [&str; 3] = ["unresolved", "unreachable", "connected"];
+
+impl AuthorityStatus {
+	/// The label of the status, used in metrics.
+	pub fn label(&self) -> &'static str {
+		match self {
+			AuthorityStatus::Unresolved => "unresolved",
+			AuthorityStatus::Unreachable => "unreachable",
+			AuthorityStatus::Connected(_) => "connected",
+		}
+	}
+}
+
+/// Report on the connectivity to the authorities we try to stay connected to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectivityReport {
+	/// The current session, if known.
+	pub session_index: Option<SessionIndex>,
+	/// The authorities of the current session.
+	pub current_session: Vec<(AuthorityDiscoveryId, AuthorityStatus)>,
+	/// The authorities of the previous and next sessions, which are not part of the current
+	/// session.
+	///
+	/// The runtime reports these together, so they can't be told apart.
+	pub adjacent_sessions: Vec<(AuthorityDiscoveryId, AuthorityStatus)>,
+}
+
+/// The latest [`ConnectivityReport`] of the gossip support subsystem.
+///
+/// Cloning the handle gives access to the same report.
+#[derive(Debug, Clone, Default)]
+pub struct SharedConnectivityReport(Arc<Mutex<Option<ConnectivityReport>>>);
+
+impl SharedConnectivityReport {
+	/// The latest report, if any has been produced yet.
+	pub fn latest(&self) -> Option<ConnectivityReport> {
+		self.0.lock().clone()
+	}
+
+	pub(crate) fn update(&self, report: ConnectivityReport) {
+		*self.0.lock() = Some(report);
+	}
+}
@@ -23,8 +23,12 @@
 //! to be an order of sqrt of the validators. Our neighbors
 //! in this graph will be forwarded to the network bridge with
 //! the `NetworkBridgeRxMessage::NewGossipTopology` message.
+//!
+//! It also periodically reports on the connectivity to the authorities it tries to stay
+//! connected to, see [`ConnectivityReport`].
 
 use std::{
+	cmp::min,
 	collections::{HashMap, HashSet},
 	fmt,
 	time::{Duration, Instant},
@@ -60,11 +64,22 @@ mod metrics;
 
 use metrics::Metrics;
 
+mod connectivity;
+
+pub use connectivity::{AuthorityStatus, ConnectivityReport, SharedConnectivityReport};
+
 const LOG_TARGET: &str = "parachain::gossip-support";
 // How much time should we wait to reissue a connection request
-// since the last authority discovery resolution failure.
+// since the first authority discovery resolution failure.
 const BACKOFF_DURATION: Duration = Duration::from_secs(5);
 
+// The backoff doubles with every reissued connection request still failing to resolve
+// authorities, up to this duration.
+const MAX_BACKOFF_DURATION: Duration = Duration::from_secs(320);
+
+/// How often the connectivity report is produced.
+const CONNECTIVITY_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Duration after which we consider low connectivity a problem.
 ///
 /// Especially at startup low connectivity is expected (authority discovery cache needs to be
@@ -83,9 +98,11 @@ pub struct GossipSupport<AD> {
 
 	last_session_index: Option<SessionIndex>,
 	// Some(timestamp) if we failed to resolve
-	// at least a third of authorities the last time.
+	// any authorities the last time.
 	// `None` otherwise.
 	last_failure: Option<Instant>,
+	// How long to wait after `last_failure` before reissuing the connection request.
+	retry_backoff: Duration,
 
 	/// First time we did not reach our connectivity threshold.
 	///
@@ -99,6 +116,12 @@ pub struct GossipSupport<AD> {
 	/// waiting for actual connection.
 	resolved_authorities: HashMap<AuthorityDiscoveryId, HashSet<Multiaddr>>,
 
+	/// Authorities we failed to resolve in the last connection request.
+	unresolved_authorities: HashSet<AuthorityDiscoveryId>,
+
+	/// Authorities of the current session.
+	current_session_authorities: HashSet<AuthorityDiscoveryId>,
+
 	/// Actually connected authorities.
 	connected_authorities: HashMap<AuthorityDiscoveryId, PeerId>,
 	/// By `PeerId`.
@@ -108,6 +131,9 @@ pub struct GossipSupport<AD> {
 	/// Authority discovery service.
 	authority_discovery: AD,
 
+	/// The latest connectivity report, shared with the RPC.
+	connectivity_report: SharedConnectivityReport,
+
 	/// Subsystem metrics.
 	metrics: Metrics,
 }
@@ -118,7 +144,14 @@ where
 	AD: AuthorityDiscovery,
 {
 	/// Create a new instance of the [`GossipSupport`] subsystem.
-	pub fn new(keystore: SyncCryptoStorePtr, authority_discovery: AD, metrics: Metrics) -> Self {
+	///
+	/// The subsystem periodically publishes its [`ConnectivityReport`] to `connectivity_report`.
+	pub fn new(
+		keystore: SyncCryptoStorePtr,
+		authority_discovery: AD,
+		connectivity_report: SharedConnectivityReport,
+		metrics: Metrics,
+	) -> Self {
 		// Initialize metrics to `0`.
 		metrics.on_is_not_authority();
 		metrics.on_is_not_parachain_validator();
@@ -127,18 +160,22 @@ where
 			keystore,
 			last_session_index: None,
 			last_failure: None,
+			retry_backoff: BACKOFF_DURATION,
 			failure_start: None,
 			resolved_authorities: HashMap::new(),
+			unresolved_authorities: HashSet::new(),
+			current_session_authorities: HashSet::new(),
 			connected_authorities: HashMap::new(),
 			connected_authorities_by_peer_id: HashMap::new(),
 			authority_discovery,
+			connectivity_report,
 			metrics,
 		}
 	}
 
 	async fn run<Context>(mut self, mut ctx: Context) -> Self {
 		fn get_connectivity_check_delay() -> Delay {
-			Delay::new(LOW_CONNECTIVITY_WARN_DELAY)
+			Delay::new(CONNECTIVITY_REPORT_INTERVAL)
 		}
 		let mut next_connectivity_check = get_connectivity_check_delay().fuse();
 		loop {
@@ -193,7 +230,7 @@ where
 		for leaf in leaves {
 			let current_index = util::request_session_index_for_child(leaf, sender).await.await??;
 			let since_failure = self.last_failure.map(|i| i.elapsed()).unwrap_or_default();
-			let force_request = since_failure >= BACKOFF_DURATION;
+			let force_request = since_failure >= self.retry_backoff;
 			let leaf_session = Some((current_index, leaf));
 			let maybe_new_session = match self.last_session_index {
 				Some(i) if current_index <= i => None,
@@ -220,6 +257,9 @@ where
 					},
 				};
 
+				self.current_session_authorities =
+					session_info.discovery_keys.iter().cloned().collect();
+
 				// Note: we only update `last_session_index` once we've
 				// successfully gotten the `SessionInfo`.
 				let is_new_session = maybe_new_session.is_some();
@@ -328,6 +368,7 @@ where
 		let mut validator_addrs = Vec::with_capacity(authorities.len());
 		let mut failures = 0;
 		let mut resolved = HashMap::with_capacity(authorities.len());
+		let mut unresolved = HashSet::new();
 		for authority in authorities {
 			if let Some(addrs) =
 				self.authority_discovery.get_addresses_by_authority_id(authority.clone()).await
@@ -341,9 +382,11 @@ where
 					"Couldn't resolve addresses of authority: {:?}",
 					authority
 				);
+				unresolved.insert(authority);
 			}
 		}
 		self.resolved_authorities = resolved;
+		self.unresolved_authorities = unresolved;
 		gum::debug!(target: LOG_TARGET, %num, "Issuing a connection request");
 
 		sender
@@ -353,10 +396,23 @@ where
 			})
 			.await;
 
-		// issue another request for the same session
-		// if at least a third of the authorities were not resolved.
+		// issue another request for the same session if any of the authorities were not
+		// resolved, backing off further with every request still failing.
+		let timestamp = Instant::now();
+		if failures != 0 {
+			self.retry_backoff = match self.last_failure {
+				None => BACKOFF_DURATION,
+				Some(_) => min(self.retry_backoff * 2, MAX_BACKOFF_DURATION),
+			};
+			self.last_failure = Some(timestamp);
+		} else {
+			self.retry_backoff = BACKOFF_DURATION;
+			self.last_failure = None;
+		}
+
+		// warn about low connectivity if at least a third of the authorities were not resolved
+		// for too long.
 		if num != 0 && 3 * failures >= num {
-			let timestamp = Instant::now();
 			match self.failure_start {
 				None => self.failure_start = Some(timestamp),
 				Some(first) if first.elapsed() >= LOW_CONNECTIVITY_WARN_DELAY => {
@@ -376,9 +432,7 @@ where
 					);
 				},
 			}
-			self.last_failure = Some(timestamp);
 		} else {
-			self.last_failure = None;
 			self.failure_start = None;
 		};
 	}
@@ -404,14 +458,44 @@ where
 			NetworkBridgeEvent::OurViewChange(_) => {},
 			NetworkBridgeEvent::PeerViewChange(_, _) => {},
 			NetworkBridgeEvent::NewGossipTopology { .. } => {},
-			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v)) => {
+			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v) | Versioned::V2(v)) => {
 				match v {};
 			},
 		}
 	}
 
-	/// Check connectivity and report on it in logs.
+	/// Build a report on the connectivity to all authorities of the last connection request.
+	fn connectivity_report(&self) -> ConnectivityReport {
+		let mut report =
+			ConnectivityReport { session_index: self.last_session_index, ..Default::default() };
+
+		let resolved = self.resolved_authorities.keys().map(|authority| {
+			let status = match self.connected_authorities.get(authority) {
+				Some(peer_id) => AuthorityStatus::Connected(*peer_id),
+				None => AuthorityStatus::Unreachable,
+			};
+			(authority, status)
+		});
+		let unresolved =
+			self.unresolved_authorities.iter().map(|a| (a, AuthorityStatus::Unresolved));
+
+		for (authority, status) in resolved.chain(unresolved) {
+			if self.current_session_authorities.contains(authority) {
+				report.current_session.push((authority.clone(), status));
+			} else {
+				report.adjacent_sessions.push((authority.clone(), status));
+			}
+		}
+
+		report
+	}
+
+	/// Check connectivity and report on it in logs, metrics and the shared connectivity report.
 	fn check_connectivity(&mut self) {
+		let report = self.connectivity_report();
+		self.metrics.on_connectivity_report(&report);
+		self.connectivity_report.update(report);
+
 		let absolute_connected = self.connected_authorities.len();
 		let absolute_resolved = self.resolved_authorities.len();
 		let connected_ratio =
@@ -18,10 +18,12 @@ use polkadot_node_subsystem_util::{
 	metrics,
 	metrics::{
 		prometheus,
-		prometheus::{Gauge, PrometheusError, Registry, U64},
+		prometheus::{Gauge, GaugeVec, Opts, PrometheusError, Registry, U64},
 	},
 };
 
+use crate::connectivity::{ConnectivityReport, STATUS_LABELS};
+
 /// Dispute Distribution metrics.
 #[derive(Clone, Default)]
 pub struct Metrics(Option<MetricsInner>);
@@ -32,6 +34,8 @@ struct MetricsInner {
 	is_authority: Gauge<U64>,
 	/// Tracks authority status for parachain approval checking.
 	is_parachain_validator: Gauge<U64>,
+	/// Number of authorities we try to connect to, by session and connectivity status.
+	authorities: GaugeVec<U64>,
 }
 
 impl Metrics {
@@ -68,6 +72,20 @@ impl Metrics {
 			metrics.is_parachain_validator.set(0);
 		}
 	}
+
+	/// Update the authority connectivity metrics from the given report.
+	pub fn on_connectivity_report(&self, report: &ConnectivityReport) {
+		if let Some(metrics) = &self.0 {
+			let sessions =
+				[("current", &report.current_session), ("adjacent", &report.adjacent_sessions)];
+			for (session, authorities) in sessions {
+				for status in STATUS_LABELS {
+					let count = authorities.iter().filter(|(_, s)| s.label() == status).count();
+					metrics.authorities.with_label_values(&[session, status]).set(count as u64);
+				}
+			}
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -85,6 +103,16 @@ impl metrics::Metrics for Metrics {
 				Updates at session boundary.")?,
 				registry,
 			)?,
+			authorities: prometheus::register(
+				GaugeVec::new(
+					Opts::new(
+						"polkadot_parachain_gossip_support_authorities",
+						"Number of authorities of the current and adjacent sessions we try to connect to, by connectivity status.",
+					),
+					&["session", "status"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -161,6 +161,7 @@ fn make_subsystem() -> GossipSupport<MockAuthorityDiscovery> {
 	GossipSupport::new(
 		make_ferdie_keystore(),
 		MOCK_AUTHORITY_DISCOVERY.clone(),
+		SharedConnectivityReport::default(),
 		Metrics::new_dummy(),
 	)
 }
@@ -717,5 +718,40 @@ fn issues_a_connection_request_when_last_request_was_mostly_unresolved() {
 	});
 
 	assert_eq!(state.last_session_index, Some(1));
-	assert!(state.last_failure.is_none());
+	// Bob is still unresolved, so we keep retrying, backing off further.
+	assert!(state.last_failure.is_some());
+	assert_eq!(state.retry_backoff, 2 * BACKOFF_DURATION);
+}
+
+#[test]
+fn connectivity_report_lists_authorities_by_status() {
+	let mut state = make_subsystem();
+	let report = state.connectivity_report.clone();
+	assert_eq!(report.latest(), None);
+
+	let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+	let bob: AuthorityDiscoveryId = Sr25519Keyring::Bob.public().into();
+	let past = PAST_PRESENT_FUTURE_AUTHORITIES[0].clone();
+	let alice_peer = PeerId::random();
+
+	state.last_session_index = Some(1);
+	state.current_session_authorities = AUTHORITIES.iter().cloned().collect();
+	state.resolved_authorities =
+		HashMap::from([(alice.clone(), HashSet::new()), (past.clone(), HashSet::new())]);
+	state.unresolved_authorities = HashSet::from([bob.clone()]);
+	state.connected_authorities = HashMap::from([(alice.clone(), alice_peer)]);
+
+	state.check_connectivity();
+
+	assert_eq!(
+		report.latest(),
+		Some(ConnectivityReport {
+			session_index: Some(1),
+			current_session: vec![
+				(alice, AuthorityStatus::Connected(alice_peer)),
+				(bob, AuthorityStatus::Unresolved),
+			],
+			adjacent_sessions: vec![(past, AuthorityStatus::Unreachable)],
+		})
+	);
 }
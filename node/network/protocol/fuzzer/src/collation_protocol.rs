@@ -0,0 +1,100 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Decodes the input as a message of every version of the collation protocol and feeds the ones
+//! decoding into both sides of the collator protocol.
+
+use honggfuzz::fuzz;
+use sp_core::Pair;
+
+use polkadot_collator_protocol::{CollatorProtocolSubsystem, ProtocolSide, ValidatorSideConfig};
+use polkadot_network_protocol_fuzzer::{decode, focus, Harness};
+use polkadot_node_network_protocol::{
+	peer_set::{CollationVersion, ProtocolVersion, MAX_NOTIFICATION_SIZE},
+	v1 as protocol_v1, v2 as protocol_v2, PeerId, VersionedCollationProtocol,
+};
+use polkadot_node_subsystem::messages::CollatorProtocolMessage;
+use polkadot_primitives::{CollatorPair, Id as ParaId};
+
+/// The para the collator side collates on.
+const PARA_ID: ParaId = ParaId::new(1);
+
+fn main() {
+	let harness = Harness::new();
+	let local_peer = PeerId::random();
+	let collator_pair = CollatorPair::from_seed(&[1; 32]);
+
+	loop {
+		fuzz!(|data: &[u8]| {
+			if data.len() as u64 > MAX_NOTIFICATION_SIZE {
+				return
+			}
+
+			if let Some(message) = decode::<protocol_v1::CollationProtocol>(data) {
+				feed(
+					&harness,
+					local_peer,
+					&collator_pair,
+					CollationVersion::V1.into(),
+					message.into(),
+				);
+			}
+			if let Some(message) = decode::<protocol_v2::CollationProtocol>(data) {
+				feed(
+					&harness,
+					local_peer,
+					&collator_pair,
+					CollationVersion::V2.into(),
+					message.into(),
+				);
+			}
+		});
+	}
+}
+
+fn feed(
+	harness: &Harness,
+	local_peer: PeerId,
+	collator_pair: &CollatorPair,
+	version: ProtocolVersion,
+	message: VersionedCollationProtocol,
+) {
+	let events = harness.peer_events(version, message);
+
+	let validator_side = ProtocolSide::Validator {
+		keystore: harness.keystore(),
+		eviction_policy: Default::default(),
+		db: harness.database(),
+		config: ValidatorSideConfig { col_reputation_data: 0, collator_filter: Default::default() },
+		metrics: Default::default(),
+	};
+	harness.feed_messages(
+		CollatorProtocolSubsystem::new(validator_side),
+		focus(&events, CollatorProtocolMessage::NetworkBridgeUpdate),
+	);
+
+	let collator_side = ProtocolSide::Collator(
+		local_peer,
+		collator_pair.clone(),
+		harness.request_receiver().0,
+		harness.request_receiver().0,
+		Default::default(),
+	);
+	let messages = std::iter::once(CollatorProtocolMessage::CollateOn(PARA_ID))
+		.chain(focus(&events, CollatorProtocolMessage::NetworkBridgeUpdate))
+		.collect();
+	harness.feed_messages(CollatorProtocolSubsystem::new(collator_side), messages);
+}
@@ -0,0 +1,360 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The harness shared by the fuzzing targets of the parachain network protocols.
+//!
+//! The targets decode the fuzzer input into the messages of the notification protocols and the
+//! payloads of the request-response protocols, and feed whatever a peer could get through the
+//! network bridge into the subsystems handling it, by means of the subsystem test context.
+//!
+//! Everything the subsystems send to the overseer is dropped, so their requests to other
+//! subsystems fail. An input is considered a failure if it makes a subsystem panic or allocate
+//! more memory than it could reasonably need for it.
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	collections::HashSet,
+	fmt::Debug,
+	future::Future,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{
+	channel::oneshot,
+	future::{self, FutureExt},
+	SinkExt, StreamExt,
+};
+use futures_timer::Delay;
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use sc_network::Multiaddr;
+use sp_keyring::Sr25519Keyring;
+use sp_keystore::SyncCryptoStorePtr;
+
+use polkadot_node_network_protocol::{
+	authority_discovery::AuthorityDiscovery,
+	jaeger,
+	peer_set::ProtocolVersion,
+	request_response::{
+		network::IncomingRequest as RawIncomingRequest, IncomingRequest, IncomingRequestReceiver,
+		IsRequest, ReqProtocolNames, RequestResponseConfig,
+	},
+	ObservedRole, OurView, PeerId, View, WrongVariant,
+};
+use polkadot_node_subsystem::{
+	messages::NetworkBridgeEvent,
+	overseer::{AssociateOutgoing, Subsystem},
+	ActivatedLeaf, ActiveLeavesUpdate, FromOrchestra, LeafStatus, OverseerSignal, SpawnGlue,
+	SubsystemError,
+};
+use polkadot_node_subsystem_test_helpers::{
+	make_subsystem_context, TestSubsystemContext, TestSubsystemContextHandle,
+};
+use polkadot_node_subsystem_util::database::{kvdb_impl::DbAdapter, Database};
+use polkadot_primitives::{AuthorityDiscoveryId, Hash};
+use sp_core::testing::TaskExecutor;
+
+/// The memory a decoded message may take in excess of `DECODE_MEMORY_FACTOR` times its encoded
+/// size.
+const DECODE_MEMORY_OVERHEAD: usize = 64 * 1024;
+
+/// The factor by which a decoded message may exceed its encoded size.
+const DECODE_MEMORY_FACTOR: usize = 256;
+
+/// The memory a subsystem may allocate while handling a single input.
+const SUBSYSTEM_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// How long a request is waited on to be answered or dropped, before it is abandoned.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The relay chain block the subsystems work on.
+const LEAF: Hash = Hash::repeat_byte(0xaa);
+
+/// The genesis hash the request-response protocol names are derived from.
+const GENESIS_HASH: Hash = Hash::repeat_byte(0xff);
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+/// The number of bytes currently allocated.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// The highest number of bytes allocated since the last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, keeping track of the number of bytes allocated.
+struct TrackingAllocator;
+
+impl TrackingAllocator {
+	fn on_alloc(size: usize) {
+		let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+		PEAK.fetch_max(allocated, Ordering::Relaxed);
+	}
+
+	fn on_dealloc(size: usize) {
+		ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+	}
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let ptr = System.alloc(layout);
+		if !ptr.is_null() {
+			Self::on_alloc(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let ptr = System.alloc_zeroed(layout);
+		if !ptr.is_null() {
+			Self::on_alloc(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout);
+		Self::on_dealloc(layout.size());
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_ptr = System.realloc(ptr, layout, new_size);
+		if !new_ptr.is_null() {
+			Self::on_dealloc(layout.size());
+			Self::on_alloc(new_size);
+		}
+		new_ptr
+	}
+}
+
+/// Run `f`, panicking if the memory allocated meanwhile exceeds `limit` bytes at any point.
+///
+/// Allocations of all threads are accounted, which includes the tasks spawned by the subsystems.
+pub fn assert_bounded_memory<R>(limit: usize, f: impl FnOnce() -> R) -> R {
+	let baseline = ALLOCATED.load(Ordering::Relaxed);
+	PEAK.store(baseline, Ordering::Relaxed);
+
+	let result = f();
+
+	let used = PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+	assert!(used <= limit, "allocated {} bytes, more than the limit of {} bytes", used, limit);
+	result
+}
+
+/// Decode `data` the way the network bridge does, panicking if decoding takes an unreasonable
+/// amount of memory.
+pub fn decode<T: Decode>(data: &[u8]) -> Option<T> {
+	let limit = DECODE_MEMORY_OVERHEAD + DECODE_MEMORY_FACTOR * data.len();
+	assert_bounded_memory(limit, || T::decode_all(&mut &data[..]).ok())
+}
+
+/// Narrow down the events of a peer set to those of a single subsystem, the way the network
+/// bridge does.
+pub fn focus<M, T, W>(
+	events: &[NetworkBridgeEvent<M>],
+	wrap: impl Fn(NetworkBridgeEvent<T>) -> W,
+) -> Vec<W>
+where
+	T: Clone + 'static,
+	for<'a> T: TryFrom<&'a M, Error = WrongVariant>,
+{
+	events.iter().filter_map(|event| event.focus().ok()).map(wrap).collect()
+}
+
+/// An authority discovery knowing the authority behind the fuzzing peer only.
+#[derive(Debug, Clone)]
+pub struct PeerAuthority {
+	peer: PeerId,
+	authority: AuthorityDiscoveryId,
+}
+
+#[async_trait]
+impl AuthorityDiscovery for PeerAuthority {
+	async fn get_addresses_by_authority_id(
+		&mut self,
+		_authority: AuthorityDiscoveryId,
+	) -> Option<HashSet<Multiaddr>> {
+		None
+	}
+
+	async fn get_authority_ids_by_peer_id(
+		&mut self,
+		peer: PeerId,
+	) -> Option<HashSet<AuthorityDiscoveryId>> {
+		(peer == self.peer).then(|| HashSet::from([self.authority.clone()]))
+	}
+}
+
+/// The environment of the subsystems under test, set up once per fuzzing process.
+pub struct Harness {
+	spawner: TaskExecutor,
+	keystore: SyncCryptoStorePtr,
+	req_protocol_names: ReqProtocolNames,
+	peer: PeerId,
+	authority: AuthorityDiscoveryId,
+}
+
+impl Harness {
+	/// Create a new harness, with an empty keystore and a random peer sending all the input.
+	pub fn new() -> Self {
+		Self {
+			spawner: TaskExecutor::new(),
+			keystore: Arc::new(sc_keystore::LocalKeystore::in_memory()),
+			req_protocol_names: ReqProtocolNames::new(GENESIS_HASH, None),
+			peer: PeerId::random(),
+			authority: Sr25519Keyring::Alice.public().into(),
+		}
+	}
+
+	/// The keystore to pass to the subsystems.
+	pub fn keystore(&self) -> SyncCryptoStorePtr {
+		self.keystore.clone()
+	}
+
+	/// The authority discovery to pass to the subsystems.
+	pub fn authority_discovery(&self) -> PeerAuthority {
+		PeerAuthority { peer: self.peer, authority: self.authority.clone() }
+	}
+
+	/// A fresh in-memory database with a single column.
+	pub fn database(&self) -> Arc<dyn Database> {
+		Arc::new(DbAdapter::new(kvdb_memorydb::create(1), &[]))
+	}
+
+	/// The receiver of a request protocol to pass to a subsystem, along with the config to feed
+	/// it requests with.
+	pub fn request_receiver<Req>(&self) -> (IncomingRequestReceiver<Req>, RequestResponseConfig)
+	where
+		Req: IsRequest + Decode + Encode,
+	{
+		IncomingRequest::<Req>::get_config_receiver(&self.req_protocol_names)
+	}
+
+	/// The events of the fuzzing peer connecting with `version`, sending `message` and
+	/// disconnecting again, as the network bridge would emit them.
+	pub fn peer_events<M>(
+		&self,
+		version: ProtocolVersion,
+		message: M,
+	) -> Vec<NetworkBridgeEvent<M>> {
+		let our_view = OurView::new([(LEAF, Arc::new(jaeger::Span::Disabled))], 0);
+		let authorities = HashSet::from([self.authority.clone()]);
+
+		vec![
+			NetworkBridgeEvent::OurViewChange(our_view),
+			NetworkBridgeEvent::PeerConnected(
+				self.peer,
+				ObservedRole::Authority,
+				version,
+				Some(authorities),
+			),
+			NetworkBridgeEvent::PeerViewChange(self.peer, View::new([LEAF], 0)),
+			NetworkBridgeEvent::PeerMessage(self.peer, message),
+			NetworkBridgeEvent::PeerDisconnected(self.peer),
+		]
+	}
+
+	/// Run `subsystem` until it has handled `messages` and concluded.
+	pub fn feed_messages<M, Sub>(&self, subsystem: Sub, messages: Vec<M>)
+	where
+		M: AssociateOutgoing + Debug + Send + 'static,
+		Sub: Subsystem<TestSubsystemContext<M, SpawnGlue<TaskExecutor>>, SubsystemError>,
+	{
+		self.run(subsystem, messages, future::ready(()))
+	}
+
+	/// Run `subsystem` until it has answered or dropped the request of the fuzzing peer with
+	/// `payload`, received on the protocol of `config`, and concluded.
+	///
+	/// The payload is passed on undecoded, so the decoding of the subsystem is exercised as well.
+	pub fn feed_request<M, Sub>(
+		&self,
+		subsystem: Sub,
+		messages: Vec<M>,
+		config: RequestResponseConfig,
+		payload: Vec<u8>,
+	) where
+		M: AssociateOutgoing + Debug + Send + 'static,
+		Sub: Subsystem<TestSubsystemContext<M, SpawnGlue<TaskExecutor>>, SubsystemError>,
+	{
+		if payload.len() as u64 > config.max_request_size {
+			return
+		}
+
+		let mut inbound_queue = config
+			.inbound_queue
+			.expect("Request protocols handled by subsystems have an inbound queue; qed");
+		let (pending_response, response) = oneshot::channel();
+		let request = RawIncomingRequest { peer: self.peer, payload, pending_response };
+
+		let request = async move {
+			if inbound_queue.send(request).await.is_ok() {
+				let _ = future::select(response, Delay::new(REQUEST_TIMEOUT)).await;
+			}
+		};
+
+		self.run(subsystem, messages, request)
+	}
+
+	/// Run `subsystem` on `LEAF`, send it `messages`, wait for `until` and conclude it, dropping
+	/// everything it sends.
+	fn run<M, Sub>(&self, subsystem: Sub, messages: Vec<M>, until: impl Future<Output = ()>)
+	where
+		M: AssociateOutgoing + Debug + Send + 'static,
+		Sub: Subsystem<TestSubsystemContext<M, SpawnGlue<TaskExecutor>>, SubsystemError>,
+	{
+		let (context, TestSubsystemContextHandle { mut tx, mut rx }) =
+			make_subsystem_context(self.spawner.clone());
+
+		let subsystem = subsystem.start(context).future;
+		let drain = async move { while rx.next().await.is_some() {} }.boxed();
+
+		let feed = async move {
+			let leaf = ActivatedLeaf {
+				hash: LEAF,
+				number: 1,
+				status: LeafStatus::Fresh,
+				span: Arc::new(jaeger::Span::Disabled),
+			};
+			let signal = OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf));
+			let _ = tx.send(FromOrchestra::Signal(signal)).await;
+
+			for msg in messages {
+				let _ = tx.send(FromOrchestra::Communication { msg }).await;
+			}
+			until.await;
+
+			let _ = tx.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		// Errors of the subsystem are fine, panics are not.
+		assert_bounded_memory(SUBSYSTEM_MEMORY_LIMIT, || {
+			let _ =
+				futures::executor::block_on(future::join(future::select(subsystem, drain), feed));
+		});
+	}
+}
+
+impl Default for Harness {
+	fn default() -> Self {
+		Self::new()
+	}
+}
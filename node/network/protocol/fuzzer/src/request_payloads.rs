@@ -0,0 +1,231 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Picks a request-response protocol by the first byte of the input, decodes the rest as a
+//! request and as a response of the protocol and feeds it as a request into the subsystem
+//! answering the protocol.
+
+use honggfuzz::fuzz;
+use parity_scale_codec::Decode;
+use rand::{rngs::StdRng, SeedableRng};
+use sp_core::Pair;
+
+use polkadot_availability_distribution::{
+	AvailabilityDistributionSubsystem, IncomingRequestReceivers,
+};
+use polkadot_availability_recovery::AvailabilityRecoverySubsystem;
+use polkadot_collator_protocol::{CollatorProtocolSubsystem, ProtocolSide};
+use polkadot_dispute_distribution::DisputeDistributionSubsystem;
+use polkadot_network_protocol_fuzzer::{decode, Harness};
+use polkadot_node_network_protocol::{
+	request_response::{v1, v2, IsRequest, Protocol},
+	PeerId,
+};
+use polkadot_node_subsystem::messages::{
+	AvailabilityDistributionMessage, AvailabilityRecoveryMessage, CollatorProtocolMessage,
+	DisputeDistributionMessage, StatementDistributionMessage,
+};
+use polkadot_primitives::{CollatorPair, Id as ParaId};
+use polkadot_statement_distribution::StatementDistributionSubsystem;
+
+/// The protocols selected by the first byte of the input.
+const PROTOCOLS: [Protocol; 8] = [
+	Protocol::ChunkFetchingV1,
+	Protocol::CollationFetchingV1,
+	Protocol::CollationFetchingV2,
+	Protocol::PoVFetchingV1,
+	Protocol::AvailableDataFetchingV1,
+	Protocol::StatementFetchingV1,
+	Protocol::CandidateStatementsFetchingV1,
+	Protocol::DisputeSendingV1,
+];
+
+/// The para the collator side collates on.
+const PARA_ID: ParaId = ParaId::new(1);
+
+fn main() {
+	let harness = Harness::new();
+	let local_peer = PeerId::random();
+	let collator_pair = CollatorPair::from_seed(&[1; 32]);
+
+	loop {
+		fuzz!(|data: &[u8]| {
+			let (selector, payload) = match data.split_first() {
+				Some(split) => split,
+				None => return,
+			};
+			let protocol = PROTOCOLS[*selector as usize % PROTOCOLS.len()];
+
+			feed(&harness, local_peer, &collator_pair, protocol, payload.to_vec());
+		});
+	}
+}
+
+/// Decode `payload` as a request of `Req` and as its response.
+fn decode_payloads<Req>(payload: &[u8])
+where
+	Req: IsRequest + Decode,
+	Req::Response: Decode,
+{
+	decode::<Req>(payload);
+	decode::<Req::Response>(payload);
+}
+
+fn feed(
+	harness: &Harness,
+	local_peer: PeerId,
+	collator_pair: &CollatorPair,
+	protocol: Protocol,
+	payload: Vec<u8>,
+) {
+	match protocol {
+		Protocol::ChunkFetchingV1 => {
+			decode_payloads::<v1::ChunkFetchingRequest>(&payload);
+
+			let (chunk_req_receiver, config) = harness.request_receiver();
+			let (pov_req_receiver, _pov_config) = harness.request_receiver();
+			let receivers = IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver };
+			harness.feed_request(
+				AvailabilityDistributionSubsystem::new(
+					harness.keystore(),
+					receivers,
+					Default::default(),
+				),
+				Vec::<AvailabilityDistributionMessage>::new(),
+				config,
+				payload,
+			);
+		},
+		Protocol::PoVFetchingV1 => {
+			decode_payloads::<v1::PoVFetchingRequest>(&payload);
+
+			let (pov_req_receiver, config) = harness.request_receiver();
+			let (chunk_req_receiver, _chunk_config) = harness.request_receiver();
+			let receivers = IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver };
+			harness.feed_request(
+				AvailabilityDistributionSubsystem::new(
+					harness.keystore(),
+					receivers,
+					Default::default(),
+				),
+				Vec::<AvailabilityDistributionMessage>::new(),
+				config,
+				payload,
+			);
+		},
+		Protocol::CollationFetchingV1 => {
+			decode_payloads::<v1::CollationFetchingRequest>(&payload);
+
+			let (v1_receiver, config) = harness.request_receiver();
+			let (v2_receiver, _v2_config) = harness.request_receiver();
+			let collator_side = ProtocolSide::Collator(
+				local_peer,
+				collator_pair.clone(),
+				v1_receiver,
+				v2_receiver,
+				Default::default(),
+			);
+			harness.feed_request(
+				CollatorProtocolSubsystem::new(collator_side),
+				vec![CollatorProtocolMessage::CollateOn(PARA_ID)],
+				config,
+				payload,
+			);
+		},
+		Protocol::CollationFetchingV2 => {
+			decode_payloads::<v2::CollationFetchingRequest>(&payload);
+
+			let (v1_receiver, _v1_config) = harness.request_receiver();
+			let (v2_receiver, config) = harness.request_receiver();
+			let collator_side = ProtocolSide::Collator(
+				local_peer,
+				collator_pair.clone(),
+				v1_receiver,
+				v2_receiver,
+				Default::default(),
+			);
+			harness.feed_request(
+				CollatorProtocolSubsystem::new(collator_side),
+				vec![CollatorProtocolMessage::CollateOn(PARA_ID)],
+				config,
+				payload,
+			);
+		},
+		Protocol::AvailableDataFetchingV1 => {
+			decode_payloads::<v1::AvailableDataFetchingRequest>(&payload);
+
+			let (receiver, config) = harness.request_receiver();
+			harness.feed_request(
+				AvailabilityRecoverySubsystem::with_chunks_only(receiver, Default::default()),
+				Vec::<AvailabilityRecoveryMessage>::new(),
+				config,
+				payload,
+			);
+		},
+		Protocol::StatementFetchingV1 => {
+			decode_payloads::<v1::StatementFetchingRequest>(&payload);
+
+			let (receiver, config) = harness.request_receiver();
+			let (statements_receiver, _statements_config) = harness.request_receiver();
+			harness.feed_request(
+				StatementDistributionSubsystem::new(
+					harness.keystore(),
+					receiver,
+					statements_receiver,
+					Default::default(),
+					StdRng::seed_from_u64(0),
+				),
+				Vec::<StatementDistributionMessage>::new(),
+				config,
+				payload,
+			);
+		},
+		Protocol::CandidateStatementsFetchingV1 => {
+			decode_payloads::<v1::CandidateStatementsFetchingRequest>(&payload);
+
+			let (receiver, _statement_config) = harness.request_receiver();
+			let (statements_receiver, config) = harness.request_receiver();
+			harness.feed_request(
+				StatementDistributionSubsystem::new(
+					harness.keystore(),
+					receiver,
+					statements_receiver,
+					Default::default(),
+					StdRng::seed_from_u64(0),
+				),
+				Vec::<StatementDistributionMessage>::new(),
+				config,
+				payload,
+			);
+		},
+		Protocol::DisputeSendingV1 => {
+			decode_payloads::<v1::DisputeRequest>(&payload);
+
+			let (receiver, config) = harness.request_receiver();
+			harness.feed_request(
+				DisputeDistributionSubsystem::new(
+					harness.keystore(),
+					receiver,
+					harness.authority_discovery(),
+					Default::default(),
+				),
+				Vec::<DisputeDistributionMessage>::new(),
+				config,
+				payload,
+			);
+		},
+	}
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Decodes the input as a message of every version of the validation protocol and feeds the
+//! ones decoding into the subsystems of the validation peer-set.
+
+use honggfuzz::fuzz;
+use rand::{rngs::StdRng, SeedableRng};
+
+use polkadot_approval_distribution::ApprovalDistribution;
+use polkadot_availability_bitfield_distribution::BitfieldDistribution;
+use polkadot_gossip_support::GossipSupport;
+use polkadot_network_protocol_fuzzer::{decode, focus, Harness};
+use polkadot_node_network_protocol::{
+	peer_set::{ProtocolVersion, ValidationVersion, MAX_NOTIFICATION_SIZE},
+	v1 as protocol_v1, v2 as protocol_v2, VersionedValidationProtocol,
+};
+use polkadot_node_subsystem::messages::{
+	ApprovalDistributionMessage, BitfieldDistributionMessage, GossipSupportMessage,
+	StatementDistributionMessage,
+};
+use polkadot_statement_distribution::StatementDistributionSubsystem;
+
+fn main() {
+	let harness = Harness::new();
+
+	loop {
+		fuzz!(|data: &[u8]| {
+			if data.len() as u64 > MAX_NOTIFICATION_SIZE {
+				return
+			}
+
+			if let Some(message) = decode::<protocol_v1::ValidationProtocol>(data) {
+				feed(&harness, ValidationVersion::V1.into(), message.into());
+			}
+			if let Some(message) = decode::<protocol_v2::ValidationProtocol>(data) {
+				feed(&harness, ValidationVersion::V2.into(), message.into());
+			}
+		});
+	}
+}
+
+fn feed(harness: &Harness, version: ProtocolVersion, message: VersionedValidationProtocol) {
+	let events = harness.peer_events(version, message);
+
+	harness.feed_messages(
+		BitfieldDistribution::new(Default::default()),
+		focus(&events, BitfieldDistributionMessage::from),
+	);
+	harness.feed_messages(
+		ApprovalDistribution::new(Default::default()),
+		focus(&events, ApprovalDistributionMessage::from),
+	);
+	harness.feed_messages(
+		StatementDistributionSubsystem::new(
+			harness.keystore(),
+			harness.request_receiver().0,
+			harness.request_receiver().0,
+			Default::default(),
+			StdRng::seed_from_u64(0),
+		),
+		focus(&events, StatementDistributionMessage::from),
+	);
+	harness.feed_messages(
+		GossipSupport::new(
+			harness.keystore(),
+			harness.authority_discovery(),
+			Default::default(),
+			Default::default(),
+		),
+		focus(&events, GossipSupportMessage::from),
+	);
+}
@@ -78,6 +78,16 @@ impl SessionGridTopology {
 		SessionGridTopology { shuffled_indices, canonical_shuffling }
 	}
 
+	/// The indices of the validators in the shuffling, by validator index.
+	pub fn shuffled_indices(&self) -> &[usize] {
+		&self.shuffled_indices
+	}
+
+	/// The canonical shuffling of validators for the session.
+	pub fn canonical_shuffling(&self) -> &[TopologyPeerInfo] {
+		&self.canonical_shuffling
+	}
+
 	/// Produces the outgoing routing logic for a particular peer.
 	///
 	/// Returns `None` if the validator index is out of bounds.
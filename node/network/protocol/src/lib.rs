@@ -251,22 +251,25 @@ impl View {
 
 /// A protocol-versioned type.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Versioned<V1> {
+pub enum Versioned<V1, V2> {
 	/// V1 type.
 	V1(V1),
+	/// V2 type.
+	V2(V2),
 }
 
-impl<V1: Clone> Versioned<&'_ V1> {
+impl<V1: Clone, V2: Clone> Versioned<&'_ V1, &'_ V2> {
 	/// Convert to a fully-owned version of the message.
-	pub fn clone_inner(&self) -> Versioned<V1> {
+	pub fn clone_inner(&self) -> Versioned<V1, V2> {
 		match *self {
 			Versioned::V1(inner) => Versioned::V1(inner.clone()),
+			Versioned::V2(inner) => Versioned::V2(inner.clone()),
 		}
 	}
 }
 
 /// All supported versions of the validation protocol message.
-pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol>;
+pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol, v2::ValidationProtocol>;
 
 impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	fn from(v1: v1::ValidationProtocol) -> Self {
@@ -274,8 +277,14 @@ impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	}
 }
 
+impl From<v2::ValidationProtocol> for VersionedValidationProtocol {
+	fn from(v2: v2::ValidationProtocol) -> Self {
+		VersionedValidationProtocol::V2(v2)
+	}
+}
+
 /// All supported versions of the collation protocol message.
-pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol>;
+pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol, v2::CollationProtocol>;
 
 impl From<v1::CollationProtocol> for VersionedCollationProtocol {
 	fn from(v1: v1::CollationProtocol) -> Self {
@@ -283,12 +292,19 @@ impl From<v1::CollationProtocol> for VersionedCollationProtocol {
 	}
 }
 
+impl From<v2::CollationProtocol> for VersionedCollationProtocol {
+	fn from(v2: v2::CollationProtocol) -> Self {
+		VersionedCollationProtocol::V2(v2)
+	}
+}
+
 macro_rules! impl_versioned_full_protocol_from {
 	($from:ty, $out:ty, $variant:ident) => {
 		impl From<$from> for $out {
 			fn from(versioned_from: $from) -> $out {
 				match versioned_from {
 					Versioned::V1(x) => Versioned::V1(x.into()),
+					Versioned::V2(x) => Versioned::V2(x.into()),
 				}
 			}
 		}
@@ -298,7 +314,7 @@ macro_rules! impl_versioned_full_protocol_from {
 /// Implement `TryFrom` for one versioned enum variant into the inner type.
 /// `$m_ty::$variant(inner) -> Ok(inner)`
 macro_rules! impl_versioned_try_from {
-	($from:ty, $out:ty, $v1_pat:pat => $v1_out:expr) => {
+	($from:ty, $out:ty, $v1_pat:pat => $v1_out:expr, $v2_pat:pat => $v2_out:expr) => {
 		impl TryFrom<$from> for $out {
 			type Error = crate::WrongVariant;
 
@@ -306,6 +322,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out)),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out)),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -318,6 +335,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out.clone())),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out.clone())),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -326,7 +344,8 @@ macro_rules! impl_versioned_try_from {
 }
 
 /// Version-annotated messages used by the bitfield distribution subsystem.
-pub type BitfieldDistributionMessage = Versioned<v1::BitfieldDistributionMessage>;
+pub type BitfieldDistributionMessage =
+	Versioned<v1::BitfieldDistributionMessage, v2::BitfieldDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	BitfieldDistributionMessage,
 	VersionedValidationProtocol,
@@ -335,11 +354,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	BitfieldDistributionMessage,
-	v1::ValidationProtocol::BitfieldDistribution(x) => x
+	v1::ValidationProtocol::BitfieldDistribution(x) => x,
+	v2::ValidationProtocol::BitfieldDistribution(x) => x
 );
 
 /// Version-annotated messages used by the statement distribution subsystem.
-pub type StatementDistributionMessage = Versioned<v1::StatementDistributionMessage>;
+pub type StatementDistributionMessage =
+	Versioned<v1::StatementDistributionMessage, v2::StatementDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	StatementDistributionMessage,
 	VersionedValidationProtocol,
@@ -348,11 +369,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	StatementDistributionMessage,
-	v1::ValidationProtocol::StatementDistribution(x) => x
+	v1::ValidationProtocol::StatementDistribution(x) => x,
+	v2::ValidationProtocol::StatementDistribution(x) => x
 );
 
 /// Version-annotated messages used by the approval distribution subsystem.
-pub type ApprovalDistributionMessage = Versioned<v1::ApprovalDistributionMessage>;
+pub type ApprovalDistributionMessage =
+	Versioned<v1::ApprovalDistributionMessage, v2::ApprovalDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	ApprovalDistributionMessage,
 	VersionedValidationProtocol,
@@ -361,11 +384,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	ApprovalDistributionMessage,
-	v1::ValidationProtocol::ApprovalDistribution(x) => x
+	v1::ValidationProtocol::ApprovalDistribution(x) => x,
+	v2::ValidationProtocol::ApprovalDistribution(x) => x
 );
 
 /// Version-annotated messages used by the gossip-support subsystem (this is void).
-pub type GossipSupportNetworkMessage = Versioned<v1::GossipSupportNetworkMessage>;
+pub type GossipSupportNetworkMessage =
+	Versioned<v1::GossipSupportNetworkMessage, v2::GossipSupportNetworkMessage>;
 // This is a void enum placeholder, so never gets sent over the wire.
 impl TryFrom<VersionedValidationProtocol> for GossipSupportNetworkMessage {
 	type Error = WrongVariant;
@@ -381,8 +406,9 @@ impl<'a> TryFrom<&'a VersionedValidationProtocol> for GossipSupportNetworkMessag
 	}
 }
 
-/// Version-annotated messages used by the bitfield distribution subsystem.
-pub type CollatorProtocolMessage = Versioned<v1::CollatorProtocolMessage>;
+/// Version-annotated messages used by the collator protocol subsystem.
+pub type CollatorProtocolMessage =
+	Versioned<v1::CollatorProtocolMessage, v2::CollatorProtocolMessage>;
 impl_versioned_full_protocol_from!(
 	CollatorProtocolMessage,
 	VersionedCollationProtocol,
@@ -391,7 +417,8 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedCollationProtocol,
 	CollatorProtocolMessage,
-	v1::CollationProtocol::CollatorProtocol(x) => x
+	v1::CollationProtocol::CollatorProtocol(x) => x,
+	v2::CollationProtocol::CollatorProtocol(x) => x
 );
 
 /// v1 notification protocol types.
@@ -551,3 +578,403 @@ pub mod v1 {
 		payload
 	}
 }
+
+/// v2 notification protocol types.
+///
+/// On the validation peer-set only the statement, bitfield and approval distribution messages
+/// changed in this version. The v2 validation protocol is a superset of v1: every v1 message is
+/// encoded the same way in v2, so v1 messages can be sent to peers on either version.
+pub mod v2 {
+	use bitvec::{order::Lsb0, slice::BitSlice, vec::BitVec};
+	use parity_scale_codec::{Compact, Decode, Encode};
+
+	use polkadot_primitives::{
+		CandidateHash, CandidateIndex, CollatorId, CollatorSignature, CompactStatement, Hash,
+		Id as ParaId, UncheckedSignedAvailabilityBitfield, ValidatorIndex, ValidatorSignature,
+	};
+
+	use polkadot_node_primitives::{
+		approval::{
+			IndirectAssignmentCert, IndirectSignedApprovalVote, IndirectSignedCoalescedApprovalVote,
+		},
+		UncheckedSignedFullStatement,
+	};
+
+	pub use super::v1::{
+		declare_signature_payload, GossipSupportNetworkMessage, StatementMetadata,
+	};
+
+	/// Network messages used by the bitfield distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum BitfieldDistributionMessage {
+		/// A signed availability bitfield for a given relay-parent hash.
+		#[codec(index = 0)]
+		Bitfield(Hash, UncheckedSignedAvailabilityBitfield),
+		/// A signed availability bitfield for a given relay-parent hash, with the bitfield in
+		/// compact encoding.
+		#[codec(index = 1)]
+		CompactBitfield(Hash, CompactSignedBitfield),
+	}
+
+	impl From<super::v1::BitfieldDistributionMessage> for BitfieldDistributionMessage {
+		fn from(message: super::v1::BitfieldDistributionMessage) -> Self {
+			match message {
+				super::v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) =>
+					BitfieldDistributionMessage::Bitfield(relay_parent, bitfield),
+			}
+		}
+	}
+
+	/// A signed availability bitfield with the bitfield in compact encoding.
+	///
+	/// The signature is over the reconstructed `AvailabilityBitfield`, exactly as for the full
+	/// encoding.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub struct CompactSignedBitfield {
+		/// The index of the validator which signed the bitfield.
+		pub validator_index: ValidatorIndex,
+		/// The signature of the validator over the reconstructed bitfield.
+		pub signature: ValidatorSignature,
+		/// The compactly encoded bitfield.
+		pub bits: CompactBitfield,
+	}
+
+	/// The number of bytes of the relay parent hash identifying the base of a bitfield delta.
+	pub const DELTA_BASE_PREFIX_LEN: usize = 4;
+
+	/// Compact encodings of an availability bitfield.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum CompactBitfield {
+		/// Lengths of alternating runs of unset and set bits, starting with a run of unset bits.
+		///
+		/// The length of the bitfield is the sum of all runs.
+		#[codec(index = 0)]
+		RunLength(Vec<Compact<u32>>),
+		/// The bits differing from the bitfield signed by the same validator under another relay
+		/// parent, which the receiver is known to have.
+		///
+		/// The positions of the flipped bits are given in increasing order, each one as its
+		/// distance to the previous flipped bit, minus one.
+		#[codec(index = 1)]
+		Delta {
+			/// The first bytes of the hash of the relay parent of the bitfield the delta is
+			/// against.
+			///
+			/// The full hash would often be larger than the whole bitfield.
+			base: [u8; DELTA_BASE_PREFIX_LEN],
+			/// The gaps between the flipped bits.
+			flipped: Vec<Compact<u32>>,
+		},
+	}
+
+	impl CompactBitfield {
+		/// Run-length encode the given bitfield.
+		pub fn run_length(bits: &BitSlice<u8, Lsb0>) -> Self {
+			let mut runs = Vec::new();
+			let mut current = false;
+			let mut run = 0u32;
+			for bit in bits.iter().by_vals() {
+				if bit != current {
+					runs.push(Compact(run));
+					current = bit;
+					run = 0;
+				}
+				run += 1;
+			}
+			runs.push(Compact(run));
+			CompactBitfield::RunLength(runs)
+		}
+
+		/// Encode the given bitfield as delta against the bitfield `base_bits` of relay parent
+		/// `base`.
+		///
+		/// Returns `None` if the lengths of both bitfields differ.
+		pub fn delta(
+			base: &Hash,
+			base_bits: &BitSlice<u8, Lsb0>,
+			bits: &BitSlice<u8, Lsb0>,
+		) -> Option<Self> {
+			if base_bits.len() != bits.len() {
+				return None
+			}
+
+			let mut flipped = Vec::new();
+			let mut next = 0;
+			for (i, (a, b)) in base_bits.iter().by_vals().zip(bits.iter().by_vals()).enumerate() {
+				if a != b {
+					flipped.push(Compact((i - next) as u32));
+					next = i + 1;
+				}
+			}
+			Some(CompactBitfield::Delta { base: delta_base_prefix(base), flipped })
+		}
+
+		/// Whether a bitfield of another relay parent is needed for reconstruction.
+		pub fn is_delta(&self) -> bool {
+			matches!(self, CompactBitfield::Delta { .. })
+		}
+
+		/// Whether the bitfield under the given relay parent is the base of this delta.
+		pub fn has_base(&self, relay_parent: &Hash) -> bool {
+			match self {
+				CompactBitfield::RunLength(_) => false,
+				CompactBitfield::Delta { base, .. } => base == &delta_base_prefix(relay_parent),
+			}
+		}
+
+		/// Reconstruct the bitfield.
+		///
+		/// `base_bits` must be the bitfield of a relay parent for which `has_base` holds, in case
+		/// of a delta. Returns `None`
+		/// if the encoding is invalid, refers to bits out of bounds of the base, or the
+		/// reconstructed bitfield would be longer than `max_len`.
+		pub fn reconstruct(
+			&self,
+			base_bits: Option<&BitSlice<u8, Lsb0>>,
+			max_len: usize,
+		) -> Option<BitVec<u8, Lsb0>> {
+			match self {
+				CompactBitfield::RunLength(runs) => {
+					let len =
+						runs.iter().try_fold(0usize, |len, run| len.checked_add(run.0 as usize))?;
+					if len > max_len {
+						return None
+					}
+
+					let mut bits = BitVec::with_capacity(len);
+					for (i, run) in runs.iter().enumerate() {
+						bits.resize(bits.len() + run.0 as usize, i % 2 == 1);
+					}
+					Some(bits)
+				},
+				CompactBitfield::Delta { flipped, .. } => {
+					let base_bits = base_bits?;
+					if base_bits.len() > max_len {
+						return None
+					}
+
+					let mut bits = base_bits.to_bitvec();
+					let mut next = 0usize;
+					for gap in flipped {
+						let i = next.checked_add(gap.0 as usize)?;
+						let mut bit = bits.get_mut(i)?;
+						*bit = !*bit;
+						next = i + 1;
+					}
+					Some(bits)
+				},
+			}
+		}
+	}
+
+	fn delta_base_prefix(relay_parent: &Hash) -> [u8; DELTA_BASE_PREFIX_LEN] {
+		let mut prefix = [0u8; DELTA_BASE_PREFIX_LEN];
+		prefix.copy_from_slice(&relay_parent.as_bytes()[..DELTA_BASE_PREFIX_LEN]);
+		prefix
+	}
+
+	/// Compact representation of the statements known about a single candidate.
+	///
+	/// Both bitfields are indexed by the `ValidatorIndex` of the signing validator and have one
+	/// bit per validator of the session.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub struct StatementFilter {
+		/// Validators whose `Seconded` statement for the candidate is known.
+		pub seconded: BitVec<u8, Lsb0>,
+		/// Validators whose `Valid` statement for the candidate is known.
+		pub valid: BitVec<u8, Lsb0>,
+	}
+
+	impl StatementFilter {
+		/// Create an empty filter for a session with `n_validators` validators.
+		pub fn new(n_validators: usize) -> Self {
+			StatementFilter {
+				seconded: BitVec::repeat(false, n_validators),
+				valid: BitVec::repeat(false, n_validators),
+			}
+		}
+
+		/// Whether the filter has the expected size for a session with `n_validators` validators.
+		pub fn has_len(&self, n_validators: usize) -> bool {
+			self.seconded.len() == n_validators && self.valid.len() == n_validators
+		}
+
+		/// Whether the filter contains no statements at all.
+		pub fn is_empty(&self) -> bool {
+			self.seconded.not_any() && self.valid.not_any()
+		}
+
+		/// Whether the statement of the given kind by the given validator is contained.
+		///
+		/// Returns `false` for validator indices out of bounds.
+		pub fn contains(&self, statement: &CompactStatement, validator: ValidatorIndex) -> bool {
+			self.bits(statement).get(validator.0 as usize).map_or(false, |bit| *bit)
+		}
+
+		/// Add the statement of the given kind by the given validator.
+		///
+		/// Statements by validator indices out of bounds are ignored.
+		pub fn insert(&mut self, statement: &CompactStatement, validator: ValidatorIndex) {
+			let bits = match statement {
+				CompactStatement::Seconded(_) => &mut self.seconded,
+				CompactStatement::Valid(_) => &mut self.valid,
+			};
+			if let Some(mut bit) = bits.get_mut(validator.0 as usize) {
+				*bit = true;
+			}
+		}
+
+		/// Whether all statements of this filter are contained in `other` as well.
+		pub fn is_subset_of(&self, other: &StatementFilter) -> bool {
+			let contained = |bits: &BitSlice<u8, Lsb0>, other: &BitSlice<u8, Lsb0>| {
+				bits.iter_ones().all(|i| other.get(i).map_or(false, |bit| *bit))
+			};
+			contained(&self.seconded, &other.seconded) && contained(&self.valid, &other.valid)
+		}
+
+		/// Iterate over all contained statements, given the candidate they are about.
+		pub fn statements(
+			&self,
+			candidate_hash: CandidateHash,
+		) -> impl Iterator<Item = (CompactStatement, ValidatorIndex)> + '_ {
+			let seconded = self.seconded.iter_ones().map(move |i| {
+				(CompactStatement::Seconded(candidate_hash), ValidatorIndex(i as u32))
+			});
+			let valid = self
+				.valid
+				.iter_ones()
+				.map(move |i| (CompactStatement::Valid(candidate_hash), ValidatorIndex(i as u32)));
+			seconded.chain(valid)
+		}
+
+		fn bits(&self, statement: &CompactStatement) -> &BitSlice<u8, Lsb0> {
+			match statement {
+				CompactStatement::Seconded(_) => &self.seconded,
+				CompactStatement::Valid(_) => &self.valid,
+			}
+		}
+	}
+
+	/// Announcement of the statements a peer holds about a candidate.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub struct StatementsAnnouncement {
+		/// Relay parent the candidate is relevant under.
+		pub relay_parent: Hash,
+		/// Hash of the candidate the statements are about.
+		pub candidate_hash: CandidateHash,
+		/// The statements known to the sender.
+		pub known: StatementFilter,
+	}
+
+	/// Network messages used by the statement distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum StatementDistributionMessage {
+		/// A signed full statement under a given relay-parent.
+		#[codec(index = 0)]
+		Statement(Hash, UncheckedSignedFullStatement),
+		/// Seconded statement with large payload (e.g. containing a runtime upgrade).
+		///
+		/// We only gossip the hash in that case, actual payloads can be fetched from sending node
+		/// via request/response.
+		#[codec(index = 1)]
+		LargeStatement(StatementMetadata),
+		/// Compact announcement of the statements the sender holds about a candidate.
+		///
+		/// The receiver fetches any statements it is missing via request/response.
+		#[codec(index = 2)]
+		Announcement(StatementsAnnouncement),
+	}
+
+	impl From<super::v1::StatementDistributionMessage> for StatementDistributionMessage {
+		fn from(message: super::v1::StatementDistributionMessage) -> Self {
+			match message {
+				super::v1::StatementDistributionMessage::Statement(relay_parent, statement) =>
+					StatementDistributionMessage::Statement(relay_parent, statement),
+				super::v1::StatementDistributionMessage::LargeStatement(metadata) =>
+					StatementDistributionMessage::LargeStatement(metadata),
+			}
+		}
+	}
+
+	/// Network messages used by the approval distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum ApprovalDistributionMessage {
+		/// Assignments for candidates in recent, unfinalized blocks.
+		///
+		/// Actually checking the assignment may yield a different result.
+		#[codec(index = 0)]
+		Assignments(Vec<(IndirectAssignmentCert, CandidateIndex)>),
+		/// Approvals for candidates in some recent, unfinalized block.
+		#[codec(index = 1)]
+		Approvals(Vec<IndirectSignedApprovalVote>),
+		/// Approvals covering multiple candidates of some recent, unfinalized block.
+		///
+		/// Only sent once the runtime enables coalescing of approvals, which implies that
+		/// validators understand this message.
+		#[codec(index = 2)]
+		CoalescedApprovals(Vec<IndirectSignedCoalescedApprovalVote>),
+	}
+
+	impl From<super::v1::ApprovalDistributionMessage> for ApprovalDistributionMessage {
+		fn from(message: super::v1::ApprovalDistributionMessage) -> Self {
+			match message {
+				super::v1::ApprovalDistributionMessage::Assignments(assignments) =>
+					ApprovalDistributionMessage::Assignments(assignments),
+				super::v1::ApprovalDistributionMessage::Approvals(approvals) =>
+					ApprovalDistributionMessage::Approvals(approvals),
+			}
+		}
+	}
+
+	/// All network messages on the validation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, derive_more::From)]
+	pub enum ValidationProtocol {
+		/// Bitfield distribution messages
+		#[codec(index = 1)]
+		#[from]
+		BitfieldDistribution(BitfieldDistributionMessage),
+		/// Statement distribution messages
+		#[codec(index = 3)]
+		#[from]
+		StatementDistribution(StatementDistributionMessage),
+		/// Approval distribution messages
+		#[codec(index = 4)]
+		#[from]
+		ApprovalDistribution(ApprovalDistributionMessage),
+	}
+
+	/// Network messages used by the collator protocol subsystem
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum CollatorProtocolMessage {
+		/// Declare the intent to advertise collations under a collator ID, attaching a
+		/// signature of the `PeerId` of the node using the given collator ID key.
+		#[codec(index = 0)]
+		Declare(CollatorId, ParaId, CollatorSignature),
+		/// Advertise a collation to a validator. Can only be sent once the peer has
+		/// declared that they are a collator with given ID.
+		///
+		/// In contrast to v1, the advertisement identifies the collation, so validators can
+		/// decide whether it is worth fetching before downloading the `PoV`.
+		#[codec(index = 1)]
+		AdvertiseCollation {
+			/// Hash of the relay parent the advertised candidate is built on.
+			relay_parent: Hash,
+			/// Hash of the candidate receipt.
+			candidate_hash: CandidateHash,
+			/// Hash of the para head the candidate builds upon.
+			parent_head_data_hash: Hash,
+		},
+		/// A collation sent to a validator was seconded.
+		#[codec(index = 4)]
+		CollationSeconded(Hash, UncheckedSignedFullStatement),
+	}
+
+	/// All network messages on the collation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, derive_more::From)]
+	pub enum CollationProtocol {
+		/// Collator protocol messages
+		#[codec(index = 0)]
+		#[from]
+		CollatorProtocol(CollatorProtocolMessage),
+	}
+}
@@ -72,7 +72,7 @@ impl PeerSet {
 		// Networking layer relies on `get_main_name()` being the main name of the protocol
 		// for peersets and connection management.
 		let protocol = peerset_protocol_names.get_main_name(self);
-		let fallback_names = PeerSetProtocolNames::get_fallback_names(self);
+		let fallback_names = peerset_protocol_names.get_fallback_names(self);
 		let max_notification_size = self.get_max_notification_size(is_authority);
 
 		match self {
@@ -118,8 +118,8 @@ impl PeerSet {
 	/// of the main protocol name reported by [`PeerSetProtocolNames::get_main_name()`].
 	pub fn get_main_version(self) -> ProtocolVersion {
 		match self {
-			PeerSet::Validation => ValidationVersion::V1.into(),
-			PeerSet::Collation => CollationVersion::V1.into(),
+			PeerSet::Validation => ValidationVersion::V2.into(),
+			PeerSet::Collation => CollationVersion::V2.into(),
 		}
 	}
 
@@ -144,12 +144,16 @@ impl PeerSet {
 			PeerSet::Validation =>
 				if version == ValidationVersion::V1.into() {
 					Some("validation/1")
+				} else if version == ValidationVersion::V2.into() {
+					Some("validation/2")
 				} else {
 					None
 				},
 			PeerSet::Collation =>
 				if version == CollationVersion::V1.into() {
 					Some("collation/1")
+				} else if version == CollationVersion::V2.into() {
+					Some("collation/2")
 				} else {
 					None
 				},
@@ -211,6 +215,9 @@ impl From<ProtocolVersion> for u32 {
 pub enum ValidationVersion {
 	/// The first version.
 	V1 = 1,
+	/// The second version, adding compact announcements of the statements known about a
+	/// candidate.
+	V2 = 2,
 }
 
 /// Supported collation protocol versions. Only versions defined here must be used in the codebase.
@@ -218,6 +225,9 @@ pub enum ValidationVersion {
 pub enum CollationVersion {
 	/// The first version.
 	V1 = 1,
+	/// The second version, advertising the candidate hash and parent head-data hash along with
+	/// the relay parent.
+	V2 = 2,
 }
 
 impl From<ValidationVersion> for ProtocolVersion {
@@ -374,10 +384,23 @@ impl PeerSetProtocolNames {
 		.into()
 	}
 
-	/// Get the protocol fallback names. Currently only holds the legacy name
-	/// for `LEGACY_PROTOCOL_VERSION` = 1.
-	fn get_fallback_names(protocol: PeerSet) -> Vec<ProtocolName> {
-		std::iter::once(Self::get_legacy_name(protocol)).collect()
+	/// Get the protocol fallback names: the names of all versions older than the main one,
+	/// newest first, followed by the legacy name for `LEGACY_PROTOCOL_VERSION` = 1.
+	fn get_fallback_names(&self, protocol: PeerSet) -> Vec<ProtocolName> {
+		let main_version = u32::from(protocol.get_main_version());
+		let mut versions: Vec<ProtocolVersion> = self
+			.names
+			.keys()
+			.filter(|(p, version)| *p == protocol && u32::from(*version) < main_version)
+			.map(|(_, version)| *version)
+			.collect();
+		versions.sort_by_key(|version| std::cmp::Reverse(u32::from(*version)));
+
+		versions
+			.into_iter()
+			.map(|version| self.get_name(protocol, version))
+			.chain(std::iter::once(Self::get_legacy_name(protocol)))
+			.collect()
 	}
 }
 
@@ -452,26 +475,40 @@ mod tests {
 		]);
 		let protocol_names = PeerSetProtocolNames::new(genesis_hash, None);
 
-		let validation_main =
+		let validation_v1 =
 			"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/validation/1";
 		assert_eq!(
-			protocol_names.try_get_protocol(&validation_main.into()),
+			protocol_names.try_get_protocol(&validation_v1.into()),
 			Some((PeerSet::Validation, TestVersion(1).into())),
 		);
 
+		let validation_main =
+			"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/validation/2";
+		assert_eq!(
+			protocol_names.try_get_protocol(&validation_main.into()),
+			Some((PeerSet::Validation, TestVersion(2).into())),
+		);
+
 		let validation_legacy = "/polkadot/validation/1";
 		assert_eq!(
 			protocol_names.try_get_protocol(&validation_legacy.into()),
 			Some((PeerSet::Validation, TestVersion(1).into())),
 		);
 
-		let collation_main =
+		let collation_v1 =
 			"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/collation/1";
 		assert_eq!(
-			protocol_names.try_get_protocol(&collation_main.into()),
+			protocol_names.try_get_protocol(&collation_v1.into()),
 			Some((PeerSet::Collation, TestVersion(1).into())),
 		);
 
+		let collation_main =
+			"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/collation/2";
+		assert_eq!(
+			protocol_names.try_get_protocol(&collation_main.into()),
+			Some((PeerSet::Collation, TestVersion(2).into())),
+		);
+
 		let collation_legacy = "/polkadot/collation/1";
 		assert_eq!(
 			protocol_names.try_get_protocol(&collation_legacy.into()),
@@ -479,6 +516,32 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn older_protocol_versions_are_fallbacks() {
+		let genesis_hash = Hash::from([
+			122, 200, 116, 29, 232, 183, 20, 109, 138, 86, 23, 253, 70, 41, 20, 85, 127, 230, 60,
+			38, 90, 127, 28, 16, 231, 218, 227, 40, 88, 238, 187, 128,
+		]);
+		let protocol_names = PeerSetProtocolNames::new(genesis_hash, None);
+
+		assert_eq!(
+			protocol_names.get_fallback_names(PeerSet::Validation),
+			vec![
+				"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/validation/1"
+					.into(),
+				"/polkadot/validation/1".into(),
+			],
+		);
+		assert_eq!(
+			protocol_names.get_fallback_names(PeerSet::Collation),
+			vec![
+				"/7ac8741de8b7146d8a5617fd462914557fe63c265a7f1c10e7dae32858eebb80/collation/1"
+					.into(),
+				"/polkadot/collation/1".into(),
+			],
+		);
+	}
+
 	#[test]
 	fn all_protocol_versions_are_registered() {
 		let genesis_hash = Hash::from([
@@ -30,7 +30,12 @@
 //! `trait IsRequest` .... A trait describing a particular request. It is used for gathering meta
 //! data, like what is the corresponding response type.
 //!
-//!  Versioned (v1 module): The actual requests and responses as sent over the network.
+//!  Versioned (v1 and v2 modules): The actual requests and responses as sent over the network.
+//!
+//! Multiple versions of a protocol are registered side by side, each as a `Protocol` of its own.
+//! Requests of a newer version can be sent with a request of an older version as fallback (see
+//! `OutgoingRequest::new_with_fallback`), which is sent instead if the peer does not support the
+//! newer version. This way protocols can be upgraded without all nodes upgrading at once.
 
 use std::{collections::HashMap, time::Duration, u64};
 
@@ -55,6 +60,9 @@ pub use outgoing::{OutgoingRequest, OutgoingResult, Recipient, Requests, Respons
 /// Actual versioned requests and responses, that are sent over the wire.
 pub mod v1;
 
+/// Actual versioned requests and responses, that are sent over the wire, version 2.
+pub mod v2;
+
 /// A protocol per subsystem seems to make the most sense, this way we don't need any dispatching
 /// within protocols.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EnumIter)]
@@ -63,12 +71,16 @@ pub enum Protocol {
 	ChunkFetchingV1,
 	/// Protocol for fetching collations from collators.
 	CollationFetchingV1,
+	/// Protocol for fetching collations from collators, identified by the advertised candidate.
+	CollationFetchingV2,
 	/// Protocol for fetching seconded PoVs from validators of the same group.
 	PoVFetchingV1,
 	/// Protocol for fetching available data.
 	AvailableDataFetchingV1,
 	/// Fetching of statements that are too large for gossip.
 	StatementFetchingV1,
+	/// Fetching of the statements about a candidate, which were announced by a peer.
+	CandidateStatementsFetchingV1,
 	/// Sending of dispute statements with application level confirmations.
 	DisputeSendingV1,
 }
@@ -167,15 +179,16 @@ impl Protocol {
 				request_timeout: CHUNK_REQUEST_TIMEOUT,
 				inbound_queue: tx,
 			},
-			Protocol::CollationFetchingV1 => RequestResponseConfig {
-				name,
-				fallback_names,
-				max_request_size: 1_000,
-				max_response_size: POV_RESPONSE_SIZE,
-				// Taken from initial implementation in collator protocol:
-				request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
-				inbound_queue: tx,
-			},
+			Protocol::CollationFetchingV1 | Protocol::CollationFetchingV2 =>
+				RequestResponseConfig {
+					name,
+					fallback_names,
+					max_request_size: 1_000,
+					max_response_size: POV_RESPONSE_SIZE,
+					// Taken from initial implementation in collator protocol:
+					request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
+					inbound_queue: tx,
+				},
 			Protocol::PoVFetchingV1 => RequestResponseConfig {
 				name,
 				fallback_names,
@@ -211,6 +224,16 @@ impl Protocol {
 				request_timeout: Duration::from_secs(1),
 				inbound_queue: tx,
 			},
+			Protocol::CandidateStatementsFetchingV1 => RequestResponseConfig {
+				name,
+				fallback_names,
+				// Requests carry a bitfield of known statements, two bits per validator.
+				max_request_size: 10_000,
+				// Responses might contain a `Seconded` statement with a large payload.
+				max_response_size: STATEMENT_RESPONSE_SIZE,
+				request_timeout: STATEMENTS_TIMEOUT,
+				inbound_queue: tx,
+			},
 			Protocol::DisputeSendingV1 => RequestResponseConfig {
 				name,
 				fallback_names,
@@ -234,7 +257,7 @@ impl Protocol {
 			// as well.
 			Protocol::ChunkFetchingV1 => 100,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
-			Protocol::CollationFetchingV1 => 10,
+			Protocol::CollationFetchingV1 | Protocol::CollationFetchingV2 => 10,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
 			Protocol::PoVFetchingV1 => 10,
 			// Validators are constantly self-selecting to request available data which may lead
@@ -261,6 +284,10 @@ impl Protocol {
 				);
 				size as usize
 			},
+			// Grid neighbors request the statements they are missing for each candidate we
+			// announce to them, which happens in bursts whenever they learn about a new relay
+			// parent.
+			Protocol::CandidateStatementsFetchingV1 => 100,
 			// Incoming requests can get bursty, we should also be able to handle them fast on
 			// average, so something in the ballpark of 100 should be fine. Nodes will retry on
 			// failure, so having a good value here is mostly about performance tuning.
@@ -270,22 +297,81 @@ impl Protocol {
 
 	/// Fallback protocol names of this protocol, as understood by substrate networking.
 	fn get_fallback_names(self) -> Vec<ProtocolName> {
-		std::iter::once(self.get_legacy_name().into()).collect()
+		self.get_legacy_name().into_iter().map(Into::into).collect()
+	}
+
+	/// Legacy protocol name associated with each protocol.
+	///
+	/// Only protocols which existed before names were based on the genesis hash have one.
+	const fn get_legacy_name(self) -> Option<&'static str> {
+		match self {
+			Protocol::ChunkFetchingV1 => Some("/polkadot/req_chunk/1"),
+			Protocol::CollationFetchingV1 => Some("/polkadot/req_collation/1"),
+			Protocol::CollationFetchingV2 => None,
+			Protocol::PoVFetchingV1 => Some("/polkadot/req_pov/1"),
+			Protocol::AvailableDataFetchingV1 => Some("/polkadot/req_available_data/1"),
+			Protocol::StatementFetchingV1 => Some("/polkadot/req_statement/1"),
+			Protocol::CandidateStatementsFetchingV1 => None,
+			Protocol::DisputeSendingV1 => Some("/polkadot/send_dispute/1"),
+		}
+	}
+
+	/// Name of the protocol regardless of its version, e.g. `req_collation`.
+	pub const fn get_base_name(self) -> &'static str {
+		match self {
+			Protocol::ChunkFetchingV1 => "req_chunk",
+			Protocol::CollationFetchingV1 | Protocol::CollationFetchingV2 => "req_collation",
+			Protocol::PoVFetchingV1 => "req_pov",
+			Protocol::AvailableDataFetchingV1 => "req_available_data",
+			Protocol::StatementFetchingV1 => "req_statement",
+			Protocol::CandidateStatementsFetchingV1 => "req_candidate_statements",
+			Protocol::DisputeSendingV1 => "send_dispute",
+		}
+	}
+
+	/// The version of the protocol.
+	pub const fn get_version(self) -> ReqProtocolVersion {
+		match self {
+			Protocol::ChunkFetchingV1 |
+			Protocol::CollationFetchingV1 |
+			Protocol::PoVFetchingV1 |
+			Protocol::AvailableDataFetchingV1 |
+			Protocol::StatementFetchingV1 |
+			Protocol::CandidateStatementsFetchingV1 |
+			Protocol::DisputeSendingV1 => 1,
+			Protocol::CollationFetchingV2 => 2,
+		}
 	}
 
-	/// Legacy protocol name associated with each peer set.
-	const fn get_legacy_name(self) -> &'static str {
+	/// The next older version of the protocol, if any.
+	///
+	/// Requests on this protocol can be sent with a request on the older version as fallback.
+	pub const fn get_older_version(self) -> Option<Protocol> {
 		match self {
-			Protocol::ChunkFetchingV1 => "/polkadot/req_chunk/1",
-			Protocol::CollationFetchingV1 => "/polkadot/req_collation/1",
-			Protocol::PoVFetchingV1 => "/polkadot/req_pov/1",
-			Protocol::AvailableDataFetchingV1 => "/polkadot/req_available_data/1",
-			Protocol::StatementFetchingV1 => "/polkadot/req_statement/1",
-			Protocol::DisputeSendingV1 => "/polkadot/send_dispute/1",
+			Protocol::CollationFetchingV2 => Some(Protocol::CollationFetchingV1),
+			Protocol::ChunkFetchingV1 |
+			Protocol::CollationFetchingV1 |
+			Protocol::PoVFetchingV1 |
+			Protocol::AvailableDataFetchingV1 |
+			Protocol::StatementFetchingV1 |
+			Protocol::CandidateStatementsFetchingV1 |
+			Protocol::DisputeSendingV1 => None,
 		}
 	}
+
+	/// All versions of this protocol, newest first.
+	pub fn get_versions(self) -> impl Iterator<Item = Protocol> {
+		let newest = Protocol::iter()
+			.filter(|p| p.get_base_name() == self.get_base_name())
+			.max_by_key(|p| p.get_version())
+			.unwrap_or(self);
+		std::iter::successors(Some(newest), |p| p.get_older_version())
+	}
 }
 
+/// Version of a request-response protocol.
+pub type ReqProtocolVersion = u32;
+
 /// Common properties of any `Request`.
 pub trait IsRequest {
 	/// Each request has a corresponding `Response`.
@@ -330,15 +416,57 @@ impl ReqProtocolNames {
 			format!("/{}", hex::encode(genesis_hash))
 		};
 
-		let short_name = match protocol {
-			Protocol::ChunkFetchingV1 => "/req_chunk/1",
-			Protocol::CollationFetchingV1 => "/req_collation/1",
-			Protocol::PoVFetchingV1 => "/req_pov/1",
-			Protocol::AvailableDataFetchingV1 => "/req_available_data/1",
-			Protocol::StatementFetchingV1 => "/req_statement/1",
-			Protocol::DisputeSendingV1 => "/send_dispute/1",
-		};
+		format!("{}/{}/{}", prefix, protocol.get_base_name(), protocol.get_version()).into()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn protocol_names_are_versioned() {
+		let genesis_hash = [0u8; 32];
+		let names = ReqProtocolNames::new(genesis_hash, None);
+		let prefix = format!("/{}", hex::encode(genesis_hash));
+
+		assert_eq!(
+			names.get_name(Protocol::CollationFetchingV1),
+			ProtocolName::from(format!("{}/req_collation/1", prefix)),
+		);
+		assert_eq!(
+			names.get_name(Protocol::CollationFetchingV2),
+			ProtocolName::from(format!("{}/req_collation/2", prefix)),
+		);
+		assert_eq!(
+			names.get_name(Protocol::DisputeSendingV1),
+			ProtocolName::from(format!("{}/send_dispute/1", prefix)),
+		);
+		assert_eq!(
+			names.get_name(Protocol::CandidateStatementsFetchingV1),
+			ProtocolName::from(format!("{}/req_candidate_statements/1", prefix)),
+		);
+	}
+
+	#[test]
+	fn versions_are_listed_newest_first() {
+		let versions = |protocol: Protocol| protocol.get_versions().collect::<Vec<_>>();
+
+		assert_eq!(
+			versions(Protocol::CollationFetchingV1),
+			vec![Protocol::CollationFetchingV2, Protocol::CollationFetchingV1],
+		);
+		assert_eq!(
+			versions(Protocol::CollationFetchingV2),
+			vec![Protocol::CollationFetchingV2, Protocol::CollationFetchingV1],
+		);
+		assert_eq!(versions(Protocol::ChunkFetchingV1), vec![Protocol::ChunkFetchingV1]);
 
-		format!("{}{}", prefix, short_name).into()
+		for protocol in Protocol::iter() {
+			if let Some(older) = protocol.get_older_version() {
+				assert_eq!(older.get_base_name(), protocol.get_base_name());
+				assert!(older.get_version() < protocol.get_version());
+			}
+		}
 	}
 }
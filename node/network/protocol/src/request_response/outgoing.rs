@@ -23,7 +23,7 @@ use sc_network::PeerId;
 
 use polkadot_primitives::AuthorityDiscoveryId;
 
-use super::{v1, IsRequest, Protocol};
+use super::{v1, v2, IsRequest, Protocol};
 
 /// All requests that can be sent to the network bridge via `NetworkBridgeTxMessage::SendRequest`.
 #[derive(Debug)]
@@ -32,12 +32,20 @@ pub enum Requests {
 	ChunkFetchingV1(OutgoingRequest<v1::ChunkFetchingRequest>),
 	/// Fetch a collation from a collator which previously announced it.
 	CollationFetchingV1(OutgoingRequest<v1::CollationFetchingRequest>),
+	/// Fetch a collation from a collator which previously announced it, identified by the
+	/// advertised candidate. Falls back to `CollationFetchingV1`.
+	CollationFetchingV2(
+		OutgoingRequest<v2::CollationFetchingRequest, v1::CollationFetchingRequest>,
+	),
 	/// Fetch a PoV from a validator which previously sent out a seconded statement.
 	PoVFetchingV1(OutgoingRequest<v1::PoVFetchingRequest>),
 	/// Request full available data from a node.
 	AvailableDataFetchingV1(OutgoingRequest<v1::AvailableDataFetchingRequest>),
 	/// Requests for fetching large statements as part of statement distribution.
 	StatementFetchingV1(OutgoingRequest<v1::StatementFetchingRequest>),
+	/// Requests for fetching announced statements about a candidate as part of statement
+	/// distribution.
+	CandidateStatementsFetchingV1(OutgoingRequest<v1::CandidateStatementsFetchingRequest>),
 	/// Requests for notifying about an ongoing dispute.
 	DisputeSendingV1(OutgoingRequest<v1::DisputeRequest>),
 }
@@ -48,13 +56,31 @@ impl Requests {
 		match self {
 			Self::ChunkFetchingV1(_) => Protocol::ChunkFetchingV1,
 			Self::CollationFetchingV1(_) => Protocol::CollationFetchingV1,
+			Self::CollationFetchingV2(_) => Protocol::CollationFetchingV2,
 			Self::PoVFetchingV1(_) => Protocol::PoVFetchingV1,
 			Self::AvailableDataFetchingV1(_) => Protocol::AvailableDataFetchingV1,
 			Self::StatementFetchingV1(_) => Protocol::StatementFetchingV1,
+			Self::CandidateStatementsFetchingV1(_) => Protocol::CandidateStatementsFetchingV1,
 			Self::DisputeSendingV1(_) => Protocol::DisputeSendingV1,
 		}
 	}
 
+	/// The size of the encoded request payload.
+	///
+	/// A fallback request is only sent instead of the payload, so it is not taken into account.
+	pub fn payload_size(&self) -> usize {
+		match self {
+			Self::ChunkFetchingV1(r) => r.payload.encoded_size(),
+			Self::CollationFetchingV1(r) => r.payload.encoded_size(),
+			Self::CollationFetchingV2(r) => r.payload.encoded_size(),
+			Self::PoVFetchingV1(r) => r.payload.encoded_size(),
+			Self::AvailableDataFetchingV1(r) => r.payload.encoded_size(),
+			Self::StatementFetchingV1(r) => r.payload.encoded_size(),
+			Self::CandidateStatementsFetchingV1(r) => r.payload.encoded_size(),
+			Self::DisputeSendingV1(r) => r.payload.encoded_size(),
+		}
+	}
+
 	/// Encode the request.
 	///
 	/// The corresponding protocol is returned as well, as we are now leaving typed territory.
@@ -66,9 +92,11 @@ impl Requests {
 		match self {
 			Self::ChunkFetchingV1(r) => r.encode_request(),
 			Self::CollationFetchingV1(r) => r.encode_request(),
+			Self::CollationFetchingV2(r) => r.encode_request(),
 			Self::PoVFetchingV1(r) => r.encode_request(),
 			Self::AvailableDataFetchingV1(r) => r.encode_request(),
 			Self::StatementFetchingV1(r) => r.encode_request(),
+			Self::CandidateStatementsFetchingV1(r) => r.encode_request(),
 			Self::DisputeSendingV1(r) => r.encode_request(),
 		}
 	}
@@ -117,12 +145,19 @@ impl RequestError {
 /// expected to be already connected to.
 /// When using `Recipient::Authority`, the addresses can be found thanks to the authority
 /// discovery system.
+///
+/// A request can carry a fallback request on an older version of the protocol, which is sent
+/// instead if the peer does not support the protocol of `payload`. Both requests have to share
+/// the response type.
 #[derive(Debug)]
-pub struct OutgoingRequest<Req> {
+pub struct OutgoingRequest<Req, FallbackReq = Req> {
 	/// Intended recipient of this request.
 	pub peer: Recipient,
 	/// The actual request to send over the wire.
 	pub payload: Req,
+	/// The request to send if the peer does not support the protocol of `payload`, along with
+	/// its protocol.
+	pub fallback_request: Option<(FallbackReq, Protocol)>,
 	/// Sender which is used by networking to get us back a response.
 	pub pending_response: ResponseSender,
 }
@@ -153,7 +188,34 @@ where
 		payload: Req,
 	) -> (Self, impl Future<Output = OutgoingResult<Req::Response>>) {
 		let (tx, rx) = oneshot::channel();
-		let r = Self { peer, payload, pending_response: tx };
+		let r = Self { peer, payload, fallback_request: None, pending_response: tx };
+		(r, receive_response::<Req>(rx))
+	}
+}
+
+impl<Req, FallbackReq> OutgoingRequest<Req, FallbackReq>
+where
+	Req: IsRequest + Encode,
+	Req::Response: Decode,
+	FallbackReq: IsRequest<Response = Req::Response> + Encode,
+{
+	/// Create a new `OutgoingRequest` with a fallback request.
+	///
+	/// The fallback request is sent instead of `payload`, if the peer does not support the
+	/// protocol of `payload`. As both requests share the response type, the returned receiver
+	/// works the same as the one returned by [`OutgoingRequest::new`].
+	pub fn new_with_fallback(
+		peer: Recipient,
+		payload: Req,
+		fallback_request: FallbackReq,
+	) -> (Self, impl Future<Output = OutgoingResult<Req::Response>>) {
+		let (tx, rx) = oneshot::channel();
+		let r = Self {
+			peer,
+			payload,
+			fallback_request: Some((fallback_request, FallbackReq::PROTOCOL)),
+			pending_response: tx,
+		};
 		(r, receive_response::<Req>(rx))
 	}
 
@@ -162,8 +224,14 @@ where
 	/// As this throws away type information, we also return the `Protocol` this encoded request
 	/// adheres to.
 	pub fn encode_request(self) -> (Protocol, OutgoingRequest<Vec<u8>>) {
-		let OutgoingRequest { peer, payload, pending_response } = self;
-		let encoded = OutgoingRequest { peer, payload: payload.encode(), pending_response };
+		let OutgoingRequest { peer, payload, fallback_request, pending_response } = self;
+		let encoded = OutgoingRequest {
+			peer,
+			payload: payload.encode(),
+			fallback_request: fallback_request
+				.map(|(fallback, protocol)| (fallback.encode(), protocol)),
+			pending_response,
+		};
 		(Req::PROTOCOL, encoded)
 	}
 }
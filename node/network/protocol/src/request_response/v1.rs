@@ -20,12 +20,14 @@ use parity_scale_codec::{Decode, Encode};
 
 use polkadot_node_primitives::{
 	AvailableData, DisputeMessage, ErasureChunk, PoV, Proof, UncheckedDisputeMessage,
+	UncheckedSignedFullStatement,
 };
 use polkadot_primitives::{
 	CandidateHash, CandidateReceipt, CommittedCandidateReceipt, Hash, Id as ParaId, ValidatorIndex,
 };
 
 use super::{IsRequest, Protocol};
+use crate::v2::StatementFilter;
 
 /// Request an availability chunk.
 #[derive(Debug, Copy, Clone, Encode, Decode)]
@@ -191,6 +193,32 @@ impl IsRequest for StatementFetchingRequest {
 	const PROTOCOL: Protocol = Protocol::StatementFetchingV1;
 }
 
+/// Request the statements about a candidate the requester does not know yet.
+///
+/// Sent in reaction to a statements announcement of the peer.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct CandidateStatementsFetchingRequest {
+	/// Relay parent the candidate is relevant under.
+	pub relay_parent: Hash,
+	/// Hash of the candidate the statements are about.
+	pub candidate_hash: CandidateHash,
+	/// Statements the requester already knows, those will not be sent.
+	pub known: StatementFilter,
+}
+
+/// Respond with the requested statements.
+#[derive(Debug, Clone, Encode, Decode)]
+pub enum CandidateStatementsFetchingResponse {
+	/// Statements unknown to the requester, `Seconded` statements come first.
+	#[codec(index = 0)]
+	Statements(Vec<UncheckedSignedFullStatement>),
+}
+
+impl IsRequest for CandidateStatementsFetchingRequest {
+	type Response = CandidateStatementsFetchingResponse;
+	const PROTOCOL: Protocol = Protocol::CandidateStatementsFetchingV1;
+}
+
 /// A dispute request.
 ///
 /// Contains an invalid vote a valid one for a particular candidate in a given session.
@@ -0,0 +1,45 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Requests and responses as sent over the wire for the individual protocols, version 2.
+//!
+//! Requests of this version fall back to their [`v1`](super::v1) counterpart, if the peer does
+//! not support version 2 of the protocol.
+
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_primitives::{CandidateHash, Hash, Id as ParaId};
+
+use super::{v1, IsRequest, Protocol};
+
+/// Request the advertised collation at that relay-parent.
+///
+/// Unlike [`v1::CollationFetchingRequest`], the collation is identified by the candidate hash
+/// advertised by the collator.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct CollationFetchingRequest {
+	/// Relay parent we want a collation for.
+	pub relay_parent: Hash,
+	/// The `ParaId` of the collation.
+	pub para_id: ParaId,
+	/// Hash of the candidate we want.
+	pub candidate_hash: CandidateHash,
+}
+
+impl IsRequest for CollationFetchingRequest {
+	type Response = v1::CollationFetchingResponse;
+	const PROTOCOL: Protocol = Protocol::CollationFetchingV2;
+}
@@ -76,6 +76,10 @@ pub enum Error {
 	// Responder no longer waits for our data. (Should not happen right now.)
 	#[error("Oneshot `GetData` channel closed")]
 	ResponderGetDataCanceled,
+
+	// Responder no longer waits for our statements. (Should not happen right now.)
+	#[error("Oneshot `GetStatements` channel closed")]
+	ResponderGetStatementsCanceled,
 }
 
 /// Utility for eating top level errors and log them.
@@ -18,6 +18,11 @@
 //!
 //! This is responsible for distributing signed statements about candidate
 //! validity among validators.
+//!
+//! Statements are propagated along the grid topology. Peers on version 2 of the validation
+//! protocol only receive statements from their grid neighbors and the members of the backing
+//! group. When they learn about a new relay parent, grid neighbors announce the statements they
+//! hold compactly and the missing statements get fetched via request/response.
 
 #![deny(unused_crate_dependencies)]
 #![warn(missing_docs)]
@@ -28,9 +33,10 @@ use parity_scale_codec::Encode;
 use polkadot_node_network_protocol::{
 	self as net_protocol,
 	grid_topology::{GridNeighbors, RequiredRouting, SessionBoundGridTopologyStorage},
-	peer_set::{IsAuthority, PeerSet},
+	peer_set::{IsAuthority, PeerSet, ProtocolVersion, ValidationVersion},
 	request_response::{v1 as request_v1, IncomingRequestReceiver},
 	v1::{self as protocol_v1, StatementMetadata},
+	v2::{self as protocol_v2, StatementFilter, StatementsAnnouncement},
 	IfDisconnected, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
 use polkadot_node_primitives::{SignedFullStatement, Statement, UncheckedSignedFullStatement};
@@ -67,13 +73,13 @@ use fatality::Nested;
 mod error;
 pub use error::{Error, FatalError, JfyiError, Result};
 
-/// Background task logic for requesting of large statements.
+/// Background task logic for requesting of large statements and announced statements.
 mod requester;
-use requester::{fetch, RequesterMessage};
+use requester::{fetch, fetch_statements, RequesterMessage};
 
-/// Background task logic for responding for large statements.
+/// Background task logic for responding for large statements and announced statements.
 mod responder;
-use responder::{respond, ResponderMessage};
+use responder::{respond, respond_with_statements, ResponderMessage};
 
 /// Metrics for the statement distribution
 pub(crate) mod metrics;
@@ -92,6 +98,10 @@ const COST_UNEXPECTED_STATEMENT_REMOTE: Rep =
 
 const COST_FETCH_FAIL: Rep =
 	Rep::CostMinor("Requesting `CommittedCandidateReceipt` from peer failed");
+const COST_STATEMENTS_FETCH_FAIL: Rep =
+	Rep::CostMinor("Requesting announced statements from peer failed");
+const COST_UNEXPECTED_ANNOUNCEMENT: Rep = Rep::CostMinor("Unexpected statements announcement");
+const COST_INVALID_ANNOUNCEMENT: Rep = Rep::CostMajor("Invalid statements announcement");
 const COST_INVALID_SIGNATURE: Rep = Rep::CostMajor("Invalid Statement Signature");
 const COST_WRONG_HASH: Rep = Rep::CostMajor("Received candidate had wrong hash");
 const COST_DUPLICATE_STATEMENT: Rep =
@@ -122,6 +132,9 @@ pub struct StatementDistributionSubsystem<R> {
 	keystore: SyncCryptoStorePtr,
 	/// Receiver for incoming large statement requests.
 	req_receiver: Option<IncomingRequestReceiver<request_v1::StatementFetchingRequest>>,
+	/// Receiver for incoming requests for announced statements.
+	statements_req_receiver:
+		Option<IncomingRequestReceiver<request_v1::CandidateStatementsFetchingRequest>>,
 	/// Prometheus metrics
 	metrics: Metrics,
 	/// Pseudo-random generator for peers selection logic
@@ -228,6 +241,14 @@ struct PeerRelayParentKnowledge {
 	/// fingerprints of all statements a peer should be aware of: those that
 	/// were sent to us by the peer.
 	received_statements: HashSet<(CompactStatement, ValidatorIndex)>,
+	/// candidates we announced our statements about to the peer. The peer may request the
+	/// statements it is missing about those candidates from us.
+	announced_candidates: HashSet<CandidateHash>,
+	/// candidates the peer announced its statements about to us.
+	received_announcements: HashSet<CandidateHash>,
+	/// fingerprints of all statements a peer should be aware of: those that
+	/// the peer announced to us.
+	announced_statements: HashSet<(CompactStatement, ValidatorIndex)>,
 	/// How many candidates this peer is aware of for each given validator index.
 	seconded_counts: HashMap<ValidatorIndex, VcPerPeerTracker>,
 	/// How many statements we've received for each candidate that we're aware of.
@@ -296,7 +317,8 @@ impl PeerRelayParentKnowledge {
 	/// state, `false` otherwise.
 	fn can_send(&self, fingerprint: &(CompactStatement, ValidatorIndex)) -> bool {
 		let already_known = self.sent_statements.contains(fingerprint) ||
-			self.received_statements.contains(fingerprint);
+			self.received_statements.contains(fingerprint) ||
+			self.announced_statements.contains(fingerprint);
 
 		if already_known {
 			return false
@@ -378,6 +400,41 @@ impl PeerRelayParentKnowledge {
 		Ok(fresh)
 	}
 
+	/// Note a statements announcement received from the peer.
+	///
+	/// Provide the maximum number of candidates the peer may announce. Every announced candidate
+	/// must have been seconded, so `VC_THRESHOLD * n_validators` is a sensible maximum.
+	///
+	/// This returns an error if the peer should not have sent us this announcement. If this
+	/// returns `Ok`, the peer is known to be aware of the candidate and all announced statements.
+	fn receive_announcement(
+		&mut self,
+		candidate_hash: CandidateHash,
+		known: &StatementFilter,
+		max_announcements: usize,
+	) -> std::result::Result<(), Rep> {
+		if self.received_announcements.contains(&candidate_hash) {
+			return Err(COST_UNEXPECTED_ANNOUNCEMENT)
+		}
+
+		if self.received_announcements.len() >= max_announcements {
+			return Err(COST_APPARENT_FLOOD)
+		}
+
+		// Nobody can know statements about a candidate, which has not been seconded.
+		if !known
+			.statements(candidate_hash)
+			.any(|(compact, _)| matches!(compact, CompactStatement::Seconded(_)))
+		{
+			return Err(COST_INVALID_ANNOUNCEMENT)
+		}
+
+		self.received_announcements.insert(candidate_hash);
+		self.received_candidates.insert(candidate_hash);
+		self.announced_statements.extend(known.statements(candidate_hash));
+		Ok(())
+	}
+
 	/// Note a received large statement metadata.
 	fn receive_large_statement(&mut self) -> std::result::Result<(), Rep> {
 		if self.large_statement_count >= MAX_LARGE_STATEMENTS_PER_SENDER {
@@ -444,9 +501,18 @@ struct PeerData {
 	view_knowledge: HashMap<Hash, PeerRelayParentKnowledge>,
 	/// Peer might be known as authority with the given ids.
 	maybe_authority: Option<HashSet<AuthorityDiscoveryId>>,
+	/// The validation protocol version the peer is connected on.
+	protocol_version: ProtocolVersion,
 }
 
 impl PeerData {
+	/// Whether the peer understands statements announcements.
+	///
+	/// Such peers only get statements along the grid, all other peers are gossiped to as well.
+	fn supports_announcements(&self) -> bool {
+		self.protocol_version == ValidationVersion::V2.into()
+	}
+
 	/// Updates our view of the peer's knowledge with this statement's fingerprint based
 	/// on something that we would like to send to the peer.
 	///
@@ -542,6 +608,22 @@ impl PeerData {
 			.ok_or(COST_UNEXPECTED_STATEMENT_MISSING_KNOWLEDGE)?
 			.receive_large_statement()
 	}
+
+	/// Note a statements announcement received from the peer.
+	///
+	/// This returns an error if the peer should not have sent us this announcement.
+	fn receive_announcement(
+		&mut self,
+		relay_parent: &Hash,
+		candidate_hash: CandidateHash,
+		known: &StatementFilter,
+		max_announcements: usize,
+	) -> std::result::Result<(), Rep> {
+		self.view_knowledge
+			.get_mut(relay_parent)
+			.ok_or(COST_UNEXPECTED_STATEMENT_MISSING_KNOWLEDGE)?
+			.receive_announcement(candidate_hash, known, max_announcements)
+	}
 }
 
 // A statement stored while a relay chain head is active.
@@ -664,6 +746,10 @@ struct ActiveHeadData {
 	statements: IndexMap<StoredStatementComparator, SignedFullStatement>,
 	/// Large statements we are waiting for with associated meta data.
 	waiting_large_statements: HashMap<CandidateHash, LargeStatementStatus>,
+	/// Announced statements we are currently fetching, per candidate.
+	///
+	/// The fetching tasks will be killed once dropped.
+	fetching_statements: HashMap<CandidateHash, RemoteHandle<()>>,
 	/// The parachain validators at the head's child session index.
 	validators: IndexedVec<ValidatorIndex, ValidatorId>,
 	/// The current session index of this fork.
@@ -684,6 +770,7 @@ impl ActiveHeadData {
 			candidates: Default::default(),
 			statements: Default::default(),
 			waiting_large_statements: Default::default(),
+			fetching_statements: Default::default(),
 			validators,
 			session_index,
 			seconded_counts: Default::default(),
@@ -866,6 +953,25 @@ impl ActiveHeadData {
 		self.statements()
 			.filter(move |s| s.compact().candidate_hash() == &candidate_hash)
 	}
+
+	/// Get an iterator over all candidates we have statements about, in the order they got
+	/// seconded.
+	fn seconded_candidates(&self) -> impl Iterator<Item = CandidateHash> + '_ {
+		let mut seen = HashSet::new();
+		self.statements().filter_map(move |s| match s.compact() {
+			CompactStatement::Seconded(h) if seen.insert(*h) => Some(*h),
+			_ => None,
+		})
+	}
+
+	/// Compact representation of all statements we have about a particular candidate.
+	fn statement_filter(&self, candidate_hash: CandidateHash) -> StatementFilter {
+		let mut filter = StatementFilter::new(self.validators.len());
+		for statement in self.statements_about(candidate_hash) {
+			filter.insert(statement.compact(), statement.statement.validator_index());
+		}
+		filter
+	}
 }
 
 /// Check a statement signature under this parent hash.
@@ -1044,6 +1150,13 @@ async fn circulate_statement<'a, Context>(
 	let priority_set: HashSet<&PeerId> = priority_peers.iter().collect();
 	peers_to_send.retain(|p| !priority_set.contains(p));
 
+	// Apart from priority peers, peers understanding announcements only get statements along
+	// the grid. They will learn about the remaining statements from their own grid neighbors.
+	peers_to_send.retain(|p| {
+		peers.get(p).map_or(false, |data| !data.supports_announcements()) ||
+			topology.route_to_peer(required_routing, p)
+	});
+
 	util::choose_random_subset_with_rng(
 		|e| topology.route_to_peer(required_routing, e),
 		&mut peers_to_send,
@@ -1167,6 +1280,56 @@ async fn send_statements<Context>(
 	}
 }
 
+/// Announce the statements we have at a given relay-parent to a peer, one announcement per
+/// candidate.
+///
+/// Candidates the peer already knows all our statements about are not announced.
+#[overseer::contextbounds(StatementDistribution, prefix=self::overseer)]
+async fn send_announcements<Context>(
+	peer: PeerId,
+	peer_data: &mut PeerData,
+	ctx: &mut Context,
+	relay_parent: Hash,
+	active_head: &ActiveHeadData,
+	metrics: &Metrics,
+) {
+	for candidate_hash in active_head.seconded_candidates() {
+		let has_unknown = active_head
+			.statements_about(candidate_hash)
+			.any(|statement| peer_data.can_send(&relay_parent, &statement.fingerprint()));
+		if !has_unknown {
+			continue
+		}
+
+		let knowledge = match peer_data.view_knowledge.get_mut(&relay_parent) {
+			Some(knowledge) => knowledge,
+			None => return,
+		};
+		knowledge.announced_candidates.insert(candidate_hash);
+
+		let announcement = StatementsAnnouncement {
+			relay_parent,
+			candidate_hash,
+			known: active_head.statement_filter(candidate_hash),
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer,
+			?relay_parent,
+			?candidate_hash,
+			"Sending statements announcement"
+		);
+		let payload = protocol_v2::ValidationProtocol::StatementDistribution(
+			protocol_v2::StatementDistributionMessage::Announcement(announcement),
+		);
+		ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(vec![peer], payload.into()))
+			.await;
+
+		metrics.on_announcement_sent();
+	}
+}
+
 async fn report_peer(
 	sender: &mut impl overseer::StatementDistributionSenderTrait,
 	peer: PeerId,
@@ -1615,10 +1778,20 @@ async fn update_peer_view_and_maybe_send_unlocked<Context, R>(
 		);
 
 	// Add entries for all relay-parents in the new view but not the old.
-	// Furthermore, send all statements we have for those relay parents.
+	// Furthermore, send all statements we have for those relay parents - or announce them to
+	// grid neighbors, which understand announcements.
 	let new_view = peer_data.view.difference(&old_view).copied().collect::<Vec<_>>();
 	for new in new_view.iter().copied() {
 		peer_data.view_knowledge.insert(new, Default::default());
+		if peer_data.supports_announcements() {
+			if !is_gossip_peer {
+				continue
+			}
+			if let Some(active_head) = active_heads.get(&new) {
+				send_announcements(peer, peer_data, ctx, new, active_head, metrics).await;
+			}
+			continue
+		}
 		if !lucky {
 			continue
 		}
@@ -1628,6 +1801,91 @@ async fn update_peer_view_and_maybe_send_unlocked<Context, R>(
 	}
 }
 
+/// Handle a statements announcement of a peer and fetch the statements we are missing.
+#[overseer::contextbounds(StatementDistribution, prefix=self::overseer)]
+async fn handle_incoming_announcement<Context>(
+	peer: PeerId,
+	peer_data: &mut PeerData,
+	active_heads: &mut HashMap<Hash, ActiveHeadData>,
+	recent_outdated_heads: &RecentOutdatedHeads,
+	ctx: &mut Context,
+	announcement: StatementsAnnouncement,
+	req_sender: &mpsc::Sender<RequesterMessage>,
+	metrics: &Metrics,
+) {
+	let StatementsAnnouncement { relay_parent, candidate_hash, known } = announcement;
+	let _ = metrics.time_network_bridge_update_v1("handle_incoming_announcement");
+
+	let active_head = match active_heads.get_mut(&relay_parent) {
+		Some(h) => h,
+		None => {
+			gum::debug!(
+				target: LOG_TARGET,
+				%relay_parent,
+				"our view out-of-sync with active heads; head not found",
+			);
+
+			if !recent_outdated_heads.is_recent_outdated(&relay_parent) {
+				report_peer(ctx.sender(), peer, COST_UNEXPECTED_ANNOUNCEMENT).await;
+			}
+
+			return
+		},
+	};
+
+	let n_validators = active_head.validators.len();
+	if !known.has_len(n_validators) {
+		gum::debug!(target: LOG_TARGET, ?peer, ?candidate_hash, "Announcement of wrong size.");
+		report_peer(ctx.sender(), peer, COST_INVALID_ANNOUNCEMENT).await;
+		return
+	}
+
+	if let Err(rep) = peer_data.receive_announcement(
+		&relay_parent,
+		candidate_hash,
+		&known,
+		n_validators * VC_THRESHOLD,
+	) {
+		gum::debug!(
+			target: LOG_TARGET,
+			?relay_parent,
+			?peer,
+			?candidate_hash,
+			?rep,
+			"Unexpected statements announcement."
+		);
+		report_peer(ctx.sender(), peer, rep).await;
+		return
+	}
+
+	// Statements about this candidate are already being fetched, the same statements should
+	// not be fetched twice.
+	if active_head.fetching_statements.contains_key(&candidate_hash) {
+		return
+	}
+
+	let ours = active_head.statement_filter(candidate_hash);
+	if known.is_subset_of(&ours) {
+		return
+	}
+
+	let (task, handle) = fetch_statements(
+		relay_parent,
+		candidate_hash,
+		peer,
+		ours,
+		req_sender.clone(),
+		metrics.clone(),
+	)
+	.remote_handle();
+
+	if let Err(err) = ctx.spawn("announced-statements-fetcher", task.boxed()) {
+		gum::error!(target: LOG_TARGET, ?err, "Spawning task failed.");
+		return
+	}
+	active_head.fetching_statements.insert(candidate_hash, handle);
+}
+
 #[overseer::contextbounds(StatementDistribution, prefix=self::overseer)]
 async fn handle_network_update<Context, R>(
 	peers: &mut HashMap<PeerId, PeerData>,
@@ -1645,14 +1903,15 @@ async fn handle_network_update<Context, R>(
 	R: rand::Rng,
 {
 	match update {
-		NetworkBridgeEvent::PeerConnected(peer, role, _, maybe_authority) => {
-			gum::trace!(target: LOG_TARGET, ?peer, ?role, "Peer connected");
+		NetworkBridgeEvent::PeerConnected(peer, role, protocol_version, maybe_authority) => {
+			gum::trace!(target: LOG_TARGET, ?peer, ?role, ?protocol_version, "Peer connected");
 			peers.insert(
 				peer,
 				PeerData {
 					view: Default::default(),
 					view_knowledge: Default::default(),
 					maybe_authority: maybe_authority.clone(),
+					protocol_version,
 				},
 			);
 			if let Some(authority_ids) = maybe_authority {
@@ -1700,7 +1959,32 @@ async fn handle_network_update<Context, R>(
 				}
 			}
 		},
-		NetworkBridgeEvent::PeerMessage(peer, Versioned::V1(message)) => {
+		NetworkBridgeEvent::PeerMessage(peer, message) => {
+			let message = match message {
+				Versioned::V1(message) => message,
+				Versioned::V2(protocol_v2::StatementDistributionMessage::Statement(r, s)) =>
+					protocol_v1::StatementDistributionMessage::Statement(r, s),
+				Versioned::V2(protocol_v2::StatementDistributionMessage::LargeStatement(m)) =>
+					protocol_v1::StatementDistributionMessage::LargeStatement(m),
+				Versioned::V2(protocol_v2::StatementDistributionMessage::Announcement(
+					announcement,
+				)) => {
+					if let Some(data) = peers.get_mut(&peer) {
+						handle_incoming_announcement(
+							peer,
+							data,
+							active_heads,
+							recent_outdated_heads,
+							ctx,
+							announcement,
+							req_sender,
+							metrics,
+						)
+						.await;
+					}
+					return
+				},
+			};
 			handle_incoming_message_and_circulate(
 				peer,
 				topology_storage,
@@ -1747,10 +2031,19 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 	pub fn new(
 		keystore: SyncCryptoStorePtr,
 		req_receiver: IncomingRequestReceiver<request_v1::StatementFetchingRequest>,
+		statements_req_receiver: IncomingRequestReceiver<
+			request_v1::CandidateStatementsFetchingRequest,
+		>,
 		metrics: Metrics,
 		rng: R,
 	) -> Self {
-		Self { keystore, req_receiver: Some(req_receiver), metrics, rng }
+		Self {
+			keystore,
+			req_receiver: Some(req_receiver),
+			statements_req_receiver: Some(statements_req_receiver),
+			metrics,
+			rng,
+		}
 	}
 
 	async fn run<Context>(mut self, mut ctx: Context) -> std::result::Result<(), FatalError> {
@@ -1777,6 +2070,16 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 		)
 		.map_err(FatalError::SpawnTask)?;
 
+		ctx.spawn(
+			"announced-statements-responder",
+			respond_with_statements(
+				self.statements_req_receiver.take().expect("Mandatory argument to new. qed"),
+				res_sender.clone(),
+			)
+			.boxed(),
+		)
+		.map_err(FatalError::SpawnTask)?;
+
 		loop {
 			let message =
 				MuxedMessage::receive(&mut ctx, &mut req_receiver, &mut res_receiver).await;
@@ -1819,7 +2122,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 				MuxedMessage::Responder(result) => {
 					let result = self
 						.handle_responder_message(
-							&peers,
+							&mut peers,
 							&mut active_heads,
 							result.ok_or(FatalError::ResponderReceiverFinished)?,
 						)
@@ -1834,7 +2137,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 	/// Handle messages from responder background task.
 	async fn handle_responder_message(
 		&self,
-		peers: &HashMap<PeerId, PeerData>,
+		peers: &mut HashMap<PeerId, PeerData>,
 		active_heads: &mut HashMap<Hash, ActiveHeadData>,
 		message: ResponderMessage,
 	) -> JfyiErrorResult<()> {
@@ -1866,6 +2169,47 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 
 				tx.send(committed).map_err(|_| JfyiError::ResponderGetDataCanceled)?;
 			},
+			ResponderMessage::GetStatements {
+				requesting_peer,
+				relay_parent,
+				candidate_hash,
+				known,
+				tx,
+			} => {
+				let peer_data = peers
+					.get_mut(&requesting_peer)
+					.ok_or(JfyiError::NoSuchPeer(requesting_peer))?;
+				let announced = peer_data
+					.view_knowledge
+					.get(&relay_parent)
+					.ok_or(JfyiError::NoSuchHead(relay_parent))?
+					.announced_candidates
+					.contains(&candidate_hash);
+				if !announced {
+					return Err(JfyiError::RequestedUnannouncedCandidate(
+						requesting_peer,
+						candidate_hash,
+					))
+				}
+
+				let active_head =
+					active_heads.get(&relay_parent).ok_or(JfyiError::NoSuchHead(relay_parent))?;
+
+				let mut statements = Vec::new();
+				for statement in active_head.statements_about(candidate_hash) {
+					let fingerprint = statement.fingerprint();
+					if known.contains(&fingerprint.0, fingerprint.1) ||
+						!peer_data.can_send(&relay_parent, &fingerprint)
+					{
+						continue
+					}
+					peer_data.send(&relay_parent, &fingerprint);
+					statements.push(statement.statement.clone().into());
+					self.metrics.on_statement_distributed();
+				}
+
+				tx.send(statements).map_err(|_| JfyiError::ResponderGetStatementsCanceled)?;
+			},
 		}
 		Ok(())
 	}
@@ -1975,6 +2319,48 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 					}
 				}
 			},
+			RequesterMessage::StatementsFetched {
+				relay_parent,
+				candidate_hash,
+				from_peer,
+				statements,
+			} => {
+				let active_head = active_heads
+					.get_mut(&relay_parent)
+					.ok_or(JfyiError::NoSuchHead(relay_parent))?;
+				active_head.fetching_statements.remove(&candidate_hash);
+
+				for statement in statements {
+					if statement.unchecked_payload().candidate_hash() != candidate_hash {
+						gum::debug!(
+							target: LOG_TARGET,
+							?from_peer,
+							?candidate_hash,
+							"Peer responded with statement about another candidate."
+						);
+						report_peer(ctx.sender(), from_peer, COST_WRONG_HASH).await;
+						break
+					}
+
+					handle_incoming_message_and_circulate(
+						from_peer,
+						topology_storage,
+						peers,
+						active_heads,
+						recent_outdated_heads,
+						ctx,
+						protocol_v1::StatementDistributionMessage::Statement(
+							relay_parent,
+							statement,
+						),
+						req_sender,
+						&self.metrics,
+						runtime,
+						&mut self.rng,
+					)
+					.await;
+				}
+			},
 			RequesterMessage::ReportPeer(peer, rep) => report_peer(ctx.sender(), peer, rep).await,
 		}
 		Ok(())
@@ -32,6 +32,8 @@ struct MetricsInner {
 	network_bridge_update_v1: prometheus::HistogramVec,
 	statements_unexpected: prometheus::CounterVec<prometheus::U64>,
 	created_message_size: prometheus::Gauge<prometheus::U64>,
+	announcements_sent: prometheus::Counter<prometheus::U64>,
+	statements_fetched: prometheus::Counter<prometheus::U64>,
 }
 
 /// Statement Distribution metrics.
@@ -117,6 +119,20 @@ impl Metrics {
 			metrics.created_message_size.set(size as u64);
 		}
 	}
+
+	/// Update the counter of statements announcements sent to peers.
+	pub fn on_announcement_sent(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.announcements_sent.inc();
+		}
+	}
+
+	/// Update the counter of statements fetched after an announcement.
+	pub fn on_statements_fetched(&self, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.statements_fetched.inc_by(count as u64);
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -196,6 +212,20 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			announcements_sent: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_statement_distribution_announcements_sent_total",
+					"Number of statements announcements sent to grid neighbors.",
+				)?,
+				registry,
+			)?,
+			statements_fetched: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_statement_distribution_statements_fetched_total",
+					"Number of statements fetched from peers after they announced them.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -23,16 +23,21 @@ use futures::{
 
 use polkadot_node_network_protocol::{
 	request_response::{
-		v1::{StatementFetchingRequest, StatementFetchingResponse},
+		v1::{
+			CandidateStatementsFetchingRequest, CandidateStatementsFetchingResponse,
+			StatementFetchingRequest, StatementFetchingResponse,
+		},
 		OutgoingRequest, Recipient, Requests,
 	},
+	v2::StatementFilter,
 	PeerId, UnifiedReputationChange,
 };
+use polkadot_node_primitives::UncheckedSignedFullStatement;
 use polkadot_node_subsystem::{Span, Stage};
 use polkadot_node_subsystem_util::TimeoutExt;
 use polkadot_primitives::{CandidateHash, CommittedCandidateReceipt, Hash};
 
-use crate::{metrics::Metrics, COST_WRONG_HASH, LOG_TARGET};
+use crate::{metrics::Metrics, COST_STATEMENTS_FETCH_FAIL, COST_WRONG_HASH, LOG_TARGET};
 
 // In case we failed fetching from our known peers, how long we should wait before attempting a
 // retry, even though we have not yet discovered any new peers. Or in other words how long to
@@ -61,6 +66,18 @@ pub enum RequesterMessage {
 		/// Peers which failed providing the data.
 		bad_peers: Vec<PeerId>,
 	},
+	/// Fetching announced statements about a candidate finished, the statements are to be
+	/// checked and imported by the subsystem.
+	StatementsFetched {
+		/// Relay parent this candidate is in the context of.
+		relay_parent: Hash,
+		/// The candidate the statements are about.
+		candidate_hash: CandidateHash,
+		/// Statements were fetched from this peer.
+		from_peer: PeerId,
+		/// The fetched statements, empty if the request failed.
+		statements: Vec<UncheckedSignedFullStatement>,
+	},
 	/// Report a peer which behaved worse than just not providing data:
 	ReportPeer(PeerId, UnifiedReputationChange),
 	/// Ask subsystem to send a request for us.
@@ -195,6 +212,79 @@ pub async fn fetch(
 	}
 }
 
+/// A fetching task, taking care of fetching the statements a peer announced about a candidate.
+///
+/// Only the announcing peer is asked, as it is the only one known to have the statements. The
+/// statements are not checked here, but passed on to the subsystem, which handles them like
+/// statements received via gossip from that peer.
+pub async fn fetch_statements(
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+	peer: PeerId,
+	known: StatementFilter,
+	mut sender: mpsc::Sender<RequesterMessage>,
+	metrics: Metrics,
+) {
+	let _span = Span::new(candidate_hash, "fetch-announced-statements")
+		.with_relay_parent(relay_parent)
+		.with_peer_id(&peer)
+		.with_stage(Stage::StatementDistribution);
+
+	let req = CandidateStatementsFetchingRequest { relay_parent, candidate_hash, known };
+	let (outgoing, pending_response) = OutgoingRequest::new(Recipient::Peer(peer), req);
+	if let Err(err) = sender
+		.feed(RequesterMessage::SendRequest(Requests::CandidateStatementsFetchingV1(outgoing)))
+		.await
+	{
+		gum::info!(
+			target: LOG_TARGET,
+			?err,
+			"Sending request failed, node might be shutting down - exiting."
+		);
+		return
+	}
+
+	let statements = match pending_response.await {
+		Ok(CandidateStatementsFetchingResponse::Statements(statements)) => {
+			metrics.on_statements_fetched(statements.len());
+			statements
+		},
+		Err(err) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?err,
+				?peer,
+				?candidate_hash,
+				"Fetching announced statements failed."
+			);
+
+			if let Err(err) = sender
+				.feed(RequesterMessage::ReportPeer(peer, COST_STATEMENTS_FETCH_FAIL))
+				.await
+			{
+				gum::warn!(
+					target: LOG_TARGET,
+					?err,
+					"Sending reputation change failed: This should not happen."
+				);
+			}
+			Vec::new()
+		},
+	};
+
+	if let Err(err) = sender
+		.feed(RequesterMessage::StatementsFetched {
+			relay_parent,
+			candidate_hash,
+			from_peer: peer,
+			statements,
+		})
+		.await
+	{
+		gum::warn!(target: LOG_TARGET, ?err, "Sending task response failed: This should not happen.");
+	}
+}
+
 /// Try getting new peers from subsystem.
 ///
 /// If there are non, we will return after a timeout with `None`.
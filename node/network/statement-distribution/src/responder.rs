@@ -24,11 +24,16 @@ use fatality::Nested;
 use polkadot_node_network_protocol::{
 	request_response::{
 		incoming::OutgoingResponse,
-		v1::{StatementFetchingRequest, StatementFetchingResponse},
+		v1::{
+			CandidateStatementsFetchingRequest, CandidateStatementsFetchingResponse,
+			StatementFetchingRequest, StatementFetchingResponse,
+		},
 		IncomingRequestReceiver, MAX_PARALLEL_STATEMENT_REQUESTS,
 	},
+	v2::StatementFilter,
 	PeerId, UnifiedReputationChange as Rep,
 };
+use polkadot_node_primitives::UncheckedSignedFullStatement;
 use polkadot_primitives::{CandidateHash, CommittedCandidateReceipt, Hash};
 
 use crate::LOG_TARGET;
@@ -44,6 +49,14 @@ pub enum ResponderMessage {
 		candidate_hash: CandidateHash,
 		tx: oneshot::Sender<CommittedCandidateReceipt>,
 	},
+	/// Get the statements about a candidate, which are not yet known to the requesting peer.
+	GetStatements {
+		requesting_peer: PeerId,
+		relay_parent: Hash,
+		candidate_hash: CandidateHash,
+		known: StatementFilter,
+		tx: oneshot::Sender<Vec<UncheckedSignedFullStatement>>,
+	},
 }
 
 /// A fetching task, taking care of fetching large statements via request/response.
@@ -119,3 +132,56 @@ pub async fn respond(
 		}
 	}
 }
+
+/// A responder task, answering requests for statements we announced to peers.
+///
+/// Statements are small compared to large statements, so requests are answered one after the
+/// other as they come in.
+pub async fn respond_with_statements(
+	mut receiver: IncomingRequestReceiver<CandidateStatementsFetchingRequest>,
+	mut sender: mpsc::Sender<ResponderMessage>,
+) {
+	loop {
+		let req = match receiver.recv(|| vec![COST_INVALID_REQUEST]).await.into_nested() {
+			Ok(Ok(v)) => v,
+			Err(fatal) => {
+				gum::debug!(target: LOG_TARGET, error = ?fatal, "Shutting down statements responder");
+				return
+			},
+			Ok(Err(jfyi)) => {
+				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Decoding request failed");
+				continue
+			},
+		};
+
+		let (tx, rx) = oneshot::channel();
+		if let Err(err) = sender
+			.feed(ResponderMessage::GetStatements {
+				requesting_peer: req.peer,
+				relay_parent: req.payload.relay_parent,
+				candidate_hash: req.payload.candidate_hash,
+				known: req.payload.known.clone(),
+				tx,
+			})
+			.await
+		{
+			gum::debug!(target: LOG_TARGET, ?err, "Shutting down statements responder");
+			return
+		}
+		let response = match rx.await {
+			Err(err) => {
+				gum::debug!(target: LOG_TARGET, ?err, "Requested statements not found.");
+				Err(())
+			},
+			Ok(v) => Ok(CandidateStatementsFetchingResponse::Statements(v)),
+		};
+		let response = OutgoingResponse {
+			result: response,
+			reputation_changes: Vec::new(),
+			sent_feedback: None,
+		};
+		if let Err(_) = req.send_outgoing_response(response) {
+			gum::debug!(target: LOG_TARGET, "Sending response failed");
+		}
+	}
+}
@@ -23,7 +23,10 @@ use polkadot_node_network_protocol::{
 	grid_topology::{SessionGridTopology, TopologyPeerInfo},
 	peer_set::ValidationVersion,
 	request_response::{
-		v1::{StatementFetchingRequest, StatementFetchingResponse},
+		v1::{
+			CandidateStatementsFetchingResponse, StatementFetchingRequest,
+			StatementFetchingResponse,
+		},
 		IncomingRequest, Recipient, ReqProtocolNames, Requests,
 	},
 	view, ObservedRole,
@@ -381,6 +384,52 @@ fn per_peer_relay_parent_knowledge_receive() {
 	);
 }
 
+#[test]
+fn per_peer_relay_parent_knowledge_receive_announcement() {
+	let mut knowledge = PeerRelayParentKnowledge::default();
+
+	let hash_a = CandidateHash([1; 32].into());
+	let hash_b = CandidateHash([2; 32].into());
+	let hash_c = CandidateHash([3; 32].into());
+
+	// Announcements without any `Seconded` statement are invalid.
+	let mut known = StatementFilter::new(3);
+	known.insert(&CompactStatement::Valid(hash_a), ValidatorIndex(1));
+	assert_eq!(knowledge.receive_announcement(hash_a, &known, 2), Err(COST_INVALID_ANNOUNCEMENT));
+	assert!(!knowledge.is_known_candidate(&hash_a));
+
+	known.insert(&CompactStatement::Seconded(hash_a), ValidatorIndex(0));
+	assert_eq!(knowledge.receive_announcement(hash_a, &known, 2), Ok(()));
+	assert!(knowledge.is_known_candidate(&hash_a));
+
+	// Announced statements are not sent back to the peer.
+	assert!(!knowledge.can_send(&(CompactStatement::Seconded(hash_a), ValidatorIndex(0))));
+	assert!(!knowledge.can_send(&(CompactStatement::Valid(hash_a), ValidatorIndex(1))));
+	assert!(knowledge.can_send(&(CompactStatement::Valid(hash_a), ValidatorIndex(2))));
+
+	// But they can still be received, as they will be fetched from the peer.
+	assert_eq!(
+		knowledge.receive(&(CompactStatement::Valid(hash_a), ValidatorIndex(1)), 3),
+		Ok(false),
+	);
+
+	// Each candidate must only be announced once.
+	assert_eq!(
+		knowledge.receive_announcement(hash_a, &known, 2),
+		Err(COST_UNEXPECTED_ANNOUNCEMENT)
+	);
+
+	let mut known = StatementFilter::new(3);
+	known.insert(&CompactStatement::Seconded(hash_b), ValidatorIndex(1));
+	assert_eq!(knowledge.receive_announcement(hash_b, &known, 2), Ok(()));
+
+	// Flood limit reached.
+	let mut known = StatementFilter::new(3);
+	known.insert(&CompactStatement::Seconded(hash_c), ValidatorIndex(2));
+	assert_eq!(knowledge.receive_announcement(hash_c, &known, 2), Err(COST_APPARENT_FLOOD));
+	assert!(!knowledge.is_known_candidate(&hash_c));
+}
+
 #[test]
 fn peer_view_update_sends_messages() {
 	let hash_a = Hash::repeat_byte(1);
@@ -502,6 +551,7 @@ fn peer_view_update_sends_messages() {
 			k
 		},
 		maybe_authority: None,
+		protocol_version: ValidationVersion::V1.into(),
 	};
 
 	let pool = sp_core::testing::TaskExecutor::new();
@@ -595,6 +645,7 @@ fn circulated_statement_goes_to_all_peers_with_view() {
 		view: view.clone(),
 		view_knowledge: view.iter().map(|v| (v.clone(), Default::default())).collect(),
 		maybe_authority: None,
+		protocol_version: ValidationVersion::V1.into(),
 	};
 
 	let mut peer_data: HashMap<_, _> = vec![
@@ -733,11 +784,13 @@ fn receiving_from_one_sends_to_another_and_to_candidate_backing() {
 
 	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
 	let (statement_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
 
 	let bg = async move {
 		let s = StatementDistributionSubsystem::new(
 			Arc::new(LocalKeystore::in_memory()),
 			statement_req_receiver,
+			statements_req_receiver,
 			Default::default(),
 			AlwaysZeroRng,
 		);
@@ -901,6 +954,220 @@ fn receiving_from_one_sends_to_another_and_to_candidate_backing() {
 	executor::block_on(future::join(test_fut, bg));
 }
 
+#[test]
+fn announced_statements_are_fetched_and_circulated() {
+	sp_tracing::try_init_simple();
+	let hash_a = Hash::repeat_byte(1);
+
+	let candidate = {
+		let mut c = dummy_committed_candidate_receipt(dummy_hash());
+		c.descriptor.relay_parent = hash_a;
+		c.descriptor.para_id = 1.into();
+		c
+	};
+	let candidate_hash = candidate.hash();
+
+	// Peer A announces, peer B is a legacy peer and peer C understands announcements, but is not
+	// a grid neighbor.
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+
+	let validators = vec![
+		Sr25519Keyring::Alice.pair(),
+		Sr25519Keyring::Bob.pair(),
+		Sr25519Keyring::Charlie.pair(),
+	];
+
+	let session_info = make_session_info(validators, vec![]);
+
+	let session_index = 1;
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (ctx, mut handle) = polkadot_node_subsystem_test_helpers::make_subsystem_context(pool);
+
+	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
+	let (statement_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
+
+	let bg = async move {
+		let s = StatementDistributionSubsystem::new(
+			Arc::new(LocalKeystore::in_memory()),
+			statement_req_receiver,
+			statements_req_receiver,
+			Default::default(),
+			AlwaysZeroRng,
+		);
+		s.run(ctx).await.unwrap();
+	};
+
+	let test_fut = async move {
+		// register our active heads.
+		handle
+			.send(FromOrchestra::Signal(OverseerSignal::ActiveLeaves(
+				ActiveLeavesUpdate::start_work(ActivatedLeaf {
+					hash: hash_a,
+					number: 1,
+					status: LeafStatus::Fresh,
+					span: Arc::new(jaeger::Span::Disabled),
+				}),
+			)))
+			.await;
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::RuntimeApi(
+				RuntimeApiMessage::Request(r, RuntimeApiRequest::SessionIndexForChild(tx))
+			)
+				if r == hash_a
+			=> {
+				let _ = tx.send(Ok(session_index));
+			}
+		);
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::RuntimeApi(
+				RuntimeApiMessage::Request(r, RuntimeApiRequest::SessionInfo(sess_index, tx))
+			)
+				if r == hash_a && sess_index == session_index
+			=> {
+				let _ = tx.send(Ok(Some(session_info)));
+			}
+		);
+
+		// notify of peers and view
+		for (peer, version) in [
+			(peer_a, ValidationVersion::V2),
+			(peer_b, ValidationVersion::V1),
+			(peer_c, ValidationVersion::V2),
+		] {
+			handle
+				.send(FromOrchestra::Communication {
+					msg: StatementDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerConnected(
+							peer,
+							ObservedRole::Full,
+							version.into(),
+							None,
+						),
+					),
+				})
+				.await;
+
+			handle
+				.send(FromOrchestra::Communication {
+					msg: StatementDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerViewChange(peer, view![hash_a]),
+					),
+				})
+				.await;
+		}
+
+		let statement = {
+			let signing_context = SigningContext { parent_hash: hash_a, session_index };
+
+			let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+			let alice_public = CryptoStore::sr25519_generate_new(
+				&*keystore,
+				ValidatorId::ID,
+				Some(&Sr25519Keyring::Alice.to_seed()),
+			)
+			.await
+			.unwrap();
+
+			SignedFullStatement::sign(
+				&keystore,
+				Statement::Seconded(candidate),
+				&signing_context,
+				ValidatorIndex(0),
+				&alice_public.into(),
+			)
+			.await
+			.ok()
+			.flatten()
+			.expect("should be signed")
+		};
+
+		// Peer A announces the statement, which should get fetched.
+		let mut known = StatementFilter::new(3);
+		known.insert(&CompactStatement::Seconded(candidate_hash), ValidatorIndex(0));
+
+		handle
+			.send(FromOrchestra::Communication {
+				msg: StatementDistributionMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(
+						peer_a,
+						Versioned::V2(protocol_v2::StatementDistributionMessage::Announcement(
+							StatementsAnnouncement { relay_parent: hash_a, candidate_hash, known },
+						)),
+					),
+				),
+			})
+			.await;
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::SendRequests(
+					mut reqs, IfDisconnected::ImmediateError
+				)
+			) => {
+				let reqs = reqs.pop().unwrap();
+				let outgoing = match reqs {
+					Requests::CandidateStatementsFetchingV1(outgoing) => outgoing,
+					_ => panic!("Unexpected request"),
+				};
+				let req = outgoing.payload;
+				assert_eq!(req.relay_parent, hash_a);
+				assert_eq!(req.candidate_hash, candidate_hash);
+				assert_eq!(req.known, StatementFilter::new(3));
+				assert_eq!(outgoing.peer, Recipient::Peer(peer_a));
+				let response =
+					CandidateStatementsFetchingResponse::Statements(vec![statement.clone().into()]);
+				outgoing.pending_response.send(Ok(response.encode())).unwrap();
+			}
+		);
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(p, r)
+			) if p == peer_a && r == BENEFIT_VALID_STATEMENT_FIRST => {}
+		);
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::CandidateBacking(
+				CandidateBackingMessage::Statement(r, s)
+			) if r == hash_a && s == statement => {}
+		);
+
+		// Only the legacy peer gets the statement, peer C is no grid neighbor.
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::SendValidationMessage(
+					recipients,
+					Versioned::V1(protocol_v1::ValidationProtocol::StatementDistribution(
+						protocol_v1::StatementDistributionMessage::Statement(r, s)
+					)),
+				)
+			) => {
+				assert_eq!(recipients, vec![peer_b]);
+				assert_eq!(r, hash_a);
+				assert_eq!(s, statement.into());
+			}
+		);
+		handle.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+	};
+
+	futures::pin_mut!(test_fut);
+	futures::pin_mut!(bg);
+
+	executor::block_on(future::join(test_fut, bg));
+}
+
 #[test]
 fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing() {
 	sp_tracing::try_init_simple();
@@ -938,11 +1205,13 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
 	let (statement_req_receiver, mut req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
 
 	let bg = async move {
 		let s = StatementDistributionSubsystem::new(
 			make_ferdie_keystore(),
 			statement_req_receiver,
+			statements_req_receiver,
 			Default::default(),
 			AlwaysZeroRng,
 		);
@@ -1452,11 +1721,13 @@ fn share_prioritizes_backing_group() {
 	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
 	let (statement_req_receiver, mut req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
 
 	let bg = async move {
 		let s = StatementDistributionSubsystem::new(
 			make_ferdie_keystore(),
 			statement_req_receiver,
+			statements_req_receiver,
 			Default::default(),
 			AlwaysZeroRng,
 		);
@@ -1748,10 +2019,12 @@ fn peer_cant_flood_with_large_statements() {
 
 	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
 	let (statement_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	let bg = async move {
 		let s = StatementDistributionSubsystem::new(
 			make_ferdie_keystore(),
 			statement_req_receiver,
+			statements_req_receiver,
 			Default::default(),
 			AlwaysZeroRng,
 		);
@@ -1953,11 +2226,13 @@ fn handle_multiple_seconded_statements() {
 
 	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
 	let (statement_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (statements_req_receiver, _) = IncomingRequest::get_config_receiver(&req_protocol_names);
 
 	let virtual_overseer_fut = async move {
 		let s = StatementDistributionSubsystem::new(
 			Arc::new(LocalKeystore::in_memory()),
 			statement_req_receiver,
+			statements_req_receiver,
 			Default::default(),
 			AlwaysZeroRng,
 		);
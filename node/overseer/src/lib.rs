@@ -97,6 +97,12 @@ pub use self::metrics::Metrics as OverseerMetrics;
 pub mod dummy;
 pub use self::dummy::DummySubsystem;
 
+/// Recording of subsystem deliveries, for debugging and replaying.
+pub mod tap;
+
+/// Detection of stalled subsystems and profiling of message handling.
+pub mod watchdog;
+
 pub use polkadot_node_metrics::{
 	metrics::{prometheus, Metrics as MetricsTrait},
 	Metronome,
@@ -679,19 +685,43 @@ where
 			},
 		};
 
+	let mut stall_watchdog = watchdog::StallWatchdog::new(watchdog::STALL_THRESHOLD);
+
 	let metronome = Metronome::new(std::time::Duration::from_millis(950)).for_each(move |_| {
 		collect_memory_stats(&metronome_metrics);
 
+		let readouts: Vec<_> = subsystem_meters
+			.iter()
+			.cloned()
+			.flatten()
+			.map(|(name, ref meters)| (name, meters.read()))
+			.collect();
+
+		let now = std::time::Instant::now();
+		for &(name, ref readouts) in &readouts {
+			let sent = readouts.bounded.sent + readouts.unbounded.sent + readouts.signals.sent;
+			let received =
+				readouts.bounded.received + readouts.unbounded.received + readouts.signals.received;
+
+			if let Some(stall) = stall_watchdog.check(name, sent, received, now) {
+				let handling = metronome_metrics.activity().current(name);
+				gum::warn!(
+					target: LOG_TARGET,
+					subsystem = name,
+					queue_depth = stall.queue_depth,
+					stalled_for = ?stall.stalled_for,
+					message_type = ?handling.as_ref().map(|(message_type, _)| message_type),
+					handling_for = ?handling.as_ref().map(|(_, elapsed)| elapsed),
+					"Subsystem stopped draining its queue",
+				);
+				metronome_metrics.on_subsystem_stalled(name);
+			}
+		}
+
 		// We combine the amount of messages from subsystems to the overseer
 		// as well as the amount of messages from external sources to the overseer
 		// into one `to_overseer` value.
-		metronome_metrics.channel_metrics_snapshot(
-			subsystem_meters
-				.iter()
-				.cloned()
-				.flatten()
-				.map(|(name, ref meters)| (name, meters.read())),
-		);
+		metronome_metrics.channel_metrics_snapshot(readouts);
 
 		futures::future::ready(())
 	});
@@ -20,6 +20,7 @@ use super::*;
 pub use polkadot_node_metrics::metrics::{self, prometheus, Metrics as MetricsTrait};
 
 use memory_stats::MemoryAllocationSnapshot;
+use crate::watchdog::SubsystemActivity;
 
 /// Overseer Prometheus metrics.
 #[derive(Clone)]
@@ -32,21 +33,28 @@ struct MetricsInner {
 	to_subsystem_bounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_received: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_blocked: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_bounded_depth: prometheus::GaugeVec<prometheus::U64>,
 
 	to_subsystem_unbounded_tof: prometheus::HistogramVec,
 	to_subsystem_unbounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_unbounded_received: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_unbounded_depth: prometheus::GaugeVec<prometheus::U64>,
 
 	signals_sent: prometheus::GaugeVec<prometheus::U64>,
 	signals_received: prometheus::GaugeVec<prometheus::U64>,
 
+	message_handling_time: prometheus::HistogramVec,
+	subsystem_stalls_total: prometheus::CounterVec<prometheus::U64>,
+
 	memory_stats_resident: prometheus::Gauge<prometheus::U64>,
 	memory_stats_allocated: prometheus::Gauge<prometheus::U64>,
 }
 
 /// A shareable metrics type for usage with the overseer.
+///
+/// Also shares the message types profiled subsystems are handling with the stall watchdog.
 #[derive(Default, Clone)]
-pub struct Metrics(Option<MetricsInner>);
+pub struct Metrics(Option<MetricsInner>, SubsystemActivity);
 
 impl Metrics {
 	pub(crate) fn on_head_activated(&self) {
@@ -67,6 +75,30 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_message_handled(
+		&self,
+		subsystem: &'static str,
+		message_type: &str,
+		elapsed: std::time::Duration,
+	) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.message_handling_time
+				.with_label_values(&[subsystem, message_type])
+				.observe(elapsed.as_secs_f64());
+		}
+	}
+
+	pub(crate) fn on_subsystem_stalled(&self, subsystem: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.subsystem_stalls_total.with_label_values(&[subsystem]).inc();
+		}
+	}
+
+	pub(crate) fn activity(&self) -> &SubsystemActivity {
+		&self.1
+	}
+
 	pub(crate) fn memory_stats_snapshot(&self, memory_stats: MemoryAllocationSnapshot) {
 		if let Some(metrics) = &self.0 {
 			metrics.memory_stats_allocated.set(memory_stats.allocated);
@@ -97,6 +129,11 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.bounded.blocked as u64);
 
+					metrics
+						.to_subsystem_bounded_depth
+						.with_label_values(&[name])
+						.set(readouts.bounded.sent.saturating_sub(readouts.bounded.received) as u64);
+
 					metrics
 						.to_subsystem_unbounded_sent
 						.with_label_values(&[name])
@@ -107,6 +144,12 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.unbounded.received as u64);
 
+					metrics
+						.to_subsystem_unbounded_depth
+						.with_label_values(&[name])
+						.set(readouts.unbounded.sent.saturating_sub(readouts.unbounded.received)
+							as u64);
+
 					metrics
 						.signals_sent
 						.with_label_values(&[name])
@@ -196,6 +239,16 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			to_subsystem_bounded_depth: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_bounded_queue_depth",
+						"Number of elements waiting in subsystems' bounded queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 			to_subsystem_unbounded_tof: prometheus::register(
 				prometheus::HistogramVec::new(
 					prometheus::HistogramOpts::new(
@@ -226,6 +279,16 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			to_subsystem_unbounded_depth: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_unbounded_queue_depth",
+						"Number of elements waiting in subsystems' unbounded queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 			signals_sent: prometheus::register(
 				prometheus::GaugeVec::<prometheus::U64>::new(
 					prometheus::Opts::new(
@@ -246,6 +309,26 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			message_handling_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_subsystem_message_handling_time",
+						"Time spent by profiled subsystems handling a message, by message type",
+					),
+					&["subsystem_name", "message_type"],
+				)?,
+				registry,
+			)?,
+			subsystem_stalls_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_stalls_total",
+						"Number of times subsystems stopped draining their queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 
 			memory_stats_allocated: prometheus::register(
 				prometheus::Gauge::<prometheus::U64>::new(
@@ -262,7 +345,7 @@ impl MetricsTrait for Metrics {
 				registry,
 			)?,
 		};
-		Ok(Metrics(Some(metrics)))
+		Ok(Metrics(Some(metrics), Default::default()))
 	}
 }
 
@@ -0,0 +1,1184 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recording of the messages and signals delivered to subsystems.
+//!
+//! Subsystems are wrapped into a [`TappedSubsystem`] when building the overseer. If the node
+//! runs with a [`MessageTap`], every signal and message the wrapped subsystem receives is
+//! written to the tap, one line per delivery, together with the time elapsed since the tap was
+//! created.
+//!
+//! Signals are recorded in a structured form and can be reconstructed from a recording.
+//! Messages are recorded by the name of their variant and the SCALE encoding of their payload,
+//! see [`RecordableMessage`].
+
+use std::{
+	fmt,
+	fs::File,
+	io::{self, BufRead, BufReader, BufWriter, Write},
+	path::Path,
+	pin::Pin,
+	str::FromStr,
+	sync::Arc,
+	thread,
+	time::{Duration, Instant},
+};
+
+use futures::{
+	channel::mpsc,
+	executor,
+	future::{self, Either},
+	Future, StreamExt,
+};
+use futures_timer::Delay;
+use parity_scale_codec::{Compact, Encode};
+use parking_lot::Mutex;
+use polkadot_primitives::{BlockNumber, Hash};
+use sp_core::bytes::{from_hex, to_hex};
+
+use crate::{
+	jaeger, watchdog::message_type, ActivatedLeaf, ActiveLeavesUpdate, FromOrchestra, LeafStatus,
+	OverseerSignal, SpawnedSubsystem, Subsystem, SubsystemContext, SubsystemError, LOG_TARGET,
+};
+
+use crate::messages::{BitfieldSigningMessage, PvfCheckerMessage};
+use polkadot_node_network_protocol::{
+	peer_set::PeerSet,
+	request_response::{OutgoingRequest, Recipient, Requests},
+	ObservedRole, PeerId, UnifiedReputationChange, Versioned,
+};
+use polkadot_node_subsystem_types::messages::{
+	ApprovalDistributionMessage, ApprovalVotingMessage, AvailabilityDistributionMessage,
+	AvailabilityRecoveryMessage, AvailabilityStoreMessage, BitfieldDistributionMessage,
+	CandidateBackingMessage, CandidateValidationMessage, ChainApiMessage, ChainSelectionMessage,
+	CollationGenerationMessage, CollatorProtocolMessage, DisputeCoordinatorMessage,
+	DisputeDistributionMessage, GossipSupportMessage, IfDisconnected, NetworkBridgeEvent,
+	NetworkBridgeRxMessage, NetworkBridgeTxMessage, ProvisionableData, ProvisionerMessage,
+	RuntimeApiMessage, RuntimeApiRequest, StatementDistributionMessage,
+};
+
+/// The first line of every recording.
+const RECORDING_HEADER: &str = "# polkadot overseer message tap v2";
+
+/// How often the records written by a tap are flushed.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Records the signals and messages delivered to subsystems.
+///
+/// Records are written and flushed by a background thread, so recording doesn't hold up the
+/// delivery of messages. The thread exits once every clone of the tap is dropped.
+#[derive(Clone)]
+pub struct MessageTap {
+	records: mpsc::UnboundedSender<WriterCommand>,
+	start: Instant,
+}
+
+enum WriterCommand {
+	Record(TapRecord),
+	Flush(std::sync::mpsc::SyncSender<()>),
+}
+
+impl MessageTap {
+	/// Create a tap recording into a new file at the given path.
+	pub fn create(path: &Path) -> io::Result<Self> {
+		Self::new(File::create(path)?)
+	}
+
+	/// Create a tap recording into the given writer.
+	pub fn new(writer: impl Write + Send + 'static) -> io::Result<Self> {
+		let mut writer = BufWriter::new(writer);
+		writeln!(writer, "{}", RECORDING_HEADER)?;
+		writer.flush()?;
+
+		let (records, rx) = mpsc::unbounded();
+		thread::Builder::new()
+			.name("message-tap".into())
+			.spawn(move || write_records(writer, rx))?;
+
+		Ok(Self { records, start: Instant::now() })
+	}
+
+	/// Block until everything recorded so far has been written and flushed.
+	pub fn flush(&self) {
+		let (tx, rx) = std::sync::mpsc::sync_channel(1);
+		if self.records.unbounded_send(WriterCommand::Flush(tx)).is_ok() {
+			let _ = rx.recv();
+		}
+	}
+
+	/// Record a delivery to the subsystem with the given name.
+	pub fn record<M: RecordableMessage + fmt::Debug>(
+		&self,
+		subsystem: &str,
+		delivery: &FromOrchestra<M, OverseerSignal>,
+	) {
+		let event = match delivery {
+			FromOrchestra::Signal(signal) => TapEvent::Signal(RecordedSignal::from(signal)),
+			FromOrchestra::Communication { msg } => TapEvent::Message(RecordedMessage {
+				message_type: message_type(msg),
+				encoded: msg.encode_recorded(),
+			}),
+		};
+
+		let record =
+			TapRecord { elapsed: self.start.elapsed(), subsystem: subsystem.into(), event };
+
+		// The writer only stops once every tap is dropped.
+		let _ = self.records.unbounded_send(WriterCommand::Record(record));
+	}
+}
+
+/// Write the records sent by the taps, flushing them at most every [`FLUSH_INTERVAL`], when a
+/// flush is requested and once the taps are dropped.
+fn write_records(mut writer: impl Write, mut commands: mpsc::UnboundedReceiver<WriterCommand>) {
+	let mut last_flush = Instant::now();
+	loop {
+		let timeout = Delay::new(FLUSH_INTERVAL.saturating_sub(last_flush.elapsed()));
+		let flushed = match executor::block_on(future::select(commands.next(), timeout)) {
+			Either::Left((Some(WriterCommand::Record(record)), _)) => {
+				if let Err(err) = writeln!(writer, "{}", record) {
+					gum::warn!(target: LOG_TARGET, ?err, "Failed to write to the message tap");
+				}
+				if last_flush.elapsed() < FLUSH_INTERVAL {
+					continue
+				}
+				None
+			},
+			Either::Left((Some(WriterCommand::Flush(flushed)), _)) => Some(flushed),
+			Either::Left((None, _)) => {
+				flush_records(&mut writer);
+				return
+			},
+			Either::Right(_) => None,
+		};
+
+		flush_records(&mut writer);
+		last_flush = Instant::now();
+		if let Some(flushed) = flushed {
+			let _ = flushed.send(());
+		}
+	}
+}
+
+fn flush_records(writer: &mut impl Write) {
+	if let Err(err) = writer.flush() {
+		gum::warn!(target: LOG_TARGET, ?err, "Failed to flush the message tap");
+	}
+}
+
+/// A single delivery to a subsystem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TapRecord {
+	/// The time elapsed since the tap was created.
+	pub elapsed: Duration,
+	/// The name of the subsystem the delivery was made to.
+	pub subsystem: String,
+	/// What was delivered.
+	pub event: TapEvent,
+}
+
+/// What was delivered to a subsystem.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TapEvent {
+	/// A signal.
+	Signal(RecordedSignal),
+	/// A message.
+	Message(RecordedMessage),
+}
+
+/// A recorded message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedMessage {
+	/// The name of the message's variant, e.g. `CollateOn`.
+	pub message_type: String,
+	/// The recorded form of the message, see [`RecordableMessage::encode_recorded`]. `None` if
+	/// the message can't be recorded.
+	pub encoded: Option<Vec<u8>>,
+}
+
+/// A message which can be recorded by a [`MessageTap`].
+///
+/// Messages may carry response channels, which can't be recorded. The recorded form of a
+/// message is the SCALE encoding of its other fields, in order, so a replay can decode them and
+/// fill in fresh channels. Messages which don't implement it are recorded by the name of their
+/// variant only.
+///
+/// Fields without a SCALE encoding of their own are recorded in a plain form: nested enums by the
+/// name of their variant followed by its fields, peer ids and multiaddresses by their bytes,
+/// signed payloads unchecked, collections as vectors and `usize`s as `u64`s. Jaeger spans are
+/// dropped.
+pub trait RecordableMessage {
+	/// The SCALE encoding of the message's fields without its response channels, or `None` if
+	/// the message can't be recorded.
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		None
+	}
+}
+
+impl RecordableMessage for PvfCheckerMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		match *self {}
+	}
+}
+
+impl RecordableMessage for BitfieldSigningMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		match *self {}
+	}
+}
+
+impl RecordableMessage for ChainApiMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			ChainApiMessage::BlockNumber(hash, _) |
+			ChainApiMessage::BlockHeader(hash, _) |
+			ChainApiMessage::BlockWeight(hash, _) => hash.encode(),
+			ChainApiMessage::FinalizedBlockHash(number, _) => number.encode(),
+			ChainApiMessage::FinalizedBlockNumber(_) => Vec::new(),
+			ChainApiMessage::Ancestors { hash, k, .. } => (hash, *k as u64).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for ChainSelectionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			ChainSelectionMessage::Approved(hash) |
+			ChainSelectionMessage::BestLeafContaining(hash, _) => hash.encode(),
+			ChainSelectionMessage::Leaves(_) => Vec::new(),
+			ChainSelectionMessage::RevertBlocks(blocks) => blocks.encode(),
+		})
+	}
+}
+
+impl RecordableMessage for CollatorProtocolMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			CollatorProtocolMessage::CollateOn(para_id) => para_id.encode(),
+			CollatorProtocolMessage::DistributeCollation(receipt, parent_head_hash, pov, _) =>
+				(receipt, parent_head_hash, pov).encode(),
+			CollatorProtocolMessage::ReportCollator(collator) => collator.encode(),
+			CollatorProtocolMessage::NetworkBridgeUpdate(event) =>
+				encode_network_bridge_event(event, encode_versioned),
+			CollatorProtocolMessage::Invalid(relay_parent, receipt) =>
+				(relay_parent, receipt).encode(),
+			CollatorProtocolMessage::Seconded(relay_parent, statement) =>
+				(relay_parent, statement.as_unchecked()).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for BitfieldDistributionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			BitfieldDistributionMessage::DistributeBitfield(relay_parent, bitfield) =>
+				(relay_parent, bitfield.as_unchecked()).encode(),
+			BitfieldDistributionMessage::NetworkBridgeUpdate(event) =>
+				encode_network_bridge_event(event, encode_versioned),
+		})
+	}
+}
+
+impl RecordableMessage for StatementDistributionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			StatementDistributionMessage::Share(relay_parent, statement) =>
+				(relay_parent, statement.as_unchecked()).encode(),
+			StatementDistributionMessage::NetworkBridgeUpdate(event) =>
+				encode_network_bridge_event(event, encode_versioned),
+		})
+	}
+}
+
+impl RecordableMessage for ApprovalDistributionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			ApprovalDistributionMessage::NewBlocks(metas) => metas.encode(),
+			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index) =>
+				(cert, candidate_index).encode(),
+			ApprovalDistributionMessage::DistributeApproval(vote) => vote.encode(),
+			ApprovalDistributionMessage::DistributeCoalescedApproval(vote) => vote.encode(),
+			ApprovalDistributionMessage::NetworkBridgeUpdate(event) =>
+				encode_network_bridge_event(event, encode_versioned),
+			ApprovalDistributionMessage::GetApprovalSignatures(candidates, _) =>
+				candidates.iter().collect::<Vec<_>>().encode(),
+		})
+	}
+}
+
+impl RecordableMessage for ApprovalVotingMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			ApprovalVotingMessage::CheckAndImportAssignment(cert, candidate_index, _) =>
+				(cert, candidate_index).encode(),
+			ApprovalVotingMessage::CheckAndImportApproval(vote, _) => vote.encode(),
+			ApprovalVotingMessage::CheckAndImportApprovals(votes, _) => votes.encode(),
+			ApprovalVotingMessage::CheckAndImportCoalescedApproval(vote, _) => vote.encode(),
+			ApprovalVotingMessage::ApprovedAncestor(hash, number, _) => (hash, number).encode(),
+			ApprovalVotingMessage::GetApprovalSignaturesForCandidate(candidate_hash, _) =>
+				candidate_hash.encode(),
+		})
+	}
+}
+
+impl RecordableMessage for AvailabilityDistributionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			AvailabilityDistributionMessage::FetchPoV {
+				relay_parent,
+				from_validator,
+				para_id,
+				candidate_hash,
+				pov_hash,
+				..
+			} => (relay_parent, from_validator, para_id, candidate_hash, pov_hash).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for AvailabilityRecoveryMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			AvailabilityRecoveryMessage::RecoverAvailableData(receipt, session, group, _) =>
+				(receipt, session, group).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for AvailabilityStoreMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			AvailabilityStoreMessage::QueryAvailableData(candidate_hash, _) |
+			AvailabilityStoreMessage::QueryDataAvailability(candidate_hash, _) |
+			AvailabilityStoreMessage::QueryAllChunks(candidate_hash, _) => candidate_hash.encode(),
+			AvailabilityStoreMessage::QueryChunk(candidate_hash, validator_index, _) |
+			AvailabilityStoreMessage::QueryChunkAvailability(
+				candidate_hash,
+				validator_index,
+				_,
+			) => (candidate_hash, validator_index).encode(),
+			AvailabilityStoreMessage::StoreChunk { candidate_hash, chunk, .. } =>
+				(candidate_hash, chunk).encode(),
+			AvailabilityStoreMessage::StoreAvailableData {
+				candidate_hash,
+				n_validators,
+				available_data,
+				..
+			} => (candidate_hash, n_validators, available_data).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for CandidateBackingMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			CandidateBackingMessage::GetBackedCandidates(relay_parent, candidates, _) =>
+				(relay_parent, candidates).encode(),
+			CandidateBackingMessage::Second(relay_parent, receipt, pov) =>
+				(relay_parent, receipt, pov).encode(),
+			CandidateBackingMessage::Statement(relay_parent, statement) =>
+				(relay_parent, statement.as_unchecked()).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for CandidateValidationMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			CandidateValidationMessage::ValidateFromChainState(receipt, pov, timeout, _) =>
+				(receipt, &**pov, timeout).encode(),
+			CandidateValidationMessage::ValidateFromExhaustive(
+				validation_data,
+				validation_code,
+				receipt,
+				pov,
+				timeout,
+				_,
+			) => (validation_data, validation_code, receipt, &**pov, timeout).encode(),
+			CandidateValidationMessage::PreCheck(relay_parent, validation_code_hash, _) =>
+				(relay_parent, validation_code_hash).encode(),
+		})
+	}
+}
+
+impl RecordableMessage for CollationGenerationMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		match self {
+			// The config carries the collator's key and its collation function, neither of which
+			// can be recorded.
+			CollationGenerationMessage::Initialize(_) => None,
+		}
+	}
+}
+
+impl RecordableMessage for DisputeCoordinatorMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			DisputeCoordinatorMessage::ImportStatements {
+				candidate_receipt,
+				session,
+				statements,
+				..
+			} => {
+				let statements = statements
+					.iter()
+					.map(|(statement, validator_index)| {
+						(
+							(
+								statement.statement(),
+								statement.candidate_hash(),
+								statement.session_index(),
+								statement.validator_public(),
+								statement.validator_signature(),
+							),
+							validator_index,
+						)
+					})
+					.collect::<Vec<_>>();
+
+				(candidate_receipt, session, statements).encode()
+			},
+			DisputeCoordinatorMessage::RecentDisputes(_) |
+			DisputeCoordinatorMessage::ActiveDisputes(_) => Vec::new(),
+			DisputeCoordinatorMessage::QueryCandidateVotes(queries, _) => queries.encode(),
+			DisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				candidate_hash,
+				receipt,
+				valid,
+			) => (session, candidate_hash, receipt, valid).encode(),
+			DisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions,
+				..
+			} => {
+				let block_descriptions = block_descriptions
+					.iter()
+					.map(|block| (block.block_hash, block.session, &block.candidates))
+					.collect::<Vec<_>>();
+
+				(base, block_descriptions).encode()
+			},
+		})
+	}
+}
+
+impl RecordableMessage for DisputeDistributionMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			// The layout of `UncheckedDisputeMessage`.
+			DisputeDistributionMessage::SendDispute(message) => (
+				message.candidate_receipt(),
+				message.session_index(),
+				message.invalid_vote(),
+				message.valid_vote(),
+			)
+				.encode(),
+		})
+	}
+}
+
+impl RecordableMessage for GossipSupportMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			GossipSupportMessage::NetworkBridgeUpdate(event) =>
+				encode_network_bridge_event(event, |msg, _| match msg {
+					Versioned::V1(msg) | Versioned::V2(msg) => match *msg {},
+				}),
+		})
+	}
+}
+
+impl RecordableMessage for NetworkBridgeRxMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			NetworkBridgeRxMessage::NewGossipTopology {
+				session,
+				local_index,
+				canonical_shuffling,
+				shuffled_indices,
+			} => (session, local_index, canonical_shuffling, encode_indices(shuffled_indices))
+				.encode(),
+		})
+	}
+}
+
+impl RecordableMessage for NetworkBridgeTxMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		let mut encoded = Vec::new();
+		match self {
+			NetworkBridgeTxMessage::ReportPeer(peer, rep) =>
+				(peer.to_bytes(), reputation_change_name(rep), rep.description())
+					.encode_to(&mut encoded),
+			NetworkBridgeTxMessage::DisconnectPeer(peer, peer_set) =>
+				(peer.to_bytes(), peer_set_name(peer_set)).encode_to(&mut encoded),
+			NetworkBridgeTxMessage::SendValidationMessage(peers, msg) => {
+				encode_peers(peers).encode_to(&mut encoded);
+				encode_versioned(msg, &mut encoded);
+			},
+			NetworkBridgeTxMessage::SendCollationMessage(peers, msg) => {
+				encode_peers(peers).encode_to(&mut encoded);
+				encode_versioned(msg, &mut encoded);
+			},
+			NetworkBridgeTxMessage::SendValidationMessages(msgs) => {
+				Compact(msgs.len() as u32).encode_to(&mut encoded);
+				for (peers, msg) in msgs {
+					encode_peers(peers).encode_to(&mut encoded);
+					encode_versioned(msg, &mut encoded);
+				}
+			},
+			NetworkBridgeTxMessage::SendCollationMessages(msgs) => {
+				Compact(msgs.len() as u32).encode_to(&mut encoded);
+				for (peers, msg) in msgs {
+					encode_peers(peers).encode_to(&mut encoded);
+					encode_versioned(msg, &mut encoded);
+				}
+			},
+			NetworkBridgeTxMessage::SendRequests(requests, if_disconnected) => {
+				Compact(requests.len() as u32).encode_to(&mut encoded);
+				for request in requests {
+					encode_request(request, &mut encoded);
+				}
+				if_disconnected_name(if_disconnected).encode_to(&mut encoded);
+			},
+			NetworkBridgeTxMessage::ConnectToValidators { validator_ids, peer_set, .. } =>
+				(validator_ids, peer_set_name(peer_set)).encode_to(&mut encoded),
+			NetworkBridgeTxMessage::ConnectToResolvedValidators { validator_addrs, peer_set } => {
+				let validator_addrs = validator_addrs
+					.iter()
+					.map(|addrs| addrs.iter().map(|addr| addr.to_vec()).collect::<Vec<_>>())
+					.collect::<Vec<_>>();
+
+				(validator_addrs, peer_set_name(peer_set)).encode_to(&mut encoded)
+			},
+		}
+
+		Some(encoded)
+	}
+}
+
+impl RecordableMessage for ProvisionerMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			ProvisionerMessage::RequestInherentData(relay_parent, _) => relay_parent.encode(),
+			ProvisionerMessage::ProvisionableData(relay_parent, data) => {
+				let mut encoded = relay_parent.encode();
+				match data {
+					ProvisionableData::Bitfield(relay_parent, bitfield) =>
+						("Bitfield", relay_parent, bitfield.as_unchecked()).encode_to(&mut encoded),
+					ProvisionableData::BackedCandidate(receipt) =>
+						("BackedCandidate", receipt).encode_to(&mut encoded),
+					ProvisionableData::MisbehaviorReport(relay_parent, validator_index, report) =>
+						("MisbehaviorReport", relay_parent, validator_index, report)
+							.encode_to(&mut encoded),
+					ProvisionableData::Dispute(relay_parent, signature) =>
+						("Dispute", relay_parent, signature).encode_to(&mut encoded),
+				}
+				encoded
+			},
+		})
+	}
+}
+
+impl RecordableMessage for RuntimeApiMessage {
+	fn encode_recorded(&self) -> Option<Vec<u8>> {
+		Some(match self {
+			RuntimeApiMessage::Request(relay_parent, request) => {
+				let mut encoded = relay_parent.encode();
+				encode_runtime_api_request(request, &mut encoded);
+				encoded
+			},
+		})
+	}
+}
+
+/// Append the recorded form of a runtime API request: the name of its variant, followed by its
+/// fields without the response channel.
+fn encode_runtime_api_request(request: &RuntimeApiRequest, dest: &mut Vec<u8>) {
+	match request {
+		RuntimeApiRequest::Version(_) => "Version".encode_to(dest),
+		RuntimeApiRequest::Authorities(_) => "Authorities".encode_to(dest),
+		RuntimeApiRequest::Validators(_) => "Validators".encode_to(dest),
+		RuntimeApiRequest::ValidatorGroups(_) => "ValidatorGroups".encode_to(dest),
+		RuntimeApiRequest::AvailabilityCores(_) => "AvailabilityCores".encode_to(dest),
+		RuntimeApiRequest::PersistedValidationData(para_id, assumption, _) =>
+			("PersistedValidationData", para_id, assumption).encode_to(dest),
+		RuntimeApiRequest::AssumedValidationData(para_id, expected_hash, _) =>
+			("AssumedValidationData", para_id, expected_hash).encode_to(dest),
+		RuntimeApiRequest::CheckValidationOutputs(para_id, commitments, _) =>
+			("CheckValidationOutputs", para_id, commitments).encode_to(dest),
+		RuntimeApiRequest::SessionIndexForChild(_) => "SessionIndexForChild".encode_to(dest),
+		RuntimeApiRequest::ValidationCode(para_id, assumption, _) =>
+			("ValidationCode", para_id, assumption).encode_to(dest),
+		RuntimeApiRequest::ValidationCodeByHash(validation_code_hash, _) =>
+			("ValidationCodeByHash", validation_code_hash).encode_to(dest),
+		RuntimeApiRequest::CandidatePendingAvailability(para_id, _) =>
+			("CandidatePendingAvailability", para_id).encode_to(dest),
+		RuntimeApiRequest::CandidateEvents(_) => "CandidateEvents".encode_to(dest),
+		RuntimeApiRequest::SessionInfo(session, _) => ("SessionInfo", session).encode_to(dest),
+		RuntimeApiRequest::DmqContents(para_id, _) => ("DmqContents", para_id).encode_to(dest),
+		RuntimeApiRequest::InboundHrmpChannelsContents(para_id, _) =>
+			("InboundHrmpChannelsContents", para_id).encode_to(dest),
+		RuntimeApiRequest::CurrentBabeEpoch(_) => "CurrentBabeEpoch".encode_to(dest),
+		RuntimeApiRequest::FetchOnChainVotes(_) => "FetchOnChainVotes".encode_to(dest),
+		RuntimeApiRequest::SubmitPvfCheckStatement(statement, signature, _) =>
+			("SubmitPvfCheckStatement", statement, signature).encode_to(dest),
+		RuntimeApiRequest::PvfsRequirePrecheck(_) => "PvfsRequirePrecheck".encode_to(dest),
+		RuntimeApiRequest::ValidationCodeHash(para_id, assumption, _) =>
+			("ValidationCodeHash", para_id, assumption).encode_to(dest),
+		RuntimeApiRequest::Disputes(_) => "Disputes".encode_to(dest),
+		RuntimeApiRequest::ApprovalVotingParams(_) => "ApprovalVotingParams".encode_to(dest),
+		RuntimeApiRequest::SubmitBackingMisbehaviorReport(report, signature, _) =>
+			("SubmitBackingMisbehaviorReport", report, signature).encode_to(dest),
+		RuntimeApiRequest::InherentWeightInfo(_) => "InherentWeightInfo".encode_to(dest),
+	}
+}
+
+/// The recorded form of a network bridge event: the name of its variant, followed by its
+/// fields. The message of a peer is appended by `encode_message`.
+fn encode_network_bridge_event<M>(
+	event: &NetworkBridgeEvent<M>,
+	encode_message: impl FnOnce(&M, &mut Vec<u8>),
+) -> Vec<u8> {
+	match event {
+		NetworkBridgeEvent::PeerConnected(peer, role, version, authority_ids) => (
+			"PeerConnected",
+			peer.to_bytes(),
+			observed_role_name(role),
+			u32::from(*version),
+			authority_ids.as_ref().map(|ids| ids.iter().collect::<Vec<_>>()),
+		)
+			.encode(),
+		NetworkBridgeEvent::PeerDisconnected(peer) =>
+			("PeerDisconnected", peer.to_bytes()).encode(),
+		NetworkBridgeEvent::NewGossipTopology(topology) => {
+			let canonical_shuffling = topology
+				.topology
+				.canonical_shuffling()
+				.iter()
+				.map(|info| {
+					(encode_peers(&info.peer_ids), info.validator_index, &info.discovery_id)
+				})
+				.collect::<Vec<_>>();
+
+			(
+				"NewGossipTopology",
+				topology.session,
+				encode_indices(topology.topology.shuffled_indices()),
+				canonical_shuffling,
+				topology.local_index,
+			)
+				.encode()
+		},
+		NetworkBridgeEvent::PeerMessage(peer, msg) => {
+			let mut encoded = ("PeerMessage", peer.to_bytes()).encode();
+			encode_message(msg, &mut encoded);
+			encoded
+		},
+		NetworkBridgeEvent::PeerViewChange(peer, view) =>
+			("PeerViewChange", peer.to_bytes(), view).encode(),
+		// Without the jaeger spans of the heads.
+		NetworkBridgeEvent::OurViewChange(view) => ("OurViewChange", &**view).encode(),
+	}
+}
+
+/// Append the recorded form of a versioned message: the name of its version, followed by the
+/// message.
+fn encode_versioned<V1: Encode, V2: Encode>(msg: &Versioned<V1, V2>, dest: &mut Vec<u8>) {
+	match msg {
+		Versioned::V1(msg) => ("V1", msg).encode_to(dest),
+		Versioned::V2(msg) => ("V2", msg).encode_to(dest),
+	}
+}
+
+/// Append the recorded form of a request, without its response channel.
+fn encode_request(request: &Requests, dest: &mut Vec<u8>) {
+	match request {
+		Requests::ChunkFetchingV1(r) => encode_outgoing_request("ChunkFetchingV1", r, dest),
+		Requests::CollationFetchingV1(r) => encode_outgoing_request("CollationFetchingV1", r, dest),
+		Requests::CollationFetchingV2(r) => encode_outgoing_request("CollationFetchingV2", r, dest),
+		Requests::PoVFetchingV1(r) => encode_outgoing_request("PoVFetchingV1", r, dest),
+		Requests::AvailableDataFetchingV1(r) =>
+			encode_outgoing_request("AvailableDataFetchingV1", r, dest),
+		Requests::StatementFetchingV1(r) => encode_outgoing_request("StatementFetchingV1", r, dest),
+		Requests::CandidateStatementsFetchingV1(r) =>
+			encode_outgoing_request("CandidateStatementsFetchingV1", r, dest),
+		Requests::DisputeSendingV1(r) => encode_outgoing_request("DisputeSendingV1", r, dest),
+	}
+}
+
+/// The protocol of a fallback request is implied by its type, so it is not recorded.
+fn encode_outgoing_request<Req: Encode, FallbackReq: Encode>(
+	name: &str,
+	request: &OutgoingRequest<Req, FallbackReq>,
+	dest: &mut Vec<u8>,
+) {
+	name.encode_to(dest);
+	match &request.peer {
+		Recipient::Peer(peer) => ("Peer", peer.to_bytes()).encode_to(dest),
+		Recipient::Authority(authority_id) => ("Authority", authority_id).encode_to(dest),
+	}
+	(&request.payload, request.fallback_request.as_ref().map(|(fallback, _)| fallback))
+		.encode_to(dest);
+}
+
+fn encode_peers(peers: &[PeerId]) -> Vec<Vec<u8>> {
+	peers.iter().map(PeerId::to_bytes).collect()
+}
+
+fn encode_indices(indices: &[usize]) -> Vec<u64> {
+	indices.iter().map(|i| *i as u64).collect()
+}
+
+fn observed_role_name(role: &ObservedRole) -> &'static str {
+	match role {
+		ObservedRole::Light => "Light",
+		ObservedRole::Full => "Full",
+		ObservedRole::Authority => "Authority",
+	}
+}
+
+fn peer_set_name(peer_set: &PeerSet) -> &'static str {
+	match peer_set {
+		PeerSet::Validation => "Validation",
+		PeerSet::Collation => "Collation",
+	}
+}
+
+fn if_disconnected_name(if_disconnected: &IfDisconnected) -> &'static str {
+	match if_disconnected {
+		IfDisconnected::TryConnect => "TryConnect",
+		IfDisconnected::ImmediateError => "ImmediateError",
+	}
+}
+
+fn reputation_change_name(rep: &UnifiedReputationChange) -> &'static str {
+	match rep {
+		UnifiedReputationChange::CostMajor(_) => "CostMajor",
+		UnifiedReputationChange::CostMinor(_) => "CostMinor",
+		UnifiedReputationChange::CostMajorRepeated(_) => "CostMajorRepeated",
+		UnifiedReputationChange::CostMinorRepeated(_) => "CostMinorRepeated",
+		UnifiedReputationChange::Malicious(_) => "Malicious",
+		UnifiedReputationChange::BenefitMinorFirst(_) => "BenefitMinorFirst",
+		UnifiedReputationChange::BenefitMinor(_) => "BenefitMinor",
+		UnifiedReputationChange::BenefitMajorFirst(_) => "BenefitMajorFirst",
+		UnifiedReputationChange::BenefitMajor(_) => "BenefitMajor",
+	}
+}
+
+/// A recorded [`OverseerSignal`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedSignal {
+	/// [`OverseerSignal::ActiveLeaves`], without the jaeger span of the activated leaf.
+	ActiveLeaves {
+		/// The activated leaf, its number and whether it was fresh.
+		activated: Option<(Hash, BlockNumber, LeafStatus)>,
+		/// The deactivated leaves.
+		deactivated: Vec<Hash>,
+	},
+	/// [`OverseerSignal::BlockFinalized`].
+	BlockFinalized(Hash, BlockNumber),
+	/// [`OverseerSignal::Conclude`].
+	Conclude,
+}
+
+impl From<&OverseerSignal> for RecordedSignal {
+	fn from(signal: &OverseerSignal) -> Self {
+		match signal {
+			OverseerSignal::ActiveLeaves(update) => RecordedSignal::ActiveLeaves {
+				activated: update
+					.activated
+					.as_ref()
+					.map(|leaf| (leaf.hash, leaf.number, leaf.status.clone())),
+				deactivated: update.deactivated.iter().cloned().collect(),
+			},
+			OverseerSignal::BlockFinalized(hash, number) =>
+				RecordedSignal::BlockFinalized(*hash, *number),
+			OverseerSignal::Conclude => RecordedSignal::Conclude,
+		}
+	}
+}
+
+impl RecordedSignal {
+	/// Reconstruct the signal. Activated leaves get a disabled jaeger span.
+	pub fn to_signal(&self) -> OverseerSignal {
+		match self {
+			RecordedSignal::ActiveLeaves { activated, deactivated } =>
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: activated.as_ref().map(|(hash, number, status)| ActivatedLeaf {
+						hash: *hash,
+						number: *number,
+						status: status.clone(),
+						span: Arc::new(jaeger::Span::Disabled),
+					}),
+					deactivated: deactivated.iter().cloned().collect(),
+				}),
+			RecordedSignal::BlockFinalized(hash, number) =>
+				OverseerSignal::BlockFinalized(*hash, *number),
+			RecordedSignal::Conclude => OverseerSignal::Conclude,
+		}
+	}
+}
+
+impl fmt::Display for RecordedSignal {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RecordedSignal::ActiveLeaves { activated, deactivated } => {
+				write!(f, "ActiveLeaves ")?;
+				match activated {
+					Some((hash, number, status)) => {
+						let status = match status {
+							LeafStatus::Fresh => "fresh",
+							LeafStatus::Stale => "stale",
+						};
+						write!(f, "{:?}:{}:{}", hash, number, status)?;
+					},
+					None => write!(f, "-")?,
+				}
+
+				let deactivated =
+					deactivated.iter().map(|h| format!("{:?}", h)).collect::<Vec<_>>();
+				write!(
+					f,
+					" {}",
+					if deactivated.is_empty() { "-".into() } else { deactivated.join(",") }
+				)
+			},
+			RecordedSignal::BlockFinalized(hash, number) =>
+				write!(f, "BlockFinalized {:?} {}", hash, number),
+			RecordedSignal::Conclude => write!(f, "Conclude"),
+		}
+	}
+}
+
+impl FromStr for RecordedSignal {
+	type Err = io::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parts: Vec<&str> = s.split(' ').collect();
+		match parts.as_slice() {
+			["ActiveLeaves", activated, deactivated] => {
+				let activated = match *activated {
+					"-" => None,
+					activated => match activated.split(':').collect::<Vec<_>>().as_slice() {
+						[hash, number, status] => Some((
+							parse_hash(hash)?,
+							number.parse().map_err(|_| invalid_data(s))?,
+							match *status {
+								"fresh" => LeafStatus::Fresh,
+								"stale" => LeafStatus::Stale,
+								_ => return Err(invalid_data(s)),
+							},
+						)),
+						_ => return Err(invalid_data(s)),
+					},
+				};
+
+				let deactivated = match *deactivated {
+					"-" => Vec::new(),
+					deactivated =>
+						deactivated.split(',').map(parse_hash).collect::<Result<_, _>>()?,
+				};
+
+				Ok(RecordedSignal::ActiveLeaves { activated, deactivated })
+			},
+			["BlockFinalized", hash, number] => Ok(RecordedSignal::BlockFinalized(
+				parse_hash(hash)?,
+				number.parse().map_err(|_| invalid_data(s))?,
+			)),
+			["Conclude"] => Ok(RecordedSignal::Conclude),
+			_ => Err(invalid_data(s)),
+		}
+	}
+}
+
+impl fmt::Display for TapRecord {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}\t{}\t", self.elapsed.as_micros(), self.subsystem)?;
+		match &self.event {
+			TapEvent::Signal(signal) => write!(f, "signal\t{}", signal),
+			TapEvent::Message(msg) => write!(
+				f,
+				"message\t{}\t{}",
+				msg.message_type,
+				msg.encoded
+					.as_ref()
+					.map_or_else(|| "-".into(), |encoded| to_hex(encoded, false)),
+			),
+		}
+	}
+}
+
+impl FromStr for TapRecord {
+	type Err = io::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(4, '\t');
+		let (elapsed, subsystem, kind, payload) =
+			match (parts.next(), parts.next(), parts.next(), parts.next()) {
+				(Some(elapsed), Some(subsystem), Some(kind), Some(payload)) =>
+					(elapsed, subsystem, kind, payload),
+				_ => return Err(invalid_data(s)),
+			};
+
+		let event = match kind {
+			"signal" => TapEvent::Signal(payload.parse()?),
+			"message" => {
+				let (message_type, encoded) =
+					payload.split_once('\t').ok_or_else(|| invalid_data(s))?;
+				let encoded = match encoded {
+					"-" => None,
+					encoded => Some(from_hex(encoded).map_err(|_| invalid_data(s))?),
+				};
+
+				TapEvent::Message(RecordedMessage { message_type: message_type.into(), encoded })
+			},
+			_ => return Err(invalid_data(s)),
+		};
+
+		Ok(TapRecord {
+			elapsed: Duration::from_micros(elapsed.parse().map_err(|_| invalid_data(s))?),
+			subsystem: subsystem.into(),
+			event,
+		})
+	}
+}
+
+/// Read a recording from the file at the given path.
+pub fn read_recording(path: &Path) -> io::Result<Vec<TapRecord>> {
+	parse_recording(BufReader::new(File::open(path)?))
+}
+
+/// Parse a recording, in the order of deliveries.
+pub fn parse_recording(reader: impl BufRead) -> io::Result<Vec<TapRecord>> {
+	let mut lines = reader.lines();
+	match lines.next() {
+		Some(Ok(header)) if header == RECORDING_HEADER => {},
+		Some(Err(err)) => return Err(err),
+		_ => return Err(invalid_data("missing recording header")),
+	}
+
+	lines
+		.filter(|line| line.as_ref().map_or(true, |l| !l.is_empty()))
+		.map(|line| line?.parse())
+		.collect()
+}
+
+fn parse_hash(s: &str) -> io::Result<Hash> {
+	Hash::from_str(s).map_err(|_| invalid_data(s))
+}
+
+fn invalid_data(s: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, format!("Invalid message tap record: {}", s))
+}
+
+/// A subsystem whose deliveries are recorded into a [`MessageTap`], if there is one.
+pub struct TappedSubsystem<Sub> {
+	subsystem: Sub,
+	tap: Option<MessageTap>,
+}
+
+impl<Sub> TappedSubsystem<Sub> {
+	/// Wrap a subsystem, recording its deliveries into the given tap under the name it is
+	/// spawned with. Without a tap, nothing is recorded.
+	pub fn new(subsystem: Sub, tap: Option<MessageTap>) -> Self {
+		Self { subsystem, tap }
+	}
+}
+
+impl<Context, Sub> Subsystem<Context, SubsystemError> for TappedSubsystem<Sub>
+where
+	Context: SubsystemContext<Error = SubsystemError, Signal = OverseerSignal> + Send,
+	Context::Message: RecordableMessage + fmt::Debug,
+	Sub: Subsystem<TappedContext<Context>, SubsystemError>,
+{
+	fn start(self, ctx: Context) -> SpawnedSubsystem<SubsystemError> {
+		let name = Arc::new(Mutex::new("unknown"));
+		let spawned =
+			self.subsystem
+				.start(TappedContext { inner: ctx, tap: self.tap, name: name.clone() });
+
+		// Nothing is received before the returned future is polled.
+		*name.lock() = spawned.name;
+		spawned
+	}
+}
+
+/// A subsystem context recording every delivery into a [`MessageTap`], if there is one.
+pub struct TappedContext<Context> {
+	inner: Context,
+	tap: Option<MessageTap>,
+	name: Arc<Mutex<&'static str>>,
+}
+
+impl<Context> TappedContext<Context> {
+	fn record<M: RecordableMessage + fmt::Debug>(
+		&self,
+		delivery: &FromOrchestra<M, OverseerSignal>,
+	) {
+		if let Some(tap) = self.tap.as_ref() {
+			tap.record(*self.name.lock(), delivery);
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<Context> SubsystemContext for TappedContext<Context>
+where
+	Context: SubsystemContext<Error = SubsystemError, Signal = OverseerSignal> + Send,
+	Context::Message: RecordableMessage + fmt::Debug,
+{
+	type Message = Context::Message;
+	type Signal = OverseerSignal;
+	type OutgoingMessages = Context::OutgoingMessages;
+	type Sender = Context::Sender;
+	type Error = SubsystemError;
+
+	async fn try_recv(
+		&mut self,
+	) -> Result<Option<FromOrchestra<Self::Message, OverseerSignal>>, ()> {
+		let delivery = self.inner.try_recv().await?;
+		if let Some(delivery) = delivery.as_ref() {
+			self.record(delivery);
+		}
+
+		Ok(delivery)
+	}
+
+	async fn recv(
+		&mut self,
+	) -> Result<FromOrchestra<Self::Message, OverseerSignal>, SubsystemError> {
+		let delivery = self.inner.recv().await?;
+		self.record(&delivery);
+
+		Ok(delivery)
+	}
+
+	fn spawn(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> Result<(), SubsystemError> {
+		self.inner.spawn(name, s)
+	}
+
+	fn spawn_blocking(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> Result<(), SubsystemError> {
+		self.inner.spawn_blocking(name, s)
+	}
+
+	fn sender(&mut self) -> &mut Self::Sender {
+		self.inner.sender()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Clone, Default)]
+	struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedBuffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[derive(Debug)]
+	struct TestMessage(u32);
+
+	impl RecordableMessage for TestMessage {
+		fn encode_recorded(&self) -> Option<Vec<u8>> {
+			Some(self.0.encode())
+		}
+	}
+
+	#[derive(Debug)]
+	enum UnrecordableMessage {
+		Request(#[allow(dead_code)] futures::channel::oneshot::Sender<()>),
+	}
+
+	impl RecordableMessage for UnrecordableMessage {}
+
+	#[test]
+	fn recording_round_trip() {
+		let buffer = SharedBuffer::default();
+		let tap = MessageTap::new(buffer.clone()).unwrap();
+
+		let activated = RecordedSignal::ActiveLeaves {
+			activated: Some((Hash::repeat_byte(1), 1, LeafStatus::Fresh)),
+			deactivated: Vec::new(),
+		};
+		let deactivated = RecordedSignal::ActiveLeaves {
+			activated: None,
+			deactivated: vec![Hash::repeat_byte(1), Hash::repeat_byte(2)],
+		};
+		let finalized = RecordedSignal::BlockFinalized(Hash::repeat_byte(3), 3);
+
+		for signal in [&activated, &deactivated, &finalized, &RecordedSignal::Conclude] {
+			tap.record::<TestMessage>("foo", &FromOrchestra::Signal(signal.to_signal()));
+		}
+		tap.record("bar", &FromOrchestra::Communication { msg: TestMessage(42) });
+		let (tx, _rx) = futures::channel::oneshot::channel();
+		tap.record("bar", &FromOrchestra::Communication { msg: UnrecordableMessage::Request(tx) });
+
+		tap.flush();
+		let recording = parse_recording(&buffer.0.lock()[..]).unwrap();
+		let events: Vec<_> =
+			recording.iter().map(|r| (r.subsystem.as_str(), r.event.clone())).collect();
+
+		assert_eq!(
+			events,
+			vec![
+				("foo", TapEvent::Signal(activated)),
+				("foo", TapEvent::Signal(deactivated)),
+				("foo", TapEvent::Signal(finalized)),
+				("foo", TapEvent::Signal(RecordedSignal::Conclude)),
+				(
+					"bar",
+					TapEvent::Message(RecordedMessage {
+						message_type: "TestMessage".into(),
+						encoded: Some(42u32.encode()),
+					})
+				),
+				(
+					"bar",
+					TapEvent::Message(RecordedMessage {
+						message_type: "Request".into(),
+						encoded: None,
+					})
+				),
+			],
+		);
+		assert!(recording.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+	}
+
+	#[test]
+	fn rejects_malformed_recordings() {
+		assert!(parse_recording(&b"0\tfoo\tsignal\tConclude\n"[..]).is_err());
+
+		let recording = format!("{}\n0\tfoo\tsignal\tBlockFinalized 0x12 1\n", RECORDING_HEADER);
+		assert!(parse_recording(recording.as_bytes()).is_err());
+	}
+}
@@ -0,0 +1,359 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of subsystems which stopped draining their queues.
+//!
+//! The metrics metronome periodically compares the number of messages and signals sent to each
+//! subsystem with the number the subsystem received. A subsystem with pending messages which
+//! didn't receive any of them for longer than [`STALL_THRESHOLD`] is reported as stalled.
+//!
+//! Subsystems wrapped into a [`ProfiledSubsystem`] additionally report the time spent handling
+//! each type of message, and the message they are currently handling, so a stall report can
+//! name the message the subsystem is stuck on.
+
+use std::{
+	collections::HashMap,
+	fmt,
+	pin::Pin,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use futures::Future;
+use parking_lot::Mutex;
+
+use crate::{
+	FromOrchestra, OverseerMetrics, OverseerSignal, SpawnedSubsystem, Subsystem, SubsystemContext,
+	SubsystemError, LOG_TARGET,
+};
+
+/// The time a subsystem may not receive any of its pending messages before it is reported as
+/// stalled.
+pub const STALL_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// The message types subsystems are currently handling.
+#[derive(Clone, Default)]
+pub(crate) struct SubsystemActivity(Arc<Mutex<HashMap<&'static str, (String, Instant)>>>);
+
+impl SubsystemActivity {
+	/// Note that the subsystem started handling a message of the given type.
+	fn start(&self, subsystem: &'static str, message_type: String) {
+		self.0.lock().insert(subsystem, (message_type, Instant::now()));
+	}
+
+	/// Note that the subsystem finished handling its current message, returning its type and
+	/// the time spent handling it.
+	fn finish(&self, subsystem: &'static str) -> Option<(String, Duration)> {
+		self.0
+			.lock()
+			.remove(subsystem)
+			.map(|(message_type, start)| (message_type, start.elapsed()))
+	}
+
+	/// The type of the message the subsystem is currently handling and for how long.
+	pub(crate) fn current(&self, subsystem: &'static str) -> Option<(String, Duration)> {
+		self.0
+			.lock()
+			.get(subsystem)
+			.map(|(message_type, start)| (message_type.clone(), start.elapsed()))
+	}
+}
+
+/// The name of the enum variant of a message or signal, e.g. `ActiveLeaves`.
+///
+/// Only the beginning of the `Debug` representation is formatted, so this is cheap even for
+/// messages with large payloads.
+pub(crate) fn message_type<M: fmt::Debug>(msg: &M) -> String {
+	struct VariantName(String);
+
+	impl fmt::Write for VariantName {
+		fn write_str(&mut self, s: &str) -> fmt::Result {
+			match s.find(|c: char| !(c.is_alphanumeric() || c == '_')) {
+				Some(end) => {
+					self.0.push_str(&s[..end]);
+					// Abort formatting, the rest isn't needed.
+					Err(fmt::Error)
+				},
+				None => {
+					self.0.push_str(s);
+					Ok(())
+				},
+			}
+		}
+	}
+
+	let mut name = VariantName(String::new());
+	let _ = fmt::write(&mut name, format_args!("{:?}", msg));
+
+	if name.0.is_empty() {
+		"unknown".into()
+	} else {
+		name.0
+	}
+}
+
+/// A subsystem which stopped draining its queue.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Stall {
+	/// The number of messages and signals waiting to be received.
+	pub queue_depth: usize,
+	/// The time since the subsystem last received anything.
+	pub stalled_for: Duration,
+}
+
+struct ChannelProgress {
+	received: usize,
+	last_progress: Instant,
+	idle: bool,
+	reported: bool,
+}
+
+/// Detects subsystems which stopped draining their queues.
+pub(crate) struct StallWatchdog {
+	threshold: Duration,
+	channels: HashMap<&'static str, ChannelProgress>,
+}
+
+impl StallWatchdog {
+	/// Create a watchdog reporting subsystems stalled for longer than the threshold.
+	pub(crate) fn new(threshold: Duration) -> Self {
+		Self { threshold, channels: HashMap::new() }
+	}
+
+	/// Note the total number of messages and signals sent to and received by a subsystem.
+	///
+	/// Returns a stall once per stall of the subsystem, when it is first detected.
+	pub(crate) fn check(
+		&mut self,
+		subsystem: &'static str,
+		sent: usize,
+		received: usize,
+		now: Instant,
+	) -> Option<Stall> {
+		let progress = self.channels.entry(subsystem).or_insert(ChannelProgress {
+			received,
+			last_progress: now,
+			idle: true,
+			reported: false,
+		});
+
+		// The stall clock starts once messages are pending, and restarts whenever any are received.
+		let idle = sent <= received;
+		if progress.received != received || idle || progress.idle {
+			if progress.reported {
+				gum::info!(target: LOG_TARGET, subsystem, "Subsystem resumed draining its queue");
+			}
+
+			*progress = ChannelProgress { received, last_progress: now, idle, reported: false };
+			return None
+		}
+
+		let stalled_for = now.saturating_duration_since(progress.last_progress);
+		if progress.reported || stalled_for < self.threshold {
+			return None
+		}
+
+		progress.reported = true;
+		Some(Stall { queue_depth: sent - received, stalled_for })
+	}
+}
+
+/// A subsystem reporting the time spent handling each type of message to the overseer metrics.
+///
+/// The metrics need to be the ones passed to the overseer, for stall reports to name the
+/// message the subsystem is stuck on.
+pub struct ProfiledSubsystem<Sub> {
+	subsystem: Sub,
+	metrics: OverseerMetrics,
+}
+
+impl<Sub> ProfiledSubsystem<Sub> {
+	/// Wrap a subsystem, reporting under the name it is spawned with.
+	pub fn new(subsystem: Sub, metrics: OverseerMetrics) -> Self {
+		Self { subsystem, metrics }
+	}
+}
+
+impl<Context, Sub> Subsystem<Context, SubsystemError> for ProfiledSubsystem<Sub>
+where
+	Context: SubsystemContext<Error = SubsystemError, Signal = OverseerSignal> + Send,
+	Context::Message: fmt::Debug,
+	Sub: Subsystem<ProfiledContext<Context>, SubsystemError>,
+{
+	fn start(self, ctx: Context) -> SpawnedSubsystem<SubsystemError> {
+		let name = Arc::new(Mutex::new(None));
+		let spawned = self.subsystem.start(ProfiledContext {
+			inner: ctx,
+			metrics: self.metrics,
+			name: name.clone(),
+		});
+
+		// The overseer meters the subsystem under the name it is spawned with, which is only
+		// known now. Nothing is received before the returned future is polled.
+		*name.lock() = Some(spawned.name);
+		spawned
+	}
+}
+
+/// A subsystem context measuring the time between receiving a message and asking for the next.
+pub struct ProfiledContext<Context> {
+	inner: Context,
+	metrics: OverseerMetrics,
+	name: Arc<Mutex<Option<&'static str>>>,
+}
+
+impl<Context> ProfiledContext<Context> {
+	fn on_handled(&self) {
+		let name = match *self.name.lock() {
+			Some(name) => name,
+			None => return,
+		};
+
+		if let Some((message_type, elapsed)) = self.metrics.activity().finish(name) {
+			self.metrics.on_message_handled(name, &message_type, elapsed);
+		}
+	}
+
+	fn on_delivery<M: fmt::Debug>(&self, delivery: &FromOrchestra<M, OverseerSignal>) {
+		let name = match *self.name.lock() {
+			Some(name) => name,
+			None => return,
+		};
+
+		let message_type = match delivery {
+			FromOrchestra::Signal(signal) => message_type(signal),
+			FromOrchestra::Communication { msg } => message_type(msg),
+		};
+
+		self.metrics.activity().start(name, message_type);
+	}
+}
+
+#[async_trait::async_trait]
+impl<Context> SubsystemContext for ProfiledContext<Context>
+where
+	Context: SubsystemContext<Error = SubsystemError, Signal = OverseerSignal> + Send,
+	Context::Message: fmt::Debug,
+{
+	type Message = Context::Message;
+	type Signal = OverseerSignal;
+	type OutgoingMessages = Context::OutgoingMessages;
+	type Sender = Context::Sender;
+	type Error = SubsystemError;
+
+	async fn try_recv(
+		&mut self,
+	) -> Result<Option<FromOrchestra<Self::Message, OverseerSignal>>, ()> {
+		self.on_handled();
+
+		let delivery = self.inner.try_recv().await?;
+		if let Some(delivery) = delivery.as_ref() {
+			self.on_delivery(delivery);
+		}
+
+		Ok(delivery)
+	}
+
+	async fn recv(
+		&mut self,
+	) -> Result<FromOrchestra<Self::Message, OverseerSignal>, SubsystemError> {
+		self.on_handled();
+
+		let delivery = self.inner.recv().await?;
+		self.on_delivery(&delivery);
+
+		Ok(delivery)
+	}
+
+	fn spawn(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> Result<(), SubsystemError> {
+		self.inner.spawn(name, s)
+	}
+
+	fn spawn_blocking(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> Result<(), SubsystemError> {
+		self.inner.spawn_blocking(name, s)
+	}
+
+	fn sender(&mut self) -> &mut Self::Sender {
+		self.inner.sender()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug)]
+	#[allow(dead_code)]
+	enum TestMessage {
+		Unit,
+		Tuple(Vec<u8>),
+		Struct { payload: Vec<u8> },
+	}
+
+	#[test]
+	fn message_type_is_variant_name() {
+		assert_eq!(message_type(&TestMessage::Unit), "Unit");
+		assert_eq!(message_type(&TestMessage::Tuple(vec![1; 1024])), "Tuple");
+		assert_eq!(message_type(&TestMessage::Struct { payload: vec![] }), "Struct");
+		assert_eq!(message_type(&OverseerSignal::Conclude), "Conclude");
+	}
+
+	#[test]
+	fn reports_stall_once_after_threshold() {
+		let mut watchdog = StallWatchdog::new(STALL_THRESHOLD);
+		let start = Instant::now();
+
+		assert_eq!(watchdog.check("foo", 10, 5, start), None);
+		assert_eq!(watchdog.check("foo", 12, 5, start + STALL_THRESHOLD / 2), None);
+		assert_eq!(
+			watchdog.check("foo", 12, 5, start + STALL_THRESHOLD),
+			Some(Stall { queue_depth: 7, stalled_for: STALL_THRESHOLD }),
+		);
+		assert_eq!(watchdog.check("foo", 12, 5, start + STALL_THRESHOLD * 2), None);
+
+		// Receiving anything resets the stall.
+		assert_eq!(watchdog.check("foo", 12, 6, start + STALL_THRESHOLD * 2), None);
+		assert_eq!(
+			watchdog.check("foo", 12, 6, start + STALL_THRESHOLD * 3),
+			Some(Stall { queue_depth: 6, stalled_for: STALL_THRESHOLD }),
+		);
+	}
+
+	#[test]
+	fn empty_queues_never_stall() {
+		let mut watchdog = StallWatchdog::new(STALL_THRESHOLD);
+		let start = Instant::now();
+
+		assert_eq!(watchdog.check("foo", 5, 5, start), None);
+		assert_eq!(watchdog.check("foo", 5, 5, start + STALL_THRESHOLD * 2), None);
+
+		// The stall clock starts when messages are pending.
+		assert_eq!(watchdog.check("foo", 6, 5, start + STALL_THRESHOLD * 3), None);
+		assert_eq!(
+			watchdog.check("foo", 6, 5, start + STALL_THRESHOLD * 4 - STALL_THRESHOLD / 2),
+			None
+		);
+		assert!(watchdog.check("foo", 6, 5, start + STALL_THRESHOLD * 4).is_some());
+	}
+}
@@ -19,6 +19,7 @@
 pub use sp_consensus_babe::Slot;
 pub use sp_consensus_vrf::schnorrkel::{Randomness, VRFOutput, VRFProof};
 
+use bitvec::{order::Lsb0, vec::BitVec};
 use parity_scale_codec::{Decode, Encode};
 use polkadot_primitives::{
 	BlockNumber, CandidateHash, CandidateIndex, CoreIndex, Hash, Header, SessionIndex,
@@ -114,8 +115,36 @@ pub struct IndirectSignedApprovalVote {
 	pub signature: ValidatorSignature,
 }
 
+/// A bitfield of candidates, indexed by [`CandidateIndex`] within a relay-chain block.
+pub type CandidateBitfield = BitVec<u8, Lsb0>;
+
+/// A signed approval vote covering multiple candidates of the same block, which references
+/// the candidates indirectly via the block.
+///
+/// The signature is over the hashes of all covered candidates, in the order of their
+/// candidate indices, see [`polkadot_primitives::vstaging::ApprovalVoteMultipleCandidates`].
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub struct IndirectSignedCoalescedApprovalVote {
+	/// A block hash where the candidates appear.
+	pub block_hash: Hash,
+	/// The indices of the approved candidates in the list of candidates fully included
+	/// as-of the block.
+	pub candidate_indices: CandidateBitfield,
+	/// The validator index.
+	pub validator: ValidatorIndex,
+	/// The signature by the validator.
+	pub signature: ValidatorSignature,
+}
+
+impl IndirectSignedCoalescedApprovalVote {
+	/// The indices of all candidates covered by this vote, in ascending order.
+	pub fn candidate_indices(&self) -> impl Iterator<Item = CandidateIndex> + '_ {
+		self.candidate_indices.iter_ones().map(|i| i as CandidateIndex)
+	}
+}
+
 /// Metadata about a block which is now live in the approval protocol.
-#[derive(Debug)]
+#[derive(Debug, Encode, Decode)]
 pub struct BlockApprovalMeta {
 	/// The hash of the block.
 	pub hash: Hash,
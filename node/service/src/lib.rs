@@ -27,7 +27,9 @@ mod relay_chain_selection;
 pub mod overseer;
 
 #[cfg(feature = "full-node")]
-pub use self::overseer::{OverseerGen, OverseerGenArgs, RealOverseerGen};
+pub use self::overseer::{
+	CollatorFilter, MessageTap, OverseerGen, OverseerGenArgs, PeerFilter, RealOverseerGen,
+};
 
 #[cfg(test)]
 mod tests;
@@ -36,6 +38,9 @@ mod tests;
 use {
 	grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider},
 	gum::info,
+	polkadot_dispute_distribution::Config as DisputeDistributionConfig,
+	polkadot_gossip_support::SharedConnectivityReport,
+	polkadot_network_bridge::{NetworkTraffic, ReputationAggregator},
 	polkadot_node_core_approval_voting::{
 		self as approval_voting_subsystem, Config as ApprovalVotingConfig,
 	},
@@ -55,7 +60,7 @@ use {
 	sp_trie::PrefixedMemoryDB,
 };
 
-use polkadot_node_subsystem_util::database::Database;
+use polkadot_node_subsystem_util::{database::Database, pvf_queue_depth::PvfQueueDepth};
 
 #[cfg(feature = "full-node")]
 pub use {
@@ -447,6 +452,7 @@ fn new_partial<RuntimeApi, ExecutorDispatch, ChainSelection>(
 		ExecutorDispatch,
 	>,
 	select_chain: ChainSelection,
+	parachain_network: polkadot_rpc::ParachainNetworkDeps,
 ) -> Result<
 	service::PartialComponents<
 		FullClient<RuntimeApi, ExecutorDispatch>,
@@ -595,6 +601,7 @@ where
 					beefy_best_block_stream: beefy_rpc_links.from_voter_best_beefy_stream.clone(),
 					subscription_executor,
 				},
+				parachain_network: parachain_network.clone(),
 			};
 
 			polkadot_rpc::create_full(deps, backend.clone()).map_err(Into::into)
@@ -742,6 +749,11 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	overseer_enable_anyways: bool,
 	overseer_gen: OverseerGenerator,
 	overseer_message_channel_capacity_override: Option<usize>,
+	collator_filter: CollatorFilter,
+	parachain_outbound_bandwidth: Option<u64>,
+	dispute_send_rate_limit: Option<Duration>,
+	peer_filter: PeerFilter,
+	message_tap: Option<MessageTap>,
 	_malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> Result<NewFull<Arc<FullClient<RuntimeApi, ExecutorDispatch>>>, Error>
@@ -820,6 +832,16 @@ where
 		SelectRelayChain::new_longest_chain(basics.backend.clone())
 	};
 
+	let network_traffic = parachain_outbound_bandwidth
+		.map_or_else(NetworkTraffic::default, NetworkTraffic::with_outbound_limit);
+	let connectivity_report = SharedConnectivityReport::default();
+
+	let parachains_db = open_database(&config.database)?;
+	let reputation = ReputationAggregator::new(
+		peer_filter,
+		Some((parachains_db.clone(), parachains_db::REAL_COLUMNS.col_peer_reputation_data)),
+	);
+
 	let service::PartialComponents::<_, _, SelectRelayChain<_>, _, _, _> {
 		client,
 		backend,
@@ -833,6 +855,11 @@ where
 		&mut config,
 		basics,
 		select_chain,
+		polkadot_rpc::ParachainNetworkDeps {
+			traffic: network_traffic.clone(),
+			connectivity_report: connectivity_report.clone(),
+			reputation: reputation.clone(),
+		},
 	)?;
 
 	let shared_voter_state = rpc_setup;
@@ -889,11 +916,17 @@ where
 	config.network.request_response_protocols.push(cfg);
 	let (collation_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
+	let (collation_req_v2_receiver, cfg) =
+		IncomingRequest::get_config_receiver(&req_protocol_names);
+	config.network.request_response_protocols.push(cfg);
 	let (available_data_req_receiver, cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
 	let (statement_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
+	let (candidate_statements_req_receiver, cfg) =
+		IncomingRequest::get_config_receiver(&req_protocol_names);
+	config.network.request_response_protocols.push(cfg);
 	let (dispute_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
 
@@ -940,12 +973,14 @@ where
 		);
 	}
 
-	let parachains_db = open_database(&config.database)?;
+	// Lets approval voting shed load while candidate validation is busy.
+	let pvf_queue_depth = PvfQueueDepth::default();
 
 	let approval_voting_config = ApprovalVotingConfig {
 		col_approval_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
 		slot_duration_millis: slot_duration.as_millis() as u64,
+		pvf_queue_depth: pvf_queue_depth.clone(),
 	};
 
 	let candidate_validation_config = CandidateValidationConfig {
@@ -958,6 +993,7 @@ where
 			None => std::env::current_exe()?,
 			Some(p) => p,
 		},
+		pvf_queue_depth,
 	};
 
 	let chain_selection_config = ChainSelectionConfig {
@@ -971,6 +1007,16 @@ where
 		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
 	};
 
+	let dispute_distribution_config = dispute_send_rate_limit
+		.map_or_else(Default::default, |send_rate_limit| DisputeDistributionConfig {
+			send_rate_limit,
+		});
+
+	let collator_protocol_config = overseer::CollatorProtocolConfig {
+		col_reputation_data: parachains_db::REAL_COLUMNS.col_collator_reputation_data,
+		collator_filter,
+	};
+
 	let rpc_handlers = service::spawn_tasks(service::SpawnTasksParams {
 		config,
 		backend: backend.clone(),
@@ -1073,11 +1119,16 @@ where
 					parachains_db,
 					network_service: network.clone(),
 					authority_discovery_service,
+					network_traffic,
+					reputation,
+					connectivity_report,
 					pov_req_receiver,
 					chunk_req_receiver,
 					collation_req_receiver,
+					collation_req_v2_receiver,
 					available_data_req_receiver,
 					statement_req_receiver,
+					candidate_statements_req_receiver,
 					dispute_req_receiver,
 					registry: prometheus_registry.as_ref(),
 					spawner,
@@ -1087,10 +1138,13 @@ where
 					candidate_validation_config,
 					chain_selection_config,
 					dispute_coordinator_config,
+					dispute_distribution_config,
+					collator_protocol_config,
 					pvf_checker_enabled,
 					overseer_message_channel_capacity_override,
 					req_protocol_names,
 					peerset_protocol_names,
+					message_tap,
 				},
 			)
 			.map_err(|e| {
@@ -1334,6 +1388,7 @@ macro_rules! chain_ops {
 				&mut config,
 				basics,
 				chain_selection,
+				Default::default(),
 			)?;
 		Ok((Arc::new(Client::$variant(client)), backend, import_queue, task_manager))
 	}};
@@ -1402,6 +1457,11 @@ pub fn build_full(
 	overseer_enable_anyways: bool,
 	overseer_gen: impl OverseerGen,
 	overseer_message_channel_override: Option<usize>,
+	collator_filter: CollatorFilter,
+	parachain_outbound_bandwidth: Option<u64>,
+	dispute_send_rate_limit: Option<Duration>,
+	peer_filter: PeerFilter,
+	message_tap: Option<MessageTap>,
 	malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> Result<NewFull<Client>, Error> {
@@ -1421,6 +1481,11 @@ pub fn build_full(
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			collator_filter,
+			parachain_outbound_bandwidth,
+			dispute_send_rate_limit,
+			peer_filter,
+			message_tap,
 			malus_finality_delay,
 			hwbench,
 		)
@@ -1440,6 +1505,11 @@ pub fn build_full(
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			collator_filter,
+			parachain_outbound_bandwidth,
+			dispute_send_rate_limit,
+			peer_filter,
+			message_tap,
 			malus_finality_delay,
 			hwbench,
 		)
@@ -1459,6 +1529,11 @@ pub fn build_full(
 			overseer_enable_anyways,
 			overseer_gen,
 			overseer_message_channel_override,
+			collator_filter,
+			parachain_outbound_bandwidth,
+			dispute_send_rate_limit,
+			peer_filter,
+			message_tap,
 			malus_finality_delay,
 			hwbench,
 		)
@@ -1481,6 +1556,11 @@ pub fn build_full(
 				gum::warn!("Channel capacity should _never_ be tampered with on polkadot!");
 				capacity
 			}),
+			collator_filter,
+			parachain_outbound_bandwidth,
+			dispute_send_rate_limit,
+			peer_filter,
+			message_tap,
 			malus_finality_delay,
 			hwbench,
 		)
@@ -1550,6 +1630,7 @@ fn revert_approval_voting(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::R
 		col_approval_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
 		slot_duration_millis: Default::default(),
+		pvf_queue_depth: Default::default(),
 	};
 
 	let approval_voting = approval_voting_subsystem::ApprovalVotingSubsystem::with_config(
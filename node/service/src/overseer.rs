@@ -19,6 +19,7 @@ use sp_core::traits::SpawnNamed;
 
 use lru::LruCache;
 use polkadot_availability_distribution::IncomingRequestReceivers;
+use polkadot_dispute_distribution::Config as DisputeDistributionConfig;
 use polkadot_node_core_approval_voting::Config as ApprovalVotingConfig;
 use polkadot_node_core_av_store::Config as AvailabilityConfig;
 use polkadot_node_core_candidate_validation::Config as CandidateValidationConfig;
@@ -26,16 +27,20 @@ use polkadot_node_core_chain_selection::Config as ChainSelectionConfig;
 use polkadot_node_core_dispute_coordinator::Config as DisputeCoordinatorConfig;
 use polkadot_node_network_protocol::{
 	peer_set::PeerSetProtocolNames,
-	request_response::{v1 as request_v1, IncomingRequestReceiver, ReqProtocolNames},
+	request_response::{
+		v1 as request_v1, v2 as request_v2, IncomingRequestReceiver, ReqProtocolNames,
+	},
 };
+pub use polkadot_overseer::tap::MessageTap;
 #[cfg(any(feature = "malus", test))]
 pub use polkadot_overseer::{
 	dummy::{dummy_overseer_builder, DummySubsystem},
 	HeadSupportsParachains,
 };
 use polkadot_overseer::{
-	metrics::Metrics as OverseerMetrics, BlockInfo, InitializedOverseerBuilder, MetricsTrait,
-	Overseer, OverseerConnector, OverseerHandle, SpawnGlue,
+	metrics::Metrics as OverseerMetrics, tap::TappedSubsystem, watchdog::ProfiledSubsystem,
+	BlockInfo, InitializedOverseerBuilder, MetricsTrait, Overseer, OverseerConnector,
+	OverseerHandle, SpawnGlue,
 };
 
 use polkadot_primitives::runtime_api::ParachainHost;
@@ -52,12 +57,17 @@ pub use polkadot_approval_distribution::ApprovalDistribution as ApprovalDistribu
 pub use polkadot_availability_bitfield_distribution::BitfieldDistribution as BitfieldDistributionSubsystem;
 pub use polkadot_availability_distribution::AvailabilityDistributionSubsystem;
 pub use polkadot_availability_recovery::AvailabilityRecoverySubsystem;
-pub use polkadot_collator_protocol::{CollatorProtocolSubsystem, ProtocolSide};
+pub use polkadot_collator_protocol::{
+	CollatorFilter, CollatorProtocolSubsystem, ProtocolSide,
+	ValidatorSideConfig as CollatorProtocolConfig,
+};
 pub use polkadot_dispute_distribution::DisputeDistributionSubsystem;
-pub use polkadot_gossip_support::GossipSupport as GossipSupportSubsystem;
+pub use polkadot_gossip_support::{
+	GossipSupport as GossipSupportSubsystem, SharedConnectivityReport,
+};
 pub use polkadot_network_bridge::{
 	Metrics as NetworkBridgeMetrics, NetworkBridgeRx as NetworkBridgeRxSubsystem,
-	NetworkBridgeTx as NetworkBridgeTxSubsystem,
+	NetworkBridgeTx as NetworkBridgeTxSubsystem, NetworkTraffic, PeerFilter, ReputationAggregator,
 };
 pub use polkadot_node_collation_generation::CollationGenerationSubsystem;
 pub use polkadot_node_core_approval_voting::ApprovalVotingSubsystem;
@@ -93,13 +103,22 @@ where
 	pub network_service: Arc<sc_network::NetworkService<Block, Hash>>,
 	/// Underlying authority discovery service.
 	pub authority_discovery_service: AuthorityDiscoveryService,
+	/// Traffic accounting and outbound budget of the network bridge.
+	pub network_traffic: NetworkTraffic,
+	/// Aggregated reputation changes of the peers of the network bridge.
+	pub reputation: ReputationAggregator,
+	/// The connectivity report published by the gossip support.
+	pub connectivity_report: SharedConnectivityReport,
 	/// POV request receiver
 	pub pov_req_receiver: IncomingRequestReceiver<request_v1::PoVFetchingRequest>,
 	pub chunk_req_receiver: IncomingRequestReceiver<request_v1::ChunkFetchingRequest>,
 	pub collation_req_receiver: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+	pub collation_req_v2_receiver: IncomingRequestReceiver<request_v2::CollationFetchingRequest>,
 	pub available_data_req_receiver:
 		IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
 	pub statement_req_receiver: IncomingRequestReceiver<request_v1::StatementFetchingRequest>,
+	pub candidate_statements_req_receiver:
+		IncomingRequestReceiver<request_v1::CandidateStatementsFetchingRequest>,
 	pub dispute_req_receiver: IncomingRequestReceiver<request_v1::DisputeRequest>,
 	/// Prometheus registry, commonly used for production systems, less so for test.
 	pub registry: Option<&'a Registry>,
@@ -117,6 +136,10 @@ where
 	pub chain_selection_config: ChainSelectionConfig,
 	/// Configuration for the dispute coordinator subsystem.
 	pub dispute_coordinator_config: DisputeCoordinatorConfig,
+	/// Configuration for the dispute distribution subsystem.
+	pub dispute_distribution_config: DisputeDistributionConfig,
+	/// Configuration for the validator side of the collator protocol subsystem.
+	pub collator_protocol_config: CollatorProtocolConfig,
 	/// Enable PVF pre-checking
 	pub pvf_checker_enabled: bool,
 	/// Overseer channel capacity override.
@@ -125,6 +148,20 @@ where
 	pub req_protocol_names: ReqProtocolNames,
 	/// [`PeerSet`] protocol names to protocols mapping.
 	pub peerset_protocol_names: PeerSetProtocolNames,
+	/// Records the deliveries to all subsystems, if set.
+	pub message_tap: Option<MessageTap>,
+}
+
+/// A subsystem as built by [`prepared_overseer_builder`]: profiled, and tapped if there is a
+/// message tap.
+pub type InstrumentedSubsystem<Sub> = ProfiledSubsystem<TappedSubsystem<Sub>>;
+
+fn instrumented<Sub>(
+	subsystem: Sub,
+	metrics: &OverseerMetrics,
+	message_tap: &Option<MessageTap>,
+) -> InstrumentedSubsystem<Sub> {
+	ProfiledSubsystem::new(TappedSubsystem::new(subsystem, message_tap.clone()), metrics.clone())
 }
 
 /// Obtain a prepared `OverseerBuilder`, that is initialized
@@ -137,11 +174,16 @@ pub fn prepared_overseer_builder<Spawner, RuntimeClient>(
 		parachains_db,
 		network_service,
 		authority_discovery_service,
+		network_traffic,
+		reputation,
+		connectivity_report,
 		pov_req_receiver,
 		chunk_req_receiver,
 		collation_req_receiver,
+		collation_req_v2_receiver,
 		available_data_req_receiver,
 		statement_req_receiver,
+		candidate_statements_req_receiver,
 		dispute_req_receiver,
 		registry,
 		spawner,
@@ -151,43 +193,50 @@ pub fn prepared_overseer_builder<Spawner, RuntimeClient>(
 		candidate_validation_config,
 		chain_selection_config,
 		dispute_coordinator_config,
+		dispute_distribution_config,
+		collator_protocol_config,
 		pvf_checker_enabled,
 		overseer_message_channel_capacity_override,
 		req_protocol_names,
 		peerset_protocol_names,
+		message_tap,
 	}: OverseerGenArgs<Spawner, RuntimeClient>,
 ) -> Result<
 	InitializedOverseerBuilder<
 		SpawnGlue<Spawner>,
 		Arc<RuntimeClient>,
-		CandidateValidationSubsystem,
-		PvfCheckerSubsystem,
-		CandidateBackingSubsystem,
-		StatementDistributionSubsystem<rand::rngs::StdRng>,
-		AvailabilityDistributionSubsystem,
-		AvailabilityRecoverySubsystem,
-		BitfieldSigningSubsystem,
-		BitfieldDistributionSubsystem,
-		ProvisionerSubsystem,
-		RuntimeApiSubsystem<RuntimeClient>,
-		AvailabilityStoreSubsystem,
-		NetworkBridgeRxSubsystem<
-			Arc<sc_network::NetworkService<Block, Hash>>,
-			AuthorityDiscoveryService,
+		InstrumentedSubsystem<CandidateValidationSubsystem>,
+		InstrumentedSubsystem<PvfCheckerSubsystem>,
+		InstrumentedSubsystem<CandidateBackingSubsystem>,
+		InstrumentedSubsystem<StatementDistributionSubsystem<rand::rngs::StdRng>>,
+		InstrumentedSubsystem<AvailabilityDistributionSubsystem>,
+		InstrumentedSubsystem<AvailabilityRecoverySubsystem>,
+		InstrumentedSubsystem<BitfieldSigningSubsystem>,
+		InstrumentedSubsystem<BitfieldDistributionSubsystem>,
+		InstrumentedSubsystem<ProvisionerSubsystem>,
+		InstrumentedSubsystem<RuntimeApiSubsystem<RuntimeClient>>,
+		InstrumentedSubsystem<AvailabilityStoreSubsystem>,
+		InstrumentedSubsystem<
+			NetworkBridgeRxSubsystem<
+				Arc<sc_network::NetworkService<Block, Hash>>,
+				AuthorityDiscoveryService,
+			>,
 		>,
-		NetworkBridgeTxSubsystem<
-			Arc<sc_network::NetworkService<Block, Hash>>,
-			AuthorityDiscoveryService,
+		InstrumentedSubsystem<
+			NetworkBridgeTxSubsystem<
+				Arc<sc_network::NetworkService<Block, Hash>>,
+				AuthorityDiscoveryService,
+			>,
 		>,
-		ChainApiSubsystem<RuntimeClient>,
-		CollationGenerationSubsystem,
-		CollatorProtocolSubsystem,
-		ApprovalDistributionSubsystem,
-		ApprovalVotingSubsystem,
-		GossipSupportSubsystem<AuthorityDiscoveryService>,
-		DisputeCoordinatorSubsystem,
-		DisputeDistributionSubsystem<AuthorityDiscoveryService>,
-		ChainSelectionSubsystem,
+		InstrumentedSubsystem<ChainApiSubsystem<RuntimeClient>>,
+		InstrumentedSubsystem<CollationGenerationSubsystem>,
+		InstrumentedSubsystem<CollatorProtocolSubsystem>,
+		InstrumentedSubsystem<ApprovalDistributionSubsystem>,
+		InstrumentedSubsystem<ApprovalVotingSubsystem>,
+		InstrumentedSubsystem<GossipSupportSubsystem<AuthorityDiscoveryService>>,
+		InstrumentedSubsystem<DisputeCoordinatorSubsystem>,
+		InstrumentedSubsystem<DisputeDistributionSubsystem<AuthorityDiscoveryService>>,
+		InstrumentedSubsystem<ChainSelectionSubsystem>,
 	>,
 	Error,
 >
@@ -205,109 +254,201 @@ where
 	let network_bridge_metrics: NetworkBridgeMetrics = Metrics::register(registry)?;
 
 	let builder = Overseer::builder()
-		.network_bridge_tx(NetworkBridgeTxSubsystem::new(
-			network_service.clone(),
-			authority_discovery_service.clone(),
-			network_bridge_metrics.clone(),
-			req_protocol_names,
-			peerset_protocol_names.clone(),
+		.network_bridge_tx(instrumented(
+			NetworkBridgeTxSubsystem::new(
+				network_service.clone(),
+				authority_discovery_service.clone(),
+				network_bridge_metrics.clone(),
+				req_protocol_names,
+				peerset_protocol_names.clone(),
+				network_traffic.clone(),
+				reputation.clone(),
+			),
+			&metrics,
+			&message_tap,
+		))
+		.network_bridge_rx(instrumented(
+			NetworkBridgeRxSubsystem::new(
+				network_service.clone(),
+				authority_discovery_service.clone(),
+				Box::new(network_service.clone()),
+				network_bridge_metrics,
+				peerset_protocol_names,
+				network_traffic,
+				reputation,
+			),
+			&metrics,
+			&message_tap,
+		))
+		.availability_distribution(instrumented(
+			AvailabilityDistributionSubsystem::new(
+				keystore.clone(),
+				IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
+		))
+		.availability_recovery(instrumented(
+			AvailabilityRecoverySubsystem::with_chunks_only(
+				available_data_req_receiver,
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
+		))
+		.availability_store(instrumented(
+			AvailabilityStoreSubsystem::new(
+				parachains_db.clone(),
+				availability_config,
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
+		))
+		.bitfield_distribution(instrumented(
+			BitfieldDistributionSubsystem::new(Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
+		))
+		.bitfield_signing(instrumented(
+			BitfieldSigningSubsystem::new(keystore.clone(), Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
+		))
+		.candidate_backing(instrumented(
+			CandidateBackingSubsystem::new(keystore.clone(), Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
 		))
-		.network_bridge_rx(NetworkBridgeRxSubsystem::new(
-			network_service.clone(),
-			authority_discovery_service.clone(),
-			Box::new(network_service.clone()),
-			network_bridge_metrics,
-			peerset_protocol_names,
+		.candidate_validation(instrumented(
+			CandidateValidationSubsystem::with_config(
+				candidate_validation_config,
+				Metrics::register(registry)?, // candidate-validation metrics
+				Metrics::register(registry)?, // validation host metrics
+			),
+			&metrics,
+			&message_tap,
 		))
-		.availability_distribution(AvailabilityDistributionSubsystem::new(
-			keystore.clone(),
-			IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
-			Metrics::register(registry)?,
+		.pvf_checker(instrumented(
+			PvfCheckerSubsystem::new(
+				pvf_checker_enabled,
+				keystore.clone(),
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
 		))
-		.availability_recovery(AvailabilityRecoverySubsystem::with_chunks_only(
-			available_data_req_receiver,
-			Metrics::register(registry)?,
+		.chain_api(instrumented(
+			ChainApiSubsystem::new(runtime_client.clone(), Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
 		))
-		.availability_store(AvailabilityStoreSubsystem::new(
-			parachains_db.clone(),
-			availability_config,
-			Metrics::register(registry)?,
+		.collation_generation(instrumented(
+			CollationGenerationSubsystem::new(Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
 		))
-		.bitfield_distribution(BitfieldDistributionSubsystem::new(Metrics::register(registry)?))
-		.bitfield_signing(BitfieldSigningSubsystem::new(
-			keystore.clone(),
-			Metrics::register(registry)?,
+		.collator_protocol(instrumented(
+			{
+				let side = match is_collator {
+					IsCollator::Yes(collator_pair) => ProtocolSide::Collator(
+						network_service.local_peer_id(),
+						collator_pair,
+						collation_req_receiver,
+						collation_req_v2_receiver,
+						Metrics::register(registry)?,
+					),
+					IsCollator::No => ProtocolSide::Validator {
+						keystore: keystore.clone(),
+						eviction_policy: Default::default(),
+						db: parachains_db.clone(),
+						config: collator_protocol_config,
+						metrics: Metrics::register(registry)?,
+					},
+				};
+				CollatorProtocolSubsystem::new(side)
+			},
+			&metrics,
+			&message_tap,
 		))
-		.candidate_backing(CandidateBackingSubsystem::new(
-			keystore.clone(),
-			Metrics::register(registry)?,
+		.provisioner(instrumented(
+			ProvisionerSubsystem::new(Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
 		))
-		.candidate_validation(CandidateValidationSubsystem::with_config(
-			candidate_validation_config,
-			Metrics::register(registry)?, // candidate-validation metrics
-			Metrics::register(registry)?, // validation host metrics
+		.runtime_api(instrumented(
+			RuntimeApiSubsystem::new(
+				runtime_client.clone(),
+				Metrics::register(registry)?,
+				spawner.clone(),
+			),
+			&metrics,
+			&message_tap,
 		))
-		.pvf_checker(PvfCheckerSubsystem::new(
-			pvf_checker_enabled,
-			keystore.clone(),
-			Metrics::register(registry)?,
+		.statement_distribution(instrumented(
+			StatementDistributionSubsystem::new(
+				keystore.clone(),
+				statement_req_receiver,
+				candidate_statements_req_receiver,
+				Metrics::register(registry)?,
+				rand::rngs::StdRng::from_entropy(),
+			),
+			&metrics,
+			&message_tap,
 		))
-		.chain_api(ChainApiSubsystem::new(runtime_client.clone(), Metrics::register(registry)?))
-		.collation_generation(CollationGenerationSubsystem::new(Metrics::register(registry)?))
-		.collator_protocol({
-			let side = match is_collator {
-				IsCollator::Yes(collator_pair) => ProtocolSide::Collator(
-					network_service.local_peer_id(),
-					collator_pair,
-					collation_req_receiver,
-					Metrics::register(registry)?,
-				),
-				IsCollator::No => ProtocolSide::Validator {
-					keystore: keystore.clone(),
-					eviction_policy: Default::default(),
-					metrics: Metrics::register(registry)?,
-				},
-			};
-			CollatorProtocolSubsystem::new(side)
-		})
-		.provisioner(ProvisionerSubsystem::new(Metrics::register(registry)?))
-		.runtime_api(RuntimeApiSubsystem::new(
-			runtime_client.clone(),
-			Metrics::register(registry)?,
-			spawner.clone(),
+		.approval_distribution(instrumented(
+			ApprovalDistributionSubsystem::new(Metrics::register(registry)?),
+			&metrics,
+			&message_tap,
 		))
-		.statement_distribution(StatementDistributionSubsystem::new(
-			keystore.clone(),
-			statement_req_receiver,
-			Metrics::register(registry)?,
-			rand::rngs::StdRng::from_entropy(),
+		.approval_voting(instrumented(
+			ApprovalVotingSubsystem::with_config(
+				approval_voting_config,
+				parachains_db.clone(),
+				keystore.clone(),
+				Box::new(network_service.clone()),
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
 		))
-		.approval_distribution(ApprovalDistributionSubsystem::new(Metrics::register(registry)?))
-		.approval_voting(ApprovalVotingSubsystem::with_config(
-			approval_voting_config,
-			parachains_db.clone(),
-			keystore.clone(),
-			Box::new(network_service.clone()),
-			Metrics::register(registry)?,
+		.gossip_support(instrumented(
+			GossipSupportSubsystem::new(
+				keystore.clone(),
+				authority_discovery_service.clone(),
+				connectivity_report,
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
 		))
-		.gossip_support(GossipSupportSubsystem::new(
-			keystore.clone(),
-			authority_discovery_service.clone(),
-			Metrics::register(registry)?,
+		.dispute_coordinator(instrumented(
+			DisputeCoordinatorSubsystem::new(
+				parachains_db.clone(),
+				dispute_coordinator_config,
+				keystore.clone(),
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
 		))
-		.dispute_coordinator(DisputeCoordinatorSubsystem::new(
-			parachains_db.clone(),
-			dispute_coordinator_config,
-			keystore.clone(),
-			Metrics::register(registry)?,
+		.dispute_distribution(instrumented(
+			DisputeDistributionSubsystem::with_config(
+				keystore.clone(),
+				dispute_req_receiver,
+				authority_discovery_service.clone(),
+				dispute_distribution_config,
+				Metrics::register(registry)?,
+			),
+			&metrics,
+			&message_tap,
 		))
-		.dispute_distribution(DisputeDistributionSubsystem::new(
-			keystore.clone(),
-			dispute_req_receiver,
-			authority_discovery_service.clone(),
-			Metrics::register(registry)?,
+		.chain_selection(instrumented(
+			ChainSelectionSubsystem::new(chain_selection_config, parachains_db),
+			&metrics,
+			&message_tap,
 		))
-		.chain_selection(ChainSelectionSubsystem::new(chain_selection_config, parachains_db))
 		.leaves(Vec::from_iter(
 			leaves
 				.into_iter()
@@ -16,10 +16,12 @@
 #![cfg(feature = "full-node")]
 
 use super::{columns, other_io_error, DatabaseKind, LOG_TARGET};
+use polkadot_node_subsystem_util::database::Database;
 use std::{
 	fs, io,
 	path::{Path, PathBuf},
 	str::FromStr,
+	sync::Arc,
 };
 
 type Version = u32;
@@ -28,7 +30,23 @@ type Version = u32;
 const VERSION_FILE_NAME: &'static str = "parachain_db_version";
 
 /// Current db version.
-const CURRENT_VERSION: Version = 2;
+const CURRENT_VERSION: Version = 5;
+
+/// Upgrade steps, indexed by the version they upgrade from. The step at index `n` upgrades the
+/// database from version `n` to version `n + 1`.
+///
+/// To change the layout of a column or the encoding of its entries, bump `CURRENT_VERSION` and
+/// append a step here.
+const MIGRATIONS: &[MigrationFn] = &[
+	migrate_from_version_0_to_1,
+	migrate_from_version_1_to_2,
+	migrate_from_version_2_to_3,
+	migrate_from_version_3_to_4,
+	migrate_from_version_4_to_5,
+];
+
+/// A single upgrade step of the database.
+type MigrationFn = fn(&Path, DatabaseKind) -> Result<(), Error>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -38,6 +56,14 @@ pub enum Error {
 	CorruptedVersionFile,
 	#[error("Parachains DB has a future version (expected {current:?}, found {got:?})")]
 	FutureVersion { current: Version, got: Version },
+	#[error("No migration defined from parachains DB version {0}")]
+	MissingMigration(Version),
+	#[error("Approval-voting DB migration failed: {0}")]
+	ApprovalDb(#[from] polkadot_node_core_approval_voting::approval_db::v2::Error),
+	#[error("Chain-selection DB migration failed: {0}")]
+	ChainSelectionDb(#[from] polkadot_node_core_chain_selection::Error),
+	#[error("Dispute-coordinator DB migration failed: {0}")]
+	DisputeCoordinatorDb(#[from] polkadot_node_core_dispute_coordinator::migration_helpers::Error),
 }
 
 impl From<Error> for io::Error {
@@ -50,29 +76,54 @@ impl From<Error> for io::Error {
 }
 
 /// Try upgrading parachain's database to the current version.
+///
+/// The database is upgraded one version at a time, and the version file is updated after every
+/// step. An interrupted upgrade therefore resumes from the last completed step.
 pub(crate) fn try_upgrade_db(db_path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
-	if !is_empty {
-		match get_db_version(db_path)? {
-			// 0 -> 1 migration
-			Some(0) => migrate_from_version_0_to_1(db_path, db_kind)?,
-			// 1 -> 2 migration
-			Some(1) => migrate_from_version_1_to_2(db_path, db_kind)?,
-			// Already at current version, do nothing.
-			Some(CURRENT_VERSION) => (),
-			// This is an arbitrary future version, we don't handle it.
-			Some(v) => return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
-			// No version file. For `RocksDB` we dont need to do anything.
-			None if db_kind == DatabaseKind::RocksDB => (),
-			// No version file. `ParityDB` did not previously have a version defined.
-			// We handle this as a `0 -> 1` migration.
-			None if db_kind == DatabaseKind::ParityDB =>
-				migrate_from_version_0_to_1(db_path, db_kind)?,
-			None => unreachable!(),
-		}
+	if is_empty {
+		return update_version(db_path, CURRENT_VERSION)
+	}
+
+	let mut version = match get_db_version(db_path)? {
+		Some(v) if v > CURRENT_VERSION =>
+			return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
+		Some(v) => v,
+		// No version file. For `RocksDB` we dont need to do anything.
+		None if db_kind == DatabaseKind::RocksDB => CURRENT_VERSION,
+		// No version file. `ParityDB` did not previously have a version defined.
+		// We handle this as a `0 -> 1` migration.
+		None if db_kind == DatabaseKind::ParityDB => 0,
+		None => unreachable!(),
+	};
+
+	if version < CURRENT_VERSION {
+		gum::info!(
+			target: LOG_TARGET,
+			"Parachains db is at version {}, {} migration(s) to run",
+			version,
+			CURRENT_VERSION - version,
+		);
+	}
+
+	while version < CURRENT_VERSION {
+		let migrate = MIGRATIONS.get(version as usize).ok_or(Error::MissingMigration(version))?;
+
+		gum::info!(
+			target: LOG_TARGET,
+			"Migrating parachains db from version {} to version {} ...",
+			version,
+			version + 1,
+		);
+
+		migrate(db_path, db_kind)?;
+		version += 1;
+		update_version(db_path, version)?;
+
+		gum::info!(target: LOG_TARGET, "Migration to version {} complete!", version);
 	}
 
-	update_version(db_path)
+	update_version(db_path, CURRENT_VERSION)
 }
 
 /// Reads current database version from the file at given path.
@@ -87,11 +138,11 @@ fn get_db_version(path: &Path) -> Result<Option<Version>, Error> {
 	}
 }
 
-/// Writes current database version to the file.
+/// Writes the given database version to the file.
 /// Creates a new file if the version file does not exist yet.
-fn update_version(path: &Path) -> Result<(), Error> {
+fn update_version(path: &Path, version: Version) -> Result<(), Error> {
 	fs::create_dir_all(path)?;
-	fs::write(version_file_path(path), CURRENT_VERSION.to_string()).map_err(Into::into)
+	fs::write(version_file_path(path), version.to_string()).map_err(Into::into)
 }
 
 /// Returns the version file path.
@@ -102,29 +153,93 @@ fn version_file_path(path: &Path) -> PathBuf {
 }
 
 fn migrate_from_version_0_to_1(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
-	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 0 to version 1 ...");
-
 	match db_kind {
 		DatabaseKind::ParityDB => paritydb_migrate_from_version_0_to_1(path),
 		DatabaseKind::RocksDB => rocksdb_migrate_from_version_0_to_1(path),
 	}
-	.and_then(|result| {
-		gum::info!(target: LOG_TARGET, "Migration complete! ");
-		Ok(result)
-	})
 }
 
 fn migrate_from_version_1_to_2(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
-	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 1 to version 2 ...");
-
 	match db_kind {
 		DatabaseKind::ParityDB => paritydb_migrate_from_version_1_to_2(path),
 		DatabaseKind::RocksDB => rocksdb_migrate_from_version_1_to_2(path),
 	}
-	.and_then(|result| {
-		gum::info!(target: LOG_TARGET, "Migration complete! ");
-		Ok(result)
-	})
+}
+
+/// Migration from version 2 to version 3:
+/// * the chain-selection and dispute-coordinator entries are checked against their v1 schema,
+/// which didn't change;
+/// * the approval-voting entries are re-encoded with the v2 approval DB schema.
+///
+/// Each column is migrated on its own, one after the other. The read-only checks go first, so
+/// that a failed step leaves the DB untouched and is retried on the next start.
+fn migrate_from_version_2_to_3(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+	use polkadot_node_core_approval_voting::approval_db::v2::{
+		migration_helpers::v1_to_v2, Config as ApprovalDbConfig,
+	};
+	use polkadot_node_core_chain_selection::migration_helpers as chain_selection;
+	use polkadot_node_core_dispute_coordinator::migration_helpers as dispute_coordinator;
+
+	let approval_db_config = ApprovalDbConfig {
+		col_approval_data: super::REAL_COLUMNS.col_approval_data,
+		col_session_data: super::REAL_COLUMNS.col_session_window_data,
+	};
+
+	let db = match db_kind {
+		DatabaseKind::ParityDB => open_paritydb_version_2(path)?,
+		DatabaseKind::RocksDB => open_rocksdb_version_2(path)?,
+	};
+
+	chain_selection::v1_sanity_check(db.clone(), super::REAL_COLUMNS.col_chain_selection_data)?;
+	dispute_coordinator::v1_sanity_check(
+		db.clone(),
+		super::REAL_COLUMNS.col_dispute_coordinator_data,
+	)?;
+	v1_to_v2(db, approval_db_config)?;
+
+	Ok(())
+}
+
+/// Migration from version 3 to version 4:
+/// * a new column for collator reputations is added.
+fn migrate_from_version_3_to_4(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+	match db_kind {
+		DatabaseKind::ParityDB => paritydb_migrate_from_version_3_to_4(path),
+		DatabaseKind::RocksDB => rocksdb_migrate_from_version_3_to_4(path),
+	}
+}
+
+/// Migration from version 4 to version 5:
+/// * a new column for peer reputations is added.
+fn migrate_from_version_4_to_5(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+	match db_kind {
+		DatabaseKind::ParityDB => paritydb_migrate_from_version_4_to_5(path),
+		DatabaseKind::RocksDB => rocksdb_migrate_from_version_4_to_5(path),
+	}
+}
+
+/// Open a version 2 `RocksDB` database, for migrating its contents.
+fn open_rocksdb_version_2(path: &Path) -> Result<Arc<dyn Database>, Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+	use polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter;
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path)?;
+
+	Ok(Arc::new(DbAdapter::new(db, columns::v2::ORDERED_COL)))
+}
+
+/// Open a version 2 `ParityDB` database, for migrating its contents.
+fn open_paritydb_version_2(path: &Path) -> Result<Arc<dyn Database>, Error> {
+	use polkadot_node_subsystem_util::database::paritydb_impl::DbAdapter;
+
+	let db = parity_db::Db::open(&paritydb_version_2_config(path))
+		.map_err(|e| other_io_error(format!("Error opening database {:?}", e)))?;
+
+	Ok(Arc::new(DbAdapter::new(db, columns::v2::ORDERED_COL)))
 }
 
 /// Migration from version 0 to version 1:
@@ -160,6 +275,38 @@ fn rocksdb_migrate_from_version_1_to_2(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 3 to version 4:
+/// * the number of columns has changed from 6 to 7;
+fn rocksdb_migrate_from_version_3_to_4(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+	let mut db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
+
+/// Migration from version 4 to version 5:
+/// * the number of columns has changed from 7 to 8;
+fn rocksdb_migrate_from_version_4_to_5(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v3::NUM_COLUMNS);
+	let mut db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
+
 // This currently clears columns which had their configs altered between versions.
 // The columns to be changed are constrained by the `allowed_columns` vector.
 fn paritydb_fix_columns(
@@ -239,6 +386,28 @@ pub(crate) fn paritydb_version_2_config(path: &Path) -> parity_db::Options {
 	options
 }
 
+/// Database configuration for version 3.
+pub(crate) fn paritydb_version_3_config(path: &Path) -> parity_db::Options {
+	let mut options =
+		parity_db::Options::with_columns(&path, super::columns::v3::NUM_COLUMNS as u8);
+	for i in columns::v3::ORDERED_COL {
+		options.columns[*i as usize].btree_index = true;
+	}
+
+	options
+}
+
+/// Database configuration for version 4.
+pub(crate) fn paritydb_version_4_config(path: &Path) -> parity_db::Options {
+	let mut options =
+		parity_db::Options::with_columns(&path, super::columns::v4::NUM_COLUMNS as u8);
+	for i in columns::v4::ORDERED_COL {
+		options.columns[*i as usize].btree_index = true;
+	}
+
+	options
+}
+
 /// Database configuration for version 0. This is useful just for testing.
 #[cfg(test)]
 pub(crate) fn paritydb_version_0_config(path: &Path) -> parity_db::Options {
@@ -278,6 +447,30 @@ fn paritydb_migrate_from_version_1_to_2(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 3 to version 4:
+/// - add a new column for collator reputations
+fn paritydb_migrate_from_version_3_to_4(path: &Path) -> Result<(), Error> {
+	let mut options = paritydb_version_2_config(path);
+
+	// Adds the collator reputation column.
+	parity_db::Db::add_column(&mut options, Default::default())
+		.map_err(|e| other_io_error(format!("Error adding column {:?}", e)))?;
+
+	Ok(())
+}
+
+/// Migration from version 4 to version 5:
+/// - add a new column for peer reputations
+fn paritydb_migrate_from_version_4_to_5(path: &Path) -> Result<(), Error> {
+	let mut options = paritydb_version_3_config(path);
+
+	// Adds the peer reputation column.
+	parity_db::Db::add_column(&mut options, Default::default())
+		.map_err(|e| other_io_error(format!("Error adding column {:?}", e)))?;
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{columns::v2::*, *};
@@ -299,8 +492,11 @@ mod tests {
 		}
 
 		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(&path).unwrap(), Some(CURRENT_VERSION));
 
-		let db = Db::open(&paritydb_version_1_config(&path)).unwrap();
+		// The later migrations are applied on top, adding the session info, collator
+		// reputation and peer reputation columns.
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
 		assert_eq!(db.get(COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(), None);
 		assert_eq!(
 			db.get(COL_AVAILABILITY_META as u8, b"5678").unwrap(),
@@ -334,9 +530,9 @@ mod tests {
 
 		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
 
-		let db = Db::open(&paritydb_version_2_config(&path)).unwrap();
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
 
-		assert_eq!(db.num_columns(), columns::v2::NUM_COLUMNS as u8);
+		assert_eq!(db.num_columns(), columns::v4::NUM_COLUMNS as u8);
 
 		assert_eq!(
 			db.get(COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(),
@@ -388,10 +584,10 @@ mod tests {
 
 		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
 
-		let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
 		let db = Database::open(&db_cfg, db_path).unwrap();
 
-		assert_eq!(db.num_columns(), super::columns::v2::NUM_COLUMNS);
+		assert_eq!(db.num_columns(), super::columns::v4::NUM_COLUMNS);
 
 		let db = DbAdapter::new(db, columns::v2::ORDERED_COL);
 
@@ -416,4 +612,365 @@ mod tests {
 			Some("0xdeadb00b".as_bytes().to_vec())
 		);
 	}
+
+	fn approval_db_config() -> polkadot_node_core_approval_voting::approval_db::v2::Config {
+		polkadot_node_core_approval_voting::approval_db::v2::Config {
+			col_approval_data: COL_APPROVAL_DATA,
+			col_session_data: COL_SESSION_WINDOW_DATA,
+		}
+	}
+
+	#[test]
+	fn test_paritydb_migrate_2_to_3() {
+		use parity_db::Db;
+		use polkadot_node_core_approval_voting::approval_db::v2::migration_helpers;
+		use polkadot_node_subsystem_util::database::paritydb_impl::DbAdapter;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(path), "2").expect("Failed to write DB version");
+
+		{
+			let db = Db::open_or_create(&paritydb_version_2_config(&path)).unwrap();
+
+			db.commit(vec![(
+				COL_AVAILABILITY_META as u8,
+				b"1234".to_vec(),
+				Some(b"somevalue".to_vec()),
+			)])
+			.unwrap();
+		}
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(&path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
+		let db: Arc<dyn Database> = Arc::new(DbAdapter::new(db, columns::v4::ORDERED_COL));
+
+		migration_helpers::v2_sanity_check(db.clone(), approval_db_config()).unwrap();
+		assert_eq!(
+			db.get(COL_AVAILABILITY_META, b"1234").unwrap(),
+			Some("somevalue".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_2_to_3() {
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+		use polkadot_node_core_approval_voting::approval_db::v2::migration_helpers;
+		use polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(db_dir.path()), "2").expect("Failed to write DB version");
+
+		Database::open(&db_cfg, db_path).unwrap();
+
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(db_dir.path()).unwrap(), Some(CURRENT_VERSION));
+
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+		let db: Arc<dyn super::Database> = Arc::new(DbAdapter::new(db, columns::v4::ORDERED_COL));
+
+		migration_helpers::v2_sanity_check(db, approval_db_config()).unwrap();
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_2_to_3_fails_on_undecodable_entries() {
+		use kvdb::{DBKey, DBOp};
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+		use polkadot_node_subsystem_util::database::{
+			kvdb_impl::DbAdapter, DBTransaction, KeyValueDB,
+		};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(db_dir.path()), "2").expect("Failed to write DB version");
+		{
+			// A chain-selection block entry which doesn't decode.
+			let mut key = b"CS_block_entry".to_vec();
+			key.extend_from_slice(&[1u8; 32]);
+
+			let db = DbAdapter::new(db, columns::v2::ORDERED_COL);
+			db.write(DBTransaction {
+				ops: vec![DBOp::Insert {
+					col: COL_CHAIN_SELECTION_DATA,
+					key: DBKey::from_slice(&key),
+					value: b"0xdeadb00b".to_vec(),
+				}],
+			})
+			.unwrap();
+		}
+
+		assert_matches::assert_matches!(
+			try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB),
+			Err(Error::ChainSelectionDb(_))
+		);
+
+		// The failed step is retried on the next start.
+		assert_eq!(get_db_version(db_dir.path()).unwrap(), Some(2));
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_0_to_current() {
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		{
+			let db_cfg = DatabaseConfig::with_columns(super::columns::v0::NUM_COLUMNS);
+			let db = Database::open(&db_cfg, db_path).unwrap();
+			assert_eq!(db.num_columns(), super::columns::v0::NUM_COLUMNS);
+		}
+
+		fs::write(version_file_path(db_dir.path()), "0").expect("Failed to write DB version");
+
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(db_dir.path()).unwrap(), Some(CURRENT_VERSION));
+
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+		assert_eq!(db.num_columns(), super::columns::v4::NUM_COLUMNS);
+	}
+
+	#[test]
+	fn test_paritydb_migrate_3_to_4() {
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(path), "3").expect("Failed to write DB version");
+
+		{
+			let db = Db::open_or_create(&paritydb_version_2_config(&path)).unwrap();
+
+			db.commit(vec![(
+				COL_SESSION_WINDOW_DATA as u8,
+				b"1234".to_vec(),
+				Some(b"somevalue".to_vec()),
+			)])
+			.unwrap();
+
+			assert_eq!(db.num_columns(), columns::v2::NUM_COLUMNS as u8);
+		}
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(&path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
+
+		assert_eq!(db.num_columns(), columns::v4::NUM_COLUMNS as u8);
+		assert_eq!(
+			db.get(COL_SESSION_WINDOW_DATA as u8, b"1234").unwrap(),
+			Some("somevalue".as_bytes().to_vec())
+		);
+
+		// Test we can write the new column.
+		db.commit(vec![(
+			columns::v4::COL_COLLATOR_REPUTATION_DATA as u8,
+			b"1337".to_vec(),
+			Some(b"0xdeadb00b".to_vec()),
+		)])
+		.unwrap();
+
+		assert_eq!(
+			db.get(columns::v4::COL_COLLATOR_REPUTATION_DATA as u8, b"1337").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_3_to_4() {
+		use kvdb::{DBKey, DBOp};
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+		use polkadot_node_subsystem_util::database::{
+			kvdb_impl::DbAdapter, DBTransaction, KeyValueDB,
+		};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(db_dir.path()), "3").expect("Failed to write DB version");
+		{
+			let db = DbAdapter::new(db, columns::v2::ORDERED_COL);
+			db.write(DBTransaction {
+				ops: vec![DBOp::Insert {
+					col: COL_SESSION_WINDOW_DATA,
+					key: DBKey::from_slice(b"1234"),
+					value: b"0xdeadb00b".to_vec(),
+				}],
+			})
+			.unwrap();
+		}
+
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(db_dir.path()).unwrap(), Some(CURRENT_VERSION));
+
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		assert_eq!(db.num_columns(), super::columns::v4::NUM_COLUMNS);
+
+		let db = DbAdapter::new(db, columns::v4::ORDERED_COL);
+
+		assert_eq!(
+			db.get(COL_SESSION_WINDOW_DATA, b"1234").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+
+		// Test we can write the new column.
+		db.write(DBTransaction {
+			ops: vec![DBOp::Insert {
+				col: columns::v4::COL_COLLATOR_REPUTATION_DATA,
+				key: DBKey::from_slice(b"1337"),
+				value: b"0xdeadb00b".to_vec(),
+			}],
+		})
+		.unwrap();
+
+		assert_eq!(
+			db.get(columns::v4::COL_COLLATOR_REPUTATION_DATA, b"1337").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn test_paritydb_migrate_4_to_5() {
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(path), "4").expect("Failed to write DB version");
+
+		{
+			let db = Db::open_or_create(&paritydb_version_3_config(&path)).unwrap();
+
+			db.commit(vec![(
+				columns::v3::COL_COLLATOR_REPUTATION_DATA as u8,
+				b"1234".to_vec(),
+				Some(b"somevalue".to_vec()),
+			)])
+			.unwrap();
+
+			assert_eq!(db.num_columns(), columns::v3::NUM_COLUMNS as u8);
+		}
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(&path).unwrap(), Some(5));
+
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
+
+		assert_eq!(db.num_columns(), columns::v4::NUM_COLUMNS as u8);
+		assert_eq!(
+			db.get(columns::v4::COL_COLLATOR_REPUTATION_DATA as u8, b"1234").unwrap(),
+			Some("somevalue".as_bytes().to_vec())
+		);
+
+		// Test we can write the new column.
+		db.commit(vec![(
+			columns::v4::COL_PEER_REPUTATION_DATA as u8,
+			b"1337".to_vec(),
+			Some(b"0xdeadb00b".to_vec()),
+		)])
+		.unwrap();
+
+		assert_eq!(
+			db.get(columns::v4::COL_PEER_REPUTATION_DATA as u8, b"1337").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_4_to_5() {
+		use kvdb::{DBKey, DBOp};
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+		use polkadot_node_subsystem_util::database::{
+			kvdb_impl::DbAdapter, DBTransaction, KeyValueDB,
+		};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v3::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(db_dir.path()), "4").expect("Failed to write DB version");
+		{
+			let db = DbAdapter::new(db, columns::v3::ORDERED_COL);
+			db.write(DBTransaction {
+				ops: vec![DBOp::Insert {
+					col: columns::v3::COL_COLLATOR_REPUTATION_DATA,
+					key: DBKey::from_slice(b"1234"),
+					value: b"0xdeadb00b".to_vec(),
+				}],
+			})
+			.unwrap();
+		}
+
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(db_dir.path()).unwrap(), Some(5));
+
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		assert_eq!(db.num_columns(), super::columns::v4::NUM_COLUMNS);
+
+		let db = DbAdapter::new(db, columns::v4::ORDERED_COL);
+
+		assert_eq!(
+			db.get(columns::v4::COL_COLLATOR_REPUTATION_DATA, b"1234").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+
+		// Test we can write the new column.
+		db.write(DBTransaction {
+			ops: vec![DBOp::Insert {
+				col: columns::v4::COL_PEER_REPUTATION_DATA,
+				key: DBKey::from_slice(b"1337"),
+				value: b"0xdeadb00b".to_vec(),
+			}],
+		})
+		.unwrap();
+
+		assert_eq!(
+			db.get(columns::v4::COL_PEER_REPUTATION_DATA, b"1337").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn future_version_is_rejected() {
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		fs::write(version_file_path(path), (CURRENT_VERSION + 1).to_string())
+			.expect("Failed to write DB version");
+
+		assert_matches::assert_matches!(
+			try_upgrade_db(&path, DatabaseKind::RocksDB),
+			Err(Error::FutureVersion { current: CURRENT_VERSION, .. })
+		);
+	}
+
+	#[test]
+	fn migrations_cover_all_versions() {
+		assert_eq!(MIGRATIONS.len(), CURRENT_VERSION as usize);
+	}
 }
@@ -40,6 +40,9 @@ use std::{
 /// Generally useful mock data providers for unit tests.
 pub mod mock;
 
+/// Replaying of recorded subsystem traffic.
+pub mod replay;
+
 enum SinkState<T> {
 	Empty { read_waker: Option<Waker> },
 	Item { item: T, ready_waker: Option<Waker>, flush_waker: Option<Waker> },
@@ -0,0 +1,1095 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replaying of recordings made with the overseer message tap against a single subsystem.
+//!
+//! See [`polkadot_node_subsystem::overseer::tap`] for how recordings are made.
+
+use polkadot_node_network_protocol::{
+	grid_topology::{SessionGridTopology, TopologyPeerInfo},
+	peer_set::{PeerSet, ValidationVersion},
+	request_response::{IsRequest, OutgoingRequest, Recipient, Requests},
+	ObservedRole, OurView, PeerId, UnifiedReputationChange, Versioned, View,
+};
+use polkadot_node_primitives::{DisputeMessage, SignedDisputeStatement, UncheckedDisputeMessage};
+use polkadot_node_subsystem::{
+	jaeger,
+	messages::{
+		network_bridge_event::NewGossipTopology, AllMessages, ApprovalDistributionMessage,
+		ApprovalVotingMessage, AvailabilityDistributionMessage, AvailabilityRecoveryMessage,
+		AvailabilityStoreMessage, BitfieldDistributionMessage, BitfieldSigningMessage,
+		BlockDescription, CandidateBackingMessage, CandidateValidationMessage, ChainApiMessage,
+		ChainSelectionMessage, CollationGenerationMessage, CollatorProtocolMessage,
+		DisputeCoordinatorMessage, DisputeDistributionMessage, GossipSupportMessage,
+		IfDisconnected, NetworkBridgeEvent, NetworkBridgeRxMessage, NetworkBridgeTxMessage,
+		ProvisionableData, ProvisionerMessage, PvfCheckerMessage, RuntimeApiMessage,
+		RuntimeApiRequest, StatementDistributionMessage,
+	},
+	overseer::tap::{RecordedMessage, TapEvent, TapRecord},
+	FromOrchestra,
+};
+use polkadot_primitives::{
+	AuthorityDiscoveryId, CandidateHash, CandidateIndex, DisputeStatement, EncodeAs, Hash,
+	SessionIndex, SessionInfo, Signed, SigningContext, UncheckedSigned, ValidatorId,
+	ValidatorIndex, ValidatorSignature,
+};
+
+use futures::{channel::oneshot, pin_mut, prelude::*, select};
+use parity_scale_codec::{Compact, Decode, Encode};
+use sc_network::Multiaddr;
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use crate::TestSubsystemContextHandle;
+
+/// Replay the deliveries recorded for the given subsystem, in order, against a test context.
+///
+/// Signals are reconstructed from the recording. Messages are recorded by the name of their
+/// variant and the SCALE encoding of their fields without response channels, which
+/// `decode_message` turns back into a message, e.g. with
+/// [`ReplayableMessage::decode_recorded`]. Messages it returns `None` for are skipped.
+///
+/// Everything the subsystem sends while the recording is replayed is passed to `on_outgoing`,
+/// which may answer requests the subsystem waits on.
+///
+/// Returns the number of deliveries which were replayed.
+pub async fn replay_recording<M, D, O>(
+	handle: &mut TestSubsystemContextHandle<M>,
+	records: &[TapRecord],
+	subsystem: &str,
+	mut decode_message: D,
+	mut on_outgoing: O,
+) -> usize
+where
+	D: FnMut(&RecordedMessage) -> Option<M>,
+	O: FnMut(AllMessages),
+{
+	let mut replayed = 0;
+
+	for record in records.iter().filter(|record| record.subsystem == subsystem) {
+		let delivery = match &record.event {
+			TapEvent::Signal(signal) => FromOrchestra::Signal(signal.to_signal()),
+			TapEvent::Message(msg) => match decode_message(msg) {
+				Some(msg) => FromOrchestra::Communication { msg },
+				None => continue,
+			},
+		};
+
+		// The subsystem may wait for responses before reading the next delivery.
+		let send = handle.tx.send(delivery).fuse();
+		pin_mut!(send);
+
+		loop {
+			select! {
+				res = send => {
+					res.expect("Test subsystem no longer live");
+					break
+				},
+				msg = handle.rx.next() => match msg {
+					Some(msg) => on_outgoing(msg),
+					None => panic!("Test subsystem no longer live"),
+				},
+			}
+		}
+
+		replayed += 1;
+	}
+
+	replayed
+}
+
+/// The sessions a recording was made in.
+///
+/// Signed payloads of recorded messages are only restored if their signature checks out against
+/// the validators of the session they were signed in.
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySessions {
+	/// The session of every relay parent payloads were signed at.
+	pub session_indices: HashMap<Hash, SessionIndex>,
+	/// The info of every session.
+	pub session_info: HashMap<SessionIndex, SessionInfo>,
+}
+
+impl ReplaySessions {
+	fn check_signed<Payload: EncodeAs<RealPayload>, RealPayload: Encode>(
+		&self,
+		relay_parent: Hash,
+		unchecked: UncheckedSigned<Payload, RealPayload>,
+	) -> Option<Signed<Payload, RealPayload>> {
+		let session_index = *self.session_indices.get(&relay_parent)?;
+		let key = self
+			.session_info
+			.get(&session_index)?
+			.validators
+			.get(unchecked.unchecked_validator_index())?;
+
+		unchecked
+			.try_into_checked(&SigningContext { session_index, parent_hash: relay_parent }, key)
+			.ok()
+	}
+}
+
+/// A message which can be restored from the form it was recorded in, see
+/// [`RecordableMessage`](polkadot_node_subsystem::overseer::tap::RecordableMessage).
+pub trait ReplayableMessage: Sized {
+	/// Restore a recorded message. Response channels are filled in with fresh ones, whose
+	/// receivers are dropped.
+	///
+	/// Returns `None` if the message was recorded by name only, can't be decoded or carries a
+	/// signed payload which doesn't check out against `sessions`.
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self>;
+}
+
+impl ReplayableMessage for PvfCheckerMessage {
+	fn decode_recorded(_: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		None
+	}
+}
+
+impl ReplayableMessage for BitfieldSigningMessage {
+	fn decode_recorded(_: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		None
+	}
+}
+
+impl ReplayableMessage for ChainApiMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"BlockNumber" => ChainApiMessage::BlockNumber(decode(input)?, channel()),
+				"BlockHeader" => ChainApiMessage::BlockHeader(decode(input)?, channel()),
+				"BlockWeight" => ChainApiMessage::BlockWeight(decode(input)?, channel()),
+				"FinalizedBlockHash" =>
+					ChainApiMessage::FinalizedBlockHash(decode(input)?, channel()),
+				"FinalizedBlockNumber" => ChainApiMessage::FinalizedBlockNumber(channel()),
+				"Ancestors" => ChainApiMessage::Ancestors {
+					hash: decode(input)?,
+					k: decode::<u64>(input)? as usize,
+					response_channel: channel(),
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for ChainSelectionMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"Approved" => ChainSelectionMessage::Approved(decode(input)?),
+				"Leaves" => ChainSelectionMessage::Leaves(channel()),
+				"BestLeafContaining" =>
+					ChainSelectionMessage::BestLeafContaining(decode(input)?, channel()),
+				"RevertBlocks" => ChainSelectionMessage::RevertBlocks(decode(input)?),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for CollatorProtocolMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"CollateOn" => CollatorProtocolMessage::CollateOn(decode(input)?),
+				"DistributeCollation" => CollatorProtocolMessage::DistributeCollation(
+					decode(input)?,
+					decode(input)?,
+					decode(input)?,
+					None,
+				),
+				"ReportCollator" => CollatorProtocolMessage::ReportCollator(decode(input)?),
+				"NetworkBridgeUpdate" => CollatorProtocolMessage::NetworkBridgeUpdate(
+					decode_network_bridge_event(input, decode_versioned)?,
+				),
+				"Invalid" => CollatorProtocolMessage::Invalid(decode(input)?, decode(input)?),
+				"Seconded" => {
+					let relay_parent = decode(input)?;
+					let statement = sessions.check_signed(relay_parent, decode(input)?)?;
+					CollatorProtocolMessage::Seconded(relay_parent, statement)
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for BitfieldDistributionMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"DistributeBitfield" => {
+					let relay_parent = decode(input)?;
+					let bitfield = sessions.check_signed(relay_parent, decode(input)?)?;
+					BitfieldDistributionMessage::DistributeBitfield(relay_parent, bitfield)
+				},
+				"NetworkBridgeUpdate" => BitfieldDistributionMessage::NetworkBridgeUpdate(
+					decode_network_bridge_event(input, decode_versioned)?,
+				),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for StatementDistributionMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"Share" => {
+					let relay_parent = decode(input)?;
+					let statement = sessions.check_signed(relay_parent, decode(input)?)?;
+					StatementDistributionMessage::Share(relay_parent, statement)
+				},
+				"NetworkBridgeUpdate" => StatementDistributionMessage::NetworkBridgeUpdate(
+					decode_network_bridge_event(input, decode_versioned)?,
+				),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for ApprovalDistributionMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"NewBlocks" => ApprovalDistributionMessage::NewBlocks(decode(input)?),
+				"DistributeAssignment" => ApprovalDistributionMessage::DistributeAssignment(
+					decode(input)?,
+					decode(input)?,
+				),
+				"DistributeApproval" =>
+					ApprovalDistributionMessage::DistributeApproval(decode(input)?),
+				"DistributeCoalescedApproval" =>
+					ApprovalDistributionMessage::DistributeCoalescedApproval(decode(input)?),
+				"NetworkBridgeUpdate" => ApprovalDistributionMessage::NetworkBridgeUpdate(
+					decode_network_bridge_event(input, decode_versioned)?,
+				),
+				"GetApprovalSignatures" => ApprovalDistributionMessage::GetApprovalSignatures(
+					decode::<Vec<(Hash, CandidateIndex)>>(input)?.into_iter().collect(),
+					channel(),
+				),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for ApprovalVotingMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"CheckAndImportAssignment" => ApprovalVotingMessage::CheckAndImportAssignment(
+					decode(input)?,
+					decode(input)?,
+					channel(),
+				),
+				"CheckAndImportApproval" =>
+					ApprovalVotingMessage::CheckAndImportApproval(decode(input)?, channel()),
+				"CheckAndImportApprovals" =>
+					ApprovalVotingMessage::CheckAndImportApprovals(decode(input)?, channel()),
+				"CheckAndImportCoalescedApproval" =>
+					ApprovalVotingMessage::CheckAndImportCoalescedApproval(
+						decode(input)?,
+						channel(),
+					),
+				"ApprovedAncestor" => ApprovalVotingMessage::ApprovedAncestor(
+					decode(input)?,
+					decode(input)?,
+					channel(),
+				),
+				"GetApprovalSignaturesForCandidate" =>
+					ApprovalVotingMessage::GetApprovalSignaturesForCandidate(
+						decode(input)?,
+						channel(),
+					),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for AvailabilityDistributionMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"FetchPoV" => AvailabilityDistributionMessage::FetchPoV {
+					relay_parent: decode(input)?,
+					from_validator: decode(input)?,
+					para_id: decode(input)?,
+					candidate_hash: decode(input)?,
+					pov_hash: decode(input)?,
+					tx: channel(),
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for AvailabilityRecoveryMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"RecoverAvailableData" => AvailabilityRecoveryMessage::RecoverAvailableData(
+					decode(input)?,
+					decode(input)?,
+					decode(input)?,
+					channel(),
+				),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for AvailabilityStoreMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"QueryAvailableData" =>
+					AvailabilityStoreMessage::QueryAvailableData(decode(input)?, channel()),
+				"QueryDataAvailability" =>
+					AvailabilityStoreMessage::QueryDataAvailability(decode(input)?, channel()),
+				"QueryChunk" =>
+					AvailabilityStoreMessage::QueryChunk(decode(input)?, decode(input)?, channel()),
+				"QueryAllChunks" =>
+					AvailabilityStoreMessage::QueryAllChunks(decode(input)?, channel()),
+				"QueryChunkAvailability" => AvailabilityStoreMessage::QueryChunkAvailability(
+					decode(input)?,
+					decode(input)?,
+					channel(),
+				),
+				"StoreChunk" => AvailabilityStoreMessage::StoreChunk {
+					candidate_hash: decode(input)?,
+					chunk: decode(input)?,
+					tx: channel(),
+				},
+				"StoreAvailableData" => AvailabilityStoreMessage::StoreAvailableData {
+					candidate_hash: decode(input)?,
+					n_validators: decode(input)?,
+					available_data: decode(input)?,
+					tx: channel(),
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for CandidateBackingMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"GetBackedCandidates" => CandidateBackingMessage::GetBackedCandidates(
+					decode(input)?,
+					decode(input)?,
+					channel(),
+				),
+				"Second" =>
+					CandidateBackingMessage::Second(decode(input)?, decode(input)?, decode(input)?),
+				"Statement" => {
+					let relay_parent = decode(input)?;
+					let statement = sessions.check_signed(relay_parent, decode(input)?)?;
+					CandidateBackingMessage::Statement(relay_parent, statement)
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for CandidateValidationMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"ValidateFromChainState" => CandidateValidationMessage::ValidateFromChainState(
+					decode(input)?,
+					Arc::new(decode(input)?),
+					decode(input)?,
+					channel(),
+				),
+				"ValidateFromExhaustive" => CandidateValidationMessage::ValidateFromExhaustive(
+					decode(input)?,
+					decode(input)?,
+					decode(input)?,
+					Arc::new(decode(input)?),
+					decode(input)?,
+					channel(),
+				),
+				"PreCheck" =>
+					CandidateValidationMessage::PreCheck(decode(input)?, decode(input)?, channel()),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for CollationGenerationMessage {
+	fn decode_recorded(_: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		// The config is not recorded.
+		None
+	}
+}
+
+impl ReplayableMessage for DisputeCoordinatorMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"ImportStatements" => DisputeCoordinatorMessage::ImportStatements {
+					candidate_receipt: decode(input)?,
+					session: decode(input)?,
+					statements: decode::<
+						Vec<(
+							(
+								DisputeStatement,
+								CandidateHash,
+								SessionIndex,
+								ValidatorId,
+								ValidatorSignature,
+							),
+							ValidatorIndex,
+						)>,
+					>(input)?
+					.into_iter()
+					.map(|((statement, candidate_hash, session, public, signature), index)| {
+						SignedDisputeStatement::new_checked(
+							statement,
+							candidate_hash,
+							session,
+							public,
+							signature,
+						)
+						.ok()
+						.map(|statement| (statement, index))
+					})
+					.collect::<Option<_>>()?,
+					pending_confirmation: None,
+				},
+				"RecentDisputes" => DisputeCoordinatorMessage::RecentDisputes(channel()),
+				"ActiveDisputes" => DisputeCoordinatorMessage::ActiveDisputes(channel()),
+				"QueryCandidateVotes" =>
+					DisputeCoordinatorMessage::QueryCandidateVotes(decode(input)?, channel()),
+				"IssueLocalStatement" => DisputeCoordinatorMessage::IssueLocalStatement(
+					decode(input)?,
+					decode(input)?,
+					decode(input)?,
+					decode(input)?,
+				),
+				"DetermineUndisputedChain" => DisputeCoordinatorMessage::DetermineUndisputedChain {
+					base: decode(input)?,
+					block_descriptions: decode::<Vec<(Hash, SessionIndex, Vec<CandidateHash>)>>(
+						input,
+					)?
+					.into_iter()
+					.map(|(block_hash, session, candidates)| BlockDescription {
+						block_hash,
+						session,
+						candidates,
+					})
+					.collect(),
+					tx: channel(),
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for DisputeDistributionMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"SendDispute" => {
+					let message: UncheckedDisputeMessage = decode(input)?;
+					let session_info = sessions.session_info.get(&message.session_index)?;
+					let (receipt, (valid, valid_index), (invalid, invalid_index)) =
+						message.try_into_signed_votes(session_info).ok()?;
+
+					DisputeDistributionMessage::SendDispute(
+						DisputeMessage::from_signed_statements(
+							valid,
+							valid_index,
+							invalid,
+							invalid_index,
+							receipt,
+							session_info,
+						)
+						.ok()?,
+					)
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for GossipSupportMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				// There are no gossip support network messages.
+				"NetworkBridgeUpdate" => GossipSupportMessage::NetworkBridgeUpdate(
+					decode_network_bridge_event(input, |_| None)?,
+				),
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for NetworkBridgeRxMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"NewGossipTopology" => NetworkBridgeRxMessage::NewGossipTopology {
+					session: decode(input)?,
+					local_index: decode(input)?,
+					canonical_shuffling: decode(input)?,
+					shuffled_indices: decode_indices(input)?,
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for NetworkBridgeTxMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"ReportPeer" => NetworkBridgeTxMessage::ReportPeer(
+					decode_peer(input)?,
+					decode_reputation_change(input)?,
+				),
+				"DisconnectPeer" => NetworkBridgeTxMessage::DisconnectPeer(
+					decode_peer(input)?,
+					decode_peer_set(input)?,
+				),
+				"SendValidationMessage" => NetworkBridgeTxMessage::SendValidationMessage(
+					decode_peers(input)?,
+					decode_versioned(input)?,
+				),
+				"SendCollationMessage" => NetworkBridgeTxMessage::SendCollationMessage(
+					decode_peers(input)?,
+					decode_versioned(input)?,
+				),
+				"SendValidationMessages" =>
+					NetworkBridgeTxMessage::SendValidationMessages(decode_seq(input, |input| {
+						Some((decode_peers(input)?, decode_versioned(input)?))
+					})?),
+				"SendCollationMessages" =>
+					NetworkBridgeTxMessage::SendCollationMessages(decode_seq(input, |input| {
+						Some((decode_peers(input)?, decode_versioned(input)?))
+					})?),
+				"SendRequests" => NetworkBridgeTxMessage::SendRequests(
+					decode_seq(input, decode_request)?,
+					match decode::<String>(input)?.as_str() {
+						"TryConnect" => IfDisconnected::TryConnect,
+						"ImmediateError" => IfDisconnected::ImmediateError,
+						_ => return None,
+					},
+				),
+				"ConnectToValidators" => NetworkBridgeTxMessage::ConnectToValidators {
+					validator_ids: decode(input)?,
+					peer_set: decode_peer_set(input)?,
+					failed: channel(),
+				},
+				"ConnectToResolvedValidators" =>
+					NetworkBridgeTxMessage::ConnectToResolvedValidators {
+						validator_addrs: decode::<Vec<Vec<Vec<u8>>>>(input)?
+							.into_iter()
+							.map(|addrs| {
+								addrs
+									.into_iter()
+									.map(|addr| Multiaddr::try_from(addr).ok())
+									.collect::<Option<HashSet<_>>>()
+							})
+							.collect::<Option<_>>()?,
+						peer_set: decode_peer_set(input)?,
+					},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for ProvisionerMessage {
+	fn decode_recorded(msg: &RecordedMessage, sessions: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"RequestInherentData" =>
+					ProvisionerMessage::RequestInherentData(decode(input)?, channel()),
+				"ProvisionableData" => {
+					let relay_parent = decode(input)?;
+					let data = match decode::<String>(input)?.as_str() {
+						"Bitfield" => {
+							let relay_parent = decode(input)?;
+							let bitfield = sessions.check_signed(relay_parent, decode(input)?)?;
+							ProvisionableData::Bitfield(relay_parent, bitfield)
+						},
+						"BackedCandidate" => ProvisionableData::BackedCandidate(decode(input)?),
+						"MisbehaviorReport" => ProvisionableData::MisbehaviorReport(
+							decode(input)?,
+							decode(input)?,
+							decode(input)?,
+						),
+						"Dispute" => ProvisionableData::Dispute(decode(input)?, decode(input)?),
+						_ => return None,
+					};
+
+					ProvisionerMessage::ProvisionableData(relay_parent, data)
+				},
+				_ => return None,
+			})
+		})
+	}
+}
+
+impl ReplayableMessage for RuntimeApiMessage {
+	fn decode_recorded(msg: &RecordedMessage, _: &ReplaySessions) -> Option<Self> {
+		decode_with(msg, |message_type, input| {
+			Some(match message_type {
+				"Request" =>
+					RuntimeApiMessage::Request(decode(input)?, decode_runtime_api_request(input)?),
+				_ => return None,
+			})
+		})
+	}
+}
+
+fn decode_runtime_api_request(input: &mut &[u8]) -> Option<RuntimeApiRequest> {
+	Some(match decode::<String>(input)?.as_str() {
+		"Version" => RuntimeApiRequest::Version(channel()),
+		"Authorities" => RuntimeApiRequest::Authorities(channel()),
+		"Validators" => RuntimeApiRequest::Validators(channel()),
+		"ValidatorGroups" => RuntimeApiRequest::ValidatorGroups(channel()),
+		"AvailabilityCores" => RuntimeApiRequest::AvailabilityCores(channel()),
+		"PersistedValidationData" =>
+			RuntimeApiRequest::PersistedValidationData(decode(input)?, decode(input)?, channel()),
+		"AssumedValidationData" =>
+			RuntimeApiRequest::AssumedValidationData(decode(input)?, decode(input)?, channel()),
+		"CheckValidationOutputs" =>
+			RuntimeApiRequest::CheckValidationOutputs(decode(input)?, decode(input)?, channel()),
+		"SessionIndexForChild" => RuntimeApiRequest::SessionIndexForChild(channel()),
+		"ValidationCode" =>
+			RuntimeApiRequest::ValidationCode(decode(input)?, decode(input)?, channel()),
+		"ValidationCodeByHash" =>
+			RuntimeApiRequest::ValidationCodeByHash(decode(input)?, channel()),
+		"CandidatePendingAvailability" =>
+			RuntimeApiRequest::CandidatePendingAvailability(decode(input)?, channel()),
+		"CandidateEvents" => RuntimeApiRequest::CandidateEvents(channel()),
+		"SessionInfo" => RuntimeApiRequest::SessionInfo(decode(input)?, channel()),
+		"DmqContents" => RuntimeApiRequest::DmqContents(decode(input)?, channel()),
+		"InboundHrmpChannelsContents" =>
+			RuntimeApiRequest::InboundHrmpChannelsContents(decode(input)?, channel()),
+		"CurrentBabeEpoch" => RuntimeApiRequest::CurrentBabeEpoch(channel()),
+		"FetchOnChainVotes" => RuntimeApiRequest::FetchOnChainVotes(channel()),
+		"SubmitPvfCheckStatement" =>
+			RuntimeApiRequest::SubmitPvfCheckStatement(decode(input)?, decode(input)?, channel()),
+		"PvfsRequirePrecheck" => RuntimeApiRequest::PvfsRequirePrecheck(channel()),
+		"ValidationCodeHash" =>
+			RuntimeApiRequest::ValidationCodeHash(decode(input)?, decode(input)?, channel()),
+		"Disputes" => RuntimeApiRequest::Disputes(channel()),
+		"ApprovalVotingParams" => RuntimeApiRequest::ApprovalVotingParams(channel()),
+		"SubmitBackingMisbehaviorReport" => RuntimeApiRequest::SubmitBackingMisbehaviorReport(
+			decode(input)?,
+			decode(input)?,
+			channel(),
+		),
+		"InherentWeightInfo" => RuntimeApiRequest::InherentWeightInfo(channel()),
+		_ => return None,
+	})
+}
+
+fn decode_network_bridge_event<M>(
+	input: &mut &[u8],
+	decode_message: impl FnOnce(&mut &[u8]) -> Option<M>,
+) -> Option<NetworkBridgeEvent<M>> {
+	Some(match decode::<String>(input)?.as_str() {
+		"PeerConnected" => NetworkBridgeEvent::PeerConnected(
+			decode_peer(input)?,
+			match decode::<String>(input)?.as_str() {
+				"Light" => ObservedRole::Light,
+				"Full" => ObservedRole::Full,
+				"Authority" => ObservedRole::Authority,
+				_ => return None,
+			},
+			// Validation and collation versions share their numbers.
+			match decode::<u32>(input)? {
+				1 => ValidationVersion::V1.into(),
+				2 => ValidationVersion::V2.into(),
+				_ => return None,
+			},
+			decode::<Option<Vec<AuthorityDiscoveryId>>>(input)?
+				.map(|ids| ids.into_iter().collect()),
+		),
+		"PeerDisconnected" => NetworkBridgeEvent::PeerDisconnected(decode_peer(input)?),
+		"NewGossipTopology" => {
+			let session = decode(input)?;
+			let shuffled_indices = decode_indices(input)?;
+			let canonical_shuffling =
+				decode::<Vec<(Vec<Vec<u8>>, ValidatorIndex, AuthorityDiscoveryId)>>(input)?
+					.into_iter()
+					.map(|(peer_ids, validator_index, discovery_id)| {
+						Some(TopologyPeerInfo {
+							peer_ids: peer_ids
+								.iter()
+								.map(|peer| PeerId::from_bytes(peer).ok())
+								.collect::<Option<_>>()?,
+							validator_index,
+							discovery_id,
+						})
+					})
+					.collect::<Option<_>>()?;
+
+			NetworkBridgeEvent::NewGossipTopology(NewGossipTopology {
+				session,
+				topology: SessionGridTopology::new(shuffled_indices, canonical_shuffling),
+				local_index: decode(input)?,
+			})
+		},
+		"PeerMessage" =>
+			NetworkBridgeEvent::PeerMessage(decode_peer(input)?, decode_message(input)?),
+		"PeerViewChange" => NetworkBridgeEvent::PeerViewChange(decode_peer(input)?, decode(input)?),
+		"OurViewChange" => {
+			let view: View = decode(input)?;
+			NetworkBridgeEvent::OurViewChange(OurView::new(
+				view.iter().map(|head| (*head, Arc::new(jaeger::Span::Disabled))),
+				view.finalized_number,
+			))
+		},
+		_ => return None,
+	})
+}
+
+fn decode_versioned<V1: Decode, V2: Decode>(input: &mut &[u8]) -> Option<Versioned<V1, V2>> {
+	Some(match decode::<String>(input)?.as_str() {
+		"V1" => Versioned::V1(decode(input)?),
+		"V2" => Versioned::V2(decode(input)?),
+		_ => return None,
+	})
+}
+
+fn decode_request(input: &mut &[u8]) -> Option<Requests> {
+	Some(match decode::<String>(input)?.as_str() {
+		"ChunkFetchingV1" => Requests::ChunkFetchingV1(decode_outgoing_request(input)?),
+		"CollationFetchingV1" => Requests::CollationFetchingV1(decode_outgoing_request(input)?),
+		"CollationFetchingV2" => Requests::CollationFetchingV2(decode_outgoing_request(input)?),
+		"PoVFetchingV1" => Requests::PoVFetchingV1(decode_outgoing_request(input)?),
+		"AvailableDataFetchingV1" =>
+			Requests::AvailableDataFetchingV1(decode_outgoing_request(input)?),
+		"StatementFetchingV1" => Requests::StatementFetchingV1(decode_outgoing_request(input)?),
+		"CandidateStatementsFetchingV1" =>
+			Requests::CandidateStatementsFetchingV1(decode_outgoing_request(input)?),
+		"DisputeSendingV1" => Requests::DisputeSendingV1(decode_outgoing_request(input)?),
+		_ => return None,
+	})
+}
+
+fn decode_outgoing_request<Req: Decode, FallbackReq: IsRequest + Decode>(
+	input: &mut &[u8],
+) -> Option<OutgoingRequest<Req, FallbackReq>> {
+	let peer = match decode::<String>(input)?.as_str() {
+		"Peer" => Recipient::Peer(decode_peer(input)?),
+		"Authority" => Recipient::Authority(decode(input)?),
+		_ => return None,
+	};
+
+	Some(OutgoingRequest {
+		peer,
+		payload: decode(input)?,
+		fallback_request: decode::<Option<FallbackReq>>(input)?
+			.map(|fallback| (fallback, FallbackReq::PROTOCOL)),
+		pending_response: channel(),
+	})
+}
+
+fn decode_reputation_change(input: &mut &[u8]) -> Option<UnifiedReputationChange> {
+	let name = decode::<String>(input)?;
+	// Reputation changes are described by static strings. Replays are short-lived, so the
+	// descriptions are leaked.
+	let description: &'static str = Box::leak(decode::<String>(input)?.into_boxed_str());
+
+	Some(match name.as_str() {
+		"CostMajor" => UnifiedReputationChange::CostMajor(description),
+		"CostMinor" => UnifiedReputationChange::CostMinor(description),
+		"CostMajorRepeated" => UnifiedReputationChange::CostMajorRepeated(description),
+		"CostMinorRepeated" => UnifiedReputationChange::CostMinorRepeated(description),
+		"Malicious" => UnifiedReputationChange::Malicious(description),
+		"BenefitMinorFirst" => UnifiedReputationChange::BenefitMinorFirst(description),
+		"BenefitMinor" => UnifiedReputationChange::BenefitMinor(description),
+		"BenefitMajorFirst" => UnifiedReputationChange::BenefitMajorFirst(description),
+		"BenefitMajor" => UnifiedReputationChange::BenefitMajor(description),
+		_ => return None,
+	})
+}
+
+fn decode_peer_set(input: &mut &[u8]) -> Option<PeerSet> {
+	Some(match decode::<String>(input)?.as_str() {
+		"Validation" => PeerSet::Validation,
+		"Collation" => PeerSet::Collation,
+		_ => return None,
+	})
+}
+
+fn decode_peer(input: &mut &[u8]) -> Option<PeerId> {
+	PeerId::from_bytes(&decode::<Vec<u8>>(input)?).ok()
+}
+
+fn decode_peers(input: &mut &[u8]) -> Option<Vec<PeerId>> {
+	decode::<Vec<Vec<u8>>>(input)?
+		.iter()
+		.map(|peer| PeerId::from_bytes(peer).ok())
+		.collect()
+}
+
+fn decode_indices(input: &mut &[u8]) -> Option<Vec<usize>> {
+	Some(decode::<Vec<u64>>(input)?.into_iter().map(|i| i as usize).collect())
+}
+
+/// Decode a sequence of values without a SCALE encoding of their own.
+fn decode_seq<T>(
+	input: &mut &[u8],
+	mut decode_item: impl FnMut(&mut &[u8]) -> Option<T>,
+) -> Option<Vec<T>> {
+	let len = Compact::<u32>::decode(input).ok()?.0;
+	(0..len).map(|_| decode_item(input)).collect()
+}
+
+fn decode<T: Decode>(input: &mut &[u8]) -> Option<T> {
+	T::decode(input).ok()
+}
+
+/// Decode the fields of a recorded message, which all have to be consumed.
+fn decode_with<M>(
+	msg: &RecordedMessage,
+	decode_fields: impl FnOnce(&str, &mut &[u8]) -> Option<M>,
+) -> Option<M> {
+	let mut input = msg.encoded.as_deref()?;
+	let decoded = decode_fields(&msg.message_type, &mut input)?;
+
+	input.is_empty().then(|| decoded)
+}
+
+/// A response channel nobody listens on.
+fn channel<T>() -> oneshot::Sender<T> {
+	oneshot::channel().0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::make_subsystem_context;
+	use futures::executor;
+	use polkadot_node_network_protocol::{our_view, v1 as protocol_v1, view};
+	use polkadot_node_subsystem::{
+		overseer::{
+			tap::{parse_recording, MessageTap, RecordableMessage, RecordedSignal},
+			SubsystemContext, SubsystemSender,
+		},
+		LeafStatus, OverseerSignal,
+	};
+	use polkadot_primitives::{Id as ParaId, OccupiedCoreAssumption};
+	use sp_keyring::Sr25519Keyring;
+	use std::{io::Write, sync::Mutex};
+
+	#[derive(Clone, Default)]
+	struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedBuffer {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	fn record_and_decode<M: RecordableMessage + ReplayableMessage + std::fmt::Debug>(
+		msg: M,
+	) -> Option<M> {
+		let buffer = SharedBuffer::default();
+		let tap = MessageTap::new(buffer.clone()).unwrap();
+		tap.record("subsystem", &FromOrchestra::Communication { msg });
+
+		tap.flush();
+		let records = parse_recording(&buffer.0.lock().unwrap()[..]).unwrap();
+		match &records[..] {
+			[TapRecord { event: TapEvent::Message(msg), .. }] =>
+				M::decode_recorded(msg, &ReplaySessions::default()),
+			records => panic!("Unexpected records: {:?}", records),
+		}
+	}
+
+	#[test]
+	fn replays_recording_against_subsystem() {
+		let buffer = SharedBuffer::default();
+		let tap = MessageTap::new(buffer.clone()).unwrap();
+
+		let leaf = RecordedSignal::ActiveLeaves {
+			activated: Some((Hash::repeat_byte(1), 1, LeafStatus::Fresh)),
+			deactivated: Vec::new(),
+		};
+		let deliveries = vec![
+			("collator-protocol", FromOrchestra::Signal(leaf.to_signal())),
+			(
+				"collator-protocol",
+				FromOrchestra::Communication { msg: CollatorProtocolMessage::CollateOn(5.into()) },
+			),
+			(
+				"other-subsystem",
+				FromOrchestra::Communication { msg: CollatorProtocolMessage::CollateOn(6.into()) },
+			),
+			("collator-protocol", FromOrchestra::Signal(OverseerSignal::Conclude)),
+		];
+		for (subsystem, delivery) in &deliveries {
+			tap.record(subsystem, delivery);
+		}
+
+		tap.flush();
+		let records = parse_recording(&buffer.0.lock().unwrap()[..]).unwrap();
+
+		let pool = sp_core::testing::TaskExecutor::new();
+		let (mut ctx, mut handle) = make_subsystem_context::<CollatorProtocolMessage, _>(pool);
+
+		let subsystem = async move {
+			let mut collating_on = Vec::new();
+			loop {
+				match ctx.recv().await.unwrap() {
+					FromOrchestra::Signal(OverseerSignal::ActiveLeaves(_)) => {
+						// Wait for a response before reading further deliveries.
+						let (tx, rx) = oneshot::channel();
+						ctx.sender().send_message(ChainApiMessage::FinalizedBlockNumber(tx)).await;
+						rx.await.unwrap().unwrap();
+					},
+					FromOrchestra::Communication {
+						msg: CollatorProtocolMessage::CollateOn(para_id),
+					} => collating_on.push(para_id),
+					FromOrchestra::Signal(OverseerSignal::Conclude) => return collating_on,
+					_ => {},
+				}
+			}
+		};
+
+		let replay = async move {
+			replay_recording(
+				&mut handle,
+				&records,
+				"collator-protocol",
+				|msg| CollatorProtocolMessage::decode_recorded(msg, &ReplaySessions::default()),
+				|msg| match msg {
+					AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(tx)) => {
+						let _ = tx.send(Ok(0));
+					},
+					msg => panic!("Unexpected message: {:?}", msg),
+				},
+			)
+			.await
+		};
+
+		let (collating_on, replayed) = executor::block_on(future::join(subsystem, replay));
+
+		assert_eq!(replayed, 3);
+		assert_eq!(collating_on, vec![ParaId::from(5)]);
+	}
+
+	#[test]
+	fn decodes_recorded_network_bridge_events() {
+		let peer = PeerId::random();
+		let authority_id: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+		let hash = Hash::repeat_byte(1);
+
+		let events = vec![
+			NetworkBridgeEvent::PeerConnected(
+				peer,
+				ObservedRole::Authority,
+				ValidationVersion::V2.into(),
+				Some([authority_id.clone()].into_iter().collect()),
+			),
+			NetworkBridgeEvent::PeerDisconnected(peer),
+			NetworkBridgeEvent::NewGossipTopology(NewGossipTopology {
+				session: 2,
+				topology: SessionGridTopology::new(
+					vec![0],
+					vec![TopologyPeerInfo {
+						peer_ids: vec![peer],
+						validator_index: ValidatorIndex(0),
+						discovery_id: authority_id,
+					}],
+				),
+				local_index: Some(ValidatorIndex(0)),
+			}),
+			NetworkBridgeEvent::PeerMessage(
+				peer,
+				Versioned::V1(protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new())),
+			),
+			NetworkBridgeEvent::PeerViewChange(peer, view![hash]),
+			NetworkBridgeEvent::OurViewChange(our_view![hash]),
+		];
+
+		for event in events {
+			match record_and_decode(ApprovalDistributionMessage::NetworkBridgeUpdate(event.clone()))
+			{
+				Some(ApprovalDistributionMessage::NetworkBridgeUpdate(decoded)) =>
+					assert_eq!(decoded, event),
+				decoded => panic!("Unexpected message: {:?}", decoded),
+			}
+		}
+	}
+
+	#[test]
+	fn decodes_recorded_requests() {
+		let hash = Hash::repeat_byte(1);
+		let (tx, _rx) = oneshot::channel();
+		let msg = RuntimeApiMessage::Request(
+			hash,
+			RuntimeApiRequest::PersistedValidationData(
+				5.into(),
+				OccupiedCoreAssumption::TimedOut,
+				tx,
+			),
+		);
+
+		match record_and_decode(msg) {
+			Some(RuntimeApiMessage::Request(
+				relay_parent,
+				RuntimeApiRequest::PersistedValidationData(
+					para_id,
+					OccupiedCoreAssumption::TimedOut,
+					_,
+				),
+			)) => {
+				assert_eq!(relay_parent, hash);
+				assert_eq!(para_id, ParaId::from(5));
+			},
+			decoded => panic!("Unexpected message: {:?}", decoded),
+		}
+
+		// The recorded form of a message has to be consumed entirely.
+		let msg = RecordedMessage {
+			message_type: "Approved".into(),
+			encoded: Some((hash, hash).encode()),
+		};
+		assert!(ChainSelectionMessage::decode_recorded(&msg, &ReplaySessions::default()).is_none());
+	}
+}
@@ -33,19 +33,22 @@ use polkadot_node_network_protocol::{
 	UnifiedReputationChange,
 };
 use polkadot_node_primitives::{
-	approval::{BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote},
+	approval::{
+		BlockApprovalMeta, CandidateBitfield, IndirectAssignmentCert, IndirectSignedApprovalVote,
+		IndirectSignedCoalescedApprovalVote,
+	},
 	AvailableData, BabeEpoch, BlockWeight, CandidateVotes, CollationGenerationConfig,
 	CollationSecondedSignal, DisputeMessage, DisputeStatus, ErasureChunk, PoV,
 	SignedDisputeStatement, SignedFullStatement, ValidationResult,
 };
 use polkadot_primitives::{
-	AuthorityDiscoveryId, BackedCandidate, BlockNumber, CandidateEvent, CandidateHash,
+	vstaging, AuthorityDiscoveryId, BackedCandidate, BlockNumber, CandidateEvent, CandidateHash,
 	CandidateIndex, CandidateReceipt, CollatorId, CommittedCandidateReceipt, CoreState,
 	DisputeState, GroupIndex, GroupRotationInfo, Hash, Header as BlockHeader, Id as ParaId,
 	InboundDownwardMessage, InboundHrmpMessage, MultiDisputeStatementSet, OccupiedCoreAssumption,
 	PersistedValidationData, PvfCheckStatement, SessionIndex, SessionInfo,
-	SignedAvailabilityBitfield, SignedAvailabilityBitfields, ValidationCode, ValidationCodeHash,
-	ValidatorId, ValidatorIndex, ValidatorSignature,
+	SignedAvailabilityBitfield, SignedAvailabilityBitfields, ValidDisputeStatementKind,
+	ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 use polkadot_statement_table::v2::Misbehavior;
 use std::{
@@ -166,9 +169,17 @@ pub enum CollatorProtocolMessage {
 	CollateOn(ParaId),
 	/// Provide a collation to distribute to validators with an optional result sender.
 	///
+	/// The hash is the hash of the para head the candidate builds upon, which is advertised to
+	/// validators alongside the candidate hash.
+	///
 	/// The result sender should be informed when at least one parachain validator seconded the collation. It is also
 	/// completely okay to just drop the sender.
-	DistributeCollation(CandidateReceipt, PoV, Option<oneshot::Sender<CollationSecondedSignal>>),
+	DistributeCollation(
+		CandidateReceipt,
+		Hash,
+		PoV,
+		Option<oneshot::Sender<CollationSecondedSignal>>,
+	),
 	/// Report a collator as having provided an invalid collation. This should lead to disconnect
 	/// and blacklist of the collator.
 	ReportCollator(CollatorId),
@@ -601,6 +612,16 @@ pub enum RuntimeApiRequest {
 	),
 	/// Returns all on-chain disputes at given block number. Available in `v3`.
 	Disputes(RuntimeApiSender<Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>>),
+	/// Get the approval voting parameters. Available in `v4`.
+	ApprovalVotingParams(RuntimeApiSender<vstaging::ApprovalVotingParams>),
+	/// Submits a signed report of backing misbehavior into the transaction pool. Available in `v5`.
+	SubmitBackingMisbehaviorReport(
+		vstaging::BackingMisbehaviorReport,
+		ValidatorSignature,
+		RuntimeApiSender<()>,
+	),
+	/// Get the weight parameters used to limit the `paras_inherent`. Available in `v6`.
+	InherentWeightInfo(RuntimeApiSender<vstaging::InherentWeightInfo>),
 }
 
 impl RuntimeApiRequest {
@@ -608,6 +629,15 @@ impl RuntimeApiRequest {
 
 	/// `Disputes`
 	pub const DISPUTES_RUNTIME_REQUIREMENT: u32 = 3;
+
+	/// `ApprovalVotingParams`
+	pub const APPROVAL_VOTING_PARAMS_RUNTIME_REQUIREMENT: u32 = 4;
+
+	/// `SubmitBackingMisbehaviorReport`
+	pub const SUBMIT_BACKING_MISBEHAVIOR_REPORT_RUNTIME_REQUIREMENT: u32 = 5;
+
+	/// `InherentWeightInfo`
+	pub const INHERENT_WEIGHT_INFO_RUNTIME_REQUIREMENT: u32 = 6;
 }
 
 /// A message to the Runtime API subsystem.
@@ -735,6 +765,8 @@ pub enum ApprovalCheckError {
 	NoAssignment(ValidatorIndex),
 	#[error("Internal state mismatch: {0:?}, {1:?}")]
 	Internal(Hash, CandidateHash),
+	#[error("Coalescing {1} approvals is not allowed at block {0:?}")]
+	CoalescingNotAllowed(Hash, usize),
 }
 
 /// Describes a relay-chain block by the para-chain candidates
@@ -778,6 +810,25 @@ pub enum ApprovalVotingMessage {
 	///
 	/// Should not be sent unless the block hash within the indirect vote is known.
 	CheckAndImportApproval(IndirectSignedApprovalVote, oneshot::Sender<ApprovalCheckResult>),
+	/// Check a batch of approval votes and import the valid ones, in order.
+	///
+	/// Signatures of the batch are verified together where possible. The response contains
+	/// one result per vote, in the same order as the votes were given.
+	///
+	/// Should not be sent unless the block hashes within the indirect votes are known.
+	CheckAndImportApprovals(
+		Vec<IndirectSignedApprovalVote>,
+		oneshot::Sender<Vec<ApprovalCheckResult>>,
+	),
+	/// Check if an approval vote covering multiple candidates is valid and can be accepted
+	/// by our view of the protocol. The vote is only accepted if coalescing of approvals is
+	/// enabled by the runtime at the block the vote refers to.
+	///
+	/// Should not be sent unless the block hash within the indirect vote is known.
+	CheckAndImportCoalescedApproval(
+		IndirectSignedCoalescedApprovalVote,
+		oneshot::Sender<ApprovalCheckResult>,
+	),
 	/// Returns the highest possible ancestor hash of the provided block hash which is
 	/// acceptable to vote on finality for.
 	/// The `BlockNumber` provided is the number of the block's ancestor which is the
@@ -791,9 +842,12 @@ pub enum ApprovalVotingMessage {
 	///
 	/// This message involves a linear search for candidates on each relay chain fork and also
 	/// requires calling into `approval-distribution`: Calls should be infrequent and bounded.
+	///
+	/// Every signature comes with the kind of approval statement it signs, which covers other
+	/// candidates as well if the approval was coalesced.
 	GetApprovalSignaturesForCandidate(
 		CandidateHash,
-		oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+		oneshot::Sender<HashMap<ValidatorIndex, (ValidDisputeStatementKind, ValidatorSignature)>>,
 	),
 }
 
@@ -810,14 +864,22 @@ pub enum ApprovalDistributionMessage {
 	/// valid, relevant, and the corresponding approval already issued.
 	/// If not, the subsystem is free to drop the message.
 	DistributeApproval(IndirectSignedApprovalVote),
+	/// Distribute an approval vote of the local validator covering multiple candidates.
+	/// The same assumptions as for `DistributeApproval` apply.
+	DistributeCoalescedApproval(IndirectSignedCoalescedApprovalVote),
 	/// An update from the network bridge.
 	#[from]
 	NetworkBridgeUpdate(NetworkBridgeEvent<net_protocol::ApprovalDistributionMessage>),
 
 	/// Get all approval signatures for all chains a candidate appeared in.
+	///
+	/// Every signature comes with the hash of the block it was issued at and, if the approval was
+	/// coalesced, the indices of all the candidates of that block it covers.
 	GetApprovalSignatures(
 		HashSet<(Hash, CandidateIndex)>,
-		oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+		oneshot::Sender<
+			HashMap<ValidatorIndex, (Hash, Option<CandidateBitfield>, ValidatorSignature)>,
+		>,
 	),
 }
 
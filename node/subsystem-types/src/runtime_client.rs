@@ -16,11 +16,12 @@
 
 use async_trait::async_trait;
 use polkadot_primitives::{
-	runtime_api::ParachainHost, Block, BlockId, BlockNumber, CandidateCommitments, CandidateEvent,
-	CandidateHash, CommittedCandidateReceipt, CoreState, DisputeState, GroupRotationInfo, Hash, Id,
-	InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption, PersistedValidationData,
-	PvfCheckStatement, ScrapedOnChainVotes, SessionIndex, SessionInfo, ValidationCode,
-	ValidationCodeHash, ValidatorId, ValidatorIndex, ValidatorSignature,
+	runtime_api::ParachainHost, vstaging, Block, BlockId, BlockNumber, CandidateCommitments,
+	CandidateEvent, CandidateHash, CommittedCandidateReceipt, CoreState, DisputeState,
+	GroupRotationInfo, Hash, Id, InboundDownwardMessage, InboundHrmpMessage,
+	OccupiedCoreAssumption, PersistedValidationData, PvfCheckStatement, ScrapedOnChainVotes,
+	SessionIndex, SessionInfo, ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
+	ValidatorSignature,
 };
 use sp_api::{ApiError, ApiExt, ProvideRuntimeApi};
 use sp_authority_discovery::AuthorityDiscoveryApi;
@@ -166,6 +167,13 @@ pub trait RuntimeApiSubsystemClient {
 		signature: ValidatorSignature,
 	) -> Result<(), ApiError>;
 
+	/// Returns the weight parameters used to limit the `paras_inherent`.
+	/// This is a staging method! Do not use on production runtimes!
+	async fn inherent_weight_info(
+		&self,
+		at: Hash,
+	) -> Result<vstaging::InherentWeightInfo, ApiError>;
+
 	/// Returns code hashes of PVFs that require pre-checking by validators in the active set.
 	///
 	/// NOTE: This function is only available since parachain host version 2.
@@ -188,6 +196,29 @@ pub trait RuntimeApiSubsystemClient {
 		at: Hash,
 	) -> Result<Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>, ApiError>;
 
+	/// Approval voting configuration parameters.
+	/// This is a staging method! Do not use on production runtimes!
+	async fn approval_voting_params(
+		&self,
+		at: Hash,
+	) -> Result<vstaging::ApprovalVotingParams, ApiError>;
+
+	/// Submits a signed report of backing misbehavior into the transaction pool.
+	/// This is a staging method! Do not use on production runtimes!
+	async fn submit_backing_misbehavior_report(
+		&self,
+		at: Hash,
+		report: vstaging::BackingMisbehaviorReport,
+		signature: ValidatorSignature,
+	) -> Result<(), ApiError>;
+
+	/// Returns the weight parameters used to limit the `paras_inherent`.
+	/// This is a staging method! Do not use on production runtimes!
+	async fn inherent_weight_info(
+		&self,
+		at: Hash,
+	) -> Result<vstaging::InherentWeightInfo, ApiError>;
+
 	// === BABE API ===
 
 	/// Returns information regarding the current epoch.
@@ -378,4 +409,28 @@ where
 	) -> Result<Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>, ApiError> {
 		self.runtime_api().disputes(&BlockId::Hash(at))
 	}
+
+	async fn approval_voting_params(
+		&self,
+		at: Hash,
+	) -> Result<vstaging::ApprovalVotingParams, ApiError> {
+		self.runtime_api().approval_voting_params(&BlockId::Hash(at))
+	}
+
+	async fn submit_backing_misbehavior_report(
+		&self,
+		at: Hash,
+		report: vstaging::BackingMisbehaviorReport,
+		signature: ValidatorSignature,
+	) -> Result<(), ApiError> {
+		self.runtime_api()
+			.submit_backing_misbehavior_report(&BlockId::Hash(at), report, signature)
+	}
+
+	async fn inherent_weight_info(
+		&self,
+		at: Hash,
+	) -> Result<vstaging::InherentWeightInfo, ApiError> {
+		self.runtime_api().inherent_weight_info(&BlockId::Hash(at))
+	}
 }
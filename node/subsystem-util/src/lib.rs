@@ -78,6 +78,9 @@ pub mod database;
 /// tasks, sending messages back.
 pub mod nesting_sender;
 
+/// The depth of the PVF execution queue, shared between subsystems.
+pub mod pvf_queue_depth;
+
 mod determine_new_blocks;
 
 #[cfg(test)]
@@ -0,0 +1,50 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Arc,
+};
+
+/// The number of candidate validations which were requested from the PVF host and didn't
+/// finish yet, by any subsystem.
+///
+/// Cloning the handle gives access to the same counter.
+#[derive(Debug, Clone, Default)]
+pub struct PvfQueueDepth(Arc<AtomicUsize>);
+
+impl PvfQueueDepth {
+	/// The number of pending validations.
+	pub fn get(&self) -> usize {
+		self.0.load(Ordering::Relaxed)
+	}
+
+	/// Note that a validation was requested. It is pending until the returned guard is dropped.
+	pub fn enter(&self) -> PendingValidation {
+		self.0.fetch_add(1, Ordering::Relaxed);
+		PendingValidation(self.0.clone())
+	}
+}
+
+/// A pending validation, see [`PvfQueueDepth::enter`].
+#[must_use = "the validation is only pending while the guard is alive"]
+pub struct PendingValidation(Arc<AtomicUsize>);
+
+impl Drop for PendingValidation {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::Relaxed);
+	}
+}
@@ -99,6 +99,11 @@ pub fn new_full(
 		false,
 		polkadot_service::RealOverseerGen,
 		None,
+		Default::default(),
+		None,
+		None,
+		Default::default(),
+		None,
 		None,
 		None,
 	)
@@ -66,6 +66,11 @@ fn main() -> Result<()> {
 					false,
 					polkadot_service::RealOverseerGen,
 					None,
+					Default::default(),
+					None,
+					None,
+					Default::default(),
+					None,
 					None,
 					None,
 				)
@@ -110,7 +110,7 @@
 //! All staging API functions should use primitives from `vstaging`. They should be clearly separated
 //! from the stable primitives.
 
-use crate::v2;
+use crate::{v2, vstaging};
 use parity_scale_codec::{Decode, Encode};
 use polkadot_core_primitives as pcp;
 use polkadot_parachain::primitives as ppp;
@@ -221,5 +221,39 @@ sp_api::decl_runtime_apis! {
 		/// Returns all onchain disputes.
 		#[api_version(3)]
 		fn disputes() -> Vec<(v2::SessionIndex, v2::CandidateHash, v2::DisputeState<v2::BlockNumber>)>;
+
+		/// Approval voting configuration parameters.
+		#[api_version(4)]
+		fn approval_voting_params() -> vstaging::ApprovalVotingParams;
+
+		/// Submits a signed report of backing misbehavior into the transaction pool.
+		#[api_version(5)]
+		fn submit_backing_misbehavior_report(
+			report: vstaging::BackingMisbehaviorReport,
+			signature: v2::ValidatorSignature,
+		);
+
+		/// Returns the weight parameters used to limit the `paras_inherent`.
+		#[api_version(6)]
+		fn inherent_weight_info() -> vstaging::InherentWeightInfo;
+
+		/// Returns the current spot price of an on-demand order for a parathread block.
+		#[api_version(7)]
+		fn on_demand_spot_price() -> v2::Balance;
+
+		/// Returns the number and total size of the upward messages of a para which are queued
+		/// to be processed by the relay chain.
+		#[api_version(8)]
+		fn ump_queue_footprint(para_id: ppp::Id) -> vstaging::QueueFootprint;
+
+		/// Returns the number and total size of the downward messages which are pending to be
+		/// processed by a para.
+		#[api_version(9)]
+		fn dmq_footprint(para_id: ppp::Id) -> vstaging::QueueFootprint;
+
+		/// Returns the HRMP channels a para participates in as either sender or recipient,
+		/// including the ones which are pending to be opened, along with their capacities.
+		#[api_version(10)]
+		fn hrmp_channels(para_id: ppp::Id) -> Vec<vstaging::HrmpChannelSummary>;
 	}
 }
@@ -43,7 +43,7 @@ pub use polkadot_core_primitives::{
 
 // Export some polkadot-parachain primitives
 pub use polkadot_parachain::primitives::{
-	HeadData, HrmpChannelId, Id, UpwardMessage, ValidationCode, ValidationCodeHash,
+	HeadData, HrmpChannelId, Id, IsSystem, UpwardMessage, ValidationCode, ValidationCodeHash,
 	LOWEST_PUBLIC_ID, LOWEST_USER_ID,
 };
 
@@ -1197,22 +1197,26 @@ pub enum DisputeStatement {
 impl DisputeStatement {
 	/// Get the payload data for this type of dispute statement.
 	pub fn payload_data(&self, candidate_hash: CandidateHash, session: SessionIndex) -> Vec<u8> {
-		match *self {
+		match self {
 			DisputeStatement::Valid(ValidDisputeStatementKind::Explicit) =>
 				ExplicitDisputeStatement { valid: true, candidate_hash, session }.signing_payload(),
 			DisputeStatement::Valid(ValidDisputeStatementKind::BackingSeconded(
 				inclusion_parent,
 			)) => CompactStatement::Seconded(candidate_hash).signing_payload(&SigningContext {
 				session_index: session,
-				parent_hash: inclusion_parent,
+				parent_hash: *inclusion_parent,
 			}),
 			DisputeStatement::Valid(ValidDisputeStatementKind::BackingValid(inclusion_parent)) =>
 				CompactStatement::Valid(candidate_hash).signing_payload(&SigningContext {
 					session_index: session,
-					parent_hash: inclusion_parent,
+					parent_hash: *inclusion_parent,
 				}),
 			DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking) =>
 				ApprovalVote(candidate_hash).signing_payload(session),
+			DisputeStatement::Valid(
+				ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(candidate_hashes),
+			) => crate::vstaging::ApprovalVoteMultipleCandidates(candidate_hashes)
+				.signing_payload(session),
 			DisputeStatement::Invalid(InvalidDisputeStatementKind::Explicit) =>
 				ExplicitDisputeStatement { valid: false, candidate_hash, session }.signing_payload(),
 		}
@@ -1226,6 +1230,16 @@ impl DisputeStatement {
 		session: SessionIndex,
 		validator_signature: &ValidatorSignature,
 	) -> Result<(), ()> {
+		// An approval of multiple candidates only speaks for the candidates it covers.
+		if let DisputeStatement::Valid(
+			ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(candidate_hashes),
+		) = self
+		{
+			if !candidate_hashes.contains(&candidate_hash) {
+				return Err(())
+			}
+		}
+
 		let payload = self.payload_data(candidate_hash, session);
 
 		if validator_signature.verify(&payload[..], &validator_public) {
@@ -1258,13 +1272,14 @@ impl DisputeStatement {
 			Self::Valid(ValidDisputeStatementKind::BackingValid(_)) => true,
 			Self::Valid(ValidDisputeStatementKind::Explicit) |
 			Self::Valid(ValidDisputeStatementKind::ApprovalChecking) |
+			Self::Valid(ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(_)) |
 			Self::Invalid(_) => false,
 		}
 	}
 }
 
 /// Different kinds of statements of validity on  a candidate.
-#[derive(Encode, Decode, Copy, Clone, PartialEq, RuntimeDebug, TypeInfo)]
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug, TypeInfo)]
 pub enum ValidDisputeStatementKind {
 	/// An explicit statement issued as part of a dispute.
 	#[codec(index = 0)]
@@ -1278,6 +1293,11 @@ pub enum ValidDisputeStatementKind {
 	/// An approval vote from the approval checking phase.
 	#[codec(index = 3)]
 	ApprovalChecking,
+	/// An approval vote from the approval checking phase, coalesced with the approvals of
+	/// other candidates of the same relay-chain block. Carries the hashes of all the covered
+	/// candidates, ordered by candidate index, which must include the disputed candidate.
+	#[codec(index = 4)]
+	ApprovalCheckingMultipleCandidates(Vec<CandidateHash>),
 }
 
 /// Different kinds of statements of invalidity on a candidate.
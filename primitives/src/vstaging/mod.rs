@@ -17,3 +17,244 @@
 //! Staging Primitives.
 
 // Put any primitives used by staging APIs functions here
+
+use crate::v2::{
+	BackedCandidate, CandidateHash, DisputeStatementSet, Hash, HrmpChannelId, SessionIndex,
+	ValidatorIndex, ValidatorSignature,
+};
+use parity_scale_codec::{Decode, Encode};
+use primitives::RuntimeDebug;
+use scale_info::TypeInfo;
+use sp_std::prelude::*;
+
+/// Approval voting configuration parameters.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct ApprovalVotingParams {
+	/// The maximum number of candidates `approval-voting` can vote for with
+	/// a single signature.
+	///
+	/// A value of 1 means coalescing of approvals is disabled.
+	pub max_approval_coalesce_count: u32,
+}
+
+impl Default for ApprovalVotingParams {
+	fn default() -> Self {
+		Self { max_approval_coalesce_count: 1 }
+	}
+}
+
+impl ApprovalVotingParams {
+	/// Whether validators may cover multiple candidates with a single approval vote.
+	pub fn coalescing_enabled(&self) -> bool {
+		self.max_approval_coalesce_count > 1
+	}
+}
+
+/// A vote of approval for multiple candidates of the same relay-chain block.
+#[derive(Clone, RuntimeDebug)]
+pub struct ApprovalVoteMultipleCandidates<'a>(pub &'a [CandidateHash]);
+
+impl<'a> ApprovalVoteMultipleCandidates<'a> {
+	/// Yields the signing payload for this approval vote.
+	///
+	/// The candidate hashes are length-prefixed, so the payload never coincides with the one
+	/// of an [`ApprovalVote`](crate::v2::ApprovalVote) for a single candidate.
+	pub fn signing_payload(&self, session_index: SessionIndex) -> Vec<u8> {
+		const MAGIC: [u8; 4] = *b"APPR";
+
+		(MAGIC, self.0, session_index).encode()
+	}
+}
+
+/// Misbehavior of a validator during backing which can be proven on-chain.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum BackingMisbehavior {
+	/// The validator seconded two different candidates at the same relay parent.
+	///
+	/// Each candidate hash is accompanied by the validator's signature of the compact
+	/// `Seconded` statement about it.
+	#[codec(index = 0)]
+	MultipleCandidates {
+		/// The first candidate seconded and the signature of the statement.
+		first: (CandidateHash, ValidatorSignature),
+		/// The second candidate seconded and the signature of the statement.
+		second: (CandidateHash, ValidatorSignature),
+	},
+}
+
+/// A report of backing misbehavior, to be signed by the reporting validator and submitted
+/// on-chain.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct BackingMisbehaviorReport {
+	/// The session the misbehavior happened in.
+	pub session_index: SessionIndex,
+	/// The relay parent the offending statements were made at.
+	pub relay_parent: Hash,
+	/// The index of the misbehaving validator in the session.
+	pub offender: ValidatorIndex,
+	/// The proof of the misbehavior.
+	pub misbehavior: BackingMisbehavior,
+	/// The index of the validator submitting the report.
+	pub reporter: ValidatorIndex,
+}
+
+impl BackingMisbehaviorReport {
+	/// Produce the payload used for signing this report.
+	///
+	/// It is expected that it will be signed by the validator at `reporter` in the
+	/// `session_index`.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		const MAGIC: [u8; 4] = *b"BKMR"; // for "backing misbehavior report"
+		(MAGIC, self).encode()
+	}
+}
+
+/// The weight of (parts of) the `paras_inherent`, in both dimensions of the relay chain's
+/// block weight.
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct InherentWeight {
+	/// The computational time consumed.
+	pub ref_time: u64,
+	/// The size of the storage proof consumed.
+	pub proof_size: u64,
+}
+
+impl InherentWeight {
+	/// Saturating addition of two weights.
+	pub fn saturating_add(self, other: Self) -> Self {
+		Self {
+			ref_time: self.ref_time.saturating_add(other.ref_time),
+			proof_size: self.proof_size.saturating_add(other.proof_size),
+		}
+	}
+
+	/// Saturating multiplication of a weight by a scalar.
+	pub fn saturating_mul(self, n: u64) -> Self {
+		Self {
+			ref_time: self.ref_time.saturating_mul(n),
+			proof_size: self.proof_size.saturating_mul(n),
+		}
+	}
+
+	/// Subtraction of another weight, returning `None` if it is larger in any dimension.
+	pub fn checked_sub(self, other: Self) -> Option<Self> {
+		Some(Self {
+			ref_time: self.ref_time.checked_sub(other.ref_time)?,
+			proof_size: self.proof_size.checked_sub(other.proof_size)?,
+		})
+	}
+
+	/// Whether this weight is no larger than `other` in every dimension.
+	pub fn all_lte(&self, other: &Self) -> bool {
+		self.ref_time <= other.ref_time && self.proof_size <= other.proof_size
+	}
+}
+
+/// The weight parameters the runtime uses to limit the `paras_inherent`.
+///
+/// The weight functions of the `paras_inherent` are linear in the number of votes, so these
+/// allow the block author to compute exactly the weight the runtime will account for the
+/// inherent data it provides.
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct InherentWeightInfo {
+	/// The maximum weight of a block, which the inherent must not exceed.
+	pub max_block: InherentWeight,
+	/// The weight of a dispute statement set without any statements.
+	pub dispute_statement_set: InherentWeight,
+	/// The additional weight of every statement in a dispute statement set.
+	pub dispute_statement: InherentWeight,
+	/// The weight of a single signed availability bitfield.
+	pub bitfield: InherentWeight,
+	/// The weight of a backed candidate without any validity votes.
+	pub backed_candidate: InherentWeight,
+	/// The additional weight of every validity vote of a backed candidate.
+	pub validity_vote: InherentWeight,
+	/// The weight of a backed candidate with a code upgrade, regardless of its votes.
+	pub backed_candidate_code_upgrade: InherentWeight,
+}
+
+impl InherentWeightInfo {
+	/// The weight of a dispute statement set.
+	pub fn dispute_statement_set_weight(&self, set: &DisputeStatementSet) -> InherentWeight {
+		self.dispute_statement_set
+			.saturating_add(self.dispute_statement.saturating_mul(set.statements.len() as u64))
+	}
+
+	/// The weight of `n` signed availability bitfields.
+	pub fn bitfields_weight(&self, n: usize) -> InherentWeight {
+		self.bitfield.saturating_mul(n as u64)
+	}
+
+	/// The weight of a backed candidate.
+	pub fn backed_candidate_weight<H>(&self, candidate: &BackedCandidate<H>) -> InherentWeight {
+		if candidate.candidate.commitments.new_validation_code.is_some() {
+			self.backed_candidate_code_upgrade
+		} else {
+			self.backed_candidate.saturating_add(
+				self.validity_vote.saturating_mul(candidate.validity_votes.len() as u64),
+			)
+		}
+	}
+}
+
+/// The number and total size of the messages in a message queue.
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct QueueFootprint {
+	/// The number of messages in the queue.
+	pub count: u64,
+	/// The total size of the messages in the queue, in bytes.
+	pub size: u64,
+}
+
+/// The state of an HRMP channel from the point of view of its lifecycle.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub enum HrmpChannelStatus {
+	/// The sender requested to open the channel, but the recipient didn't accept it yet.
+	#[codec(index = 0)]
+	Requested,
+	/// The recipient accepted the request. The channel opens on the next session change.
+	#[codec(index = 1)]
+	Accepted,
+	/// The channel is open.
+	#[codec(index = 2)]
+	Open,
+	/// The channel is open, but either party requested to close it on the next session change.
+	#[codec(index = 3)]
+	Closing,
+}
+
+/// A summary of an HRMP channel a para participates in, either pending or open.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct HrmpChannelSummary {
+	/// The sender and the recipient of the channel.
+	pub channel: HrmpChannelId,
+	/// The state of the channel.
+	pub status: HrmpChannelStatus,
+	/// The maximum number of messages that can be pending in the channel at once.
+	pub max_capacity: u32,
+	/// The maximum total size of the messages that can be pending in the channel at once.
+	pub max_total_size: u32,
+	/// The maximum message size that could be put into the channel.
+	pub max_message_size: u32,
+	/// The current number of messages pending in the channel. Zero unless the channel is open.
+	pub msg_count: u32,
+	/// The total size in bytes of the messages pending in the channel. Zero unless the channel
+	/// is open.
+	pub total_size: u32,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::v2::ApprovalVote;
+
+	#[test]
+	fn multiple_candidates_payload_differs_from_single_candidate_payload() {
+		let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+
+		assert_ne!(
+			ApprovalVoteMultipleCandidates(&[candidate_hash]).signing_payload(1),
+			ApprovalVote(candidate_hash).signing_payload(1),
+		);
+	}
+}
@@ -21,6 +21,8 @@
 use std::sync::Arc;
 
 use jsonrpsee::RpcModule;
+use polkadot_gossip_support::SharedConnectivityReport;
+use polkadot_network_bridge::{NetworkTraffic, ReputationAggregator};
 use polkadot_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Nonce};
 use sc_client_api::AuxStore;
 use sc_consensus_babe::{BabeConfiguration, Epoch};
@@ -34,6 +36,8 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::SyncCryptoStorePtr;
 use txpool_api::TransactionPool;
 
+pub mod parachain_network;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
@@ -74,6 +78,17 @@ pub struct BeefyDeps {
 	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
 }
 
+/// Dependencies for the parachain networking RPC.
+#[derive(Clone, Default)]
+pub struct ParachainNetworkDeps {
+	/// Traffic accounting of the parachain network bridge.
+	pub traffic: NetworkTraffic,
+	/// The latest connectivity report of the gossip support.
+	pub connectivity_report: SharedConnectivityReport,
+	/// The aggregated peer reputations of the parachain network bridge.
+	pub reputation: ReputationAggregator,
+}
+
 /// Full client dependencies
 pub struct FullDeps<C, P, SC, B> {
 	/// The client instance to use.
@@ -92,6 +107,8 @@ pub struct FullDeps<C, P, SC, B> {
 	pub grandpa: GrandpaDeps<B>,
 	/// BEEFY specific dependencies.
 	pub beefy: BeefyDeps,
+	/// Parachain networking specific dependencies.
+	pub parachain_network: ParachainNetworkDeps,
 }
 
 /// Instantiate all RPC extensions.
@@ -121,14 +138,24 @@ where
 	use frame_rpc_system::{System, SystemApiServer};
 	use mmr_rpc::{Mmr, MmrApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use parachain_network::{ParachainNetwork, ParachainNetworkApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
 	use sc_finality_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use sc_sync_state_rpc::{SyncState, SyncStateApiServer};
 	use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 	let mut io = RpcModule::new(());
-	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa, beefy } =
-		deps;
+	let FullDeps {
+		client,
+		pool,
+		select_chain,
+		chain_spec,
+		deny_unsafe,
+		babe,
+		grandpa,
+		beefy,
+		parachain_network,
+	} = deps;
 	let BabeDeps { keystore, babe_config, shared_epoch_changes } = babe;
 	let GrandpaDeps {
 		shared_voter_state,
@@ -176,5 +203,7 @@ where
 		.into_rpc(),
 	)?;
 
+	io.merge(ParachainNetwork::new(parachain_network, deny_unsafe).into_rpc())?;
+
 	Ok(io)
 }
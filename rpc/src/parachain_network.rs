@@ -0,0 +1,189 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC inspecting the traffic on the parachain peer-sets, the reputations of peers and the
+//! connectivity to authorities.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use polkadot_gossip_support::{AuthorityStatus, SharedConnectivityReport};
+use polkadot_network_bridge::{NetworkTraffic, ReputationAggregator};
+use polkadot_primitives::{AuthorityDiscoveryId, SessionIndex};
+use sc_rpc::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+
+use crate::ParachainNetworkDeps;
+
+/// The number of peers returned by `parachain_networkTopTalkers` if no limit is given.
+const DEFAULT_TOP_TALKERS: u32 = 20;
+
+/// The number of peers returned by `parachain_networkPeerScores` if no limit is given.
+const DEFAULT_PEER_SCORES: u32 = 20;
+
+/// The notification traffic exchanged with a peer on a parachain peer-set since it connected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopTalker {
+	/// The peer id of the peer.
+	pub peer_id: String,
+	/// The peer-set the traffic was exchanged on, either `validation` or `collation`.
+	pub peer_set: String,
+	/// Bytes of notifications sent to the peer.
+	pub bytes_sent: u64,
+	/// Bytes of notifications received from the peer.
+	pub bytes_received: u64,
+}
+
+/// The aggregated reputation of a peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerScore {
+	/// The peer id of the peer.
+	pub peer_id: String,
+	/// The sum of the reputation changes of the peer, decaying over time.
+	pub score: i32,
+	/// Whether the peer is banned by the node operator.
+	pub banned: bool,
+	/// Whether the peer is allowed by the node operator.
+	pub allowed: bool,
+}
+
+/// The connectivity to an authority, as of the latest connectivity report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityConnectivity {
+	/// The authority discovery key of the authority.
+	pub authority_id: String,
+	/// Either `unresolved`, `unreachable` or `connected`.
+	pub status: String,
+	/// The peer id we are connected to the authority with, if connected.
+	pub peer_id: Option<String>,
+}
+
+impl AuthorityConnectivity {
+	fn new((authority, status): (AuthorityDiscoveryId, AuthorityStatus)) -> Self {
+		let peer_id = match status {
+			AuthorityStatus::Connected(peer) => Some(peer.to_base58()),
+			AuthorityStatus::Unresolved | AuthorityStatus::Unreachable => None,
+		};
+		Self { authority_id: authority.to_string(), status: status.label().to_owned(), peer_id }
+	}
+}
+
+/// The latest connectivity report of the gossip support.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityReport {
+	/// The current session, if known.
+	pub session_index: Option<SessionIndex>,
+	/// The authorities of the current session.
+	pub current_session: Vec<AuthorityConnectivity>,
+	/// The authorities of the previous and next sessions, which are not part of the current
+	/// session.
+	pub adjacent_sessions: Vec<AuthorityConnectivity>,
+}
+
+/// Parachain networking RPC methods.
+#[rpc(server)]
+pub trait ParachainNetworkApi {
+	/// Returns the peers with the most notification traffic on the parachain peer-sets, sorted by
+	/// the total number of bytes exchanged since they connected.
+	#[method(name = "parachain_networkTopTalkers")]
+	fn top_talkers(&self, limit: Option<u32>) -> RpcResult<Vec<TopTalker>>;
+
+	/// Returns the peers with the lowest aggregated reputation on the parachain protocols, lowest
+	/// first.
+	#[method(name = "parachain_networkPeerScores")]
+	fn peer_scores(&self, limit: Option<u32>) -> RpcResult<Vec<PeerScore>>;
+
+	/// Returns the latest report on which authorities could not be resolved, are not reachable
+	/// or are connected, if any report has been produced yet.
+	#[method(name = "parachain_connectivityReport")]
+	fn connectivity_report(&self) -> RpcResult<Option<ConnectivityReport>>;
+}
+
+/// Implements the [`ParachainNetworkApiServer`] RPC trait.
+pub struct ParachainNetwork {
+	traffic: NetworkTraffic,
+	connectivity_report: SharedConnectivityReport,
+	reputation: ReputationAggregator,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl ParachainNetwork {
+	/// Create a new instance, inspecting the traffic accounting and peer reputations of the
+	/// network bridge and the connectivity report of the gossip support.
+	pub fn new(deps: ParachainNetworkDeps, deny_unsafe: DenyUnsafe) -> Self {
+		let ParachainNetworkDeps { traffic, connectivity_report, reputation } = deps;
+		Self { traffic, connectivity_report, reputation, deny_unsafe }
+	}
+}
+
+impl ParachainNetworkApiServer for ParachainNetwork {
+	fn top_talkers(&self, limit: Option<u32>) -> RpcResult<Vec<TopTalker>> {
+		// Exposes the peers we are connected to.
+		self.deny_unsafe.check_if_safe()?;
+
+		let limit = limit.unwrap_or(DEFAULT_TOP_TALKERS) as usize;
+		Ok(self
+			.traffic
+			.top_talkers(limit)
+			.into_iter()
+			.map(|talker| TopTalker {
+				peer_id: talker.peer.to_base58(),
+				peer_set: talker.peer_set.get_label().to_owned(),
+				bytes_sent: talker.bytes_sent,
+				bytes_received: talker.bytes_received,
+			})
+			.collect())
+	}
+
+	fn peer_scores(&self, limit: Option<u32>) -> RpcResult<Vec<PeerScore>> {
+		// Exposes the peers we interacted with.
+		self.deny_unsafe.check_if_safe()?;
+
+		let limit = limit.unwrap_or(DEFAULT_PEER_SCORES) as usize;
+		Ok(self
+			.reputation
+			.lowest_scores(limit)
+			.into_iter()
+			.map(|score| PeerScore {
+				peer_id: score.peer.to_base58(),
+				score: score.score,
+				banned: score.banned,
+				allowed: score.allowed,
+			})
+			.collect())
+	}
+
+	fn connectivity_report(&self) -> RpcResult<Option<ConnectivityReport>> {
+		// Exposes which peers the authorities are connected with.
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self.connectivity_report.latest().map(|report| ConnectivityReport {
+			session_index: report.session_index,
+			current_session: report
+				.current_session
+				.into_iter()
+				.map(AuthorityConnectivity::new)
+				.collect(),
+			adjacent_sessions: report
+				.adjacent_sessions
+				.into_iter()
+				.map(AuthorityConnectivity::new)
+				.collect(),
+		}))
+	}
+}
@@ -198,12 +198,14 @@ impl origin::Config for Test {}
 
 parameter_types! {
 	pub const ParasUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	pub const MaxExtraCores: u32 = 4;
 }
 
 impl paras::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = paras::TestWeightInfo;
 	type UnsignedPriority = ParasUnsignedPriority;
+	type MaxExtraCores = MaxExtraCores;
 	type NextSessionRotation = crate::mock::TestNextSessionRotation;
 }
 
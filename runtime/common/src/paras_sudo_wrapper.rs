@@ -62,6 +62,8 @@ pub mod pallet {
 		CannotUpgrade,
 		/// Cannot downgrade parachain.
 		CannotDowngrade,
+		/// A DMP message couldn't be sent because the downward message queue of the para is full.
+		DownwardMessageQueueFull,
 	}
 
 	#[pallet::hooks]
@@ -147,6 +149,8 @@ pub mod pallet {
 			{
 				dmp::QueueDownwardMessageError::ExceedsMaxMessageSize =>
 					Error::<T>::ExceedsMaxMessageSize.into(),
+				dmp::QueueDownwardMessageError::QueueFull =>
+					Error::<T>::DownwardMessageQueueFull.into(),
 			})
 		}
 
@@ -83,8 +83,9 @@ impl<T: configuration::Config + dmp::Config, W: xcm::WrapVersion, P: PriceForPar
 		(config, para, blob): (HostConfiguration<T::BlockNumber>, ParaId, Vec<u8>),
 	) -> Result<XcmHash, SendError> {
 		let hash = sp_io::hashing::blake2_256(&blob[..]);
+		// The queue of the para may have filled up since the message was validated.
 		<dmp::Pallet<T>>::queue_downward_message(&config, para, blob)
 			.map(|()| hash)
-			.map_err(|_| SendError::Transport(&"Error placing into DMP queue"))
+			.map_err(Into::<SendError>::into)
 	}
 }
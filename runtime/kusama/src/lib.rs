@@ -39,7 +39,8 @@ use sp_std::{cmp::Ordering, collections::btree_map::BTreeMap, prelude::*};
 use runtime_parachains::{
 	configuration as parachains_configuration, disputes as parachains_disputes,
 	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
-	initializer as parachains_initializer, origin as parachains_origin, paras as parachains_paras,
+	initializer as parachains_initializer, message_queue as parachains_message_queue,
+	origin as parachains_origin, paras as parachains_paras,
 	paras_inherent as parachains_paras_inherent, reward_points as parachains_reward_points,
 	runtime_api_impl::v2 as parachains_runtime_api_impl, scheduler as parachains_scheduler,
 	session_info as parachains_session_info, shared as parachains_shared, ump as parachains_ump,
@@ -1128,28 +1129,48 @@ impl parachains_inclusion::Config for Runtime {
 
 parameter_types! {
 	pub const ParasUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	pub const MaxExtraCores: u32 = 4;
 }
 
 impl parachains_paras::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = weights::runtime_parachains_paras::WeightInfo<Runtime>;
 	type UnsignedPriority = ParasUnsignedPriority;
+	type MaxExtraCores = MaxExtraCores;
 	type NextSessionRotation = Babe;
 }
 
-parameter_types! {
-	pub const FirstMessageFactorPercent: u64 = 100;
-}
-
 impl parachains_ump::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type UmpSink =
 		crate::parachains_ump::XcmSink<xcm_executor::XcmExecutor<xcm_config::XcmConfig>, Runtime>;
-	type FirstMessageFactorPercent = FirstMessageFactorPercent;
+	type MessageQueue = MessageQueue;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
 	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
+parameter_types! {
+	/// The size of the pages of the upward message queues. Messages larger than this get a page of
+	/// their own.
+	pub const MessageQueueHeapSize: u32 = 64 * 1024;
+	/// The number of upward messages processed per block, until their weights account for
+	/// the proof size of reading them.
+	pub const MessageQueueMaxMessagesPerBlock: u32 = 10;
+}
+
+impl parachains_message_queue::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MessageProcessor = Ump;
+	type QueueChangeHandler = Ump;
+	type QueuePausedQuery = parachains_ump::PauseOffboardingParas<Runtime>;
+	type HeapSize = MessageQueueHeapSize;
+	type ServiceWeight = parachains_ump::ServiceTotalWeight<Runtime>;
+	type MaxMessageWeight = parachains_ump::MaxIndividualWeight<Runtime>;
+	type MaxMessagesPerBlock = MessageQueueMaxMessagesPerBlock;
+	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = weights::runtime_parachains_message_queue::WeightInfo<Runtime>;
+}
+
 impl parachains_dmp::Config for Runtime {}
 
 impl parachains_hrmp::Config for Runtime {
@@ -1163,7 +1184,13 @@ impl parachains_paras_inherent::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_paras_inherent::WeightInfo<Runtime>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = (
+		parachains_scheduler::ParachainAssignmentProvider<Runtime>,
+		parachains_scheduler::ParathreadAssignmentProvider<Runtime>,
+	);
+	type OnParathreadClaimEnded = ();
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -1441,6 +1468,7 @@ construct_runtime! {
 		Hrmp: parachains_hrmp::{Pallet, Call, Storage, Event<T>, Config} = 60,
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage} = 61,
 		ParasDisputes: parachains_disputes::{Pallet, Call, Storage, Event<T>} = 62,
+		MessageQueue: parachains_message_queue::{Pallet, Call, Storage, Event<T>} = 63,
 
 		// Parachain Onboarding Pallets. Start indices at 70 to leave room.
 		Registrar: paras_registrar::{Pallet, Call, Storage, Event<T>} = 70,
@@ -1502,6 +1530,9 @@ pub type Migrations = (
 	// "Use 2D weights in XCM v3" <https://github.com/paritytech/polkadot/pull/6134>
 	pallet_xcm::migration::v1::MigrateToV1<Runtime>,
 	parachains_ump::migration::v1::MigrateToV1<Runtime>,
+	parachains_ump::migration::v2::MigrateToV2<Runtime>,
+	parachains_dmp::migration::v1::MigrateToV1<Runtime>,
+	parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -1538,6 +1569,7 @@ mod benches {
 		[runtime_parachains::hrmp, Hrmp]
 		[runtime_parachains::disputes, ParasDisputes]
 		[runtime_parachains::initializer, Initializer]
+		[runtime_parachains::message_queue, MessageQueue]
 		[runtime_parachains::paras_inherent, ParaInherent]
 		[runtime_parachains::paras, Paras]
 		[runtime_parachains::ump, Ump]
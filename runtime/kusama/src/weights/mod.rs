@@ -60,6 +60,7 @@ pub mod runtime_parachains_configuration;
 pub mod runtime_parachains_disputes;
 pub mod runtime_parachains_hrmp;
 pub mod runtime_parachains_initializer;
+pub mod runtime_parachains_message_queue;
 pub mod runtime_parachains_paras;
 pub mod runtime_parachains_paras_inherent;
 pub mod runtime_parachains_ump;
@@ -0,0 +1,80 @@
+// Copyright 2017-2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+//! Placeholder weights for `runtime_parachains::message_queue`
+//!
+//! NOTE: these weights were NOT produced by the benchmark CLI. They are hand-written estimates
+//! for servicing pages of `HeapSize` bytes, until the benchmarks of the pallet are run with the
+//! command below.
+
+// Command to generate the weights:
+// ./target/production/polkadot
+// benchmark
+// pallet
+// --chain=kusama-dev
+// --steps=50
+// --repeat=20
+// --pallet=runtime_parachains::message_queue
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --header=./file_header.txt
+// --output=./runtime/kusama/src/weights/runtime_parachains_message_queue.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::message_queue`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::message_queue::WeightInfo for WeightInfo<T> {
+	// Storage: MessageQueue ServiceHead (r:1 w:0)
+	// Storage: MessageQueue MessagesVisited (r:1 w:1)
+	fn service_queue_base() -> Weight {
+		Weight::from_ref_time(3_047_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: MessageQueue BookStateFor (r:2 w:2)
+	// Storage: MessageQueue Pages (r:1 w:1)
+	// Storage: MessageQueue ServiceHead (r:1 w:1)
+	// Storage: Ump RelayDispatchQueueSize (r:0 w:1)
+	fn service_page_base() -> Weight {
+		Weight::from_ref_time(27_312_000)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+	fn service_page_item() -> Weight {
+		Weight::from_ref_time(50_243_000)
+	}
+	// Storage: MessageQueue BookStateFor (r:1 w:0)
+	// Storage: MessageQueue ServiceHead (r:0 w:1)
+	fn bump_service_head() -> Weight {
+		Weight::from_ref_time(7_439_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: Paras ParaLifecycles (r:1 w:0)
+	// Storage: MessageQueue BookStateFor (r:1 w:1)
+	// Storage: MessageQueue Pages (r:1 w:1)
+	fn execute_overweight() -> Weight {
+		Weight::from_ref_time(73_006_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+}
@@ -0,0 +1,52 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+
+fn shares(n: u32) -> Vec<ParaShare> {
+	(0..n)
+		.map(|i| ParaShare { para_id: ParaId::from(1000 + i), parts: 1 })
+		.collect()
+}
+
+benchmarks! {
+	// The worst case is the last core, with all other cores assigned to the maximum number of
+	// paras.
+	assign_core {
+		let s in 0 .. T::MaxParasPerCore::get();
+		let max_cores = T::MaxCores::get();
+
+		PendingCores::<T>::put(vec![shares(T::MaxParasPerCore::get()); max_cores as usize]);
+		let core = max_cores - 1;
+		let shares = shares(s);
+	}: _(RawOrigin::Root, core, shares.clone())
+	verify {
+		let pending = PendingCores::<T>::get().unwrap();
+		if s > 0 {
+			assert_eq!(pending[core as usize], shares);
+		} else {
+			assert_eq!(pending.len(), core as usize);
+		}
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
@@ -0,0 +1,274 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bulk assignment of availability cores to paras.
+//!
+//! Governance assigns the bulk cores to paras for whole sessions, e.g. to hand out coretime sold
+//! in bulk. A core may be split between several paras: each para assigned to a core has a number
+//! of parts, and the blocks scheduled on the core are split between the paras by the ratio of
+//! their parts. A core assigned to para A with 2 parts and to para B with 1 part is scheduled for
+//! A twice as often as for B.
+//!
+//! The paras of a core take turns in a smooth weighted round robin, so that their blocks are
+//! spread evenly over time. A turn is used up whenever the core is scheduled, even if the para
+//! does not back a candidate.
+//!
+//! The pallet is an [`AssignmentProvider`] for the scheduler, to be composed with the other
+//! providers of the runtime. Changes to the assignments take effect at the next session.
+
+use crate::{
+	configuration::{self, HostConfiguration},
+	paras,
+	scheduler::{Assignment, AssignmentProvider},
+};
+use frame_support::pallet_prelude::*;
+use frame_system::pallet_prelude::*;
+use primitives::{CoreIndex, Id as ParaId};
+use sp_std::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait WeightInfo {
+	fn assign_core(s: u32) -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn assign_core(_: u32) -> Weight {
+		Weight::MAX
+	}
+}
+
+/// The share of a para in the blocks scheduled on a bulk core.
+#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq, RuntimeDebug)]
+pub struct ParaShare {
+	/// The para the core is assigned to.
+	pub para_id: ParaId,
+	/// The parts of the blocks of the core scheduled for the para, relative to the parts of the
+	/// other paras of the core.
+	pub parts: u16,
+}
+
+/// A bulk core and its progress in splitting the blocks between its paras.
+#[derive(Clone, Encode, Decode, TypeInfo, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct BulkCore {
+	/// The paras the core is assigned to.
+	pub shares: Vec<ParaShare>,
+	/// The credit of each para, in the order of `shares`. The para with the most credit takes the
+	/// next turn.
+	credits: Vec<i64>,
+}
+
+impl BulkCore {
+	/// A core assigned to the given paras, none of which had a turn yet.
+	pub fn new(shares: Vec<ParaShare>) -> Self {
+		let credits = vec![0; shares.len()];
+		Self { shares, credits }
+	}
+
+	/// Give the next turn to a para of the core and return it, if the core is assigned to any.
+	///
+	/// Every para gains its parts as credit, the para with the most credit takes the turn and
+	/// pays for it with the parts of all paras. Over the total number of parts of all paras,
+	/// every para takes exactly as many turns as it has parts.
+	fn next_turn(&mut self) -> Option<ParaId> {
+		let total: i64 = self.shares.iter().map(|share| share.parts as i64).sum();
+
+		for (credit, share) in self.credits.iter_mut().zip(&self.shares) {
+			*credit += share.parts as i64;
+		}
+
+		let mut next: Option<usize> = None;
+		for (i, credit) in self.credits.iter().enumerate() {
+			if next.map_or(true, |next| *credit > self.credits[next]) {
+				next = Some(i);
+			}
+		}
+
+		let next = next?;
+		self.credits[next] -= total;
+		Some(self.shares[next].para_id)
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + configuration::Config + paras::Config {
+		/// The outer event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The maximum number of bulk cores.
+		#[pallet::constant]
+		type MaxCores: Get<u32>;
+
+		/// The maximum number of paras a bulk core can be split between.
+		#[pallet::constant]
+		type MaxParasPerCore: Get<u32>;
+
+		/// Something that provides the weight of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A bulk core was assigned to the given paras, starting with the next session. The core
+		/// is idle if there are none.
+		CoreAssigned { core: u32, shares: Vec<ParaShare> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The core is beyond the maximum number of bulk cores.
+		CoreOutOfBounds,
+		/// The core is split between too many paras.
+		TooManyParas,
+		/// A para is assigned to the core more than once.
+		DuplicatePara,
+		/// A para is assigned to the core without any parts.
+		NoParts,
+	}
+
+	/// The bulk cores of the current session.
+	///
+	/// Bounded by `MaxCores`.
+	#[pallet::storage]
+	#[pallet::getter(fn cores)]
+	pub(super) type Cores<T: Config> = StorageValue<_, Vec<BulkCore>, ValueQuery>;
+
+	/// The assignments of the bulk cores starting with the next session, if they change.
+	///
+	/// Bounded by `MaxCores`.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_cores)]
+	pub(super) type PendingCores<T: Config> = StorageValue<_, Vec<Vec<ParaShare>>>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Assign the bulk core `core` to the paras of `shares`, starting with the next session.
+		///
+		/// The blocks scheduled on the core are split between the paras by the ratio of their
+		/// parts. The core is idle if `shares` is empty. Cores are added or removed as needed,
+		/// cores before `core` which are not assigned yet are idle.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::assign_core(shares.len() as u32))]
+		pub fn assign_core(
+			origin: OriginFor<T>,
+			core: u32,
+			shares: Vec<ParaShare>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			ensure!(core < T::MaxCores::get(), Error::<T>::CoreOutOfBounds);
+			ensure!(shares.len() as u32 <= T::MaxParasPerCore::get(), Error::<T>::TooManyParas);
+			ensure!(shares.iter().all(|share| share.parts > 0), Error::<T>::NoParts);
+
+			let mut paras: Vec<_> = shares.iter().map(|share| share.para_id).collect();
+			paras.sort();
+			paras.dedup();
+			ensure!(paras.len() == shares.len(), Error::<T>::DuplicatePara);
+
+			let mut pending = PendingCores::<T>::get()
+				.unwrap_or_else(|| Cores::<T>::get().into_iter().map(|core| core.shares).collect());
+			if pending.len() <= core as usize {
+				pending.resize(core as usize + 1, Vec::new());
+			}
+			pending[core as usize] = shares.clone();
+
+			// Idle cores at the end are removed.
+			while pending.last().map_or(false, |shares| shares.is_empty()) {
+				pending.pop();
+			}
+			PendingCores::<T>::put(pending);
+
+			Self::deposit_event(Event::CoreAssigned { core, shares });
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> AssignmentProvider<T::BlockNumber> for Pallet<T> {
+	fn on_new_session(_config: &HostConfiguration<T::BlockNumber>, _returned: Vec<Assignment>) {
+		if let Some(pending) = PendingCores::<T>::take() {
+			Cores::<T>::put(pending.into_iter().map(BulkCore::new).collect::<Vec<_>>());
+		}
+	}
+
+	fn session_core_count(_config: &HostConfiguration<T::BlockNumber>) -> u32 {
+		Cores::<T>::decode_len().unwrap_or(0) as u32
+	}
+
+	fn pop_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		_now: T::BlockNumber,
+	) -> Option<Assignment> {
+		let para_id = Cores::<T>::mutate(|cores| cores.get_mut(core_idx.0 as usize)?.next_turn())?;
+		Self::assignment(para_id)
+	}
+
+	fn peek_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		_now: T::BlockNumber,
+	) -> Option<Assignment> {
+		let mut core = Cores::<T>::get().get(core_idx.0 as usize)?.clone();
+		Self::assignment(core.next_turn()?)
+	}
+
+	fn push_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		_assignment: Assignment,
+	) {
+		// The turn of the para is used up, whether it backed a candidate or not.
+	}
+
+	fn report_processed(
+		_config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		_assignment: Assignment,
+	) {
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The assignment of a para taking its turn on a bulk core. Paras which are not onboarded
+	/// lose their turn.
+	fn assignment(para_id: ParaId) -> Option<Assignment> {
+		if <paras::Pallet<T>>::is_valid_para(para_id) {
+			Some(Assignment::parachain(para_id))
+		} else {
+			None
+		}
+	}
+}
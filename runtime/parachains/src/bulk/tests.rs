@@ -0,0 +1,266 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok};
+use keyring::Sr25519Keyring;
+use primitives::{BlockNumber, ScheduledCore, SessionIndex, ValidatorId};
+use sp_runtime::DispatchError;
+
+use crate::{
+	configuration::HostConfiguration,
+	initializer::SessionChangeNotification,
+	mock::{
+		new_test_ext, Bulk, MockGenesisConfig, Paras, ParasShared, RuntimeOrigin, Scheduler,
+		System, Test,
+	},
+	paras::{ParaGenesisArgs, ParaKind},
+	scheduler::AssignmentKind,
+};
+
+fn default_config() -> HostConfiguration<BlockNumber> {
+	HostConfiguration {
+		parathread_cores: 1,
+		group_rotation_frequency: 10,
+		scheduling_lookahead: 2,
+		pvf_checking_enabled: false,
+		// Required by the consistency check of the `HostConfiguration`.
+		minimum_validation_upgrade_delay: 6,
+		..Default::default()
+	}
+}
+
+fn genesis_config() -> MockGenesisConfig {
+	MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	}
+}
+
+fn schedule_blank_para(id: ParaId, para_kind: ParaKind) {
+	assert_ok!(Paras::schedule_para_initialize(
+		id,
+		ParaGenesisArgs {
+			genesis_head: Vec::new().into(),
+			validation_code: vec![1, 2, 3].into(),
+			para_kind,
+		}
+	));
+}
+
+fn run_to_block(
+	to: BlockNumber,
+	new_session: impl Fn(BlockNumber) -> Option<SessionChangeNotification<BlockNumber>>,
+) {
+	while System::block_number() < to {
+		let b = System::block_number();
+
+		Scheduler::initializer_finalize();
+		Paras::initializer_finalize(b);
+
+		if let Some(mut notification) = new_session(b + 1) {
+			// Make every session change enact the scheduled actions.
+			if notification.session_index == SessionIndex::default() {
+				notification.session_index = ParasShared::scheduled_session();
+			}
+			Paras::initializer_on_new_session(&notification);
+			Scheduler::initializer_on_new_session(&notification);
+		}
+
+		System::on_finalize(b);
+
+		System::on_initialize(b + 1);
+		System::set_block_number(b + 1);
+
+		Paras::initializer_initialize(b + 1);
+		Scheduler::initializer_initialize(b + 1);
+
+		// In the real runtime this is expected to be called by the `InclusionInherent` pallet.
+		Scheduler::clear();
+		Scheduler::schedule(Vec::new(), b + 1);
+	}
+}
+
+fn session_change(
+	block: BlockNumber,
+) -> impl Fn(BlockNumber) -> Option<SessionChangeNotification<BlockNumber>> {
+	move |n| {
+		(n == block).then(|| SessionChangeNotification {
+			new_config: default_config(),
+			validators: vec![
+				ValidatorId::from(Sr25519Keyring::Alice.public()),
+				ValidatorId::from(Sr25519Keyring::Bob.public()),
+				ValidatorId::from(Sr25519Keyring::Charlie.public()),
+				ValidatorId::from(Sr25519Keyring::Dave.public()),
+			],
+			..Default::default()
+		})
+	}
+}
+
+fn share(para_id: u32, parts: u16) -> ParaShare {
+	ParaShare { para_id: para_id.into(), parts }
+}
+
+/// The para scheduled on the given core, if any.
+fn scheduled_on(core: u32) -> Option<ParaId> {
+	Scheduler::scheduled()
+		.into_iter()
+		.find(|assignment| assignment.core == CoreIndex(core))
+		.map(|assignment| {
+			assert_eq!(assignment.kind, AssignmentKind::Parachain);
+			assignment.para_id
+		})
+}
+
+#[test]
+fn turns_are_split_by_parts() {
+	let mut core = BulkCore::new(vec![share(1, 2), share(2, 1)]);
+	let turns: Vec<_> = (0..6).map(|_| core.next_turn().unwrap()).collect();
+	assert_eq!(turns, [1, 2, 1, 1, 2, 1].map(ParaId::from));
+
+	let mut core = BulkCore::new(vec![share(1, 1), share(2, 1), share(3, 2)]);
+	let turns: Vec<_> = (0..8).map(|_| core.next_turn().unwrap()).collect();
+	assert_eq!(turns, [3, 1, 2, 3, 3, 1, 2, 3].map(ParaId::from));
+
+	let mut core = BulkCore::new(vec![share(1, 7)]);
+	assert_eq!(core.next_turn(), Some(ParaId::from(1)));
+	assert_eq!(core.next_turn(), Some(ParaId::from(1)));
+
+	assert_eq!(BulkCore::new(Vec::new()).next_turn(), None);
+}
+
+#[test]
+fn assign_core_checks_assignment() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		assert_noop!(
+			Bulk::assign_core(RuntimeOrigin::signed(1), 0, vec![share(1, 1)]),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Bulk::assign_core(RuntimeOrigin::root(), 4, vec![share(1, 1)]),
+			Error::<Test>::CoreOutOfBounds,
+		);
+		assert_noop!(
+			Bulk::assign_core(
+				RuntimeOrigin::root(),
+				0,
+				vec![share(1, 1), share(2, 1), share(3, 1), share(4, 1)],
+			),
+			Error::<Test>::TooManyParas,
+		);
+		assert_noop!(
+			Bulk::assign_core(RuntimeOrigin::root(), 0, vec![share(1, 1), share(1, 2)]),
+			Error::<Test>::DuplicatePara,
+		);
+		assert_noop!(
+			Bulk::assign_core(RuntimeOrigin::root(), 0, vec![share(1, 1), share(2, 0)]),
+			Error::<Test>::NoParts,
+		);
+	});
+}
+
+#[test]
+fn assignments_take_effect_at_next_session() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		run_to_block(1, |_| None);
+
+		assert_ok!(Bulk::assign_core(RuntimeOrigin::root(), 1, vec![share(1, 1)]));
+		System::assert_last_event(
+			Event::CoreAssigned { core: 1, shares: vec![share(1, 1)] }.into(),
+		);
+		assert_eq!(Bulk::pending_cores(), Some(vec![vec![], vec![share(1, 1)]]));
+		assert_eq!(Bulk::cores(), Vec::new());
+
+		run_to_block(2, session_change(2));
+		assert_eq!(Bulk::pending_cores(), None);
+		assert_eq!(
+			Bulk::cores(),
+			vec![BulkCore::new(Vec::new()), BulkCore::new(vec![share(1, 1)])],
+		);
+
+		// Idle cores at the end are removed.
+		assert_ok!(Bulk::assign_core(RuntimeOrigin::root(), 1, Vec::new()));
+		assert_eq!(Bulk::pending_cores(), Some(Vec::new()));
+
+		run_to_block(3, session_change(3));
+		assert_eq!(Bulk::cores(), Vec::new());
+	});
+}
+
+#[test]
+fn bulk_cores_are_scheduled_after_other_cores() {
+	let chain = ParaId::from(1);
+	let thread_a = ParaId::from(2);
+	let thread_b = ParaId::from(3);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		schedule_blank_para(chain, ParaKind::Parachain);
+		schedule_blank_para(thread_a, ParaKind::Parathread);
+		schedule_blank_para(thread_b, ParaKind::Parathread);
+
+		assert_ok!(Bulk::assign_core(RuntimeOrigin::root(), 0, vec![share(2, 2), share(3, 1)],));
+
+		run_to_block(1, session_change(1));
+
+		// One parachain core, one parathread core and one bulk core.
+		assert_eq!(Scheduler::availability_cores().len(), 3);
+		assert_eq!(scheduled_on(0), Some(chain));
+		assert_eq!(scheduled_on(1), None);
+
+		// The bulk core is not occupied, so a new turn is taken every block.
+		let mut turns = vec![scheduled_on(2).unwrap()];
+		for b in 2..=6 {
+			run_to_block(b, |_| None);
+			turns.push(scheduled_on(2).unwrap());
+		}
+		assert_eq!(turns, vec![thread_a, thread_b, thread_a, thread_a, thread_b, thread_a]);
+
+		// The next turn is the one of `thread_b`.
+		assert_eq!(
+			Scheduler::next_up_on_available(CoreIndex(2)),
+			Some(ScheduledCore { para_id: thread_b, collator: None }),
+		);
+		assert_eq!(scheduled_on(2), Some(thread_a));
+	});
+}
+
+#[test]
+fn paras_which_are_not_onboarded_lose_their_turn() {
+	let thread_a = ParaId::from(2);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		schedule_blank_para(thread_a, ParaKind::Parathread);
+
+		assert_ok!(Bulk::assign_core(RuntimeOrigin::root(), 0, vec![share(2, 1), share(3, 1)],));
+
+		run_to_block(1, session_change(1));
+
+		// One parathread core and one bulk core.
+		assert_eq!(Scheduler::availability_cores().len(), 2);
+		assert_eq!(scheduled_on(1), Some(thread_a));
+
+		run_to_block(2, |_| None);
+		assert_eq!(scheduled_on(1), None);
+
+		run_to_block(3, |_| None);
+		assert_eq!(scheduled_on(1), Some(thread_a));
+	});
+}
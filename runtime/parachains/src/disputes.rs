@@ -22,11 +22,11 @@ use frame_support::{ensure, traits::Get, weights::Weight};
 use frame_system::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
 use primitives::{
-	byzantine_threshold, supermajority_threshold, ApprovalVote, CandidateHash,
-	CheckedDisputeStatementSet, CheckedMultiDisputeStatementSet, CompactStatement, ConsensusLog,
-	DisputeState, DisputeStatement, DisputeStatementSet, ExplicitDisputeStatement,
-	InvalidDisputeStatementKind, MultiDisputeStatementSet, SessionIndex, SigningContext,
-	ValidDisputeStatementKind, ValidatorId, ValidatorIndex, ValidatorSignature,
+	byzantine_threshold, supermajority_threshold, vstaging::ApprovalVoteMultipleCandidates,
+	ApprovalVote, CandidateHash, CheckedDisputeStatementSet, CheckedMultiDisputeStatementSet,
+	CompactStatement, ConsensusLog, DisputeState, DisputeStatement, DisputeStatementSet,
+	ExplicitDisputeStatement, InvalidDisputeStatementKind, MultiDisputeStatementSet, SessionIndex,
+	SigningContext, ValidDisputeStatementKind, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 use scale_info::TypeInfo;
 use sp_runtime::{
@@ -95,6 +95,15 @@ pub trait SlashingHandler<BlockNumber> {
 		losers: impl IntoIterator<Item = ValidatorIndex>,
 	);
 
+	/// Punish a series of validators who misbehaved while backing a candidate,
+	/// e.g. by seconding multiple candidates at the same relay parent. This is
+	/// expected to be a minor punishment.
+	fn punish_backing_misbehavior(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		offenders: impl IntoIterator<Item = ValidatorIndex>,
+	);
+
 	/// Called by the initializer to initialize the slashing pallet.
 	fn initializer_initialize(now: BlockNumber) -> Weight;
 
@@ -120,6 +129,13 @@ impl<BlockNumber> SlashingHandler<BlockNumber> for () {
 	) {
 	}
 
+	fn punish_backing_misbehavior(
+		_: SessionIndex,
+		_: CandidateHash,
+		_: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
+
 	fn initializer_initialize(_now: BlockNumber) -> Weight {
 		Weight::zero()
 	}
@@ -1174,21 +1190,30 @@ fn check_signature(
 	statement: &DisputeStatement,
 	validator_signature: &ValidatorSignature,
 ) -> Result<(), ()> {
-	let payload = match *statement {
+	let payload = match statement {
 		DisputeStatement::Valid(ValidDisputeStatementKind::Explicit) =>
 			ExplicitDisputeStatement { valid: true, candidate_hash, session }.signing_payload(),
 		DisputeStatement::Valid(ValidDisputeStatementKind::BackingSeconded(inclusion_parent)) =>
 			CompactStatement::Seconded(candidate_hash).signing_payload(&SigningContext {
 				session_index: session,
-				parent_hash: inclusion_parent,
+				parent_hash: *inclusion_parent,
 			}),
 		DisputeStatement::Valid(ValidDisputeStatementKind::BackingValid(inclusion_parent)) =>
 			CompactStatement::Valid(candidate_hash).signing_payload(&SigningContext {
 				session_index: session,
-				parent_hash: inclusion_parent,
+				parent_hash: *inclusion_parent,
 			}),
 		DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking) =>
 			ApprovalVote(candidate_hash).signing_payload(session),
+		DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(
+			candidate_hashes,
+		)) => {
+			// A coalesced approval only speaks for the candidates it covers.
+			if !candidate_hashes.contains(&candidate_hash) {
+				return Err(())
+			}
+			ApprovalVoteMultipleCandidates(candidate_hashes).signing_payload(session)
+		},
 		DisputeStatement::Invalid(InvalidDisputeStatementKind::Explicit) =>
 			ExplicitDisputeStatement { valid: false, candidate_hash, session }.signing_payload(),
 	};
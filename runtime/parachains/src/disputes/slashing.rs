@@ -42,6 +42,12 @@
 //! Later on, a block producer can submit an unsigned transaction with
 //! `KeyOwnershipProof` of an offender and submit it to the runtime
 //! to produce an offence.
+//!
+//! Validators seconding multiple candidates at the same relay parent are
+//! punished the same way. Any validator can submit a signed report of such
+//! misbehavior, which is checked against the session info of the session the
+//! statements were made in. Such reports are only accepted during the session
+//! of the misbehavior, so that the offence is always submitted directly.
 
 use crate::{disputes, initializer::ValidatorSetCount, session_info::IdentificationTuple};
 use frame_support::{
@@ -51,7 +57,10 @@ use frame_support::{
 };
 
 use parity_scale_codec::{Decode, Encode};
-use primitives::{CandidateHash, SessionIndex, ValidatorId, ValidatorIndex};
+use primitives::{
+	vstaging::BackingMisbehaviorReport, CandidateHash, Hash, SessionIndex, ValidatorId,
+	ValidatorIndex, ValidatorSignature,
+};
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::Convert,
@@ -74,8 +83,10 @@ const LOG_TARGET: &str = "runtime::parachains::slashing";
 // via `HostConfiguration` in the future.
 const SLASH_FOR_INVALID: Perbill = Perbill::from_percent(100);
 const SLASH_AGAINST_VALID: Perbill = Perbill::from_perthousand(1);
+const SLASH_FOR_BACKING_MISBEHAVIOR: Perbill = Perbill::from_percent(1);
 const DEFENSIVE_PROOF: &'static str = "disputes module should bail on old session";
 
+mod backing;
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
 
@@ -153,6 +164,7 @@ where
 			// in the future we might change it based on number of disputes initiated:
 			// <https://github.com/paritytech/polkadot/issues/5946>
 			SlashingOffenceKind::AgainstValid => DisableStrategy::Never,
+			SlashingOffenceKind::BackingMisbehavior => DisableStrategy::Never,
 		}
 	}
 
@@ -173,6 +185,7 @@ impl<KeyOwnerIdentification> SlashingOffence<KeyOwnerIdentification> {
 		let slash_fraction = match kind {
 			SlashingOffenceKind::ForInvalid => SLASH_FOR_INVALID,
 			SlashingOffenceKind::AgainstValid => SLASH_AGAINST_VALID,
+			SlashingOffenceKind::BackingMisbehavior => SLASH_FOR_BACKING_MISBEHAVIOR,
 		};
 		Self { time_slot, validator_set_count, offenders, slash_fraction, kind }
 	}
@@ -255,6 +268,17 @@ where
 			return
 		}
 
+		// Nothing would ever apply unapplied slashes for backing misbehavior,
+		// as it isn't reported for past sessions.
+		if kind == SlashingOffenceKind::BackingMisbehavior {
+			log::warn!(
+				target: LOG_TARGET,
+				"Dropping backing misbehavior slash for a past session({})",
+				session_index,
+			);
+			return
+		}
+
 		let keys = losers
 			.into_iter()
 			.filter_map(|i| session_info.validators.get(i).cloned().map(|id| (i, id)))
@@ -286,6 +310,15 @@ where
 		Self::do_punish(session_index, candidate_hash, kind, losers);
 	}
 
+	fn punish_backing_misbehavior(
+		session_index: SessionIndex,
+		candidate_hash: CandidateHash,
+		offenders: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		let kind = SlashingOffenceKind::BackingMisbehavior;
+		Self::do_punish(session_index, candidate_hash, kind, offenders);
+	}
+
 	fn initializer_initialize(now: T::BlockNumber) -> Weight {
 		Pallet::<T>::initializer_initialize(now)
 	}
@@ -305,6 +338,8 @@ pub enum SlashingOffenceKind {
 	ForInvalid,
 	#[codec(index = 1)]
 	AgainstValid,
+	#[codec(index = 2)]
+	BackingMisbehavior,
 }
 
 /// We store most of the information about a lost dispute on chain. This struct
@@ -387,6 +422,7 @@ impl<T: Config> HandleReports<T> for () {
 
 pub trait WeightInfo {
 	fn report_dispute_lost(validator_count: ValidatorSetCount) -> Weight;
+	fn report_backing_misbehavior() -> Weight;
 }
 
 pub struct TestWeightInfo;
@@ -394,12 +430,17 @@ impl WeightInfo for TestWeightInfo {
 	fn report_dispute_lost(_validator_count: ValidatorSetCount) -> Weight {
 		Weight::zero()
 	}
+
+	fn report_backing_misbehavior() -> Weight {
+		Weight::zero()
+	}
 }
 
 pub use pallet::*;
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
+	use crate::disputes::SlashingHandler as _;
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 
@@ -456,6 +497,18 @@ pub mod pallet {
 	pub(super) type ValidatorSetCounts<T> =
 		StorageMap<_, Twox64Concat, SessionIndex, ValidatorSetCount>;
 
+	/// Offenders and relay parents of backing misbehavior which has already
+	/// been reported.
+	#[pallet::storage]
+	pub(super) type ReportedBackingMisbehavior<T> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Blake2_128Concat,
+		(ValidatorIndex, Hash),
+		(),
+	>;
+
 	#[pallet::error]
 	pub enum Error<T> {
 		/// The key ownership proof is invalid.
@@ -471,6 +524,11 @@ pub mod pallet {
 		ValidatorIndexIdMismatch,
 		/// The given slashing report is valid but already previously reported.
 		DuplicateSlashingReport,
+		/// The backing misbehavior report is not signed by the reporter.
+		InvalidReportSignature,
+		/// The backing misbehavior report doesn't prove misbehavior of the
+		/// offender.
+		InvalidMisbehaviorProof,
 	}
 
 	#[pallet::call]
@@ -540,6 +598,41 @@ pub mod pallet {
 
 			Ok(Pays::No.into())
 		}
+
+		#[pallet::call_index(1)]
+		#[pallet::weight(<T as Config>::WeightInfo::report_backing_misbehavior())]
+		pub fn report_backing_misbehavior_unsigned(
+			origin: OriginFor<T>,
+			// box to decrease the size of the call
+			report: Box<BackingMisbehaviorReport>,
+			signature: ValidatorSignature,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			// the offenders of past sessions can't be identified without a key
+			// ownership proof, so the slash is applied right away.
+			ensure!(
+				report.session_index == T::ValidatorSet::session_index(),
+				Error::<T>::InvalidSessionIndex,
+			);
+			let candidate_hash =
+				backing::verify_report::<T>(&report, &signature).map_err(report_error::<T>)?;
+
+			let key = (report.offender, report.relay_parent);
+			ensure!(
+				!<ReportedBackingMisbehavior<T>>::contains_key(report.session_index, key),
+				Error::<T>::DuplicateSlashingReport,
+			);
+			<ReportedBackingMisbehavior<T>>::insert(report.session_index, key, ());
+
+			T::SlashingHandler::punish_backing_misbehavior(
+				report.session_index,
+				candidate_hash,
+				[report.offender],
+			);
+
+			Ok(Pays::No.into())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -578,6 +671,51 @@ impl<T: Config> Pallet<T> {
 
 		let old_session = session_index - config.dispute_period - 1;
 		let _ = <UnappliedSlashes<T>>::clear_prefix(old_session, REMOVE_LIMIT, None);
+		let _ = <ReportedBackingMisbehavior<T>>::clear_prefix(old_session, REMOVE_LIMIT, None);
+	}
+
+	/// Submits a signed report of backing misbehavior into the transaction
+	/// pool.
+	///
+	/// This function expects an offchain context and cannot be callable from
+	/// the on-chain logic.
+	pub fn submit_backing_misbehavior_report(
+		report: BackingMisbehaviorReport,
+		signature: ValidatorSignature,
+	) where
+		T: frame_system::offchain::SendTransactionTypes<Call<T>>,
+	{
+		use frame_system::offchain::SubmitTransaction;
+
+		let session_index = report.session_index;
+		let offender = report.offender.0;
+
+		let call =
+			Call::report_backing_misbehavior_unsigned { report: Box::new(report), signature };
+
+		match SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()) {
+			Ok(()) => log::info!(
+				target: LOG_TARGET,
+				"Submitted backing misbehavior report, session({}), index({})",
+				session_index,
+				offender,
+			),
+			Err(()) => log::error!(
+				target: LOG_TARGET,
+				"Error submitting backing misbehavior report, session({}), index({})",
+				session_index,
+				offender,
+			),
+		}
+	}
+}
+
+fn report_error<T: Config>(error: backing::ReportError) -> Error<T> {
+	match error {
+		backing::ReportError::UnknownSession => Error::<T>::InvalidSessionIndex,
+		backing::ReportError::InvalidValidatorIndex => Error::<T>::InvalidValidatorIndex,
+		backing::ReportError::InvalidReporterSignature => Error::<T>::InvalidReportSignature,
+		backing::ReportError::InvalidProof => Error::<T>::InvalidMisbehaviorProof,
 	}
 }
 
@@ -586,6 +724,9 @@ impl<T: Config> Pallet<T> {
 /// It restricts calls to `report_dispute_lost_unsigned` to local calls (i.e.
 /// extrinsics generated on this node) or that already in a block. This
 /// guarantees that only block authors can include unsigned slashing reports.
+///
+/// Calls to `report_backing_misbehavior_unsigned` are signed by the reporting
+/// validator and can be verified by any node, so they are propagated.
 impl<T: Config> Pallet<T> {
 	pub fn validate_unsigned(source: TransactionSource, call: &Call<T>) -> TransactionValidity {
 		if let Call::report_dispute_lost_unsigned { dispute_proof, key_owner_proof } = call {
@@ -610,6 +751,7 @@ impl<T: Config> Pallet<T> {
 			let tag_prefix = match dispute_proof.kind {
 				SlashingOffenceKind::ForInvalid => "DisputeForInvalid",
 				SlashingOffenceKind::AgainstValid => "DisputeAgainstValid",
+				SlashingOffenceKind::BackingMisbehavior => "DisputeBackingMisbehavior",
 			};
 
 			ValidTransaction::with_tag_prefix(tag_prefix)
@@ -621,6 +763,20 @@ impl<T: Config> Pallet<T> {
 				// We don't propagate this. This can never be included on a remote node.
 				.propagate(false)
 				.build()
+		} else if let Call::report_backing_misbehavior_unsigned { report, signature } = call {
+			check_backing_misbehavior_report::<T>(report, signature)?;
+
+			let longevity = <T::HandleReports as HandleReports<T>>::ReportLongevity::get();
+
+			ValidTransaction::with_tag_prefix("BackingMisbehavior")
+				// We assign the maximum priority for any report.
+				.priority(TransactionPriority::max_value())
+				// Only one report for the same offender at the same relay parent.
+				.and_provides((report.session_index, report.offender, report.relay_parent))
+				.longevity(longevity)
+				// Any validator can report, not only block authors.
+				.propagate(true)
+				.build()
 		} else {
 			InvalidTransaction::Call.into()
 		}
@@ -629,12 +785,33 @@ impl<T: Config> Pallet<T> {
 	pub fn pre_dispatch(call: &Call<T>) -> Result<(), TransactionValidityError> {
 		if let Call::report_dispute_lost_unsigned { dispute_proof, key_owner_proof } = call {
 			is_known_offence::<T>(dispute_proof, key_owner_proof)
+		} else if let Call::report_backing_misbehavior_unsigned { report, signature } = call {
+			check_backing_misbehavior_report::<T>(report, signature)
 		} else {
 			Err(InvalidTransaction::Call.into())
 		}
 	}
 }
 
+fn check_backing_misbehavior_report<T: Config>(
+	report: &BackingMisbehaviorReport,
+	signature: &ValidatorSignature,
+) -> Result<(), TransactionValidityError> {
+	if report.session_index != T::ValidatorSet::session_index() {
+		return Err(InvalidTransaction::Stale.into())
+	}
+	backing::verify_report::<T>(report, signature).map_err(|_| InvalidTransaction::BadProof)?;
+
+	// check if the misbehavior has already been reported,
+	// and if so then we can discard the report.
+	let key = (report.offender, report.relay_parent);
+	if <ReportedBackingMisbehavior<T>>::contains_key(report.session_index, key) {
+		Err(InvalidTransaction::Stale.into())
+	} else {
+		Ok(())
+	}
+}
+
 fn is_known_offence<T: Config>(
 	dispute_proof: &DisputeProof,
 	key_owner_proof: &T::KeyOwnerProof,
@@ -0,0 +1,209 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of backing misbehavior reports.
+//!
+//! Validators detect misbehavior of other validators while importing backing statements. A
+//! report of it is only accepted if the misbehavior can be proven with the signatures of the
+//! offender, checked against the validator set of the session the statements were made in.
+
+use crate::session_info;
+use primitives::{
+	vstaging::{BackingMisbehavior, BackingMisbehaviorReport},
+	CandidateHash, CompactStatement, SigningContext, ValidatorSignature,
+};
+use sp_runtime::traits::{AppVerify, BlakeTwo256, Hash as HashT};
+
+/// Domain separator of the hashes identifying backing misbehavior offences.
+const OFFENCE_MAGIC: [u8; 4] = *b"BKMO";
+
+/// The reasons a backing misbehavior report can be rejected for.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ReportError {
+	/// There is no session info for the session of the report.
+	UnknownSession,
+	/// The offender or the reporter are not validators in the session.
+	InvalidValidatorIndex,
+	/// The report isn't signed by the reporter.
+	InvalidReporterSignature,
+	/// The statements don't prove misbehavior of the offender.
+	InvalidProof,
+}
+
+/// Verify a signed backing misbehavior report.
+///
+/// Returns the hash identifying the offence, which is the same for both orders the conflicting
+/// statements can be reported in. It differs from the hashes of the candidates, so that the
+/// offence is kept apart from the slashes of lost disputes about either candidate.
+pub(crate) fn verify_report<T: session_info::Config>(
+	report: &BackingMisbehaviorReport,
+	signature: &ValidatorSignature,
+) -> Result<CandidateHash, ReportError> {
+	let session_info = <session_info::Pallet<T>>::session_info(report.session_index)
+		.ok_or(ReportError::UnknownSession)?;
+
+	let reporter = session_info
+		.validators
+		.get(report.reporter)
+		.ok_or(ReportError::InvalidValidatorIndex)?;
+	let offender = session_info
+		.validators
+		.get(report.offender)
+		.ok_or(ReportError::InvalidValidatorIndex)?;
+
+	if !signature.verify(&report.signing_payload()[..], reporter) {
+		return Err(ReportError::InvalidReporterSignature)
+	}
+
+	let context =
+		SigningContext { session_index: report.session_index, parent_hash: report.relay_parent };
+
+	match &report.misbehavior {
+		BackingMisbehavior::MultipleCandidates { first, second } => {
+			if first.0 == second.0 {
+				return Err(ReportError::InvalidProof)
+			}
+
+			for (candidate_hash, signature) in [first, second] {
+				let payload = CompactStatement::Seconded(*candidate_hash).signing_payload(&context);
+				if !signature.verify(&payload[..], offender) {
+					return Err(ReportError::InvalidProof)
+				}
+			}
+
+			let (first, second) =
+				if first.0 < second.0 { (first.0, second.0) } else { (second.0, first.0) };
+			Ok(CandidateHash(BlakeTwo256::hash_of(&(OFFENCE_MAGIC, first, second))))
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, MockGenesisConfig, Test};
+	use primitives::{Hash, SessionInfo, ValidatorId, ValidatorIndex, ValidatorPair};
+	use sp_core::{crypto::CryptoType, Pair};
+
+	const SESSION: primitives::SessionIndex = 3;
+
+	fn validator_pair() -> ValidatorPair {
+		<ValidatorId as CryptoType>::Pair::generate().0
+	}
+
+	fn insert_session(validators: &[&ValidatorPair]) {
+		let session_info = SessionInfo {
+			validators: validators.iter().map(|v| v.public()).collect(),
+			discovery_keys: Vec::new(),
+			assignment_keys: Vec::new(),
+			validator_groups: Default::default(),
+			n_cores: 0,
+			zeroth_delay_tranche_width: 0,
+			relay_vrf_modulo_samples: 0,
+			n_delay_tranches: 0,
+			no_show_slots: 0,
+			needed_approvals: 0,
+			active_validator_indices: Vec::new(),
+			dispute_period: 6,
+			random_seed: [0; 32],
+		};
+		session_info::Sessions::<Test>::insert(SESSION, session_info);
+	}
+
+	fn seconded(
+		offender: &ValidatorPair,
+		relay_parent: Hash,
+		candidate_hash: CandidateHash,
+	) -> (CandidateHash, ValidatorSignature) {
+		let context = SigningContext { session_index: SESSION, parent_hash: relay_parent };
+		let payload = CompactStatement::Seconded(candidate_hash).signing_payload(&context);
+		(candidate_hash, offender.sign(&payload))
+	}
+
+	fn report(
+		offender: &ValidatorPair,
+		first: CandidateHash,
+		second: CandidateHash,
+	) -> BackingMisbehaviorReport {
+		let relay_parent = Hash::repeat_byte(42);
+		BackingMisbehaviorReport {
+			session_index: SESSION,
+			relay_parent,
+			offender: ValidatorIndex(0),
+			misbehavior: BackingMisbehavior::MultipleCandidates {
+				first: seconded(offender, relay_parent, first),
+				second: seconded(offender, relay_parent, second),
+			},
+			reporter: ValidatorIndex(1),
+		}
+	}
+
+	#[test]
+	fn accepts_multiple_seconded_candidates() {
+		new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+			let (offender, reporter) = (validator_pair(), validator_pair());
+			insert_session(&[&offender, &reporter]);
+
+			let a = CandidateHash(Hash::repeat_byte(1));
+			let b = CandidateHash(Hash::repeat_byte(2));
+
+			let offences = [report(&offender, a, b), report(&offender, b, a)].map(|report| {
+				let signature = reporter.sign(&report.signing_payload());
+				verify_report::<Test>(&report, &signature).unwrap()
+			});
+			assert_eq!(offences[0], offences[1]);
+			assert!(offences[0] != a && offences[0] != b);
+		});
+	}
+
+	#[test]
+	fn rejects_invalid_reports() {
+		new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+			let (offender, reporter) = (validator_pair(), validator_pair());
+			insert_session(&[&offender, &reporter]);
+
+			let a = CandidateHash(Hash::repeat_byte(1));
+			let b = CandidateHash(Hash::repeat_byte(2));
+
+			// Not signed by the reporter.
+			let valid = report(&offender, a, b);
+			let signature = offender.sign(&valid.signing_payload());
+			assert_eq!(
+				verify_report::<Test>(&valid, &signature),
+				Err(ReportError::InvalidReporterSignature),
+			);
+
+			// The same candidate twice.
+			let same = report(&offender, a, a);
+			let signature = reporter.sign(&same.signing_payload());
+			assert_eq!(verify_report::<Test>(&same, &signature), Err(ReportError::InvalidProof));
+
+			// Statements not signed by the offender.
+			let forged = report(&reporter, a, b);
+			let signature = reporter.sign(&forged.signing_payload());
+			assert_eq!(verify_report::<Test>(&forged, &signature), Err(ReportError::InvalidProof));
+
+			// Unknown session.
+			let mut unknown = report(&offender, a, b);
+			unknown.session_index = SESSION + 1;
+			let signature = reporter.sign(&unknown.signing_payload());
+			assert_eq!(
+				verify_report::<Test>(&unknown, &signature),
+				Err(ReportError::UnknownSession),
+			);
+		});
+	}
+}
@@ -21,7 +21,10 @@ use frame_benchmarking::{benchmarks, whitelist_account};
 use frame_support::traits::{OnFinalize, OnInitialize};
 use frame_system::RawOrigin;
 use pallet_staking::testing_utils::create_validators;
-use primitives::{Hash, PARACHAIN_KEY_TYPE_ID};
+use primitives::{
+	vstaging::BackingMisbehavior, CompactStatement, Hash, SigningContext, PARACHAIN_KEY_TYPE_ID,
+};
+use sp_application_crypto::RuntimeAppPublic;
 use sp_runtime::traits::{One, StaticLookup};
 use sp_session::MembershipProof;
 
@@ -129,6 +132,46 @@ fn dispute_proof(
 	DisputeProof { time_slot, kind, validator_index, validator_id }
 }
 
+fn setup_backing_misbehavior<T>(
+	session_index: SessionIndex,
+) -> (BackingMisbehaviorReport, ValidatorSignature)
+where
+	T: Config,
+{
+	// The session keys of the validator set can't sign, so replace the
+	// first two validators with keys we can sign with.
+	let offender = <ValidatorId as RuntimeAppPublic>::generate_pair(None);
+	let reporter = <ValidatorId as RuntimeAppPublic>::generate_pair(None);
+
+	let mut session_info = crate::session_info::Pallet::<T>::session_info(session_index).unwrap();
+	let mut validators = session_info.validators.to_vec();
+	validators[0] = offender.clone();
+	validators[1] = reporter.clone();
+	session_info.validators = validators.into();
+	crate::session_info::Sessions::<T>::insert(session_index, session_info);
+
+	let relay_parent = Hash::repeat_byte(1);
+	let context = SigningContext { session_index, parent_hash: relay_parent };
+	let seconded = |candidate_hash: CandidateHash| {
+		let payload = CompactStatement::Seconded(candidate_hash).signing_payload(&context);
+		(candidate_hash, offender.sign(&payload).unwrap())
+	};
+
+	let report = BackingMisbehaviorReport {
+		session_index,
+		relay_parent,
+		offender: ValidatorIndex(0),
+		misbehavior: BackingMisbehavior::MultipleCandidates {
+			first: seconded(CANDIDATE_HASH),
+			second: seconded(CandidateHash(Hash::repeat_byte(2))),
+		},
+		reporter: ValidatorIndex(1),
+	};
+	let signature = reporter.sign(&report.signing_payload()).unwrap();
+
+	(report, signature)
+}
+
 benchmarks! {
 	where_clause {
 		where T: Config<KeyOwnerProof = MembershipProof>,
@@ -153,4 +196,26 @@ benchmarks! {
 		let unapplied = <UnappliedSlashes<T>>::get(session_index, CANDIDATE_HASH);
 		assert!(unapplied.is_none());
 	}
+
+	// in this setup the misbehavior is reported for the current session,
+	// so the offence is reported right away
+	report_backing_misbehavior {
+		let n = <<T as super::Config>::BenchmarkingConfig as BenchmarkingConfiguration>::MAX_VALIDATORS;
+
+		let origin = RawOrigin::None.into();
+		let _ = setup_validator_set::<T>(n);
+		let session_index = T::ValidatorSet::session_index();
+		let (report, signature) = setup_backing_misbehavior::<T>(session_index);
+		let key = (report.offender, report.relay_parent);
+	}: {
+		let result = Pallet::<T>::report_backing_misbehavior_unsigned(
+			origin,
+			Box::new(report),
+			signature,
+		);
+		assert!(result.is_ok());
+	} verify {
+		assert!(<ReportedBackingMisbehavior<T>>::contains_key(session_index, key));
+		assert!(<UnappliedSlashes<T>>::iter_prefix(session_index).next().is_none());
+	}
 }
@@ -1593,6 +1593,52 @@ fn test_check_signature() {
 	.is_err());
 }
 
+#[test]
+fn test_check_signature_multiple_candidates() {
+	let validator_id = <ValidatorId as CryptoType>::Pair::generate().0;
+
+	let session = 0;
+	let candidate_hash = CandidateHash(sp_core::H256::repeat_byte(1));
+	let other_candidate_hash = CandidateHash(sp_core::H256::repeat_byte(2));
+	let unrelated_candidate_hash = CandidateHash(sp_core::H256::repeat_byte(3));
+	let candidate_hashes = vec![candidate_hash, other_candidate_hash];
+
+	let statement = DisputeStatement::Valid(
+		ValidDisputeStatementKind::ApprovalCheckingMultipleCandidates(candidate_hashes.clone()),
+	);
+	let signed = validator_id
+		.sign(&ApprovalVoteMultipleCandidates(&candidate_hashes).signing_payload(session));
+
+	assert!(check_signature(&validator_id.public(), candidate_hash, session, &statement, &signed)
+		.is_ok());
+	assert!(check_signature(
+		&validator_id.public(),
+		other_candidate_hash,
+		session,
+		&statement,
+		&signed
+	)
+	.is_ok());
+	// The candidate must be one of those covered by the signature.
+	assert!(check_signature(
+		&validator_id.public(),
+		unrelated_candidate_hash,
+		session,
+		&statement,
+		&signed
+	)
+	.is_err());
+	// A coalesced signature is not a valid single approval.
+	assert!(check_signature(
+		&validator_id.public(),
+		candidate_hash,
+		session,
+		&DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking),
+		&signed
+	)
+	.is_err());
+}
+
 #[test]
 fn deduplication_and_sorting_works() {
 	new_test_ext(Default::default()).execute_with(|| {
@@ -19,33 +19,67 @@ use crate::{
 	initializer,
 };
 use frame_support::pallet_prelude::*;
-use primitives::{DownwardMessage, Hash, Id as ParaId, InboundDownwardMessage};
-use sp_runtime::traits::{BlakeTwo256, Hash as HashT, SaturatedConversion};
+use primitives::{
+	vstaging::QueueFootprint, DownwardMessage, Hash, Id as ParaId, InboundDownwardMessage,
+};
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT, SaturatedConversion, Saturating};
 use sp_std::{fmt, prelude::*};
 use xcm::latest::SendError;
 
 pub use pallet::*;
 
+pub mod migration;
+
 #[cfg(test)]
 mod tests;
 
+/// The maximum number of messages which may be pending in the downward message queue of a para.
 pub const MAX_MESSAGE_QUEUE_SIZE: usize = 1024;
 
+/// The maximum total size in bytes of the messages which may be pending in the downward message
+/// queue of a para.
+pub const MAX_MESSAGE_QUEUE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The maximum number of messages stored in a single page of a downward message queue.
+pub const QUEUE_PAGE_CAPACITY: u32 = 32;
+
+/// The index of a page of a downward message queue.
+pub type PageIndex = u32;
+
 /// An error sending a downward message.
 #[cfg_attr(test, derive(Debug))]
 pub enum QueueDownwardMessageError {
 	/// The message being sent exceeds the configured max message size.
 	ExceedsMaxMessageSize,
+	/// The downward message queue of the recipient can't take any more messages until the
+	/// recipient processes some of them.
+	QueueFull,
 }
 
 impl From<QueueDownwardMessageError> for SendError {
 	fn from(err: QueueDownwardMessageError) -> Self {
 		match err {
 			QueueDownwardMessageError::ExceedsMaxMessageSize => SendError::ExceedsMaxMessageSize,
+			QueueDownwardMessageError::QueueFull =>
+				SendError::Transport("The downward message queue of the recipient is full"),
 		}
 	}
 }
 
+/// The state of the downward message queue of a para.
+///
+/// The messages are kept in the pages `begin..end`, oldest first. All pages but the last one are
+/// full, except for the first one, which loses its messages from the front as they are processed.
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct QueueState {
+	/// The index of the first page of the queue.
+	pub begin: PageIndex,
+	/// One past the index of the last page of the queue.
+	pub end: PageIndex,
+	/// The number and total size of the messages in the queue.
+	pub footprint: QueueFootprint,
+}
+
 /// An error returned by [`check_processed_downward_messages`] that indicates an acceptance check
 /// didn't pass.
 pub enum ProcessedDownwardMessagesAcceptanceErr {
@@ -76,22 +110,37 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config + configuration::Config {}
 
-	/// The downward messages addressed for a certain para.
+	/// The pages of the downward message queues, holding the messages addressed for a certain para.
+	///
+	/// Invariant:
+	/// - There is a page for exactly the indices `begin..end` of the `DownwardMessageQueueStates`
+	///   of a para, and none of them is empty.
 	#[pallet::storage]
-	pub(crate) type DownwardMessageQueues<T: Config> = StorageMap<
+	pub(crate) type DownwardMessageQueuePages<T: Config> = StorageDoubleMap<
 		_,
 		Twox64Concat,
 		ParaId,
+		Twox64Concat,
+		PageIndex,
 		Vec<InboundDownwardMessage<T::BlockNumber>>,
 		ValueQuery,
 	>;
 
+	/// The state of the downward message queue of a certain para.
+	///
+	/// Invariant:
+	/// - There is an entry for exactly the paras which have messages in their queue.
+	#[pallet::storage]
+	pub(crate) type DownwardMessageQueueStates<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, QueueState, ValueQuery>;
+
 	/// A mapping that stores the downward message queue MQC head for each para.
 	///
 	/// Each link in this chain has a form:
@@ -135,7 +184,9 @@ impl<T: Config> Pallet<T> {
 
 	/// Remove all relevant storage items for an outgoing parachain.
 	fn clean_dmp_after_outgoing(outgoing_para: &ParaId) {
-		<Self as Store>::DownwardMessageQueues::remove(outgoing_para);
+		let _ =
+			<Self as Store>::DownwardMessageQueuePages::clear_prefix(outgoing_para, u32::MAX, None);
+		<Self as Store>::DownwardMessageQueueStates::remove(outgoing_para);
 		<Self as Store>::DownwardMessageQueueHeads::remove(outgoing_para);
 	}
 
@@ -152,10 +203,11 @@ impl<T: Config> Pallet<T> {
 			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
 		}
 
-		if <Self as Store>::DownwardMessageQueues::decode_len(para).unwrap_or(0) >
-			MAX_MESSAGE_QUEUE_SIZE
+		let footprint = Self::dmq_footprint(*para);
+		if footprint.count >= MAX_MESSAGE_QUEUE_SIZE as u64 ||
+			footprint.size.saturating_add(serialized_len as u64) > MAX_MESSAGE_QUEUE_BYTES
 		{
-			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
+			return Err(QueueDownwardMessageError::QueueFull)
 		}
 
 		Ok(())
@@ -174,16 +226,7 @@ impl<T: Config> Pallet<T> {
 		para: ParaId,
 		msg: DownwardMessage,
 	) -> Result<(), QueueDownwardMessageError> {
-		let serialized_len = msg.len() as u32;
-		if serialized_len > config.max_downward_message_size {
-			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
-		}
-
-		if <Self as Store>::DownwardMessageQueues::decode_len(para).unwrap_or(0) >
-			MAX_MESSAGE_QUEUE_SIZE
-		{
-			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
-		}
+		Self::can_queue_downward_message(config, &para, &msg)?;
 
 		let inbound =
 			InboundDownwardMessage { msg, sent_at: <frame_system::Pallet<T>>::block_number() };
@@ -195,8 +238,23 @@ impl<T: Config> Pallet<T> {
 			*head = new_head;
 		});
 
-		<Self as Store>::DownwardMessageQueues::mutate(para, |v| {
-			v.push(inbound);
+		<Self as Store>::DownwardMessageQueueStates::mutate(para, |state| {
+			state.footprint.count.saturating_inc();
+			state.footprint.size.saturating_accrue(inbound.msg.len() as u64);
+
+			// The last page is appended to as long as it has room, without decoding it.
+			let last_page_len = if state.begin < state.end {
+				<Self as Store>::DownwardMessageQueuePages::decode_len(para, state.end - 1)
+					.unwrap_or(0)
+			} else {
+				QUEUE_PAGE_CAPACITY as usize
+			};
+			if last_page_len < QUEUE_PAGE_CAPACITY as usize {
+				<Self as Store>::DownwardMessageQueuePages::append(para, state.end - 1, inbound);
+			} else {
+				<Self as Store>::DownwardMessageQueuePages::insert(para, state.end, vec![inbound]);
+				state.end.saturating_inc();
+			}
 		});
 
 		Ok(())
@@ -223,18 +281,41 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Prunes the specified number of messages from the downward message queue of the given para.
+	///
+	/// Only the pages holding the pruned messages are touched.
 	pub(crate) fn prune_dmq(para: ParaId, processed_downward_messages: u32) -> Weight {
-		<Self as Store>::DownwardMessageQueues::mutate(para, |q| {
-			let processed_downward_messages = processed_downward_messages as usize;
-			if processed_downward_messages > q.len() {
-				// reaching this branch is unexpected due to the constraint established by
-				// `check_processed_downward_messages`. But better be safe than sorry.
-				q.clear();
+		let mut state = <Self as Store>::DownwardMessageQueueStates::get(para);
+		let mut remaining = processed_downward_messages as usize;
+		let mut pages = 0u64;
+
+		// pruning more messages than there are in the queue is unexpected due to the constraint
+		// established by `check_processed_downward_messages`, and simply empties the queue.
+		while remaining > 0 && state.begin < state.end {
+			let mut page = <Self as Store>::DownwardMessageQueuePages::get(para, state.begin);
+			pages += 1;
+
+			let pruned = remaining.min(page.len());
+			for inbound in page.drain(..pruned) {
+				state.footprint.count.saturating_dec();
+				state.footprint.size.saturating_reduce(inbound.msg.len() as u64);
+			}
+			remaining -= pruned;
+
+			if page.is_empty() {
+				<Self as Store>::DownwardMessageQueuePages::remove(para, state.begin);
+				state.begin.saturating_inc();
 			} else {
-				*q = q.split_off(processed_downward_messages);
+				<Self as Store>::DownwardMessageQueuePages::insert(para, state.begin, page);
 			}
-		});
-		T::DbWeight::get().reads_writes(1, 1)
+		}
+
+		if state.begin < state.end {
+			<Self as Store>::DownwardMessageQueueStates::insert(para, state);
+		} else {
+			<Self as Store>::DownwardMessageQueueStates::remove(para);
+		}
+
+		T::DbWeight::get().reads_writes(1 + pages, 1 + pages)
 	}
 
 	/// Returns the Head of Message Queue Chain for the given para or `None` if there is none
@@ -248,15 +329,24 @@ impl<T: Config> Pallet<T> {
 	///
 	/// Returns 0 if the para doesn't have an associated downward message queue.
 	pub(crate) fn dmq_length(para: ParaId) -> u32 {
-		<Self as Store>::DownwardMessageQueues::decode_len(&para)
-			.unwrap_or(0)
-			.saturated_into::<u32>()
+		Self::dmq_footprint(para).count.saturated_into::<u32>()
+	}
+
+	/// Returns the number and total size of the pending downward messages addressed to the given
+	/// para.
+	///
+	/// Senders can use this to hold back messages before the queue of the para fills up.
+	pub fn dmq_footprint(para: ParaId) -> QueueFootprint {
+		<Self as Store>::DownwardMessageQueueStates::get(&para).footprint
 	}
 
 	/// Returns the downward message queue contents for the given para.
 	///
 	/// The most recent messages are the latest in the vector.
 	pub(crate) fn dmq_contents(recipient: ParaId) -> Vec<InboundDownwardMessage<T::BlockNumber>> {
-		<Self as Store>::DownwardMessageQueues::get(&recipient)
+		let state = <Self as Store>::DownwardMessageQueueStates::get(&recipient);
+		(state.begin..state.end)
+			.flat_map(|page| <Self as Store>::DownwardMessageQueuePages::get(&recipient, page))
+			.collect()
 	}
 }
@@ -0,0 +1,124 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::dmp::{
+	Config, DownwardMessageQueuePages, DownwardMessageQueueStates, Pallet, QueueState,
+	QUEUE_PAGE_CAPACITY,
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+	use frame_support::storage_alias;
+	use primitives::{vstaging::QueueFootprint, Id as ParaId, InboundDownwardMessage};
+	use sp_std::prelude::*;
+
+	#[storage_alias]
+	type DownwardMessageQueues<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		ParaId,
+		Vec<InboundDownwardMessage<<T as frame_system::Config>::BlockNumber>>,
+		ValueQuery,
+	>;
+
+	/// Splits the downward message queue of every para into pages.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let mut weight = T::DbWeight::get().reads(1);
+
+				let (mut queues, mut pages) = (0u64, 0u64);
+				for (para, queue) in DownwardMessageQueues::<T>::drain() {
+					let mut state = QueueState {
+						begin: 0,
+						end: 0,
+						footprint: QueueFootprint {
+							count: queue.len() as u64,
+							size: queue.iter().map(|inbound| inbound.msg.len() as u64).sum(),
+						},
+					};
+					for page in queue.chunks(QUEUE_PAGE_CAPACITY as usize) {
+						DownwardMessageQueuePages::<T>::insert(para, state.end, page.to_vec());
+						state.end += 1;
+					}
+					pages += state.end as u64;
+					queues += 1;
+
+					if state.begin < state.end {
+						DownwardMessageQueueStates::<T>::insert(para, state);
+					}
+				}
+				log::info!("Split {} downward message queues into {} pages", queues, pages);
+				weight
+					.saturating_accrue(T::DbWeight::get().reads_writes(queues, queues * 2 + pages));
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				weight.saturating_add(T::DbWeight::get().writes(1))
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == 0,
+				"Storage version should be `0` before the migration",
+			);
+			let queues = DownwardMessageQueues::<T>::iter().collect::<Vec<_>>();
+			Ok(queues.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be `1` after the migration"
+			);
+			ensure!(
+				DownwardMessageQueues::<T>::iter().next().is_none(),
+				"DownwardMessageQueues should be empty after the migration"
+			);
+
+			let queues = Vec::<(
+				ParaId,
+				Vec<InboundDownwardMessage<<T as frame_system::Config>::BlockNumber>>,
+			)>::decode(&mut &state[..])
+			.map_err(|_| "the state of `pre_upgrade` decodes")?;
+			for (para, queue) in queues {
+				ensure!(
+					Pallet::<T>::dmq_contents(para) == queue,
+					"The downward messages of every para should be kept in order"
+				);
+				ensure!(
+					Pallet::<T>::dmq_footprint(para).count == queue.len() as u64,
+					"The footprint of every queue should match its messages"
+				);
+			}
+			Ok(())
+		}
+	}
+}
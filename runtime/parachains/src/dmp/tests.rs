@@ -73,9 +73,11 @@ fn clean_dmp_works() {
 		let outgoing_paras = vec![a, b];
 		Dmp::initializer_on_new_session(&notification, &outgoing_paras);
 
-		assert!(<Dmp as Store>::DownwardMessageQueues::get(&a).is_empty());
-		assert!(<Dmp as Store>::DownwardMessageQueues::get(&b).is_empty());
-		assert!(!<Dmp as Store>::DownwardMessageQueues::get(&c).is_empty());
+		assert!(Dmp::dmq_contents(a).is_empty());
+		assert!(Dmp::dmq_contents(b).is_empty());
+		assert!(!Dmp::dmq_contents(c).is_empty());
+		assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 0);
+		assert!(!<Dmp as Store>::DownwardMessageQueueStates::contains_key(&a));
 	});
 }
 
@@ -160,6 +162,111 @@ fn dmq_pruning() {
 	});
 }
 
+#[test]
+fn dmq_pruning_across_pages() {
+	let a = ParaId::from(1312);
+	let capacity = QUEUE_PAGE_CAPACITY;
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		for i in 0..(2 * capacity + 1) {
+			queue_downward_message(a, i.encode()).unwrap();
+		}
+		let state = <Dmp as Store>::DownwardMessageQueueStates::get(&a);
+		assert_eq!((state.begin, state.end), (0, 3));
+		assert_eq!(
+			state.footprint,
+			QueueFootprint { count: 2 * capacity as u64 + 1, size: 4 * (2 * capacity as u64 + 1) }
+		);
+
+		// Pruning part of the first page keeps the rest of it.
+		Dmp::prune_dmq(a, 1);
+		let state = <Dmp as Store>::DownwardMessageQueueStates::get(&a);
+		assert_eq!((state.begin, state.end), (0, 3));
+		assert_eq!(
+			<Dmp as Store>::DownwardMessageQueuePages::get(&a, 0).len(),
+			capacity as usize - 1
+		);
+
+		// Pruning across a page boundary removes the emptied page.
+		Dmp::prune_dmq(a, capacity);
+		let state = <Dmp as Store>::DownwardMessageQueueStates::get(&a);
+		assert_eq!((state.begin, state.end), (1, 3));
+		assert!(!<Dmp as Store>::DownwardMessageQueuePages::contains_key(&a, 0));
+		assert_eq!(Dmp::dmq_length(a), capacity);
+		assert_eq!(
+			Dmp::dmq_contents(a).into_iter().map(|inbound| inbound.msg).collect::<Vec<_>>(),
+			((capacity + 1)..(2 * capacity + 1)).map(|i| i.encode()).collect::<Vec<_>>(),
+		);
+
+		// New messages go to the last page while it has room.
+		queue_downward_message(a, vec![1]).unwrap();
+		assert_eq!(<Dmp as Store>::DownwardMessageQueueStates::get(&a).end, 3);
+		assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::get(&a, 2).len(), 2);
+
+		// Pruning everything removes the queue.
+		Dmp::prune_dmq(a, capacity + 1);
+		assert!(!<Dmp as Store>::DownwardMessageQueueStates::contains_key(&a));
+		assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 0);
+		assert_eq!(Dmp::dmq_footprint(a), QueueFootprint::default());
+	});
+}
+
+#[test]
+fn queue_is_full_when_exceeding_message_count() {
+	let a = ParaId::from(1312);
+	let b = ParaId::from(228);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		for _ in 0..MAX_MESSAGE_QUEUE_SIZE {
+			queue_downward_message(a, vec![1, 2, 3]).unwrap();
+		}
+		assert!(matches!(
+			Dmp::can_queue_downward_message(&Configuration::config(), &a, &vec![1, 2, 3]),
+			Err(QueueDownwardMessageError::QueueFull)
+		));
+		assert!(matches!(
+			queue_downward_message(a, vec![1, 2, 3]),
+			Err(QueueDownwardMessageError::QueueFull)
+		));
+		assert_eq!(Dmp::dmq_length(a), MAX_MESSAGE_QUEUE_SIZE as u32);
+
+		// Other paras are not affected.
+		assert!(queue_downward_message(b, vec![1, 2, 3]).is_ok());
+
+		// Processing messages makes room again.
+		Dmp::prune_dmq(a, 1);
+		assert!(queue_downward_message(a, vec![1, 2, 3]).is_ok());
+	});
+}
+
+#[test]
+fn queue_is_full_when_exceeding_total_size() {
+	let a = ParaId::from(1312);
+	let max_message_size = (MAX_MESSAGE_QUEUE_BYTES / 4) as u32;
+
+	let mut genesis = default_genesis_config();
+	genesis.configuration.config.max_downward_message_size = max_message_size;
+
+	new_test_ext(genesis).execute_with(|| {
+		let large = vec![0; max_message_size as usize];
+		for _ in 0..4 {
+			queue_downward_message(a, large.clone()).unwrap();
+		}
+		assert_eq!(
+			Dmp::dmq_footprint(a),
+			QueueFootprint { count: 4, size: MAX_MESSAGE_QUEUE_BYTES }
+		);
+
+		assert!(matches!(
+			queue_downward_message(a, vec![1]),
+			Err(QueueDownwardMessageError::QueueFull)
+		));
+
+		Dmp::prune_dmq(a, 1);
+		assert!(queue_downward_message(a, vec![1]).is_ok());
+	});
+}
+
 #[test]
 fn queue_downward_message_critical() {
 	let a = ParaId::from(1312);
@@ -22,8 +22,9 @@ use frame_support::{pallet_prelude::*, traits::ReservableCurrency};
 use frame_system::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
 use primitives::{
-	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
-	SessionIndex,
+	vstaging::{HrmpChannelStatus, HrmpChannelSummary},
+	Balance, DownwardMessage, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, IsSystem,
+	OutboundHrmpMessage, SessionIndex,
 };
 use scale_info::TypeInfo;
 use sp_runtime::traits::{AccountIdConversion, BlakeTwo256, Hash as HashT, UniqueSaturatedInto};
@@ -35,6 +36,8 @@ use sp_std::{
 
 pub use pallet::*;
 
+pub mod migration;
+
 /// Maximum bound that can be set for inbound channels.
 ///
 /// If inaccurate, the weighing of this pallet might become inaccurate. It is expected form the
@@ -59,6 +62,9 @@ pub trait WeightInfo {
 	fn hrmp_cancel_open_request(c: u32) -> Weight;
 	fn clean_open_channel_requests(c: u32) -> Weight;
 	fn force_open_hrmp_channel() -> Weight;
+	fn hrmp_establish_bidirectional_channel() -> Weight;
+	fn hrmp_set_channel_pre_approval() -> Weight;
+	fn force_set_auto_accept_policy() -> Weight;
 }
 
 /// A weight info that is only suitable for testing.
@@ -92,6 +98,15 @@ impl WeightInfo for TestWeightInfo {
 	fn force_open_hrmp_channel() -> Weight {
 		Weight::MAX
 	}
+	fn hrmp_establish_bidirectional_channel() -> Weight {
+		Weight::MAX
+	}
+	fn hrmp_set_channel_pre_approval() -> Weight {
+		Weight::MAX
+	}
+	fn force_set_auto_accept_policy() -> Weight {
+		Weight::MAX
+	}
 }
 
 /// A description of a request to open an HRMP channel.
@@ -110,6 +125,9 @@ pub struct HrmpOpenChannelRequest {
 	pub max_capacity: u32,
 	/// The maximum total size of the messages that can be pending in the channel at once.
 	pub max_total_size: u32,
+	/// The amount that the recipient supplied at the time of accepting this request. Zero while
+	/// the request is not confirmed.
+	pub recipient_deposit: Balance,
 }
 
 /// A metadata of an HRMP channel.
@@ -148,6 +166,25 @@ pub struct HrmpChannel {
 	pub recipient_deposit: Balance,
 }
 
+/// The policy deciding which paras may establish channels between each other in one step via
+/// [`hrmp_establish_bidirectional_channel`](Pallet::hrmp_establish_bidirectional_channel).
+///
+/// Channels between two system parachains can always be established this way.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum ChannelAutoAcceptPolicy {
+	/// Only channels between two system parachains are accepted automatically.
+	SystemOnly,
+	/// Additionally, channels between any two paras that pre-approved each other are accepted
+	/// automatically.
+	MutualPreApproval,
+}
+
+impl Default for ChannelAutoAcceptPolicy {
+	fn default() -> Self {
+		Self::SystemOnly
+	}
+}
+
 /// An error returned by [`check_hrmp_watermark`] that indicates an acceptance criteria check
 /// didn't pass.
 pub enum HrmpWatermarkAcceptanceErr<BlockNumber> {
@@ -236,6 +273,7 @@ pub mod pallet {
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -276,6 +314,14 @@ pub mod pallet {
 		/// An HRMP channel was opened via Root origin.
 		/// `[sender, recipient, proposed_max_capacity, proposed_max_message_size]`
 		HrmpChannelForceOpened(ParaId, ParaId, u32, u32),
+		/// Channels in both directions between two paras were requested and accepted at once.
+		/// `[initiator, counterparty, max_capacity, max_message_size]`
+		BidirectionalChannelRequested(ParaId, ParaId, u32, u32),
+		/// A para changed whether it pre-approves channels with the counterparty.
+		/// `[approver, counterparty, approved]`
+		ChannelPreApprovalSet(ParaId, ParaId, bool),
+		/// The policy for establishing channels in one step was changed. `[policy]`
+		AutoAcceptPolicySet(ChannelAutoAcceptPolicy),
 	}
 
 	#[pallet::error]
@@ -318,6 +364,15 @@ pub mod pallet {
 		OpenHrmpChannelAlreadyConfirmed,
 		/// The provided witness data is wrong.
 		WrongWitness,
+		/// The current auto-accept policy doesn't allow the paras to establish a channel in one
+		/// step.
+		EstablishChannelNotAllowed,
+		/// The paras haven't pre-approved channels with each other.
+		EstablishChannelNotPreApproved,
+		/// The notification to the counterparty exceeds the maximum downward message size.
+		NotificationTooLarge,
+		/// The downward message queue of the counterparty is full, so it can't be notified.
+		NotificationQueueFull,
 	}
 
 	/// The set of pending HRMP open channel requests.
@@ -421,6 +476,17 @@ pub mod pallet {
 	pub type HrmpChannelDigests<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, Vec<(T::BlockNumber, Vec<ParaId>)>, ValueQuery>;
 
+	/// The counterparties each para pre-approved channels with, keyed by the approver first.
+	///
+	/// Only taken into account under [`ChannelAutoAcceptPolicy::MutualPreApproval`].
+	#[pallet::storage]
+	pub type HrmpChannelPreApprovals<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, ParaId, Twox64Concat, ParaId, ()>;
+
+	/// The policy deciding which paras may establish channels in one step. Set by governance.
+	#[pallet::storage]
+	pub type HrmpAutoAcceptPolicy<T: Config> = StorageValue<_, ChannelAutoAcceptPolicy, ValueQuery>;
+
 	/// Preopen the given HRMP channels.
 	///
 	/// The values in the tuple corresponds to
@@ -547,8 +613,7 @@ pub mod pallet {
 		#[pallet::weight(<T as Config>::WeightInfo::force_process_hrmp_open(*_channels))]
 		pub fn force_process_hrmp_open(origin: OriginFor<T>, _channels: u32) -> DispatchResult {
 			ensure_root(origin)?;
-			let host_config = configuration::Pallet::<T>::config();
-			Self::process_hrmp_open_channel_requests(&host_config);
+			Self::process_hrmp_open_channel_requests();
 			Ok(())
 		}
 
@@ -618,12 +683,84 @@ pub mod pallet {
 			));
 			Ok(())
 		}
+
+		/// Establish channels in both directions between the origin parachain and the
+		/// `counterparty` in one step, using the maximum capacity and message size allowed by the
+		/// relay-chain configuration.
+		///
+		/// Both channels are requested and accepted at once and are opened on the next session
+		/// change. Deposits are reserved from the sovereign accounts of both paras as usual,
+		/// except that system parachains are not charged any.
+		///
+		/// This is allowed between two system parachains, and, if the auto-accept policy is
+		/// [`ChannelAutoAcceptPolicy::MutualPreApproval`], between any two paras that
+		/// pre-approved each other.
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_establish_bidirectional_channel())]
+		pub fn hrmp_establish_bidirectional_channel(
+			origin: OriginFor<T>,
+			counterparty: ParaId,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			Self::ensure_can_establish_channel(origin, counterparty)?;
+
+			let config = <configuration::Pallet<T>>::config();
+			let max_capacity = config.hrmp_channel_max_capacity;
+			let max_message_size = config.hrmp_channel_max_message_size;
+			Self::init_open_channel(origin, counterparty, max_capacity, max_message_size)?;
+			Self::accept_open_channel(counterparty, origin)?;
+			Self::init_open_channel(counterparty, origin, max_capacity, max_message_size)?;
+			Self::accept_open_channel(origin, counterparty)?;
+
+			Self::deposit_event(Event::BidirectionalChannelRequested(
+				origin,
+				counterparty,
+				max_capacity,
+				max_message_size,
+			));
+			Ok(())
+		}
+
+		/// Set whether the origin parachain pre-approves establishing channels with the
+		/// `counterparty` via
+		/// [`hrmp_establish_bidirectional_channel`](Pallet::hrmp_establish_bidirectional_channel).
+		#[pallet::call_index(9)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_set_channel_pre_approval())]
+		pub fn hrmp_set_channel_pre_approval(
+			origin: OriginFor<T>,
+			counterparty: ParaId,
+			approved: bool,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			ensure!(origin != counterparty, Error::<T>::OpenHrmpChannelToSelf);
+			if approved {
+				<Self as Store>::HrmpChannelPreApprovals::insert(&origin, &counterparty, ());
+			} else {
+				<Self as Store>::HrmpChannelPreApprovals::remove(&origin, &counterparty);
+			}
+			Self::deposit_event(Event::ChannelPreApprovalSet(origin, counterparty, approved));
+			Ok(())
+		}
+
+		/// Set the policy deciding which paras may establish channels in one step.
+		///
+		/// Origin must be Root.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<T as Config>::WeightInfo::force_set_auto_accept_policy())]
+		pub fn force_set_auto_accept_policy(
+			origin: OriginFor<T>,
+			policy: ChannelAutoAcceptPolicy,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			<Self as Store>::HrmpAutoAcceptPolicy::put(policy);
+			Self::deposit_event(Event::AutoAcceptPolicySet(policy));
+			Ok(())
+		}
 	}
 }
 
 #[cfg(feature = "std")]
 fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32, u32)]) {
-	let host_config = configuration::Pallet::<T>::config();
 	for &(sender, recipient, max_capacity, max_message_size) in preopen_hrmp_channels {
 		if let Err(err) =
 			preopen_hrmp_channel::<T>(sender, recipient, max_capacity, max_message_size)
@@ -631,7 +768,7 @@ fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32,
 			panic!("failed to initialize the genesis storage: {:?}", err);
 		}
 	}
-	<Pallet<T>>::process_hrmp_open_channel_requests(&host_config);
+	<Pallet<T>>::process_hrmp_open_channel_requests();
 }
 
 #[cfg(feature = "std")]
@@ -661,24 +798,21 @@ impl<T: Config> Pallet<T> {
 		notification: &initializer::SessionChangeNotification<T::BlockNumber>,
 		outgoing_paras: &[ParaId],
 	) -> Weight {
-		let w1 = Self::perform_outgoing_para_cleanup(&notification.prev_config, outgoing_paras);
-		Self::process_hrmp_open_channel_requests(&notification.prev_config);
+		let w1 = Self::perform_outgoing_para_cleanup(outgoing_paras);
+		Self::process_hrmp_open_channel_requests();
 		Self::process_hrmp_close_channel_requests();
 		w1.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_open(
 			outgoing_paras.len() as u32
 		))
 		.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_close(
-			outgoing_paras.len() as u32
+			outgoing_paras.len() as u32,
 		))
 	}
 
 	/// Iterate over all paras that were noted for offboarding and remove all the data
 	/// associated with them.
-	fn perform_outgoing_para_cleanup(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
-		let mut w = Self::clean_open_channel_requests(config, outgoing);
+	fn perform_outgoing_para_cleanup(outgoing: &[ParaId]) -> Weight {
+		let mut w = Self::clean_open_channel_requests(outgoing);
 		for outgoing_para in outgoing {
 			Self::clean_hrmp_after_outgoing(outgoing_para);
 
@@ -699,10 +833,7 @@ impl<T: Config> Pallet<T> {
 	// Go over the HRMP open channel requests and remove all in which offboarding paras participate.
 	//
 	// This will also perform the refunds for the counterparty if it doesn't offboard.
-	pub(crate) fn clean_open_channel_requests(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
+	pub(crate) fn clean_open_channel_requests(outgoing: &[ParaId]) -> Weight {
 		// First collect all the channel ids of the open requests in which there is at least one
 		// party presents in the outgoing list.
 		//
@@ -733,16 +864,15 @@ impl<T: Config> Pallet<T> {
 				);
 			}
 
-			// If the request was confirmed, then it means it was confirmed in the finished session.
-			// Therefore, the config's hrmp_recipient_deposit represents the actual value of the
-			// deposit.
+			// If the request was confirmed, then the recipient left the deposit recorded in the
+			// request.
 			//
 			// We still want to refund the deposit only if the para is not being offboarded.
 			if req_data.confirmed {
 				if !outgoing.contains(&req_id.recipient) {
 					T::Currency::unreserve(
 						&req_id.recipient.into_account_truncating(),
-						config.hrmp_recipient_deposit.unique_saturated_into(),
+						req_data.recipient_deposit.unique_saturated_into(),
 					);
 				}
 				Self::decrease_accepted_channel_request_count(req_id.recipient);
@@ -756,6 +886,8 @@ impl<T: Config> Pallet<T> {
 	fn clean_hrmp_after_outgoing(outgoing_para: &ParaId) {
 		<Self as Store>::HrmpOpenChannelRequestCount::remove(outgoing_para);
 		<Self as Store>::HrmpAcceptedChannelRequestCount::remove(outgoing_para);
+		let _ =
+			<Self as Store>::HrmpChannelPreApprovals::clear_prefix(outgoing_para, u32::MAX, None);
 
 		let ingress = <Self as Store>::HrmpIngressChannelsIndex::take(outgoing_para)
 			.into_iter()
@@ -776,7 +908,7 @@ impl<T: Config> Pallet<T> {
 	///
 	/// - prune the stale requests
 	/// - enact the confirmed requests
-	fn process_hrmp_open_channel_requests(config: &HostConfiguration<T::BlockNumber>) {
+	fn process_hrmp_open_channel_requests() {
 		let mut open_req_channels = <Self as Store>::HrmpOpenChannelRequestsList::get();
 		if open_req_channels.is_empty() {
 			return
@@ -805,7 +937,7 @@ impl<T: Config> Pallet<T> {
 						&channel_id,
 						HrmpChannel {
 							sender_deposit: request.sender_deposit,
-							recipient_deposit: config.hrmp_recipient_deposit,
+							recipient_deposit: request.recipient_deposit,
 							max_capacity: request.max_capacity,
 							max_total_size: request.max_total_size,
 							max_message_size: request.max_message_size,
@@ -929,10 +1061,15 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Check that the outbound messages of a candidate pass the acceptance criteria.
+	///
+	/// `chained` are the number and total size of the messages sent to each recipient by the
+	/// candidates this one is chained to, which are not in the channels yet.
 	pub(crate) fn check_outbound_hrmp(
 		config: &HostConfiguration<T::BlockNumber>,
 		sender: ParaId,
 		out_hrmp_msgs: &[OutboundHrmpMessage<ParaId>],
+		chained: &BTreeMap<ParaId, (u32, u32)>,
 	) -> Result<(), OutboundHrmpAcceptanceErr> {
 		if out_hrmp_msgs.len() as u32 > config.hrmp_max_message_num_per_candidate {
 			return Err(OutboundHrmpAcceptanceErr::MoreMessagesThanPermitted {
@@ -971,7 +1108,10 @@ impl<T: Config> Pallet<T> {
 				})
 			}
 
-			let new_total_size = channel.total_size + out_msg.data.len() as u32;
+			let (chained_count, chained_size) =
+				chained.get(&out_msg.recipient).copied().unwrap_or_default();
+
+			let new_total_size = channel.total_size + chained_size + out_msg.data.len() as u32;
 			if new_total_size > channel.max_total_size {
 				return Err(OutboundHrmpAcceptanceErr::TotalSizeExceeded {
 					idx,
@@ -980,7 +1120,7 @@ impl<T: Config> Pallet<T> {
 				})
 			}
 
-			let new_msg_count = channel.msg_count + 1;
+			let new_msg_count = channel.msg_count + chained_count + 1;
 			if new_msg_count > channel.max_capacity {
 				return Err(OutboundHrmpAcceptanceErr::CapacityExceeded {
 					idx,
@@ -1177,9 +1317,23 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::OpenHrmpChannelLimitExceeded,
 		);
 
+		let notification_bytes = {
+			use parity_scale_codec::Encode as _;
+			use xcm::opaque::{latest::prelude::*, VersionedXcm};
+
+			VersionedXcm::from(Xcm(vec![HrmpNewChannelOpenRequest {
+				sender: u32::from(origin),
+				max_capacity: proposed_max_capacity,
+				max_message_size: proposed_max_message_size,
+			}]))
+			.encode()
+		};
+		Self::ensure_can_notify(&config, recipient, &notification_bytes)?;
+
+		let sender_deposit = Self::sender_deposit(&config, origin);
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_sender_deposit.unique_saturated_into(),
+			sender_deposit.unique_saturated_into(),
 		)?;
 
 		// mutating storage directly now -- shall not bail henceforth.
@@ -1190,30 +1344,18 @@ impl<T: Config> Pallet<T> {
 			HrmpOpenChannelRequest {
 				confirmed: false,
 				_age: 0,
-				sender_deposit: config.hrmp_sender_deposit,
+				sender_deposit,
 				max_capacity: proposed_max_capacity,
 				max_message_size: proposed_max_message_size,
 				max_total_size: config.hrmp_channel_max_total_size,
+				recipient_deposit: 0,
 			},
 		);
 		<Self as Store>::HrmpOpenChannelRequestsList::append(channel_id);
 
-		let notification_bytes = {
-			use parity_scale_codec::Encode as _;
-			use xcm::opaque::{latest::prelude::*, VersionedXcm};
-
-			VersionedXcm::from(Xcm(vec![HrmpNewChannelOpenRequest {
-				sender: u32::from(origin),
-				max_capacity: proposed_max_capacity,
-				max_message_size: proposed_max_message_size,
-			}]))
-			.encode()
-		};
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Pallet<T>>::queue_downward_message(&config, recipient, notification_bytes)
+		if <dmp::Pallet<T>>::queue_downward_message(&config, recipient, notification_bytes).is_err()
 		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
+			// this should never happen, as we ensured above that the notification can be sent.
 			log::error!(
 				target: "runtime::hrmp",
 				"sending 'init_open_channel::notification_bytes' failed."
@@ -1250,28 +1392,29 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::AcceptHrmpChannelLimitExceeded,
 		);
 
+		let notification_bytes = {
+			use parity_scale_codec::Encode as _;
+			use xcm::opaque::{latest::prelude::*, VersionedXcm};
+			let xcm = Xcm(vec![HrmpChannelAccepted { recipient: u32::from(origin) }]);
+			VersionedXcm::from(xcm).encode()
+		};
+		Self::ensure_can_notify(&config, sender, &notification_bytes)?;
+
+		let recipient_deposit = Self::recipient_deposit(&config, origin);
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_recipient_deposit.unique_saturated_into(),
+			recipient_deposit.unique_saturated_into(),
 		)?;
 
 		// persist the updated open channel request and then increment the number of accepted
 		// channels.
 		channel_req.confirmed = true;
+		channel_req.recipient_deposit = recipient_deposit;
 		<Self as Store>::HrmpOpenChannelRequests::insert(&channel_id, channel_req);
 		<Self as Store>::HrmpAcceptedChannelRequestCount::insert(&origin, accepted_cnt + 1);
 
-		let notification_bytes = {
-			use parity_scale_codec::Encode as _;
-			use xcm::opaque::{latest::prelude::*, VersionedXcm};
-			let xcm = Xcm(vec![HrmpChannelAccepted { recipient: u32::from(origin) }]);
-			VersionedXcm::from(xcm).encode()
-		};
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Pallet<T>>::queue_downward_message(&config, sender, notification_bytes)
-		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
+		if <dmp::Pallet<T>>::queue_downward_message(&config, sender, notification_bytes).is_err() {
+			// this should never happen, as we ensured above that the notification can be sent.
 			log::error!(
 				target: "runtime::hrmp",
 				"sending 'accept_open_channel::notification_bytes' failed."
@@ -1282,6 +1425,62 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// The deposit the given para has to supply for requesting to open a channel. System
+	/// parachains are not charged any.
+	fn sender_deposit(config: &HostConfiguration<T::BlockNumber>, sender: ParaId) -> Balance {
+		if sender.is_system() {
+			0
+		} else {
+			config.hrmp_sender_deposit
+		}
+	}
+
+	/// The deposit the given para has to supply for accepting to open a channel. System
+	/// parachains are not charged any.
+	fn recipient_deposit(config: &HostConfiguration<T::BlockNumber>, recipient: ParaId) -> Balance {
+		if recipient.is_system() {
+			0
+		} else {
+			config.hrmp_recipient_deposit
+		}
+	}
+
+	/// Ensures that the notification can be queued for the given para, so that no request is
+	/// recorded which the para would never learn about.
+	fn ensure_can_notify(
+		config: &HostConfiguration<T::BlockNumber>,
+		para: ParaId,
+		notification: &DownwardMessage,
+	) -> Result<(), Error<T>> {
+		<dmp::Pallet<T>>::can_queue_downward_message(config, &para, notification).map_err(|err| {
+			match err {
+				dmp::QueueDownwardMessageError::ExceedsMaxMessageSize =>
+					Error::<T>::NotificationTooLarge,
+				dmp::QueueDownwardMessageError::QueueFull => Error::<T>::NotificationQueueFull,
+			}
+		})
+	}
+
+	/// Checks whether the current auto-accept policy allows `origin` and `counterparty` to
+	/// establish channels between each other in one step.
+	fn ensure_can_establish_channel(origin: ParaId, counterparty: ParaId) -> DispatchResult {
+		if origin.is_system() && counterparty.is_system() {
+			return Ok(())
+		}
+
+		ensure!(
+			<Self as Store>::HrmpAutoAcceptPolicy::get() ==
+				ChannelAutoAcceptPolicy::MutualPreApproval,
+			Error::<T>::EstablishChannelNotAllowed,
+		);
+		ensure!(
+			<Self as Store>::HrmpChannelPreApprovals::contains_key(&origin, &counterparty) &&
+				<Self as Store>::HrmpChannelPreApprovals::contains_key(&counterparty, &origin),
+			Error::<T>::EstablishChannelNotPreApproved,
+		);
+		Ok(())
+	}
+
 	fn cancel_open_request(origin: ParaId, channel_id: HrmpChannelId) -> DispatchResult {
 		// check if the origin is allowed to close the channel.
 		ensure!(channel_id.is_participant(origin), Error::<T>::CancelHrmpOpenChannelUnauthorized);
@@ -1328,9 +1527,6 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::CloseHrmpChannelAlreadyUnderway,
 		);
 
-		<Self as Store>::HrmpCloseChannelRequests::insert(&channel_id, ());
-		<Self as Store>::HrmpCloseChannelRequestsList::append(channel_id.clone());
-
 		let config = <configuration::Pallet<T>>::config();
 		let notification_bytes = {
 			use parity_scale_codec::Encode as _;
@@ -1345,11 +1541,15 @@ impl<T: Config> Pallet<T> {
 		};
 		let opposite_party =
 			if origin == channel_id.sender { channel_id.recipient } else { channel_id.sender };
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Pallet<T>>::queue_downward_message(&config, opposite_party, notification_bytes)
+		Self::ensure_can_notify(&config, opposite_party, &notification_bytes)?;
+
+		<Self as Store>::HrmpCloseChannelRequests::insert(&channel_id, ());
+		<Self as Store>::HrmpCloseChannelRequestsList::append(channel_id.clone());
+
+		if <dmp::Pallet<T>>::queue_downward_message(&config, opposite_party, notification_bytes)
+			.is_err()
 		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
+			// this should never happen, as we ensured above that the notification can be sent.
 			log::error!(
 				target: "runtime::hrmp",
 				"sending 'close_channel::notification_bytes' failed."
@@ -1397,6 +1597,63 @@ impl<T: Config> Pallet<T> {
 
 		inbound_hrmp_channels_contents
 	}
+
+	/// Returns the channels the given para participates in as either sender or recipient: first
+	/// the pending open channel requests in the order they were made, then the open channels
+	/// ordered by their ids.
+	pub fn hrmp_channels(para: ParaId) -> Vec<HrmpChannelSummary> {
+		let mut channels = Vec::new();
+		for channel_id in <Self as Store>::HrmpOpenChannelRequestsList::get() {
+			if !channel_id.is_participant(para) {
+				continue
+			}
+			if let Some(request) = <Self as Store>::HrmpOpenChannelRequests::get(&channel_id) {
+				channels.push(HrmpChannelSummary {
+					channel: channel_id,
+					status: if request.confirmed {
+						HrmpChannelStatus::Accepted
+					} else {
+						HrmpChannelStatus::Requested
+					},
+					max_capacity: request.max_capacity,
+					max_total_size: request.max_total_size,
+					max_message_size: request.max_message_size,
+					msg_count: 0,
+					total_size: 0,
+				});
+			}
+		}
+
+		let ingress = <Self as Store>::HrmpIngressChannelsIndex::get(&para)
+			.into_iter()
+			.map(|sender| HrmpChannelId { sender, recipient: para });
+		let egress = <Self as Store>::HrmpEgressChannelsIndex::get(&para)
+			.into_iter()
+			.map(|recipient| HrmpChannelId { sender: para, recipient });
+		let mut open = ingress.chain(egress).collect::<Vec<_>>();
+		open.sort();
+		for channel_id in open {
+			if let Some(channel) = <Self as Store>::HrmpChannels::get(&channel_id) {
+				let status = if <Self as Store>::HrmpCloseChannelRequests::contains_key(&channel_id)
+				{
+					HrmpChannelStatus::Closing
+				} else {
+					HrmpChannelStatus::Open
+				};
+				channels.push(HrmpChannelSummary {
+					channel: channel_id,
+					status,
+					max_capacity: channel.max_capacity,
+					max_total_size: channel.max_total_size,
+					max_message_size: channel.max_message_size,
+					msg_count: channel.msg_count,
+					total_size: channel.total_size,
+				});
+			}
+		}
+
+		channels
+	}
 }
 
 impl<T: Config> Pallet<T> {
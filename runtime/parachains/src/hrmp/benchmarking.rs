@@ -105,7 +105,7 @@ where
 		return output
 	}
 
-	Hrmp::<T>::process_hrmp_open_channel_requests(&Configuration::<T>::config());
+	Hrmp::<T>::process_hrmp_open_channel_requests();
 	if matches!(until, ParachainSetupStep::Established) {
 		return output
 	}
@@ -290,9 +290,8 @@ frame_benchmarking::benchmarks! {
 
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
 		let outgoing = (0..c).map(|id| (id + PREFIX_1).into()).collect::<Vec<ParaId>>();
-		let config = Configuration::<T>::config();
 	}: {
-		Hrmp::<T>::clean_open_channel_requests(&config, &outgoing);
+		Hrmp::<T>::clean_open_channel_requests(&outgoing);
 	} verify {
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, 0);
 	}
@@ -322,6 +321,53 @@ frame_benchmarking::benchmarks! {
 			Event::<T>::HrmpChannelForceOpened(sender_id, recipient_id, capacity, message_size).into()
 		);
 	}
+
+	// worst case is two public paras which pre-approved each other: besides checking the
+	// approvals, deposits are reserved from both of them.
+	hrmp_establish_bidirectional_channel {
+		let origin_id: ParaId = 2000u32.into();
+		let origin: crate::Origin = 2000u32.into();
+		let counterparty_id: ParaId = 2001u32.into();
+		let counterparty: crate::Origin = 2001u32.into();
+
+		let config = Configuration::<T>::config();
+		let deposit: BalanceOf<T> =
+			(config.hrmp_sender_deposit + config.hrmp_recipient_deposit).unique_saturated_into();
+		register_parachain_with_balance::<T>(origin_id, deposit);
+		register_parachain_with_balance::<T>(counterparty_id, deposit);
+
+		assert_ok!(Hrmp::<T>::force_set_auto_accept_policy(
+			frame_system::RawOrigin::Root.into(),
+			ChannelAutoAcceptPolicy::MutualPreApproval,
+		));
+		assert_ok!(Hrmp::<T>::hrmp_set_channel_pre_approval(origin.clone().into(), counterparty_id, true));
+		assert_ok!(Hrmp::<T>::hrmp_set_channel_pre_approval(counterparty.into(), origin_id, true));
+	}: _(origin, counterparty_id)
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::BidirectionalChannelRequested(
+				origin_id,
+				counterparty_id,
+				config.hrmp_channel_max_capacity,
+				config.hrmp_channel_max_message_size,
+			).into()
+		);
+		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default(), 2);
+	}
+
+	hrmp_set_channel_pre_approval {
+		let origin: crate::Origin = 2000u32.into();
+		let counterparty_id: ParaId = 2001u32.into();
+	}: _(origin, counterparty_id, true)
+	verify {
+		assert!(HrmpChannelPreApprovals::<T>::contains_key(ParaId::from(2000u32), counterparty_id));
+	}
+
+	force_set_auto_accept_policy {
+	}: _(frame_system::Origin::<T>::Root, ChannelAutoAcceptPolicy::MutualPreApproval)
+	verify {
+		assert_eq!(HrmpAutoAcceptPolicy::<T>::get(), ChannelAutoAcceptPolicy::MutualPreApproval);
+	}
 }
 
 frame_benchmarking::impl_benchmark_test_suite!(
@@ -0,0 +1,108 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	configuration,
+	hrmp::{Config, HrmpOpenChannelRequest, HrmpOpenChannelRequests, Pallet},
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+	use primitives::{Balance, SessionIndex};
+	#[cfg(feature = "try-runtime")]
+	use sp_std::prelude::*;
+
+	/// The open channel request as it was stored before the recipient deposit was recorded.
+	#[derive(Encode, Decode)]
+	struct OldHrmpOpenChannelRequest {
+		confirmed: bool,
+		_age: SessionIndex,
+		sender_deposit: Balance,
+		max_message_size: u32,
+		max_capacity: u32,
+		max_total_size: u32,
+	}
+
+	/// Records the recipient deposit on every open channel request.
+	///
+	/// Before this migration every recipient was charged `hrmp_recipient_deposit` when accepting a
+	/// request, so that is the deposit recorded on the confirmed ones.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let mut weight = T::DbWeight::get().reads(2);
+
+				let recipient_deposit = <configuration::Pallet<T>>::config().hrmp_recipient_deposit;
+				let mut requests = 0u64;
+				HrmpOpenChannelRequests::<T>::translate::<OldHrmpOpenChannelRequest, _>(
+					|_, old| {
+						requests += 1;
+						Some(HrmpOpenChannelRequest {
+							confirmed: old.confirmed,
+							_age: old._age,
+							sender_deposit: old.sender_deposit,
+							max_message_size: old.max_message_size,
+							max_capacity: old.max_capacity,
+							max_total_size: old.max_total_size,
+							recipient_deposit: if old.confirmed { recipient_deposit } else { 0 },
+						})
+					},
+				);
+				log::info!("Recorded the recipient deposit on {} open channel requests", requests);
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(requests, requests));
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				weight.saturating_add(T::DbWeight::get().writes(1))
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == 0,
+				"Storage version should be `0` before the migration",
+			);
+			Ok((HrmpOpenChannelRequests::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be `1` after the migration"
+			);
+			let requests =
+				u32::decode(&mut &state[..]).map_err(|_| "the state of `pre_upgrade` decodes")?;
+			ensure!(
+				HrmpOpenChannelRequests::<T>::iter().count() as u32 == requests,
+				"Every open channel request should decode after the migration"
+			);
+			Ok(())
+		}
+	}
+}
@@ -17,7 +17,7 @@
 use super::*;
 use crate::{
 	mock::{
-		new_test_ext, Configuration, Hrmp, MockGenesisConfig, Paras, ParasShared,
+		new_test_ext, Configuration, Dmp, Hrmp, MockGenesisConfig, Paras, ParasShared,
 		RuntimeEvent as MockEvent, RuntimeOrigin, System, Test,
 	},
 	paras::ParaKind,
@@ -153,6 +153,13 @@ fn channel_exists(sender: ParaId, recipient: ParaId) -> bool {
 	<Hrmp as Store>::HrmpChannels::get(&HrmpChannelId { sender, recipient }).is_some()
 }
 
+fn fill_downward_message_queue(para: ParaId) {
+	let config = Configuration::config();
+	while Dmp::can_queue_downward_message(&config, &para, &vec![1]).is_ok() {
+		assert_ok!(Dmp::queue_downward_message(&config, para, vec![1]));
+	}
+}
+
 #[test]
 fn empty_state_consistent_state() {
 	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
@@ -281,7 +288,7 @@ fn send_recv_messages() {
 		let msgs =
 			vec![OutboundHrmpMessage { recipient: para_b, data: b"this is an emergency".to_vec() }];
 		let config = Configuration::config();
-		assert!(Hrmp::check_outbound_hrmp(&config, para_a, &msgs).is_ok());
+		assert!(Hrmp::check_outbound_hrmp(&config, para_a, &msgs, &Default::default()).is_ok());
 		let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
 		Hrmp::assert_storage_consistency_exhaustive();
 
@@ -383,7 +390,7 @@ fn check_sent_messages() {
 
 		let msgs = vec![OutboundHrmpMessage { recipient: para_b, data: b"knock".to_vec() }];
 		let config = Configuration::config();
-		assert!(Hrmp::check_outbound_hrmp(&config, para_a, &msgs).is_ok());
+		assert!(Hrmp::check_outbound_hrmp(&config, para_a, &msgs, &Default::default()).is_ok());
 		let _ = Hrmp::queue_outbound_hrmp(para_a, msgs.clone());
 
 		// Verify that the sent messages are there and that also the empty channels are present.
@@ -478,8 +485,8 @@ fn verify_externally_accessible() {
 
 #[test]
 fn charging_deposits() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
 		register_parachain_with_balance(para_a, 0);
@@ -506,10 +513,47 @@ fn charging_deposits() {
 	});
 }
 
+#[test]
+fn notifying_para_with_full_downward_message_queue_fails() {
+	let para_a = 2032.into();
+	let para_b = 2064.into();
+	let para_c = 2096.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		register_parachain(para_c);
+		run_to_block(5, Some(vec![4, 5]));
+
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+		assert!(channel_exists(para_a, para_b));
+		Hrmp::init_open_channel(para_b, para_a, 2, 8).unwrap();
+
+		fill_downward_message_queue(para_a);
+		fill_downward_message_queue(para_b);
+
+		// Nothing changes if the other party can't be notified.
+		assert_noop!(
+			Hrmp::init_open_channel(para_c, para_a, 2, 8),
+			Error::<Test>::NotificationQueueFull
+		);
+		assert_noop!(
+			Hrmp::accept_open_channel(para_a, para_b),
+			Error::<Test>::NotificationQueueFull
+		);
+		assert_noop!(
+			Hrmp::close_channel(para_b, HrmpChannelId { sender: para_a, recipient: para_b }),
+			Error::<Test>::NotificationQueueFull
+		);
+	});
+}
+
 #[test]
 fn refund_deposit_on_normal_closure() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -541,8 +585,8 @@ fn refund_deposit_on_normal_closure() {
 
 #[test]
 fn refund_deposit_on_offboarding() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -579,10 +623,53 @@ fn refund_deposit_on_offboarding() {
 	});
 }
 
+#[test]
+fn refund_recorded_recipient_deposit() {
+	let para_a = 2032.into();
+	let para_b = 1.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		// `para_b` is a system parachain, so it isn't charged any deposit when accepting.
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+
+		// Pretend that the request was accepted back when system parachains were charged.
+		<Test as Config>::Currency::reserve(&para_b.into_account_truncating(), 15).unwrap();
+		<Hrmp as Store>::HrmpOpenChannelRequests::mutate(&channel_id, |request| {
+			request.as_mut().unwrap().recipient_deposit = 15;
+		});
+		run_to_block(8, Some(vec![8]));
+		assert_eq!(<Hrmp as Store>::HrmpChannels::get(&channel_id).unwrap().recipient_deposit, 15);
+
+		// The recorded deposit is returned once the channel is closed.
+		Hrmp::close_channel(para_b, channel_id).unwrap();
+		run_to_block(10, Some(vec![10]));
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()),
+			100
+		);
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+	});
+}
+
 #[test]
 fn no_dangling_open_requests() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -619,8 +706,8 @@ fn no_dangling_open_requests() {
 
 #[test]
 fn cancel_pending_open_channel_request() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -648,3 +735,154 @@ fn cancel_pending_open_channel_request() {
 		Hrmp::assert_storage_consistency_exhaustive();
 	});
 }
+
+#[test]
+fn establish_channel_between_system_parachains_works() {
+	let para_a = 1.into();
+	let para_a_origin: crate::Origin = 1.into();
+	let para_b = 3.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		// System parachains are not charged any deposits, so they don't need any funds.
+		register_parachain_with_balance(para_a, 0);
+		register_parachain_with_balance(para_b, 0);
+		run_to_block(5, Some(vec![4, 5]));
+
+		Hrmp::hrmp_establish_bidirectional_channel(para_a_origin.into(), para_b).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::BidirectionalChannelRequested(para_a, para_b, 2, 8))));
+
+		// Both channels are opened on the next session change.
+		run_to_block(6, None);
+		assert!(!channel_exists(para_a, para_b));
+		assert!(!channel_exists(para_b, para_a));
+		run_to_block(8, Some(vec![8]));
+		assert!(channel_exists(para_a, para_b));
+		assert!(channel_exists(para_b, para_a));
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		let channel = <Hrmp as Store>::HrmpChannels::get(&HrmpChannelId {
+			sender: para_a,
+			recipient: para_b,
+		})
+		.unwrap();
+		assert_eq!((channel.sender_deposit, channel.recipient_deposit), (0, 0));
+	});
+}
+
+#[test]
+fn establish_channel_requires_mutual_pre_approval() {
+	let para_a = 2000.into();
+	let para_a_origin: crate::Origin = 2000.into();
+	let para_b = 2001.into();
+	let para_b_origin: crate::Origin = 2001.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+
+		// Only system parachains may establish channels in one step by default.
+		Hrmp::hrmp_set_channel_pre_approval(para_a_origin.clone().into(), para_b, true).unwrap();
+		Hrmp::hrmp_set_channel_pre_approval(para_b_origin.clone().into(), para_a, true).unwrap();
+		assert_noop!(
+			Hrmp::hrmp_establish_bidirectional_channel(para_a_origin.clone().into(), para_b),
+			Error::<Test>::EstablishChannelNotAllowed,
+		);
+
+		// Under the mutual pre-approval policy, both paras need to approve each other.
+		Hrmp::force_set_auto_accept_policy(
+			RuntimeOrigin::root(),
+			ChannelAutoAcceptPolicy::MutualPreApproval,
+		)
+		.unwrap();
+		Hrmp::hrmp_set_channel_pre_approval(para_b_origin.clone().into(), para_a, false).unwrap();
+		assert_noop!(
+			Hrmp::hrmp_establish_bidirectional_channel(para_a_origin.clone().into(), para_b),
+			Error::<Test>::EstablishChannelNotPreApproved,
+		);
+
+		Hrmp::hrmp_set_channel_pre_approval(para_b_origin.into(), para_a, true).unwrap();
+		Hrmp::hrmp_establish_bidirectional_channel(para_a_origin.into(), para_b).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// Both paras supplied the deposits for sending and for receiving.
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()), 65);
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()), 75);
+
+		run_to_block(8, Some(vec![8]));
+		assert!(channel_exists(para_a, para_b));
+		assert!(channel_exists(para_b, para_a));
+	});
+}
+
+#[test]
+fn pre_approvals_are_removed_on_offboarding() {
+	let para_a = 2000.into();
+	let para_a_origin: crate::Origin = 2000.into();
+	let para_b = 2001.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		run_to_block(5, Some(vec![4, 5]));
+
+		Hrmp::hrmp_set_channel_pre_approval(para_a_origin.into(), para_b, true).unwrap();
+		assert!(<Hrmp as Store>::HrmpChannelPreApprovals::contains_key(&para_a, &para_b));
+
+		deregister_parachain(para_a);
+		run_to_block(10, Some(vec![9, 10]));
+		assert!(!<Hrmp as Store>::HrmpChannelPreApprovals::contains_key(&para_a, &para_b));
+	});
+}
+
+#[test]
+fn hrmp_channels_lists_pending_and_open_channels() {
+	let para_a = 1.into();
+	let para_b = 3.into();
+	let para_c = 5.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		register_parachain(para_c);
+		run_to_block(5, Some(vec![4, 5]));
+
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(8, Some(vec![8]));
+		Hrmp::close_channel(para_b, HrmpChannelId { sender: para_a, recipient: para_b }).unwrap();
+		Hrmp::init_open_channel(para_c, para_a, 1, 4).unwrap();
+
+		let summary = |sender: ParaId, recipient: ParaId, status, capacity, message_size| {
+			HrmpChannelSummary {
+				channel: HrmpChannelId { sender, recipient },
+				status,
+				max_capacity: capacity,
+				max_total_size: 16,
+				max_message_size: message_size,
+				msg_count: 0,
+				total_size: 0,
+			}
+		};
+		assert_eq!(
+			Hrmp::hrmp_channels(para_a),
+			vec![
+				summary(para_c, para_a, HrmpChannelStatus::Requested, 1, 4),
+				summary(para_a, para_b, HrmpChannelStatus::Closing, 2, 8),
+			],
+		);
+		assert_eq!(
+			Hrmp::hrmp_channels(para_b),
+			vec![summary(para_a, para_b, HrmpChannelStatus::Closing, 2, 8)],
+		);
+		assert!(Hrmp::hrmp_channels(2000.into()).is_empty());
+	});
+}
@@ -35,7 +35,10 @@ use primitives::{
 };
 use scale_info::TypeInfo;
 use sp_runtime::{traits::One, DispatchError};
-use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
 
 pub use pallet::*;
 
@@ -139,6 +142,45 @@ impl<H, N> CandidatePendingAvailability<H, N> {
 	}
 }
 
+/// The outputs of the candidates of a para which were backed earlier in the same relay-chain
+/// block, in order. A candidate backed after them is chained to the last one of them.
+#[derive(Default)]
+pub(crate) struct ChainedOutputs<N> {
+	/// The head data produced by the last candidate of the chain, if any.
+	head_data: Option<HeadData>,
+	/// The total number of downward messages processed by the chain.
+	processed_downward_messages: u32,
+	/// The HRMP watermark of the last candidate of the chain, if any.
+	hrmp_watermark: Option<N>,
+	/// Whether any candidate of the chain upgrades the validation code.
+	code_upgrade: bool,
+	/// The number and total size of the upward messages sent by the chain.
+	upward_messages: (u32, u32),
+	/// The number and total size of the messages sent by the chain to each recipient.
+	horizontal_messages: BTreeMap<ParaId, (u32, u32)>,
+}
+
+impl<N: From<u32>> ChainedOutputs<N> {
+	/// Append a candidate with the given commitments to the chain.
+	pub(crate) fn append(&mut self, commitments: &CandidateCommitments) {
+		self.head_data = Some(commitments.head_data.clone());
+		self.processed_downward_messages = self
+			.processed_downward_messages
+			.saturating_add(commitments.processed_downward_messages);
+		self.hrmp_watermark = Some(N::from(commitments.hrmp_watermark));
+		self.code_upgrade |= commitments.new_validation_code.is_some();
+		for msg in &commitments.upward_messages {
+			self.upward_messages.0 = self.upward_messages.0.saturating_add(1);
+			self.upward_messages.1 = self.upward_messages.1.saturating_add(msg.len() as u32);
+		}
+		for msg in &commitments.horizontal_messages {
+			let (count, size) = self.horizontal_messages.entry(msg.recipient).or_default();
+			*count = count.saturating_add(1);
+			*size = size.saturating_add(msg.data.len() as u32);
+		}
+	}
+}
+
 /// A hook for applying validator rewards
 pub trait RewardValidators {
 	// Reward the validators with the given indices for issuing backing statements.
@@ -294,6 +336,19 @@ pub mod pallet {
 	pub(crate) type PendingAvailabilityCommitments<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, CandidateCommitments>;
 
+	/// Candidates pending availability which are chained to the candidate of the same para in
+	/// `PendingAvailability`, in order and along with their commitments.
+	///
+	/// Only parachains with extra cores can have those.
+	#[pallet::storage]
+	pub(crate) type PendingAvailabilityDescendants<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Vec<(CandidatePendingAvailability<T::Hash, T::BlockNumber>, CandidateCommitments)>,
+		ValueQuery,
+	>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
 }
@@ -317,12 +372,17 @@ impl<T: Config> Pallet<T> {
 		// and require consumption.
 		for _ in <PendingAvailabilityCommitments<T>>::drain() {}
 		for _ in <PendingAvailability<T>>::drain() {}
+		for _ in <PendingAvailabilityDescendants<T>>::drain() {}
 		for _ in <AvailabilityBitfields<T>>::drain() {}
 	}
 
 	/// Extract the freed cores based on cores that became available.
 	///
-	/// Updates storage items `PendingAvailability` and `AvailabilityBitfields`.
+	/// Updates storage items `PendingAvailability`, `PendingAvailabilityDescendants` and
+	/// `AvailabilityBitfields`.
+	///
+	/// Chained candidates are enacted in order: a candidate which became available is only
+	/// enacted together with or after the candidate it is chained to.
 	pub(crate) fn update_pending_availability_and_get_freed_cores<F>(
 		expected_bits: usize,
 		validators: &[ValidatorId],
@@ -333,10 +393,26 @@ impl<T: Config> Pallet<T> {
 	where
 		F: Fn(CoreIndex) -> Option<ParaId>,
 	{
-		let mut assigned_paras_record = (0..expected_bits)
-			.map(|bit_index| core_lookup(CoreIndex::from(bit_index as u32)))
-			.map(|opt_para_id| {
-				opt_para_id.map(|para_id| (para_id, PendingAvailability::<T>::get(&para_id)))
+		// The candidates pending availability of every para assigned to a core, the first one
+		// followed by its chained descendants.
+		let mut pending_chains = BTreeMap::new();
+		let assigned_cores_record = (0..expected_bits)
+			.map(|bit_index| {
+				let core = CoreIndex::from(bit_index as u32);
+				let para_id = core_lookup(core)?;
+				let (first, descendants) = pending_chains.entry(para_id).or_insert_with(|| {
+					(
+						PendingAvailability::<T>::get(&para_id),
+						PendingAvailabilityDescendants::<T>::get(&para_id),
+					)
+				});
+
+				// the position of the candidate occupying the core in the chain.
+				let position = match first {
+					Some(first) if first.core == core => Some(0),
+					_ => descendants.iter().position(|(d, _)| d.core == core).map(|i| i + 1),
+				};
+				Some((para_id, position))
 			})
 			.collect::<Vec<_>>();
 
@@ -349,23 +425,32 @@ impl<T: Config> Pallet<T> {
 				(checked_bitfield, validator_idx)
 			}) {
 			for (bit_idx, _) in checked_bitfield.0.iter().enumerate().filter(|(_, is_av)| **is_av) {
-				let pending_availability = if let Some((_, pending_availability)) =
-					assigned_paras_record[bit_idx].as_mut()
-				{
-					pending_availability
-				} else {
-					// For honest validators, this happens in case of unoccupied cores,
-					// which in turn happens in case of a disputed candidate.
-					// A malicious one might include arbitrary indices, but they are represented
-					// by `None` values and will be sorted out in the next if case.
-					continue
+				let (para_id, position) =
+					if let Some((para_id, Some(position))) = assigned_cores_record[bit_idx] {
+						(para_id, position)
+					} else {
+						// For honest validators, this happens in case of unoccupied cores,
+						// which in turn happens in case of a disputed candidate.
+						// A malicious one might include arbitrary indices, but they are represented
+						// by `None` values and will be sorted out in the next if case.
+						continue
+					};
+
+				let pending_availability = match pending_chains.get_mut(&para_id) {
+					Some((first, descendants)) =>
+						if position == 0 {
+							first.as_mut()
+						} else {
+							descendants.get_mut(position - 1).map(|(d, _)| d)
+						},
+					None => None,
 				};
 
 				// defensive check - this is constructed by loading the availability bitfield record,
 				// which is always `Some` if the core is occupied - that's why we're here.
 				let validator_index = validator_index.0 as usize;
 				if let Some(mut bit) =
-					pending_availability.as_mut().and_then(|candidate_pending_availability| {
+					pending_availability.and_then(|candidate_pending_availability| {
 						candidate_pending_availability.availability_votes.get_mut(validator_index)
 					}) {
 					*bit = true;
@@ -381,14 +466,29 @@ impl<T: Config> Pallet<T> {
 		let threshold = availability_threshold(validators.len());
 
 		let mut freed_cores = Vec::with_capacity(expected_bits);
-		for (para_id, pending_availability) in assigned_paras_record
-			.into_iter()
-			.flatten()
-			.filter_map(|(id, p)| p.map(|p| (id, p)))
-		{
-			if pending_availability.availability_votes.count_ones() >= threshold {
+		for (para_id, (first, descendants)) in pending_chains {
+			let first = match first {
+				Some(first) => first,
+				None => continue,
+			};
+			let had_descendants = !descendants.is_empty();
+			let mut descendants = descendants.into_iter();
+
+			// The commitments of the first candidate are only loaded if it is enacted.
+			let mut next = Some((first, None));
+			while let Some((pending_availability, commitments)) = next.take() {
+				if pending_availability.availability_votes.count_ones() < threshold {
+					<PendingAvailability<T>>::insert(&para_id, &pending_availability);
+					if let Some(commitments) = commitments {
+						<PendingAvailabilityCommitments<T>>::insert(&para_id, commitments);
+					}
+					break
+				}
+
 				<PendingAvailability<T>>::remove(&para_id);
-				let commitments = match PendingAvailabilityCommitments::<T>::take(&para_id) {
+				let commitments = match commitments
+					.or_else(|| PendingAvailabilityCommitments::<T>::take(&para_id))
+				{
 					Some(commitments) => commitments,
 					None => {
 						log::warn!(
@@ -396,7 +496,9 @@ impl<T: Config> Pallet<T> {
 							"Inclusion::process_bitfields: PendingAvailability and PendingAvailabilityCommitments
 							are out of sync, did someone mess with the storage?",
 						);
-						continue
+						// the descendants can't be enacted without their parent.
+						descendants.by_ref().for_each(drop);
+						break
 					},
 				};
 
@@ -416,11 +518,19 @@ impl<T: Config> Pallet<T> {
 				}
 
 				freed_cores.push((pending_availability.core, pending_availability.hash));
-			} else {
-				<PendingAvailability<T>>::insert(&para_id, &pending_availability);
+				next = descendants.next().map(|(d, commitments)| (d, Some(commitments)));
+			}
+
+			let descendants = descendants.collect::<Vec<_>>();
+			if !descendants.is_empty() {
+				<PendingAvailabilityDescendants<T>>::insert(&para_id, descendants);
+			} else if had_descendants {
+				<PendingAvailabilityDescendants<T>>::remove(&para_id);
 			}
 		}
 
+		// the chains are processed by para, but the freed cores are expected in order.
+		freed_cores.sort_unstable_by_key(|(core, _)| *core);
 		freed_cores
 	}
 
@@ -465,6 +575,9 @@ impl<T: Config> Pallet<T> {
 	///
 	/// Both should be sorted ascending by core index, and the candidates should be a subset of
 	/// scheduled cores. If these conditions are not met, the execution of the function fails.
+	///
+	/// A para with multiple scheduled cores can have multiple candidates backed, which have to
+	/// form a chain in the order of the cores.
 	pub(crate) fn process_candidates<GV>(
 		parent_storage_root: T::Hash,
 		candidates: Vec<BackedCandidate<T::Hash>>,
@@ -493,6 +606,9 @@ impl<T: Config> Pallet<T> {
 		let mut candidate_receipt_with_backing_validator_indices =
 			Vec::with_capacity(candidates.len());
 
+		// The outputs of the candidates already backed for each para.
+		let mut chains = BTreeMap::<ParaId, ChainedOutputs<T::BlockNumber>>::new();
+
 		// Do all checks before writing storage.
 		let core_indices_and_backers = {
 			let mut skip = 0;
@@ -525,11 +641,15 @@ impl<T: Config> Pallet<T> {
 			'next_backed_candidate: for (candidate_idx, backed_candidate) in
 				candidates.iter().enumerate()
 			{
+				let para_id = backed_candidate.descriptor().para_id;
+				let chained = chains.get(&para_id);
+
 				match check_ctx.verify_backed_candidate(
 					parent_hash,
 					parent_storage_root,
 					candidate_idx,
 					backed_candidate,
+					chained.unwrap_or(&Default::default()),
 				)? {
 					Err(FailedToCreatePVD) => {
 						log::debug!(
@@ -546,7 +666,6 @@ impl<T: Config> Pallet<T> {
 					Ok(rpn) => rpn,
 				}
 
-				let para_id = backed_candidate.descriptor().para_id;
 				let mut backers = bitvec::bitvec![u8, BitOrderLsb0; 0; validators.len()];
 
 				for (i, assignment) in scheduled[skip..].iter().enumerate() {
@@ -560,11 +679,15 @@ impl<T: Config> Pallet<T> {
 							);
 						}
 
-						ensure!(
-							<PendingAvailability<T>>::get(&para_id).is_none() &&
-								<PendingAvailabilityCommitments<T>>::get(&para_id).is_none(),
-							Error::<T>::CandidateScheduledBeforeParaFree,
-						);
+						// a candidate chained to one backed in this block occupies another core
+						// of the para, whose chain was checked to be free.
+						if chained.is_none() {
+							ensure!(
+								<PendingAvailability<T>>::get(&para_id).is_none() &&
+									<PendingAvailabilityCommitments<T>>::get(&para_id).is_none(),
+								Error::<T>::CandidateScheduledBeforeParaFree,
+							);
+						}
 
 						// account for already skipped, and then skip this one.
 						skip = i + skip + 1;
@@ -625,6 +748,10 @@ impl<T: Config> Pallet<T> {
 							backers,
 							assignment.group_idx,
 						));
+						chains
+							.entry(para_id)
+							.or_default()
+							.append(&backed_candidate.candidate.commitments);
 						continue 'next_backed_candidate
 					}
 				}
@@ -666,20 +793,28 @@ impl<T: Config> Pallet<T> {
 			let (descriptor, commitments) =
 				(candidate.candidate.descriptor, candidate.candidate.commitments);
 
-			<PendingAvailability<T>>::insert(
-				&para_id,
-				CandidatePendingAvailability {
-					core,
-					hash: candidate_hash,
-					descriptor,
-					availability_votes,
-					relay_parent_number,
-					backers: backers.to_bitvec(),
-					backed_in_number: check_ctx.now,
-					backing_group: group,
-				},
-			);
-			<PendingAvailabilityCommitments<T>>::insert(&para_id, commitments);
+			let pending_availability = CandidatePendingAvailability {
+				core,
+				hash: candidate_hash,
+				descriptor,
+				availability_votes,
+				relay_parent_number,
+				backers: backers.to_bitvec(),
+				backed_in_number: check_ctx.now,
+				backing_group: group,
+			};
+
+			// the para was checked to be free, unless the candidate is chained to one backed
+			// in this block.
+			if <PendingAvailability<T>>::contains_key(&para_id) {
+				<PendingAvailabilityDescendants<T>>::append(
+					&para_id,
+					(pending_availability, commitments),
+				);
+			} else {
+				<PendingAvailability<T>>::insert(&para_id, pending_availability);
+				<PendingAvailabilityCommitments<T>>::insert(&para_id, commitments);
+			}
 		}
 
 		Ok(ProcessedCandidates::<T::Hash> {
@@ -707,6 +842,7 @@ impl<T: Config> Pallet<T> {
 			&validation_outputs.upward_messages,
 			T::BlockNumber::from(validation_outputs.hrmp_watermark),
 			&validation_outputs.horizontal_messages,
+			&Default::default(),
 		) {
 			log::debug!(
 				target: LOG_TARGET,
@@ -796,6 +932,8 @@ impl<T: Config> Pallet<T> {
 	///
 	/// The predicate accepts the index of the core and the block number the core has been occupied
 	/// since (i.e. the block number the candidate was backed at in this fork of the relay chain).
+	/// It is applied to the first candidate pending availability of each para, and the candidates
+	/// chained to it are cleaned up along with it.
 	///
 	/// Returns a vector of cleaned-up core IDs.
 	pub(crate) fn collect_pending(
@@ -814,19 +952,25 @@ impl<T: Config> Pallet<T> {
 		for para_id in cleaned_up_ids {
 			let pending = <PendingAvailability<T>>::take(&para_id);
 			let commitments = <PendingAvailabilityCommitments<T>>::take(&para_id);
+			let descendants = <PendingAvailabilityDescendants<T>>::take(&para_id);
 
 			if let (Some(pending), Some(commitments)) = (pending, commitments) {
 				// defensive: this should always be true.
-				let candidate = CandidateReceipt {
-					descriptor: pending.descriptor,
-					commitments_hash: commitments.hash(),
-				};
+				cleaned_up_cores.extend(descendants.iter().map(|(d, _)| d.core));
+				for (pending, commitments) in
+					sp_std::iter::once((pending, commitments)).chain(descendants)
+				{
+					let candidate = CandidateReceipt {
+						descriptor: pending.descriptor,
+						commitments_hash: commitments.hash(),
+					};
 
-				Self::deposit_event(Event::<T>::CandidateTimedOut(
-					candidate,
-					commitments.head_data,
-					pending.core,
-				));
+					Self::deposit_event(Event::<T>::CandidateTimedOut(
+						candidate,
+						commitments.head_data,
+						pending.core,
+					));
+				}
 			}
 		}
 
@@ -835,6 +979,8 @@ impl<T: Config> Pallet<T> {
 
 	/// Cleans up all paras pending availability that are in the given list of disputed candidates.
 	///
+	/// Candidates chained to a disputed candidate are cleaned up as well.
+	///
 	/// Returns a vector of cleaned-up core IDs.
 	pub(crate) fn collect_disputed(disputed: &BTreeSet<CandidateHash>) -> Vec<CoreIndex> {
 		let mut cleaned_up_ids = Vec::new();
@@ -850,13 +996,32 @@ impl<T: Config> Pallet<T> {
 		for para_id in cleaned_up_ids {
 			let _ = <PendingAvailability<T>>::take(&para_id);
 			let _ = <PendingAvailabilityCommitments<T>>::take(&para_id);
+			let descendants = <PendingAvailabilityDescendants<T>>::take(&para_id);
+			cleaned_up_cores.extend(descendants.into_iter().map(|(d, _)| d.core));
+		}
+
+		let mut truncated = Vec::new();
+		for (para_id, descendants) in <PendingAvailabilityDescendants<T>>::iter() {
+			if let Some(i) = descendants.iter().position(|(d, _)| disputed.contains(&d.hash)) {
+				cleaned_up_cores.extend(descendants[i..].iter().map(|(d, _)| d.core));
+				truncated.push((para_id, descendants, i));
+			}
+		}
+
+		for (para_id, mut descendants, i) in truncated {
+			descendants.truncate(i);
+			if descendants.is_empty() {
+				<PendingAvailabilityDescendants<T>>::remove(&para_id);
+			} else {
+				<PendingAvailabilityDescendants<T>>::insert(&para_id, descendants);
+			}
 		}
 
 		cleaned_up_cores
 	}
 
 	/// Forcibly enact the candidate with the given ID as though it had been deemed available
-	/// by bitfields.
+	/// by bitfields, along with the candidates chained to it.
 	///
 	/// Is a no-op if there is no candidate pending availability for this para-id.
 	/// This should generally not be used but it is useful during execution of Runtime APIs,
@@ -864,19 +1029,24 @@ impl<T: Config> Pallet<T> {
 	pub(crate) fn force_enact(para: ParaId) {
 		let pending = <PendingAvailability<T>>::take(&para);
 		let commitments = <PendingAvailabilityCommitments<T>>::take(&para);
+		let descendants = <PendingAvailabilityDescendants<T>>::take(&para);
 
 		if let (Some(pending), Some(commitments)) = (pending, commitments) {
-			let candidate =
-				CommittedCandidateReceipt { descriptor: pending.descriptor, commitments };
-
-			Self::enact_candidate(
-				pending.relay_parent_number,
-				candidate,
-				pending.backers,
-				pending.availability_votes,
-				pending.core,
-				pending.backing_group,
-			);
+			for (pending, commitments) in
+				sp_std::iter::once((pending, commitments)).chain(descendants)
+			{
+				let candidate =
+					CommittedCandidateReceipt { descriptor: pending.descriptor, commitments };
+
+				Self::enact_candidate(
+					pending.relay_parent_number,
+					candidate,
+					pending.backers,
+					pending.availability_votes,
+					pending.core,
+					pending.backing_group,
+				);
+			}
 		}
 	}
 
@@ -897,6 +1067,23 @@ impl<T: Config> Pallet<T> {
 	) -> Option<CandidatePendingAvailability<T::Hash, T::BlockNumber>> {
 		<PendingAvailability<T>>::get(&para)
 	}
+
+	/// Returns the metadata around the candidate of the para provided which is pending
+	/// availability on the given core, if any.
+	pub(crate) fn pending_availability_on_core(
+		para: ParaId,
+		core: CoreIndex,
+	) -> Option<CandidatePendingAvailability<T::Hash, T::BlockNumber>> {
+		let pending = <PendingAvailability<T>>::get(&para)?;
+		if pending.core == core {
+			return Some(pending)
+		}
+
+		<PendingAvailabilityDescendants<T>>::get(&para)
+			.into_iter()
+			.map(|(d, _)| d)
+			.find(|d| d.core == core)
+	}
 }
 
 const fn availability_threshold(n_validators: usize) -> usize {
@@ -951,6 +1138,9 @@ impl<T: Config> CandidateCheckContext<T> {
 
 	/// Execute verification of the candidate.
 	///
+	/// `chained` are the outputs of the candidates of the same para backed before this one in
+	/// the same block, which the candidate builds upon.
+	///
 	/// Assures:
 	///  * correct expected relay parent reference
 	///  * collator signature check passes
@@ -962,6 +1152,7 @@ impl<T: Config> CandidateCheckContext<T> {
 		parent_storage_root: T::Hash,
 		candidate_idx: usize,
 		backed_candidate: &BackedCandidate<<T as frame_system::Config>::Hash>,
+		chained: &ChainedOutputs<T::BlockNumber>,
 	) -> Result<Result<(), FailedToCreatePVD>, Error<T>> {
 		let para_id = backed_candidate.descriptor().para_id;
 		let now = <frame_system::Pallet<T>>::block_number();
@@ -969,7 +1160,7 @@ impl<T: Config> CandidateCheckContext<T> {
 
 		{
 			// this should never fail because the para is registered
-			let persisted_validation_data = match crate::util::make_persisted_validation_data::<T>(
+			let mut persisted_validation_data = match crate::util::make_persisted_validation_data::<T>(
 				para_id,
 				relay_parent_number,
 				parent_storage_root,
@@ -978,6 +1169,10 @@ impl<T: Config> CandidateCheckContext<T> {
 				None => return Ok(Err(FailedToCreatePVD)),
 			};
 
+			if let Some(ref head_data) = chained.head_data {
+				persisted_validation_data.parent_head = head_data.clone();
+			}
+
 			let expected = persisted_validation_data.hash();
 
 			ensure!(
@@ -1018,6 +1213,7 @@ impl<T: Config> CandidateCheckContext<T> {
 			&backed_candidate.candidate.commitments.upward_messages,
 			T::BlockNumber::from(backed_candidate.candidate.commitments.hrmp_watermark),
 			&backed_candidate.candidate.commitments.horizontal_messages,
+			chained,
 		) {
 			log::debug!(
 				target: LOG_TARGET,
@@ -1033,6 +1229,12 @@ impl<T: Config> CandidateCheckContext<T> {
 
 	/// Check the given outputs after candidate validation on whether it passes the acceptance
 	/// criteria.
+	///
+	/// A candidate chained to others is checked against the relay-chain state the first candidate
+	/// of the chain is checked against, taking the outputs of the chain into account where they
+	/// affect the acceptance criteria. In particular, the messages sent by the chain count towards
+	/// the limits of the upward queue and of the HRMP channels, as they are not enqueued until the
+	/// chain is enacted.
 	fn check_validation_outputs(
 		&self,
 		para_id: ParaId,
@@ -1042,6 +1244,7 @@ impl<T: Config> CandidateCheckContext<T> {
 		upward_messages: &[primitives::UpwardMessage],
 		hrmp_watermark: T::BlockNumber,
 		horizontal_messages: &[primitives::OutboundHrmpMessage<ParaId>],
+		chained: &ChainedOutputs<T::BlockNumber>,
 	) -> Result<(), AcceptanceCheckErr<T::BlockNumber>> {
 		ensure!(
 			head_data.0.len() <= self.config.max_head_data_size as _,
@@ -1051,7 +1254,7 @@ impl<T: Config> CandidateCheckContext<T> {
 		// if any, the code upgrade attempt is allowed.
 		if let Some(new_validation_code) = new_validation_code {
 			ensure!(
-				<paras::Pallet<T>>::can_upgrade_validation_code(para_id),
+				!chained.code_upgrade && <paras::Pallet<T>>::can_upgrade_validation_code(para_id),
 				AcceptanceCheckErr::PrematureCodeUpgrade,
 			);
 			ensure!(
@@ -1061,10 +1264,33 @@ impl<T: Config> CandidateCheckContext<T> {
 		}
 
 		// check if the candidate passes the messaging acceptance criteria
-		<dmp::Pallet<T>>::check_processed_downward_messages(para_id, processed_downward_messages)?;
-		<ump::Pallet<T>>::check_upward_messages(&self.config, para_id, upward_messages)?;
+		<dmp::Pallet<T>>::check_processed_downward_messages(
+			para_id,
+			chained.processed_downward_messages.saturating_add(processed_downward_messages),
+		)?;
+		<ump::Pallet<T>>::check_upward_messages(
+			&self.config,
+			para_id,
+			upward_messages,
+			chained.upward_messages,
+		)?;
 		<hrmp::Pallet<T>>::check_hrmp_watermark(para_id, self.relay_parent_number, hrmp_watermark)?;
-		<hrmp::Pallet<T>>::check_outbound_hrmp(&self.config, para_id, horizontal_messages)?;
+		if let Some(last_watermark) = chained.hrmp_watermark {
+			// the watermark of the chain must advance as well, unless it stays at the relay parent.
+			ensure!(
+				hrmp_watermark > last_watermark || hrmp_watermark == self.relay_parent_number,
+				hrmp::HrmpWatermarkAcceptanceErr::AdvancementRule {
+					new_watermark: hrmp_watermark,
+					last_watermark,
+				},
+			);
+		}
+		<hrmp::Pallet<T>>::check_outbound_hrmp(
+			&self.config,
+			para_id,
+			horizontal_messages,
+			&chained.horizontal_messages,
+		)?;
 
 		Ok(())
 	}
@@ -32,9 +32,9 @@ use futures::executor::block_on;
 use keyring::Sr25519Keyring;
 use primitives::{
 	BlockNumber, CandidateCommitments, CandidateDescriptor, CollatorId,
-	CompactStatement as Statement, Hash, SignedAvailabilityBitfield, SignedStatement,
-	UncheckedSignedAvailabilityBitfield, ValidationCode, ValidatorId, ValidityAttestation,
-	PARACHAIN_KEY_TYPE_ID,
+	CompactStatement as Statement, Hash, HrmpChannelId, OutboundHrmpMessage,
+	SignedAvailabilityBitfield, SignedStatement, UncheckedSignedAvailabilityBitfield,
+	ValidationCode, ValidatorId, ValidityAttestation, PARACHAIN_KEY_TYPE_ID,
 };
 use sc_keystore::LocalKeystore;
 use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
@@ -1841,6 +1841,308 @@ fn can_include_candidate_with_ok_code_upgrade() {
 	});
 }
 
+#[test]
+fn chained_candidates_are_backed_and_enacted_in_order() {
+	let chain_a = ParaId::from(1_u32);
+
+	// The block number of the relay-parent for testing.
+	const RELAY_PARENT_NUM: BlockNumber = 4;
+
+	let paras = vec![(chain_a, ParaKind::Parachain)];
+	let validators = vec![
+		Sr25519Keyring::Alice,
+		Sr25519Keyring::Bob,
+		Sr25519Keyring::Charlie,
+		Sr25519Keyring::Dave,
+		Sr25519Keyring::Ferdie,
+	];
+	let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+	for validator in validators.iter() {
+		SyncCryptoStore::sr25519_generate_new(
+			&*keystore,
+			PARACHAIN_KEY_TYPE_ID,
+			Some(&validator.to_seed()),
+		)
+		.unwrap();
+	}
+	let validator_public = validator_pubkeys(&validators);
+
+	new_test_ext(genesis_config(paras)).execute_with(|| {
+		shared::Pallet::<Test>::set_active_validators_ascending(validator_public.clone());
+		shared::Pallet::<Test>::set_session_index(5);
+
+		run_to_block(5, |_| None);
+
+		let signing_context =
+			SigningContext { parent_hash: System::parent_hash(), session_index: 5 };
+
+		let group_validators = |group_index: GroupIndex| {
+			match group_index {
+				group_index if group_index == GroupIndex::from(0) => Some(vec![0, 1, 2]),
+				group_index if group_index == GroupIndex::from(1) => Some(vec![3, 4]),
+				_ => panic!("Group index out of bounds for 2 cores"),
+			}
+			.map(|vs| vs.into_iter().map(ValidatorIndex).collect::<Vec<_>>())
+		};
+
+		// chain A is assigned an extra core.
+		let core_lookup = |core| match core {
+			core if core == CoreIndex::from(0) => Some(chain_a),
+			core if core == CoreIndex::from(1) => Some(chain_a),
+			_ => panic!("Core out of bounds for 2 cores."),
+		};
+
+		let assignments = vec![
+			CoreAssignment {
+				core: CoreIndex::from(0),
+				para_id: chain_a,
+				kind: AssignmentKind::Parachain,
+				group_idx: GroupIndex::from(0),
+			},
+			CoreAssignment {
+				core: CoreIndex::from(1),
+				para_id: chain_a,
+				kind: AssignmentKind::Parachain,
+				group_idx: GroupIndex::from(1),
+			},
+		];
+
+		let mut candidate_a = TestCandidateBuilder {
+			para_id: chain_a,
+			relay_parent: System::parent_hash(),
+			pov_hash: Hash::repeat_byte(1),
+			head_data: vec![1].into(),
+			persisted_validation_data_hash: make_vdata_hash(chain_a).unwrap(),
+			hrmp_watermark: RELAY_PARENT_NUM,
+			..Default::default()
+		}
+		.build();
+		collator_sign_candidate(Sr25519Keyring::One, &mut candidate_a);
+
+		// candidate B builds on top of candidate A.
+		let chained_vdata_hash = {
+			let mut persisted_validation_data =
+				crate::util::make_persisted_validation_data::<Test>(
+					chain_a,
+					RELAY_PARENT_NUM,
+					Default::default(),
+				)
+				.unwrap();
+			persisted_validation_data.parent_head = vec![1].into();
+			persisted_validation_data.hash()
+		};
+		let mut candidate_b = TestCandidateBuilder {
+			para_id: chain_a,
+			relay_parent: System::parent_hash(),
+			pov_hash: Hash::repeat_byte(2),
+			head_data: vec![2].into(),
+			persisted_validation_data_hash: chained_vdata_hash,
+			hrmp_watermark: RELAY_PARENT_NUM,
+			..Default::default()
+		}
+		.build();
+		collator_sign_candidate(Sr25519Keyring::Two, &mut candidate_b);
+
+		let backed_a = block_on(back_candidate(
+			candidate_a.clone(),
+			&validators,
+			group_validators(GroupIndex::from(0)).unwrap().as_ref(),
+			&keystore,
+			&signing_context,
+			BackingKind::Threshold,
+		));
+		let backed_b = block_on(back_candidate(
+			candidate_b.clone(),
+			&validators,
+			group_validators(GroupIndex::from(1)).unwrap().as_ref(),
+			&keystore,
+			&signing_context,
+			BackingKind::Threshold,
+		));
+
+		// a chained candidate must not be backed before the one it builds on.
+		assert_noop!(
+			ParaInclusion::process_candidates(
+				Default::default(),
+				vec![backed_b.clone(), backed_a.clone()],
+				assignments.clone(),
+				&group_validators,
+			),
+			Error::<Test>::ValidationDataHashMismatch
+		);
+
+		let ProcessedCandidates { core_indices: occupied_cores, .. } =
+			ParaInclusion::process_candidates(
+				Default::default(),
+				vec![backed_a, backed_b],
+				assignments,
+				&group_validators,
+			)
+			.expect("candidates scheduled, in order, and backed");
+
+		assert_eq!(occupied_cores, vec![CoreIndex::from(0), CoreIndex::from(1)]);
+		assert_eq!(<PendingAvailability<Test>>::get(&chain_a).unwrap().hash, candidate_a.hash());
+		assert_eq!(
+			<PendingAvailability<Test>>::get(&chain_a).unwrap().hash,
+			ParaInclusion::pending_availability_on_core(chain_a, CoreIndex::from(0))
+				.unwrap()
+				.hash,
+		);
+		assert_eq!(
+			ParaInclusion::pending_availability_on_core(chain_a, CoreIndex::from(1))
+				.unwrap()
+				.hash,
+			candidate_b.hash(),
+		);
+		assert_eq!(<PendingAvailabilityDescendants<Test>>::get(&chain_a).len(), 1);
+
+		let expected_bits = 2;
+		let sign_bitfields = |available: &[usize]| {
+			let mut bare_bitfield =
+				AvailabilityBitfield(bitvec::bitvec![u8, BitOrderLsb0; 0; expected_bits]);
+			for core in available {
+				*bare_bitfield.0.get_mut(*core).unwrap() = true;
+			}
+
+			validators
+				.iter()
+				.enumerate()
+				.take(4)
+				.map(|(i, key)| {
+					block_on(sign_bitfield(
+						&keystore,
+						key,
+						ValidatorIndex(i as _),
+						bare_bitfield.clone(),
+						&signing_context,
+					))
+					.into()
+				})
+				.collect::<Vec<_>>()
+		};
+
+		// candidate B is available, but can't be enacted before candidate A.
+		assert_matches!(
+			ParaInclusion::process_bitfields(
+				expected_bits,
+				sign_bitfields(&[1]),
+				DisputedBitfield::zeros(expected_bits),
+				&core_lookup,
+				FullCheck::Yes,
+			),
+			Ok(v) => {
+				assert!(v.is_empty());
+			}
+		);
+		assert_eq!(Paras::para_head(&chain_a), Some(Vec::new().into()));
+
+		// once candidate A is available as well, both are enacted in order.
+		assert_matches!(
+			ParaInclusion::process_bitfields(
+				expected_bits,
+				sign_bitfields(&[0]),
+				DisputedBitfield::zeros(expected_bits),
+				&core_lookup,
+				FullCheck::Yes,
+			),
+			Ok(v) => {
+				assert_eq!(
+					v,
+					vec![
+						(CoreIndex::from(0), candidate_a.hash()),
+						(CoreIndex::from(1), candidate_b.hash()),
+					],
+				);
+			}
+		);
+
+		assert_eq!(Paras::para_head(&chain_a), Some(vec![2].into()));
+		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
+		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_none());
+		assert!(<PendingAvailabilityDescendants<Test>>::get(&chain_a).is_empty());
+	});
+}
+
+#[test]
+fn chained_outputs_count_towards_message_limits() {
+	let chain_a = ParaId::from(1_u32);
+	let chain_b = ParaId::from(2_u32);
+
+	// The block number of the relay-parent for testing.
+	const RELAY_PARENT_NUM: BlockNumber = 4;
+
+	let paras = vec![(chain_a, ParaKind::Parachain), (chain_b, ParaKind::Parachain)];
+	let mut genesis = genesis_config(paras);
+	let config = &mut genesis.configuration.config;
+	config.max_upward_queue_count = 3;
+	config.max_upward_queue_size = 100;
+	config.max_upward_message_size = 10;
+	config.max_upward_message_num_per_candidate = 2;
+	config.hrmp_max_message_num_per_candidate = 1;
+	new_test_ext(genesis).execute_with(|| {
+		run_to_block(5, |_| None);
+
+		let channel_id = HrmpChannelId { sender: chain_a, recipient: chain_b };
+		<hrmp::HrmpChannels<Test>>::insert(
+			&channel_id,
+			hrmp::HrmpChannel {
+				max_capacity: 1,
+				max_total_size: 10,
+				max_message_size: 10,
+				msg_count: 0,
+				total_size: 0,
+				mqc_head: None,
+				sender_deposit: 0,
+				recipient_deposit: 0,
+			},
+		);
+
+		let check_ctx = CandidateCheckContext::<Test>::new(5, RELAY_PARENT_NUM);
+		let check = |commitments: &CandidateCommitments, chained: &ChainedOutputs<BlockNumber>| {
+			check_ctx.check_validation_outputs(
+				chain_a,
+				&commitments.head_data,
+				&commitments.new_validation_code,
+				commitments.processed_downward_messages,
+				&commitments.upward_messages,
+				commitments.hrmp_watermark,
+				&commitments.horizontal_messages,
+				chained,
+			)
+		};
+
+		let commitments = CandidateCommitments {
+			upward_messages: vec![vec![1; 10], vec![2; 10]],
+			horizontal_messages: vec![OutboundHrmpMessage { recipient: chain_b, data: vec![3] }],
+			hrmp_watermark: RELAY_PARENT_NUM,
+			..Default::default()
+		};
+
+		// each candidate on its own fits into the upward queue and the channel.
+		let mut chained = ChainedOutputs::default();
+		assert!(check(&commitments, &chained).is_ok());
+
+		// but a candidate chained to another one doesn't, as the messages of both are enqueued.
+		chained.append(&commitments);
+		assert_matches!(
+			check(&commitments, &chained),
+			Err(AcceptanceCheckErr::UpwardMessages(ump::AcceptanceCheckErr::CapacityExceeded {
+				count: 4,
+				limit: 3,
+			}))
+		);
+
+		let commitments =
+			CandidateCommitments { upward_messages: Vec::new(), ..commitments.clone() };
+		assert_matches!(
+			check(&commitments, &chained),
+			Err(AcceptanceCheckErr::OutboundHrmp(
+				hrmp::OutboundHrmpAcceptanceErr::CapacityExceeded { idx: 0, count: 2, limit: 1 }
+			))
+		);
+	});
+}
+
 #[test]
 fn session_change_wipes() {
 	let chain_a = ParaId::from(1_u32);
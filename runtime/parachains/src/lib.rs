@@ -23,13 +23,16 @@
 #![cfg_attr(feature = "runtime-benchmarks", recursion_limit = "256")]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod bulk;
 pub mod configuration;
 pub mod disputes;
 pub mod dmp;
 pub mod hrmp;
 pub mod inclusion;
 pub mod initializer;
+pub mod message_queue;
 pub mod metrics;
+pub mod on_demand;
 pub mod origin;
 pub mod paras;
 pub mod paras_inherent;
@@ -0,0 +1,102 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{Pallet as MessageQueue, *};
+use frame_system::RawOrigin;
+
+/// A message of `HeapSize` bytes, which is not valid for any processor we know of, so the
+/// processing itself is cheap.
+fn heap_sized_message<T: Config>() -> Vec<u8> {
+	vec![0xff; T::HeapSize::get() as usize]
+}
+
+frame_benchmarking::benchmarks! {
+	where_clause { where MessageOriginOf<T>: From<u32> }
+
+	// Servicing without any queue to service.
+	service_queue_base {
+	}: {
+		MessageQueue::<T>::service_queues(Weight::MAX);
+	}
+
+	// Visiting the page of a queue with a single empty message, which leaves the ring.
+	service_page_base {
+		let origin: MessageOriginOf<T> = 0.into();
+		let other: MessageOriginOf<T> = 1.into();
+		MessageQueue::<T>::enqueue_messages(vec![vec![]], other);
+		MessageQueue::<T>::enqueue_messages(vec![vec![]], origin.clone());
+		let mut weight_used = Weight::zero();
+	}: {
+		MessageQueue::<T>::service_page(&origin, &mut weight_used, Weight::MAX, &mut u32::MAX);
+	}
+	verify {
+		assert!(!BookStateFor::<T>::contains_key(&origin));
+	}
+
+	// Visiting a single message of `HeapSize` bytes.
+	service_page_item {
+		let origin: MessageOriginOf<T> = 0.into();
+		MessageQueue::<T>::enqueue_messages(vec![heap_sized_message::<T>()], origin.clone());
+		let mut book = BookStateFor::<T>::get(&origin);
+		let mut page = Pages::<T>::get(&origin, 0).unwrap();
+		let mut weight_used = Weight::zero();
+	}: {
+		assert!(MessageQueue::<T>::service_page_item(
+			&origin,
+			0,
+			&mut page,
+			&mut book,
+			&mut weight_used,
+			Weight::MAX,
+			&mut u32::MAX,
+		));
+	}
+	verify {
+		assert!(page.is_visited());
+	}
+
+	bump_service_head {
+		let origin: MessageOriginOf<T> = 0.into();
+		let other: MessageOriginOf<T> = 1.into();
+		MessageQueue::<T>::enqueue_messages(vec![vec![]], origin.clone());
+		MessageQueue::<T>::enqueue_messages(vec![vec![]], other.clone());
+	}: {
+		MessageQueue::<T>::bump_service_head(&origin);
+	}
+	verify {
+		assert_eq!(ServiceHead::<T>::get(), Some(other));
+	}
+
+	execute_overweight {
+		let origin: MessageOriginOf<T> = 0.into();
+		MessageQueue::<T>::enqueue_messages(vec![heap_sized_message::<T>()], origin.clone());
+		// Pretend the message was visited and kept as overweight.
+		Pages::<T>::mutate(&origin, 0, |page| {
+			if let Some(page) = page {
+				page.first = 1;
+			}
+		});
+	}: _(RawOrigin::Root, origin.clone(), 0, 0, Weight::MAX)
+	verify {
+		assert!(Pages::<T>::get(&origin, 0).is_none());
+	}
+}
+
+frame_benchmarking::impl_benchmark_test_suite!(
+	MessageQueue,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
@@ -0,0 +1,786 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A generic queue of opaque messages which are processed in the background.
+//!
+//! Messages are enqueued for an origin, e.g. the para which sent them, and every origin has a
+//! queue of its own. The queues are stored in pages of up to `HeapSize` bytes, so the cost of
+//! enqueuing and processing a message does not depend on the length of its queue.
+//!
+//! The queues with messages to be processed are kept in a ring and serviced round-robin, one page
+//! of a queue after the other: with `ServiceWeight` at the beginning of every block and with the
+//! remaining weight of the block in `on_idle`. The messages of a queue are processed in order, so
+//! servicing stops as soon as a message needs more weight than is left. It also stops once
+//! `MaxMessagesPerBlock` messages were visited in the block, as the weight of a message does not
+//! account for the proof size of reading its page.
+//!
+//! A message which needs more than `MaxMessageWeight` is never processed in the background.
+//! Instead it is skipped and kept as overweight, to be executed with `execute_overweight`. Queues
+//! which are paused by `QueuePausedQuery` are skipped as well, until they are resumed.
+
+use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
+use frame_system::pallet_prelude::*;
+use parity_scale_codec::FullCodec;
+use primitives::vstaging::QueueFootprint;
+use sp_runtime::traits::Saturating;
+use sp_std::{fmt::Debug, prelude::*};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+/// The index of a page of a queue.
+pub type PageIndex = u32;
+
+/// Simple type used to identify messages for the purpose of reporting events. Secure if and only
+/// if the message content is unique.
+pub type MessageId = [u8; 32];
+
+/// The origin messages are enqueued for.
+pub type MessageOriginOf<T> = <<T as Config>::MessageProcessor as ProcessMessage>::Origin;
+
+/// Why a message could not be processed.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub enum ProcessMessageError {
+	/// The message could not be decoded.
+	BadFormat,
+	/// The message is of a kind or version which is not supported.
+	Unsupported,
+	/// Processing the message needs the given weight, which is more than it may use.
+	Overweight(Weight),
+}
+
+/// Something which processes the messages of the queues.
+pub trait ProcessMessage {
+	/// The origin messages are enqueued for.
+	type Origin: FullCodec + Clone + Eq + TypeInfo + Debug + 'static;
+
+	/// Process `message` from `origin` with at most `weight_limit`, returning the weight used.
+	///
+	/// The message must not have any effect if `ProcessMessageError::Overweight` is returned, as
+	/// it will be processed again later. Any other error drops the message.
+	fn process_message(
+		message: &[u8],
+		origin: Self::Origin,
+		weight_limit: Weight,
+	) -> Result<Weight, ProcessMessageError>;
+}
+
+/// Something which enqueues messages to be processed in the background.
+pub trait EnqueueMessage<Origin> {
+	/// Enqueue `messages` at the back of the queue of `origin`.
+	fn enqueue_messages(messages: Vec<Vec<u8>>, origin: Origin);
+
+	/// Remove the queue of `origin` with all of its messages, including the overweight ones.
+	///
+	/// Returns the number of pages removed.
+	fn sweep_queue(origin: Origin) -> u32;
+
+	/// The number and total size of the messages of `origin` which are still to be processed.
+	///
+	/// Overweight messages are not part of the footprint.
+	fn footprint(origin: Origin) -> QueueFootprint;
+}
+
+/// Notified whenever the footprint of a queue changes.
+pub trait OnQueueChanged<Origin> {
+	/// The footprint of the queue of `origin` changed to `footprint`.
+	fn on_queue_changed(origin: Origin, footprint: QueueFootprint);
+}
+
+impl<Origin> OnQueueChanged<Origin> for () {
+	fn on_queue_changed(_: Origin, _: QueueFootprint) {}
+}
+
+/// Decides which queues are paused.
+pub trait QueuePausedQuery<Origin> {
+	/// Whether the queue of `origin` is paused.
+	fn is_paused(origin: &Origin) -> bool;
+}
+
+impl<Origin> QueuePausedQuery<Origin> for () {
+	fn is_paused(_: &Origin) -> bool {
+		false
+	}
+}
+
+/// A message of a page.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct PageItem {
+	/// Whether the message was processed or dropped already.
+	pub processed: bool,
+	/// The message.
+	pub payload: Vec<u8>,
+}
+
+/// A page of a queue.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct Page {
+	/// The messages of the page, in the order they were enqueued.
+	pub items: Vec<PageItem>,
+	/// The index of the first message which was not visited by the background servicing yet.
+	pub first: u32,
+	/// The number of messages which were not processed yet, including the overweight ones.
+	pub remaining: u32,
+	/// The total size of the messages of the page, in bytes.
+	pub size: u32,
+}
+
+impl Page {
+	/// Whether all messages of the page were visited by the background servicing.
+	fn is_visited(&self) -> bool {
+		self.first as usize >= self.items.len()
+	}
+}
+
+/// The neighbours of a queue in the ring of queues to be serviced.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct Neighbours<Origin> {
+	/// The queue serviced before this one.
+	pub prev: Origin,
+	/// The queue serviced after this one.
+	pub next: Origin,
+}
+
+/// The state of the queue of an origin.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, TypeInfo, RuntimeDebug)]
+pub struct BookState<Origin> {
+	/// The first page with messages which were not visited yet.
+	pub begin: PageIndex,
+	/// The index following the last page.
+	pub end: PageIndex,
+	/// The number of pages in storage, including those only kept for their overweight messages.
+	pub count: PageIndex,
+	/// The neighbours of the queue in the ring of queues to be serviced, if it has messages which
+	/// were not visited yet.
+	pub ready_neighbours: Option<Neighbours<Origin>>,
+	/// The number of messages which were not visited yet.
+	pub message_count: u64,
+	/// The total size of the messages which were not visited yet, in bytes.
+	pub size: u64,
+}
+
+impl<Origin> Default for BookState<Origin> {
+	fn default() -> Self {
+		Self { begin: 0, end: 0, count: 0, ready_neighbours: None, message_count: 0, size: 0 }
+	}
+}
+
+impl<Origin> BookState<Origin> {
+	fn footprint(&self) -> QueueFootprint {
+		QueueFootprint { count: self.message_count, size: self.size }
+	}
+}
+
+/// Weight information of this pallet.
+pub trait WeightInfo {
+	fn service_queue_base() -> Weight;
+	fn service_page_base() -> Weight;
+	fn service_page_item() -> Weight;
+	fn bump_service_head() -> Weight;
+	fn execute_overweight() -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+///
+/// Servicing the queues is free, so only the weight of the messages decides how many of them are
+/// processed.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn service_queue_base() -> Weight {
+		Weight::zero()
+	}
+
+	fn service_page_base() -> Weight {
+		Weight::zero()
+	}
+
+	fn service_page_item() -> Weight {
+		Weight::zero()
+	}
+
+	fn bump_service_head() -> Weight {
+		Weight::zero()
+	}
+
+	fn execute_overweight() -> Weight {
+		Weight::MAX
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The outer event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Processes the messages of the queues.
+		type MessageProcessor: ProcessMessage;
+
+		/// Notified whenever the footprint of a queue changes.
+		type QueueChangeHandler: OnQueueChanged<MessageOriginOf<Self>>;
+
+		/// Decides which queues are paused. Messages are still enqueued for paused queues, but
+		/// not processed until the queue is resumed.
+		type QueuePausedQuery: QueuePausedQuery<MessageOriginOf<Self>>;
+
+		/// The total size of the messages of a page, in bytes. A larger message gets a page of
+		/// its own.
+		#[pallet::constant]
+		type HeapSize: Get<u32>;
+
+		/// The weight to service the queues with at the beginning of every block, if any.
+		type ServiceWeight: Get<Option<Weight>>;
+
+		/// The weight a message may need to be processed in the background. Messages which need
+		/// more are kept as overweight.
+		///
+		/// This should not be more than `ServiceWeight`, so all other messages can be processed
+		/// eventually.
+		type MaxMessageWeight: Get<Weight>;
+
+		/// The maximum number of messages visited in the background per block.
+		///
+		/// The weight of a message does not account for the proof size of reading its page, so
+		/// this bounds the proof size of servicing the queues.
+		#[pallet::constant]
+		type MaxMessagesPerBlock: Get<u32>;
+
+		/// Origin which is allowed to execute overweight messages.
+		type ExecuteOverweightOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A message was processed.
+		Processed { id: MessageId, origin: MessageOriginOf<T>, weight_used: Weight },
+		/// A message could not be processed and was dropped.
+		ProcessingFailed { id: MessageId, origin: MessageOriginOf<T>, error: ProcessMessageError },
+		/// A message needs more than `MaxMessageWeight` and was kept as overweight, to be executed
+		/// with `execute_overweight`.
+		OverweightEnqueued {
+			id: MessageId,
+			origin: MessageOriginOf<T>,
+			page_index: PageIndex,
+			message_index: u32,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// There is no message at the given position which still needs to be processed.
+		NoMessage,
+		/// The message was not visited by the background servicing yet.
+		NotOverweight,
+		/// The queue of the message is paused.
+		QueuePaused,
+		/// The weight limit is not enough for executing the message.
+		InsufficientWeight,
+	}
+
+	/// The state of the queue of every origin with messages.
+	#[pallet::storage]
+	pub(super) type BookStateFor<T: Config> =
+		StorageMap<_, Twox64Concat, MessageOriginOf<T>, BookState<MessageOriginOf<T>>, ValueQuery>;
+
+	/// The queue to be serviced next, if any queue has messages to be processed.
+	#[pallet::storage]
+	pub(super) type ServiceHead<T: Config> = StorageValue<_, MessageOriginOf<T>, OptionQuery>;
+
+	/// The block the queues were last serviced in and the number of messages visited in it.
+	#[pallet::storage]
+	pub(super) type MessagesVisited<T: Config> =
+		StorageValue<_, (T::BlockNumber, u32), OptionQuery>;
+
+	/// The pages of the queues.
+	#[pallet::storage]
+	pub(super) type Pages<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		MessageOriginOf<T>,
+		Twox64Concat,
+		PageIndex,
+		Page,
+		OptionQuery,
+	>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			match T::ServiceWeight::get() {
+				Some(weight_limit) => Self::service_queues(weight_limit),
+				None => Weight::zero(),
+			}
+		}
+
+		fn on_idle(_now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::service_queues(remaining_weight)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Execute an overweight message.
+		///
+		/// - `origin`: Must pass `ExecuteOverweightOrigin`.
+		/// - `message_origin`: The origin the message was enqueued for.
+		/// - `page_index`: The page of the message.
+		/// - `index`: The index of the message in its page.
+		/// - `weight_limit`: The amount of weight that message execution may take.
+		///
+		/// Errors:
+		/// - `NoMessage`: There is no unprocessed message at the position.
+		/// - `NotOverweight`: The message was not visited by the background servicing yet.
+		/// - `QueuePaused`: The queue of `message_origin` is paused.
+		/// - `InsufficientWeight`: Message execution may use greater than `weight_limit`.
+		#[pallet::call_index(0)]
+		#[pallet::weight(weight_limit.saturating_add(<T as Config>::WeightInfo::execute_overweight()))]
+		pub fn execute_overweight(
+			origin: OriginFor<T>,
+			message_origin: MessageOriginOf<T>,
+			page_index: PageIndex,
+			index: u32,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			T::ExecuteOverweightOrigin::ensure_origin(origin)?;
+
+			let used =
+				Self::do_execute_overweight(message_origin, page_index, index, weight_limit)?;
+			Ok(Some(used.saturating_add(<T as Config>::WeightInfo::execute_overweight())).into())
+		}
+	}
+}
+
+/// The outcome of servicing a page of a queue.
+struct PageOutcome {
+	/// Whether any message was visited.
+	progressed: bool,
+	/// Whether servicing stopped, because a message needs more weight than is left.
+	exhausted: bool,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Service the queues round-robin with at most `weight_limit`, returning the weight used.
+	///
+	/// Servicing stops once a message needs more weight than is left, once `MaxMessagesPerBlock`
+	/// messages were visited in the block, or after a full round over the ready queues without
+	/// processing anything.
+	pub(crate) fn service_queues(weight_limit: Weight) -> Weight {
+		let mut weight_used = <T as Config>::WeightInfo::service_queue_base();
+		if weight_used.any_gt(weight_limit) {
+			return Weight::zero()
+		}
+
+		let now = frame_system::Pallet::<T>::block_number();
+		let visited = match MessagesVisited::<T>::get() {
+			Some((block, visited)) if block == now => visited,
+			_ => 0,
+		};
+		let mut messages_left = T::MaxMessagesPerBlock::get().saturating_sub(visited);
+		if messages_left == 0 {
+			return weight_used
+		}
+		let messages_before = messages_left;
+
+		// The first queue of a round without any progress.
+		let mut idle_since = None;
+		while let Some(origin) = ServiceHead::<T>::get() {
+			let overhead = <T as Config>::WeightInfo::service_page_base()
+				.saturating_add(<T as Config>::WeightInfo::bump_service_head());
+			if weight_used.saturating_add(overhead).any_gt(weight_limit) {
+				break
+			}
+			weight_used.saturating_accrue(overhead);
+
+			let outcome =
+				Self::service_page(&origin, &mut weight_used, weight_limit, &mut messages_left);
+			if outcome.exhausted {
+				// Continue with the same queue next time.
+				break
+			}
+
+			if outcome.progressed {
+				idle_since = None;
+			} else if idle_since.is_none() {
+				idle_since = Some(origin.clone());
+			}
+
+			Self::bump_service_head(&origin);
+			if idle_since.is_some() && ServiceHead::<T>::get() == idle_since {
+				break
+			}
+		}
+
+		if messages_left < messages_before {
+			let visited = visited.saturating_add(messages_before - messages_left);
+			MessagesVisited::<T>::put((now, visited));
+		}
+
+		weight_used
+	}
+
+	/// Visit the messages of the first page of the queue of `origin` which were not visited yet,
+	/// at most `messages_left` of them.
+	fn service_page(
+		origin: &MessageOriginOf<T>,
+		weight_used: &mut Weight,
+		weight_limit: Weight,
+		messages_left: &mut u32,
+	) -> PageOutcome {
+		let mut outcome = PageOutcome { progressed: false, exhausted: false };
+		if T::QueuePausedQuery::is_paused(origin) {
+			return outcome
+		}
+
+		let mut book = BookStateFor::<T>::get(origin);
+		let page_index = book.begin;
+		let mut page = match Pages::<T>::get(origin, page_index) {
+			Some(page) if book.begin < book.end => page,
+			_ => {
+				// A queue in the ring always has a page with messages to be visited.
+				frame_support::defensive!("Ready queue without a page to service");
+				Self::unknit(origin, &mut book);
+				BookStateFor::<T>::insert(origin, book);
+				return outcome
+			},
+		};
+
+		while !page.is_visited() {
+			if !Self::service_page_item(
+				origin,
+				page_index,
+				&mut page,
+				&mut book,
+				weight_used,
+				weight_limit,
+				messages_left,
+			) {
+				outcome.exhausted = true;
+				break
+			}
+			outcome.progressed = true;
+		}
+
+		if !outcome.progressed {
+			return outcome
+		}
+
+		if page.is_visited() {
+			book.begin += 1;
+			if page.remaining == 0 {
+				Pages::<T>::remove(origin, page_index);
+				book.count.saturating_dec();
+			} else {
+				Pages::<T>::insert(origin, page_index, page);
+			}
+		} else {
+			Pages::<T>::insert(origin, page_index, page);
+		}
+
+		if book.begin >= book.end {
+			Self::unknit(origin, &mut book);
+		}
+		let footprint = book.footprint();
+		if book.count == 0 {
+			BookStateFor::<T>::remove(origin);
+		} else {
+			BookStateFor::<T>::insert(origin, book);
+		}
+		T::QueueChangeHandler::on_queue_changed(origin.clone(), footprint);
+
+		outcome
+	}
+
+	/// Visit the first message of `page` which was not visited yet, returning whether it was
+	/// visited.
+	///
+	/// The message is not visited if no more messages may be visited, or if it needs more weight
+	/// than is left, unless it needs more than `MaxMessageWeight`.
+	fn service_page_item(
+		origin: &MessageOriginOf<T>,
+		page_index: PageIndex,
+		page: &mut Page,
+		book: &mut BookState<MessageOriginOf<T>>,
+		weight_used: &mut Weight,
+		weight_limit: Weight,
+		messages_left: &mut u32,
+	) -> bool {
+		if *messages_left == 0 {
+			return false
+		}
+
+		let item_weight = <T as Config>::WeightInfo::service_page_item();
+		if weight_used.saturating_add(item_weight).any_gt(weight_limit) {
+			return false
+		}
+
+		let message_index = page.first;
+		let item = &mut page.items[message_index as usize];
+		let id = sp_io::hashing::blake2_256(&item.payload);
+		let message_limit = weight_limit.saturating_sub(weight_used.saturating_add(item_weight));
+		match T::MessageProcessor::process_message(&item.payload, origin.clone(), message_limit) {
+			Ok(used) => {
+				weight_used.saturating_accrue(item_weight.saturating_add(used));
+				item.processed = true;
+				page.remaining.saturating_dec();
+				Self::deposit_event(Event::Processed {
+					id,
+					origin: origin.clone(),
+					weight_used: used,
+				});
+			},
+			Err(ProcessMessageError::Overweight(required))
+				if required.any_gt(T::MaxMessageWeight::get()) =>
+			{
+				weight_used.saturating_accrue(item_weight);
+				Self::deposit_event(Event::OverweightEnqueued {
+					id,
+					origin: origin.clone(),
+					page_index,
+					message_index,
+				});
+			},
+			// The messages are processed in order, so we don't skip it but wait for more weight.
+			Err(ProcessMessageError::Overweight(_)) => return false,
+			Err(error) => {
+				weight_used.saturating_accrue(item_weight);
+				item.processed = true;
+				page.remaining.saturating_dec();
+				Self::deposit_event(Event::ProcessingFailed { id, origin: origin.clone(), error });
+			},
+		}
+
+		book.message_count.saturating_dec();
+		book.size.saturating_reduce(item.payload.len() as u64);
+		page.first += 1;
+		*messages_left -= 1;
+		true
+	}
+
+	/// Move the service head to the queue following `origin`, unless `origin` left the ring.
+	fn bump_service_head(origin: &MessageOriginOf<T>) {
+		if ServiceHead::<T>::get().as_ref() != Some(origin) {
+			return
+		}
+		if let Some(neighbours) = BookStateFor::<T>::get(origin).ready_neighbours {
+			ServiceHead::<T>::put(neighbours.next);
+		}
+	}
+
+	/// Add the queue of `origin` to the end of the ring of queues to be serviced.
+	///
+	/// `book` is the state of the queue, which the caller needs to store.
+	fn knit(origin: &MessageOriginOf<T>, book: &mut BookState<MessageOriginOf<T>>) {
+		if book.ready_neighbours.is_some() {
+			return
+		}
+
+		let head = match ServiceHead::<T>::get() {
+			Some(head) => head,
+			None => {
+				ServiceHead::<T>::put(origin);
+				book.ready_neighbours =
+					Some(Neighbours { prev: origin.clone(), next: origin.clone() });
+				return
+			},
+		};
+
+		let mut head_book = BookStateFor::<T>::get(&head);
+		let head_neighbours = match head_book.ready_neighbours.as_mut() {
+			Some(neighbours) => neighbours,
+			None => {
+				frame_support::defensive!("The service head is always in the ring");
+				return
+			},
+		};
+		let tail = sp_std::mem::replace(&mut head_neighbours.prev, origin.clone());
+		if tail == head {
+			head_neighbours.next = origin.clone();
+		} else {
+			BookStateFor::<T>::mutate(&tail, |tail_book| {
+				if let Some(tail_neighbours) = tail_book.ready_neighbours.as_mut() {
+					tail_neighbours.next = origin.clone();
+				}
+			});
+		}
+		BookStateFor::<T>::insert(&head, head_book);
+
+		book.ready_neighbours = Some(Neighbours { prev: tail, next: head });
+	}
+
+	/// Remove the queue of `origin` from the ring of queues to be serviced.
+	///
+	/// `book` is the state of the queue, which the caller needs to store.
+	fn unknit(origin: &MessageOriginOf<T>, book: &mut BookState<MessageOriginOf<T>>) {
+		let neighbours = match book.ready_neighbours.take() {
+			Some(neighbours) => neighbours,
+			None => return,
+		};
+
+		if &neighbours.next == origin {
+			// The queue was the only one in the ring.
+			ServiceHead::<T>::kill();
+			return
+		}
+
+		BookStateFor::<T>::mutate(&neighbours.prev, |prev_book| {
+			if let Some(prev_neighbours) = prev_book.ready_neighbours.as_mut() {
+				prev_neighbours.next = neighbours.next.clone();
+			}
+		});
+		BookStateFor::<T>::mutate(&neighbours.next, |next_book| {
+			if let Some(next_neighbours) = next_book.ready_neighbours.as_mut() {
+				next_neighbours.prev = neighbours.prev.clone();
+			}
+		});
+		if ServiceHead::<T>::get().as_ref() == Some(origin) {
+			ServiceHead::<T>::put(neighbours.next);
+		}
+	}
+
+	fn do_execute_overweight(
+		origin: MessageOriginOf<T>,
+		page_index: PageIndex,
+		index: u32,
+		weight_limit: Weight,
+	) -> Result<Weight, DispatchError> {
+		ensure!(!T::QueuePausedQuery::is_paused(&origin), Error::<T>::QueuePaused);
+
+		let mut page = Pages::<T>::get(&origin, page_index).ok_or(Error::<T>::NoMessage)?;
+		let item = page.items.get_mut(index as usize).ok_or(Error::<T>::NoMessage)?;
+		ensure!(!item.processed, Error::<T>::NoMessage);
+		ensure!(index < page.first, Error::<T>::NotOverweight);
+
+		let id = sp_io::hashing::blake2_256(&item.payload);
+		let used =
+			match T::MessageProcessor::process_message(&item.payload, origin.clone(), weight_limit)
+			{
+				Ok(used) => {
+					Self::deposit_event(Event::Processed {
+						id,
+						origin: origin.clone(),
+						weight_used: used,
+					});
+					used
+				},
+				Err(ProcessMessageError::Overweight(_)) =>
+					return Err(Error::<T>::InsufficientWeight.into()),
+				Err(error) => {
+					Self::deposit_event(Event::ProcessingFailed {
+						id,
+						origin: origin.clone(),
+						error,
+					});
+					Weight::zero()
+				},
+			};
+		item.processed = true;
+		page.remaining.saturating_dec();
+
+		if page.remaining == 0 && page.is_visited() {
+			Pages::<T>::remove(&origin, page_index);
+			let mut book = BookStateFor::<T>::get(&origin);
+			book.count.saturating_dec();
+			if book.count == 0 {
+				BookStateFor::<T>::remove(&origin);
+			} else {
+				BookStateFor::<T>::insert(&origin, book);
+			}
+		} else {
+			Pages::<T>::insert(&origin, page_index, page);
+		}
+
+		Ok(used)
+	}
+}
+
+impl<T: Config> EnqueueMessage<MessageOriginOf<T>> for Pallet<T> {
+	fn enqueue_messages(messages: Vec<Vec<u8>>, origin: MessageOriginOf<T>) {
+		if messages.is_empty() {
+			return
+		}
+
+		let mut book = BookStateFor::<T>::get(&origin);
+		// The last page, if it still has messages to be visited.
+		let mut last_page =
+			if book.begin < book.end { Pages::<T>::get(&origin, book.end - 1) } else { None };
+
+		for payload in messages {
+			let len = payload.len() as u32;
+			let item = PageItem { processed: false, payload };
+			match last_page.as_mut() {
+				Some(page) if page.size.saturating_add(len) <= T::HeapSize::get() => {
+					page.items.push(item);
+					page.remaining.saturating_inc();
+					page.size.saturating_accrue(len);
+				},
+				_ => {
+					if let Some(page) = last_page.take() {
+						Pages::<T>::insert(&origin, book.end - 1, page);
+					}
+					last_page = Some(Page { items: vec![item], first: 0, remaining: 1, size: len });
+					book.end += 1;
+					book.count.saturating_inc();
+				},
+			}
+			book.message_count.saturating_inc();
+			book.size.saturating_accrue(len as u64);
+		}
+
+		if let Some(page) = last_page {
+			Pages::<T>::insert(&origin, book.end - 1, page);
+		}
+
+		Self::knit(&origin, &mut book);
+		let footprint = book.footprint();
+		BookStateFor::<T>::insert(&origin, book);
+		T::QueueChangeHandler::on_queue_changed(origin, footprint);
+	}
+
+	fn sweep_queue(origin: MessageOriginOf<T>) -> u32 {
+		if !BookStateFor::<T>::contains_key(&origin) {
+			return 0
+		}
+
+		let mut book = BookStateFor::<T>::take(&origin);
+		Self::unknit(&origin, &mut book);
+		// The book counts all pages in storage, so this removes all of them.
+		let _ = Pages::<T>::clear_prefix(&origin, book.count, None);
+		T::QueueChangeHandler::on_queue_changed(origin, QueueFootprint::default());
+		book.count
+	}
+
+	fn footprint(origin: MessageOriginOf<T>) -> QueueFootprint {
+		BookStateFor::<T>::get(&origin).footprint()
+	}
+}
@@ -0,0 +1,343 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok};
+use primitives::{BlockNumber, Id as ParaId};
+use std::collections::BTreeSet;
+
+use crate::{
+	configuration::HostConfiguration,
+	initializer::SessionChangeNotification,
+	mock::{
+		assert_last_event, new_test_ext, take_processed, MessageQueue, MockGenesisConfig, Paras,
+		ParasShared, RuntimeOrigin, System, Test,
+	},
+	paras::{ParaGenesisArgs, ParaKind},
+	ump::AggregateMessageOrigin,
+};
+
+fn default_config() -> HostConfiguration<BlockNumber> {
+	HostConfiguration {
+		ump_service_total_weight: Weight::from_parts(1000, 1000),
+		ump_max_individual_weight: Weight::from_parts(100, 100),
+		pvf_checking_enabled: false,
+		// Required by the consistency check of the `HostConfiguration`.
+		minimum_validation_upgrade_delay: 6,
+		..Default::default()
+	}
+}
+
+fn genesis_config() -> MockGenesisConfig {
+	MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	}
+}
+
+fn ump(para: u32) -> AggregateMessageOrigin {
+	AggregateMessageOrigin::Ump(ParaId::from(para))
+}
+
+/// A message which needs `weight` to be processed by the `TestUmpSink`, of 4 bytes.
+fn message(weight: u32) -> Vec<u8> {
+	weight.encode()
+}
+
+fn service() {
+	MessageQueue::service_queues(Weight::from_parts(1000, 1000));
+}
+
+fn processed() -> Vec<(u32, Vec<u8>)> {
+	take_processed().into_iter().map(|(para, msg)| (u32::from(para), msg)).collect()
+}
+
+/// Check that the ring consists of exactly the queues with messages to be visited, and that the
+/// books match the pages.
+fn assert_consistent() {
+	let mut ready = BTreeSet::new();
+	if let Some(head) = ServiceHead::<Test>::get() {
+		let mut origin = head;
+		loop {
+			assert!(ready.insert(origin), "the ring is a single cycle");
+			let neighbours = BookStateFor::<Test>::get(&origin).ready_neighbours.unwrap();
+			assert_eq!(
+				BookStateFor::<Test>::get(&neighbours.next).ready_neighbours.unwrap().prev,
+				origin
+			);
+			origin = neighbours.next;
+			if origin == head {
+				break
+			}
+		}
+	}
+
+	for (origin, book) in BookStateFor::<Test>::iter() {
+		assert_eq!(book.begin < book.end, ready.contains(&origin));
+
+		let pages = Pages::<Test>::iter_prefix(&origin).collect::<Vec<_>>();
+		assert_eq!(pages.len() as u32, book.count);
+		assert!(pages.iter().all(|(_, page)| page.remaining > 0));
+
+		let unvisited = pages
+			.iter()
+			.flat_map(|(_, page)| page.items.iter().skip(page.first as usize))
+			.collect::<Vec<_>>();
+		assert_eq!(unvisited.len() as u64, book.message_count);
+		assert_eq!(unvisited.iter().map(|item| item.payload.len() as u64).sum::<u64>(), book.size);
+	}
+	for (origin, _, _) in Pages::<Test>::iter() {
+		assert!(BookStateFor::<Test>::contains_key(&origin));
+	}
+}
+
+#[test]
+fn messages_are_packed_into_pages() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		MessageQueue::enqueue_messages(vec![message(1), message(2), message(3)], ump(1));
+
+		let book = BookStateFor::<Test>::get(ump(1));
+		assert_eq!((book.begin, book.end, book.count), (0, 2, 2));
+		assert_eq!(Pages::<Test>::get(ump(1), 0).unwrap().items.len(), 2);
+		assert_eq!(Pages::<Test>::get(ump(1), 1).unwrap().items.len(), 1);
+		assert_eq!(MessageQueue::footprint(ump(1)), QueueFootprint { count: 3, size: 12 });
+
+		// Later messages fill up the last page first.
+		MessageQueue::enqueue_messages(vec![message(4)], ump(1));
+		assert_eq!(Pages::<Test>::get(ump(1), 1).unwrap().items.len(), 2);
+		assert_eq!(BookStateFor::<Test>::get(ump(1)).end, 2);
+
+		// Larger messages get a page of their own.
+		MessageQueue::enqueue_messages(vec![(5u32, "large").encode()], ump(1));
+		assert_eq!(BookStateFor::<Test>::get(ump(1)).end, 3);
+		assert_consistent();
+	});
+}
+
+#[test]
+fn queues_are_serviced_round_robin_by_page() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		MessageQueue::enqueue_messages(vec![message(1), message(2), message(3)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(4), message(5), message(6)], ump(2));
+		assert_consistent();
+
+		service();
+		assert_eq!(
+			processed(),
+			vec![
+				(1, message(1)),
+				(1, message(2)),
+				(2, message(4)),
+				(2, message(5)),
+				(1, message(3)),
+				(2, message(6)),
+			]
+		);
+		assert_eq!(ServiceHead::<Test>::get(), None);
+		assert_eq!(BookStateFor::<Test>::iter().count(), 0);
+		assert_eq!(Pages::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn servicing_resumes_where_the_weight_ran_out() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		MessageQueue::enqueue_messages(vec![message(60), message(60)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(60)], ump(2));
+
+		MessageQueue::service_queues(Weight::from_parts(100, 100));
+		assert_eq!(processed(), vec![(1, message(60))]);
+		assert_eq!(MessageQueue::footprint(ump(1)).count, 1);
+		assert_consistent();
+
+		MessageQueue::service_queues(Weight::from_parts(100, 100));
+		assert_eq!(processed(), vec![(1, message(60))]);
+		assert_consistent();
+
+		MessageQueue::service_queues(Weight::from_parts(100, 100));
+		assert_eq!(processed(), vec![(2, message(60))]);
+		assert_consistent();
+	});
+}
+
+#[test]
+fn servicing_visits_at_most_max_messages_per_block() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		System::set_block_number(1);
+
+		let messages = (1..=12).map(message).collect::<Vec<_>>();
+		MessageQueue::enqueue_messages(messages[..6].to_vec(), ump(1));
+		MessageQueue::enqueue_messages(messages[6..].to_vec(), ump(2));
+
+		// `MaxMessagesPerBlock` is shared by all servicing within a block.
+		MessageQueue::service_queues(Weight::from_parts(1000, 1000));
+		MessageQueue::service_queues(Weight::from_parts(1000, 1000));
+		assert_eq!(processed().len(), 10);
+		assert_eq!(MessagesVisited::<Test>::get(), Some((1, 10)));
+		assert_consistent();
+
+		System::set_block_number(2);
+		service();
+		assert_eq!(processed().len(), 2);
+		assert_eq!(MessagesVisited::<Test>::get(), Some((2, 2)));
+		assert_eq!(ServiceHead::<Test>::get(), None);
+		assert_consistent();
+	});
+}
+
+#[test]
+fn overweight_messages_are_kept_and_can_be_executed() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		System::set_block_number(1);
+
+		MessageQueue::enqueue_messages(vec![message(200), message(1)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(2)], ump(2));
+
+		// Messages which were not visited yet can't be executed.
+		assert_noop!(
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::root(),
+				ump(1),
+				0,
+				0,
+				Weight::from_parts(1000, 1000)
+			),
+			Error::<Test>::NotOverweight
+		);
+
+		// The first message needs more than `MaxMessageWeight` and doesn't fit.
+		MessageQueue::service_queues(Weight::from_parts(150, 150));
+		assert_eq!(processed(), vec![(1, message(1)), (2, message(2))]);
+		assert_eq!(MessageQueue::footprint(ump(1)).count, 0);
+		// The page is kept for the overweight message.
+		assert_eq!(BookStateFor::<Test>::get(ump(1)).count, 1);
+		assert_consistent();
+
+		// Only `ExecuteOverweightOrigin` may execute overweight messages.
+		assert_noop!(
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::signed(1),
+				ump(1),
+				0,
+				0,
+				Weight::from_parts(1000, 1000)
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::root(),
+				ump(1),
+				0,
+				1,
+				Weight::from_parts(1000, 1000)
+			),
+			Error::<Test>::NoMessage
+		);
+
+		assert_ok!(MessageQueue::execute_overweight(
+			RuntimeOrigin::root(),
+			ump(1),
+			0,
+			0,
+			Weight::from_parts(200, 200)
+		));
+		assert_eq!(processed(), vec![(1, message(200))]);
+		assert_last_event(
+			Event::Processed {
+				id: sp_io::hashing::blake2_256(&message(200)),
+				origin: ump(1),
+				weight_used: Weight::from_parts(200, 200),
+			}
+			.into(),
+		);
+		assert_eq!(BookStateFor::<Test>::iter().count(), 0);
+		assert_eq!(Pages::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn paused_queues_are_skipped() {
+	let para = ParaId::from(1);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		assert_ok!(Paras::schedule_para_initialize(
+			para,
+			ParaGenesisArgs {
+				genesis_head: Vec::new().into(),
+				validation_code: vec![1, 2, 3].into(),
+				para_kind: ParaKind::Parathread,
+			}
+		));
+		Paras::initializer_on_new_session(&SessionChangeNotification {
+			session_index: ParasShared::scheduled_session(),
+			..Default::default()
+		});
+
+		MessageQueue::enqueue_messages(vec![message(1)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(2)], ump(2));
+
+		// The upward messages of offboarding paras are not processed.
+		assert_ok!(Paras::schedule_para_cleanup(para));
+		service();
+		assert_eq!(processed(), vec![(2, message(2))]);
+		assert_eq!(MessageQueue::footprint(ump(1)).count, 1);
+		assert_eq!(ServiceHead::<Test>::get(), Some(ump(1)));
+		assert_consistent();
+
+		// Nor can overweight messages be executed.
+		assert_noop!(
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::root(),
+				ump(1),
+				0,
+				0,
+				Weight::from_parts(1000, 1000)
+			),
+			Error::<Test>::QueuePaused
+		);
+	});
+}
+
+#[test]
+fn sweeping_a_queue_removes_all_of_its_messages() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		MessageQueue::enqueue_messages(vec![message(200)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(1)], ump(2));
+		MessageQueue::service_queues(Weight::from_parts(150, 150));
+		assert_eq!(processed(), vec![(2, message(1))]);
+		assert_eq!(BookStateFor::<Test>::get(ump(1)).count, 1);
+
+		MessageQueue::enqueue_messages(vec![message(1), message(2), message(3)], ump(1));
+		MessageQueue::enqueue_messages(vec![message(4)], ump(2));
+		MessageQueue::enqueue_messages(vec![message(5)], ump(3));
+		assert_consistent();
+
+		// The page kept for the overweight message and two pages of new messages.
+		assert_eq!(MessageQueue::sweep_queue(ump(1)), 3);
+		assert!(!BookStateFor::<Test>::contains_key(ump(1)));
+		assert_eq!(Pages::<Test>::iter_prefix(ump(1)).count(), 0);
+		assert_consistent();
+
+		service();
+		assert_eq!(processed(), vec![(2, message(4)), (3, message(5))]);
+		assert_consistent();
+	});
+}
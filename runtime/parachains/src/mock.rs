@@ -17,8 +17,8 @@
 //! Mocks for all the traits.
 
 use crate::{
-	configuration, disputes, dmp, hrmp, inclusion, initializer, origin, paras, paras_inherent,
-	scheduler, session_info, shared,
+	bulk, configuration, disputes, dmp, hrmp, inclusion, initializer, message_queue, on_demand,
+	origin, paras, paras_inherent, scheduler, session_info, shared,
 	ump::{self, MessageId, UmpSink},
 	ParaId,
 };
@@ -39,7 +39,7 @@ use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
 	transaction_validity::TransactionPriority,
-	KeyTypeId, Permill,
+	FixedU128, KeyTypeId, Perbill, Permill,
 };
 use std::{cell::RefCell, collections::HashMap};
 
@@ -63,7 +63,10 @@ frame_support::construct_runtime!(
 		Initializer: initializer,
 		Dmp: dmp,
 		Ump: ump,
+		MessageQueue: message_queue,
 		Hrmp: hrmp,
+		OnDemand: on_demand,
+		Bulk: bulk,
 		ParachainsOrigin: origin,
 		SessionInfo: session_info,
 		Disputes: disputes,
@@ -194,6 +197,7 @@ impl origin::Config for Test {}
 
 parameter_types! {
 	pub const ParasUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	pub const MaxExtraCores: u32 = 4;
 }
 
 /// A very dumb implementation of `EstimateNextSessionRotation`. At the moment of writing, this
@@ -218,23 +222,39 @@ impl crate::paras::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = crate::paras::TestWeightInfo;
 	type UnsignedPriority = ParasUnsignedPriority;
+	type MaxExtraCores = MaxExtraCores;
 	type NextSessionRotation = TestNextSessionRotation;
 }
 
 impl crate::dmp::Config for Test {}
 
-parameter_types! {
-	pub const FirstMessageFactorPercent: u64 = 100;
-}
-
 impl crate::ump::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type UmpSink = TestUmpSink;
-	type FirstMessageFactorPercent = FirstMessageFactorPercent;
+	type MessageQueue = MessageQueue;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
 	type WeightInfo = crate::ump::TestWeightInfo;
 }
 
+parameter_types! {
+	// Small enough for most test messages to get a page of their own.
+	pub const MessageQueueHeapSize: u32 = 8;
+	pub const MessageQueueMaxMessagesPerBlock: u32 = 10;
+}
+
+impl crate::message_queue::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type MessageProcessor = Ump;
+	type QueueChangeHandler = Ump;
+	type QueuePausedQuery = ump::PauseOffboardingParas<Test>;
+	type HeapSize = MessageQueueHeapSize;
+	type ServiceWeight = ump::ServiceTotalWeight<Test>;
+	type MaxMessageWeight = ump::MaxIndividualWeight<Test>;
+	type MaxMessagesPerBlock = MessageQueueMaxMessagesPerBlock;
+	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = crate::message_queue::TestWeightInfo;
+}
+
 impl crate::hrmp::Config for Test {
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeEvent = RuntimeEvent;
@@ -242,6 +262,36 @@ impl crate::hrmp::Config for Test {
 	type WeightInfo = crate::hrmp::TestWeightInfo;
 }
 
+parameter_types! {
+	pub const OnDemandBaseFee: Balance = 10_000;
+	pub const OnDemandFeeVariability: Perbill = Perbill::from_percent(3);
+	pub const OnDemandTargetQueueUtilization: Perbill = Perbill::from_percent(25);
+	pub const OnDemandTrafficDefaultValue: FixedU128 = FixedU128::from_u32(1);
+}
+
+impl crate::on_demand::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = pallet_balances::Pallet<Test>;
+	type OnOrderPayment = ();
+	type BaseFee = OnDemandBaseFee;
+	type FeeVariability = OnDemandFeeVariability;
+	type TargetQueueUtilization = OnDemandTargetQueueUtilization;
+	type TrafficDefaultValue = OnDemandTrafficDefaultValue;
+	type WeightInfo = crate::on_demand::TestWeightInfo;
+}
+
+parameter_types! {
+	pub const BulkMaxCores: u32 = 4;
+	pub const BulkMaxParasPerCore: u32 = 3;
+}
+
+impl crate::bulk::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type MaxCores = BulkMaxCores;
+	type MaxParasPerCore = BulkMaxParasPerCore;
+	type WeightInfo = crate::bulk::TestWeightInfo;
+}
+
 impl crate::disputes::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type RewardValidators = Self;
@@ -282,6 +332,13 @@ impl crate::disputes::SlashingHandler<BlockNumber> for Test {
 			.with(|r| r.borrow_mut().push((session, losers.into_iter().collect())))
 	}
 
+	fn punish_backing_misbehavior(
+		_: SessionIndex,
+		_: CandidateHash,
+		_: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+	}
+
 	fn initializer_initialize(_now: BlockNumber) -> Weight {
 		Weight::zero()
 	}
@@ -291,7 +348,14 @@ impl crate::disputes::SlashingHandler<BlockNumber> for Test {
 	fn initializer_on_new_session(_: SessionIndex) {}
 }
 
-impl crate::scheduler::Config for Test {}
+impl crate::scheduler::Config for Test {
+	type AssignmentProvider = (
+		scheduler::ParachainAssignmentProvider<Test>,
+		scheduler::ParathreadAssignmentProvider<Test>,
+		Bulk,
+	);
+	type OnParathreadClaimEnded = OnDemand;
+}
 
 impl crate::inclusion::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
@@ -0,0 +1,81 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::{
+	configuration::ActiveConfig,
+	paras::{Pallet as Paras, ParaGenesisArgs, ParaKind, ParachainsCache},
+	scheduler::Pallet as Scheduler,
+};
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+
+fn register_parathread<T: Config>(id: ParaId) {
+	let mut parachains = ParachainsCache::new();
+	Paras::<T>::initialize_para_now(
+		&mut parachains,
+		id,
+		&ParaGenesisArgs {
+			para_kind: ParaKind::Parathread,
+			genesis_head: vec![1].into(),
+			validation_code: vec![1].into(),
+		},
+	);
+}
+
+fn collator(seed: u8) -> CollatorId {
+	CollatorId::from(sr25519::Public::from_raw([seed; 32]))
+}
+
+benchmarks! {
+	// The worst case is an order which fills up the parathread queue, as the queue and the claim
+	// index are the largest then.
+	place_order {
+		ActiveConfig::<T>::mutate(|config| {
+			config.parathread_cores = config.parathread_cores.max(1);
+			config.scheduling_lookahead = config.scheduling_lookahead.max(1);
+		});
+		let (_, capacity) = Scheduler::<T>::parathread_queue_utilization();
+
+		for i in 1..capacity {
+			let id = ParaId::from(1000 + i);
+			register_parathread::<T>(id);
+			Scheduler::<T>::try_add_parathread_claim(ParathreadClaim(id, collator(i as u8)))
+				.expect("queue has capacity for all but one claim; qed");
+		}
+
+		let para_id = ParaId::from(1000 + capacity);
+		register_parathread::<T>(para_id);
+
+		let caller: T::AccountId = whitelisted_caller();
+		let spot_price = Pallet::<T>::spot_price();
+		T::Currency::make_free_balance_be(
+			&caller,
+			T::Currency::minimum_balance().saturating_add(spot_price.saturating_mul(2u32.into())),
+		);
+	}: _(RawOrigin::Signed(caller), spot_price, para_id, collator(0), true)
+	verify {
+		assert_eq!(Scheduler::<T>::parathread_queue_utilization(), (capacity, capacity));
+		assert!(Orders::<T>::contains_key(para_id));
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
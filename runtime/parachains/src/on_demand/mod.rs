@@ -0,0 +1,295 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The on-demand order book for parathread blocks.
+//!
+//! Parathreads are not assigned a core of their own. Instead, the scheduler assigns the claims in
+//! its parathread queue to the parathread multiplexer cores. This pallet allows anyone to buy
+//! such a claim for a parathread, by placing an order which pays the current spot price and is
+//! added to the parathread queue right away.
+//!
+//! The spot price is reserved while the claim of an order is queued or assigned to a core. It is
+//! paid once a candidate for the claim is included, and unreserved again if the scheduler drops
+//! the claim before that.
+//!
+//! The spot price is the base fee multiplied by the spot traffic. The spot traffic is adjusted at
+//! the beginning of every block to the utilization of the parathread queue: it rises while the
+//! queue is fuller than targeted and falls back to its default value otherwise.
+
+use crate::{
+	configuration, paras,
+	scheduler::{self, OnParathreadClaimEnded, ParathreadClaimError},
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{Currency, OnUnbalanced, ReservableCurrency},
+};
+use frame_system::pallet_prelude::*;
+use primitives::{CollatorId, Id as ParaId, ParathreadClaim};
+use sp_runtime::{
+	traits::{SaturatedConversion, Saturating},
+	FixedPointNumber, FixedU128, Perbill,
+};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait WeightInfo {
+	fn place_order() -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn place_order() -> Weight {
+		Weight::MAX
+	}
+}
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + configuration::Config + paras::Config + scheduler::Config
+	{
+		/// The outer event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency orders are paid in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Handler for the payments of orders, e.g. moving them to the treasury. Payments are
+		/// burned with `()`.
+		type OnOrderPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The spot price at the default spot traffic.
+		#[pallet::constant]
+		type BaseFee: Get<BalanceOf<Self>>;
+
+		/// How much the spot traffic changes per block, relative to its current value, for every
+		/// unit the utilization of the parathread queue deviates from the target utilization.
+		#[pallet::constant]
+		type FeeVariability: Get<Perbill>;
+
+		/// The utilization of the parathread queue the spot price steers towards.
+		#[pallet::constant]
+		type TargetQueueUtilization: Get<Perbill>;
+
+		/// The initial and lowest spot traffic.
+		#[pallet::constant]
+		type TrafficDefaultValue: Get<FixedU128>;
+
+		/// Something that provides the weight of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An order for a block of a parathread was placed.
+		OnDemandOrderPlaced { para_id: ParaId, spot_price: BalanceOf<T> },
+		/// The spot traffic was adjusted to the utilization of the parathread queue.
+		SpotTrafficSet { traffic: FixedU128 },
+		/// The claim of an order was dropped before a block of the parathread was included, so
+		/// the spot price was refunded.
+		OnDemandOrderRefunded { para_id: ParaId, who: T::AccountId, amount: BalanceOf<T> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The para is not a live parathread.
+		NotParathread,
+		/// The parathread queue is full.
+		QueueFull,
+		/// There already is a claim for the parathread queued or assigned to a core.
+		AlreadyClaimed,
+		/// The spot price is higher than the maximum amount the order allows.
+		SpotPriceHigherThanMaxAmount,
+		/// Paying the spot price would kill the account, while `keep_alive` is set.
+		KeepAlive,
+	}
+
+	#[pallet::type_value]
+	pub fn SpotTrafficOnEmpty<T: Config>() -> FixedU128 {
+		T::TrafficDefaultValue::get()
+	}
+
+	/// The factor the base fee is multiplied with to get the spot price.
+	#[pallet::storage]
+	#[pallet::getter(fn spot_traffic)]
+	pub(super) type SpotTraffic<T: Config> =
+		StorageValue<_, FixedU128, ValueQuery, SpotTrafficOnEmpty<T>>;
+
+	/// The account which placed the order of a queued or assigned parathread claim and the spot
+	/// price it reserved.
+	///
+	/// There is at most one claim per parathread, so orders are keyed by the para.
+	#[pallet::storage]
+	pub(super) type Orders<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, (T::AccountId, BalanceOf<T>)>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			Self::update_spot_traffic();
+
+			// The active configuration, the parathread queue and the spot traffic.
+			T::DbWeight::get().reads_writes(3, 1)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Buy a block of the parathread `para_id` at the current spot price, to be collated by
+		/// `collator`.
+		///
+		/// The order is added to the parathread queue of the scheduler. It fails without paying
+		/// anything if the spot price is higher than `max_amount`, if `para_id` is not a live
+		/// parathread, if the queue is full or if a claim for `para_id` is already queued or
+		/// assigned to a core.
+		///
+		/// The spot price is reserved until a block of the parathread is included, and refunded if
+		/// the claim is dropped before that.
+		///
+		/// - `keep_alive` - whether paying the spot price must not reap the account of the origin.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::place_order())]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			max_amount: BalanceOf<T>,
+			para_id: ParaId,
+			collator: CollatorId,
+			keep_alive: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let spot_price = Self::spot_price();
+			ensure!(spot_price <= max_amount, Error::<T>::SpotPriceHigherThanMaxAmount);
+
+			<scheduler::Pallet<T>>::try_add_parathread_claim(ParathreadClaim(para_id, collator))
+				.map_err(Error::<T>::from)?;
+
+			// Calls are transactional, so the claim is removed from the queue again if the payment
+			// fails.
+			if keep_alive {
+				let remaining = T::Currency::free_balance(&who).saturating_sub(spot_price);
+				ensure!(remaining >= T::Currency::minimum_balance(), Error::<T>::KeepAlive);
+			}
+			T::Currency::reserve(&who, spot_price)?;
+			Orders::<T>::insert(para_id, (who, spot_price));
+
+			Self::deposit_event(Event::OnDemandOrderPlaced { para_id, spot_price });
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> From<ParathreadClaimError> for Error<T> {
+	fn from(err: ParathreadClaimError) -> Self {
+		match err {
+			ParathreadClaimError::NotParathread => Error::<T>::NotParathread,
+			ParathreadClaimError::QueueFull => Error::<T>::QueueFull,
+			ParathreadClaimError::AlreadyClaimed => Error::<T>::AlreadyClaimed,
+		}
+	}
+}
+
+impl<T: Config> OnParathreadClaimEnded for Pallet<T> {
+	fn on_claim_processed(para_id: ParaId) {
+		if let Some((who, amount)) = Orders::<T>::take(para_id) {
+			let (payment, _) = T::Currency::slash_reserved(&who, amount);
+			T::OnOrderPayment::on_unbalanced(payment);
+		}
+	}
+
+	fn on_claim_dropped(para_id: ParaId) {
+		if let Some((who, amount)) = Orders::<T>::take(para_id) {
+			T::Currency::unreserve(&who, amount);
+			Self::deposit_event(Event::OnDemandOrderRefunded { para_id, who, amount });
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The price of an order placed in the current block.
+	pub fn spot_price() -> BalanceOf<T> {
+		let base_fee: u128 = T::BaseFee::get().saturated_into();
+		Self::spot_traffic().saturating_mul_int(base_fee).saturated_into()
+	}
+
+	/// Adjust the spot traffic to the current utilization of the parathread queue.
+	fn update_spot_traffic() {
+		let (queued, capacity) = <scheduler::Pallet<T>>::parathread_queue_utilization();
+		let traffic = SpotTraffic::<T>::get();
+		let new_traffic = Self::calculate_spot_traffic(traffic, queued, capacity);
+
+		if new_traffic != traffic {
+			SpotTraffic::<T>::set(new_traffic);
+			Self::deposit_event(Event::SpotTrafficSet { traffic: new_traffic });
+		}
+	}
+
+	/// The spot traffic following `traffic`, with `queued` claims in a parathread queue which can
+	/// hold `capacity` claims.
+	///
+	/// The traffic changes by `FeeVariability` times the difference between the utilization of
+	/// the queue and `TargetQueueUtilization`, relative to its current value. It never drops
+	/// below `TrafficDefaultValue`, which it is reset to if there are no parathread cores.
+	pub(crate) fn calculate_spot_traffic(
+		traffic: FixedU128,
+		queued: u32,
+		capacity: u32,
+	) -> FixedU128 {
+		let min_traffic = T::TrafficDefaultValue::get();
+		if capacity == 0 {
+			return min_traffic
+		}
+
+		let utilization = Perbill::from_rational(queued.min(capacity), capacity);
+		let target = T::TargetQueueUtilization::get();
+		let variability = FixedU128::from(T::FeeVariability::get());
+
+		let new_traffic = if utilization > target {
+			let excess = FixedU128::from(utilization.saturating_sub(target));
+			traffic.saturating_add(traffic.saturating_mul(variability.saturating_mul(excess)))
+		} else {
+			let shortfall = FixedU128::from(target.saturating_sub(utilization));
+			traffic.saturating_sub(traffic.saturating_mul(variability.saturating_mul(shortfall)))
+		};
+
+		new_traffic.max(min_traffic)
+	}
+}
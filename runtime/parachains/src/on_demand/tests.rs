@@ -0,0 +1,441 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use keyring::Sr25519Keyring;
+use primitives::{BlockNumber, SessionIndex, ValidatorId};
+
+use crate::{
+	configuration::HostConfiguration,
+	initializer::SessionChangeNotification,
+	mock::{
+		new_test_ext, Balances, MockGenesisConfig, OnDemand, Paras, ParasShared, RuntimeOrigin,
+		Scheduler, System, Test,
+	},
+	paras::{ParaGenesisArgs, ParaKind},
+	scheduler::{FreedReason, ParathreadQueue},
+};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+fn default_config() -> HostConfiguration<BlockNumber> {
+	HostConfiguration {
+		parathread_cores: 2,
+		scheduling_lookahead: 2,
+		parathread_retries: 1,
+		pvf_checking_enabled: false,
+		// Required by the consistency check of the `HostConfiguration`.
+		minimum_validation_upgrade_delay: 6,
+		..Default::default()
+	}
+}
+
+fn genesis_config() -> MockGenesisConfig {
+	MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	}
+}
+
+fn schedule_blank_para(id: ParaId, para_kind: ParaKind) {
+	assert_ok!(Paras::schedule_para_initialize(
+		id,
+		ParaGenesisArgs {
+			genesis_head: Vec::new().into(),
+			validation_code: vec![1, 2, 3].into(),
+			para_kind,
+		}
+	));
+}
+
+fn run_to_block(
+	to: BlockNumber,
+	new_session: impl Fn(BlockNumber) -> Option<SessionChangeNotification<BlockNumber>>,
+) {
+	while System::block_number() < to {
+		let b = System::block_number();
+
+		Scheduler::initializer_finalize();
+		Paras::initializer_finalize(b);
+
+		if let Some(mut notification) = new_session(b + 1) {
+			// Make every session change enact the scheduled actions.
+			if notification.session_index == SessionIndex::default() {
+				notification.session_index = ParasShared::scheduled_session();
+			}
+			Paras::initializer_on_new_session(&notification);
+			Scheduler::initializer_on_new_session(&notification);
+		}
+
+		System::on_finalize(b);
+
+		System::on_initialize(b + 1);
+		System::set_block_number(b + 1);
+
+		Paras::initializer_initialize(b + 1);
+		Scheduler::initializer_initialize(b + 1);
+		OnDemand::on_initialize(b + 1);
+	}
+}
+
+/// Onboard the given parathreads, taking effect with the session change at block 10.
+fn onboard_parathreads(ids: impl IntoIterator<Item = ParaId>) {
+	for id in ids {
+		schedule_blank_para(id, ParaKind::Parathread);
+	}
+
+	run_to_block(10, |n| if n == 10 { Some(Default::default()) } else { None });
+}
+
+/// Start a session with validators for the parathread cores of `config` at block `at`.
+fn start_session(at: BlockNumber, config: HostConfiguration<BlockNumber>) {
+	run_to_block(at, |n| {
+		(n == at).then(|| SessionChangeNotification {
+			new_config: config.clone(),
+			validators: vec![
+				ValidatorId::from(Sr25519Keyring::Alice.public()),
+				ValidatorId::from(Sr25519Keyring::Bob.public()),
+			],
+			..Default::default()
+		})
+	});
+}
+
+/// Schedule the free cores, like the inclusion inherent does in every block.
+fn schedule_free_cores() {
+	Scheduler::clear();
+	Scheduler::schedule(Vec::new(), System::block_number());
+}
+
+fn collator() -> CollatorId {
+	Sr25519Keyring::Alice.public().into()
+}
+
+fn queue_len() -> usize {
+	ParathreadQueue::<Test>::get().queue.len()
+}
+
+#[test]
+fn spot_traffic_rises_above_target_utilization() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		// Full queue, 75% above the target utilization, raising the traffic by 3% of that.
+		assert_eq!(
+			OnDemand::calculate_spot_traffic(FixedU128::from_u32(1), 4, 4),
+			FixedU128::from_rational(10225, 10000),
+		);
+		assert_eq!(
+			OnDemand::calculate_spot_traffic(FixedU128::from_u32(2), 4, 4),
+			FixedU128::from_rational(20450, 10000),
+		);
+	});
+}
+
+#[test]
+fn spot_traffic_stays_at_target_utilization() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let traffic = FixedU128::from_rational(3, 2);
+		assert_eq!(OnDemand::calculate_spot_traffic(traffic, 1, 4), traffic);
+	});
+}
+
+#[test]
+fn spot_traffic_falls_below_target_utilization_down_to_default() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		// Empty queue, 25% below the target utilization, lowering the traffic by 3% of that.
+		assert_eq!(
+			OnDemand::calculate_spot_traffic(FixedU128::from_u32(2), 0, 4),
+			FixedU128::from_rational(1985, 1000),
+		);
+		assert_eq!(
+			OnDemand::calculate_spot_traffic(FixedU128::from_u32(1), 0, 4),
+			FixedU128::from_u32(1),
+		);
+	});
+}
+
+#[test]
+fn spot_traffic_is_reset_without_parathread_cores() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		assert_eq!(
+			OnDemand::calculate_spot_traffic(FixedU128::from_u32(5), 0, 0),
+			FixedU128::from_u32(1),
+		);
+	});
+}
+
+#[test]
+fn place_order_queues_claim_and_pays_spot_price() {
+	let thread_id = ParaId::from(10);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		onboard_parathreads([thread_id]);
+		Balances::make_free_balance_be(&ALICE, 100_000);
+
+		assert_eq!(OnDemand::spot_price(), 10_000);
+		assert_ok!(OnDemand::place_order(
+			RuntimeOrigin::signed(ALICE),
+			20_000,
+			thread_id,
+			collator(),
+			false,
+		));
+
+		// Only the spot price is paid, not the maximum amount.
+		assert_eq!(Balances::free_balance(&ALICE), 90_000);
+		assert_eq!(queue_len(), 1);
+		System::assert_last_event(
+			Event::OnDemandOrderPlaced { para_id: thread_id, spot_price: 10_000 }.into(),
+		);
+	});
+}
+
+#[test]
+fn place_order_fails_without_paying() {
+	let thread_id = ParaId::from(10);
+	let chain_id = ParaId::from(11);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		schedule_blank_para(chain_id, ParaKind::Parachain);
+		onboard_parathreads([thread_id]);
+		Balances::make_free_balance_be(&ALICE, 100_000);
+		Balances::make_free_balance_be(&BOB, 5_000);
+
+		assert_noop!(
+			OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				9_999,
+				thread_id,
+				collator(),
+				false
+			),
+			Error::<Test>::SpotPriceHigherThanMaxAmount,
+		);
+		assert_noop!(
+			OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				chain_id,
+				collator(),
+				false
+			),
+			Error::<Test>::NotParathread,
+		);
+		assert_noop!(
+			OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				ParaId::from(12),
+				collator(),
+				false,
+			),
+			Error::<Test>::NotParathread,
+		);
+		// The claim is not queued if the payment fails.
+		assert_noop!(
+			OnDemand::place_order(RuntimeOrigin::signed(BOB), 10_000, thread_id, collator(), false),
+			pallet_balances::Error::<Test>::InsufficientBalance,
+		);
+
+		assert_ok!(OnDemand::place_order(
+			RuntimeOrigin::signed(ALICE),
+			10_000,
+			thread_id,
+			collator(),
+			false,
+		));
+		assert_noop!(
+			OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				thread_id,
+				collator(),
+				false
+			),
+			Error::<Test>::AlreadyClaimed,
+		);
+		assert_eq!(Balances::free_balance(&ALICE), 90_000);
+	});
+}
+
+#[test]
+fn place_order_fails_when_queue_is_full() {
+	let thread_ids: Vec<ParaId> = (10..15).map(ParaId::from).collect();
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		onboard_parathreads(thread_ids.iter().cloned());
+		Balances::make_free_balance_be(&ALICE, 100_000);
+
+		// The queue holds `parathread_cores * scheduling_lookahead` claims.
+		for id in &thread_ids[..4] {
+			assert_ok!(OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				*id,
+				collator(),
+				false,
+			));
+		}
+		assert_noop!(
+			OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				thread_ids[4],
+				collator(),
+				false,
+			),
+			Error::<Test>::QueueFull,
+		);
+		assert_eq!(queue_len(), 4);
+		assert_eq!(Balances::free_balance(&ALICE), 60_000);
+	});
+}
+
+#[test]
+fn spot_price_follows_queue_utilization() {
+	let thread_ids: Vec<ParaId> = (10..14).map(ParaId::from).collect();
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		onboard_parathreads(thread_ids.iter().cloned());
+		Balances::make_free_balance_be(&ALICE, 100_000);
+
+		for id in &thread_ids {
+			assert_ok!(OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				10_000,
+				*id,
+				collator(),
+				false,
+			));
+		}
+
+		// The queue is not drained without validators, so the traffic keeps rising.
+		run_to_block(11, |_| None);
+		let traffic = FixedU128::from_rational(10225, 10000);
+		assert_eq!(OnDemand::spot_traffic(), traffic);
+		assert_eq!(OnDemand::spot_price(), 10_225);
+		System::assert_last_event(Event::SpotTrafficSet { traffic }.into());
+
+		run_to_block(12, |_| None);
+		assert!(OnDemand::spot_price() > 10_225);
+	});
+}
+
+fn place_order_and_start_session(thread_id: ParaId) {
+	onboard_parathreads([thread_id]);
+	start_session(11, default_config());
+	Balances::make_free_balance_be(&ALICE, 100_000);
+
+	assert_ok!(OnDemand::place_order(
+		RuntimeOrigin::signed(ALICE),
+		10_000,
+		thread_id,
+		collator(),
+		false,
+	));
+	assert_eq!(Balances::reserved_balance(&ALICE), 10_000);
+}
+
+fn assert_refunded(thread_id: ParaId) {
+	assert_eq!(Balances::free_balance(&ALICE), 100_000);
+	assert_eq!(Balances::reserved_balance(&ALICE), 0);
+	assert!(Orders::<Test>::get(thread_id).is_none());
+	System::assert_has_event(
+		Event::OnDemandOrderRefunded { para_id: thread_id, who: ALICE, amount: 10_000 }.into(),
+	);
+}
+
+#[test]
+fn order_is_paid_when_block_is_included() {
+	let thread_id = ParaId::from(10);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		place_order_and_start_session(thread_id);
+		let issuance = Balances::total_issuance();
+
+		schedule_free_cores();
+		let core = Scheduler::scheduled()[0].core;
+		Scheduler::occupied(&[core]);
+		Scheduler::free_cores(vec![(core, FreedReason::Concluded)]);
+
+		// The payment is burned by the mock.
+		assert_eq!(Balances::free_balance(&ALICE), 90_000);
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		assert_eq!(Balances::total_issuance(), issuance - 10_000);
+		assert!(Orders::<Test>::get(thread_id).is_none());
+	});
+}
+
+#[test]
+fn order_is_refunded_when_claim_runs_out_of_retries() {
+	let thread_id = ParaId::from(10);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		place_order_and_start_session(thread_id);
+
+		// The claim is scheduled without being backed `parathread_retries + 1` times.
+		schedule_free_cores();
+		schedule_free_cores();
+		assert_eq!(Scheduler::scheduled().len(), 1);
+		schedule_free_cores();
+		assert!(Scheduler::scheduled().is_empty());
+
+		assert_refunded(thread_id);
+
+		// The para can be claimed again.
+		assert_ok!(OnDemand::place_order(
+			RuntimeOrigin::signed(ALICE),
+			10_000,
+			thread_id,
+			collator(),
+			false,
+		));
+	});
+}
+
+#[test]
+fn order_is_refunded_when_para_is_offboarded() {
+	let thread_id = ParaId::from(10);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		place_order_and_start_session(thread_id);
+
+		assert_ok!(Paras::schedule_para_cleanup(thread_id));
+		start_session(12, default_config());
+
+		assert_eq!(queue_len(), 0);
+		assert_refunded(thread_id);
+	});
+}
+
+#[test]
+fn order_is_refunded_when_parathread_cores_are_removed() {
+	let thread_id = ParaId::from(10);
+
+	new_test_ext(genesis_config()).execute_with(|| {
+		place_order_and_start_session(thread_id);
+
+		start_session(12, HostConfiguration { parathread_cores: 0, ..default_config() });
+
+		assert_eq!(queue_len(), 0);
+		assert_refunded(thread_id);
+	});
+}
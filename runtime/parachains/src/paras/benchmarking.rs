@@ -134,6 +134,13 @@ benchmarks! {
 		assert_last_event::<T>(Event::ActionQueued(para_id, next_session).into());
 	}
 
+	force_set_extra_cores {
+		let para_id = ParaId::from(1000);
+	}: _(RawOrigin::Root, para_id, 2)
+	verify {
+		assert_last_event::<T>(Event::ExtraCoresScheduled(para_id, 2).into());
+	}
+
 	add_trusted_validation_code {
 		let c in 1 .. MAX_CODE_SIZE;
 		let new_code = ValidationCode(vec![0; c as usize]);
@@ -473,6 +473,7 @@ pub trait WeightInfo {
 	fn force_schedule_code_upgrade(c: u32) -> Weight;
 	fn force_note_new_head(s: u32) -> Weight;
 	fn force_queue_action() -> Weight;
+	fn force_set_extra_cores() -> Weight;
 	fn add_trusted_validation_code(c: u32) -> Weight;
 	fn poke_unused_validation_code() -> Weight;
 
@@ -500,6 +501,9 @@ impl WeightInfo for TestWeightInfo {
 	fn force_queue_action() -> Weight {
 		Weight::MAX
 	}
+	fn force_set_extra_cores() -> Weight {
+		Weight::MAX
+	}
 	fn add_trusted_validation_code(_c: u32) -> Weight {
 		Weight::MAX
 	}
@@ -549,6 +553,11 @@ pub mod pallet {
 		#[pallet::constant]
 		type UnsignedPriority: Get<TransactionPriority>;
 
+		/// The maximum number of availability cores a parachain can be assigned in addition to
+		/// its regular core.
+		#[pallet::constant]
+		type MaxExtraCores: Get<u32>;
+
 		type NextSessionRotation: EstimateNextSessionRotation<Self::BlockNumber>;
 
 		/// Weight information for extrinsics in this pallet.
@@ -577,6 +586,9 @@ pub mod pallet {
 		/// The given validation code was rejected by the PVF pre-checking vote.
 		/// `code_hash` `para_id`
 		PvfCheckRejected(ValidationCodeHash, ParaId),
+		/// The number of extra cores of a parachain was changed, effective from the next session.
+		/// `para_id` `extra_cores`
+		ExtraCoresScheduled(ParaId, u32),
 	}
 
 	#[pallet::error]
@@ -608,6 +620,8 @@ pub mod pallet {
 		PvfCheckDisabled,
 		/// Parachain cannot currently schedule a code upgrade.
 		CannotUpgradeCode,
+		/// More extra cores than `MaxExtraCores` were requested for a parachain.
+		TooManyExtraCores,
 	}
 
 	/// All currently active PVF pre-checking votes.
@@ -635,6 +649,19 @@ pub mod pallet {
 	#[pallet::getter(fn parachains)]
 	pub(crate) type Parachains<T: Config> = StorageValue<_, Vec<ParaId>, ValueQuery>;
 
+	/// The number of availability cores assigned to parachains in addition to their regular core.
+	/// Ordered ascending by `ParaId`. Parachains without an entry have a single core.
+	///
+	/// Only changed at session boundaries, because the layout of the availability cores depends
+	/// on it.
+	#[pallet::storage]
+	#[pallet::getter(fn extra_cores)]
+	pub(crate) type ExtraCores<T: Config> = StorageValue<_, Vec<(ParaId, u32)>, ValueQuery>;
+
+	/// Changes to [`ExtraCores`] which are applied at the next session change.
+	#[pallet::storage]
+	pub(super) type UpcomingExtraCores<T: Config> = StorageMap<_, Twox64Concat, ParaId, u32>;
+
 	/// The current lifecycle of a all known Para IDs.
 	#[pallet::storage]
 	pub(super) type ParaLifecycles<T: Config> = StorageMap<_, Twox64Concat, ParaId, ParaLifecycle>;
@@ -879,6 +906,26 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Set the number of availability cores assigned to a parachain in addition to its
+		/// regular core, starting with the next session.
+		///
+		/// Every core allows one more candidate of the parachain to be backed in the same
+		/// relay-chain block, as a chain of dependent candidates. At most `MaxExtraCores` can be
+		/// assigned.
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as Config>::WeightInfo::force_set_extra_cores())]
+		pub fn force_set_extra_cores(
+			origin: OriginFor<T>,
+			para: ParaId,
+			extra_cores: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(extra_cores <= T::MaxExtraCores::get(), Error::<T>::TooManyExtraCores);
+			UpcomingExtraCores::<T>::insert(&para, extra_cores);
+			Self::deposit_event(Event::ExtraCoresScheduled(para, extra_cores));
+			Ok(())
+		}
+
 		/// Adds the validation code to the storage.
 		///
 		/// The code will not be added if it is already present. Additionally, if PVF pre-checking
@@ -1173,6 +1220,7 @@ impl<T: Config> Pallet<T> {
 		notification: &SessionChangeNotification<T::BlockNumber>,
 	) -> Vec<ParaId> {
 		let outgoing_paras = Self::apply_actions_queue(notification.session_index);
+		Self::apply_upcoming_extra_cores(&outgoing_paras);
 		Self::groom_ongoing_pvf_votes(&notification.new_config, notification.validators.len());
 		outgoing_paras
 	}
@@ -1192,6 +1240,30 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	// Apply the queued changes to the extra cores of parachains and drop the extra cores of
+	// outgoing paras.
+	fn apply_upcoming_extra_cores(outgoing: &[ParaId]) {
+		let changes = UpcomingExtraCores::<T>::drain().collect::<Vec<_>>();
+		if changes.is_empty() && outgoing.is_empty() {
+			return
+		}
+
+		ExtraCores::<T>::mutate(|extra_cores| {
+			for (para, n) in changes {
+				match extra_cores.binary_search_by_key(&para, |(p, _)| *p) {
+					Ok(i) if n == 0 => {
+						extra_cores.remove(i);
+					},
+					Ok(i) => extra_cores[i].1 = n,
+					Err(i) if n != 0 => extra_cores.insert(i, (para, n)),
+					Err(_) => {},
+				}
+			}
+
+			extra_cores.retain(|(para, _)| !outgoing.contains(para));
+		});
+	}
+
 	// Apply all para actions queued for the given session index.
 	//
 	// The actions to take are based on the lifecycle of of the paras.
@@ -983,6 +983,41 @@ fn para_incoming_at_session() {
 	})
 }
 
+#[test]
+fn extra_cores_applied_at_session_change() {
+	new_test_ext(Default::default()).execute_with(|| {
+		let a = ParaId::from(100);
+		let b = ParaId::from(200);
+
+		run_to_block(1, Some(vec![1]));
+
+		assert_ok!(Paras::force_set_extra_cores(RuntimeOrigin::root(), b, 2));
+		assert_ok!(Paras::force_set_extra_cores(RuntimeOrigin::root(), a, 1));
+		assert!(Paras::extra_cores().is_empty());
+
+		run_to_block(2, Some(vec![2]));
+		assert_eq!(Paras::extra_cores(), vec![(a, 1), (b, 2)]);
+
+		// Setting zero extra cores removes the entry.
+		assert_ok!(Paras::force_set_extra_cores(RuntimeOrigin::root(), a, 0));
+		assert_ok!(Paras::force_set_extra_cores(RuntimeOrigin::root(), b, 3));
+		assert_eq!(Paras::extra_cores(), vec![(a, 1), (b, 2)]);
+
+		run_to_block(3, Some(vec![3]));
+		assert_eq!(Paras::extra_cores(), vec![(b, 3)]);
+
+		// The number of extra cores is bounded.
+		assert_err!(
+			Paras::force_set_extra_cores(
+				RuntimeOrigin::root(),
+				a,
+				<Test as Config>::MaxExtraCores::get() + 1
+			),
+			Error::<Test>::TooManyExtraCores,
+		);
+	});
+}
+
 #[test]
 fn code_hash_at_returns_up_to_end_of_code_retention_period() {
 	let code_retention_period = 10;
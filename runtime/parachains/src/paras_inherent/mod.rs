@@ -25,11 +25,11 @@ use crate::{
 	configuration,
 	disputes::{DisputesHandler, VerifyDisputeSignatures},
 	inclusion,
-	inclusion::{CandidateCheckContext, FullCheck},
+	inclusion::{CandidateCheckContext, ChainedOutputs, FullCheck},
 	initializer,
 	metrics::METRICS,
 	scheduler::{self, CoreAssignment, FreedReason},
-	shared, ump, ParaId,
+	shared, ParaId,
 };
 use bitvec::prelude::BitVec;
 use frame_support::{
@@ -65,8 +65,8 @@ pub use self::{
 	misc::{IndexedRetain, IsSortedBy},
 	weights::{
 		backed_candidate_weight, backed_candidates_weight, dispute_statement_set_weight,
-		multi_dispute_statement_sets_weight, paras_inherent_total_weight, signed_bitfields_weight,
-		TestWeightInfo, WeightInfo,
+		inherent_weight_info, multi_dispute_statement_sets_weight, paras_inherent_total_weight,
+		signed_bitfields_weight, TestWeightInfo, WeightInfo,
 	},
 };
 
@@ -533,10 +533,6 @@ impl<T: Config> Pallet<T> {
 		// Note which of the scheduled cores were actually occupied by a backed candidate.
 		<scheduler::Pallet<T>>::occupied(&occupied);
 
-		// Give some time slice to dispatch pending upward messages.
-		// this is max config.ump_service_total_weight
-		let _ump_weight = <ump::Pallet<T>>::process_pending_upward_messages();
-
 		METRICS.on_after_filter(total_consumed_weight.ref_time());
 
 		Ok(Some(total_consumed_weight).into())
@@ -711,21 +707,39 @@ impl<T: Config> Pallet<T> {
 			let parent_storage_root = *parent_header.state_root();
 
 			let check_ctx = CandidateCheckContext::<T>::new(now, relay_parent_number);
+			// The outputs of the candidates of each para accepted so far, which the following
+			// candidates of the para are chained to.
+			let mut chains = BTreeMap::<ParaId, ChainedOutputs<T::BlockNumber>>::new();
 			let backed_candidates = sanitize_backed_candidates::<T, _>(
 				parent_hash,
 				backed_candidates,
 				move |candidate_idx: usize,
 				      backed_candidate: &BackedCandidate<<T as frame_system::Config>::Hash>|
 				      -> bool {
+					let para_id = backed_candidate.descriptor().para_id;
+					// the first candidate of a para requires it to be free, the following ones
+					// are chained to it.
+					if !chains.contains_key(&para_id) &&
+						<inclusion::Pallet<T>>::pending_availability(para_id).is_some()
+					{
+						return true
+					}
+
+					let chained = chains.entry(para_id).or_default();
 					// never include a concluded-invalid candidate
-					concluded_invalid_disputes.contains(&backed_candidate.hash()) ||
+					let invalid = concluded_invalid_disputes.contains(&backed_candidate.hash()) ||
 							// Instead of checking the candidates with code upgrades twice
 							// move the checking up here and skip it in the training wheels fallback.
 							// That way we avoid possible duplicate checks while assuring all
 							// backed candidates fine to pass on.
 							check_ctx
-								.verify_backed_candidate(parent_hash, parent_storage_root, candidate_idx, backed_candidate)
-								.is_err()
+								.verify_backed_candidate(parent_hash, parent_storage_root, candidate_idx, backed_candidate, chained)
+								.is_err();
+
+					if !invalid {
+						chained.append(&backed_candidate.candidate.commitments);
+					}
+					invalid
 				},
 				&scheduled[..],
 			);
@@ -901,7 +915,18 @@ fn apply_weight_limit<T: Config + inclusion::Config>(
 				|c| backed_candidate_weight::<T>(c),
 				max_consumable_by_candidates,
 			);
-		candidates.indexed_retain(|idx, _backed_candidate| indices.binary_search(&idx).is_ok());
+		// The candidates of a para are chained, so a candidate is dropped along with the
+		// candidate it is chained to.
+		let mut broken_chains = BTreeSet::new();
+		candidates.indexed_retain(|idx, backed_candidate| {
+			let para_id = backed_candidate.descriptor().para_id;
+			if !broken_chains.contains(&para_id) && indices.binary_search(&idx).is_ok() {
+				true
+			} else {
+				broken_chains.insert(para_id);
+				false
+			}
+		});
 		// pick all bitfields, and
 		// fill the remaining space with candidates
 		let total_consumed = acc_candidate_weight.saturating_add(total_bitfields_weight);
@@ -1102,6 +1127,10 @@ pub(crate) fn assure_sanity_bitfields<T: crate::inclusion::Config>(
 /// `candidate_has_concluded_invalid_dispute` must return `true` if the candidate
 /// is disputed, false otherwise. The passed `usize` is the candidate index.
 ///
+/// The candidates of a para occupy the cores scheduled for it in ascending order, which
+/// requires them to be provided in the order they are chained in. Candidates exceeding the
+/// number of scheduled cores of their para are dropped.
+///
 /// The returned `Vec` is sorted according to the occupied core index.
 fn sanitize_backed_candidates<
 	T: crate::inclusion::Config,
@@ -1118,33 +1147,52 @@ fn sanitize_backed_candidates<
 		!candidate_has_concluded_invalid_dispute_or_is_invalid(candidate_idx, backed_candidate)
 	});
 
-	let scheduled_paras_to_core_idx = scheduled
-		.into_iter()
-		.map(|core_assignment| (core_assignment.para_id, core_assignment.core))
-		.collect::<BTreeMap<ParaId, CoreIndex>>();
+	let mut scheduled_paras_to_core_idx = scheduled_paras_to_core_idx(scheduled);
 
 	// Assure the backed candidate's `ParaId`'s core is free.
 	// This holds under the assumption that `Scheduler::schedule` is called _before_.
 	// Also checks the candidate references the correct relay parent.
+	let mut backed_candidates = backed_candidates
+		.into_iter()
+		.filter_map(|backed_candidate| {
+			let desc = backed_candidate.descriptor();
+			if desc.relay_parent != relay_parent {
+				return None
+			}
 
-	backed_candidates.retain(|backed_candidate| {
-		let desc = backed_candidate.descriptor();
-		desc.relay_parent == relay_parent &&
-			scheduled_paras_to_core_idx.get(&desc.para_id).is_some()
-	});
+			let core = scheduled_paras_to_core_idx.get_mut(&desc.para_id)?.next()?;
+			Some((core, backed_candidate))
+		})
+		.collect::<Vec<_>>();
 
 	// Sort the `Vec` last, once there is a guarantee that these
 	// `BackedCandidates` references the expected relay chain parent,
 	// but more importantly are scheduled for a free core.
-	// This both avoids extra work for obviously invalid candidates,
-	// but also allows this to be done in place.
-	backed_candidates.sort_by(|x, y| {
-		// Never panics, since we filtered all panic arguments out in the previous `fn retain`.
-		scheduled_paras_to_core_idx[&x.descriptor().para_id]
-			.cmp(&scheduled_paras_to_core_idx[&y.descriptor().para_id])
-	});
+	// This avoids extra work for obviously invalid candidates.
+	backed_candidates.sort_by_key(|(core, _)| *core);
 
 	backed_candidates
+		.into_iter()
+		.map(|(_, backed_candidate)| backed_candidate)
+		.collect()
+}
+
+/// The scheduled cores of each para, in ascending order.
+fn scheduled_paras_to_core_idx(
+	scheduled: &[CoreAssignment],
+) -> BTreeMap<ParaId, impl Iterator<Item = CoreIndex>> {
+	let mut scheduled_paras_to_core_idx = BTreeMap::<ParaId, Vec<CoreIndex>>::new();
+	for core_assignment in scheduled {
+		scheduled_paras_to_core_idx
+			.entry(core_assignment.para_id)
+			.or_default()
+			.push(core_assignment.core);
+	}
+
+	scheduled_paras_to_core_idx
+		.into_iter()
+		.map(|(para_id, cores)| (para_id, cores.into_iter()))
+		.collect()
 }
 
 /// Assumes sorted candidates.
@@ -1172,16 +1220,18 @@ pub(crate) fn assure_sanity_backed_candidates<
 		}
 	}
 
-	let scheduled_paras_to_core_idx = scheduled
-		.into_iter()
-		.map(|core_assignment| (core_assignment.para_id, core_assignment.core))
-		.collect::<BTreeMap<ParaId, CoreIndex>>();
-
-	if !IsSortedBy::is_sorted_by(backed_candidates, |x, y| {
-		// Never panics, since we would have early returned on those in the above loop.
-		scheduled_paras_to_core_idx[&x.descriptor().para_id]
-			.cmp(&scheduled_paras_to_core_idx[&y.descriptor().para_id])
-	}) {
+	let mut scheduled_paras_to_core_idx = scheduled_paras_to_core_idx(scheduled);
+	let cores = backed_candidates
+		.iter()
+		.map(|backed_candidate| {
+			scheduled_paras_to_core_idx
+				.get_mut(&backed_candidate.descriptor().para_id)
+				.and_then(|cores| cores.next())
+				.ok_or(Error::<T>::UnscheduledCandidate)
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	if !IsSortedBy::is_sorted_by(&cores[..], |x, y| x.cmp(y)) {
 		return Err(Error::<T>::UnsortedOrDuplicateBackedCandidates)
 	}
 	Ok(())
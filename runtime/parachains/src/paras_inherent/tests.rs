@@ -857,6 +857,43 @@ mod enter {
 			assert_matches!(Pallet::<Test>::on_chain_votes(), None);
 		});
 	}
+
+	#[test]
+	// Ensure that block authors can compute the exact weight of the inherent data from the
+	// weight parameters exposed by the runtime.
+	fn inherent_weight_info_matches_inherent_data_weight() {
+		new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+			let mut dispute_statements = BTreeMap::new();
+			dispute_statements.insert(2, 7);
+			dispute_statements.insert(3, 12);
+
+			let mut backed_and_concluding = BTreeMap::new();
+			backed_and_concluding.insert(0, 1);
+			backed_and_concluding.insert(1, 3);
+
+			let scenario = make_inherent_data(TestConfig {
+				dispute_statements,
+				dispute_sessions: vec![2, 2],
+				backed_and_concluding,
+				num_validators_per_core: 3,
+				code_upgrade: None,
+			});
+			let data = scenario.data;
+
+			let info = inherent_weight_info::<Test>();
+			let node_side_weight = data
+				.disputes
+				.iter()
+				.map(|set| info.dispute_statement_set_weight(set))
+				.chain(data.backed_candidates.iter().map(|c| info.backed_candidate_weight(c)))
+				.fold(info.bitfields_weight(data.bitfields.len()), |acc, w| acc.saturating_add(w));
+
+			let weight = inherent_data_weight(&data);
+			assert_eq!(node_side_weight.ref_time, weight.ref_time());
+			assert_eq!(node_side_weight.proof_size, weight.proof_size());
+			assert_eq!(info.max_block.ref_time, max_block_weight().ref_time());
+		});
+	}
 }
 
 fn default_header() -> primitives::Header {
@@ -16,6 +16,8 @@
 use super::{
 	BackedCandidate, Config, DisputeStatementSet, UncheckedSignedAvailabilityBitfield, Weight,
 };
+use frame_support::traits::Get;
+use primitives::vstaging::{InherentWeight, InherentWeightInfo};
 
 pub trait WeightInfo {
 	/// Variant over `v`, the count of dispute statements in a dispute statement set. This gives the
@@ -110,13 +112,22 @@ pub fn signed_bitfields_weight<T: Config>(bitfields_len: usize) -> Weight {
 pub fn backed_candidate_weight<T: frame_system::Config + Config>(
 	candidate: &BackedCandidate<T::Hash>,
 ) -> Weight {
-	if candidate.candidate.commitments.new_validation_code.is_some() {
+	let weight = if candidate.candidate.commitments.new_validation_code.is_some() {
 		<<T as Config>::WeightInfo as WeightInfo>::enter_backed_candidate_code_upgrade()
 	} else {
 		<<T as Config>::WeightInfo as WeightInfo>::enter_backed_candidates_variable(
 			candidate.validity_votes.len() as u32,
 		)
-	}
+	};
+
+	weight.saturating_add(core_occupied_weight::<T>())
+}
+
+/// The weight of noting the para occupying the core of a backed candidate, which is not covered
+/// by the benchmarks.
+fn core_occupied_weight<T: frame_system::Config>() -> Weight {
+	// `OccupyingParachains` in `scheduler::occupied`.
+	T::DbWeight::get().writes(1)
 }
 
 pub fn backed_candidates_weight<T: frame_system::Config + Config>(
@@ -127,3 +138,38 @@ pub fn backed_candidates_weight<T: frame_system::Config + Config>(
 		.map(|c| backed_candidate_weight::<T>(c))
 		.fold(Weight::zero(), |acc, x| acc.saturating_add(x))
 }
+
+/// The weight parameters of the `paras_inherent`, for the block author to limit the inherent data
+/// it provides. Relies on the weights being linear in the number of votes.
+pub fn inherent_weight_info<T: Config>() -> InherentWeightInfo {
+	let to_inherent_weight = |weight: Weight| InherentWeight {
+		ref_time: weight.ref_time(),
+		proof_size: weight.proof_size(),
+	};
+
+	let dispute_statement_set =
+		<<T as Config>::WeightInfo as WeightInfo>::enter_variable_disputes(0);
+	let backed_candidate =
+		<<T as Config>::WeightInfo as WeightInfo>::enter_backed_candidates_variable(0);
+
+	InherentWeightInfo {
+		max_block: to_inherent_weight(<T as frame_system::Config>::BlockWeights::get().max_block),
+		dispute_statement_set: to_inherent_weight(dispute_statement_set),
+		dispute_statement: to_inherent_weight(
+			<<T as Config>::WeightInfo as WeightInfo>::enter_variable_disputes(1)
+				.saturating_sub(dispute_statement_set),
+		),
+		bitfield: to_inherent_weight(<<T as Config>::WeightInfo as WeightInfo>::enter_bitfields()),
+		backed_candidate: to_inherent_weight(
+			backed_candidate.saturating_add(core_occupied_weight::<T>()),
+		),
+		validity_vote: to_inherent_weight(
+			<<T as Config>::WeightInfo as WeightInfo>::enter_backed_candidates_variable(1)
+				.saturating_sub(backed_candidate),
+		),
+		backed_candidate_code_upgrade: to_inherent_weight(
+			<<T as Config>::WeightInfo as WeightInfo>::enter_backed_candidate_code_upgrade()
+				.saturating_add(core_occupied_weight::<T>()),
+		),
+	}
+}
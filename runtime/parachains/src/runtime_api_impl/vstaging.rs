@@ -16,8 +16,21 @@
 
 //! Put implementations of functions from staging APIs here.
 
-use crate::disputes;
-use primitives::{CandidateHash, DisputeState, SessionIndex};
+use crate::{
+	disputes::{self, slashing},
+	dmp, hrmp,
+	message_queue::EnqueueMessage,
+	on_demand, paras_inherent, ump,
+};
+use frame_support::traits::Get;
+use primitives::{
+	vstaging::{
+		ApprovalVotingParams, BackingMisbehaviorReport, HrmpChannelSummary, InherentWeightInfo,
+		QueueFootprint,
+	},
+	Balance, CandidateHash, DisputeState, Id as ParaId, SessionIndex, ValidatorSignature,
+};
+use sp_runtime::traits::SaturatedConversion;
 use sp_std::prelude::*;
 
 /// Implementation for `get_session_disputes` function from the runtime API
@@ -25,3 +38,44 @@ pub fn get_session_disputes<T: disputes::Config>(
 ) -> Vec<(SessionIndex, CandidateHash, DisputeState<T::BlockNumber>)> {
 	<disputes::Pallet<T>>::disputes()
 }
+
+/// Implementation for `approval_voting_params` function from the runtime API
+pub fn approval_voting_params<MaxApprovalCoalesceCount: Get<u32>>() -> ApprovalVotingParams {
+	ApprovalVotingParams { max_approval_coalesce_count: MaxApprovalCoalesceCount::get().max(1) }
+}
+
+/// Submits a report of backing misbehavior. See
+/// [`slashing::Pallet::submit_backing_misbehavior_report`].
+pub fn submit_backing_misbehavior_report<T>(
+	report: BackingMisbehaviorReport,
+	signature: ValidatorSignature,
+) where
+	T: slashing::Config + frame_system::offchain::SendTransactionTypes<slashing::Call<T>>,
+{
+	<slashing::Pallet<T>>::submit_backing_misbehavior_report(report, signature)
+}
+
+/// Implementation for `inherent_weight_info` function from the runtime API
+pub fn inherent_weight_info<T: paras_inherent::Config>() -> InherentWeightInfo {
+	paras_inherent::inherent_weight_info::<T>()
+}
+
+/// Implementation for `on_demand_spot_price` function from the runtime API
+pub fn on_demand_spot_price<T: on_demand::Config>() -> Balance {
+	<on_demand::Pallet<T>>::spot_price().saturated_into()
+}
+
+/// Implementation for `ump_queue_footprint` function from the runtime API
+pub fn ump_queue_footprint<T: ump::Config>(para_id: ParaId) -> QueueFootprint {
+	T::MessageQueue::footprint(ump::AggregateMessageOrigin::Ump(para_id))
+}
+
+/// Implementation for `dmq_footprint` function from the runtime API
+pub fn dmq_footprint<T: dmp::Config>(para_id: ParaId) -> QueueFootprint {
+	<dmp::Pallet<T>>::dmq_footprint(para_id)
+}
+
+/// Implementation for `hrmp_channels` function from the runtime API
+pub fn hrmp_channels<T: hrmp::Config>(para_id: ParaId) -> Vec<HrmpChannelSummary> {
+	<hrmp::Pallet<T>>::hrmp_channels(para_id)
+}
@@ -30,10 +30,14 @@
 //! - High or close to optimal throughput of parachains and parathreads. Work among validator groups should be balanced.
 //!
 //! The Scheduler manages resource allocation using the concept of "Availability Cores".
-//! There will be one availability core for each parachain, and a fixed number of cores
-//! used for multiplexing parathreads. Validators will be partitioned into groups, with the same
+//! There will be one availability core for each parachain, plus any extra cores assigned to
+//! parachains which need more throughput, and a fixed number of cores used for multiplexing
+//! parathreads. Validators will be partitioned into groups, with the same
 //! number of groups as availability cores. Validator groups will be assigned to different availability cores
 //! over time.
+//!
+//! Which para a free core is assigned to is decided by the [`AssignmentProvider`] of the runtime,
+//! see the [`assignment`] module.
 
 use frame_support::pallet_prelude::*;
 use primitives::{
@@ -48,6 +52,12 @@ use crate::{configuration, initializer::SessionChangeNotification, paras};
 
 pub use pallet::*;
 
+pub mod assignment;
+
+pub use assignment::{
+	Assignment, AssignmentProvider, ParachainAssignmentProvider, ParathreadAssignmentProvider,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -98,6 +108,34 @@ impl Default for ParathreadClaimQueue {
 	}
 }
 
+/// Reasons a parathread claim might not be added to the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParathreadClaimError {
+	/// The claim does not correspond to any live parathread.
+	NotParathread,
+	/// The queue is full.
+	QueueFull,
+	/// There is a competing claim in the queue or currently assigned to a core.
+	AlreadyClaimed,
+}
+
+/// Something that is told when a parathread claim leaves the scheduler.
+pub trait OnParathreadClaimEnded {
+	/// A candidate for the claim of `para_id` was included.
+	fn on_claim_processed(para_id: ParaId);
+
+	/// The claim of `para_id` was dropped before a candidate for it was included. This happens
+	/// when the claim runs out of retries, when the para stops being a parathread or when there
+	/// are no parathread cores anymore.
+	fn on_claim_dropped(para_id: ParaId);
+}
+
+impl OnParathreadClaimEnded for () {
+	fn on_claim_processed(_para_id: ParaId) {}
+
+	fn on_claim_dropped(_para_id: ParaId) {}
+}
+
 /// Reasons a core might be freed
 #[derive(Clone, Copy)]
 pub enum FreedReason {
@@ -163,7 +201,13 @@ pub mod pallet {
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + configuration::Config + paras::Config {}
+	pub trait Config: frame_system::Config + configuration::Config + paras::Config {
+		/// Assigns the availability cores to paras.
+		type AssignmentProvider: AssignmentProvider<Self::BlockNumber>;
+
+		/// Notified when a parathread claim is processed or dropped.
+		type OnParathreadClaimEnded: OnParathreadClaimEnded;
+	}
 
 	/// All the validator groups. One for each core. Indices are into `ActiveValidators` - not the
 	/// broader set of Polkadot validators, but instead just the subset used for parachains during
@@ -184,16 +228,27 @@ pub mod pallet {
 
 	/// One entry for each availability core. Entries are `None` if the core is not currently occupied. Can be
 	/// temporarily `Some` if scheduled but not occupied.
-	/// The i'th parachain belongs to the i'th core, with the remaining cores all being
-	/// parathread-multiplexers.
+	/// The cores are assigned to paras by the [`Config::AssignmentProvider`], e.g. the i'th
+	/// parachain belongs to the i'th core, followed by the extra cores of parachains
+	/// (see [`Pallet::parachain_cores`]) and the parathread-multiplexers.
 	///
 	/// Bounded by the maximum of either of these two values:
-	///   * The number of parachains and parathread multiplexers
+	///   * The number of cores of the assignment provider
 	///   * The number of validators divided by `configuration.max_validators_per_core`.
 	#[pallet::storage]
 	#[pallet::getter(fn availability_cores)]
 	pub(crate) type AvailabilityCores<T> = StorageValue<_, Vec<Option<CoreOccupied>>, ValueQuery>;
 
+	/// The para occupying each availability core which is occupied by a parachain, i.e. is
+	/// `CoreOccupied::Parachain`.
+	///
+	/// Cores without an entry are occupied by the parachain at the same position in
+	/// [`Pallet::parachain_cores`].
+	///
+	/// Bounded by the number of cores.
+	#[pallet::storage]
+	pub(crate) type OccupyingParachains<T> = StorageMap<_, Twox64Concat, CoreIndex, ParaId>;
+
 	/// An index used to ensure that only one claim on a parathread exists in the queue or is
 	/// currently being handled by an occupied core.
 	///
@@ -239,31 +294,33 @@ impl<T: Config> Pallet<T> {
 		let &SessionChangeNotification { ref validators, ref new_config, .. } = notification;
 		let config = new_config;
 
-		let mut thread_queue = ParathreadQueue::<T>::get();
-		let n_parachains = <paras::Pallet<T>>::parachains().len() as u32;
+		// clear all occupied cores, handing their assignments back to the providers.
+		let mut returned = Vec::new();
+		for (i, occupied) in AvailabilityCores::<T>::take().into_iter().enumerate() {
+			match occupied {
+				Some(CoreOccupied::Parathread(entry)) =>
+					returned.push(Assignment::parathread(entry)),
+				Some(CoreOccupied::Parachain) => {
+					if let Some(para_id) = OccupyingParachains::<T>::get(CoreIndex(i as u32)) {
+						returned.push(Assignment::parachain(para_id));
+					}
+				},
+				None => {},
+			}
+		}
+		let _ = OccupyingParachains::<T>::clear(u32::MAX, None);
+
+		T::AssignmentProvider::on_new_session(config, returned);
+
 		let n_cores = core::cmp::max(
-			n_parachains + config.parathread_cores,
+			T::AssignmentProvider::session_core_count(config),
 			match config.max_validators_per_core {
 				Some(x) if x != 0 => validators.len() as u32 / x,
 				_ => 0,
 			},
 		);
 
-		AvailabilityCores::<T>::mutate(|cores| {
-			// clear all occupied cores.
-			for maybe_occupied in cores.iter_mut() {
-				if let Some(CoreOccupied::Parathread(claim)) = maybe_occupied.take() {
-					let queued = QueuedParathread {
-						claim,
-						core_offset: 0, // this gets set later in the re-balancing.
-					};
-
-					thread_queue.queue.push(queued);
-				}
-			}
-
-			cores.resize(n_cores as _, None);
-		});
+		AvailabilityCores::<T>::set(vec![None; n_cores as usize]);
 
 		// shuffle validators into groups.
 		if n_cores == 0 || validators.is_empty() {
@@ -299,45 +356,6 @@ impl<T: Config> Pallet<T> {
 			ValidatorGroups::<T>::set(groups);
 		}
 
-		// prune out all parathread claims with too many retries.
-		// assign all non-pruned claims to new cores, if they've changed.
-		ParathreadClaimIndex::<T>::mutate(|claim_index| {
-			// wipe all parathread metadata if no parathread cores are configured.
-			if config.parathread_cores == 0 {
-				thread_queue = ParathreadClaimQueue { queue: Vec::new(), next_core_offset: 0 };
-				claim_index.clear();
-				return
-			}
-
-			// prune out all entries beyond retry or that no longer correspond to live parathread.
-			thread_queue.queue.retain(|queued| {
-				let will_keep = queued.claim.retries <= config.parathread_retries &&
-					<paras::Pallet<T>>::is_parathread(queued.claim.claim.0);
-
-				if !will_keep {
-					let claim_para = queued.claim.claim.0;
-
-					// clean up the pruned entry from the index.
-					if let Ok(i) = claim_index.binary_search(&claim_para) {
-						claim_index.remove(i);
-					}
-				}
-
-				will_keep
-			});
-
-			// do re-balancing of claims.
-			{
-				for (i, queued) in thread_queue.queue.iter_mut().enumerate() {
-					queued.core_offset = (i as u32) % config.parathread_cores;
-				}
-
-				thread_queue.next_core_offset =
-					((thread_queue.queue.len()) as u32) % config.parathread_cores;
-			}
-		});
-		ParathreadQueue::<T>::set(thread_queue);
-
 		let now = <frame_system::Pallet<T>>::block_number() + One::one();
 		<SessionStartBlock<T>>::set(now);
 	}
@@ -348,8 +366,16 @@ impl<T: Config> Pallet<T> {
 	/// Fails if the claim does not correspond to any live parathread.
 	#[allow(unused)]
 	pub fn add_parathread_claim(claim: ParathreadClaim) {
+		let _ = Self::try_add_parathread_claim(claim);
+	}
+
+	/// Add a parathread claim to the queue, like [`Self::add_parathread_claim`], returning why
+	/// the claim was not added, if so.
+	pub(crate) fn try_add_parathread_claim(
+		claim: ParathreadClaim,
+	) -> Result<(), ParathreadClaimError> {
 		if !<paras::Pallet<T>>::is_parathread(claim.0) {
-			return
+			return Err(ParathreadClaimError::NotParathread)
 		}
 
 		let config = <configuration::Pallet<T>>::config();
@@ -357,7 +383,7 @@ impl<T: Config> Pallet<T> {
 
 		ParathreadQueue::<T>::mutate(|queue| {
 			if queue.queue.len() >= queue_max_size as usize {
-				return
+				return Err(ParathreadClaimError::QueueFull)
 			}
 
 			let para_id = claim.0;
@@ -372,14 +398,23 @@ impl<T: Config> Pallet<T> {
 				});
 
 			if competes_with_another {
-				return
+				return Err(ParathreadClaimError::AlreadyClaimed)
 			}
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+			Ok(())
 		})
 	}
 
+	/// The number of claims in the parathread queue and the maximum number of claims it can hold.
+	pub(crate) fn parathread_queue_utilization() -> (u32, u32) {
+		let config = <configuration::Pallet<T>>::config();
+		let queue_max_size = config.parathread_cores * config.scheduling_lookahead;
+
+		(ParathreadQueue::<T>::get().queue.len() as u32, queue_max_size)
+	}
+
 	/// Free unassigned cores. Provide a list of cores that should be considered newly-freed along with the reason
 	/// for them being freed. The list is assumed to be sorted in ascending order by core index.
 	pub(crate) fn free_cores(just_freed_cores: impl IntoIterator<Item = (CoreIndex, FreedReason)>) {
@@ -388,29 +423,34 @@ impl<T: Config> Pallet<T> {
 		AvailabilityCores::<T>::mutate(|cores| {
 			for (freed_index, freed_reason) in just_freed_cores {
 				if (freed_index.0 as usize) < cores.len() {
-					match cores[freed_index.0 as usize].take() {
+					let assignment = match cores[freed_index.0 as usize].take() {
 						None => continue,
-						Some(CoreOccupied::Parachain) => {},
-						Some(CoreOccupied::Parathread(entry)) => {
-							match freed_reason {
-								FreedReason::Concluded => {
-									// After a parathread candidate has successfully been included,
-									// open it up for further claims!
-									ParathreadClaimIndex::<T>::mutate(|index| {
-										if let Ok(i) = index.binary_search(&entry.claim.0) {
-											index.remove(i);
-										}
-									})
-								},
-								FreedReason::TimedOut => {
-									// If a parathread candidate times out, it's not the collator's fault,
-									// so we don't increment retries.
-									ParathreadQueue::<T>::mutate(|queue| {
-										queue.enqueue_entry(entry, config.parathread_cores);
-									})
-								},
+						Some(CoreOccupied::Parachain) => {
+							let para_id = Self::occupying_parachain(freed_index);
+							OccupyingParachains::<T>::remove(freed_index);
+							match para_id {
+								Some(para_id) => Assignment::parachain(para_id),
+								None => continue,
 							}
 						},
+						Some(CoreOccupied::Parathread(entry)) => Assignment::parathread(entry),
+					};
+
+					match freed_reason {
+						FreedReason::Concluded => T::AssignmentProvider::report_processed(
+							&config,
+							freed_index,
+							assignment,
+						),
+						FreedReason::TimedOut => {
+							// If a candidate times out, it's not the collator's fault,
+							// so we don't increment retries.
+							T::AssignmentProvider::push_assignment_for_core(
+								&config,
+								freed_index,
+								assignment,
+							)
+						},
 					}
 				}
 			}
@@ -426,10 +466,9 @@ impl<T: Config> Pallet<T> {
 	) {
 		Self::free_cores(just_freed_cores);
 
+		let config = <configuration::Pallet<T>>::config();
 		let cores = AvailabilityCores::<T>::get();
-		let parachains = <paras::Pallet<T>>::parachains();
 		let mut scheduled = Scheduled::<T>::get();
-		let mut parathread_queue = ParathreadQueue::<T>::get();
 
 		if ValidatorGroups::<T>::get().is_empty() {
 			return
@@ -478,31 +517,18 @@ impl<T: Config> Pallet<T> {
 
 				let core = CoreIndex(core_index as u32);
 
-				let core_assignment = if core_index < parachains.len() {
-					// parachain core.
-					Some(CoreAssignment {
-						kind: AssignmentKind::Parachain,
-						para_id: parachains[core_index],
-						core,
-						group_idx: Self::group_assigned_to_core(core, now).expect(
-							"core is not out of bounds and we are guaranteed \
-									to be after the most recent session start; qed",
-						),
-					})
-				} else {
-					// parathread core offset, rel. to beginning.
-					let core_offset = (core_index - parachains.len()) as u32;
-
-					parathread_queue.take_next_on_core(core_offset).map(|entry| CoreAssignment {
-						kind: AssignmentKind::Parathread(entry.claim.1, entry.retries),
-						para_id: entry.claim.0,
-						core,
-						group_idx: Self::group_assigned_to_core(core, now).expect(
-							"core is not out of bounds and we are guaranteed \
-									to be after the most recent session start; qed",
-						),
-					})
-				};
+				let core_assignment = T::AssignmentProvider::pop_assignment_for_core(
+					&config, core, now,
+				)
+				.map(|assignment| CoreAssignment {
+					kind: assignment.kind,
+					para_id: assignment.para_id,
+					core,
+					group_idx: Self::group_assigned_to_core(core, now).expect(
+						"core is not out of bounds and we are guaranteed \
+								to be after the most recent session start; qed",
+					),
+				});
 
 				if let Some(assignment) = core_assignment {
 					scheduled_updates.push((schedule_and_insert_at, assignment))
@@ -528,7 +554,6 @@ impl<T: Config> Pallet<T> {
 		}
 
 		Scheduled::<T>::set(scheduled);
-		ParathreadQueue::<T>::set(parathread_queue);
 	}
 
 	/// Note that the given cores have become occupied. Behavior undefined if any of the given cores were not scheduled
@@ -559,6 +584,9 @@ impl<T: Config> Pallet<T> {
 
 					availability_cores[assignment.core.0 as usize] =
 						Some(assignment.to_core_occupied());
+					if let AssignmentKind::Parachain = assignment.kind {
+						OccupyingParachains::<T>::insert(assignment.core, assignment.para_id);
+					}
 				}
 
 				retain
@@ -574,14 +602,34 @@ impl<T: Config> Pallet<T> {
 		let cores = AvailabilityCores::<T>::get();
 		match cores.get(core_index.0 as usize).and_then(|c| c.as_ref()) {
 			None => None,
-			Some(CoreOccupied::Parachain) => {
-				let parachains = <paras::Pallet<T>>::parachains();
-				Some(parachains[core_index.0 as usize])
-			},
+			Some(CoreOccupied::Parachain) => Self::occupying_parachain(core_index),
 			Some(CoreOccupied::Parathread(ref entry)) => Some(entry.claim.0),
 		}
 	}
 
+	/// Get the para occupying a core which is occupied by a parachain.
+	pub(crate) fn occupying_parachain(core_index: CoreIndex) -> Option<ParaId> {
+		OccupyingParachains::<T>::get(core_index)
+			.or_else(|| Self::parachain_cores().get(core_index.0 as usize).copied())
+	}
+
+	/// Get the para assigned to each parachain core, ordered by core index.
+	///
+	/// Each parachain has one core, in the order of `paras::Parachains`. These are followed by
+	/// the extra cores of the parachains that have any, in the order of `paras::ExtraCores`.
+	/// All cores after these are parathread multiplexers.
+	pub(crate) fn parachain_cores() -> Vec<ParaId> {
+		let mut cores = <paras::Pallet<T>>::parachains();
+		let n_parachains = cores.len();
+		for (para, extra_cores) in <paras::Pallet<T>>::extra_cores() {
+			if cores[..n_parachains].binary_search(&para).is_ok() {
+				cores.extend(sp_std::iter::repeat(para).take(extra_cores as usize));
+			}
+		}
+
+		cores
+	}
+
 	/// Get the validators in the given group, if the group index is valid for this session.
 	pub(crate) fn group_validators(group_index: GroupIndex) -> Option<Vec<ValidatorIndex>> {
 		ValidatorGroups::<T>::get().get(group_index.0 as usize).map(|g| g.clone())
@@ -682,21 +730,16 @@ impl<T: Config> Pallet<T> {
 	/// Return the next thing that will be scheduled on this core assuming it is currently
 	/// occupied and the candidate occupying it became available.
 	///
+	/// This is the next assignment of the [`AssignmentProvider`] for the core in the next block.
 	/// For parachains, this is always the ID of the parachain and no specified collator.
 	/// For parathreads, this is based on the next item in the `ParathreadQueue` assigned to that
 	/// core, and is None if there isn't one.
 	pub(crate) fn next_up_on_available(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Pallet<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore { para_id: parachains[core.0 as usize], collator: None })
-		} else {
-			let queue = ParathreadQueue::<T>::get();
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
-			queue.get_next_on_core(core_offset).map(|entry| ScheduledCore {
-				para_id: entry.claim.0,
-				collator: Some(entry.claim.1.clone()),
-			})
-		}
+		let config = <configuration::Pallet<T>>::config();
+		let now = <frame_system::Pallet<T>>::block_number() + One::one();
+
+		T::AssignmentProvider::peek_assignment_for_core(&config, core, now)
+			.map(|assignment| assignment.to_scheduled_core())
 	}
 
 	/// Return the next thing that will be scheduled on this core assuming it is currently
@@ -707,57 +750,61 @@ impl<T: Config> Pallet<T> {
 	/// core, or if there isn't one, the claim that is currently occupying the core, as long
 	/// as the claim's retries would not exceed the limit. Otherwise None.
 	pub(crate) fn next_up_on_time_out(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Pallet<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore { para_id: parachains[core.0 as usize], collator: None })
-		} else {
-			let queue = ParathreadQueue::<T>::get();
-
-			// This is the next scheduled para on this core.
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
-			queue
-				.get_next_on_core(core_offset)
-				.map(|entry| ScheduledCore {
+		// This is the next scheduled para on this core.
+		Self::next_up_on_available(core).or_else(|| {
+			// Or, if none, the claim currently occupying the core,
+			// as it would be put back on the queue after timing out.
+			let cores = AvailabilityCores::<T>::get();
+			cores.get(core.0 as usize).and_then(|c| c.as_ref()).and_then(|o| match o {
+				CoreOccupied::Parathread(entry) => Some(ScheduledCore {
 					para_id: entry.claim.0,
 					collator: Some(entry.claim.1.clone()),
-				})
-				.or_else(|| {
-					// Or, if none, the claim currently occupying the core,
-					// as it would be put back on the queue after timing out.
-					let cores = AvailabilityCores::<T>::get();
-					cores.get(core.0 as usize).and_then(|c| c.as_ref()).and_then(|o| {
-						match o {
-							CoreOccupied::Parathread(entry) => Some(ScheduledCore {
-								para_id: entry.claim.0,
-								collator: Some(entry.claim.1.clone()),
-							}),
-							CoreOccupied::Parachain => None, // defensive; not possible.
-						}
-					})
-				})
-		}
+				}),
+				CoreOccupied::Parachain => None,
+			})
+		})
+	}
+
+	/// Remove the claim of `para_id`, which is neither queued nor assigned to a core anymore,
+	/// from the claim index, so that the para can be claimed again.
+	fn drop_parathread_claim(para_id: ParaId) {
+		ParathreadClaimIndex::<T>::mutate(|index| {
+			if let Ok(i) = index.binary_search(&para_id) {
+				index.remove(i);
+			}
+		});
+		T::OnParathreadClaimEnded::on_claim_dropped(para_id);
 	}
 
-	// Free all scheduled cores and return parathread claims to queue, with retries incremented.
+	// Free all scheduled cores and return their assignments to the providers, with the retries
+	// of parathread claims incremented.
 	pub(crate) fn clear() {
 		let config = <configuration::Pallet<T>>::config();
-		ParathreadQueue::<T>::mutate(|queue| {
-			for core_assignment in Scheduled::<T>::take() {
-				if let AssignmentKind::Parathread(collator, retries) = core_assignment.kind {
-					if !<paras::Pallet<T>>::is_parathread(core_assignment.para_id) {
-						continue
-					}
-
+		for core_assignment in Scheduled::<T>::take() {
+			let assignment = match core_assignment.kind {
+				AssignmentKind::Parachain => Assignment::parachain(core_assignment.para_id),
+				AssignmentKind::Parathread(collator, retries) => {
 					let entry = ParathreadEntry {
 						claim: ParathreadClaim(core_assignment.para_id, collator),
 						retries: retries + 1,
 					};
 
-					if entry.retries <= config.parathread_retries {
-						queue.enqueue_entry(entry, config.parathread_cores);
+					if entry.retries > config.parathread_retries ||
+						!<paras::Pallet<T>>::is_parathread(core_assignment.para_id)
+					{
+						Self::drop_parathread_claim(core_assignment.para_id);
+						continue
 					}
-				}
-			}
-		});
+
+					Assignment::parathread(entry)
+				},
+			};
+
+			T::AssignmentProvider::push_assignment_for_core(
+				&config,
+				core_assignment.core,
+				assignment,
+			);
+		}
 	}
 }
@@ -0,0 +1,396 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Assignment of availability cores to paras.
+//!
+//! The scheduler does not decide itself which para a core is assigned to, but asks the
+//! [`AssignmentProvider`] of the runtime whenever a core becomes free. Providers are composed
+//! with tuples: the cores of a session are numbered in the order of the providers, so with
+//! `(A, B)` the first `A::session_core_count` cores are assigned by `A`, followed by the cores
+//! assigned by `B`.
+
+use super::*;
+use crate::configuration::HostConfiguration;
+use sp_std::marker::PhantomData;
+
+/// A para to be scheduled on a core, handed out by an [`AssignmentProvider`].
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(PartialEq, Debug))]
+pub struct Assignment {
+	/// The para to be scheduled.
+	pub para_id: ParaId,
+	/// The kind of the assignment.
+	pub kind: AssignmentKind,
+}
+
+impl Assignment {
+	/// An assignment of a para which is scheduled like a parachain, i.e. without a required
+	/// collator.
+	pub fn parachain(para_id: ParaId) -> Self {
+		Self { para_id, kind: AssignmentKind::Parachain }
+	}
+
+	/// The assignment of a parathread claim.
+	pub(crate) fn parathread(entry: ParathreadEntry) -> Self {
+		Self {
+			para_id: entry.claim.0,
+			kind: AssignmentKind::Parathread(entry.claim.1, entry.retries),
+		}
+	}
+
+	/// Get the `ScheduledCore` from this.
+	pub(crate) fn to_scheduled_core(&self) -> ScheduledCore {
+		let collator = match self.kind {
+			AssignmentKind::Parachain => None,
+			AssignmentKind::Parathread(ref collator, _) => Some(collator.clone()),
+		};
+
+		ScheduledCore { para_id: self.para_id, collator }
+	}
+}
+
+/// Something that assigns paras to the availability cores.
+///
+/// Core indices passed to a provider are relative to the first core of the provider, i.e. they
+/// range from 0 to `session_core_count`.
+pub trait AssignmentProvider<BlockNumber> {
+	/// Called on a session change, before the cores of the new session are counted.
+	///
+	/// `returned` are the assignments of all cores which were occupied at the end of the session.
+	/// These are not concluded and providers may hand out the ones they are responsible for
+	/// again.
+	fn on_new_session(config: &HostConfiguration<BlockNumber>, returned: Vec<Assignment>);
+
+	/// The number of cores assigned by this provider. This must not change within a session.
+	fn session_core_count(config: &HostConfiguration<BlockNumber>) -> u32;
+
+	/// Take the next assignment of a free core, to be scheduled at block `now`.
+	fn pop_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment>;
+
+	/// Get the assignment `pop_assignment_for_core` would return, without taking it.
+	fn peek_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment>;
+
+	/// Return an assignment which was taken, but not processed: either it was never occupied
+	/// or the candidate occupying the core timed out.
+	fn push_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	);
+
+	/// Note that the candidate of an assignment occupying the core became available.
+	fn report_processed(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	);
+}
+
+impl<BlockNumber, A, B> AssignmentProvider<BlockNumber> for (A, B)
+where
+	A: AssignmentProvider<BlockNumber>,
+	B: AssignmentProvider<BlockNumber>,
+{
+	fn on_new_session(config: &HostConfiguration<BlockNumber>, returned: Vec<Assignment>) {
+		A::on_new_session(config, returned.clone());
+		B::on_new_session(config, returned);
+	}
+
+	fn session_core_count(config: &HostConfiguration<BlockNumber>) -> u32 {
+		A::session_core_count(config).saturating_add(B::session_core_count(config))
+	}
+
+	fn pop_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment> {
+		match split_core::<_, A>(config, core_idx) {
+			Ok(core_idx) => A::pop_assignment_for_core(config, core_idx, now),
+			Err(core_idx) => B::pop_assignment_for_core(config, core_idx, now),
+		}
+	}
+
+	fn peek_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment> {
+		match split_core::<_, A>(config, core_idx) {
+			Ok(core_idx) => A::peek_assignment_for_core(config, core_idx, now),
+			Err(core_idx) => B::peek_assignment_for_core(config, core_idx, now),
+		}
+	}
+
+	fn push_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		match split_core::<_, A>(config, core_idx) {
+			Ok(core_idx) => A::push_assignment_for_core(config, core_idx, assignment),
+			Err(core_idx) => B::push_assignment_for_core(config, core_idx, assignment),
+		}
+	}
+
+	fn report_processed(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		match split_core::<_, A>(config, core_idx) {
+			Ok(core_idx) => A::report_processed(config, core_idx, assignment),
+			Err(core_idx) => B::report_processed(config, core_idx, assignment),
+		}
+	}
+}
+
+impl<BlockNumber, A, B, C> AssignmentProvider<BlockNumber> for (A, B, C)
+where
+	A: AssignmentProvider<BlockNumber>,
+	B: AssignmentProvider<BlockNumber>,
+	C: AssignmentProvider<BlockNumber>,
+{
+	fn on_new_session(config: &HostConfiguration<BlockNumber>, returned: Vec<Assignment>) {
+		<(A, (B, C))>::on_new_session(config, returned)
+	}
+
+	fn session_core_count(config: &HostConfiguration<BlockNumber>) -> u32 {
+		<(A, (B, C))>::session_core_count(config)
+	}
+
+	fn pop_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment> {
+		<(A, (B, C))>::pop_assignment_for_core(config, core_idx, now)
+	}
+
+	fn peek_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		now: BlockNumber,
+	) -> Option<Assignment> {
+		<(A, (B, C))>::peek_assignment_for_core(config, core_idx, now)
+	}
+
+	fn push_assignment_for_core(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		<(A, (B, C))>::push_assignment_for_core(config, core_idx, assignment)
+	}
+
+	fn report_processed(
+		config: &HostConfiguration<BlockNumber>,
+		core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		<(A, (B, C))>::report_processed(config, core_idx, assignment)
+	}
+}
+
+/// Split a core index of the providers `(A, _)` into the core index of `A`, or the core index
+/// of the provider following `A`.
+fn split_core<BlockNumber, A: AssignmentProvider<BlockNumber>>(
+	config: &HostConfiguration<BlockNumber>,
+	core_idx: CoreIndex,
+) -> Result<CoreIndex, CoreIndex> {
+	let n_cores = A::session_core_count(config);
+	if core_idx.0 < n_cores {
+		Ok(core_idx)
+	} else {
+		Err(CoreIndex(core_idx.0 - n_cores))
+	}
+}
+
+/// Assigns one core to each parachain holding a lease, followed by the extra cores of the
+/// parachains which have any. See [`Pallet::parachain_cores`].
+pub struct ParachainAssignmentProvider<T>(PhantomData<T>);
+
+impl<T: Config> AssignmentProvider<T::BlockNumber> for ParachainAssignmentProvider<T> {
+	fn on_new_session(_config: &HostConfiguration<T::BlockNumber>, _returned: Vec<Assignment>) {}
+
+	fn session_core_count(_config: &HostConfiguration<T::BlockNumber>) -> u32 {
+		Pallet::<T>::parachain_cores().len() as u32
+	}
+
+	fn pop_assignment_for_core(
+		config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		now: T::BlockNumber,
+	) -> Option<Assignment> {
+		Self::peek_assignment_for_core(config, core_idx, now)
+	}
+
+	fn peek_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		_now: T::BlockNumber,
+	) -> Option<Assignment> {
+		Pallet::<T>::parachain_cores()
+			.get(core_idx.0 as usize)
+			.map(|para_id| Assignment::parachain(*para_id))
+	}
+
+	fn push_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		_assignment: Assignment,
+	) {
+		// The parachain is assigned to the core again anyway.
+	}
+
+	fn report_processed(
+		_config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		_assignment: Assignment,
+	) {
+	}
+}
+
+/// Assigns the `parathread_cores` parathread multiplexer cores to the claims in the
+/// [`ParathreadQueue`], which are bought with the [`on_demand`](crate::on_demand) pallet.
+pub struct ParathreadAssignmentProvider<T>(PhantomData<T>);
+
+impl<T: Config> AssignmentProvider<T::BlockNumber> for ParathreadAssignmentProvider<T> {
+	fn on_new_session(config: &HostConfiguration<T::BlockNumber>, returned: Vec<Assignment>) {
+		let mut thread_queue = ParathreadQueue::<T>::get();
+
+		// return the claims of all occupied cores to the queue.
+		for assignment in returned {
+			if let AssignmentKind::Parathread(collator, retries) = assignment.kind {
+				let queued = QueuedParathread {
+					claim: ParathreadEntry {
+						claim: ParathreadClaim(assignment.para_id, collator),
+						retries,
+					},
+					core_offset: 0, // this gets set later in the re-balancing.
+				};
+
+				thread_queue.queue.push(queued);
+			}
+		}
+
+		// prune out all parathread claims with too many retries.
+		// assign all non-pruned claims to new cores, if they've changed.
+		ParathreadClaimIndex::<T>::mutate(|claim_index| {
+			// wipe all parathread metadata if no parathread cores are configured.
+			if config.parathread_cores == 0 {
+				for queued in &thread_queue.queue {
+					T::OnParathreadClaimEnded::on_claim_dropped(queued.claim.claim.0);
+				}
+				thread_queue = ParathreadClaimQueue { queue: Vec::new(), next_core_offset: 0 };
+				claim_index.clear();
+				return
+			}
+
+			// prune out all entries beyond retry or that no longer correspond to live parathread.
+			thread_queue.queue.retain(|queued| {
+				let will_keep = queued.claim.retries <= config.parathread_retries &&
+					<paras::Pallet<T>>::is_parathread(queued.claim.claim.0);
+
+				if !will_keep {
+					let claim_para = queued.claim.claim.0;
+
+					// clean up the pruned entry from the index.
+					if let Ok(i) = claim_index.binary_search(&claim_para) {
+						claim_index.remove(i);
+					}
+					T::OnParathreadClaimEnded::on_claim_dropped(claim_para);
+				}
+
+				will_keep
+			});
+
+			// do re-balancing of claims.
+			{
+				for (i, queued) in thread_queue.queue.iter_mut().enumerate() {
+					queued.core_offset = (i as u32) % config.parathread_cores;
+				}
+
+				thread_queue.next_core_offset =
+					((thread_queue.queue.len()) as u32) % config.parathread_cores;
+			}
+		});
+		ParathreadQueue::<T>::set(thread_queue);
+	}
+
+	fn session_core_count(config: &HostConfiguration<T::BlockNumber>) -> u32 {
+		config.parathread_cores
+	}
+
+	fn pop_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		_now: T::BlockNumber,
+	) -> Option<Assignment> {
+		ParathreadQueue::<T>::mutate(|queue| queue.take_next_on_core(core_idx.0))
+			.map(Assignment::parathread)
+	}
+
+	fn peek_assignment_for_core(
+		_config: &HostConfiguration<T::BlockNumber>,
+		core_idx: CoreIndex,
+		_now: T::BlockNumber,
+	) -> Option<Assignment> {
+		ParathreadQueue::<T>::get()
+			.get_next_on_core(core_idx.0)
+			.cloned()
+			.map(Assignment::parathread)
+	}
+
+	fn push_assignment_for_core(
+		config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		if let AssignmentKind::Parathread(collator, retries) = assignment.kind {
+			let entry =
+				ParathreadEntry { claim: ParathreadClaim(assignment.para_id, collator), retries };
+			ParathreadQueue::<T>::mutate(|queue| {
+				queue.enqueue_entry(entry, config.parathread_cores);
+			})
+		}
+	}
+
+	fn report_processed(
+		_config: &HostConfiguration<T::BlockNumber>,
+		_core_idx: CoreIndex,
+		assignment: Assignment,
+	) {
+		if let AssignmentKind::Parathread(..) = assignment.kind {
+			// After a parathread candidate has successfully been included,
+			// open it up for further claims!
+			ParathreadClaimIndex::<T>::mutate(|index| {
+				if let Ok(i) = index.binary_search(&assignment.para_id) {
+					index.remove(i);
+				}
+			});
+			T::OnParathreadClaimEnded::on_claim_processed(assignment.para_id);
+		}
+	}
+}
@@ -24,7 +24,8 @@ use crate::{
 	configuration::HostConfiguration,
 	initializer::SessionChangeNotification,
 	mock::{
-		new_test_ext, Configuration, MockGenesisConfig, Paras, ParasShared, Scheduler, System, Test,
+		new_test_ext, Configuration, MockGenesisConfig, Paras, ParasShared, RuntimeOrigin,
+		Scheduler, System, Test,
 	},
 	paras::{ParaGenesisArgs, ParaKind},
 };
@@ -1449,3 +1450,67 @@ fn parathread_claims_are_pruned_after_deregistration() {
 		assert_eq!(Scheduler::scheduled().len(), 1);
 	});
 }
+
+#[test]
+fn extra_cores_are_scheduled_after_parachain_cores() {
+	let mut config = default_config();
+	config.parathread_cores = 1;
+
+	let genesis_config = MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: config.clone(),
+			..Default::default()
+		},
+		..Default::default()
+	};
+
+	let chain_a = ParaId::from(1_u32);
+	let chain_b = ParaId::from(2_u32);
+
+	new_test_ext(genesis_config).execute_with(|| {
+		schedule_blank_para(chain_a, ParaKind::Parachain);
+		schedule_blank_para(chain_b, ParaKind::Parachain);
+		assert_ok!(Paras::force_set_extra_cores(RuntimeOrigin::root(), chain_b, 2));
+
+		run_to_block(1, |number| match number {
+			1 => Some(SessionChangeNotification {
+				new_config: config.clone(),
+				validators: vec![
+					ValidatorId::from(Sr25519Keyring::Alice.public()),
+					ValidatorId::from(Sr25519Keyring::Bob.public()),
+					ValidatorId::from(Sr25519Keyring::Charlie.public()),
+					ValidatorId::from(Sr25519Keyring::Dave.public()),
+					ValidatorId::from(Sr25519Keyring::Eve.public()),
+				],
+				..Default::default()
+			}),
+			_ => None,
+		});
+
+		assert_eq!(Scheduler::parachain_cores(), vec![chain_a, chain_b, chain_b, chain_b]);
+		// 4 parachain cores and 1 parathread multiplexer.
+		assert_eq!(Scheduler::availability_cores().len(), 5);
+		assert_eq!(Scheduler::validator_groups().len(), 5);
+
+		run_to_block(2, |_| None);
+
+		let scheduled = Scheduler::scheduled();
+		assert_eq!(
+			scheduled.iter().map(|a| (a.core, a.para_id)).collect::<Vec<_>>(),
+			vec![
+				(CoreIndex(0), chain_a),
+				(CoreIndex(1), chain_b),
+				(CoreIndex(2), chain_b),
+				(CoreIndex(3), chain_b),
+			],
+		);
+
+		Scheduler::occupied(&[CoreIndex(1), CoreIndex(3)]);
+		assert_eq!(Scheduler::core_para(CoreIndex(1)), Some(chain_b));
+		assert_eq!(Scheduler::core_para(CoreIndex(3)), Some(chain_b));
+		assert_eq!(
+			Scheduler::next_up_on_available(CoreIndex(3)).unwrap(),
+			ScheduledCore { para_id: chain_b, collator: None }
+		);
+	});
+}
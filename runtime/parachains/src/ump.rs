@@ -14,14 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Upward message passing.
+//!
+//! The upward messages of the paras are enqueued in the message queue of the relay chain, with a
+//! queue for every para, and processed by the [`UmpSink`] in the background.
+
 use crate::{
 	configuration::{self, HostConfiguration},
 	initializer,
+	message_queue::{
+		EnqueueMessage, OnQueueChanged, ProcessMessage, ProcessMessageError, QueuePausedQuery,
+	},
+	paras,
 };
 use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
 use frame_system::pallet_prelude::*;
-use primitives::{Id as ParaId, UpwardMessage};
-use sp_std::{collections::btree_map::BTreeMap, fmt, marker::PhantomData, mem, prelude::*};
+use primitives::{vstaging::QueueFootprint, Id as ParaId, UpwardMessage};
+use sp_runtime::traits::SaturatedConversion;
+use sp_std::{fmt, marker::PhantomData, prelude::*};
 use xcm::latest::Outcome;
 
 pub use pallet::*;
@@ -31,9 +41,6 @@ pub use pallet::*;
 /// This is used for benchmarking sanely bounding relevant storate items. It is expected from the `configurations`
 /// pallet to check these values before setting.
 pub const MAX_UPWARD_MESSAGE_SIZE_BOUND: u32 = 50 * 1024;
-/// Maximum amount of overweight messages that can exist in the queue at any given time.
-pub const MAX_OVERWEIGHT_MESSAGES: u32 = 1000;
-
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 pub mod migration;
@@ -87,6 +94,53 @@ pub type MessageId = [u8; 32];
 /// Index used to identify overweight messages.
 pub type OverweightIndex = u64;
 
+/// The origin of a message in the message queue of the relay chain.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, TypeInfo, RuntimeDebug)]
+pub enum AggregateMessageOrigin {
+	/// An upward message from a para.
+	Ump(ParaId),
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl From<u32> for AggregateMessageOrigin {
+	fn from(para: u32) -> Self {
+		Self::Ump(ParaId::from(para))
+	}
+}
+
+/// Pauses the upward message queues of paras which are being offboarded, as their queues are
+/// swept at the next session change anyway.
+pub struct PauseOffboardingParas<T>(PhantomData<T>);
+
+impl<T: paras::Config> QueuePausedQuery<AggregateMessageOrigin> for PauseOffboardingParas<T> {
+	fn is_paused(origin: &AggregateMessageOrigin) -> bool {
+		match origin {
+			AggregateMessageOrigin::Ump(para) =>
+				<paras::Pallet<T>>::lifecycle(*para).map_or(false, |l| l.is_offboarding()),
+		}
+	}
+}
+
+/// The weight the message queue is serviced with at the beginning of every block, i.e.
+/// `ump_service_total_weight` of the active configuration.
+pub struct ServiceTotalWeight<T>(PhantomData<T>);
+
+impl<T: configuration::Config> Get<Option<Weight>> for ServiceTotalWeight<T> {
+	fn get() -> Option<Weight> {
+		Some(<configuration::Pallet<T>>::config().ump_service_total_weight)
+	}
+}
+
+/// The weight an upward message may need to be processed in the background, i.e.
+/// `ump_max_individual_weight` of the active configuration.
+pub struct MaxIndividualWeight<T>(PhantomData<T>);
+
+impl<T: configuration::Config> Get<Weight> for MaxIndividualWeight<T> {
+	fn get() -> Weight {
+		<configuration::Pallet<T>>::config().ump_max_individual_weight
+	}
+}
+
 /// A specific implementation of a `UmpSink` where messages are in the XCM format
 /// and will be forwarded to the XCM Executor.
 pub struct XcmSink<XcmExecutor, Config>(PhantomData<(XcmExecutor, Config)>);
@@ -227,15 +281,10 @@ pub mod pallet {
 		/// A place where all received upward messages are funneled.
 		type UmpSink: UmpSink;
 
-		/// The factor by which the weight limit it multiplied for the first UMP message to execute with.
-		///
-		/// An amount less than 100 keeps more available weight in the queue for messages after the first, and potentially
-		/// stalls the queue in doing so. More than 100 will provide additional weight for the first message only.
-		///
-		/// Generally you'll want this to be a bit more - 150 or 200 would be good values.
-		type FirstMessageFactorPercent: Get<u64>;
+		/// The message queue the upward messages are enqueued in.
+		type MessageQueue: EnqueueMessage<AggregateMessageOrigin>;
 
-		/// Origin which is allowed to execute overweight messages.
+		/// Origin which is allowed to execute the overweight messages in `Overweight`.
 		type ExecuteOverweightOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 
 		/// Weight information for extrinsics in this pallet.
@@ -254,19 +303,9 @@ pub mod pallet {
 		/// Upward message executed with the given outcome.
 		/// \[ id, outcome \]
 		ExecutedUpward(MessageId, Outcome),
-		/// The weight limit for handling upward messages was reached.
-		/// \[ id, remaining, required \]
-		WeightExhausted(MessageId, Weight, Weight),
 		/// Some upward messages have been received and will be processed.
 		/// \[ para, count, size \]
 		UpwardMessagesReceived(ParaId, u32, u32),
-		/// The weight budget was exceeded for an individual upward message.
-		///
-		/// This message can be later dispatched manually using `service_overweight` dispatchable
-		/// using the assigned `overweight_index`.
-		///
-		/// \[ para, id, overweight_index, required \]
-		OverweightEnqueued(ParaId, MessageId, OverweightIndex, Weight),
 		/// Upward message from the overweight queue was executed with the given actual weight
 		/// used.
 		///
@@ -282,52 +321,23 @@ pub mod pallet {
 		WeightOverLimit,
 	}
 
-	/// The messages waiting to be handled by the relay-chain originating from a certain parachain.
-	///
-	/// Note that some upward messages might have been already processed by the inclusion logic. E.g.
-	/// channel management messages.
-	///
-	/// The messages are processed in FIFO order.
-	#[pallet::storage]
-	pub type RelayDispatchQueues<T: Config> =
-		StorageMap<_, Twox64Concat, ParaId, Vec<UpwardMessage>, ValueQuery>;
-
-	/// Size of the dispatch queues. Caches sizes of the queues in `RelayDispatchQueue`.
+	/// Size of the dispatch queues, i.e. the footprint of the upward message queue of a para.
 	///
 	/// First item in the tuple is the count of messages and second
 	/// is the total length (in bytes) of the message payloads.
 	///
-	/// Note that this is an auxiliary mapping: it's possible to tell the byte size and the number of
-	/// messages only looking at `RelayDispatchQueues`. This mapping is separate to avoid the cost of
-	/// loading the whole message queue if only the total size and count are required.
-	///
-	/// Invariant:
-	/// - The set of keys should exactly match the set of keys of `RelayDispatchQueues`.
+	/// This mirrors the footprint kept by the message queue, which is updated whenever it changes.
 	// NOTE that this field is used by parachains via merkle storage proofs, therefore changing
 	// the format will require migration of parachains.
 	#[pallet::storage]
 	pub type RelayDispatchQueueSize<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, (u32, u32), ValueQuery>;
 
-	/// The ordered list of `ParaId`s that have a `RelayDispatchQueue` entry.
+	/// The messages that exceeded max individual message weight budget before the upward messages
+	/// were moved to the message queue.
 	///
-	/// Invariant:
-	/// - The set of items from this vector should be exactly the set of the keys in
-	///   `RelayDispatchQueues` and `RelayDispatchQueueSize`.
-	#[pallet::storage]
-	pub type NeedsDispatch<T: Config> = StorageValue<_, Vec<ParaId>, ValueQuery>;
-
-	/// This is the para that gets will get dispatched first during the next upward dispatchable queue
-	/// execution round.
-	///
-	/// Invariant:
-	/// - If `Some(para)`, then `para` must be present in `NeedsDispatch`.
-	#[pallet::storage]
-	pub type NextDispatchRoundStartWith<T: Config> = StorageValue<_, ParaId>;
-
-	/// The messages that exceeded max individual message weight budget.
-	///
-	/// These messages stay there until manually dispatched.
+	/// These messages stay there until manually dispatched. Messages which became overweight since
+	/// are kept by the message queue.
 	#[pallet::storage]
 	pub type Overweight<T: Config> =
 		CountedStorageMap<_, Twox64Concat, OverweightIndex, (ParaId, Vec<u8>), OptionQuery>;
@@ -339,7 +349,7 @@ pub mod pallet {
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Service a single overweight upward message.
+		/// Service a single overweight upward message of `Overweight`.
 		///
 		/// - `origin`: Must pass `ExecuteOverweightOrigin`.
 		/// - `index`: The index of the overweight message to service.
@@ -400,33 +410,26 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Remove all relevant storage items for an outgoing parachain.
+	///
+	/// The weight includes removing each page of the upward message queue of the para.
 	pub(crate) fn clean_ump_after_outgoing(outgoing_para: &ParaId) -> Weight {
+		let pages = T::MessageQueue::sweep_queue(AggregateMessageOrigin::Ump(*outgoing_para));
 		<Self as Store>::RelayDispatchQueueSize::remove(outgoing_para);
-		<Self as Store>::RelayDispatchQueues::remove(outgoing_para);
-
-		// Remove the outgoing para from the `NeedsDispatch` list and from
-		// `NextDispatchRoundStartWith`.
-		//
-		// That's needed for maintaining invariant that `NextDispatchRoundStartWith` points to an
-		// existing item in `NeedsDispatch`.
-		<Self as Store>::NeedsDispatch::mutate(|v| {
-			if let Ok(i) = v.binary_search(outgoing_para) {
-				v.remove(i);
-			}
-		});
-		<Self as Store>::NextDispatchRoundStartWith::mutate(|v| {
-			*v = v.filter(|p| p == outgoing_para)
-		});
 
 		<T as Config>::WeightInfo::clean_ump_after_outgoing()
+			.saturating_add(T::DbWeight::get().writes(pages.into()))
 	}
 
 	/// Check that all the upward messages sent by a candidate pass the acceptance criteria. Returns
 	/// false, if any of the messages doesn't pass.
+	///
+	/// `chained` are the number and total size of the upward messages sent by the candidates this
+	/// one is chained to, which are not enqueued yet.
 	pub(crate) fn check_upward_messages(
 		config: &HostConfiguration<T::BlockNumber>,
 		para: ParaId,
 		upward_messages: &[UpwardMessage],
+		chained: (u32, u32),
 	) -> Result<(), AcceptanceCheckErr> {
 		if upward_messages.len() as u32 > config.max_upward_message_num_per_candidate {
 			return Err(AcceptanceCheckErr::MoreMessagesThanPermitted {
@@ -435,8 +438,10 @@ impl<T: Config> Pallet<T> {
 			})
 		}
 
-		let (mut para_queue_count, mut para_queue_size) =
-			<Self as Store>::RelayDispatchQueueSize::get(&para);
+		let footprint = T::MessageQueue::footprint(AggregateMessageOrigin::Ump(para));
+		let mut para_queue_count =
+			footprint.count.saturated_into::<u32>().saturating_add(chained.0);
+		let mut para_queue_size = footprint.size.saturated_into::<u32>().saturating_add(chained.1);
 
 		for (idx, msg) in upward_messages.into_iter().enumerate() {
 			let msg_size = msg.len() as u32;
@@ -447,8 +452,8 @@ impl<T: Config> Pallet<T> {
 					max_size: config.max_upward_message_size,
 				})
 			}
-			para_queue_count += 1;
-			para_queue_size += msg_size;
+			para_queue_count = para_queue_count.saturating_add(1);
+			para_queue_size = para_queue_size.saturating_add(msg_size);
 		}
 
 		// make sure that the queue is not overfilled.
@@ -481,294 +486,46 @@ impl<T: Config> Pallet<T> {
 				.iter()
 				.fold((0, 0), |(cnt, size), d| (cnt + 1, size + d.len() as u32));
 
-			<Self as Store>::RelayDispatchQueues::mutate(&para, |v| {
-				v.extend(upward_messages.into_iter())
-			});
-
-			<Self as Store>::RelayDispatchQueueSize::mutate(
-				&para,
-				|(ref mut cnt, ref mut size)| {
-					*cnt += extra_count;
-					*size += extra_size;
-				},
-			);
-
-			<Self as Store>::NeedsDispatch::mutate(|v| {
-				if let Err(i) = v.binary_search(&para) {
-					v.insert(i, para);
-				}
-			});
+			T::MessageQueue::enqueue_messages(upward_messages, AggregateMessageOrigin::Ump(para));
 
 			// NOTE: The actual computation is not accounted for. It should be benchmarked.
-			weight += T::DbWeight::get().reads_writes(3, 3);
+			weight += T::DbWeight::get().reads_writes(3, 4);
 
 			Self::deposit_event(Event::UpwardMessagesReceived(para, extra_count, extra_size));
 		}
 
 		weight
 	}
-
-	/// Devote some time into dispatching pending upward messages.
-	pub(crate) fn process_pending_upward_messages() -> Weight {
-		const MAX_MESSAGES_PER_BLOCK: u8 = 10;
-		let mut messages_processed = 0;
-		let mut weight_used = Weight::zero();
-
-		let config = <configuration::Pallet<T>>::config();
-		let mut cursor = NeedsDispatchCursor::new::<T>();
-		let mut queue_cache = QueueCache::new();
-
-		while let Some(dispatchee) = cursor.peek() {
-			if weight_used.any_gte(config.ump_service_total_weight) ||
-				messages_processed >= MAX_MESSAGES_PER_BLOCK
-			{
-				// Temporarily allow for processing of a max of 10 messages per block, until we
-				// properly account for proof size weights.
-				//
-				// Then check whether we've reached or overshoot the
-				// preferred weight for the dispatching stage.
-				//
-				// if so - bail.
-				break
-			}
-			let max_weight = if weight_used == Weight::zero() {
-				// we increase the amount of weight that we're allowed to use on the first message to try to prevent
-				// the possibility of blockage of the queue.
-				config
-					.ump_service_total_weight
-					.saturating_mul(T::FirstMessageFactorPercent::get()) /
-					100
-			} else {
-				config.ump_service_total_weight - weight_used
-			};
-
-			// attempt to process the next message from the queue of the dispatchee; if not beyond
-			// our remaining weight limit, then consume it.
-			let maybe_next = queue_cache.peek_front::<T>(dispatchee);
-			if let Some(upward_message) = maybe_next {
-				messages_processed += 1;
-				match T::UmpSink::process_upward_message(dispatchee, upward_message, max_weight) {
-					Ok(used) => {
-						weight_used += used;
-						let _ = queue_cache.consume_front::<T>(dispatchee);
-					},
-					Err((id, required)) => {
-						let is_under_limit = Overweight::<T>::count() < MAX_OVERWEIGHT_MESSAGES;
-						weight_used.saturating_accrue(T::DbWeight::get().reads(1));
-						if required.any_gt(config.ump_max_individual_weight) && is_under_limit {
-							// overweight - add to overweight queue and continue with message
-							// execution consuming the message.
-							let upward_message = queue_cache.consume_front::<T>(dispatchee).expect(
-								"`consume_front` should return the same msg as `peek_front`;\
-								if we get into this branch then `peek_front` returned `Some`;\
-								thus `upward_message` cannot be `None`; qed",
-							);
-							let index = Self::stash_overweight(dispatchee, upward_message);
-							Self::deposit_event(Event::OverweightEnqueued(
-								dispatchee, id, index, required,
-							));
-						} else {
-							// we process messages in order and don't drop them if we run out of weight,
-							// so need to break here without calling `consume_front`.
-							Self::deposit_event(Event::WeightExhausted(id, max_weight, required));
-							break
-						}
-					},
-				}
-			}
-
-			if queue_cache.is_empty::<T>(dispatchee) {
-				// the queue is empty now - this para doesn't need attention anymore.
-				cursor.remove();
-			} else {
-				cursor.advance();
-			}
-		}
-
-		cursor.flush::<T>();
-		queue_cache.flush::<T>();
-
-		weight_used
-	}
-
-	/// Puts a given upward message into the list of overweight messages allowing it to be executed
-	/// later.
-	fn stash_overweight(sender: ParaId, upward_message: Vec<u8>) -> OverweightIndex {
-		let index = <Self as Store>::OverweightCount::mutate(|count| {
-			let index = *count;
-			*count += 1;
-			index
-		});
-
-		<Self as Store>::Overweight::insert(index, (sender, upward_message));
-		index
-	}
 }
 
-/// To avoid constant fetching, deserializing and serialization the queues are cached.
-///
-/// After an item dequeued from a queue for the first time, the queue is stored in this struct
-/// rather than being serialized and persisted.
-///
-/// This implementation works best when:
-///
-/// 1. when the queues are shallow
-/// 2. the dispatcher makes more than one cycle
-///
-/// if the queues are deep and there are many we would load and keep the queues for a long time,
-/// thus increasing the peak memory consumption of the wasm runtime. Under such conditions persisting
-/// queues might play better since it's unlikely that they are going to be requested once more.
-///
-/// On the other hand, the situation when deep queues exist and it takes more than one dispatcher
-/// cycle to traverse the queues is already sub-optimal and better be avoided.
-///
-/// This struct is not supposed to be dropped but rather to be consumed by [`flush`].
-struct QueueCache(BTreeMap<ParaId, QueueCacheEntry>);
-
-struct QueueCacheEntry {
-	queue: Vec<UpwardMessage>,
-	total_size: u32,
-	consumed_count: usize,
-	consumed_size: usize,
-}
-
-impl QueueCache {
-	fn new() -> Self {
-		Self(BTreeMap::new())
-	}
-
-	fn ensure_cached<T: Config>(&mut self, para: ParaId) -> &mut QueueCacheEntry {
-		self.0.entry(para).or_insert_with(|| {
-			let queue = RelayDispatchQueues::<T>::get(&para);
-			let (_, total_size) = RelayDispatchQueueSize::<T>::get(&para);
-			QueueCacheEntry { queue, total_size, consumed_count: 0, consumed_size: 0 }
-		})
-	}
-
-	/// Returns the message at the front of `para`'s queue, or `None` if the queue is empty.
-	///
-	/// Does not mutate the queue.
-	fn peek_front<T: Config>(&mut self, para: ParaId) -> Option<&UpwardMessage> {
-		let entry = self.ensure_cached::<T>(para);
-		entry.queue.get(entry.consumed_count)
-	}
-
-	/// Attempts to remove one message from the front of `para`'s queue. If the queue is empty, then
-	/// does nothing.
-	fn consume_front<T: Config>(&mut self, para: ParaId) -> Option<UpwardMessage> {
-		let cache_entry = self.ensure_cached::<T>(para);
-
-		match cache_entry.queue.get_mut(cache_entry.consumed_count) {
-			Some(msg) => {
-				cache_entry.consumed_count += 1;
-				cache_entry.consumed_size += msg.len();
-
-				Some(mem::take(msg))
-			},
-			None => None,
-		}
-	}
-
-	/// Returns if the queue for the given para is empty.
-	///
-	/// That is, if this returns `true` then the next call to [`peek_front`] will return `None`.
-	///
-	/// Does not mutate the queue.
-	fn is_empty<T: Config>(&mut self, para: ParaId) -> bool {
-		let cache_entry = self.ensure_cached::<T>(para);
-		cache_entry.consumed_count >= cache_entry.queue.len()
-	}
-
-	/// Flushes the updated queues into the storage.
-	fn flush<T: Config>(self) {
-		// NOTE we use an explicit method here instead of Drop impl because it has unwanted semantics
-		// within runtime. It is dangerous to use because of double-panics and flushing on a panic
-		// is not necessary as well.
-		for (para, entry) in self.0 {
-			if entry.consumed_count >= entry.queue.len() {
-				// remove the entries altogether.
-				RelayDispatchQueues::<T>::remove(&para);
-				RelayDispatchQueueSize::<T>::remove(&para);
-			} else if entry.consumed_count > 0 {
-				RelayDispatchQueues::<T>::insert(&para, &entry.queue[entry.consumed_count..]);
-				let count = (entry.queue.len() - entry.consumed_count) as u32;
-				let size = entry.total_size.saturating_sub(entry.consumed_size as u32);
-				RelayDispatchQueueSize::<T>::insert(&para, (count, size));
-			}
+impl<T: Config> ProcessMessage for Pallet<T> {
+	type Origin = AggregateMessageOrigin;
+
+	fn process_message(
+		message: &[u8],
+		origin: AggregateMessageOrigin,
+		weight_limit: Weight,
+	) -> Result<Weight, ProcessMessageError> {
+		match origin {
+			AggregateMessageOrigin::Ump(para) =>
+				T::UmpSink::process_upward_message(para, message, weight_limit)
+					.map_err(|(_, required)| ProcessMessageError::Overweight(required)),
 		}
 	}
 }
 
-/// A cursor that iterates over all entries in `NeedsDispatch`.
-///
-/// This cursor will start with the para indicated by `NextDispatchRoundStartWith` storage entry.
-/// This cursor is cyclic meaning that after reaching the end it will jump to the beginning. Unlike
-/// an iterator, this cursor allows removing items during the iteration.
-///
-/// Each iteration cycle *must be* concluded with a call to either `advance` or `remove`.
-///
-/// This struct is not supposed to be dropped but rather to be consumed by [`flush`].
-#[derive(Debug)]
-struct NeedsDispatchCursor {
-	needs_dispatch: Vec<ParaId>,
-	index: usize,
-}
-
-impl NeedsDispatchCursor {
-	fn new<T: Config>() -> Self {
-		let needs_dispatch: Vec<ParaId> = <Pallet<T> as Store>::NeedsDispatch::get();
-		let start_with = <Pallet<T> as Store>::NextDispatchRoundStartWith::get();
-
-		let initial_index = match start_with {
-			Some(para) => match needs_dispatch.binary_search(&para) {
-				Ok(found_index) => found_index,
-				Err(_supposed_index) => {
-					// well that's weird because we maintain an invariant that
-					// `NextDispatchRoundStartWith` must point into one of the items in
-					// `NeedsDispatch`.
-					//
-					// let's select 0 as the starting index as a safe bet.
-					debug_assert!(false);
-					0
+impl<T: Config> OnQueueChanged<AggregateMessageOrigin> for Pallet<T> {
+	fn on_queue_changed(origin: AggregateMessageOrigin, footprint: QueueFootprint) {
+		match origin {
+			AggregateMessageOrigin::Ump(para) =>
+				if footprint.count == 0 {
+					<Self as Store>::RelayDispatchQueueSize::remove(&para);
+				} else {
+					<Self as Store>::RelayDispatchQueueSize::insert(
+						&para,
+						(footprint.count.saturated_into(), footprint.size.saturated_into()),
+					);
 				},
-			},
-			None => 0,
-		};
-
-		Self { needs_dispatch, index: initial_index }
-	}
-
-	/// Returns the item the cursor points to.
-	fn peek(&self) -> Option<ParaId> {
-		self.needs_dispatch.get(self.index).cloned()
-	}
-
-	/// Moves the cursor to the next item.
-	fn advance(&mut self) {
-		if self.needs_dispatch.is_empty() {
-			return
 		}
-		self.index = (self.index + 1) % self.needs_dispatch.len();
-	}
-
-	/// Removes the item under the cursor.
-	fn remove(&mut self) {
-		if self.needs_dispatch.is_empty() {
-			return
-		}
-		let _ = self.needs_dispatch.remove(self.index);
-
-		// we might've removed the last element and that doesn't necessarily mean that `needs_dispatch`
-		// became empty. Reposition the cursor in this case to the beginning.
-		if self.needs_dispatch.get(self.index).is_none() {
-			self.index = 0;
-		}
-	}
-
-	/// Flushes the dispatcher state into the persistent storage.
-	fn flush<T: Config>(self) {
-		let next_one = self.peek();
-		<Pallet<T> as Store>::NextDispatchRoundStartWith::set(next_one);
-		<Pallet<T> as Store>::NeedsDispatch::put(self.needs_dispatch);
 	}
 }
@@ -33,7 +33,7 @@ fn queue_upward_msg<T: Config>(
 ) {
 	let len = msg.len() as u32;
 	let msgs = vec![msg];
-	Ump::<T>::check_upward_messages(host_conf, para, &msgs).unwrap();
+	Ump::<T>::check_upward_messages(host_conf, para, &msgs, (0, 0)).unwrap();
 	let _ = Ump::<T>::receive_upward_messages(para, msgs);
 	assert_last_event_type::<T>(Event::UpwardMessagesReceived(para, 1, len).into());
 }
@@ -105,7 +105,6 @@ frame_benchmarking::benchmarks! {
 	}
 
 	service_overweight {
-		let host_conf = configuration::ActiveConfig::<T>::get();
 		let para = ParaId::from(1978);
 		// The message's weight does not really matter here, as we add service_overweight's
 		// max_weight parameter to the extrinsic's weight in the weight calculation.
@@ -114,16 +113,10 @@ frame_benchmarking::benchmarks! {
 		// in the process_upward_message function.
 		let msg = create_message_overweight::<T>();
 
-		// This just makes sure that 0 is not a valid index and we can use it later on.
-		let _ = Ump::<T>::service_overweight(RawOrigin::Root.into(), 0, Weight::from_parts(1000, 1000));
 		// Start with the block number 1. This is needed because should an event be
 		// emitted during the genesis block they will be implicitly wiped.
 		frame_system::Pallet::<T>::set_block_number(1u32.into());
-		queue_upward_msg::<T>(&host_conf, para, msg.clone());
-		Ump::<T>::process_pending_upward_messages();
-		assert_last_event_type::<T>(
-			Event::OverweightEnqueued(para, upward_message_id(&msg), 0, Weight::zero()).into()
-			);
+		Overweight::<T>::insert(0, (para, msg));
 	}: _(RawOrigin::Root, 0, Weight::MAX)
 	verify {
 		assert_last_event_type::<T>(Event::OverweightServiced(0, Weight::zero()).into());
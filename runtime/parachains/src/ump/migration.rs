@@ -21,7 +21,7 @@ use frame_support::{
 	weights::Weight,
 };
 
-pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 pub mod v1 {
 	use super::*;
@@ -47,3 +47,84 @@ pub mod v1 {
 		}
 	}
 }
+
+pub mod v2 {
+	use super::*;
+	use crate::{message_queue::EnqueueMessage, ump::AggregateMessageOrigin};
+	use frame_support::storage_alias;
+	use primitives::{Id as ParaId, UpwardMessage};
+	use sp_std::prelude::*;
+
+	#[storage_alias]
+	type RelayDispatchQueues<T: Config> =
+		StorageMap<Pallet<T>, Twox64Concat, ParaId, Vec<UpwardMessage>, ValueQuery>;
+
+	#[storage_alias]
+	type NeedsDispatch<T: Config> = StorageValue<Pallet<T>, Vec<ParaId>, ValueQuery>;
+
+	#[storage_alias]
+	type NextDispatchRoundStartWith<T: Config> = StorageValue<Pallet<T>, ParaId>;
+
+	/// Moves the upward messages waiting to be dispatched to the message queue.
+	pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 1 {
+				let mut weight = T::DbWeight::get().reads(1);
+
+				// There is a queue for every para at most, each with at most
+				// `max_upward_queue_count` messages.
+				let mut queues = 0u64;
+				for (para, queue) in RelayDispatchQueues::<T>::drain() {
+					T::MessageQueue::enqueue_messages(queue, AggregateMessageOrigin::Ump(para));
+					queues += 1;
+				}
+				log::info!("Moved {} upward message queues to the message queue", queues);
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(queues * 4, queues * 5));
+
+				NeedsDispatch::<T>::kill();
+				NextDispatchRoundStartWith::<T>::kill();
+
+				StorageVersion::new(2).put::<Pallet<T>>();
+
+				weight.saturating_add(T::DbWeight::get().writes(3))
+			} else {
+				log::warn!("skipping v2, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == 1,
+				"Storage version should be `1` before the migration",
+			);
+			let sizes = crate::ump::RelayDispatchQueueSize::<T>::iter().collect::<Vec<_>>();
+			Ok(sizes.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be `2` after the migration"
+			);
+			ensure!(
+				RelayDispatchQueues::<T>::iter().next().is_none(),
+				"RelayDispatchQueues should be empty after the migration"
+			);
+
+			let sizes = Vec::<(ParaId, (u32, u32))>::decode(&mut &state[..])
+				.map_err(|_| "the state of `pre_upgrade` decodes")?;
+			for (para, (count, size)) in sizes {
+				let footprint = T::MessageQueue::footprint(AggregateMessageOrigin::Ump(para));
+				ensure!(
+					footprint.count == count as u64 && footprint.size == size as u64,
+					"The upward messages of every para should be in the message queue"
+				);
+			}
+			Ok(())
+		}
+	}
+}
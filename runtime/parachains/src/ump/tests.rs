@@ -15,12 +15,14 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
-use crate::mock::{
-	assert_last_event, new_test_ext, take_processed, Configuration, MockGenesisConfig,
-	RuntimeOrigin, System, Test, Ump,
+use crate::{
+	message_queue::{self, EnqueueMessage},
+	mock::{
+		assert_last_event, new_test_ext, take_processed, Configuration, MessageQueue,
+		MockGenesisConfig, RuntimeOrigin, System, Test, Ump,
+	},
 };
 use frame_support::{assert_noop, assert_ok, weights::Weight};
-use std::collections::HashSet;
 
 pub(super) struct GenesisConfigBuilder {
 	max_upward_message_size: u32,
@@ -73,47 +75,22 @@ fn default_genesis_config() -> MockGenesisConfig {
 
 fn queue_upward_msg(para: ParaId, msg: UpwardMessage) {
 	let msgs = vec![msg];
-	assert!(Ump::check_upward_messages(&Configuration::config(), para, &msgs).is_ok());
+	assert!(Ump::check_upward_messages(&Configuration::config(), para, &msgs, (0, 0)).is_ok());
 	let _ = Ump::receive_upward_messages(para, msgs);
 }
 
-fn assert_storage_consistency_exhaustive() {
-	// check that empty queues don't clutter the storage.
-	for (_para, queue) in <Ump as Store>::RelayDispatchQueues::iter() {
-		assert!(!queue.is_empty());
-	}
-
-	// actually count the counts and sizes in queues and compare them to the bookkept version.
-	for (para, queue) in <Ump as Store>::RelayDispatchQueues::iter() {
-		let (expected_count, expected_size) = <Ump as Store>::RelayDispatchQueueSize::get(para);
-		let (actual_count, actual_size) = queue
-			.into_iter()
-			.fold((0, 0), |(acc_count, acc_size), x| (acc_count + 1, acc_size + x.len() as u32));
-
-		assert_eq!(expected_count, actual_count);
-		assert_eq!(expected_size, actual_size);
-	}
+fn process_upward_messages() {
+	MessageQueue::service_queues(Configuration::config().ump_service_total_weight);
+}
 
-	// since we wipe the empty queues the sets of paras in queue contents, queue sizes and
-	// need dispatch set should all be equal.
-	let queue_contents_set = <Ump as Store>::RelayDispatchQueues::iter()
-		.map(|(k, _)| k)
-		.collect::<HashSet<ParaId>>();
-	let queue_sizes_set = <Ump as Store>::RelayDispatchQueueSize::iter()
-		.map(|(k, _)| k)
-		.collect::<HashSet<ParaId>>();
-	let needs_dispatch_set =
-		<Ump as Store>::NeedsDispatch::get().into_iter().collect::<HashSet<ParaId>>();
-	assert_eq!(queue_contents_set, queue_sizes_set);
-	assert_eq!(queue_contents_set, needs_dispatch_set);
-
-	// `NextDispatchRoundStartWith` should point into a para that is tracked.
-	if let Some(para) = <Ump as Store>::NextDispatchRoundStartWith::get() {
-		assert!(queue_contents_set.contains(&para));
+fn assert_storage_consistency_exhaustive() {
+	// the footprints of the queues are mirrored for the paras, and empty queues don't clutter the
+	// storage.
+	for (para, (count, size)) in <Ump as Store>::RelayDispatchQueueSize::iter() {
+		assert!(count > 0);
+		let footprint = MessageQueue::footprint(AggregateMessageOrigin::Ump(para));
+		assert_eq!((footprint.count, footprint.size), (count as u64, size as u64));
 	}
-
-	// `NeedsDispatch` is always sorted.
-	assert!(<Ump as Store>::NeedsDispatch::get().windows(2).all(|xs| xs[0] <= xs[1]));
 }
 
 #[test]
@@ -122,7 +99,7 @@ fn dispatch_empty() {
 		assert_storage_consistency_exhaustive();
 
 		// make sure that the case with empty queues is handled properly
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 
 		assert_storage_consistency_exhaustive();
 	});
@@ -135,7 +112,7 @@ fn dispatch_single_message() {
 
 	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
 		queue_upward_msg(a, msg.clone());
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![(a, msg)]);
 
 		assert_storage_consistency_exhaustive();
@@ -169,26 +146,27 @@ fn dispatch_resume_after_exceeding_dispatch_stage_weight() {
 
 		assert_storage_consistency_exhaustive();
 
-		// we expect only two first messages to fit in the first iteration.
-		Ump::process_pending_upward_messages();
-		assert_eq!(take_processed(), vec![(a, a_msg_1), (c, c_msg_1)]);
+		// the queues are serviced in the order they received messages, so only the message of
+		// `q` fits in the first iteration.
+		process_upward_messages();
+		assert_eq!(take_processed(), vec![(q, q_msg)]);
 		assert_storage_consistency_exhaustive();
 
 		queue_upward_msg(c, c_msg_2.clone());
 		assert_storage_consistency_exhaustive();
 
-		// second iteration should process the second message.
-		Ump::process_pending_upward_messages();
-		assert_eq!(take_processed(), vec![(q, q_msg)]);
+		// second iteration should resume with `c`.
+		process_upward_messages();
+		assert_eq!(take_processed(), vec![(c, c_msg_1), (a, a_msg_1)]);
 		assert_storage_consistency_exhaustive();
 
 		// 3rd iteration.
-		Ump::process_pending_upward_messages();
-		assert_eq!(take_processed(), vec![(a, a_msg_2), (c, c_msg_2)]);
+		process_upward_messages();
+		assert_eq!(take_processed(), vec![(c, c_msg_2), (a, a_msg_2)]);
 		assert_storage_consistency_exhaustive();
 
 		// finally, make sure that the queue is empty.
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![]);
 		assert_storage_consistency_exhaustive();
 	});
@@ -216,17 +194,17 @@ fn dispatch_keeps_message_after_weight_exhausted() {
 		assert_storage_consistency_exhaustive();
 
 		// we expect only one message to fit in the first iteration.
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![(a, a_msg_1)]);
 		assert_storage_consistency_exhaustive();
 
 		// second iteration should process the remaining message.
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![(a, a_msg_2)]);
 		assert_storage_consistency_exhaustive();
 
 		// finally, make sure that the queue is empty.
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![]);
 		assert_storage_consistency_exhaustive();
 	});
@@ -249,16 +227,16 @@ fn dispatch_correctly_handle_remove_of_latest() {
 		.build(),
 	)
 	.execute_with(|| {
-		// We want to test here an edge case, where we remove the queue with the highest
-		// para id (i.e. last in the `needs_dispatch` order).
+		// We want to test here an edge case, where we remove the queue which was serviced last
+		// in a round.
 		//
-		// If the last entry was removed we should proceed execution, assuming we still have
+		// If the last queue was removed we should proceed execution, assuming we still have
 		// weight available.
 
 		queue_upward_msg(a, a_msg_1.clone());
 		queue_upward_msg(a, a_msg_2.clone());
 		queue_upward_msg(b, b_msg_1.clone());
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![(a, a_msg_1), (b, b_msg_1), (a, a_msg_2)]);
 	});
 }
@@ -302,9 +280,39 @@ fn service_overweight_unknown() {
 	});
 }
 
+#[test]
+fn service_overweight_works() {
+	let para_a = ParaId::from(2021);
+	let a_msg = (500u32, "a_msg").encode();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		// HACK: Start with the block number 1. This is needed because should an event be
+		// emitted during the genesis block they will be implicitly wiped.
+		System::set_block_number(1);
+
+		// Overweight messages are only left from before the message queue.
+		<Ump as Store>::Overweight::insert(0, (para_a, a_msg.clone()));
+
+		assert_noop!(
+			Ump::service_overweight(RuntimeOrigin::root(), 0, Weight::from_parts(499, 499)),
+			Error::<Test>::WeightOverLimit
+		);
+
+		assert_ok!(Ump::service_overweight(RuntimeOrigin::root(), 0, Weight::from_parts(500, 500)));
+		assert_last_event(Event::OverweightServiced(0, Weight::from_parts(500, 500)).into());
+		assert_eq!(take_processed(), vec![(para_a, a_msg)]);
+
+		assert_noop!(
+			Ump::service_overweight(RuntimeOrigin::root(), 0, Weight::from_parts(1000, 1000)),
+			Error::<Test>::UnknownMessageIndex
+		);
+	});
+}
+
 #[test]
 fn overweight_queue_works() {
 	let para_a = ParaId::from(2021);
+	let origin = AggregateMessageOrigin::Ump(para_a);
 
 	let a_msg_1 = (301u32, "a_msg_1").encode();
 	let a_msg_2 = (500u32, "a_msg_2").encode();
@@ -326,39 +334,82 @@ fn overweight_queue_works() {
 		// This one is overweight. However, the weight is plenty and we can afford to execute
 		// this message, thus expect it.
 		queue_upward_msg(para_a, a_msg_1.clone());
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
 		assert_eq!(take_processed(), vec![(para_a, a_msg_1)]);
 
 		// This is overweight and this message cannot fit into the total weight budget.
 		queue_upward_msg(para_a, a_msg_2.clone());
 		queue_upward_msg(para_a, a_msg_3.clone());
-		Ump::process_pending_upward_messages();
+		process_upward_messages();
+		assert_eq!(take_processed(), vec![(para_a, a_msg_2)]);
 		assert_last_event(
-			Event::OverweightEnqueued(
-				para_a,
-				upward_message_id(&a_msg_3[..]),
-				0,
-				Weight::from_parts(500, 500),
-			)
+			message_queue::Event::OverweightEnqueued {
+				id: upward_message_id(&a_msg_3[..]),
+				origin,
+				page_index: 1,
+				message_index: 0,
+			}
 			.into(),
 		);
 
+		// The overweight message is not part of the footprint of the queue anymore.
+		assert_eq!(MessageQueue::footprint(origin).count, 0);
+		assert!(!<Ump as Store>::RelayDispatchQueueSize::contains_key(para_a));
+
 		// Now verify that if we wanted to service this overweight message with less than enough
 		// weight it will fail.
 		assert_noop!(
-			Ump::service_overweight(RuntimeOrigin::root(), 0, Weight::from_parts(499, 499)),
-			Error::<Test>::WeightOverLimit
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::root(),
+				origin,
+				1,
+				0,
+				Weight::from_parts(499, 499)
+			),
+			message_queue::Error::<Test>::InsufficientWeight
 		);
 
 		// ... and if we try to service it with just enough weight it will succeed as well.
-		assert_ok!(Ump::service_overweight(RuntimeOrigin::root(), 0, Weight::from_parts(500, 500)));
-		assert_last_event(Event::OverweightServiced(0, Weight::from_parts(500, 500)).into());
-
-		// ... and if we try to service a message with index that doesn't exist it will error
-		// out.
+		assert_ok!(MessageQueue::execute_overweight(
+			RuntimeOrigin::root(),
+			origin,
+			1,
+			0,
+			Weight::from_parts(500, 500)
+		));
+		assert_eq!(take_processed(), vec![(para_a, a_msg_3)]);
+
+		// ... and if we try to service it again it will error out.
 		assert_noop!(
-			Ump::service_overweight(RuntimeOrigin::root(), 1, Weight::from_parts(1000, 1000)),
-			Error::<Test>::UnknownMessageIndex
+			MessageQueue::execute_overweight(
+				RuntimeOrigin::root(),
+				origin,
+				1,
+				0,
+				Weight::from_parts(1000, 1000)
+			),
+			message_queue::Error::<Test>::NoMessage
 		);
 	});
 }
+
+#[test]
+fn outgoing_para_queue_is_swept() {
+	let a = ParaId::from(228);
+	let b = ParaId::from(229);
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		queue_upward_msg(a, 1000u32.encode());
+		queue_upward_msg(b, 1000u32.encode());
+
+		Ump::clean_ump_after_outgoing(&a);
+		assert_eq!(MessageQueue::footprint(AggregateMessageOrigin::Ump(a)).count, 0);
+		assert!(!<Ump as Store>::RelayDispatchQueueSize::contains_key(a));
+		assert_storage_consistency_exhaustive();
+
+		// The queues of other paras are still processed.
+		process_upward_messages();
+		assert_eq!(take_processed(), vec![(b, 1000u32.encode())]);
+		assert_storage_consistency_exhaustive();
+	});
+}
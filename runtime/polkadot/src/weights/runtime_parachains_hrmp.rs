@@ -185,4 +185,40 @@ impl<T: frame_system::Config> runtime_parachains::hrmp::WeightInfo for WeightInf
 			.saturating_add(T::DbWeight::get().reads(14))
 			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	// NOTE: placeholder, not produced by the benchmark CLI. It counts the four downward
+	// notifications and the deposits reserved from both paras, until the benchmark is run.
+	// Storage: Hrmp HrmpAutoAcceptPolicy (r:1 w:0)
+	// Storage: Hrmp HrmpChannelPreApprovals (r:2 w:0)
+	// Storage: Configuration ActiveConfig (r:1 w:0)
+	// Storage: Paras ParaLifecycles (r:2 w:0)
+	// Storage: Hrmp HrmpOpenChannelRequests (r:2 w:2)
+	// Storage: Hrmp HrmpChannels (r:2 w:0)
+	// Storage: Hrmp HrmpEgressChannelsIndex (r:2 w:0)
+	// Storage: Hrmp HrmpOpenChannelRequestCount (r:2 w:2)
+	// Storage: Hrmp HrmpOpenChannelRequestsList (r:1 w:1)
+	// Storage: Dmp DownwardMessageQueueStates (r:2 w:4)
+	// Storage: Dmp DownwardMessageQueuePages (r:2 w:4)
+	// Storage: Dmp DownwardMessageQueueHeads (r:2 w:4)
+	// Storage: Hrmp HrmpIngressChannelsIndex (r:2 w:0)
+	// Storage: Hrmp HrmpAcceptedChannelRequestCount (r:2 w:2)
+	// Storage: System Account (r:2 w:2)
+	fn hrmp_establish_bidirectional_channel() -> Weight {
+		Weight::from_ref_time(118_031_000)
+			.saturating_add(T::DbWeight::get().reads(27))
+			.saturating_add(T::DbWeight::get().writes(21))
+	}
+	// NOTE: placeholder, not produced by the benchmark CLI. It charges a single write
+	// plus a small constant, until the benchmark is run.
+	// Storage: Hrmp HrmpChannelPreApprovals (r:0 w:1)
+	fn hrmp_set_channel_pre_approval() -> Weight {
+		Weight::from_ref_time(8_182_000)
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// NOTE: placeholder, not produced by the benchmark CLI. It charges a single write
+	// plus a small constant, until the benchmark is run.
+	// Storage: Hrmp HrmpAutoAcceptPolicy (r:0 w:1)
+	fn force_set_auto_accept_policy() -> Weight {
+		Weight::from_ref_time(6_363_000)
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
 }
@@ -108,6 +108,14 @@ impl<T: frame_system::Config> runtime_parachains::paras::WeightInfo for WeightIn
 			.saturating_add(T::DbWeight::get().reads(2))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
+	// Storage: Paras UpcomingExtraCores (r:0 w:1)
+	// NOTE: placeholder, not produced by the benchmark CLI. It charges the weight of
+	// `force_queue_action`, which does strictly more work, until the benchmark is run.
+	fn force_set_extra_cores() -> Weight {
+		Weight::from_ref_time(26_054_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
 	// Storage: Paras PvfActiveVoteMap (r:1 w:0)
 	// Storage: Paras CodeByHash (r:1 w:1)
 	/// The range of component `c` is `[1, 3145728]`.
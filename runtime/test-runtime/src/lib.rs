@@ -27,7 +27,8 @@ use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 use polkadot_runtime_parachains::{
 	configuration as parachains_configuration, disputes as parachains_disputes,
 	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
-	initializer as parachains_initializer, origin as parachains_origin, paras as parachains_paras,
+	initializer as parachains_initializer, message_queue as parachains_message_queue,
+	origin as parachains_origin, paras as parachains_paras,
 	paras_inherent as parachains_paras_inherent, runtime_api_impl::v2 as runtime_impl,
 	scheduler as parachains_scheduler, session_info as parachains_session_info,
 	shared as parachains_shared, ump as parachains_ump,
@@ -514,29 +515,49 @@ impl parachains_session_info::Config for Runtime {
 
 parameter_types! {
 	pub const ParasUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	pub const MaxExtraCores: u32 = 4;
 }
 
 impl parachains_paras::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = parachains_paras::TestWeightInfo;
 	type UnsignedPriority = ParasUnsignedPriority;
+	type MaxExtraCores = MaxExtraCores;
 	type NextSessionRotation = Babe;
 }
 
 impl parachains_dmp::Config for Runtime {}
 
-parameter_types! {
-	pub const FirstMessageFactorPercent: u64 = 100;
-}
-
 impl parachains_ump::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type UmpSink = ();
-	type FirstMessageFactorPercent = FirstMessageFactorPercent;
+	type MessageQueue = MessageQueue;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
 	type WeightInfo = parachains_ump::TestWeightInfo;
 }
 
+parameter_types! {
+	/// The size of the pages of the upward message queues. Messages larger than this get a page of
+	/// their own.
+	pub const MessageQueueHeapSize: u32 = 64 * 1024;
+	/// The number of upward messages processed per block, until their weights account for
+	/// the proof size of reading them.
+	pub const MessageQueueMaxMessagesPerBlock: u32 = 10;
+}
+
+impl parachains_message_queue::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MessageProcessor = Ump;
+	type QueueChangeHandler = Ump;
+	type QueuePausedQuery = parachains_ump::PauseOffboardingParas<Runtime>;
+	type HeapSize = MessageQueueHeapSize;
+	type ServiceWeight = parachains_ump::ServiceTotalWeight<Runtime>;
+	type MaxMessageWeight = parachains_ump::MaxIndividualWeight<Runtime>;
+	type MaxMessagesPerBlock = MessageQueueMaxMessagesPerBlock;
+	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = parachains_message_queue::TestWeightInfo;
+}
+
 impl parachains_hrmp::Config for Runtime {
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeEvent = RuntimeEvent;
@@ -544,7 +565,13 @@ impl parachains_hrmp::Config for Runtime {
 	type WeightInfo = parachains_hrmp::TestWeightInfo;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = (
+		parachains_scheduler::ParachainAssignmentProvider<Runtime>,
+		parachains_scheduler::ParathreadAssignmentProvider<Runtime>,
+	);
+	type OnParathreadClaimEnded = ();
+}
 
 impl paras_sudo_wrapper::Config for Runtime {}
 
@@ -686,6 +713,7 @@ construct_runtime! {
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage},
 		Hrmp: parachains_hrmp::{Pallet, Call, Storage, Event<T>},
 		Ump: parachains_ump::{Pallet, Call, Storage, Event},
+		MessageQueue: parachains_message_queue::{Pallet, Call, Storage, Event<T>},
 		Dmp: parachains_dmp::{Pallet, Call, Storage},
 		Xcm: pallet_xcm::{Pallet, Call, Event<T>, Origin},
 		ParasDisputes: parachains_disputes::{Pallet, Storage, Event<T>},
@@ -48,10 +48,11 @@ use runtime_common::{
 	BlockHashCount, BlockLength, CurrencyToVote, SlowAdjustingFeeUpdate, U256ToBalance,
 };
 use runtime_parachains::{
-	configuration as parachains_configuration, disputes as parachains_disputes,
-	disputes::slashing as parachains_slashing, dmp as parachains_dmp, hrmp as parachains_hrmp,
-	inclusion as parachains_inclusion, initializer as parachains_initializer,
-	origin as parachains_origin, paras as parachains_paras,
+	bulk as parachains_bulk, configuration as parachains_configuration,
+	disputes as parachains_disputes, disputes::slashing as parachains_slashing,
+	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
+	initializer as parachains_initializer, message_queue as parachains_message_queue,
+	on_demand as parachains_on_demand, origin as parachains_origin, paras as parachains_paras,
 	paras_inherent as parachains_paras_inherent, reward_points as parachains_reward_points,
 	runtime_api_impl::v2 as parachains_runtime_api_impl, scheduler as parachains_scheduler,
 	session_info as parachains_session_info, shared as parachains_shared, ump as parachains_ump,
@@ -833,7 +834,8 @@ impl InstanceFilter<RuntimeCall> for ProxyType {
 				matches!(
 					c,
 					RuntimeCall::Staking(..) |
-						RuntimeCall::Session(..) | RuntimeCall::Utility(..) |
+						RuntimeCall::Session(..) |
+						RuntimeCall::Utility(..) |
 						RuntimeCall::FastUnstake(..)
 				)
 			},
@@ -907,28 +909,48 @@ impl parachains_inclusion::Config for Runtime {
 
 parameter_types! {
 	pub const ParasUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+	pub const MaxExtraCores: u32 = 4;
 }
 
 impl parachains_paras::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = weights::runtime_parachains_paras::WeightInfo<Runtime>;
 	type UnsignedPriority = ParasUnsignedPriority;
+	type MaxExtraCores = MaxExtraCores;
 	type NextSessionRotation = Babe;
 }
 
-parameter_types! {
-	pub const FirstMessageFactorPercent: u64 = 100;
-}
-
 impl parachains_ump::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type UmpSink =
 		crate::parachains_ump::XcmSink<xcm_executor::XcmExecutor<xcm_config::XcmConfig>, Runtime>;
-	type FirstMessageFactorPercent = FirstMessageFactorPercent;
+	type MessageQueue = MessageQueue;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
 	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
+parameter_types! {
+	/// The size of the pages of the upward message queues. Messages larger than this get a page of
+	/// their own.
+	pub const MessageQueueHeapSize: u32 = 64 * 1024;
+	/// The number of upward messages processed per block, until their weights account for
+	/// the proof size of reading them.
+	pub const MessageQueueMaxMessagesPerBlock: u32 = 10;
+}
+
+impl parachains_message_queue::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MessageProcessor = Ump;
+	type QueueChangeHandler = Ump;
+	type QueuePausedQuery = parachains_ump::PauseOffboardingParas<Runtime>;
+	type HeapSize = MessageQueueHeapSize;
+	type ServiceWeight = parachains_ump::ServiceTotalWeight<Runtime>;
+	type MaxMessageWeight = parachains_ump::MaxIndividualWeight<Runtime>;
+	type MaxMessagesPerBlock = MessageQueueMaxMessagesPerBlock;
+	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = weights::runtime_parachains_message_queue::WeightInfo<Self>;
+}
+
 impl parachains_dmp::Config for Runtime {}
 
 impl parachains_hrmp::Config for Runtime {
@@ -938,11 +960,57 @@ impl parachains_hrmp::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_hrmp::WeightInfo<Self>;
 }
 
+parameter_types! {
+	pub const OnDemandBaseFee: Balance = 10 * CENTS;
+	pub const OnDemandFeeVariability: Perbill = Perbill::from_percent(3);
+	pub const OnDemandTargetQueueUtilization: Perbill = Perbill::from_percent(25);
+	pub const OnDemandTrafficDefaultValue: FixedU128 = FixedU128::from_u32(1);
+}
+
+impl parachains_on_demand::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	// Payments for on-demand orders are burned.
+	type OnOrderPayment = ();
+	type BaseFee = OnDemandBaseFee;
+	type FeeVariability = OnDemandFeeVariability;
+	type TargetQueueUtilization = OnDemandTargetQueueUtilization;
+	type TrafficDefaultValue = OnDemandTrafficDefaultValue;
+	type WeightInfo = weights::runtime_parachains_on_demand::WeightInfo<Self>;
+}
+
+parameter_types! {
+	/// The maximum number of candidates a validator may approve with a single signature.
+	///
+	/// Coalescing is disabled with a value of 1. It must only be raised once the majority of
+	/// validators runs a node version that understands coalesced approval votes.
+	pub const MaxApprovalCoalesceCount: u32 = 1;
+}
+
 impl parachains_paras_inherent::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_paras_inherent::WeightInfo<Runtime>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = (
+		parachains_scheduler::ParachainAssignmentProvider<Runtime>,
+		parachains_scheduler::ParathreadAssignmentProvider<Runtime>,
+		ParachainsBulk,
+	);
+	type OnParathreadClaimEnded = OnDemand;
+}
+
+parameter_types! {
+	pub const BulkMaxCores: u32 = 20;
+	pub const BulkMaxParasPerCore: u32 = 10;
+}
+
+impl parachains_bulk::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MaxCores = BulkMaxCores;
+	type MaxParasPerCore = BulkMaxParasPerCore;
+	type WeightInfo = weights::runtime_parachains_bulk::WeightInfo<Self>;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -1191,6 +1259,9 @@ construct_runtime! {
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage} = 52,
 		ParasDisputes: parachains_disputes::{Pallet, Call, Storage, Event<T>} = 53,
 		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, ValidateUnsigned} = 54,
+		OnDemand: parachains_on_demand::{Pallet, Call, Storage, Event<T>} = 55,
+		ParachainsBulk: parachains_bulk::{Pallet, Call, Storage, Event<T>} = 56,
+		MessageQueue: parachains_message_queue::{Pallet, Call, Storage, Event<T>} = 57,
 
 		// Parachain Onboarding Pallets. Start indices at 60 to leave room.
 		Registrar: paras_registrar::{Pallet, Call, Storage, Event<T>, Config} = 60,
@@ -1250,6 +1321,9 @@ pub type Migrations = (
 	// "Use 2D weights in XCM v3" <https://github.com/paritytech/polkadot/pull/6134>
 	pallet_xcm::migration::v1::MigrateToV1<Runtime>,
 	parachains_ump::migration::v1::MigrateToV1<Runtime>,
+	parachains_ump::migration::v2::MigrateToV2<Runtime>,
+	parachains_dmp::migration::v1::MigrateToV1<Runtime>,
+	parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -1285,7 +1359,10 @@ mod benches {
 		[runtime_parachains::disputes, ParasDisputes]
 		[runtime_parachains::disputes::slashing, ParasSlashing]
 		[runtime_parachains::hrmp, Hrmp]
+		[runtime_parachains::bulk, ParachainsBulk]
+		[runtime_parachains::on_demand, OnDemand]
 		[runtime_parachains::initializer, Initializer]
+		[runtime_parachains::message_queue, MessageQueue]
 		[runtime_parachains::paras, Paras]
 		[runtime_parachains::paras_inherent, ParaInherent]
 		[runtime_parachains::ump, Ump]
@@ -1378,7 +1455,7 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
-	#[api_version(3)]
+	#[api_version(10)]
 	impl primitives::runtime_api::ParachainHost<Block, Hash, BlockNumber> for Runtime {
 		fn validators() -> Vec<ValidatorId> {
 			parachains_runtime_api_impl::validators::<Runtime>()
@@ -1480,6 +1557,37 @@ sp_api::impl_runtime_apis! {
 		fn disputes() -> Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)> {
 			runtime_parachains::runtime_api_impl::vstaging::get_session_disputes::<Runtime>()
 		}
+
+		fn approval_voting_params() -> primitives::vstaging::ApprovalVotingParams {
+			runtime_parachains::runtime_api_impl::vstaging::approval_voting_params::<MaxApprovalCoalesceCount>()
+		}
+
+		fn submit_backing_misbehavior_report(
+			report: primitives::vstaging::BackingMisbehaviorReport,
+			signature: ValidatorSignature,
+		) {
+			runtime_parachains::runtime_api_impl::vstaging::submit_backing_misbehavior_report::<Runtime>(report, signature)
+		}
+
+		fn inherent_weight_info() -> primitives::vstaging::InherentWeightInfo {
+			runtime_parachains::runtime_api_impl::vstaging::inherent_weight_info::<Runtime>()
+		}
+
+		fn on_demand_spot_price() -> Balance {
+			runtime_parachains::runtime_api_impl::vstaging::on_demand_spot_price::<Runtime>()
+		}
+
+		fn ump_queue_footprint(para_id: ParaId) -> primitives::vstaging::QueueFootprint {
+			runtime_parachains::runtime_api_impl::vstaging::ump_queue_footprint::<Runtime>(para_id)
+		}
+
+		fn dmq_footprint(para_id: ParaId) -> primitives::vstaging::QueueFootprint {
+			runtime_parachains::runtime_api_impl::vstaging::dmq_footprint::<Runtime>(para_id)
+		}
+
+		fn hrmp_channels(para_id: ParaId) -> Vec<primitives::vstaging::HrmpChannelSummary> {
+			runtime_parachains::runtime_api_impl::vstaging::hrmp_channels::<Runtime>(para_id)
+		}
 	}
 
 	impl beefy_primitives::BeefyApi<Block> for Runtime {
@@ -1829,8 +1937,10 @@ sp_api::impl_runtime_apis! {
 				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da95ecffd7b6c0f78751baa9d281e0bfa3a6d6f646c70792f74727372790000000000000000000000000000000000000000").to_vec().into(),
 				// Dmp DownwardMessageQueueHeads
 				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0c4d7fefc408aac59dbfe80a72ac8e3ce5").to_vec().into(),
-				// Dmp DownwardMessageQueues
-				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0ca95dac46c07a40d91506e7637ec4ba57").to_vec().into(),
+				// Dmp DownwardMessageQueueStates
+				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0c2ed17122b1cd56638193d54a98b6cbda").to_vec().into(),
+				// Dmp DownwardMessageQueuePages
+				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0c34d894ec6fadffc214525dde845b9027").to_vec().into(),
 				// Configuration ActiveConfig
 				hex_literal::hex!("06de3d8a54d27e44a9d5ce189618f22db4b49d95320d9021994c850f25b8e385").to_vec().into(),
 				// The transactional storage limit.
@@ -0,0 +1,55 @@
+// Copyright 2017-2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+//! Placeholder weights for `runtime_parachains::bulk`
+//!
+//! NOTE: these weights were NOT produced by the benchmark CLI. They are hand-written estimates
+//! for the storage accesses listed below, until the `assign_core` benchmark is run with the
+//! command below.
+
+// Command to generate the weights:
+// ./target/production/polkadot
+// benchmark
+// pallet
+// --chain=westend-dev
+// --steps=50
+// --repeat=20
+// --pallet=runtime_parachains::bulk
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --header=./file_header.txt
+// --output=./runtime/westend/src/weights/runtime_parachains_bulk.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::bulk`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::bulk::WeightInfo for WeightInfo<T> {
+	// Storage: ParachainsBulk PendingCores (r:1 w:1)
+	// Storage: ParachainsBulk Cores (r:1 w:0)
+	/// The range of component `s` is `[0, 10]`.
+	fn assign_core(s: u32, ) -> Weight {
+		Weight::from_ref_time(20_874_000)
+			.saturating_add(Weight::from_ref_time(398_000).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+}
@@ -65,4 +65,15 @@ impl<T: frame_system::Config> runtime_parachains::disputes::slashing::WeightInfo
 			.saturating_add(T::DbWeight::get().reads(12))
 			.saturating_add(T::DbWeight::get().writes(4))
 	}
+	// NOTE: placeholder, not produced by the benchmark CLI. It charges `report_dispute_lost` for
+	// the largest validator set, which reports the offence the same way, plus three signature
+	// checks and the bookkeeping of the report, until the benchmark is run.
+	// Storage: ParaSessionInfo Sessions (r:1 w:0)
+	// Storage: ParasSlashing ReportedBackingMisbehavior (r:1 w:1)
+	fn report_backing_misbehavior() -> Weight {
+		Self::report_dispute_lost(300)
+			.saturating_add(Weight::from_ref_time(150_000_000))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
 }
@@ -50,9 +50,6 @@ pub trait Context {
 	/// get the digest of a candidate.
 	fn candidate_digest(candidate: &Self::Candidate) -> Self::Digest;
 
-	/// get the group of a candidate.
-	fn candidate_group(candidate: &Self::Candidate) -> Self::GroupId;
-
 	/// Whether a authority is a member of a group.
 	/// Members are meant to submit candidates and vote on validity.
 	fn is_member_of(&self, authority: &Self::AuthorityId, group: &Self::GroupId) -> bool;
@@ -89,7 +86,7 @@ pub struct SignedStatement<Candidate, Digest, AuthorityId, Signature> {
 ///
 /// Since there are three possible ways to vote, a double vote is possible in
 /// three possible combinations (unordered)
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum ValidityDoubleVote<Candidate, Digest, Signature> {
 	/// Implicit vote by issuing and explicitly voting validity.
 	IssuedAndValidity((Candidate, Signature), (Digest, Signature)),
@@ -115,7 +112,7 @@ impl<Candidate, Digest, Signature> ValidityDoubleVote<Candidate, Digest, Signatu
 }
 
 /// Misbehavior: multiple signatures on same statement.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum DoubleSign<Candidate, Digest, Signature> {
 	/// On candidate.
 	Seconded(Candidate, Signature, Signature),
@@ -135,7 +132,7 @@ impl<Candidate, Digest, Signature> DoubleSign<Candidate, Digest, Signature> {
 }
 
 /// Misbehavior: declaring multiple candidates.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub struct MultipleCandidates<Candidate, Signature> {
 	/// The first candidate seen.
 	pub first: (Candidate, Signature),
@@ -144,7 +141,7 @@ pub struct MultipleCandidates<Candidate, Signature> {
 }
 
 /// Misbehavior: submitted statement for wrong group.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub struct UnauthorizedStatement<Candidate, Digest, AuthorityId, Signature> {
 	/// A signed statement which was submitted without proper authority.
 	pub statement: SignedStatement<Candidate, Digest, AuthorityId, Signature>,
@@ -152,7 +149,7 @@ pub struct UnauthorizedStatement<Candidate, Digest, AuthorityId, Signature> {
 
 /// Different kinds of misbehavior. All of these kinds of malicious misbehavior
 /// are easily provable and extremely disincentivized.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum Misbehavior<Candidate, Digest, AuthorityId, Signature> {
 	/// Voted invalid and valid on validity.
 	ValidityDoubleVote(ValidityDoubleVote<Candidate, Digest, Signature>),
@@ -236,7 +233,8 @@ impl<Ctx: Context> CandidateData<Ctx> {
 	pub fn attested(
 		&self,
 		validity_threshold: usize,
-	) -> Option<AttestedCandidate<Ctx::GroupId, Ctx::Candidate, Ctx::AuthorityId, Ctx::Signature>> {
+	) -> Option<AttestedCandidate<Ctx::GroupId, Ctx::Candidate, Ctx::AuthorityId, Ctx::Signature>>
+	{
 		let valid_votes = self.validity_votes.len();
 		if valid_votes < validity_threshold {
 			return None
@@ -310,7 +308,8 @@ impl<Ctx: Context> Table<Ctx> {
 		&self,
 		digest: &Ctx::Digest,
 		context: &Ctx,
-	) -> Option<AttestedCandidate<Ctx::GroupId, Ctx::Candidate, Ctx::AuthorityId, Ctx::Signature>> {
+	) -> Option<AttestedCandidate<Ctx::GroupId, Ctx::Candidate, Ctx::AuthorityId, Ctx::Signature>>
+	{
 		self.candidate_votes.get(digest).and_then(|data| {
 			let v_threshold = context.requisite_votes(&data.group_id);
 			data.attested(v_threshold)
@@ -323,17 +322,21 @@ impl<Ctx: Context> Table<Ctx> {
 	/// Validity and invalidity statements are only valid if the corresponding
 	/// candidate has already been imported.
 	///
+	/// A seconded candidate is placed into the given group. The group is ignored for
+	/// validity statements, which are checked against the group of the candidate they refer to.
+	///
 	/// If this returns `None`, the statement was either duplicate or invalid.
 	pub fn import_statement(
 		&mut self,
 		context: &Ctx,
+		group_id: Ctx::GroupId,
 		statement: SignedStatement<Ctx::Candidate, Ctx::Digest, Ctx::AuthorityId, Ctx::Signature>,
 	) -> Option<Summary<Ctx::Digest, Ctx::GroupId>> {
 		let SignedStatement { statement, signature, sender: signer } = statement;
 
 		let res = match statement {
 			Statement::Seconded(candidate) =>
-				self.import_candidate(context, signer.clone(), candidate, signature),
+				self.import_candidate(context, signer.clone(), candidate, signature, group_id),
 			Statement::Valid(digest) =>
 				self.validity_vote(context, signer.clone(), digest, ValidityVote::Valid(signature)),
 		};
@@ -354,6 +357,11 @@ impl<Ctx: Context> Table<Ctx> {
 		self.candidate_votes.get(digest).map(|d| &d.candidate)
 	}
 
+	/// Get all candidates in the table.
+	pub fn candidates(&self) -> impl Iterator<Item = &Ctx::Candidate> {
+		self.candidate_votes.values().map(|d| &d.candidate)
+	}
+
 	/// Access all witnessed misbehavior.
 	pub fn get_misbehavior(&self) -> &HashMap<Ctx::AuthorityId, Vec<MisbehaviorFor<Ctx>>> {
 		&self.detected_misbehavior
@@ -372,8 +380,8 @@ impl<Ctx: Context> Table<Ctx> {
 		authority: Ctx::AuthorityId,
 		candidate: Ctx::Candidate,
 		signature: Ctx::Signature,
+		group: Ctx::GroupId,
 	) -> ImportResult<Ctx> {
-		let group = Ctx::candidate_group(&candidate);
 		if !context.is_member_of(&authority, &group) {
 			return Err(Misbehavior::UnauthorizedStatement(UnauthorizedStatement {
 				statement: SignedStatement {
@@ -581,7 +589,7 @@ mod tests {
 	#[derive(Debug, Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
 	struct GroupId(usize);
 
-	// group, body
+	// para, body
 	#[derive(Debug, Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
 	struct Candidate(usize, usize);
 
@@ -608,10 +616,6 @@ mod tests {
 			Digest(candidate.1)
 		}
 
-		fn candidate_group(candidate: &Candidate) -> GroupId {
-			GroupId(candidate.0)
-		}
-
 		fn is_member_of(&self, authority: &AuthorityId, group: &GroupId) -> bool {
 			self.authorities.get(authority).map(|v| v == group).unwrap_or(false)
 		}
@@ -652,10 +656,10 @@ mod tests {
 			sender: AuthorityId(1),
 		};
 
-		table.import_statement(&context, statement_a);
+		table.import_statement(&context, GroupId(2), statement_a);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 
-		table.import_statement(&context, statement_b);
+		table.import_statement(&context, GroupId(2), statement_b);
 		assert_eq!(
 			table.detected_misbehavior[&AuthorityId(1)][0],
 			Misbehavior::MultipleCandidates(MultipleCandidates {
@@ -682,7 +686,7 @@ mod tests {
 			sender: AuthorityId(1),
 		};
 
-		table.import_statement(&context, statement);
+		table.import_statement(&context, GroupId(2), statement);
 
 		assert_eq!(
 			table.detected_misbehavior[&AuthorityId(1)][0],
@@ -716,7 +720,7 @@ mod tests {
 		};
 		let candidate_a_digest = Digest(100);
 
-		table.import_statement(&context, candidate_a);
+		table.import_statement(&context, GroupId(2), candidate_a);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(2)));
 
@@ -726,7 +730,7 @@ mod tests {
 			signature: Signature(2),
 			sender: AuthorityId(2),
 		};
-		table.import_statement(&context, bad_validity_vote);
+		table.import_statement(&context, GroupId(2), bad_validity_vote);
 
 		assert_eq!(
 			table.detected_misbehavior[&AuthorityId(2)][0],
@@ -758,7 +762,7 @@ mod tests {
 			sender: AuthorityId(1),
 		};
 
-		table.import_statement(&context, statement);
+		table.import_statement(&context, GroupId(2), statement);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 
 		let invalid_statement = SignedStatement {
@@ -767,7 +771,7 @@ mod tests {
 			sender: AuthorityId(1),
 		};
 
-		table.import_statement(&context, invalid_statement);
+		table.import_statement(&context, GroupId(2), invalid_statement);
 		assert!(table.detected_misbehavior.contains_key(&AuthorityId(1)));
 	}
 
@@ -789,7 +793,7 @@ mod tests {
 		};
 		let candidate_digest = Digest(100);
 
-		table.import_statement(&context, statement);
+		table.import_statement(&context, GroupId(2), statement);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 
 		let extra_vote = SignedStatement {
@@ -798,7 +802,7 @@ mod tests {
 			sender: AuthorityId(1),
 		};
 
-		table.import_statement(&context, extra_vote);
+		table.import_statement(&context, GroupId(2), extra_vote);
 		assert_eq!(
 			table.detected_misbehavior[&AuthorityId(1)][0],
 			Misbehavior::ValidityDoubleVote(ValidityDoubleVote::IssuedAndValidity(
@@ -857,7 +861,7 @@ mod tests {
 		};
 		let candidate_digest = Digest(100);
 
-		table.import_statement(&context, statement);
+		table.import_statement(&context, GroupId(2), statement);
 
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 		assert!(table.attested_candidate(&candidate_digest, &context).is_none());
@@ -868,7 +872,7 @@ mod tests {
 			sender: AuthorityId(2),
 		};
 
-		table.import_statement(&context, vote);
+		table.import_statement(&context, GroupId(2), vote);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(2)));
 		assert!(table.attested_candidate(&candidate_digest, &context).is_some());
 	}
@@ -891,7 +895,7 @@ mod tests {
 		};
 
 		let summary = table
-			.import_statement(&context, statement)
+			.import_statement(&context, GroupId(2), statement)
 			.expect("candidate import to give summary");
 
 		assert_eq!(summary.candidate, Digest(100));
@@ -918,7 +922,7 @@ mod tests {
 		};
 		let candidate_digest = Digest(100);
 
-		table.import_statement(&context, statement);
+		table.import_statement(&context, GroupId(2), statement);
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(1)));
 
 		let vote = SignedStatement {
@@ -927,8 +931,9 @@ mod tests {
 			sender: AuthorityId(2),
 		};
 
-		let summary =
-			table.import_statement(&context, vote).expect("candidate vote to give summary");
+		let summary = table
+			.import_statement(&context, GroupId(2), vote)
+			.expect("candidate vote to give summary");
 
 		assert!(!table.detected_misbehavior.contains_key(&AuthorityId(2)));
 
@@ -936,4 +941,48 @@ mod tests {
 		assert_eq!(summary.group_id, GroupId(2));
 		assert_eq!(summary.validity_votes, 2);
 	}
+
+	#[test]
+	fn candidates_of_same_para_can_be_seconded_in_different_groups() {
+		let context = TestContext {
+			authorities: {
+				let mut map = HashMap::new();
+				map.insert(AuthorityId(1), GroupId(2));
+				map.insert(AuthorityId(2), GroupId(3));
+				map
+			},
+		};
+
+		let mut table = create();
+		let statement_a = SignedStatement {
+			statement: Statement::Seconded(Candidate(2, 100)),
+			signature: Signature(1),
+			sender: AuthorityId(1),
+		};
+		let statement_b = SignedStatement {
+			statement: Statement::Seconded(Candidate(2, 200)),
+			signature: Signature(2),
+			sender: AuthorityId(2),
+		};
+
+		let summary_a = table
+			.import_statement(&context, GroupId(2), statement_a)
+			.expect("candidate import to give summary");
+		let summary_b = table
+			.import_statement(&context, GroupId(3), statement_b)
+			.expect("candidate import to give summary");
+
+		assert!(table.detected_misbehavior.is_empty());
+		assert_eq!(summary_a.group_id, GroupId(2));
+		assert_eq!(summary_b.group_id, GroupId(3));
+
+		// authority 2 isn't a member of the group candidate A is seconded in.
+		let bad_validity_vote = SignedStatement {
+			statement: Statement::Valid(Digest(100)),
+			signature: Signature(2),
+			sender: AuthorityId(2),
+		};
+		table.import_statement(&context, GroupId(3), bad_validity_vote);
+		assert!(table.detected_misbehavior.contains_key(&AuthorityId(2)));
+	}
 }
@@ -22,8 +22,8 @@ pub use generic::{Context, Table};
 pub mod v2 {
 	use crate::generic;
 	use primitives::{
-		CandidateHash, CommittedCandidateReceipt, CompactStatement as PrimitiveStatement, Id,
-		ValidatorIndex, ValidatorSignature,
+		vstaging::BackingMisbehavior, CandidateHash, CommittedCandidateReceipt,
+		CompactStatement as PrimitiveStatement, CoreIndex, ValidatorIndex, ValidatorSignature,
 	};
 
 	/// Statements about candidates on the network.
@@ -46,7 +46,10 @@ pub mod v2 {
 	>;
 
 	/// A summary of import of a statement.
-	pub type Summary = generic::Summary<CandidateHash, Id>;
+	///
+	/// Candidates are grouped by the core their backing group is assigned to, as a para
+	/// may be assigned multiple cores.
+	pub type Summary = generic::Summary<CandidateHash, CoreIndex>;
 
 	impl<'a> From<&'a Statement> for PrimitiveStatement {
 		fn from(s: &'a Statement) -> PrimitiveStatement {
@@ -56,4 +59,24 @@ pub mod v2 {
 			}
 		}
 	}
+
+	/// The proof of a misbehavior which can be checked on-chain, if the misbehavior is slashable.
+	///
+	/// Only seconding multiple candidates is slashable. Double votes on validity aren't
+	/// necessarily malicious, signing the same statement twice is harmless, and unauthorized
+	/// statements would require the group assignments of the relay parent to be checked.
+	pub fn slashable_misbehavior(misbehavior: &Misbehavior) -> Option<BackingMisbehavior> {
+		match misbehavior {
+			generic::Misbehavior::MultipleCandidates(generic::MultipleCandidates {
+				first,
+				second,
+			}) => Some(BackingMisbehavior::MultipleCandidates {
+				first: (first.0.hash(), first.1.clone()),
+				second: (second.0.hash(), second.1.clone()),
+			}),
+			generic::Misbehavior::ValidityDoubleVote(_) |
+			generic::Misbehavior::UnauthorizedStatement(_) |
+			generic::Misbehavior::DoubleSign(_) => None,
+		}
+	}
 }
@@ -25,7 +25,7 @@ use sp_core::H256;
 use sp_runtime::{testing::Header, traits::IdentityLookup, AccountId32};
 
 use polkadot_parachain::primitives::Id as ParaId;
-use polkadot_runtime_parachains::{configuration, origin, shared, ump};
+use polkadot_runtime_parachains::{configuration, message_queue, origin, shared, ump};
 use xcm::latest::prelude::*;
 use xcm_builder::{
 	Account32Hash, AccountId32Aliases, AllowUnpaidExecutionFrom, AsPrefixedGeneralIndex,
@@ -219,18 +219,30 @@ impl pallet_xcm::Config for Runtime {
 	type ReachableDest = ReachableDest;
 }
 
-parameter_types! {
-	pub const FirstMessageFactorPercent: u64 = 100;
-}
-
 impl ump::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type UmpSink = ump::XcmSink<XcmExecutor<XcmConfig>, Runtime>;
-	type FirstMessageFactorPercent = FirstMessageFactorPercent;
+	type MessageQueue = MessageQueue;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
 	type WeightInfo = ump::TestWeightInfo;
 }
 
+parameter_types! {
+	pub const MessageQueueHeapSize: u32 = 64 * 1024;
+}
+
+impl message_queue::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MessageProcessor = ParasUmp;
+	type QueueChangeHandler = ParasUmp;
+	type QueuePausedQuery = ();
+	type HeapSize = MessageQueueHeapSize;
+	type ServiceWeight = ump::ServiceTotalWeight<Runtime>;
+	type MaxMessageWeight = ump::MaxIndividualWeight<Runtime>;
+	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = message_queue::TestWeightInfo;
+}
+
 impl origin::Config for Runtime {}
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
@@ -246,6 +258,7 @@ construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		ParasOrigin: origin::{Pallet, Origin},
 		ParasUmp: ump::{Pallet, Call, Storage, Event},
+		MessageQueue: message_queue::{Pallet, Call, Storage, Event<T>},
 		XcmPallet: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
 		Uniques: pallet_uniques::{Pallet, Call, Storage, Event<T>},
 	}